@@ -0,0 +1,10 @@
+//! Fuzzes ArbOwner, ArbWasm, ArbRetryableTx and ArbSys with ABI-valid calls, see
+//! `arbos_revm::precompiles::fuzzing`. Run with `cargo fuzz run precompiles`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    arbos_revm::precompiles::fuzzing::fuzz_precompiles(data);
+});
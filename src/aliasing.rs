@@ -0,0 +1,24 @@
+//! L1-to-L2 address aliasing.
+//!
+//! Contracts on L1 sending messages to L2 appear under their address offset by
+//! [`L1_TO_L2_ALIAS_OFFSET`], so that an L1 contract can't impersonate the L2 contract deployed
+//! at the same address.
+
+use revm::primitives::{Address, U160, address};
+
+/// Offset added to an L1 address to give its alias on L2.
+pub const L1_TO_L2_ALIAS_OFFSET: Address = address!("0x1111000000000000000000000000000000001111");
+
+/// The L2 alias of the L1 address `l1_address`, wrapping around the address space.
+pub fn apply_l1_to_l2_alias(l1_address: Address) -> Address {
+    let sum = U160::from_be_slice(l1_address.as_slice())
+        .wrapping_add(U160::from_be_slice(L1_TO_L2_ALIAS_OFFSET.as_slice()));
+    Address::from(sum)
+}
+
+/// The L1 address whose alias is `l2_address`, the inverse of [`apply_l1_to_l2_alias`].
+pub fn undo_alias(l2_address: Address) -> Address {
+    let difference = U160::from_be_slice(l2_address.as_slice())
+        .wrapping_sub(U160::from_be_slice(L1_TO_L2_ALIAS_OFFSET.as_slice()));
+    Address::from(difference)
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+
+use arbutil::evm::ARBOS_VERSION_STYLUS_CHARGING_FIXES;
+
+use crate::constants::ARBOS_VERSION_STYLUS_FIXES;
+
+/// An ArbOS version. Behaviour that changed across upgrades is gated through
+/// [`ArbosVersion::supports`] rather than by comparing raw numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArbosVersion(u16);
+
+/// Version-gated behaviour, named after what changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArbosFeature {
+    /// Retryable precompile methods revert with `NoTicketWithID` for unknown or expired tickets.
+    RetryableNotFoundError,
+    /// StartBlock drains the L2 gas backlog by the time passed instead of the L2 block number.
+    StartBlockTimePassed,
+    /// Batch poster reimbursements are capped by the amortized cost cap.
+    AmortizedCostCap,
+    /// `ArbGasInfo` prices the per-transaction L1 cost in gas instead of reporting a fixed size.
+    GasInfoL2TxPricing,
+    /// `ArbSys.sendTxToL1` returns the leaf number of the send instead of its hash.
+    SendTxLeafNumber,
+    /// `ArbSys` counts a caller as top level when the transaction's origin called it, not only
+    /// when it runs at depth two.
+    TopLevelCallerByOrigin,
+    /// Block hashes filled in for skipped L1 blocks commit to the block number.
+    L1BlockHashNumbering,
+    /// StartBlock records the reported L1 block number rather than the one after it.
+    StartBlockL1BlockNumber,
+    /// L1 pricing surplus is computed from the tracked available fees, not the pricer balance.
+    L1FeesAvailable,
+    /// `OwnerActs` is no longer emitted for owner calls made in a static context.
+    StaticOwnerActsSuppressed,
+    /// Precompile methods failing with a non-Solidity error revert instead of consuming all gas.
+    PrecompileErrorsRevert,
+    /// Stylus charges for copying return data into EVM memory.
+    StylusFixes,
+    /// Stylus charging fixes, after which `ArbWasm.minInitGas` no longer reverts.
+    StylusChargingFixes,
+    /// `ArbSys.arbBlockHash` reverts with `InvalidBlockNumber` for out-of-range blocks.
+    InvalidBlockNumberError,
+    /// `ArbOwner.setMaxTxGasLimit` sets the per-transaction limit instead of the per-block one.
+    PerTxGasLimit,
+    /// Contract creations are charged intrinsic gas per word of initcode (EIP-3860).
+    InitCodeWordGas,
+    /// Redeems refund the infra fee share of their unused gas out of the infra fee account.
+    RetryInfraFeeRefund,
+    /// Transactions must pay at least the calldata floor gas (EIP-7623).
+    CalldataFloorGas,
+}
+
+impl ArbosVersion {
+    pub const fn new(version: u16) -> Self {
+        Self(version)
+    }
+
+    pub const fn as_u16(self) -> u16 {
+        self.0
+    }
+
+    pub fn supports(self, feature: ArbosFeature) -> bool {
+        self >= feature.introduced_in()
+    }
+}
+
+impl ArbosFeature {
+    /// First ArbOS version with this behaviour.
+    pub const fn introduced_in(self) -> ArbosVersion {
+        let version = match self {
+            Self::RetryableNotFoundError => 3,
+            Self::StartBlockTimePassed => 3,
+            Self::AmortizedCostCap => 3,
+            Self::GasInfoL2TxPricing => 4,
+            Self::SendTxLeafNumber => 4,
+            Self::TopLevelCallerByOrigin => 6,
+            Self::L1BlockHashNumbering => 8,
+            Self::StartBlockL1BlockNumber => 8,
+            Self::L1FeesAvailable => 10,
+            Self::StaticOwnerActsSuppressed => 11,
+            Self::PrecompileErrorsRevert => 11,
+            Self::StylusFixes => ARBOS_VERSION_STYLUS_FIXES,
+            Self::StylusChargingFixes => ARBOS_VERSION_STYLUS_CHARGING_FIXES as u16,
+            Self::InvalidBlockNumberError => 33,
+            Self::PerTxGasLimit => 50,
+            Self::InitCodeWordGas => 11,
+            Self::RetryInfraFeeRefund => 11,
+            Self::CalldataFloorGas => 40,
+        };
+        ArbosVersion(version)
+    }
+}
+
+impl From<u16> for ArbosVersion {
+    fn from(version: u16) -> Self {
+        Self(version)
+    }
+}
+
+impl From<ArbosVersion> for u16 {
+    fn from(version: ArbosVersion) -> Self {
+        version.0
+    }
+}
+
+impl From<ArbosVersion> for u64 {
+    fn from(version: ArbosVersion) -> Self {
+        version.0.into()
+    }
+}
+
+impl fmt::Display for ArbosVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supports_features_from_their_version_on() {
+        let feature = ArbosFeature::InvalidBlockNumberError;
+        assert!(!ArbosVersion::new(32).supports(feature));
+        assert!(ArbosVersion::new(33).supports(feature));
+        assert!(ArbosVersion::new(50).supports(feature));
+    }
+
+    #[test]
+    fn stylus_features_follow_constants() {
+        assert_eq!(
+            ArbosFeature::StylusFixes.introduced_in().as_u16(),
+            ARBOS_VERSION_STYLUS_FIXES
+        );
+        assert!(
+            ArbosFeature::StylusChargingFixes.introduced_in()
+                > ArbosFeature::StylusFixes.introduced_in()
+        );
+    }
+}
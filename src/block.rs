@@ -0,0 +1,82 @@
+//! Executing whole L2 blocks.
+//!
+//! [`ArbitrumEvm::transact_block`] opens a block with the StartBlock internal transaction, runs
+//! its transactions with the retries they schedule, and reads the block's header info from ArbOS
+//! state once they are done, as Nitro's block processor does.
+//!
+//! [`ArbitrumEvm::transact_block`]: crate::ArbitrumEvm::transact_block
+
+use revm::{
+    context::result::{ExecutionResult, HaltReason},
+    primitives::{B256, U256},
+};
+
+use crate::{
+    ArbitrumContextTr, ArbitrumTransactionError,
+    state::{ArbState, ArbStateGetter, ArbosStateError, PricingReport, types::StorageBackedTr},
+};
+
+/// L1 inputs of the StartBlock internal transaction that opens an L2 block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StartBlockInfo {
+    /// Basefee of the L1 block the L2 block follows.
+    pub l1_base_fee: U256,
+    /// Number of the L1 block the L2 block follows.
+    pub l1_block_number: u64,
+    /// Seconds since the previous L2 block, over which the L2 gas backlog drains.
+    pub time_passed: u64,
+}
+
+/// Outcome of a transaction included in a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbitrumReceipt {
+    pub tx_type: u8,
+    pub result: ExecutionResult<HaltReason>,
+    /// Gas used by the block up to and including this transaction.
+    pub cumulative_gas_used: u64,
+    /// Part of the gas used that paid for posting the transaction to L1.
+    pub gas_used_for_l1: u64,
+}
+
+/// Header fields Nitro derives from ArbOS state once a block's transactions have run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArbitrumHeaderInfo {
+    /// Root of the send Merkle accumulator of L2-to-L1 messages.
+    pub send_root: B256,
+    /// Number of L2-to-L1 messages sent so far.
+    pub send_count: u64,
+    /// L1 block number the next block's StartBlock is checked against.
+    pub l1_block_number: u64,
+    pub arbos_version: u64,
+}
+
+impl ArbitrumHeaderInfo {
+    /// Read the header info from the ArbOS state of `ctx`.
+    pub fn read<CTX: ArbitrumContextTr>(ctx: &mut CTX) -> Result<Self, ArbosStateError> {
+        let mut arb_state = ctx.system_arb_state(true);
+        let mut accumulator = arb_state.send_merkle_accumulator();
+        let send_root = accumulator.root()?;
+        let send_count = accumulator.size()?;
+        Ok(Self {
+            send_root,
+            send_count,
+            l1_block_number: arb_state.blockhashes().l1_block_number().get()?,
+            arbos_version: arb_state.arbos_version().get()?,
+        })
+    }
+}
+
+/// Outcome of executing a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockExecutionResult {
+    /// Receipts of the included transactions in execution order: StartBlock first, each
+    /// scheduled retry right after the transaction that scheduled it.
+    pub receipts: Vec<ArbitrumReceipt>,
+    /// Transactions left out of the block for failing validation, by their index in the input.
+    pub rejected: Vec<(usize, ArbitrumTransactionError)>,
+    /// Gas used by the block.
+    pub gas_used: u64,
+    /// L1 and L2 pricing state before and after the block.
+    pub pricing: PricingReport,
+    pub header_info: ArbitrumHeaderInfo,
+}
@@ -1,3 +1,5 @@
+use std::mem;
+
 use revm::primitives::{Address, B256, Bytes, U256};
 
 macro_rules! take_bytes {
@@ -47,10 +49,9 @@ pub(crate) fn take_u8(data: &mut Vec<u8>) -> u8 {
     take_int!(data, u8, 1)
 }
 
+/// Takes what is left of `data`, handing over its allocation rather than copying it.
 pub(crate) fn take_rest(data: &mut Vec<u8>) -> Bytes {
-    let bytes = Bytes::from(data.clone());
-    data.clear();
-    bytes
+    Bytes::from(mem::take(data))
 }
 
 #[cfg(test)]
@@ -101,4 +102,13 @@ mod tests {
         let u16 = take_u16(&mut data);
         assert_eq!(u16, 0);
     }
+
+    #[test]
+    fn test_take_rest() {
+        let mut data = vec![1u8, 2, 3, 4, 5];
+        assert_eq!(take_u16(&mut data), 0x0102);
+        let rest = take_rest(&mut data);
+        assert_eq!(rest, Bytes::from(vec![3u8, 4, 5]));
+        assert!(data.is_empty());
+    }
 }
@@ -1,19 +1,115 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
 
 use auto_impl::auto_impl;
 use revm::{
     context::{Cfg, CfgEnv},
-    primitives::hardfork::SpecId,
+    primitives::{Address, FixedBytes, U256, hardfork::SpecId},
 };
 
-use crate::constants::INITIAL_ARBOS_VERSION;
+use crate::{
+    arbos_version::ArbosVersion,
+    constants::{ARBOS_STATE_ADDRESS, INITIAL_ARBOS_VERSION, PRECOMPILE_MIN_ARBOS_VERSIONS},
+    stylus_executor::StylusCompileTarget,
+};
 
 #[auto_impl(&, &mut, Box, Arc)]
 pub trait ArbitrumConfigTr: Cfg {
-    fn arbos_version(&self) -> u16;
+    fn arbos_version(&self) -> ArbosVersion;
     fn debug_mode(&self) -> bool;
     fn disable_auto_cache(&self) -> bool;
     fn disable_auto_activate(&self) -> bool;
+    /// Emit `OwnerActs` for every owner call, including reverted ones, with the selector
+    /// stripped from the logged data. Nitro only logs successful calls with the full calldata.
+    fn legacy_owner_acts(&self) -> bool;
+    /// Execute each transaction under the ArbOS version recorded in state, using
+    /// [`Self::arbos_version`] only while the state is uninitialized.
+    fn arbos_version_from_state(&self) -> bool;
+    /// Reuse the results of pure and view precompile calls repeated within a transaction while
+    /// the state they could read is unchanged.
+    fn cache_precompile_results(&self) -> bool;
+    /// Execute transactions at no less than the minimum L2 basefee held in ArbOS state, raising
+    /// the block's basefee when it is lower, as Nitro never builds a block below the minimum.
+    fn enforce_min_base_fee(&self) -> bool;
+    /// Reject legacy transactions without EIP-155 replay protection, as Nitro's RPC does unless
+    /// unprotected transactions are allowed. Blocks accept them at every ArbOS version.
+    fn reject_unprotected_txs(&self) -> bool;
+    /// Accept `ArbOwner.multicall`, which applies a batch of owner calls atomically under a
+    /// single `OwnerActs` event. Nitro has no such method, so it is unknown unless enabled.
+    fn owner_multicall(&self) -> bool;
+    /// Accept `ArbWasm.moduleHash`, which returns the module hash an active program was
+    /// activated into. Nitro has no such method, so it is unknown unless enabled.
+    fn arb_wasm_module_hash(&self) -> bool;
+    /// Account whose storage holds the ArbOS state.
+    fn arbos_state_address(&self) -> Address;
+    /// Start of the address block the Arbitrum precompiles are deployed in. Each precompile sits
+    /// at its canonical address added to the base, so the zero default keeps Nitro's layout.
+    fn precompile_address_base(&self) -> Address;
+    /// Directory of natively compiled Stylus programs to load instead of compiling, laid out as
+    /// described by [`crate::stylus_executor::wasm_store_path`].
+    fn wasm_store_dir(&self) -> Option<&Path>;
+    /// Most Stylus programs that may be executing at once, each holding a live wasm instance
+    /// while the calls it made run. A program called beyond the limit fails as if the call depth
+    /// limit had been hit, returning its gas to the caller. Nitro sets no limit besides the call
+    /// depth limit, which is kept with `None`.
+    fn max_stylus_call_depth(&self) -> Option<usize>;
+    /// Fail Stylus calls whose calldata or output exceeds the memory the program's page limit
+    /// can address, and charge calldata's EVM memory cost up front as output's is charged on
+    /// return. Nitro bounds and prices neither, which is kept with `false`.
+    fn limit_stylus_buffers(&self) -> bool;
+    /// ArbOS version from which reads of ArbOS state are priced like `SLOAD` under EIP-2929, cold
+    /// the first time a transaction reads a slot and warm after. Nitro charges the flat Istanbul
+    /// `SLOAD` cost at every version, which is kept with `None`.
+    fn arbos_storage_access_costs_from(&self) -> Option<ArbosVersion>;
+    /// Target `ArbitrumEvm::program_artifact` compiles native code for, to precompile programs
+    /// for nodes of another architecture. Programs executed here always run native code compiled
+    /// for the host.
+    fn stylus_compile_target(&self) -> StylusCompileTarget;
+    /// Gas charged for calls to the method with `selector` of the precompile at canonical address
+    /// `address` in place of its own cost, for chains that price precompiles differently.
+    fn precompile_gas_override(&self, address: Address, selector: [u8; 4]) -> Option<u64>;
+
+    /// Address the Arbitrum precompile with canonical address `address` is deployed at. Other
+    /// addresses are returned unchanged.
+    fn precompile_address(&self, address: Address) -> Address {
+        let base = self.precompile_address_base();
+        if base.is_zero() || !is_arbitrum_precompile(address) {
+            return address;
+        }
+        relocate(base, address)
+    }
+
+    /// Canonical address of the precompile deployed at `address`, the inverse of
+    /// [`Self::precompile_address`]. Returns `None` for a canonical Arbitrum precompile address
+    /// vacated by relocating the block.
+    fn canonical_precompile_address(&self, address: Address) -> Option<Address> {
+        let base = self.precompile_address_base();
+        if base.is_zero() {
+            return Some(address);
+        }
+        if let Some((canonical, _)) = PRECOMPILE_MIN_ARBOS_VERSIONS
+            .iter()
+            .find(|(canonical, _)| relocate(base, *canonical) == address)
+        {
+            return Some(*canonical);
+        }
+        (!is_arbitrum_precompile(address)).then_some(address)
+    }
+}
+
+fn is_arbitrum_precompile(address: Address) -> bool {
+    PRECOMPILE_MIN_ARBOS_VERSIONS
+        .iter()
+        .any(|(canonical, _)| *canonical == address)
+}
+
+/// `base + offset`, wrapping at 160 bits.
+fn relocate(base: Address, offset: Address) -> Address {
+    let sum = U256::from_be_slice(base.as_slice()) + U256::from_be_slice(offset.as_slice());
+    Address::from_word(sum.into())
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -26,6 +122,23 @@ pub struct ArbitrumConfig<SPEC = SpecId> {
     pub debug_mode: bool,
     pub disable_auto_cache: bool,
     pub disable_auto_activate: bool,
+    pub legacy_owner_acts: bool,
+    pub arbos_version_from_state: bool,
+    pub cache_precompile_results: bool,
+    pub enforce_min_base_fee: bool,
+    pub reject_unprotected_txs: bool,
+    pub owner_multicall: bool,
+    pub arb_wasm_module_hash: bool,
+    pub arbos_state_address: Address,
+    pub precompile_address_base: Address,
+    pub wasm_store_dir: Option<PathBuf>,
+    pub max_stylus_call_depth: Option<usize>,
+    pub limit_stylus_buffers: bool,
+    pub arbos_storage_access_costs_from: Option<u16>,
+    pub stylus_compile_target: StylusCompileTarget,
+    /// Gas costs of precompile methods by canonical address and selector, see
+    /// [`ArbitrumConfigTr::precompile_gas_override`].
+    pub precompile_gas_overrides: HashMap<Address, HashMap<FixedBytes<4>, u64>>,
 }
 
 impl<SPEC> ArbitrumConfig<SPEC> {
@@ -39,6 +152,21 @@ impl<SPEC> ArbitrumConfig<SPEC> {
             debug_mode: false,
             disable_auto_cache: false,
             disable_auto_activate: false,
+            legacy_owner_acts: false,
+            arbos_version_from_state: false,
+            cache_precompile_results: false,
+            enforce_min_base_fee: false,
+            reject_unprotected_txs: false,
+            owner_multicall: false,
+            arb_wasm_module_hash: false,
+            arbos_state_address: ARBOS_STATE_ADDRESS,
+            precompile_address_base: Address::ZERO,
+            wasm_store_dir: None,
+            max_stylus_call_depth: None,
+            limit_stylus_buffers: false,
+            arbos_storage_access_costs_from: None,
+            stylus_compile_target: StylusCompileTarget::Host,
+            precompile_gas_overrides: HashMap::new(),
         }
     }
 }
@@ -54,6 +182,21 @@ where
             debug_mode: false,
             disable_auto_cache: false,
             disable_auto_activate: false,
+            legacy_owner_acts: false,
+            arbos_version_from_state: false,
+            cache_precompile_results: false,
+            enforce_min_base_fee: false,
+            reject_unprotected_txs: false,
+            owner_multicall: false,
+            arb_wasm_module_hash: false,
+            arbos_state_address: ARBOS_STATE_ADDRESS,
+            precompile_address_base: Address::ZERO,
+            wasm_store_dir: None,
+            max_stylus_call_depth: None,
+            limit_stylus_buffers: false,
+            arbos_storage_access_costs_from: None,
+            stylus_compile_target: StylusCompileTarget::Host,
+            precompile_gas_overrides: HashMap::new(),
         }
     }
 }
@@ -134,8 +277,8 @@ impl<SPEC> ArbitrumConfigTr for ArbitrumConfig<SPEC>
 where
     SPEC: Into<SpecId> + Copy + Copy,
 {
-    fn arbos_version(&self) -> u16 {
-        self.arbos_version
+    fn arbos_version(&self) -> ArbosVersion {
+        ArbosVersion::new(self.arbos_version)
     }
 
     fn debug_mode(&self) -> bool {
@@ -149,6 +292,69 @@ where
     fn disable_auto_activate(&self) -> bool {
         self.disable_auto_activate
     }
+
+    fn legacy_owner_acts(&self) -> bool {
+        self.legacy_owner_acts
+    }
+
+    fn arbos_version_from_state(&self) -> bool {
+        self.arbos_version_from_state
+    }
+
+    fn cache_precompile_results(&self) -> bool {
+        self.cache_precompile_results
+    }
+
+    fn enforce_min_base_fee(&self) -> bool {
+        self.enforce_min_base_fee
+    }
+
+    fn reject_unprotected_txs(&self) -> bool {
+        self.reject_unprotected_txs
+    }
+
+    fn owner_multicall(&self) -> bool {
+        self.owner_multicall
+    }
+
+    fn arb_wasm_module_hash(&self) -> bool {
+        self.arb_wasm_module_hash
+    }
+
+    fn arbos_state_address(&self) -> Address {
+        self.arbos_state_address
+    }
+
+    fn precompile_address_base(&self) -> Address {
+        self.precompile_address_base
+    }
+
+    fn wasm_store_dir(&self) -> Option<&Path> {
+        self.wasm_store_dir.as_deref()
+    }
+
+    fn max_stylus_call_depth(&self) -> Option<usize> {
+        self.max_stylus_call_depth
+    }
+
+    fn limit_stylus_buffers(&self) -> bool {
+        self.limit_stylus_buffers
+    }
+
+    fn arbos_storage_access_costs_from(&self) -> Option<ArbosVersion> {
+        self.arbos_storage_access_costs_from.map(ArbosVersion::new)
+    }
+
+    fn stylus_compile_target(&self) -> StylusCompileTarget {
+        self.stylus_compile_target
+    }
+
+    fn precompile_gas_override(&self, address: Address, selector: [u8; 4]) -> Option<u64> {
+        self.precompile_gas_overrides
+            .get(&address)?
+            .get(&FixedBytes(selector))
+            .copied()
+    }
 }
 
 impl<SPEC: Into<SpecId> + Copy> ArbitrumConfig<SPEC> {
@@ -159,6 +365,21 @@ impl<SPEC: Into<SpecId> + Copy> ArbitrumConfig<SPEC> {
             debug_mode: false,
             disable_auto_cache: false,
             disable_auto_activate: false,
+            legacy_owner_acts: false,
+            arbos_version_from_state: false,
+            cache_precompile_results: false,
+            enforce_min_base_fee: false,
+            reject_unprotected_txs: false,
+            owner_multicall: false,
+            arb_wasm_module_hash: false,
+            arbos_state_address: ARBOS_STATE_ADDRESS,
+            precompile_address_base: Address::ZERO,
+            wasm_store_dir: None,
+            max_stylus_call_depth: None,
+            limit_stylus_buffers: false,
+            arbos_storage_access_costs_from: None,
+            stylus_compile_target: StylusCompileTarget::Host,
+            precompile_gas_overrides: HashMap::new(),
         }
     }
 }
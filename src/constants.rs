@@ -19,6 +19,7 @@ pub const INITIAL_FREE_PAGES: u16 = 2; // 2 pages come free
 pub const INITIAL_PAGE_GAS: u16 = 1000; // linear cost per allocation.
 pub const INITIAL_PAGE_RAMP: u64 = 620674314; // targets 8
 pub const INITIAL_PAGE_LIMIT: u16 = 128; // reject wasms with memories larger than 8MB.
+pub const WASM_PAGE_SIZE: usize = 64 * 1024;
 pub const INITIAL_INK_PRICE: u32 = 10000; // 1 evm
 pub const INITIAL_MIN_INIT_GAS: u8 = 72; // charge 72 * 128 = 9216 gas.
 pub const INITIAL_MIN_CACHED_GAS: u8 = 11; // charge 11
@@ -34,6 +35,26 @@ pub const INITIAL_DATA_PRICER_LAST_UPDATE_TIME: u64 = ARBOS_GENESIS_TIMESTAMP as
 pub const INITIAL_DATA_PRICER_MIN_PRICE: u32 = 82928201;
 pub const INITIAL_DATA_PRICER_INERTIA: u32 = 21360419;
 
+pub const INITIAL_SPEED_LIMIT_PER_SECOND_V0: u64 = 1_000_000;
+pub const INITIAL_PER_BLOCK_GAS_LIMIT_V0: u64 = 20_000_000;
+pub const INITIAL_BASE_FEE_WEI: u64 = 100_000_000; // 0.1 gwei
+pub const INITIAL_MINIMUM_BASE_FEE_WEI: u64 = 100_000_000; // 0.1 gwei
+pub const INITIAL_PRICING_INERTIA: u64 = 102;
+pub const INITIAL_BACKLOG_TOLERANCE: u64 = 10;
+
+pub const INITIAL_L1_EQUILIBRATION_UNITS_V0: u64 = 60 * 16 * 100_000; // 60 batches of 100kB
+pub const INITIAL_L1_PRICING_INERTIA: u64 = 10;
+pub const INITIAL_L1_PER_UNIT_REWARD: u64 = 10;
+
+// Parameters later ArbOS versions migrate to.
+pub const INITIAL_SPEED_LIMIT_PER_SECOND_V6: u64 = 7_000_000;
+pub const INITIAL_PER_BLOCK_GAS_LIMIT_V6: u64 = 32_000_000;
+pub const INITIAL_L1_EQUILIBRATION_UNITS_V6: u64 = 16 * 10_000_000; // 10 million bytes of calldata
+pub const INITIAL_PER_BATCH_GAS_COST_V6: u64 = 100_000;
+pub const INITIAL_PER_BATCH_GAS_COST_V12: u64 = 210_000;
+pub const INITIAL_PER_TX_GAS_LIMIT_V50: u64 = 32_000_000;
+pub const V2_MIN_INIT_GAS: u8 = 69; // charge 69 * 128 = 8832 gas.
+
 pub const MIN_INIT_GAS_UNITS: u64 = 128;
 pub const MIN_CACHED_GAS_UNITS: u64 = 32;
 pub const COST_SCALAR_PERCENT: u64 = 2;
@@ -74,6 +95,48 @@ pub const ARBOS_BATCH_POSTER_ADDRESS: Address =
 pub const ARBOS_L1_PRICER_FUNDS_ADDRESS: Address =
     address!("0xA4B00000000000000000000000000000000000f6");
 
+// Canonical Arbitrum precompile addresses, relocated by `ArbitrumConfigTr::precompile_address`.
+pub const ARB_SYS_ADDRESS: Address = address!("0x0000000000000000000000000000000000000064");
+pub const ARB_INFO_ADDRESS: Address = address!("0x0000000000000000000000000000000000000065");
+pub const ARB_ADDRESS_TABLE_ADDRESS: Address =
+    address!("0x0000000000000000000000000000000000000066");
+pub const ARBOS_TEST_ADDRESS: Address = address!("0x0000000000000000000000000000000000000069");
+pub const ARB_OWNER_PUBLIC_ADDRESS: Address =
+    address!("0x000000000000000000000000000000000000006b");
+pub const ARB_GAS_INFO_ADDRESS: Address = address!("0x000000000000000000000000000000000000006c");
+pub const ARB_AGGREGATOR_ADDRESS: Address = address!("0x000000000000000000000000000000000000006d");
+pub const ARB_RETRYABLE_TX_ADDRESS: Address =
+    address!("0x000000000000000000000000000000000000006e");
+pub const ARB_STATISTICS_ADDRESS: Address = address!("0x000000000000000000000000000000000000006f");
+pub const ARB_OWNER_ADDRESS: Address = address!("0x0000000000000000000000000000000000000070");
+pub const ARB_WASM_ADDRESS: Address = address!("0x0000000000000000000000000000000000000071");
+pub const ARB_WASM_CACHE_ADDRESS: Address = address!("0x0000000000000000000000000000000000000072");
+pub const ARB_NATIVE_TOKEN_MANAGER_ADDRESS: Address =
+    address!("0x0000000000000000000000000000000000000073");
+pub const ARB_DEBUG_ADDRESS: Address = address!("0x00000000000000000000000000000000000000ff");
+
+/// Arbitrum precompile addresses and the ArbOS version each was introduced in. Nitro gives every
+/// precompile a single `INVALID` opcode as code from that version on, so callers see a contract.
+pub const PRECOMPILE_MIN_ARBOS_VERSIONS: &[(Address, u16)] = &[
+    (ARB_SYS_ADDRESS, 0),
+    (ARB_INFO_ADDRESS, 0),
+    (ARB_ADDRESS_TABLE_ADDRESS, 0),
+    (ARBOS_TEST_ADDRESS, 0),
+    (ARB_OWNER_PUBLIC_ADDRESS, 0),
+    (ARB_GAS_INFO_ADDRESS, 0),
+    (ARB_AGGREGATOR_ADDRESS, 0),
+    (ARB_RETRYABLE_TX_ADDRESS, 0),
+    (ARB_STATISTICS_ADDRESS, 0),
+    (ARB_OWNER_ADDRESS, 0),
+    (ARB_WASM_ADDRESS, 30),
+    (ARB_WASM_CACHE_ADDRESS, 30),
+    (ARB_NATIVE_TOKEN_MANAGER_ADDRESS, 41),
+    (ARB_DEBUG_ADDRESS, 0),
+];
+
+/// Code installed at precompile addresses: a lone `INVALID` opcode.
+pub const PRECOMPILE_MARKER_CODE: &[u8] = &[0xfe];
+
 pub const ARBOS_GENESIS_TIMESTAMP: u32 = 1672531200; // January 1, 2023 00:00:00 GMT
 
 // Arbitrum Transaction Types (EIP-2718 style)
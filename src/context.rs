@@ -1,9 +1,10 @@
 use revm::{
     Context, Journal,
-    context::{BlockEnv, ContextTr},
+    context::{Block, BlockEnv, ContextTr},
 };
 
 use crate::{
+    arbos_version::ArbosVersion,
     config::{ArbitrumConfig, ArbitrumConfigTr},
     local_context::{ArbitrumLocalContext, ArbitrumLocalContextTr},
     transaction::{ArbitrumTransaction, ArbitrumTxTr},
@@ -26,9 +27,28 @@ pub type ArbitrumContext<DB> = Context<
 pub trait ArbitrumContextTr:
     ContextTr<Cfg: ArbitrumConfigTr, Tx: ArbitrumTxTr, Local: ArbitrumLocalContextTr>
 {
+    /// ArbOS version the current transaction executes under: the version read from ArbOS state
+    /// when the config asks for it and the state is initialized, the configured one otherwise.
+    fn arbos_version(&self) -> ArbosVersion {
+        self.local()
+            .arbos_version()
+            .unwrap_or_else(|| self.cfg().arbos_version())
+    }
 }
 
 impl<T> ArbitrumContextTr for T where
     T: ContextTr<Cfg: ArbitrumConfigTr, Tx: ArbitrumTxTr, Local: ArbitrumLocalContextTr>
 {
 }
+
+/// Block environment whose basefee can be raised to the minimum L2 basefee, see
+/// [`ArbitrumConfigTr::enforce_min_base_fee`].
+pub trait ArbitrumBlockTr: Block + Clone {
+    fn set_basefee(&mut self, basefee: u64);
+}
+
+impl ArbitrumBlockTr for BlockEnv {
+    fn set_basefee(&mut self, basefee: u64) {
+        self.basefee = basefee;
+    }
+}
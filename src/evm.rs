@@ -1,13 +1,39 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+};
 
 use crate::{
-    ArbitrumContextTr, constants::STYLUS_DISCRIMINANT, handler::ArbitrumHandler,
-    transaction::ArbitrumTransactionError,
+    ArbitrumContextTr, ArbitrumInternalTx,
+    block::{ArbitrumHeaderInfo, ArbitrumReceipt, BlockExecutionResult, StartBlockInfo},
+    config::ArbitrumConfigTr,
+    constants::{ARB_WASM_ADDRESS, STYLUS_DISCRIMINANT},
+    context::ArbitrumBlockTr,
+    handler::{ArbitrumHandler, intrinsic::TX_GAS},
+    l1_fee,
+    local_context::{
+        ActivatedProgram, ArbitrumLocalContextTr, OwnerAction, PrecompileGasUsage, RecentWasms,
+    },
+    overrides::{BlockOverrides, StateOverride, apply_state_overrides},
+    precompiles::IArbWasm,
+    program_cache::{ProgramCache, ProgramCacheStats},
+    result::{
+        ActivationEstimate, ArbitrumExecutionDetails, ArbitrumGasEstimate,
+        ArbitrumGasEstimateComponents,
+    },
+    state::{
+        ArbState, ArbStateGetter, ChainParameters, PricingReport,
+        types::{ArbosStateError, StorageBackedTr},
+    },
+    stylus_executor::{ProgramArtifact, precompile_programs, program_artifact},
+    transaction::{ArbitrumRetryTx, ArbitrumTransactionError},
 };
+use alloy_sol_types::SolCall;
 use revm::{
     Database, DatabaseCommit, ExecuteCommitEvm, ExecuteEvm, Inspector,
     context::{
-        ContextError, ContextSetters, ContextTr, Evm, FrameStack, JournalTr,
+        Block, BlockEnv, Cfg, ContextError, ContextSetters, ContextTr, Evm, FrameStack, JournalTr,
+        Transaction, TxEnv,
         result::{EVMError, ExecutionResult, HaltReason, ResultAndState},
     },
     handler::{
@@ -15,7 +41,12 @@ use revm::{
         PrecompileProvider,
         instructions::{EthInstructions, InstructionProvider},
     },
-    interpreter::{InterpreterResult, interpreter::EthInterpreter, interpreter_action::FrameInit},
+    inspector::JournalExt,
+    interpreter::{
+        CallScheme, FrameInput, InterpreterResult, gas::CALL_STIPEND, interpreter::EthInterpreter,
+        interpreter_action::FrameInit,
+    },
+    primitives::{Address, B256, TxKind, U256},
     state::EvmState,
 };
 
@@ -37,6 +68,187 @@ impl<CTX, I, INSP, P, F> ArbitrumEvm<CTX, INSP, P, I, F> {
     }
 }
 
+impl<CTX, INSP, P, I, F> ArbitrumEvm<CTX, INSP, P, I, F>
+where
+    CTX: ArbitrumContextTr,
+{
+    /// Compile Stylus programs into `cache` instead of the process-wide cache, so that a pool of
+    /// instances can share one cache.
+    pub fn with_program_cache(mut self, cache: ProgramCache) -> Self {
+        self.0.ctx.local_mut().set_program_cache(cache);
+        self
+    }
+
+    /// Take the redeems scheduled by the transactions executed so far, oldest first. Nitro runs
+    /// each as a retry transaction right after the transaction that scheduled it.
+    pub fn take_scheduled_redeems(&mut self) -> Vec<ArbitrumRetryTx> {
+        self.0.ctx.local_mut().take_queued_redeems()
+    }
+
+    /// Advance the L2 pricing model to a new block `time_passed` seconds after the last one,
+    /// draining the gas backlog at the speed limit, and return the repriced basefee to build the
+    /// block with. The StartBlock internal transaction does the same.
+    pub fn update_pricing_model(&mut self, time_passed: u64) -> Result<U256, ArbosStateError> {
+        let ctx = &mut self.0.ctx;
+        let result = {
+            let mut arb_state = ctx.system_arb_state(false);
+            let mut l2_pricing = arb_state.l2_pricing();
+            l2_pricing
+                .update_pricing_model(time_passed)
+                .and_then(|()| l2_pricing.base_fee_wei().get())
+        };
+        // Leave the state accesses out of the next transaction's warm set
+        ctx.journal_mut().commit_tx();
+        result
+    }
+
+    /// Every chain parameter the chain owners govern, read in one go. ABI-encode the result to
+    /// hand a snapshot to governance tooling.
+    pub fn chain_parameters(&mut self) -> Result<ChainParameters, ArbosStateError> {
+        let ctx = &mut self.0.ctx;
+        let result = ctx.system_arb_state(true).chain_parameters();
+        // Leave the state accesses out of the next transaction's warm set
+        ctx.journal_mut().commit_tx();
+        result
+    }
+
+    /// Gas used by each precompile method called in the last transaction, to tell ArbOS
+    /// overhead apart from the gas used by user code.
+    pub fn precompile_gas_used(&self) -> &PrecompileGasUsage {
+        self.0.ctx.local().precompile_gas()
+    }
+
+    /// Gas breakdown of the last transaction: Stylus ink, pages and startup gas, the L1 data fee
+    /// and gas used per precompile method.
+    pub fn execution_details(&self) -> &ArbitrumExecutionDetails {
+        self.0.ctx.local().execution_details()
+    }
+
+    /// Stylus programs activated by the last transaction, explicitly through ArbWasm or
+    /// implicitly when called.
+    pub fn activated_programs(&self) -> &[ActivatedProgram] {
+        self.0.ctx.local().activated_programs()
+    }
+
+    /// Stylus programs called in the current block, whose next calls are charged the cached
+    /// init gas while they stay among the block cache size most recent.
+    pub fn recent_wasms(&self) -> &RecentWasms {
+        self.0.ctx.local().recent_wasms()
+    }
+
+    /// Empty the block cache of recently called Stylus programs. Call this between blocks when
+    /// executing them transaction by transaction rather than with `transact_block`.
+    pub fn clear_recent_wasms(&mut self) {
+        self.0.ctx.local_mut().clear_recent_wasms();
+    }
+
+    /// Record the hash of L2 block `number` once the block is sealed. `ArbSys.arbBlockHash` in
+    /// the blocks after it reads the hashes of the last 256 blocks recorded this way, falling
+    /// back to the database for blocks that were not. So does BLOCKHASH while ArbOS state is
+    /// uninitialized; see [`crate::instructions`].
+    pub fn record_block_hash(&mut self, number: u64, hash: B256) {
+        self.0.ctx.local_mut().record_block_hash(number, hash);
+    }
+
+    /// Chain-owner actions applied by the transactions executed so far, oldest first.
+    pub fn owner_actions(&self) -> &[OwnerAction] {
+        self.0.ctx.local().owner_actions()
+    }
+
+    /// Take the logged chain-owner actions, starting a fresh log.
+    pub fn take_owner_actions(&mut self) -> Vec<OwnerAction> {
+        self.0.ctx.local_mut().take_owner_actions()
+    }
+
+    /// How the Stylus calls of the transactions executed so far were served by the program
+    /// cache, with the time spent compiling and the ink used.
+    pub fn program_cache_stats(&self) -> &ProgramCacheStats {
+        self.0.ctx.local().program_cache_stats()
+    }
+
+    /// Take the program cache statistics, e.g. once a block is built, starting afresh.
+    pub fn take_program_cache_stats(&mut self) -> ProgramCacheStats {
+        self.0.ctx.local_mut().take_program_cache_stats()
+    }
+
+    /// Serialized native code and compile settings of the Stylus program with `code_hash`,
+    /// compiling it under the current settings when it is not cached, for verification against
+    /// or transport to another node.
+    pub fn program_artifact(&mut self, code_hash: B256) -> Result<ProgramArtifact, String>
+    where
+        CTX::Journal: JournalExt,
+    {
+        program_artifact(&mut self.0.ctx, code_hash)
+    }
+
+    /// Apply `eth_call` state overrides ahead of the next transaction. See [`overrides`].
+    ///
+    /// [`overrides`]: crate::overrides
+    pub fn apply_state_overrides(
+        &mut self,
+        overrides: &StateOverride,
+    ) -> Result<(), <CTX::Db as Database>::Error>
+    where
+        CTX::Journal: JournalExt,
+    {
+        apply_state_overrides(&mut self.0.ctx, overrides)
+    }
+
+    /// Apply `eth_call` block overrides to the block the next transaction runs in.
+    pub fn apply_block_overrides(&mut self, overrides: &BlockOverrides)
+    where
+        CTX: ContextTr<Block = BlockEnv> + ContextSetters,
+    {
+        let mut block = self.0.ctx.block().clone();
+        overrides.apply(&mut block);
+        self.0.ctx.set_block(block);
+    }
+
+    /// Compile the Stylus programs with `code_hashes` into the program cache in parallel, ahead
+    /// of the transactions calling them. Returns the outcome for each code hash, in order.
+    pub fn precompile_programs(
+        &mut self,
+        code_hashes: impl IntoIterator<Item = B256>,
+    ) -> Vec<(B256, Result<(), String>)>
+    where
+        CTX::Journal: JournalExt,
+    {
+        precompile_programs(&mut self.0.ctx, code_hashes)
+    }
+}
+
+impl<CTX, INSP, P, I, F> ArbitrumEvm<CTX, INSP, P, I, F>
+where
+    CTX: ArbitrumContextTr<Block: ArbitrumBlockTr> + ContextSetters,
+{
+    /// Raise the block's basefee to the minimum L2 basefee held in ArbOS state when the config
+    /// enforces it. Reading the minimum leaves no trace in the journal.
+    pub(crate) fn apply_min_base_fee(&mut self) {
+        let ctx = &mut self.0.ctx;
+        if !ctx.cfg().enforce_min_base_fee() {
+            return;
+        }
+
+        let checkpoint = ctx.journal_mut().checkpoint();
+        let min_base_fee = ctx
+            .system_arb_state(true)
+            .l2_pricing()
+            .min_base_fee_wei()
+            .get();
+        ctx.journal_mut().checkpoint_revert(checkpoint);
+
+        let Ok(min_base_fee) = min_base_fee else {
+            return;
+        };
+        let min_base_fee = min_base_fee.saturating_to::<u64>();
+        if ctx.block().basefee() < min_base_fee {
+            let mut block = ctx.block().clone();
+            block.set_basefee(min_base_fee);
+            ctx.set_block(block);
+        }
+    }
+}
+
 impl<CTX, INSP, P, I, F> Deref for ArbitrumEvm<CTX, INSP, P, I, F>
 where
     CTX: ArbitrumContextTr + ContextSetters,
@@ -101,7 +313,24 @@ where
         ItemOrResult<&mut Self::Frame, <Self::Frame as FrameTr>::FrameResult>,
         ContextError<<<Self::Context as ContextTr>::Db as Database>::Error>,
     > {
-        self.0.frame_init(frame_input)
+        let (caller, acting) = frame_caller(&frame_input.frame_input);
+        self.0.ctx.local_mut().push_caller(caller, acting);
+
+        let is_first = self.0.frame_stack.index().is_none();
+        let result = self.0.frame_init(frame_input);
+
+        // Frames that complete during initialization (precompiles, early failures) are passed to
+        // `frame_return_result` like any other, except for the first frame of an execution loop,
+        // whose result the loop returns directly.
+        match &result {
+            Ok(ItemOrResult::Result(frame_result)) if is_first => {
+                self.0.ctx.local_mut().pop_caller();
+                record_frame_revert(&mut self.0.ctx, frame_result);
+            }
+            Ok(_) => {}
+            Err(_) => self.0.ctx.local_mut().pop_caller(),
+        }
+        result
     }
 
     fn frame_run(
@@ -121,11 +350,13 @@ where
         {
             let frame = self.0.frame_stack.get();
             let context = &mut self.0.ctx;
-            return frame.process_next_action(context, action).inspect(|i| {
+            let result = frame.process_next_action(context, action).inspect(|i| {
                 if i.is_result() {
                     frame.set_finished(true);
                 }
             });
+            context.local_mut().set_stylus_outcome(None);
+            return result;
         }
 
         self.0.frame_run()
@@ -138,6 +369,8 @@ where
         Option<<Self::Frame as FrameTr>::FrameResult>,
         ContextError<<<Self::Context as ContextTr>::Db as Database>::Error>,
     > {
+        self.0.ctx.local_mut().pop_caller();
+        record_frame_revert(&mut self.0.ctx, &result);
         self.0.frame_return_result(result)
     }
 
@@ -177,7 +410,8 @@ where
 impl<CTX, INSP, INST, PRECOMPILES> ExecuteEvm
     for ArbitrumEvm<CTX, INSP, PRECOMPILES, INST, EthFrame<EthInterpreter>>
 where
-    CTX: ArbitrumContextTr<Journal: JournalTr<State = EvmState>> + ContextSetters,
+    CTX: ArbitrumContextTr<Journal: JournalTr<State = EvmState>, Block: ArbitrumBlockTr>
+        + ContextSetters,
     INST: InstructionProvider<Context = CTX, InterpreterTypes = EthInterpreter>,
     PRECOMPILES: PrecompileProvider<CTX, Output = InterpreterResult>,
 {
@@ -190,6 +424,7 @@ where
     #[inline]
     fn transact_one(&mut self, tx: Self::Tx) -> Result<Self::ExecutionResult, Self::Error> {
         self.0.ctx.set_tx(tx);
+        self.apply_min_base_fee();
         ArbitrumHandler::default().run(self)
     }
 
@@ -205,6 +440,7 @@ where
 
     #[inline]
     fn replay(&mut self) -> Result<ResultAndState<HaltReason>, Self::Error> {
+        self.apply_min_base_fee();
         ArbitrumHandler::default().run(self).map(|result| {
             let state = self.finalize();
             ResultAndState::new(result, state)
@@ -212,11 +448,396 @@ where
     }
 }
 
+impl<CTX, INSP, INST, PRECOMPILES>
+    ArbitrumEvm<CTX, INSP, PRECOMPILES, INST, EthFrame<EthInterpreter>>
+where
+    CTX: ArbitrumContextTr<Journal: JournalTr<State = EvmState>, Block: ArbitrumBlockTr>
+        + ContextSetters,
+    INST: InstructionProvider<Context = CTX, InterpreterTypes = EthInterpreter>,
+    PRECOMPILES: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+    /// Execute `tx` like [`ExecuteEvm::transact_one`], also returning a breakdown of the gas it
+    /// used for profiling.
+    pub fn transact_one_with_details(
+        &mut self,
+        tx: <CTX as ContextTr>::Tx,
+    ) -> Result<
+        (ExecutionResult<HaltReason>, ArbitrumExecutionDetails),
+        EVMError<<CTX::Db as Database>::Error, ArbitrumTransactionError>,
+    > {
+        let result = self.transact_one(tx)?;
+        Ok((result, self.execution_details().clone()))
+    }
+
+    /// Estimate `tx` together with the `ArbWasm.activateProgram` calls it needs first, one per
+    /// Stylus program it called that had to be auto-activated, so the programs are only found
+    /// while auto-activation is enabled.
+    ///
+    /// `tx` is executed, then each activation from the same sender with the block gas limit and
+    /// value enough for any data fee, minted to the sender beforehand and refunded past the fee.
+    /// Like [`ExecuteEvm::transact_one`], this leaves every state change in the journal, so
+    /// estimate against state to be discarded.
+    pub fn estimate_gas_with_activations(
+        &mut self,
+        tx: TxEnv,
+    ) -> Result<ArbitrumGasEstimate, EVMError<<CTX::Db as Database>::Error, ArbitrumTransactionError>>
+    where
+        <CTX as ContextTr>::Tx: From<TxEnv>,
+    {
+        let TxEnv {
+            caller,
+            gas_price,
+            gas_priority_fee,
+            chain_id,
+            ..
+        } = tx;
+        let result = self.transact_one(tx.into())?;
+
+        let mut programs: Vec<(Address, B256)> = Vec::new();
+        for activation in self.activated_programs() {
+            if activation.auto_activated
+                && !programs
+                    .iter()
+                    .any(|(_, code_hash)| *code_hash == activation.code_hash)
+            {
+                programs.push((activation.program, activation.code_hash));
+            }
+        }
+
+        let arb_wasm = self.0.ctx.cfg().precompile_address(ARB_WASM_ADDRESS);
+        let gas_limit = self.0.ctx.block().gas_limit();
+        let value = U256::from(u128::MAX);
+        let mut activations = Vec::with_capacity(programs.len());
+        for (program, code_hash) in programs {
+            let journal = self.0.ctx.journal_mut();
+            let nonce = journal.load_account(caller)?.data.info.nonce;
+            journal.balance_incr(
+                caller,
+                value + U256::from(gas_limit) * U256::from(gas_price),
+            )?;
+
+            let activation = TxEnv {
+                caller,
+                kind: TxKind::Call(arb_wasm),
+                data: IArbWasm::activateProgramCall { program }
+                    .abi_encode()
+                    .into(),
+                value,
+                gas_limit,
+                gas_price,
+                gas_priority_fee,
+                chain_id,
+                nonce,
+                ..Default::default()
+            };
+            let result = self.transact_one(activation.into())?;
+            let data_fee = self
+                .activated_programs()
+                .iter()
+                .find(|activation| !activation.auto_activated && activation.code_hash == code_hash)
+                .map_or(U256::ZERO, |activation| activation.data_fee);
+            activations.push(ActivationEstimate {
+                program,
+                code_hash,
+                gas_used: result.gas_used(),
+                data_fee,
+                success: result.is_success(),
+            });
+        }
+
+        Ok(ArbitrumGasEstimate {
+            result,
+            activations,
+        })
+    }
+
+    /// Run `simulate` against a throwaway copy of the state, e.g. to speculatively execute a
+    /// bundle of transactions, and return what it returns. The state, ArbOS state included, is
+    /// restored afterwards, along with the block cache of recently called Stylus programs, the
+    /// owner action log and the queued redeems, which `simulate` does not see. Call it between
+    /// transactions; see [`ArbState::snapshot`] to revert within one.
+    pub fn simulate<T>(&mut self, simulate: impl FnOnce(&mut Self) -> T) -> T
+    where
+        CTX::Journal: JournalExt,
+    {
+        let state = self.0.ctx.journal_mut().evm_state().clone();
+        let local = self.0.ctx.local_mut();
+        let recent_wasms = local.recent_wasms().clone();
+        let owner_actions = local.owner_actions().to_vec();
+        // Redeems queued by earlier transactions are not the simulation's to run
+        let queued_redeems = local.take_queued_redeems();
+
+        let result = simulate(self);
+
+        let ctx = &mut self.0.ctx;
+        ctx.journal_mut().discard_tx();
+        *ctx.journal_mut().evm_state_mut() = state;
+        let local = ctx.local_mut();
+        local.set_recent_wasms(recent_wasms);
+        local.take_owner_actions();
+        local.log_owner_actions(owner_actions);
+        local.take_queued_redeems();
+        local.queue_redeems(queued_redeems);
+        local.clear_precompile_results();
+        result
+    }
+
+    /// Estimate the gas limit `tx` needs as Nitro's `eth_estimateGas` does, split into its
+    /// execution and L1 data fee components like `NodeInterface.gasEstimateComponents`.
+    ///
+    /// The execution gas is found by binary search over the gas limit, from the gas limit of
+    /// `tx`, or the block gas limit if it has none, down to within 1.5% of the least that
+    /// succeeds. Each attempt also runs the redeems `tx` schedules, failing if any fails, so
+    /// that enough gas is left to donate to them. The L1 data fee is priced from a stand-in for
+    /// the signed transaction, see [`l1_fee::estimation_envelope`], with Nitro's padding.
+    ///
+    /// Every attempt is [simulated](Self::simulate), leaving the state as it was. An invalid `tx`
+    /// is returned as an error, while a `tx` failing with the most gas is returned with its
+    /// outcome.
+    pub fn estimate_gas(
+        &mut self,
+        tx: TxEnv,
+    ) -> Result<
+        ArbitrumGasEstimateComponents,
+        EVMError<<CTX::Db as Database>::Error, ArbitrumTransactionError>,
+    >
+    where
+        CTX::Journal: JournalExt,
+        <CTX as ContextTr>::Tx: From<TxEnv> + From<ArbitrumRetryTx>,
+    {
+        let block_gas_limit = self.0.ctx.block().gas_limit();
+        let mut hi = if tx.gas_limit >= TX_GAS && tx.gas_limit < block_gas_limit {
+            tx.gas_limit
+        } else {
+            block_gas_limit
+        };
+        let with_gas_limit = |gas_limit| TxEnv {
+            gas_limit,
+            ..tx.clone()
+        };
+
+        let result = match self.simulate(|evm| evm.execute_with_redeems(with_gas_limit(hi)))? {
+            Ok((result, true)) => result,
+            Ok((result, false)) => {
+                return self.gas_estimate_components(&tx, hi, result);
+            }
+            Err(error) => return Err(EVMError::Transaction(error)),
+        };
+
+        // The gas used is not enough if the refund covered some of it, or if calls needed gas
+        // kept back by the 63/64 rule
+        let mut lo = result.gas_used().saturating_sub(1);
+        let mut best = result;
+        let refunded = match &best {
+            ExecutionResult::Success { gas_refunded, .. } => *gas_refunded,
+            _ => 0,
+        };
+        let optimistic = (best.gas_used() + refunded + CALL_STIPEND) * 64 / 63;
+        if optimistic < hi {
+            match self.simulate(|evm| evm.execute_with_redeems(with_gas_limit(optimistic)))? {
+                Ok((result, true)) => {
+                    hi = optimistic;
+                    best = result;
+                }
+                _ => lo = optimistic,
+            }
+        }
+
+        while lo + 1 < hi {
+            if (hi - lo) as f64 / (hi as f64) < ESTIMATE_GAS_ERROR_RATIO {
+                break;
+            }
+            // Most transactions need little more than they use, so search closer to `lo`
+            let mid = ((hi + lo) / 2).min(lo.saturating_mul(2));
+            match self.simulate(|evm| evm.execute_with_redeems(with_gas_limit(mid)))? {
+                Ok((result, true)) => {
+                    hi = mid;
+                    best = result;
+                }
+                _ => lo = mid,
+            }
+        }
+
+        self.gas_estimate_components(&tx, hi, best)
+    }
+
+    /// Execute `tx` followed by the redeems it schedules, as Nitro runs them when estimating,
+    /// stopping at the first that fails.
+    fn execute_with_redeems(
+        &mut self,
+        tx: TxEnv,
+    ) -> Result<EstimationAttempt, EVMError<<CTX::Db as Database>::Error, ArbitrumTransactionError>>
+    where
+        <CTX as ContextTr>::Tx: From<TxEnv> + From<ArbitrumRetryTx>,
+    {
+        let result = match self.transact_one(tx.into()) {
+            Ok(result) => result,
+            Err(EVMError::Transaction(error)) => return Ok(Err(error)),
+            Err(error) => return Err(error),
+        };
+        if !result.is_success() {
+            return Ok(Ok((result, false)));
+        }
+
+        let mut redeems = VecDeque::from(self.take_scheduled_redeems());
+        while let Some(redeem) = redeems.pop_front() {
+            match self.transact_one(redeem.into()) {
+                Ok(redeem_result) if redeem_result.is_success() => {}
+                Ok(_) | Err(EVMError::Transaction(_)) => return Ok(Ok((result, false))),
+                Err(error) => return Err(error),
+            }
+            redeems.extend(self.take_scheduled_redeems());
+        }
+        Ok(Ok((result, true)))
+    }
+
+    /// Add the L1 data fee of `tx` to the `execution_gas` it needs, with its `result`.
+    fn gas_estimate_components(
+        &mut self,
+        tx: &TxEnv,
+        execution_gas: u64,
+        result: ExecutionResult<HaltReason>,
+    ) -> Result<
+        ArbitrumGasEstimateComponents,
+        EVMError<<CTX::Db as Database>::Error, ArbitrumTransactionError>,
+    > {
+        let (l1_base_fee_estimate, min_base_fee, brotli_compression_level) = self
+            .read_block_state(|ctx| {
+                let mut arb_state = ctx.system_arb_state(true);
+                Ok((
+                    arb_state.l1_pricing().price_per_unit().get()?,
+                    arb_state.l2_pricing().min_base_fee_wei().get()?,
+                    arb_state.brotli_compression_level().get()?,
+                ))
+            })?;
+        let base_fee = U256::from(self.0.ctx.block().basefee());
+
+        let gas_estimate_for_l1 = if self.0.ctx.cfg().is_fee_charge_disabled() {
+            0
+        } else {
+            let envelope = l1_fee::estimation_envelope(tx, self.0.ctx.cfg().chain_id());
+            let l1_cost = l1_fee::calculate_estimated_tx_l1_cost(
+                &envelope,
+                l1_base_fee_estimate,
+                brotli_compression_level,
+            );
+            l1_fee::calculate_estimated_poster_gas(l1_cost, base_fee, min_base_fee)
+        };
+
+        Ok(ArbitrumGasEstimateComponents {
+            gas_estimate: execution_gas.saturating_add(gas_estimate_for_l1),
+            gas_estimate_for_l1,
+            base_fee,
+            l1_base_fee_estimate,
+            result,
+        })
+    }
+
+    /// Execute an L2 block: open it with the StartBlock internal transaction built from
+    /// `start_block`, run `txs` in order, each followed by the retries it schedules, and read the
+    /// block's header info once they are done. Transactions failing validation are left out of
+    /// the block, as Nitro does, retries included; any other error aborts it.
+    ///
+    /// The block cache of recently called Stylus programs starts empty, as in Nitro.
+    ///
+    /// L2 pricing is updated as the block executes: StartBlock drains the gas backlog and
+    /// reprices, and the gas of each transaction joins the backlog. The returned pricing report
+    /// shows the state before and after.
+    pub fn transact_block(
+        &mut self,
+        txs: impl IntoIterator<Item = <CTX as ContextTr>::Tx>,
+        block_env: <CTX as ContextTr>::Block,
+        start_block: StartBlockInfo,
+    ) -> Result<
+        BlockExecutionResult,
+        EVMError<<CTX::Db as Database>::Error, ArbitrumTransactionError>,
+    >
+    where
+        <CTX as ContextTr>::Tx: From<ArbitrumInternalTx> + From<ArbitrumRetryTx>,
+    {
+        let block_number = block_env.number().saturating_to::<u64>();
+        let timestamp = block_env.timestamp().saturating_to::<u64>();
+        self.set_block(block_env);
+        self.clear_recent_wasms();
+        let before = self.read_block_state(|ctx| ctx.system_arb_state(true).pricing_snapshot())?;
+
+        let mut receipts = Vec::new();
+        let mut rejected = Vec::new();
+        let mut gas_used = 0;
+
+        let start_block = ArbitrumInternalTx::start_block(
+            self.0.ctx.cfg().chain_id(),
+            start_block.l1_base_fee,
+            start_block.l1_block_number,
+            block_number,
+            start_block.time_passed,
+        );
+        let result = self.transact_one(start_block.into())?;
+        receipts.push(self.receipt(result, &mut gas_used));
+
+        let mut txs = txs.into_iter().enumerate();
+        let mut redeems = VecDeque::new();
+        loop {
+            // Retries run before the next transaction, including those scheduled by retries
+            redeems.extend(self.take_scheduled_redeems());
+            let (index, tx) = match redeems.pop_front() {
+                Some(redeem) => (None, redeem.into()),
+                None => match txs.next() {
+                    Some((index, tx)) => (Some(index), tx),
+                    None => break,
+                },
+            };
+            match (self.transact_one(tx), index) {
+                (Ok(result), _) => receipts.push(self.receipt(result, &mut gas_used)),
+                (Err(EVMError::Transaction(error)), Some(index)) => rejected.push((index, error)),
+                // A retry of a ticket redeemed earlier in the block is dropped, as Nitro does
+                (Err(EVMError::Transaction(_)), None) => {}
+                (Err(error), _) => return Err(error),
+            }
+        }
+
+        let after = self.read_block_state(|ctx| ctx.system_arb_state(true).pricing_snapshot())?;
+        let header_info = self.read_block_state(ArbitrumHeaderInfo::read)?;
+        Ok(BlockExecutionResult {
+            receipts,
+            rejected,
+            gas_used,
+            pricing: PricingReport::new(block_number, timestamp, gas_used, before, after),
+            header_info,
+        })
+    }
+
+    /// Receipt of the transaction that just ran with `result`, adding its gas to `gas_used`.
+    fn receipt(&self, result: ExecutionResult<HaltReason>, gas_used: &mut u64) -> ArbitrumReceipt {
+        *gas_used += result.gas_used();
+        ArbitrumReceipt {
+            tx_type: self.0.ctx.tx().tx_type(),
+            result,
+            cumulative_gas_used: *gas_used,
+            gas_used_for_l1: self.execution_details().poster_gas,
+        }
+    }
+
+    /// Read ArbOS state between the transactions of a block.
+    fn read_block_state<T>(
+        &mut self,
+        read: impl FnOnce(&mut CTX) -> Result<T, ArbosStateError>,
+    ) -> Result<T, EVMError<<CTX::Db as Database>::Error, ArbitrumTransactionError>> {
+        let result = read(&mut self.0.ctx);
+        // Leave the state accesses out of the next transaction's warm set
+        self.0.ctx.journal_mut().commit_tx();
+        result.map_err(|error| EVMError::Custom(error.into()))
+    }
+}
+
 impl<CTX, INSP, INST, PRECOMPILES> ExecuteCommitEvm
     for ArbitrumEvm<CTX, INSP, PRECOMPILES, INST, EthFrame<EthInterpreter>>
 where
-    CTX: ArbitrumContextTr<Journal: JournalTr<State = EvmState>, Db: DatabaseCommit>
-        + ContextSetters,
+    CTX: ArbitrumContextTr<
+            Journal: JournalTr<State = EvmState>,
+            Block: ArbitrumBlockTr,
+            Db: DatabaseCommit,
+        > + ContextSetters,
     INST: InstructionProvider<Context = CTX, InterpreterTypes = EthInterpreter>,
     PRECOMPILES: PrecompileProvider<CTX, Output = InterpreterResult>,
 {
@@ -272,3 +893,32 @@ where
         }
     }
 }
+
+/// Gas estimates are accurate to within this fraction of the gas limit, as in geth.
+const ESTIMATE_GAS_ERROR_RATIO: f64 = 0.015;
+
+/// Outcome of an attempt of [`ArbitrumEvm::estimate_gas`] and whether it succeeded, or why the
+/// transaction was invalid with the gas limit tried.
+type EstimationAttempt = Result<(ExecutionResult<HaltReason>, bool), ArbitrumTransactionError>;
+
+/// Failed frames rewind the journal, so cached precompile results no longer apply to it.
+/// Caller of the frame for `frame_input`, and the account it acts as for reentrancy. Delegate
+/// calls and callcodes run as their caller, so only other calls count towards reentering the
+/// account they act as.
+pub(crate) fn frame_caller(frame_input: &FrameInput) -> (Address, Option<Address>) {
+    match frame_input {
+        FrameInput::Call(inputs) => (
+            inputs.caller,
+            matches!(inputs.scheme, CallScheme::Call | CallScheme::StaticCall)
+                .then_some(inputs.target_address),
+        ),
+        FrameInput::Create(inputs) => (inputs.caller, None),
+        _ => (Address::ZERO, None),
+    }
+}
+
+fn record_frame_revert<CTX: ArbitrumContextTr>(ctx: &mut CTX, result: &FrameResult) {
+    if !result.interpreter_result().result.is_ok() {
+        ctx.local_mut().record_journal_revert();
+    }
+}
@@ -0,0 +1,110 @@
+//! Forking a live Arbitrum chain over RPC, for replaying its transactions locally.
+//!
+//! Accounts and storage are fetched from the node the first time they are read and cached from
+//! then on. ArbOS state is no exception: it lives in the storage of the ArbOS state account, so
+//! a forked EVM prices, versions and gates transactions from the chain's real ArbOS state.
+
+use alloy_provider::{DynProvider, Provider, ProviderBuilder, network::Ethereum};
+use revm::{
+    Journal,
+    context::BlockEnv,
+    database::{AlloyDB, CacheDB, WrapDatabaseAsync, alloydb::BlockId},
+    handler::instructions::EthInstructions,
+    inspector::NoOpInspector,
+    interpreter::interpreter::EthInterpreter,
+    primitives::{B256, U256},
+};
+use tokio::runtime::Runtime;
+
+use crate::{
+    ArbitrumContext, ArbitrumEvm, ArbitrumTransaction, config::ArbitrumConfig,
+    instructions::arbitrum_instructions, local_context::ArbitrumLocalContext,
+    precompiles::ArbitrumPrecompileProvider,
+};
+
+/// State of the forked chain, fetched over RPC and cached.
+pub type ForkDb = CacheDB<WrapDatabaseAsync<AlloyDB<Ethereum, DynProvider>>>;
+
+pub type ForkContext = ArbitrumContext<ForkDb>;
+
+pub type ForkEvm = ArbitrumEvm<
+    ForkContext,
+    NoOpInspector,
+    ArbitrumPrecompileProvider<ForkContext>,
+    EthInstructions<EthInterpreter, ForkContext>,
+>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ForkError {
+    #[error("failed to start the async runtime: {0}")]
+    Runtime(#[from] std::io::Error),
+    #[error("RPC request failed: {0}")]
+    Rpc(String),
+    #[error("block {0} not found")]
+    BlockNotFound(u64),
+}
+
+impl ForkEvm {
+    /// Fork the chain served at `url` to replay the transactions of `block`: state is read as it
+    /// was at the end of the block before, and the block environment is `block`'s own.
+    ///
+    /// The ArbOS version is read from ArbOS state, and the chain ID from the node.
+    pub fn fork_from_rpc(url: &str, block: u64) -> Result<Self, ForkError> {
+        let runtime = Runtime::new()?;
+        let (provider, chain_id, block_env) = runtime.block_on(async {
+            let provider = ProviderBuilder::new()
+                .connect(url)
+                .await
+                .map_err(rpc_error)?
+                .erased();
+            let chain_id = provider.get_chain_id().await.map_err(rpc_error)?;
+            let header = provider
+                .get_block_by_number(block.into())
+                .await
+                .map_err(rpc_error)?
+                .ok_or(ForkError::BlockNotFound(block))?
+                .header;
+
+            let block_env = BlockEnv {
+                number: U256::from(header.number),
+                beneficiary: header.beneficiary,
+                timestamp: U256::from(header.timestamp),
+                gas_limit: header.gas_limit,
+                basefee: header.base_fee_per_gas.unwrap_or_default(),
+                difficulty: header.difficulty,
+                // Arbitrum headers reuse the mix hash for L1 data, and PREVRANDAO is always 1
+                prevrandao: Some(B256::from(U256::from(1))),
+                ..Default::default()
+            };
+            Ok::<_, ForkError>((provider, chain_id, block_env))
+        })?;
+
+        let state = AlloyDB::new(provider, BlockId::number(block.saturating_sub(1)));
+        let db = CacheDB::new(WrapDatabaseAsync::with_runtime(state, runtime));
+
+        let mut cfg: ArbitrumConfig = ArbitrumConfig::default();
+        cfg.inner.chain_id = chain_id;
+        cfg.arbos_version_from_state = true;
+
+        let context = ArbitrumContext {
+            journaled_state: Journal::new(db),
+            block: block_env,
+            cfg,
+            tx: ArbitrumTransaction::default(),
+            chain: (),
+            local: ArbitrumLocalContext::default(),
+            error: Ok(()),
+        };
+
+        Ok(ArbitrumEvm::new_with_inspector(
+            context,
+            NoOpInspector {},
+            arbitrum_instructions(),
+            ArbitrumPrecompileProvider::default(),
+        ))
+    }
+}
+
+fn rpc_error(error: impl std::fmt::Display) -> ForkError {
+    ForkError::Rpc(error.to_string())
+}
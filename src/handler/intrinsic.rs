@@ -0,0 +1,167 @@
+//! Intrinsic gas of Arbitrum transactions.
+//!
+//! Nitro charges go-ethereum's intrinsic gas, under the fork rules its chain config enables by
+//! ArbOS version rather than by block: EIP-2028 calldata pricing and EIP-2930 access lists from
+//! genesis, the EIP-3860 initcode charge from ArbOS 11 and the EIP-7623 calldata floor from
+//! ArbOS 40.
+//!
+//! System transactions (deposits, internal and submit retryable transactions) are applied by
+//! ArbOS and charged no intrinsic gas. A retryable submission only schedules its auto-redeem when
+//! the gas it donates covers [`TX_GAS`], the intrinsic gas of the retry transaction.
+
+use revm::{
+    context::Transaction, context_interface::transaction::AccessListItemTr,
+    interpreter::gas::InitialAndFloorGas,
+};
+
+use crate::{
+    ArbitrumContextTr,
+    arbos_version::{ArbosFeature, ArbosVersion},
+    constants::{
+        ARBITRUM_DEPOSIT_TX_TYPE, ARBITRUM_INTERNAL_TX_TYPE, ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE,
+    },
+};
+
+/// Intrinsic gas of a call.
+pub const TX_GAS: u64 = 21_000;
+/// Intrinsic gas of a contract creation.
+pub const TX_GAS_CONTRACT_CREATION: u64 = 53_000;
+/// Gas per zero byte of calldata.
+pub const TX_DATA_ZERO_GAS: u64 = 4;
+/// Gas per non-zero byte of calldata.
+pub const TX_DATA_NON_ZERO_GAS: u64 = 16;
+/// Gas per 32-byte word of initcode.
+pub const INIT_CODE_WORD_GAS: u64 = 2;
+/// Gas per address in the access list.
+pub const TX_ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+/// Gas per storage key in the access list.
+pub const TX_ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+/// Gas per EIP-7702 authorization.
+pub const TX_AUTHORIZATION_GAS: u64 = 25_000;
+/// Calldata tokens per non-zero byte, a zero byte counting as one.
+pub const TX_TOKENS_PER_NON_ZERO_BYTE: u64 = 4;
+/// Floor gas per calldata token.
+pub const TX_COST_FLOOR_PER_TOKEN: u64 = 10;
+
+/// Intrinsic gas and calldata floor of a transaction with `input` under ArbOS `version`.
+pub fn intrinsic_gas(
+    version: ArbosVersion,
+    input: &[u8],
+    is_create: bool,
+    access_list_addresses: u64,
+    access_list_storage_keys: u64,
+    authorizations: u64,
+) -> InitialAndFloorGas {
+    let zero_bytes = input.iter().filter(|byte| **byte == 0).count() as u64;
+    let non_zero_bytes = input.len() as u64 - zero_bytes;
+
+    let mut gas = if is_create {
+        TX_GAS_CONTRACT_CREATION
+    } else {
+        TX_GAS
+    };
+    gas = gas
+        .saturating_add(zero_bytes.saturating_mul(TX_DATA_ZERO_GAS))
+        .saturating_add(non_zero_bytes.saturating_mul(TX_DATA_NON_ZERO_GAS));
+    if is_create && version.supports(ArbosFeature::InitCodeWordGas) {
+        let words = (input.len() as u64).div_ceil(32);
+        gas = gas.saturating_add(words.saturating_mul(INIT_CODE_WORD_GAS));
+    }
+    gas = gas
+        .saturating_add(access_list_addresses.saturating_mul(TX_ACCESS_LIST_ADDRESS_GAS))
+        .saturating_add(access_list_storage_keys.saturating_mul(TX_ACCESS_LIST_STORAGE_KEY_GAS))
+        .saturating_add(authorizations.saturating_mul(TX_AUTHORIZATION_GAS));
+
+    let floor_gas = if version.supports(ArbosFeature::CalldataFloorGas) {
+        let tokens =
+            zero_bytes.saturating_add(non_zero_bytes.saturating_mul(TX_TOKENS_PER_NON_ZERO_BYTE));
+        TX_GAS.saturating_add(tokens.saturating_mul(TX_COST_FLOOR_PER_TOKEN))
+    } else {
+        0
+    };
+
+    InitialAndFloorGas {
+        initial_gas: gas,
+        floor_gas,
+    }
+}
+
+/// Intrinsic gas and calldata floor of the transaction in `ctx`, nothing for system
+/// transactions.
+pub fn tx_intrinsic_gas<CTX: ArbitrumContextTr>(ctx: &CTX) -> InitialAndFloorGas {
+    let tx = ctx.tx();
+    if matches!(
+        tx.tx_type(),
+        ARBITRUM_DEPOSIT_TX_TYPE | ARBITRUM_INTERNAL_TX_TYPE | ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE
+    ) {
+        return InitialAndFloorGas {
+            initial_gas: 0,
+            floor_gas: 0,
+        };
+    }
+
+    let (addresses, storage_keys) = tx
+        .access_list()
+        .map(|items| {
+            items.fold((0u64, 0u64), |(addresses, keys), item| {
+                (addresses + 1, keys + item.storage_slots().count() as u64)
+            })
+        })
+        .unwrap_or_default();
+
+    intrinsic_gas(
+        ctx.arbos_version(),
+        tx.input(),
+        tx.kind().is_create(),
+        addresses,
+        storage_keys,
+        tx.authorization_list_len() as u64,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALLDATA: [u8; 4] = [0x00, 0x01, 0x00, 0x02];
+
+    #[test]
+    fn calldata_is_priced_per_byte() {
+        let gas = intrinsic_gas(ArbosVersion::new(1), &CALLDATA, false, 0, 0, 0);
+        assert_eq!(gas.initial_gas, TX_GAS + 2 * 4 + 2 * 16);
+    }
+
+    #[test]
+    fn initcode_is_charged_from_arbos_11() {
+        let initcode = [0xff; 33];
+        let creation = TX_GAS_CONTRACT_CREATION + 33 * TX_DATA_NON_ZERO_GAS;
+
+        let gas = intrinsic_gas(ArbosVersion::new(10), &initcode, true, 0, 0, 0);
+        assert_eq!(gas.initial_gas, creation);
+        let gas = intrinsic_gas(ArbosVersion::new(11), &initcode, true, 0, 0, 0);
+        assert_eq!(gas.initial_gas, creation + 2 * INIT_CODE_WORD_GAS);
+        // Calls are not charged for their input as initcode
+        let gas = intrinsic_gas(ArbosVersion::new(11), &initcode, false, 0, 0, 0);
+        assert_eq!(gas.initial_gas, TX_GAS + 33 * TX_DATA_NON_ZERO_GAS);
+    }
+
+    #[test]
+    fn calldata_floor_applies_from_arbos_40() {
+        let gas = intrinsic_gas(ArbosVersion::new(32), &CALLDATA, false, 0, 0, 0);
+        assert_eq!(gas.floor_gas, 0);
+        let gas = intrinsic_gas(ArbosVersion::new(40), &CALLDATA, false, 0, 0, 0);
+        assert_eq!(
+            gas.floor_gas,
+            TX_GAS + (2 + 2 * 4) * TX_COST_FLOOR_PER_TOKEN
+        );
+    }
+
+    #[test]
+    fn access_list_and_authorizations_are_charged() {
+        let gas = intrinsic_gas(ArbosVersion::new(40), &[], false, 2, 3, 1);
+        assert_eq!(
+            gas.initial_gas,
+            TX_GAS + 2 * 2_400 + 3 * 1_900 + TX_AUTHORIZATION_GAS
+        );
+    }
+}
@@ -0,0 +1,1155 @@
+use std::ops::Deref;
+
+use crate::{
+    ArbitrumContextTr,
+    arbos_version::{ArbosFeature, ArbosVersion},
+    config::ArbitrumConfigTr,
+    constants::{
+        ARB_OWNER_ADDRESS, ARB_RETRYABLE_TX_ADDRESS, ARB_WASM_ADDRESS, ARBITRUM_DEPOSIT_TX_TYPE,
+        ARBITRUM_INTERNAL_TX_TYPE, ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE, ARBOS_ADDRESS,
+        ARBOS_L1_PRICER_FUNDS_ADDRESS,
+    },
+    l1_fee,
+    local_context::{ActivatedProgram, ArbitrumLocalContextTr, OwnerAction},
+    precompiles::{ArbOwner, ArbRetryableTx, IArbWasm},
+    result::ArbitrumHaltReason,
+    state::{
+        ArbState, ArbStateGetter,
+        retryable::{
+            RETRYABLE_LIFETIME_SECONDS, retryable_escrow_address, retryable_submission_fee,
+        },
+        types::StorageBackedTr,
+    },
+    transaction::{
+        ArbitrumInternalTx, ArbitrumRetryTx, ArbitrumSubmitRetryableTx, ArbitrumTransactionError,
+        ArbitrumTxTr, ArbosActs,
+    },
+};
+use alloy_sol_types::{SolCall, SolEvent};
+use revm::{
+    Inspector,
+    context::{
+        Block, Cfg, ContextTr, JournalTr, LocalContextTr, Transaction,
+        result::{ExecutionResult, FromStringError, HaltReason, InvalidTransaction, SuccessReason},
+    },
+    handler::{
+        EthFrame, EvmTr, FrameTr, Handler, MainnetHandler,
+        handler::EvmTrError,
+        pre_execution::{calculate_caller_fee, validate_account_nonce_and_code_with_components},
+    },
+    inspector::{InspectorEvmTr, InspectorHandler},
+    interpreter::{
+        SuccessOrHalt, gas::InitialAndFloorGas, interpreter::EthInterpreter,
+        interpreter_action::FrameInit,
+    },
+    primitives::{Address, B256, Bytes, Log, TxKind, U256, alloy_primitives::IntoLogData},
+    state::EvmState,
+};
+use tracing::warn;
+
+pub mod intrinsic;
+pub mod validation;
+
+pub struct ArbitrumHandler<EVM, ERROR, FRAME> {
+    /// Mainnet handler allows us to use functions from the mainnet handler inside Arbitrum
+    /// handler. So we dont duplicate the logic
+    pub mainnet: MainnetHandler<EVM, ERROR, FRAME>,
+    /// Phantom data to avoid type inference issues.
+    pub _phantom: core::marker::PhantomData<(EVM, ERROR, FRAME)>,
+}
+
+impl<EVM, ERROR, FRAME> ArbitrumHandler<EVM, ERROR, FRAME> {
+    pub fn new() -> Self {
+        Self {
+            mainnet: MainnetHandler::default(),
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<EVM, ERROR, FRAME> Default for ArbitrumHandler<EVM, ERROR, FRAME> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<EVM, ERROR> ArbitrumHandler<EVM, ERROR, EthFrame<EthInterpreter>>
+where
+    EVM: EvmTr<
+            Context: ArbitrumContextTr<Journal: JournalTr<State = EvmState>>,
+            Frame = EthFrame<EthInterpreter>,
+        >,
+    ERROR: EvmTrError<EVM> + FromStringError + From<ArbitrumTransactionError>,
+{
+    /// Executes a system transaction, or returns `None` for transactions that run through the
+    /// normal execution flow.
+    fn run_system_tx(
+        &mut self,
+        evm: &mut EVM,
+    ) -> Option<Result<ExecutionResult<HaltReason>, ERROR>> {
+        let tx_type = evm.ctx().tx().tx_type();
+        if !matches!(
+            tx_type,
+            ARBITRUM_DEPOSIT_TX_TYPE
+                | ARBITRUM_INTERNAL_TX_TYPE
+                | ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE
+        ) {
+            return None;
+        }
+
+        // System transactions skip `validate_env`, so their chain ID is checked here
+        if let Err(error) = validation::validate_tx_chain_id(evm.ctx()) {
+            return Some(Err(error.into()));
+        }
+        Some(match tx_type {
+            ARBITRUM_DEPOSIT_TX_TYPE => self.execute_deposit_tx(evm),
+            ARBITRUM_INTERNAL_TX_TYPE => self.execute_internal_tx(evm),
+            _ => self.execute_submit_retryable(evm),
+        })
+    }
+
+    /// Executes an Arbitrum deposit transaction.
+    ///
+    /// Deposit transactions mint ETH from L1 to L2 without charging gas or checking the nonce:
+    /// 1. Validate the recipient
+    /// 2. Mint the value to the sender's account
+    /// 3. Transfer the value from sender to recipient
+    /// 4. Return success with 0 gas used
+    fn execute_deposit_tx(&mut self, evm: &mut EVM) -> Result<ExecutionResult<HaltReason>, ERROR> {
+        let ctx = evm.ctx();
+        let caller = ctx.tx().caller();
+        let value = ctx.tx().value();
+        let to = match ctx.tx().kind() {
+            TxKind::Call(addr) => addr,
+            TxKind::Create => {
+                // Deposit transactions should always have a recipient
+                return Err(ERROR::from_string(
+                    "deposit transaction must have a recipient".to_string(),
+                ));
+            }
+        };
+
+        // Mint ETH to the caller (from L1), then transfer to recipient
+        // This mimics nitro's behavior: MintBalance(&from, value) then Transfer(from, *to, value)
+        ctx.journal_mut().balance_incr(caller, value)?;
+
+        // Transfer from caller to recipient (if different)
+        if caller != to {
+            // The transfer method handles both deduction and increment
+            if let Some(transfer_err) = ctx.journal_mut().transfer(caller, to, value)? {
+                return Err(ERROR::from_string(format!(
+                    "deposit transfer failed: {:?}",
+                    transfer_err
+                )));
+            }
+        }
+
+        // Commit the transaction
+        ctx.journal_mut().commit_tx();
+        ctx.local_mut().clear();
+        evm.frame_stack().clear();
+
+        // Return success with 0 gas used
+        Ok(ExecutionResult::Success {
+            reason: SuccessReason::Stop,
+            gas_used: 0,
+            gas_refunded: 0,
+            output: revm::context::result::Output::Call(Bytes::new()),
+            logs: Vec::new(),
+        })
+    }
+
+    /// Executes an Arbitrum internal transaction.
+    ///
+    /// Internal transactions are sent by ArbOS to update system state, mirroring Nitro's
+    /// `ApplyInternalTxUpdate`:
+    /// - StartBlock records the L1 block, reaps expired retryables and reprices L2 gas
+    /// - BatchPostingReport reimburses the batch poster and reprices L1 data
+    fn execute_internal_tx(&mut self, evm: &mut EVM) -> Result<ExecutionResult<HaltReason>, ERROR> {
+        let ctx = evm.ctx();
+        let caller = ctx.tx().caller();
+
+        // Internal transactions must be from ArbOS
+        if caller != ARBOS_ADDRESS {
+            return Err(ERROR::from_string(
+                "internal transaction must be from ArbOS address".to_string(),
+            ));
+        }
+
+        let result = apply_internal_tx_update(ctx);
+
+        // Commit the transaction, or drop its changes if the instruction was invalid
+        if result.is_ok() {
+            ctx.journal_mut().commit_tx();
+        } else {
+            ctx.journal_mut().discard_tx();
+        }
+        ctx.local_mut().clear();
+        evm.frame_stack().clear();
+        result.map_err(ERROR::from_string)?;
+
+        // Return success with 0 gas used
+        Ok(ExecutionResult::Success {
+            reason: SuccessReason::Stop,
+            gas_used: 0,
+            gas_refunded: 0,
+            output: revm::context::result::Output::Call(Bytes::new()),
+            logs: Vec::new(),
+        })
+    }
+
+    /// Executes an Arbitrum submit retryable transaction.
+    ///
+    /// Submit retryable transactions create a retryable ticket for L1->L2 message passing. The
+    /// input is the `ArbRetryableTx.submitRetryable` encoding of the message, and no EVM code
+    /// runs:
+    /// 1. Mint the deposit to the caller and charge the submission fee
+    /// 2. Move the callvalue into escrow and create the ticket
+    /// 3. Schedule the auto-redeem if the caller can pay for its gas
+    fn execute_submit_retryable(
+        &mut self,
+        evm: &mut EVM,
+    ) -> Result<ExecutionResult<HaltReason>, ERROR> {
+        let ctx = evm.ctx();
+        let result = submit_retryable(ctx);
+        let logs = ctx.journal_mut().take_logs();
+        let redeems = ctx.local_mut().take_scheduled_redeems();
+        if result.is_ok() {
+            ctx.local_mut().queue_redeems(redeems);
+        }
+
+        // Commit the transaction, keeping the minted deposit even if submission failed
+        ctx.journal_mut().commit_tx();
+        ctx.local_mut().clear();
+        evm.frame_stack().clear();
+
+        Ok(match result {
+            Ok((ticket_id, gas_used)) => ExecutionResult::Success {
+                reason: SuccessReason::Return,
+                gas_used,
+                gas_refunded: 0,
+                output: revm::context::result::Output::Call(Bytes::copy_from_slice(
+                    ticket_id.as_slice(),
+                )),
+                logs,
+            },
+            Err(error) => ExecutionResult::Revert {
+                gas_used: 0,
+                output: Bytes::from(error.into_bytes()),
+            },
+        })
+    }
+}
+
+impl<EVM, ERROR> Handler for ArbitrumHandler<EVM, ERROR, EthFrame<EthInterpreter>>
+where
+    EVM: EvmTr<
+            Context: ArbitrumContextTr<Journal: JournalTr<State = EvmState>>,
+            Frame = EthFrame<EthInterpreter>,
+        >,
+    ERROR: EvmTrError<EVM> + FromStringError + From<ArbitrumTransactionError>,
+{
+    type Evm = EVM;
+    type Error = ERROR;
+    type HaltReason = HaltReason;
+
+    /// Main entry point for transaction execution.
+    ///
+    /// Overrides the default to handle Arbitrum system transactions (deposit, internal, submit
+    /// retryable) which skip normal EVM execution and have special semantics.
+    fn run(
+        &mut self,
+        evm: &mut Self::Evm,
+    ) -> Result<ExecutionResult<Self::HaltReason>, Self::Error> {
+        evm.ctx().local_mut().clear_precompile_gas();
+        evm.ctx().local_mut().clear_execution_details();
+        evm.ctx().local_mut().set_activated_programs(Vec::new());
+        if evm.ctx().cfg().arbos_version_from_state() {
+            load_arbos_version(evm.ctx());
+        }
+
+        if let Some(result) = self.run_system_tx(evm) {
+            return result;
+        }
+
+        // Standard transaction - use normal execution flow
+        match self.run_without_catch_error(evm) {
+            Ok(output) => Ok(output),
+            Err(e) => self.catch_error(evm, e),
+        }
+    }
+
+    /// Checks the chain ID by the rules of the transaction's type, see [`validation`], before
+    /// the mainnet block and transaction checks.
+    fn validate_env(&self, evm: &mut Self::Evm) -> Result<(), Self::Error> {
+        validation::validate_tx_chain_id(evm.ctx())?;
+        self.mainnet.validate_env(evm)
+    }
+
+    /// Computes Nitro's intrinsic gas for the transaction, see [`intrinsic`], and checks that
+    /// its gas limit covers it.
+    fn validate_initial_tx_gas(&self, evm: &Self::Evm) -> Result<InitialAndFloorGas, Self::Error> {
+        let ctx = evm.ctx_ref();
+        let gas = intrinsic::tx_intrinsic_gas(ctx);
+        let gas_limit = ctx.tx().gas_limit();
+        if gas.initial_gas > gas_limit {
+            return Err(InvalidTransaction::CallGasCostMoreThanGasLimit {
+                gas_limit,
+                initial_gas: gas.initial_gas,
+            }
+            .into());
+        }
+        if gas.floor_gas > gas_limit {
+            return Err(InvalidTransaction::GasFloorMoreThanGasLimit {
+                gas_floor: gas.floor_gas,
+                gas_limit,
+            }
+            .into());
+        }
+        Ok(gas)
+    }
+
+    /// Loads the transaction's accounts, warming the Arbitrum precompiles at the addresses the
+    /// config deploys them at rather than their canonical ones.
+    fn load_accounts(&self, evm: &mut Self::Evm) -> Result<(), Self::Error> {
+        self.mainnet.load_accounts(evm)?;
+
+        let (ctx, precompiles) = evm.ctx_precompiles();
+        if !ctx.cfg().precompile_address_base().is_zero() {
+            let addresses = precompiles
+                .warm_addresses()
+                .map(|address| ctx.cfg().precompile_address(address))
+                .collect();
+            ctx.journal_mut().warm_precompiles(addresses);
+        }
+        Ok(())
+    }
+
+    /// Validates caller state, calculates L1 fee, and deducts both L1 and L2 fees from caller's balance.
+    ///
+    /// This method:
+    /// 1. Validates account nonce and code
+    /// 2. Reads L1 pricing from ArbOS state
+    /// 3. Calculates the L1 data fee based on transaction bytes
+    /// 4. Deducts both the L1 fee and the standard L2 gas fee from the caller
+    /// 5. Caches the L1 cost in local context for later distribution
+    fn validate_against_state_and_deduct_caller(
+        &self,
+        evm: &mut Self::Evm,
+    ) -> Result<(), Self::Error> {
+        let ctx = evm.ctx();
+
+        // Get L1 pricing from state if we have enveloped transaction data
+        // Clone the enveloped_tx from transaction to avoid borrow issues
+        let enveloped_tx = ctx.tx().enveloped_tx().cloned();
+
+        let l1_cost = if !ctx.cfg().is_fee_charge_disabled() {
+            if let Some(ref enveloped_tx) = enveloped_tx {
+                // Read L1 price per unit and the compression level it is charged at from ArbOS
+                // state
+                let mut arb_state = ctx.system_arb_state(false);
+                let l1_base_fee = arb_state
+                    .l1_pricing()
+                    .price_per_unit()
+                    .get()
+                    .unwrap_or(U256::ZERO);
+                let brotli_compression_level =
+                    arb_state.brotli_compression_level().get().unwrap_or(0);
+                drop(arb_state);
+
+                // Calculate L1 cost
+                let cost = l1_fee::calculate_tx_l1_cost(
+                    enveloped_tx,
+                    l1_base_fee,
+                    brotli_compression_level,
+                );
+
+                // Calculate and cache poster gas
+                let basefee = ctx.block().basefee() as u128;
+                let poster_gas = l1_fee::calculate_poster_gas(cost, U256::from(basefee));
+                ctx.local_mut().set_poster_gas(Some(poster_gas));
+
+                Some(cost)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        // Cache the L1 cost in local context
+        ctx.local_mut().set_tx_l1_cost(l1_cost);
+
+        // Retry transactions take their callvalue from escrow and had their gas prepaid
+        let is_retry = if let Some(retry_tx) = ctx.tx().retry_tx().cloned() {
+            start_retry_tx::<_, Self::Error>(ctx, &retry_tx)?;
+            true
+        } else {
+            false
+        };
+
+        // Now do the standard validation with all_mut
+        let (block, tx, cfg, journal, _, _) = ctx.all_mut();
+
+        // Load caller's account
+        let mut caller = journal.load_account_with_code_mut(tx.caller())?.data;
+
+        // Validate nonce and code (JournaledAccount derefs to Account). Retry transactions are
+        // sent on behalf of the ticket's sender and skip these checks.
+        if !is_retry {
+            validate_account_nonce_and_code_with_components(&caller.deref().info, tx, cfg)?;
+        }
+
+        let mut balance = *caller.balance();
+
+        // Deduct L1 fee if calculated
+        if let Some(l1_cost) = l1_cost {
+            let Some(new_balance) = balance.checked_sub(l1_cost) else {
+                return Err(InvalidTransaction::LackOfFundForMaxFee {
+                    fee: Box::new(l1_cost),
+                    balance: Box::new(balance),
+                }
+                .into());
+            };
+            balance = new_balance;
+        }
+
+        // Calculate and deduct L2 gas fee
+        let balance = calculate_caller_fee(balance, tx, block, cfg)?;
+
+        // Update caller balance and nonce
+        caller.set_balance(balance);
+        if tx.kind().is_call() {
+            caller.bump_nonce();
+        }
+
+        Ok(())
+    }
+
+    /// Builds the first frame with the gas left after intrinsic gas, capped at the gas ArbOS
+    /// lets a transaction use, see [`validation::compute_gas_limit`]. The gas held back is
+    /// refunded once the frame ends.
+    fn first_frame_input(
+        &mut self,
+        evm: &mut Self::Evm,
+        gas_limit: u64,
+    ) -> Result<FrameInit, Self::Error> {
+        let max_compute_gas = validation::compute_gas_limit(evm.ctx())
+            .map_err(|err| Self::Error::from_string(String::from(err)))?;
+        let gas_limit = match max_compute_gas {
+            Some(max_compute_gas) if gas_limit > max_compute_gas => {
+                let hold_gas = gas_limit - max_compute_gas;
+                evm.ctx().local_mut().set_compute_hold_gas(hold_gas);
+                max_compute_gas
+            }
+            _ => gas_limit,
+        };
+        self.mainnet.first_frame_input(evm, gas_limit)
+    }
+
+    /// Settles the gas of the last frame and refunds the gas held back from it, whether or not
+    /// it succeeded. A frame that ran out of gas while gas was held back ran out of the gas
+    /// ArbOS allows rather than of its own, which the execution details record.
+    fn last_frame_result(
+        &mut self,
+        evm: &mut Self::Evm,
+        frame_result: &mut <<Self::Evm as EvmTr>::Frame as FrameTr>::FrameResult,
+    ) -> Result<(), Self::Error> {
+        let hold_gas = evm.ctx().local().execution_details().compute_hold_gas;
+        let out_of_gas = matches!(
+            SuccessOrHalt::<HaltReason>::from(frame_result.interpreter_result().result),
+            SuccessOrHalt::Halt(HaltReason::OutOfGas(_))
+        );
+        self.mainnet.last_frame_result(evm, frame_result)?;
+
+        if hold_gas != 0 {
+            frame_result.gas_mut().erase_cost(hold_gas);
+            if out_of_gas {
+                evm.ctx()
+                    .local_mut()
+                    .set_gas_limit_failure(ArbitrumHaltReason::TxGasLimitExceeded);
+            }
+        }
+        Ok(())
+    }
+
+    /// Distributes transaction fees to beneficiaries.
+    ///
+    /// This method:
+    /// 1. Calls the mainnet reward_beneficiary for standard L2 fee distribution
+    /// 2. Sends the cached L1 cost to the L1 pricer funds pool address
+    /// 3. Settles the refunds and the ticket of a retry transaction
+    /// 4. Adds the gas used to the L2 gas backlog
+    fn reward_beneficiary(
+        &self,
+        evm: &mut Self::Evm,
+        frame_result: &mut <<Self::Evm as EvmTr>::Frame as FrameTr>::FrameResult,
+    ) -> Result<(), Self::Error> {
+        // First, do the standard mainnet beneficiary reward (L2 fees)
+        self.mainnet.reward_beneficiary(evm, frame_result)?;
+
+        let ctx = evm.ctx();
+        let l1_cost = ctx.local().tx_l1_cost().unwrap_or(U256::ZERO);
+
+        // Send L1 fees to the L1 pricer funds pool
+        if !l1_cost.is_zero() {
+            ctx.journal_mut()
+                .balance_incr(ARBOS_L1_PRICER_FUNDS_ADDRESS, l1_cost)?;
+        }
+
+        if let Some(retry_tx) = ctx.tx().retry_tx().cloned() {
+            let gas = frame_result.gas();
+            let gas_left = gas.remaining() + gas.refunded() as u64;
+            let success = frame_result.interpreter_result().result.is_ok();
+            end_retry_tx(ctx, &retry_tx, success, gas_left).map_err(Self::Error::from_string)?;
+        }
+
+        // The gas used joins the backlog that the pricing model drains at the speed limit
+        let gas = frame_result.gas();
+        let gas_used = gas.spent().saturating_sub(gas.refunded() as u64);
+        ctx.system_arb_state(false)
+            .l2_pricing()
+            .grow_backlog(gas_used)
+            .map_err(|err| Self::Error::from_string(String::from(err)))?;
+
+        Ok(())
+    }
+
+    /// Processes the final execution output, cleans up L1 fee state, queues the redeems the
+    /// transaction scheduled, logs the owner actions it applied and lists the programs it
+    /// activated.
+    fn execution_result(
+        &mut self,
+        evm: &mut Self::Evm,
+        result: <<Self::Evm as EvmTr>::Frame as FrameTr>::FrameResult,
+    ) -> Result<ExecutionResult<Self::HaltReason>, Self::Error> {
+        let scheduled = evm.ctx().local_mut().take_scheduled_redeems();
+        let owner_actions = evm.ctx().local_mut().take_recorded_owner_actions();
+        let activations = evm.ctx().local_mut().take_recorded_activations();
+        evm.ctx().local_mut().seal_execution_details();
+
+        // Get the execution result using the mainnet implementation
+        // Note: mainnet.execution_result already clears local context
+        let result = self.mainnet.execution_result(evm, result)?;
+
+        // Like Nitro's block producer, only redeems whose RedeemScheduled event survived are run
+        let retryable_tx = evm.ctx().cfg().precompile_address(ARB_RETRYABLE_TX_ADDRESS);
+        let redeems = surviving_redeems(retryable_tx, scheduled, result.logs());
+        evm.ctx().local_mut().queue_redeems(redeems);
+
+        let arb_owner = evm.ctx().cfg().precompile_address(ARB_OWNER_ADDRESS);
+        let owner_actions = surviving_owner_actions(arb_owner, owner_actions, result.logs());
+        evm.ctx().local_mut().log_owner_actions(owner_actions);
+
+        let arb_wasm = evm.ctx().cfg().precompile_address(ARB_WASM_ADDRESS);
+        let activations = surviving_activations(arb_wasm, activations, result.logs());
+        evm.ctx().local_mut().set_activated_programs(activations);
+
+        Ok(result)
+    }
+
+    /// Handles cleanup when an error occurs during execution.
+    fn catch_error(
+        &self,
+        evm: &mut Self::Evm,
+        error: Self::Error,
+    ) -> Result<ExecutionResult<Self::HaltReason>, Self::Error> {
+        // mainnet.catch_error clears local context which includes L1 cost
+        self.mainnet.catch_error(evm, error)
+    }
+}
+
+impl<EVM, ERROR> InspectorHandler for ArbitrumHandler<EVM, ERROR, EthFrame<EthInterpreter>>
+where
+    EVM: InspectorEvmTr<
+            Context: ArbitrumContextTr<Journal: JournalTr<State = EvmState>>,
+            Frame = EthFrame<EthInterpreter>,
+            Inspector: Inspector<<<Self as Handler>::Evm as EvmTr>::Context, EthInterpreter>,
+        >,
+    ERROR: EvmTrError<EVM> + FromStringError + From<ArbitrumTransactionError>,
+{
+    type IT = EthInterpreter;
+
+    fn inspect_run(
+        &mut self,
+        evm: &mut Self::Evm,
+    ) -> Result<ExecutionResult<Self::HaltReason>, Self::Error> {
+        evm.ctx().local_mut().clear_precompile_gas();
+        evm.ctx().local_mut().clear_execution_details();
+        evm.ctx().local_mut().set_activated_programs(Vec::new());
+        if evm.ctx().cfg().arbos_version_from_state() {
+            load_arbos_version(evm.ctx());
+        }
+
+        // System transactions run no EVM code, so there is nothing to inspect
+        if let Some(result) = self.run_system_tx(evm) {
+            return result;
+        }
+
+        match self.inspect_run_without_catch_error(evm) {
+            Ok(output) => Ok(output),
+            Err(e) => self.catch_error(evm, e),
+        }
+    }
+}
+
+/// Record the ArbOS version held in state for the transaction about to run. Reading it leaves
+/// no trace in the journal, so access lists and gas are unaffected.
+pub(crate) fn load_arbos_version<CTX: ArbitrumContextTr>(ctx: &mut CTX) {
+    let checkpoint = ctx.journal_mut().checkpoint();
+    let version = ctx
+        .system_arb_state(true)
+        .arbos_version()
+        .get()
+        .ok()
+        .filter(|version| *version != 0)
+        .map(|version| ArbosVersion::new(u16::try_from(version).unwrap_or(u16::MAX)));
+    ctx.journal_mut().checkpoint_revert(checkpoint);
+
+    ctx.local_mut().set_arbos_version(version);
+}
+
+/// The redeems in `scheduled` whose `RedeemScheduled` event, emitted by ArbRetryableTx at
+/// `retryable_tx`, is in `logs`, in event order.
+fn surviving_redeems(
+    retryable_tx: Address,
+    mut scheduled: Vec<ArbitrumRetryTx>,
+    logs: &[Log],
+) -> Vec<ArbitrumRetryTx> {
+    let mut redeems = Vec::new();
+    for log in logs {
+        let topics = log.data.topics();
+        if log.address != retryable_tx
+            || topics.len() < 3
+            || topics[0] != <ArbRetryableTx::RedeemScheduled as SolEvent>::SIGNATURE_HASH
+        {
+            continue;
+        }
+        if let Some(index) = scheduled
+            .iter()
+            .position(|retry_tx| retry_tx.hash() == topics[2])
+        {
+            redeems.push(scheduled.swap_remove(index));
+        }
+    }
+    redeems
+}
+
+/// The actions in `recorded` whose `OwnerActs` event, emitted by ArbOwner at `arb_owner`, is in
+/// `logs`, in event order.
+fn surviving_owner_actions(
+    arb_owner: Address,
+    mut recorded: Vec<OwnerAction>,
+    logs: &[Log],
+) -> Vec<OwnerAction> {
+    let mut actions = Vec::new();
+    for log in logs {
+        if log.address != arb_owner {
+            continue;
+        }
+        let Ok(event) = ArbOwner::OwnerActs::decode_log_data(&log.data) else {
+            continue;
+        };
+        // Legacy OwnerActs logs omit the selector from the data
+        if let Some(index) = recorded.iter().position(|action| {
+            action.method == event.method
+                && action.owner == event.owner
+                && event.data.ends_with(&action.params)
+        }) {
+            actions.push(recorded.remove(index));
+        }
+    }
+    actions
+}
+
+/// The activations in `recorded` that were auto-activated or whose `ProgramActivated` event,
+/// emitted by ArbWasm at `arb_wasm`, is in `logs`.
+fn surviving_activations(
+    arb_wasm: Address,
+    recorded: Vec<ActivatedProgram>,
+    logs: &[Log],
+) -> Vec<ActivatedProgram> {
+    let mut activated = logs
+        .iter()
+        .filter(|log| log.address == arb_wasm)
+        .filter_map(|log| IArbWasm::ProgramActivated::decode_log_data(&log.data).ok())
+        .collect::<Vec<_>>();
+    recorded
+        .into_iter()
+        .filter(|activation| {
+            activation.auto_activated
+                || activated
+                    .iter()
+                    .position(|event| {
+                        event.codehash == activation.code_hash
+                            && event.program == activation.program
+                            && event.dataFee == activation.data_fee
+                    })
+                    .map(|index| activated.remove(index))
+                    .is_some()
+        })
+        .collect()
+}
+
+/// Apply the ArbOS instruction carried by an internal transaction.
+fn apply_internal_tx_update<CTX: ArbitrumContextTr>(ctx: &mut CTX) -> Result<(), String> {
+    let input = ctx.tx().input().clone();
+    let Some(selector) = input.get(..4) else {
+        return Err(format!(
+            "internal transaction data too short: {}",
+            input.len()
+        ));
+    };
+    let arbos_version = ctx.arbos_version();
+    let current_time = ctx.block().timestamp().saturating_to::<u64>();
+
+    match <[u8; 4]>::try_from(selector).expect("selector is four bytes") {
+        ArbitrumInternalTx::START_BLOCK_METHOD => {
+            let call = ArbosActs::startBlockCall::abi_decode(&input)
+                .map_err(|e| format!("invalid StartBlock instruction: {e}"))?;
+            let time_passed = if arbos_version.supports(ArbosFeature::StartBlockTimePassed) {
+                call.timePassed
+            } else {
+                call.l2BlockNumber
+            };
+            let mut l1_block_number = call.l1BlockNumber;
+            if !arbos_version.supports(ArbosFeature::StartBlockL1BlockNumber) {
+                l1_block_number += 1;
+            }
+
+            let old_l1_block_number = ctx
+                .system_arb_state(false)
+                .blockhashes()
+                .l1_block_number()
+                .get()
+                .map_err(String::from)?;
+            if l1_block_number > old_l1_block_number {
+                let previous = l1_block_number - 1;
+                let block_hash = ctx.block_hash(previous).unwrap_or_default();
+                ctx.system_arb_state(false)
+                    .blockhashes()
+                    .record_new_l1_block(previous, block_hash, arbos_version)
+                    .map_err(String::from)?;
+            }
+
+            let mut arb_state = ctx.system_arb_state(false);
+            let mut retryable_state = arb_state.retryable_state();
+            for _ in 0..2 {
+                retryable_state
+                    .try_to_reap_one_retryable(current_time)
+                    .map_err(|e| format!("failed to reap retryable: {e}"))?;
+            }
+            arb_state
+                .l2_pricing()
+                .update_pricing_model(time_passed)
+                .map_err(String::from)?;
+            arb_state
+                .upgrade_arbos_version_if_necessary(current_time)
+                .map_err(String::from)
+        }
+        ArbitrumInternalTx::BATCH_POSTING_REPORT_METHOD => {
+            let call = ArbosActs::batchPostingReportCall::abi_decode(&input)
+                .map_err(|e| format!("invalid BatchPostingReport instruction: {e}"))?;
+            report_batch_posting(
+                ctx,
+                call.batchTimestamp,
+                call.batchPosterAddress,
+                call.batchDataGas,
+                call.l1BaseFeeWei,
+            );
+            Ok(())
+        }
+        ArbitrumInternalTx::BATCH_POSTING_REPORT_V2_METHOD => {
+            let call = ArbosActs::batchPostingReportV2Call::abi_decode(&input)
+                .map_err(|e| format!("invalid BatchPostingReportV2 instruction: {e}"))?;
+            let gas_floor_per_token = ctx
+                .system_arb_state(false)
+                .l1_pricing()
+                .gas_floor_per_token()
+                .get()
+                .map_err(String::from)?;
+            let batch_data_gas = batch_calldata_gas(
+                call.batchCallDataLength,
+                call.batchCallDataNonZeros,
+                gas_floor_per_token,
+            )
+            .saturating_add(call.batchExtraGas);
+            report_batch_posting(
+                ctx,
+                call.batchTimestamp,
+                call.batchPosterAddress,
+                batch_data_gas,
+                call.l1BaseFeeWei,
+            );
+            Ok(())
+        }
+        selector => Err(format!(
+            "unknown internal transaction method: {}",
+            Bytes::copy_from_slice(&selector)
+        )),
+    }
+}
+
+/// Gas the L1 charged for a batch's calldata: the EIP-2028 cost, or the EIP-7623 floor of
+/// `gas_floor_per_token` per token if that is higher.
+fn batch_calldata_gas(length: u64, non_zeros: u64, gas_floor_per_token: u64) -> u64 {
+    let zeros = length.saturating_sub(non_zeros);
+    let standard = zeros
+        .saturating_mul(4)
+        .saturating_add(non_zeros.saturating_mul(16));
+    let tokens = zeros.saturating_add(non_zeros.saturating_mul(4));
+    standard.max(tokens.saturating_mul(gas_floor_per_token))
+}
+
+/// Reimburse a batch poster for posting a batch that cost `batch_data_gas` at `l1_base_fee`.
+/// Like Nitro, a failed update is logged and the report still succeeds.
+fn report_batch_posting<CTX: ArbitrumContextTr>(
+    ctx: &mut CTX,
+    batch_timestamp: U256,
+    batch_poster: Address,
+    batch_data_gas: u64,
+    l1_base_fee: U256,
+) {
+    let arbos_version = ctx.arbos_version();
+    let current_time = ctx.block().timestamp().saturating_to::<u64>();
+    let mut arb_state = ctx.system_arb_state(false);
+    let mut l1_pricing = arb_state.l1_pricing();
+
+    let per_batch_gas = l1_pricing.per_batch_gas_cost().get().unwrap_or_else(|e| {
+        warn!(target: "arbos-revm::handler", %e, "failed to read per batch gas cost");
+        0
+    });
+    let gas_spent = per_batch_gas.saturating_add(batch_data_gas);
+    let wei_spent = l1_base_fee.saturating_mul(U256::from(gas_spent));
+    if let Err(e) = l1_pricing.update_for_batch_poster_spending(
+        batch_timestamp.saturating_to(),
+        current_time,
+        batch_poster,
+        wei_spent,
+        l1_base_fee,
+        arbos_version,
+    ) {
+        warn!(target: "arbos-revm::handler", %e, "L1 pricing update for batch poster spending failed");
+    }
+}
+
+/// Take up to `amount` from `pool`, returning what was taken.
+fn take_funds(pool: &mut U256, amount: U256) -> U256 {
+    let taken = amount.min(*pool);
+    *pool -= taken;
+    taken
+}
+
+fn balance_of<CTX: ArbitrumContextTr>(ctx: &mut CTX, address: Address) -> Result<U256, String> {
+    ctx.journal_mut()
+        .load_account(address)
+        .map(|account| account.data.info.balance)
+        .map_err(|e| format!("failed to load {address}: {e:?}"))
+}
+
+fn transfer<CTX: ArbitrumContextTr>(
+    ctx: &mut CTX,
+    from: Address,
+    to: Address,
+    value: U256,
+) -> Result<(), String> {
+    if value.is_zero() {
+        return Ok(());
+    }
+    match ctx.journal_mut().transfer(from, to, value) {
+        Ok(None) => Ok(()),
+        Ok(Some(error)) => Err(format!("failed to transfer {value} from {from}: {error:?}")),
+        Err(e) => Err(format!("failed to transfer {value} from {from}: {e:?}")),
+    }
+}
+
+fn burn<CTX: ArbitrumContextTr>(ctx: &mut CTX, from: Address, value: U256) -> Result<(), String> {
+    if value.is_zero() {
+        return Ok(());
+    }
+    let mut account = ctx
+        .journal_mut()
+        .load_account_with_code_mut(from)
+        .map_err(|e| format!("failed to load {from}: {e:?}"))?
+        .data;
+    let balance = *account.balance();
+    let Some(balance) = balance.checked_sub(value) else {
+        return Err(format!(
+            "failed to burn {value} from {from}: balance is {balance}"
+        ));
+    };
+    account.set_balance(balance);
+    Ok(())
+}
+
+/// Move a redeem's callvalue out of escrow and mint the gas donated when it was scheduled,
+/// mirroring Nitro's `StartTxHook`. A redeem of a ticket that no longer exists fails validation,
+/// so it is left out of the block like any other invalid transaction.
+fn start_retry_tx<CTX, ERROR>(ctx: &mut CTX, retry_tx: &ArbitrumRetryTx) -> Result<(), ERROR>
+where
+    CTX: ArbitrumContextTr,
+    ERROR: FromStringError + From<ArbitrumTransactionError>,
+{
+    let current_time = ctx.block().timestamp().saturating_to::<u64>();
+    if ctx
+        .system_arb_state(false)
+        .retryable_state()
+        .open_retryable(retry_tx.ticket_id, current_time)
+        .map_err(|err| ERROR::from_string(String::from(err)))?
+        .is_none()
+    {
+        return Err(ArbitrumTransactionError::RetryableNotFound {
+            ticket_id: retry_tx.ticket_id,
+        }
+        .into());
+    }
+
+    let escrow = retryable_escrow_address(retry_tx.ticket_id);
+    transfer(ctx, escrow, retry_tx.from, retry_tx.value).map_err(ERROR::from_string)?;
+
+    let prepaid = U256::from(ctx.block().basefee()).saturating_mul(U256::from(retry_tx.gas));
+    ctx.journal_mut()
+        .balance_incr(retry_tx.from, prepaid)
+        .map_err(|e| ERROR::from_string(format!("failed to mint prepaid gas: {e:?}")))
+}
+
+/// Settle a redeem once it has run, mirroring Nitro's `EndTxHook`. A failed redeem returns its
+/// callvalue to escrow and leaves the ticket for another attempt; a successful one refunds the
+/// submission fee and deletes the ticket, paying what is left in escrow to the beneficiary.
+///
+/// Unused gas is refunded out of the fee accounts that were paid when the redeem was scheduled:
+/// to the refund address up to the redeem's max refund, and the rest to the sender.
+fn end_retry_tx<CTX: ArbitrumContextTr>(
+    ctx: &mut CTX,
+    retry_tx: &ArbitrumRetryTx,
+    success: bool,
+    gas_left: u64,
+) -> Result<(), String> {
+    let effective_base_fee = retry_tx.gas_fee_cap;
+    let gas_used = retry_tx.gas.saturating_sub(gas_left);
+    let network_fee_account = ctx
+        .system_arb_state(false)
+        .network_fee_account()
+        .get()
+        .map_err(String::from)?;
+
+    if !success
+        && let Err(e) = transfer(
+            ctx,
+            retry_tx.from,
+            retryable_escrow_address(retry_tx.ticket_id),
+            retry_tx.value,
+        )
+    {
+        warn!(target: "arbos-revm::handler", %e, ticket_id = %retry_tx.ticket_id, "failed to return callvalue to escrow");
+    }
+
+    // Undo the refund of unused gas to the sender, since it is refunded below instead
+    let gas_refund = effective_base_fee.saturating_mul(U256::from(gas_left));
+    if let Err(e) = burn(ctx, retry_tx.from, gas_refund) {
+        warn!(target: "arbos-revm::handler", %e, "failed to undo gas refund");
+    }
+
+    // Refund out of a fee account, to the refund address while the max refund lasts. As in
+    // Nitro, a refund the fee account can't cover is logged and skipped rather than failing
+    // the block.
+    let refund = |ctx: &mut CTX, max_refund: &mut U256, refund_from: Address, amount: U256| {
+        let to_refund_addr = take_funds(max_refund, amount);
+        for (to, value) in [
+            (retry_tx.refund_to, to_refund_addr),
+            (retry_tx.from, amount - to_refund_addr),
+        ] {
+            if let Err(e) = transfer(ctx, refund_from, to, value) {
+                warn!(target: "arbos-revm::handler", %e, %to, "fee account doesn't have enough funds to give user refund");
+            }
+        }
+    };
+
+    let mut max_refund = retry_tx.max_refund;
+    if success {
+        refund(
+            ctx,
+            &mut max_refund,
+            network_fee_account,
+            retry_tx.submission_fee_refund,
+        );
+    } else {
+        // The submission fee is kept, but still counts against the refund
+        take_funds(&mut max_refund, retry_tx.submission_fee_refund);
+    }
+    // The gas charge is taken from the L1 deposit before anything else is refunded
+    take_funds(
+        &mut max_refund,
+        effective_base_fee.saturating_mul(U256::from(gas_used)),
+    );
+
+    // The infra fee share of the unused gas was paid to the infra fee account, so it comes back
+    // from there, and only the remainder from the network fee account
+    let mut network_refund = gas_refund;
+    if ctx
+        .arbos_version()
+        .supports(ArbosFeature::RetryInfraFeeRefund)
+    {
+        let mut arb_state = ctx.system_arb_state(false);
+        let infra_fee_account = arb_state.infra_fee_account().get().map_err(String::from)?;
+        if !infra_fee_account.is_zero() {
+            let min_base_fee = arb_state
+                .l2_pricing()
+                .min_base_fee_wei()
+                .get()
+                .map_err(String::from)?;
+            let infra_fee = min_base_fee.min(effective_base_fee);
+            let infra_refund = take_funds(
+                &mut network_refund,
+                infra_fee.saturating_mul(U256::from(gas_left)),
+            );
+            refund(ctx, &mut max_refund, infra_fee_account, infra_refund);
+        }
+    }
+    refund(ctx, &mut max_refund, network_fee_account, network_refund);
+
+    if success {
+        ctx.system_arb_state(false)
+            .retryable_state()
+            .delete_retryable(retry_tx.ticket_id)
+            .map_err(String::from)?;
+    }
+    Ok(())
+}
+
+/// Create the retryable ticket described by a submit retryable transaction, mirroring Nitro's
+/// `StartTxHook`. Returns the ticket ID and the gas donated to the auto-redeem, which is zero
+/// when none is scheduled.
+fn submit_retryable<CTX: ArbitrumContextTr>(ctx: &mut CTX) -> Result<(B256, u64), String> {
+    let submit = ArbRetryableTx::submitRetryableCall::abi_decode(ctx.tx().input())
+        .map_err(|e| format!("invalid submit retryable transaction: {e}"))?;
+    let from = ctx.tx().caller();
+    let chain_id = ctx.cfg().chain_id();
+    let tx = ArbitrumSubmitRetryableTx {
+        chain_id,
+        request_id: submit.requestId,
+        from,
+        l1_base_fee: submit.l1BaseFee,
+        deposit_value: submit.deposit,
+        gas_fee_cap: submit.gasFeeCap,
+        gas: submit.gasLimit,
+        retry_to: submit.retryTo,
+        retry_value: submit.callvalue,
+        beneficiary: submit.beneficiary,
+        max_submission_fee: submit.maxSubmissionFee,
+        fee_refund_addr: submit.feeRefundAddress,
+        retry_data: submit.retryData,
+    };
+    let ticket_id = tx.hash();
+    let escrow = retryable_escrow_address(ticket_id);
+    let network_fee_account = ctx
+        .system_arb_state(false)
+        .network_fee_account()
+        .get()
+        .map_err(String::from)?;
+
+    // Mint the deposit from L1, then charge the fees out of it
+    ctx.journal_mut()
+        .balance_incr(from, tx.deposit_value)
+        .map_err(|e| format!("failed to mint deposit: {e:?}"))?;
+
+    let balance = balance_of(ctx, from)?;
+    if balance < tx.max_submission_fee {
+        return Err(format!(
+            "insufficient funds for max submission fee: address {from} have {balance} want {}",
+            tx.max_submission_fee
+        ));
+    }
+    let submission_fee = retryable_submission_fee(tx.retry_data.len(), tx.l1_base_fee);
+    if tx.max_submission_fee < submission_fee {
+        return Err(format!(
+            "max submission fee {} is less than the actual submission fee {submission_fee}",
+            tx.max_submission_fee
+        ));
+    }
+
+    let mut available_refund = tx.deposit_value;
+    take_funds(&mut available_refund, tx.retry_value);
+    transfer(ctx, from, network_fee_account, submission_fee)?;
+    let withheld_submission_fee = take_funds(&mut available_refund, submission_fee);
+    let submission_fee_refund = take_funds(
+        &mut available_refund,
+        tx.max_submission_fee - submission_fee,
+    );
+    transfer(ctx, from, tx.fee_refund_addr, submission_fee_refund)?;
+    transfer(ctx, from, escrow, tx.retry_value)?;
+
+    let current_time = ctx.block().timestamp().saturating_to::<u64>();
+    ctx.system_arb_state(false)
+        .retryable_state()
+        .create_retryable(
+            ticket_id,
+            current_time + RETRYABLE_LIFETIME_SECONDS,
+            from,
+            tx.retry_to,
+            tx.retry_value,
+            tx.beneficiary,
+            &tx.retry_data,
+        )
+        .map_err(String::from)?;
+    let retryable_tx = ctx.cfg().precompile_address(ARB_RETRYABLE_TX_ADDRESS);
+    ctx.journal_mut().log(Log {
+        address: retryable_tx,
+        data: ArbRetryableTx::TicketCreated {
+            ticketId: ticket_id,
+        }
+        .into_log_data(),
+    });
+
+    // Schedule the auto-redeem only if the caller can pay for all of its gas
+    let base_fee = U256::from(ctx.block().basefee());
+    let max_gas_cost = tx.gas_fee_cap.saturating_mul(U256::from(tx.gas));
+    let balance = balance_of(ctx, from)?;
+    if balance < max_gas_cost || tx.gas < intrinsic::TX_GAS || tx.gas_fee_cap < base_fee {
+        let gas_cost_refund = take_funds(&mut available_refund, max_gas_cost);
+        transfer(ctx, from, tx.fee_refund_addr, gas_cost_refund)?;
+        return Ok((ticket_id, 0));
+    }
+
+    let gas_cost = base_fee.saturating_mul(U256::from(tx.gas));
+    transfer(ctx, from, network_fee_account, gas_cost)?;
+    take_funds(&mut available_refund, gas_cost);
+    let gas_price_refund = take_funds(
+        &mut available_refund,
+        (tx.gas_fee_cap - base_fee).saturating_mul(U256::from(tx.gas)),
+    );
+    transfer(ctx, from, tx.fee_refund_addr, gas_price_refund)?;
+
+    let retry_tx = ctx
+        .system_arb_state(false)
+        .retryable_state()
+        .retryable(ticket_id)
+        .make_tx(
+            chain_id,
+            0,
+            base_fee,
+            tx.gas,
+            ticket_id,
+            tx.fee_refund_addr,
+            available_refund,
+            withheld_submission_fee,
+        )
+        .map_err(String::from)?;
+    let retry_tx_hash = retry_tx.hash();
+    ctx.local_mut().schedule_redeem(retry_tx);
+    ctx.journal_mut().log(Log {
+        address: retryable_tx,
+        data: ArbRetryableTx::RedeemScheduled {
+            ticketId: ticket_id,
+            retryTxHash: retry_tx_hash,
+            sequenceNum: 0,
+            donatedGas: tx.gas,
+            gasDonor: tx.fee_refund_addr,
+            maxRefund: available_refund,
+            submissionFeeRefund: withheld_submission_fee,
+        }
+        .into_log_data(),
+    });
+
+    Ok((ticket_id, tx.gas))
+}
@@ -0,0 +1,157 @@
+//! Chain ID and replay protection rules of Arbitrum transaction types.
+//!
+//! Nitro's signer takes the sender of the Arbitrum transaction types from the message that
+//! created them rather than from a signature, so there is nothing to replay: they only have to
+//! carry the chain's ID if they carry one. Signed transactions follow go-ethereum's rules. Typed
+//! transactions must carry the chain's ID, while legacy transactions, including the classic ones
+//! replayed from before Nitro, may omit it and go without EIP-155 replay protection. Nitro
+//! accepts those in blocks at every ArbOS version, and its RPC rejects them unless configured
+//! otherwise, see [`ArbitrumConfigTr::reject_unprotected_txs`].
+//!
+//! Chain IDs are only compared when the config's `tx_chain_id_check` is set.
+//!
+//! Like Nitro's `GasChargingHook`, the gas a transaction executes with is capped by ArbOS's L2
+//! pricing, see [`compute_gas_limit`].
+
+use revm::context::{Cfg, ContextTr, JournalTr, Transaction};
+
+use crate::{
+    ArbitrumContextTr, ArbitrumTransactionError,
+    arbos_version::ArbosFeature,
+    config::ArbitrumConfigTr,
+    constants::{
+        ARBITRUM_CONTRACT_TX_TYPE, ARBITRUM_DEPOSIT_TX_TYPE, ARBITRUM_INTERNAL_TX_TYPE,
+        ARBITRUM_LEGACY_TX_TYPE, ARBITRUM_RETRY_TX_TYPE, ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE,
+        ARBITRUM_UNSIGNED_TX_TYPE,
+    },
+    state::{
+        ArbState, ArbStateGetter,
+        types::{ArbosStateError, StorageBackedTr},
+    },
+};
+
+/// Type of legacy transactions, which predate EIP-2718.
+pub const LEGACY_TX_TYPE: u8 = 0;
+
+/// Whether transactions of type `tx_type` are created by a message instead of being signed.
+pub fn is_unsigned_tx_type(tx_type: u8) -> bool {
+    matches!(
+        tx_type,
+        ARBITRUM_DEPOSIT_TX_TYPE
+            | ARBITRUM_UNSIGNED_TX_TYPE
+            | ARBITRUM_CONTRACT_TX_TYPE
+            | ARBITRUM_RETRY_TX_TYPE
+            | ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE
+            | ARBITRUM_INTERNAL_TX_TYPE
+    )
+}
+
+/// Check the chain ID `tx_chain_id` of a transaction of type `tx_type` against the chain's
+/// `chain_id`, or, when `check_chain_id` is unset, only its replay protection.
+pub fn validate_chain_id(
+    tx_type: u8,
+    tx_chain_id: Option<u64>,
+    chain_id: u64,
+    check_chain_id: bool,
+    reject_unprotected: bool,
+) -> Result<(), ArbitrumTransactionError> {
+    let is_legacy = matches!(tx_type, LEGACY_TX_TYPE | ARBITRUM_LEGACY_TX_TYPE);
+    let Some(tx_chain_id) = tx_chain_id else {
+        return if is_legacy && reject_unprotected {
+            Err(ArbitrumTransactionError::UnprotectedTx)
+        } else if is_legacy || is_unsigned_tx_type(tx_type) || !check_chain_id {
+            Ok(())
+        } else {
+            Err(ArbitrumTransactionError::MissingChainId { tx_type })
+        };
+    };
+
+    if check_chain_id && tx_chain_id != chain_id {
+        return Err(ArbitrumTransactionError::InvalidChainId {
+            tx_type,
+            expected: chain_id,
+            got: tx_chain_id,
+        });
+    }
+    Ok(())
+}
+
+/// Check the chain ID of the transaction in `ctx`, see [`validate_chain_id`].
+pub fn validate_tx_chain_id<CTX: ArbitrumContextTr>(
+    ctx: &CTX,
+) -> Result<(), ArbitrumTransactionError> {
+    let cfg = ctx.cfg();
+    validate_chain_id(
+        ctx.tx().tx_type(),
+        ctx.tx().chain_id(),
+        cfg.chain_id(),
+        cfg.tx_chain_id_check(),
+        cfg.reject_unprotected_txs(),
+    )
+}
+
+/// Most gas the transaction in `ctx` may execute with, or `None` while ArbOS state sets no limit.
+/// Before ArbOS 50 a transaction may use a whole block's gas limit, from then on only the
+/// per-transaction limit. Reading the limit leaves no trace in the journal.
+pub fn compute_gas_limit<CTX: ArbitrumContextTr>(
+    ctx: &mut CTX,
+) -> Result<Option<u64>, ArbosStateError> {
+    let per_tx = ctx.arbos_version().supports(ArbosFeature::PerTxGasLimit);
+    let checkpoint = ctx.journal_mut().checkpoint();
+    let limit = {
+        let mut arb_state = ctx.system_arb_state(true);
+        let mut l2_pricing = arb_state.l2_pricing();
+        if per_tx {
+            l2_pricing.per_tx_gas_limit().get()
+        } else {
+            l2_pricing.per_block_gas_limit().get()
+        }
+    };
+    ctx.journal_mut().checkpoint_revert(checkpoint);
+    Ok(Some(limit?).filter(|limit| *limit != 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EIP1559_TX_TYPE: u8 = 2;
+
+    #[test]
+    fn signed_transactions_need_the_chain_id() {
+        assert!(validate_chain_id(EIP1559_TX_TYPE, Some(42), 42, true, false).is_ok());
+        assert_eq!(
+            validate_chain_id(EIP1559_TX_TYPE, Some(1), 42, true, false),
+            Err(ArbitrumTransactionError::InvalidChainId {
+                tx_type: EIP1559_TX_TYPE,
+                expected: 42,
+                got: 1,
+            })
+        );
+        assert_eq!(
+            validate_chain_id(EIP1559_TX_TYPE, None, 42, true, false),
+            Err(ArbitrumTransactionError::MissingChainId {
+                tx_type: EIP1559_TX_TYPE
+            })
+        );
+        assert!(validate_chain_id(EIP1559_TX_TYPE, Some(1), 42, false, false).is_ok());
+    }
+
+    #[test]
+    fn unprotected_legacy_transactions_follow_the_config() {
+        for tx_type in [LEGACY_TX_TYPE, ARBITRUM_LEGACY_TX_TYPE] {
+            assert!(validate_chain_id(tx_type, None, 42, true, false).is_ok());
+            assert_eq!(
+                validate_chain_id(tx_type, None, 42, true, true),
+                Err(ArbitrumTransactionError::UnprotectedTx)
+            );
+            assert!(validate_chain_id(tx_type, Some(1), 42, true, false).is_err());
+        }
+    }
+
+    #[test]
+    fn unsigned_transactions_skip_replay_protection() {
+        assert!(validate_chain_id(ARBITRUM_RETRY_TX_TYPE, None, 42, true, true).is_ok());
+        assert!(validate_chain_id(ARBITRUM_RETRY_TX_TYPE, Some(1), 42, true, true).is_err());
+    }
+}
@@ -9,20 +9,96 @@ use revm::{
         EthFrame, EvmTr, FrameInitOrResult, FrameResult, ItemOrResult, PrecompileProvider,
         evm::ContextDbError, instructions::InstructionProvider,
     },
-    inspector::{InspectorEvmTr, InspectorHandler, JournalExt, handler::frame_end},
+    inspector::{
+        InspectorEvmTr, InspectorHandler, JournalExt,
+        handler::{frame_end, frame_start},
+    },
     interpreter::interpreter_action::FrameInit,
     state::EvmState,
 };
 
+use arbutil::evm::api::EvmApiMethod;
 use revm::{
     Inspector,
-    interpreter::{InterpreterResult, interpreter::EthInterpreter},
+    inspector::NoOpInspector,
+    interpreter::{InstructionResult, InterpreterResult, interpreter::EthInterpreter},
+    precompile::PrecompileId,
+    primitives::{Address, Bytes, FixedBytes},
 };
 
 use crate::{
-    ArbitrumContextTr, ArbitrumEvm, constants::STYLUS_DISCRIMINANT, handler::ArbitrumHandler,
+    ArbitrumContextTr, ArbitrumEvm, constants::STYLUS_DISCRIMINANT, context::ArbitrumBlockTr,
+    evm::frame_caller, handler::ArbitrumHandler, local_context::ArbitrumLocalContextTr,
 };
 
+/// A host call made by a Stylus program, as reported to [`StylusInspector::hostio`].
+#[derive(Clone, Copy, Debug)]
+pub struct StylusHostio<'a> {
+    /// Address of the program that made the call.
+    pub address: Address,
+    /// The request made.
+    pub method: EvmApiMethod,
+    /// Request payload, encoded as sent by the program.
+    pub input: &'a [u8],
+    /// Response returned to the program.
+    pub output: &'a [u8],
+    /// EVM gas charged to the program for the call.
+    pub gas: u64,
+    /// The gas charged, in ink.
+    pub ink: u64,
+}
+
+/// Hooks into the host calls of Stylus programs, for tracing WASM frames alongside
+/// [`Inspector`].
+///
+/// Storage reads and writes, account queries, logs, calls and creations are all reported once
+/// they return, so the [`Inspector`] hooks of the frames a call ran come first.
+///
+/// Runtimes that trace also capture every hostio a program runs, including those that make no
+/// request such as `msg_value`, and report each as an [`EvmApiMethod::CaptureHostIO`] request
+/// once it returns, see [`crate::tracers::StylusTracer`].
+pub trait StylusInspector<CTX> {
+    /// Called after a Stylus program's host call returns.
+    fn hostio(&mut self, context: &mut CTX, hostio: &StylusHostio<'_>) {
+        let _ = (context, hostio);
+    }
+}
+
+impl<CTX> StylusInspector<CTX> for NoOpInspector {}
+
+/// A call to an Arbitrum precompile, as reported to [`PrecompileInspector::precompile_call`].
+#[derive(Clone, Debug)]
+pub struct PrecompileCall {
+    /// Address the precompile was called at.
+    pub address: Address,
+    /// The precompile called, e.g. `ArbSys`.
+    pub id: PrecompileId,
+    /// First four bytes of the input, zero-padded for shorter inputs.
+    pub selector: FixedBytes<4>,
+    /// Call input, including the selector.
+    pub input: Bytes,
+    /// Returned or reverted data.
+    pub output: Bytes,
+    /// How the call ended.
+    pub result: InstructionResult,
+    /// Gas used by the call.
+    pub gas_used: u64,
+}
+
+/// Hooks into calls to the Arbitrum precompiles, e.g. ArbSys and ArbOwner, which run without an
+/// interpreter.
+///
+/// A call is reported once it returns, between the [`Inspector::call`] and
+/// [`Inspector::call_end`] hooks of its frame.
+pub trait PrecompileInspector<CTX> {
+    /// Called after a call to an Arbitrum precompile returns.
+    fn precompile_call(&mut self, context: &mut CTX, call: &PrecompileCall) {
+        let _ = (context, call);
+    }
+}
+
+impl<CTX> PrecompileInspector<CTX> for NoOpInspector {}
+
 impl<CTX, INSP, P, I> ArbitrumEvm<CTX, INSP, P, I> {
     /// Consumed self and returns a new Evm type with given Inspector.
     pub fn with_inspector<OINSP>(self, inspector: OINSP) -> ArbitrumEvm<CTX, OINSP, P, I> {
@@ -45,7 +121,7 @@ where
     CTX: ArbitrumContextTr<Journal: JournalExt> + ContextSetters,
     I: InstructionProvider<Context = CTX, InterpreterTypes = EthInterpreter>,
     P: PrecompileProvider<CTX, Output = InterpreterResult>,
-    INSP: Inspector<CTX, I::InterpreterTypes>,
+    INSP: Inspector<CTX, I::InterpreterTypes> + StylusInspector<CTX> + PrecompileInspector<CTX>,
 {
     type Inspector = INSP;
 
@@ -57,6 +133,40 @@ where
         (&mut self.0.ctx, &mut self.0.inspector)
     }
 
+    /// Initialize a frame as revm does, reporting a call to an Arbitrum precompile to
+    /// [`PrecompileInspector::precompile_call`] before the frame ends.
+    fn inspect_frame_init(
+        &mut self,
+        mut frame_init: FrameInit,
+    ) -> Result<ItemOrResult<&mut Self::Frame, FrameResult>, ContextDbError<Self::Context>> {
+        let (ctx, inspector) = self.ctx_inspector();
+        if let Some(mut output) = frame_start(ctx, inspector, &mut frame_init.frame_input) {
+            frame_end(ctx, inspector, &frame_init.frame_input, &mut output);
+            // Results of nested frames are passed to `frame_return_result`, which pops the
+            // caller `frame_init` would have pushed
+            if self.0.frame_stack.index().is_some() {
+                let (caller, acting) = frame_caller(&frame_init.frame_input);
+                self.0.ctx.local_mut().push_caller(caller, acting);
+            }
+            return Ok(ItemOrResult::Result(output));
+        }
+        ctx.local_mut().set_precompile_call(None);
+
+        let frame_input = frame_init.frame_input.clone();
+        if let ItemOrResult::Result(mut output) = self.frame_init(frame_init)? {
+            let (ctx, inspector) = self.ctx_inspector();
+            if let Some(call) = ctx.local_mut().take_precompile_call() {
+                inspector.precompile_call(ctx, &call);
+            }
+            frame_end(ctx, inspector, &frame_input, &mut output);
+            return Ok(ItemOrResult::Result(output));
+        }
+
+        let (ctx, inspector, frame) = self.ctx_inspector_frame();
+        inspector.initialize_interp(&mut frame.interpreter, ctx);
+        Ok(ItemOrResult::Item(frame))
+    }
+
     fn ctx_inspector_frame(
         &mut self,
     ) -> (&mut Self::Context, &mut Self::Inspector, &mut Self::Frame) {
@@ -108,6 +218,8 @@ where
                 frame_end(ctx, inspector, &frame.input, frame_result);
                 frame.set_finished(true);
             };
+            // The outcome only describes the frame that just ended.
+            self.0.ctx.local_mut().set_stylus_outcome(None);
             return result;
         }
 
@@ -158,7 +270,7 @@ where
     I: InstructionProvider<Context = CTX, InterpreterTypes = EthInterpreter>,
     P: PrecompileProvider<CTX, Output = InterpreterResult>,
     CTX: ContextSetters,
-    INSP: Inspector<CTX>,
+    INSP: Inspector<CTX> + StylusInspector<CTX> + PrecompileInspector<CTX>,
 {
     /// Run inspection on execution loop.
     ///
@@ -204,8 +316,9 @@ where
 impl<CTX, INSP, INST, PRECOMPILES> InspectEvm
     for ArbitrumEvm<CTX, INSP, PRECOMPILES, INST, EthFrame<EthInterpreter>>
 where
-    CTX: ContextSetters + ArbitrumContextTr<Journal: JournalTr<State = EvmState> + JournalExt>,
-    INSP: Inspector<CTX, EthInterpreter>,
+    CTX: ContextSetters
+        + ArbitrumContextTr<Journal: JournalTr<State = EvmState> + JournalExt, Block: ArbitrumBlockTr>,
+    INSP: Inspector<CTX, EthInterpreter> + StylusInspector<CTX> + PrecompileInspector<CTX>,
     INST: InstructionProvider<Context = CTX, InterpreterTypes = EthInterpreter>,
     PRECOMPILES: PrecompileProvider<CTX, Output = InterpreterResult>,
 {
@@ -217,6 +330,7 @@ where
 
     fn inspect_one_tx(&mut self, tx: Self::Tx) -> Result<Self::ExecutionResult, Self::Error> {
         self.set_tx(tx);
+        self.apply_min_base_fee();
         ArbitrumHandler::default().inspect_run(self)
     }
 }
@@ -225,8 +339,12 @@ impl<CTX, INSP, INST, PRECOMPILES> InspectCommitEvm
     for ArbitrumEvm<CTX, INSP, PRECOMPILES, INST, EthFrame<EthInterpreter>>
 where
     CTX: ContextSetters
-        + ArbitrumContextTr<Journal: JournalTr<State = EvmState> + JournalExt, Db: DatabaseCommit>,
-    INSP: Inspector<CTX, EthInterpreter>,
+        + ArbitrumContextTr<
+            Journal: JournalTr<State = EvmState> + JournalExt,
+            Block: ArbitrumBlockTr,
+            Db: DatabaseCommit,
+        >,
+    INSP: Inspector<CTX, EthInterpreter> + StylusInspector<CTX> + PrecompileInspector<CTX>,
     INST: InstructionProvider<Context = CTX, InterpreterTypes = EthInterpreter>,
     PRECOMPILES: PrecompileProvider<CTX, Output = InterpreterResult>,
 {
@@ -0,0 +1,126 @@
+//! Arbitrum's changes to the Ethereum instruction set.
+//!
+//! Once ArbOS state is initialized, NUMBER and BLOCKHASH answer for L1 blocks as in Nitro:
+//! NUMBER is the L1 block number ArbOS last recorded at a block start and BLOCKHASH reads the
+//! hashes of the 256 L1 blocks before it from ArbOS state. Without ArbOS state they keep their
+//! Ethereum meaning over L2 blocks, with BLOCKHASH reading the hashes recorded through
+//! [`ArbitrumEvm::record_block_hash`](crate::ArbitrumEvm::record_block_hash).
+//!
+//! COINBASE needs no override: Nitro sets the block's beneficiary to the poster of its batch,
+//! which the block environment carries already.
+
+use revm::{
+    bytecode::opcode,
+    context::JournalTr,
+    handler::instructions::EthInstructions,
+    interpreter::{
+        Instruction, InstructionContext, InstructionResult, gas, interpreter::EthInterpreter,
+        interpreter_types::StackTr,
+    },
+    primitives::{B256, BLOCK_HASH_HISTORY, U256},
+};
+
+use crate::{
+    ArbitrumContextTr,
+    state::{
+        ArbState, ArbStateGetter,
+        block_hashes::l2_block_hash,
+        types::{ArbosStateError, StorageBackedTr},
+    },
+};
+
+/// The Ethereum instruction set with Arbitrum's NUMBER and BLOCKHASH.
+pub fn arbitrum_instructions<CTX: ArbitrumContextTr>() -> EthInstructions<EthInterpreter, CTX> {
+    let mut instructions = EthInstructions::default();
+    instructions.insert_instruction(opcode::NUMBER, Instruction::new(number, gas::BASE));
+    instructions.insert_instruction(
+        opcode::BLOCKHASH,
+        Instruction::new(blockhash, gas::BLOCKHASH),
+    );
+    instructions
+}
+
+/// NUMBER, the L1 block number once ArbOS state is initialized.
+fn number<CTX: ArbitrumContextTr>(context: InstructionContext<'_, CTX, EthInterpreter>) {
+    let number = match l1_block_number(context.host) {
+        Ok(Some(l1_block_number)) => U256::from(l1_block_number),
+        Ok(None) => context.host.block_number(),
+        Err(_) => {
+            context
+                .interpreter
+                .halt(InstructionResult::FatalExternalError);
+            return;
+        }
+    };
+    if !context.interpreter.stack.push(number) {
+        context.interpreter.halt(InstructionResult::StackOverflow);
+    }
+}
+
+/// BLOCKHASH, the hash of one of the 256 blocks before the current one, L1 blocks once ArbOS
+/// state is initialized, and zero for any other block.
+fn blockhash<CTX: ArbitrumContextTr>(context: InstructionContext<'_, CTX, EthInterpreter>) {
+    let Some(([], number)) = context.interpreter.stack.popn_top::<0>() else {
+        context.interpreter.halt(InstructionResult::StackUnderflow);
+        return;
+    };
+    let requested = *number;
+
+    let hash = match l1_block_number(context.host) {
+        Ok(Some(l1_block_number)) => match in_window(requested, U256::from(l1_block_number)) {
+            Some(requested) => read_arbos_state(context.host, |context| {
+                context
+                    .system_arb_state(true)
+                    .blockhashes()
+                    .block_hash(requested)
+            })
+            .ok(),
+            None => Some(B256::ZERO),
+        },
+        Ok(None) => match in_window(requested, context.host.block_number()) {
+            Some(requested) => l2_block_hash(context.host, requested),
+            None => Some(B256::ZERO),
+        },
+        Err(_) => None,
+    };
+
+    match hash {
+        Some(hash) => *number = U256::from_be_bytes(hash.0),
+        None => context
+            .interpreter
+            .halt(InstructionResult::FatalExternalError),
+    }
+}
+
+/// `requested` if it is one of the [`BLOCK_HASH_HISTORY`] blocks before `current`.
+fn in_window(requested: U256, current: U256) -> Option<u64> {
+    current
+        .checked_sub(requested)
+        .is_some_and(|age| age > U256::ZERO && age <= U256::from(BLOCK_HASH_HISTORY))
+        .then(|| requested.saturating_to())
+}
+
+/// The L1 block number recorded in ArbOS state, or `None` while the state is uninitialized.
+fn l1_block_number<CTX: ArbitrumContextTr>(
+    context: &mut CTX,
+) -> Result<Option<u64>, ArbosStateError> {
+    read_arbos_state(context, |context| {
+        let mut arb_state = context.system_arb_state(true);
+        if arb_state.arbos_version().get()? == 0 {
+            return Ok(None);
+        }
+        arb_state.blockhashes().l1_block_number().get().map(Some)
+    })
+}
+
+/// Read ArbOS state without leaving a trace in the journal, as Nitro reads it from outside the
+/// access list.
+fn read_arbos_state<CTX: ArbitrumContextTr, T>(
+    context: &mut CTX,
+    read: impl FnOnce(&mut CTX) -> Result<T, ArbosStateError>,
+) -> Result<T, ArbosStateError> {
+    let checkpoint = context.journal_mut().checkpoint();
+    let result = read(context);
+    context.journal_mut().checkpoint_revert(checkpoint);
+    result
+}
@@ -1,9 +1,16 @@
 //! L1 Fee calculation utilities for Arbitrum
 //!
 //! This module provides utility functions for calculating L1 data fees.
-//! The L1 fee represents the cost of posting transaction data to L1.
+//! The L1 fee represents the cost of posting transaction data to L1, priced by its size once
+//! compressed, see [`units_for_calldata`].
 
-use revm::primitives::{Bytes, U256};
+use alloy_rlp::{Encodable, RlpEncodable};
+use revm::{
+    context::TxEnv,
+    primitives::{Address, Bytes, TxKind, U256, keccak256},
+};
+
+pub use crate::state::l1_pricing::units_for_calldata;
 
 /// Gas cost per non-zero byte of calldata (EIP-2028)
 pub const TX_DATA_NON_ZERO_GAS: u64 = 16;
@@ -11,14 +18,28 @@ pub const TX_DATA_NON_ZERO_GAS: u64 = 16;
 /// Gas cost per zero byte of calldata
 pub const TX_DATA_ZERO_GAS: u64 = 4;
 
+/// L1 data units added to a transaction estimated without its signed envelope, as in Nitro.
+pub const ESTIMATION_PADDING_UNITS: u64 = 16 * TX_DATA_NON_ZERO_GAS;
+
+/// Basis points the padded L1 data units of an estimated transaction are raised by.
+pub const ESTIMATION_PADDING_BASIS_POINTS: u64 = 100;
+
+/// Basis points of the L1 data fee a gas estimate covers, so the estimate holds if the L1 price
+/// rises by up to 10% before the transaction is posted.
+pub const GAS_ESTIMATION_L1_PRICE_PADDING_BIPS: u64 = 11_000;
+
+const ONE_IN_BIPS: u64 = 10_000;
+
+/// EIP-1559 transaction type, which Nitro envelopes estimated transactions as.
+const DYNAMIC_FEE_TX_TYPE: u8 = 0x02;
+
 /// Calculate the data gas cost for transaction bytes.
 ///
 /// This counts 16 gas per non-zero byte and 4 gas per zero byte,
 /// following EIP-2028 pricing.
 ///
-/// Note: In production Arbitrum, the transaction would be compressed
-/// with Brotli first, and this calculation would be done on the
-/// compressed bytes.
+/// Note: this prices the bytes uncompressed. L1 data fees are charged for the
+/// transaction compressed with brotli, see [`units_for_calldata`].
 pub fn data_gas(data: &Bytes) -> u64 {
     let mut gas: u64 = 0;
     for &byte in data.iter() {
@@ -35,23 +56,28 @@ pub fn data_gas(data: &Bytes) -> u64 {
 ///
 /// Formula:
 /// ```text
-/// data_gas = sum(16 for each non-zero byte, 4 for each zero byte)
-/// l1_cost = data_gas * l1_base_fee
+/// units = 16 * len(brotli(enveloped_tx, brotli_compression_level))
+/// l1_cost = units * l1_base_fee
 /// ```
 ///
 /// # Arguments
 /// * `enveloped_tx` - The enveloped transaction bytes
 /// * `l1_base_fee` - The L1 base fee (price per unit) from ArbOS state
+/// * `brotli_compression_level` - The brotli compression level from ArbOS state
 ///
 /// # Returns
 /// The L1 cost in wei
-pub fn calculate_tx_l1_cost(enveloped_tx: &Bytes, l1_base_fee: U256) -> U256 {
+pub fn calculate_tx_l1_cost(
+    enveloped_tx: &Bytes,
+    l1_base_fee: U256,
+    brotli_compression_level: u64,
+) -> U256 {
     if l1_base_fee.is_zero() {
         return U256::ZERO;
     }
 
-    let gas = data_gas(enveloped_tx);
-    U256::from(gas).saturating_mul(l1_base_fee)
+    let units = units_for_calldata(enveloped_tx, brotli_compression_level);
+    U256::from(units).saturating_mul(l1_base_fee)
 }
 
 /// Calculate the poster gas (L1 gas converted to L2 gas units).
@@ -77,6 +103,88 @@ pub fn calculate_poster_gas(l1_cost: U256, l2_base_fee: U256) -> u64 {
     poster_gas.try_into().unwrap_or(u64::MAX)
 }
 
+/// Stand-in for the signed envelope of a transaction being estimated, filled with Nitro's
+/// made-up nonce, fees and signature so that it posts about as many bytes as the real one.
+#[derive(RlpEncodable)]
+struct EstimationTx {
+    chain_id: u64,
+    nonce: u64,
+    gas_tip_cap: U256,
+    gas_fee_cap: U256,
+    gas: u64,
+    to: Bytes,
+    value: U256,
+    data: Bytes,
+    access_list: Vec<Address>,
+    v: U256,
+    r: U256,
+    s: U256,
+}
+
+/// Envelope Nitro prices the L1 data of `tx` by when estimating it on the chain with
+/// `chain_id`, as the signed transaction is not known yet.
+pub fn estimation_envelope(tx: &TxEnv, chain_id: u64) -> Bytes {
+    let seed = |name: &str| keccak256(name.as_bytes());
+    let estimation_tx = EstimationTx {
+        chain_id,
+        nonce: u64::from_be_bytes(seed("Nonce")[..8].try_into().unwrap()),
+        gas_tip_cap: U256::from_be_slice(&seed("GasTipCap")[..4]),
+        gas_fee_cap: U256::from_be_slice(&seed("GasFeeCap")[..4]),
+        gas: u32::from_be_bytes(seed("Gas")[..4].try_into().unwrap()).into(),
+        to: match tx.kind {
+            TxKind::Call(to) => Bytes::copy_from_slice(to.as_slice()),
+            TxKind::Create => Bytes::new(),
+        },
+        value: tx.value,
+        data: tx.data.clone(),
+        access_list: Vec::new(),
+        v: U256::from(chain_id).saturating_mul(U256::from(3)),
+        r: U256::from_be_bytes(seed("R").0),
+        s: U256::from_be_bytes(seed("S").0),
+    };
+
+    let mut buf = vec![DYNAMIC_FEE_TX_TYPE];
+    estimation_tx.encode(&mut buf);
+    buf.into()
+}
+
+/// Calculate the L1 data cost of a transaction being estimated from its
+/// [`estimation_envelope`], padded as in Nitro for the fields the envelope makes up.
+///
+/// Formula:
+/// ```text
+/// units = 16 * len(brotli(envelope, brotli_compression_level))
+/// units = (units + ESTIMATION_PADDING_UNITS) * (1 + ESTIMATION_PADDING_BASIS_POINTS / 10000)
+/// l1_cost = units * l1_base_fee
+/// ```
+pub fn calculate_estimated_tx_l1_cost(
+    envelope: &Bytes,
+    l1_base_fee: U256,
+    brotli_compression_level: u64,
+) -> U256 {
+    let units = units_for_calldata(envelope, brotli_compression_level)
+        .saturating_add(ESTIMATION_PADDING_UNITS);
+    let units = U256::from(units) * U256::from(ONE_IN_BIPS + ESTIMATION_PADDING_BASIS_POINTS)
+        / U256::from(ONE_IN_BIPS);
+    units.saturating_mul(l1_base_fee)
+}
+
+/// Calculate the L2 gas a gas estimate sets aside for an L1 cost of `l1_cost`.
+///
+/// As in Nitro, the cost is padded by [`GAS_ESTIMATION_L1_PRICE_PADDING_BIPS`] and converted at
+/// 7/8 of the L2 base fee, but no less than `min_base_fee`, in case of congestion. A higher base
+/// fee at inclusion leaves the transaction with gas to spare.
+pub fn calculate_estimated_poster_gas(l1_cost: U256, l2_base_fee: U256, min_base_fee: U256) -> u64 {
+    let gas_price = (l2_base_fee * U256::from(7) / U256::from(8)).max(min_base_fee);
+    if gas_price.is_zero() {
+        return 0;
+    }
+
+    let padded_cost = l1_cost.saturating_mul(U256::from(GAS_ESTIMATION_L1_PRICE_PADDING_BIPS))
+        / U256::from(ONE_IN_BIPS);
+    (padded_cost / gas_price).try_into().unwrap_or(u64::MAX)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,14 +206,39 @@ mod tests {
 
     #[test]
     fn test_l1_cost_calculation() {
-        // 10 non-zero bytes = 160 gas * 1000 = 160,000 wei
+        // Ten 0x01 bytes compress to 14 bytes at level 1: 16 units per byte * 1000 wei
         let data = Bytes::from(vec![1u8; 10]);
         assert_eq!(
-            calculate_tx_l1_cost(&data, U256::from(1000)),
+            calculate_tx_l1_cost(&data, U256::from(1000), 1),
+            U256::from(224_000)
+        );
+
+        // and to 10 bytes at level 11
+        assert_eq!(
+            calculate_tx_l1_cost(&data, U256::from(1000), 11),
             U256::from(160_000)
         );
     }
 
+    #[test]
+    fn test_units_for_calldata_are_compressed() {
+        // Repetitive calldata compresses to far fewer bytes than it has: 1000 bytes of 0xab
+        // compress to 20 bytes at level 1 and 11 bytes at level 11
+        let data = vec![0xab; 1000];
+        assert_eq!(units_for_calldata(&data, 1), 20 * TX_DATA_NON_ZERO_GAS);
+        assert_eq!(units_for_calldata(&data, 11), 11 * TX_DATA_NON_ZERO_GAS);
+
+        // Cached units are the same
+        assert_eq!(units_for_calldata(&data, 1), 20 * TX_DATA_NON_ZERO_GAS);
+
+        // Levels above the highest compress at it
+        assert_eq!(units_for_calldata(&data, 12), 11 * TX_DATA_NON_ZERO_GAS);
+
+        // Random-looking calldata does not compress
+        let data = keccak256(b"calldata").to_vec();
+        assert!(units_for_calldata(&data, 1) >= 32 * TX_DATA_NON_ZERO_GAS);
+    }
+
     #[test]
     fn test_poster_gas_calculation() {
         // L1 cost = 160,000 wei, L2 base fee = 1000 wei
@@ -120,10 +253,47 @@ mod tests {
         assert_eq!(poster_gas, 161);
     }
 
+    #[test]
+    fn test_estimated_l1_cost_is_padded() {
+        // 224 units for ten 0x01 bytes at level 1, padded by 256 units and then 1%:
+        // (224 + 256) * 1.01 = 484.8, rounded down
+        let data = Bytes::from(vec![1u8; 10]);
+        assert_eq!(
+            calculate_estimated_tx_l1_cost(&data, U256::from(1000), 1),
+            U256::from(484_000)
+        );
+    }
+
+    #[test]
+    fn test_estimated_poster_gas_is_padded() {
+        // 160,000 wei padded by 10% = 176,000 wei, at 7/8 of 1000 wei = 875 wei per gas
+        let poster_gas =
+            calculate_estimated_poster_gas(U256::from(160_000), U256::from(1000), U256::ZERO);
+        assert_eq!(poster_gas, 201);
+
+        // The minimum base fee bounds the congestion discount
+        let poster_gas =
+            calculate_estimated_poster_gas(U256::from(160_000), U256::from(1000), U256::from(1000));
+        assert_eq!(poster_gas, 176);
+    }
+
+    #[test]
+    fn test_estimation_envelope_prices_calldata() {
+        let tx = TxEnv {
+            kind: TxKind::Call(Address::repeat_byte(0x11)),
+            data: Bytes::from(vec![1u8; 100]),
+            ..Default::default()
+        };
+        let empty = estimation_envelope(&TxEnv::default(), 42161);
+        let envelope = estimation_envelope(&tx, 42161);
+        assert_eq!(envelope[0], DYNAMIC_FEE_TX_TYPE);
+        assert!(data_gas(&envelope) > data_gas(&empty) + 100 * TX_DATA_NON_ZERO_GAS);
+    }
+
     #[test]
     fn test_zero_base_fee() {
         let data = Bytes::from(vec![1u8; 10]);
-        assert_eq!(calculate_tx_l1_cost(&data, U256::ZERO), U256::ZERO);
+        assert_eq!(calculate_tx_l1_cost(&data, U256::ZERO, 1), U256::ZERO);
         assert_eq!(calculate_poster_gas(U256::from(1000), U256::ZERO), 0);
     }
 }
@@ -5,33 +5,47 @@
 mod buffer;
 
 // pub mod api;
+pub mod aliasing;
+pub mod arbos_version;
+pub mod block;
 pub mod config;
 pub mod constants;
 pub mod context;
 pub mod evm;
+#[cfg(feature = "fork")]
+pub mod fork;
 pub mod handler;
 pub mod inspector;
+pub mod instructions;
 pub mod l1_fee;
 pub mod local_context;
 pub mod macros;
+pub mod multi_gas;
+pub mod overrides;
 pub mod precompiles;
+pub mod program_cache;
 pub mod result;
 pub mod state;
 pub mod stylus_api;
 pub mod stylus_executor;
+pub mod testing;
+pub mod tracers;
 pub mod transaction;
 pub mod utils;
 
 pub use evm::ArbitrumEvm;
-pub use result::ArbitrumHaltReason;
+// Named by the exported precompile macros, for crates without their own dependency on it
+#[doc(hidden)]
+pub use alloy_sol_types;
+pub use result::{ArbitrumExecutionDetails, ArbitrumHaltReason};
 
 //pub use precompiles::ArbitrumPrecompiles;
 //pub use spec::*;
 pub use context::{ArbitrumContext, ArbitrumContextTr};
 use revm::primitives::hex;
 pub use transaction::{
-    ArbitrumDepositTx, ArbitrumInternalTx, ArbitrumTransaction, ArbitrumTransactionError,
-    ArbitrumTxTr, ArbitrumTypedTransaction,
+    ArbitrumDepositTx, ArbitrumInternalTx, ArbitrumRetryTx, ArbitrumSubmitRetryableTx,
+    ArbitrumTransaction, ArbitrumTransactionError, ArbitrumTxTr, ArbitrumTypedTransaction,
 };
 
 pub trait Utf8OrHex {
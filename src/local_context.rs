@@ -1,16 +1,174 @@
-use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, VecDeque},
+    rc::Rc,
+    time::Duration,
+};
 
+use arbutil::evm::api::EvmApiMethod;
 use revm::{
     context::LocalContextTr,
-    primitives::{B256, U256},
+    interpreter::InstructionResult,
+    primitives::{Address, B256, Bytes, FixedBytes, U256},
+};
+
+use crate::{
+    arbos_version::ArbosVersion,
+    inspector::PrecompileCall,
+    program_cache::{ProgramCache, ProgramCacheStats},
+    result::{ArbitrumExecutionDetails, ArbitrumHaltReason},
+    state::block_hashes::RecentBlockHashes,
+    stylus_api::StylusStorageCache,
+    stylus_executor::StylusOutcome,
+    transaction::ArbitrumRetryTx,
 };
 
+/// Gas used by precompile calls, keyed by precompile address and the first four bytes of the
+/// call's input, zero-padded for shorter inputs.
+pub type PrecompileGasUsage = BTreeMap<(Address, FixedBytes<4>), u64>;
+
+/// Position of the journal in the current transaction: the number of times it was rewound, then
+/// its length. It grows with every journal entry and every revert, so it only repeats while the
+/// journal is unchanged.
+pub type JournalGeneration = (u64, usize);
+
+/// Outcome of a pure or view precompile call that left no trace in the journal, so an identical
+/// call against the same journal would do exactly the same.
+#[derive(Clone, Debug)]
+pub struct CachedPrecompileResult {
+    /// Journal generation when the call was made. The result only applies while it is unchanged.
+    pub generation: JournalGeneration,
+    pub result: InstructionResult,
+    pub output: Bytes,
+    pub gas_used: u64,
+}
+
+/// A chain-owner action applied through ArbOwner, recorded so governance changes can be audited
+/// without decoding `OwnerActs` logs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnerAction {
+    /// L2 block the action was applied in.
+    pub block_number: u64,
+    /// The chain owner that made the call.
+    pub owner: Address,
+    /// Selector of the ArbOwner method called.
+    pub method: FixedBytes<4>,
+    /// ABI-encoded arguments of the call.
+    pub params: Bytes,
+}
+
+/// A Stylus program activated by a transaction, recorded so deployment pipelines can confirm
+/// activation without decoding `ProgramActivated` logs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActivatedProgram {
+    pub code_hash: B256,
+    /// Account the program was activated through.
+    pub program: Address,
+    /// Stylus version the program was activated under.
+    pub version: u16,
+    /// Data fee paid for the activation, in wei.
+    pub data_fee: U256,
+    /// Whether the program was activated implicitly when called, rather than through ArbWasm.
+    /// Auto-activation is not written to state and charges no data fee.
+    pub auto_activated: bool,
+}
+
+/// Stylus programs called in the current block, like Nitro's `RecentWasms`. A program called
+/// again while still among the most recent ones is charged the cached init gas instead of the
+/// full init gas, as if cached through ArbWasmCache.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecentWasms {
+    /// Code hashes, least recently called first.
+    entries: VecDeque<B256>,
+    /// Most code hashes held, fixed by the first insert of the block as Nitro sizes its LRU.
+    capacity: Option<usize>,
+    /// Calls charged the full init gas, the first in the block or after eviction.
+    first_uses: u64,
+    /// Calls discounted to the cached init gas.
+    discounted: u64,
+}
+
+impl RecentWasms {
+    /// Record a call of the program with `code_hash`, keeping the `block_cache_size` most
+    /// recent programs, and return whether it was among them. A size of zero keeps one, as in
+    /// Nitro.
+    pub fn insert(&mut self, code_hash: B256, block_cache_size: u16) -> bool {
+        let capacity = *self
+            .capacity
+            .get_or_insert(usize::from(block_cache_size).max(1));
+
+        if let Some(pos) = self
+            .entries
+            .iter()
+            .position(|existing| *existing == code_hash)
+        {
+            // Move the entry to the back to track recency
+            self.entries.remove(pos);
+            self.entries.push_back(code_hash);
+            self.discounted += 1;
+            return true;
+        }
+
+        if self.entries.len() == capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(code_hash);
+        self.first_uses += 1;
+        false
+    }
+
+    pub fn contains(&self, code_hash: &B256) -> bool {
+        self.entries.contains(code_hash)
+    }
+
+    /// Code hashes held, least recently called first.
+    pub fn iter(&self) -> impl Iterator<Item = &B256> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Calls in the block charged the full init gas.
+    pub fn first_uses(&self) -> u64 {
+        self.first_uses
+    }
+
+    /// Calls in the block discounted to the cached init gas.
+    pub fn discounted(&self) -> u64 {
+        self.discounted
+    }
+
+    /// Forget every program and count, as a new block starts.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
 pub trait ArbitrumLocalContextTr: LocalContextTr {
     fn stylus_pages_ever(&self) -> u16;
     fn stylus_pages_open(&self) -> u16;
     fn add_stylus_pages_open(&mut self, pages: u16);
     fn set_stylus_pages_open(&mut self, pages: u16);
-    fn insert_recent_wasm(&mut self, code_hash: B256, retain: u16) -> bool;
+    /// Record a call of the program with `code_hash` in the block cache of `block_cache_size`
+    /// programs, returning whether the call is discounted. See [`RecentWasms`].
+    fn insert_recent_wasm(&mut self, code_hash: B256, block_cache_size: u16) -> bool;
+    /// Stylus programs called in the current block.
+    fn recent_wasms(&self) -> &RecentWasms;
+    /// Empty the block cache, as a new block starts.
+    fn clear_recent_wasms(&mut self);
+    /// Restore the block cache, once speculative execution is discarded.
+    fn set_recent_wasms(&mut self, recent_wasms: RecentWasms);
+
+    /// Hashes of the most recent L2 blocks, kept across blocks.
+    fn recent_block_hashes(&self) -> &RecentBlockHashes;
+    /// Record the hash of the sealed L2 block `number`.
+    fn record_block_hash(&mut self, number: u64, hash: B256);
 
     /// Get the cached L1 transaction cost (set during validation, used during reward)
     fn tx_l1_cost(&self) -> Option<U256>;
@@ -20,6 +178,132 @@ pub trait ArbitrumLocalContextTr: LocalContextTr {
     fn poster_gas(&self) -> Option<u64>;
     /// Set the poster gas
     fn set_poster_gas(&mut self, gas: Option<u64>);
+
+    /// Number of frames currently entered, including the one being initialized.
+    fn call_depth(&self) -> usize;
+    /// Record entry into a frame invoked by `caller`, acting as `acting` unless it is a delegate
+    /// call, callcode or creation.
+    fn push_caller(&mut self, caller: Address, acting: Option<Address>);
+    /// Record exit from the innermost frame.
+    fn pop_caller(&mut self);
+    /// Whether more than one executing frame acts as `address`, which is how Nitro tells a
+    /// Stylus program it was reentered.
+    fn is_reentrant(&self, address: Address) -> bool;
+    /// Caller of the frame at `depth` (1 is the top-level frame), if that frame is live.
+    fn caller_at_depth(&self, depth: usize) -> Option<Address>;
+
+    /// ArbOS version read from state for the current transaction, if any.
+    fn arbos_version(&self) -> Option<ArbosVersion>;
+    /// Set the ArbOS version read from state.
+    fn set_arbos_version(&mut self, version: Option<ArbosVersion>);
+
+    /// Storage cache of the innermost executing Stylus frame.
+    fn stylus_storage_cache(&mut self) -> Option<&mut StylusStorageCache>;
+    /// Open a storage cache for a Stylus frame that is about to execute.
+    fn push_stylus_storage_cache(&mut self, cache: StylusStorageCache);
+    /// Close the storage cache of the innermost Stylus frame.
+    fn pop_stylus_storage_cache(&mut self) -> Option<StylusStorageCache>;
+    /// Number of Stylus programs currently executing, one per open storage cache.
+    fn stylus_call_depth(&self) -> usize;
+
+    /// Record the EVM gas charged for a Stylus hostio while inspecting.
+    fn record_hostio_gas(&mut self, method: EvmApiMethod, gas: u64);
+    /// Take the hostio gas recorded since the last call.
+    fn take_hostio_gas(&mut self) -> Vec<(EvmApiMethod, u64)>;
+
+    /// Outcome of the Stylus program that returned last, while its frame is being ended.
+    fn stylus_outcome(&self) -> Option<&StylusOutcome>;
+    /// Set the outcome of the Stylus program that just returned.
+    fn set_stylus_outcome(&mut self, outcome: Option<StylusOutcome>);
+    /// Note that a Stylus program requested a hostio this EVM does not serve.
+    fn set_hostio_unavailable(&mut self);
+    /// Take whether a hostio requested since this was last taken was unavailable.
+    fn take_hostio_unavailable(&mut self) -> bool;
+
+    /// Set the Arbitrum precompile call made by the frame being initialized.
+    fn set_precompile_call(&mut self, call: Option<PrecompileCall>);
+    /// Take the Arbitrum precompile call made by the frame being initialized, once it returns.
+    fn take_precompile_call(&mut self) -> Option<PrecompileCall>;
+
+    /// Cache of compiled Stylus programs, kept across transactions.
+    fn program_cache(&self) -> &ProgramCache;
+    /// Replace the compiled program cache, e.g. with one shared by a pool of EVM instances.
+    fn set_program_cache(&mut self, cache: ProgramCache);
+
+    /// Record a redeem scheduled by the current transaction.
+    fn schedule_redeem(&mut self, retry_tx: ArbitrumRetryTx);
+    /// Take the redeems scheduled by the current transaction.
+    fn take_scheduled_redeems(&mut self) -> Vec<ArbitrumRetryTx>;
+    /// Queue redeems scheduled by a completed transaction, to be executed after it.
+    fn queue_redeems(&mut self, retry_txs: Vec<ArbitrumRetryTx>);
+    /// Take the redeems queued by completed transactions, oldest first.
+    fn take_queued_redeems(&mut self) -> Vec<ArbitrumRetryTx>;
+
+    /// Add the gas used by a call to the precompile at `address`.
+    fn record_precompile_gas(&mut self, address: Address, selector: FixedBytes<4>, gas: u64);
+    /// Gas used by precompile calls in the current transaction, or the last one once it ends.
+    fn precompile_gas(&self) -> &PrecompileGasUsage;
+    /// Forget the recorded precompile gas, as a new transaction starts.
+    fn clear_precompile_gas(&mut self);
+
+    /// Result cached for the precompile call identified by `key`.
+    fn precompile_result(&self, key: &B256) -> Option<&CachedPrecompileResult>;
+    /// Cache the result of the precompile call identified by `key`.
+    fn cache_precompile_result(&mut self, key: B256, result: CachedPrecompileResult);
+    /// Drop the cached precompile results.
+    fn clear_precompile_results(&mut self);
+    /// Number of times the journal was rewound in the current transaction.
+    fn journal_reverts(&self) -> u64;
+    /// Record that the journal was rewound, which no cached precompile result survives.
+    fn record_journal_revert(&mut self);
+
+    /// Record an owner action applied by the current transaction.
+    fn record_owner_action(&mut self, action: OwnerAction);
+    /// Take the owner actions recorded by the current transaction.
+    fn take_recorded_owner_actions(&mut self) -> Vec<OwnerAction>;
+    /// Append owner actions that completed transactions applied to the run's log.
+    fn log_owner_actions(&mut self, actions: Vec<OwnerAction>);
+    /// Owner actions applied by completed transactions, oldest first.
+    fn owner_actions(&self) -> &[OwnerAction];
+    /// Take the owner actions applied by completed transactions, emptying the log.
+    fn take_owner_actions(&mut self) -> Vec<OwnerAction>;
+
+    /// Record a program activated by the current transaction. A program auto-activated again is
+    /// recorded once.
+    fn record_activation(&mut self, activation: ActivatedProgram);
+    /// Take the activations recorded by the current transaction.
+    fn take_recorded_activations(&mut self) -> Vec<ActivatedProgram>;
+    /// Set the programs activated by the last transaction, once it ends.
+    fn set_activated_programs(&mut self, activations: Vec<ActivatedProgram>);
+    /// Programs activated by the last transaction.
+    fn activated_programs(&self) -> &[ActivatedProgram];
+
+    /// Record a Stylus program run, charged `init_gas` to start and using `ink_used`, in the
+    /// execution details and the program cache statistics.
+    fn record_stylus_run(&mut self, init_gas: u64, ink_used: u64);
+    /// Add the L1 data fee and precompile gas of the current transaction to its execution
+    /// details, once it ends.
+    fn seal_execution_details(&mut self);
+    /// Gas breakdown of the current transaction, or the last one once it ends.
+    fn execution_details(&self) -> &ArbitrumExecutionDetails;
+    /// Forget the execution details, as a new transaction starts.
+    fn clear_execution_details(&mut self);
+    /// Record the failure of the Stylus program the transaction called in its execution
+    /// details.
+    fn set_stylus_failure(&mut self, failure: ArbitrumHaltReason);
+    /// Record the gas held back from the transaction's execution above ArbOS's gas limit in
+    /// its execution details.
+    fn set_compute_hold_gas(&mut self, gas: u64);
+    /// Record the transaction's failure against ArbOS's gas limit in its execution details.
+    fn set_gas_limit_failure(&mut self, failure: ArbitrumHaltReason);
+
+    /// Record a Stylus call served from the program cache, or compiled in `compile_time` when
+    /// `cached` is false.
+    fn record_program_load(&mut self, cached: bool, compile_time: Duration);
+    /// Program cache statistics of the transactions executed since they were last taken.
+    fn program_cache_stats(&self) -> &ProgramCacheStats;
+    /// Take the program cache statistics, starting afresh.
+    fn take_program_cache_stats(&mut self) -> ProgramCacheStats;
 }
 
 /// Local context that is filled by execution.
@@ -33,12 +317,55 @@ pub struct ArbitrumLocalContext {
     pub stylus_pages_ever: u16,
     /// Stylus pages currently open.
     pub stylus_pages_open: u16,
-    /// Recently invoked Stylus wasm code hashes (block-local LRU).
-    pub recent_wasms: VecDeque<B256>,
+    /// Stylus programs called in the current block, kept across its transactions.
+    pub recent_wasms: RecentWasms,
+    /// Hashes of the most recent L2 blocks, kept across blocks.
+    pub recent_block_hashes: RecentBlockHashes,
     /// Cached L1 transaction cost (set during validation, cleared after execution)
     pub tx_l1_cost: Option<U256>,
     /// Cached poster gas (L1 cost converted to L2 gas units)
     pub poster_gas: Option<u64>,
+    /// Callers of the frames currently executing, outermost first, each with the account it
+    /// acts as unless it is a delegate call, callcode or creation.
+    pub call_stack: Vec<(Address, Option<Address>)>,
+    /// Number of executing frames acting as each account, as counted in `call_stack`.
+    pub acting_frames: HashMap<Address, usize>,
+    /// Storage caches of the Stylus frames currently executing, outermost first.
+    pub stylus_storage_caches: Vec<StylusStorageCache>,
+    /// ArbOS version read from state at the start of the transaction.
+    pub arbos_version: Option<ArbosVersion>,
+    /// EVM gas charged for Stylus hostios and not yet taken by a tracer.
+    pub hostio_gas: Vec<(EvmApiMethod, u64)>,
+    /// Outcome of the Stylus program whose frame is being ended.
+    pub stylus_outcome: Option<StylusOutcome>,
+    /// Whether a Stylus program requested a hostio this EVM does not serve.
+    pub hostio_unavailable: bool,
+    /// Arbitrum precompile call made by the frame being initialized.
+    pub precompile_call: Option<PrecompileCall>,
+    /// Compiled Stylus program cache, the process-wide one by default.
+    pub program_cache: ProgramCache,
+    /// Redeems scheduled by the current transaction.
+    pub scheduled_redeems: Vec<ArbitrumRetryTx>,
+    /// Redeems scheduled by completed transactions and not yet taken, kept across transactions.
+    pub queued_redeems: Vec<ArbitrumRetryTx>,
+    /// Gas used by precompile calls, kept after the transaction ends until the next one starts.
+    pub precompile_gas: PrecompileGasUsage,
+    /// Results of pure and view precompile calls in the current transaction.
+    pub precompile_results: HashMap<B256, CachedPrecompileResult>,
+    /// Number of times the journal was rewound in the current transaction.
+    pub journal_reverts: u64,
+    /// Owner actions applied by the current transaction.
+    pub recorded_owner_actions: Vec<OwnerAction>,
+    /// Owner actions applied by completed transactions, kept across transactions.
+    pub owner_actions: Vec<OwnerAction>,
+    /// Programs activated by the current transaction.
+    pub recorded_activations: Vec<ActivatedProgram>,
+    /// Programs activated by the last transaction, kept until the next one starts.
+    pub activated_programs: Vec<ActivatedProgram>,
+    /// Gas breakdown of the current transaction, kept after it ends until the next one starts.
+    pub execution_details: ArbitrumExecutionDetails,
+    /// Program cache statistics of completed and current transactions, kept across transactions.
+    pub program_cache_stats: ProgramCacheStats,
 }
 
 impl Default for ArbitrumLocalContext {
@@ -48,9 +375,30 @@ impl Default for ArbitrumLocalContext {
             precompile_error_message: None,
             stylus_pages_ever: 0,
             stylus_pages_open: 0,
-            recent_wasms: VecDeque::new(),
+            recent_wasms: RecentWasms::default(),
+            recent_block_hashes: RecentBlockHashes::default(),
             tx_l1_cost: None,
             poster_gas: None,
+            call_stack: Vec::new(),
+            acting_frames: HashMap::new(),
+            stylus_storage_caches: Vec::new(),
+            arbos_version: None,
+            hostio_gas: Vec::new(),
+            stylus_outcome: None,
+            hostio_unavailable: false,
+            precompile_call: None,
+            program_cache: ProgramCache::global(),
+            scheduled_redeems: Vec::new(),
+            queued_redeems: Vec::new(),
+            precompile_gas: PrecompileGasUsage::new(),
+            precompile_results: HashMap::new(),
+            journal_reverts: 0,
+            recorded_owner_actions: Vec::new(),
+            owner_actions: Vec::new(),
+            recorded_activations: Vec::new(),
+            activated_programs: Vec::new(),
+            execution_details: ArbitrumExecutionDetails::default(),
+            program_cache_stats: ProgramCacheStats::default(),
         }
     }
 }
@@ -63,6 +411,20 @@ impl LocalContextTr for ArbitrumLocalContext {
         // Clear L1 fee related fields
         self.tx_l1_cost = None;
         self.poster_gas = None;
+
+        self.call_stack.clear();
+        self.acting_frames.clear();
+        self.stylus_storage_caches.clear();
+        self.arbos_version = None;
+        self.hostio_gas.clear();
+        self.stylus_outcome = None;
+        self.hostio_unavailable = false;
+        self.precompile_call = None;
+        self.scheduled_redeems.clear();
+        self.precompile_results.clear();
+        self.journal_reverts = 0;
+        self.recorded_owner_actions.clear();
+        self.recorded_activations.clear();
     }
 
     fn shared_memory_buffer(&self) -> &Rc<RefCell<Vec<u8>>> {
@@ -92,6 +454,8 @@ impl ArbitrumLocalContextTr for ArbitrumLocalContext {
         if self.stylus_pages_open > self.stylus_pages_ever {
             self.stylus_pages_ever = self.stylus_pages_open;
         }
+        let details = &mut self.execution_details;
+        details.pages_allocated = details.pages_allocated.max(self.stylus_pages_open);
     }
 
     fn set_stylus_pages_open(&mut self, pages: u16) {
@@ -101,29 +465,28 @@ impl ArbitrumLocalContextTr for ArbitrumLocalContext {
         }
     }
 
-    fn insert_recent_wasm(&mut self, code_hash: B256, retain: u16) -> bool {
-        if let Some(pos) = self
-            .recent_wasms
-            .iter()
-            .position(|existing| *existing == code_hash)
-        {
-            // Move existing entry to the back to track recency.
-            if pos + 1 != self.recent_wasms.len()
-                && let Some(found) = self.recent_wasms.remove(pos)
-            {
-                self.recent_wasms.push_back(found);
-            }
-            return true;
-        }
+    fn insert_recent_wasm(&mut self, code_hash: B256, block_cache_size: u16) -> bool {
+        self.recent_wasms.insert(code_hash, block_cache_size)
+    }
 
-        self.recent_wasms.push_back(code_hash);
+    fn recent_wasms(&self) -> &RecentWasms {
+        &self.recent_wasms
+    }
 
-        let retain = retain as usize;
-        if retain > 0 && self.recent_wasms.len() > retain {
-            self.recent_wasms.pop_front();
-        }
+    fn clear_recent_wasms(&mut self) {
+        self.recent_wasms.clear();
+    }
 
-        false
+    fn set_recent_wasms(&mut self, recent_wasms: RecentWasms) {
+        self.recent_wasms = recent_wasms;
+    }
+
+    fn recent_block_hashes(&self) -> &RecentBlockHashes {
+        &self.recent_block_hashes
+    }
+
+    fn record_block_hash(&mut self, number: u64, hash: B256) {
+        self.recent_block_hashes.record(number, hash);
     }
 
     fn tx_l1_cost(&self) -> Option<U256> {
@@ -141,6 +504,249 @@ impl ArbitrumLocalContextTr for ArbitrumLocalContext {
     fn set_poster_gas(&mut self, gas: Option<u64>) {
         self.poster_gas = gas;
     }
+
+    fn call_depth(&self) -> usize {
+        self.call_stack.len()
+    }
+
+    fn push_caller(&mut self, caller: Address, acting: Option<Address>) {
+        self.call_stack.push((caller, acting));
+        if let Some(acting) = acting {
+            *self.acting_frames.entry(acting).or_default() += 1;
+        }
+    }
+
+    fn pop_caller(&mut self) {
+        if let Some((_, Some(acting))) = self.call_stack.pop()
+            && let Some(count) = self.acting_frames.get_mut(&acting)
+        {
+            *count -= 1;
+            if *count == 0 {
+                self.acting_frames.remove(&acting);
+            }
+        }
+    }
+
+    fn is_reentrant(&self, address: Address) -> bool {
+        self.acting_frames
+            .get(&address)
+            .is_some_and(|&count| count > 1)
+    }
+
+    fn caller_at_depth(&self, depth: usize) -> Option<Address> {
+        depth
+            .checked_sub(1)
+            .and_then(|index| self.call_stack.get(index))
+            .map(|&(caller, _)| caller)
+    }
+
+    fn arbos_version(&self) -> Option<ArbosVersion> {
+        self.arbos_version
+    }
+
+    fn set_arbos_version(&mut self, version: Option<ArbosVersion>) {
+        self.arbos_version = version;
+    }
+
+    fn stylus_storage_cache(&mut self) -> Option<&mut StylusStorageCache> {
+        self.stylus_storage_caches.last_mut()
+    }
+
+    fn push_stylus_storage_cache(&mut self, cache: StylusStorageCache) {
+        self.stylus_storage_caches.push(cache);
+    }
+
+    fn pop_stylus_storage_cache(&mut self) -> Option<StylusStorageCache> {
+        self.stylus_storage_caches.pop()
+    }
+
+    fn stylus_call_depth(&self) -> usize {
+        self.stylus_storage_caches.len()
+    }
+
+    fn record_hostio_gas(&mut self, method: EvmApiMethod, gas: u64) {
+        self.hostio_gas.push((method, gas));
+    }
+
+    fn take_hostio_gas(&mut self) -> Vec<(EvmApiMethod, u64)> {
+        std::mem::take(&mut self.hostio_gas)
+    }
+
+    fn stylus_outcome(&self) -> Option<&StylusOutcome> {
+        self.stylus_outcome.as_ref()
+    }
+
+    fn set_stylus_outcome(&mut self, outcome: Option<StylusOutcome>) {
+        self.stylus_outcome = outcome;
+    }
+
+    fn set_hostio_unavailable(&mut self) {
+        self.hostio_unavailable = true;
+    }
+
+    fn take_hostio_unavailable(&mut self) -> bool {
+        std::mem::take(&mut self.hostio_unavailable)
+    }
+
+    fn set_precompile_call(&mut self, call: Option<PrecompileCall>) {
+        self.precompile_call = call;
+    }
+
+    fn take_precompile_call(&mut self) -> Option<PrecompileCall> {
+        self.precompile_call.take()
+    }
+
+    fn program_cache(&self) -> &ProgramCache {
+        &self.program_cache
+    }
+
+    fn set_program_cache(&mut self, cache: ProgramCache) {
+        self.program_cache = cache;
+    }
+
+    fn schedule_redeem(&mut self, retry_tx: ArbitrumRetryTx) {
+        self.scheduled_redeems.push(retry_tx);
+    }
+
+    fn take_scheduled_redeems(&mut self) -> Vec<ArbitrumRetryTx> {
+        std::mem::take(&mut self.scheduled_redeems)
+    }
+
+    fn queue_redeems(&mut self, retry_txs: Vec<ArbitrumRetryTx>) {
+        self.queued_redeems.extend(retry_txs);
+    }
+
+    fn take_queued_redeems(&mut self) -> Vec<ArbitrumRetryTx> {
+        std::mem::take(&mut self.queued_redeems)
+    }
+
+    fn record_precompile_gas(&mut self, address: Address, selector: FixedBytes<4>, gas: u64) {
+        let used = self.precompile_gas.entry((address, selector)).or_default();
+        *used = used.saturating_add(gas);
+    }
+
+    fn precompile_gas(&self) -> &PrecompileGasUsage {
+        &self.precompile_gas
+    }
+
+    fn clear_precompile_gas(&mut self) {
+        self.precompile_gas.clear();
+    }
+
+    fn precompile_result(&self, key: &B256) -> Option<&CachedPrecompileResult> {
+        self.precompile_results.get(key)
+    }
+
+    fn cache_precompile_result(&mut self, key: B256, result: CachedPrecompileResult) {
+        self.precompile_results.insert(key, result);
+    }
+
+    fn clear_precompile_results(&mut self) {
+        self.precompile_results.clear();
+    }
+
+    fn journal_reverts(&self) -> u64 {
+        self.journal_reverts
+    }
+
+    fn record_journal_revert(&mut self) {
+        self.journal_reverts += 1;
+    }
+
+    fn record_owner_action(&mut self, action: OwnerAction) {
+        self.recorded_owner_actions.push(action);
+    }
+
+    fn take_recorded_owner_actions(&mut self) -> Vec<OwnerAction> {
+        std::mem::take(&mut self.recorded_owner_actions)
+    }
+
+    fn log_owner_actions(&mut self, actions: Vec<OwnerAction>) {
+        self.owner_actions.extend(actions);
+    }
+
+    fn owner_actions(&self) -> &[OwnerAction] {
+        &self.owner_actions
+    }
+
+    fn take_owner_actions(&mut self) -> Vec<OwnerAction> {
+        std::mem::take(&mut self.owner_actions)
+    }
+
+    fn record_activation(&mut self, activation: ActivatedProgram) {
+        let repeated = activation.auto_activated
+            && self.recorded_activations.iter().any(|recorded| {
+                recorded.auto_activated && recorded.code_hash == activation.code_hash
+            });
+        if !repeated {
+            self.recorded_activations.push(activation);
+        }
+    }
+
+    fn take_recorded_activations(&mut self) -> Vec<ActivatedProgram> {
+        std::mem::take(&mut self.recorded_activations)
+    }
+
+    fn set_activated_programs(&mut self, activations: Vec<ActivatedProgram>) {
+        self.activated_programs = activations;
+    }
+
+    fn activated_programs(&self) -> &[ActivatedProgram] {
+        &self.activated_programs
+    }
+
+    fn record_stylus_run(&mut self, init_gas: u64, ink_used: u64) {
+        let details = &mut self.execution_details;
+        details.stylus_init_gas = details.stylus_init_gas.saturating_add(init_gas);
+        details.ink_used = details.ink_used.saturating_add(ink_used);
+        let stats = &mut self.program_cache_stats;
+        stats.ink_used = stats.ink_used.saturating_add(ink_used);
+    }
+
+    fn seal_execution_details(&mut self) {
+        let details = &mut self.execution_details;
+        details.l1_data_fee = self.tx_l1_cost.unwrap_or_default();
+        details.poster_gas = self.poster_gas.unwrap_or_default();
+        details.precompile_gas = self.precompile_gas.clone();
+    }
+
+    fn execution_details(&self) -> &ArbitrumExecutionDetails {
+        &self.execution_details
+    }
+
+    fn clear_execution_details(&mut self) {
+        self.execution_details = ArbitrumExecutionDetails::default();
+    }
+
+    fn set_stylus_failure(&mut self, failure: ArbitrumHaltReason) {
+        self.execution_details.stylus_failure = Some(failure);
+    }
+
+    fn set_compute_hold_gas(&mut self, gas: u64) {
+        self.execution_details.compute_hold_gas = gas;
+    }
+
+    fn set_gas_limit_failure(&mut self, failure: ArbitrumHaltReason) {
+        self.execution_details.gas_limit_failure = Some(failure);
+    }
+
+    fn record_program_load(&mut self, cached: bool, compile_time: Duration) {
+        let stats = &mut self.program_cache_stats;
+        if cached {
+            stats.cached_calls += 1;
+        } else {
+            stats.cold_calls += 1;
+            stats.compile_time += compile_time;
+        }
+    }
+
+    fn program_cache_stats(&self) -> &ProgramCacheStats {
+        &self.program_cache_stats
+    }
+
+    fn take_program_cache_stats(&mut self) -> ProgramCacheStats {
+        std::mem::take(&mut self.program_cache_stats)
+    }
 }
 
 impl ArbitrumLocalContext {
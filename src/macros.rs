@@ -3,16 +3,17 @@ use revm::{
     primitives::Bytes,
 };
 
+// Named by `emit_event!`, for crates without their own dependency on revm
+#[doc(hidden)]
+pub use revm::interpreter::gas::log_cost;
+
 pub(crate) const OUT_OF_GAS_MESSAGE: &[u8] = b"out of gas";
 
-pub(crate) fn interpreter_result_return(gas: &mut Gas) -> InterpreterResult {
+pub fn interpreter_result_return(gas: &mut Gas) -> InterpreterResult {
     interpreter_result_return_with_output(gas, Bytes::default())
 }
 
-pub(crate) fn interpreter_result_return_with_output(
-    gas: &mut Gas,
-    output: Bytes,
-) -> InterpreterResult {
+pub fn interpreter_result_return_with_output(gas: &mut Gas, output: Bytes) -> InterpreterResult {
     InterpreterResult {
         result: InstructionResult::Return,
         gas: *gas,
@@ -20,14 +21,11 @@ pub(crate) fn interpreter_result_return_with_output(
     }
 }
 
-pub(crate) fn interpreter_result_revert(gas: &mut Gas) -> InterpreterResult {
+pub fn interpreter_result_revert(gas: &mut Gas) -> InterpreterResult {
     interpreter_result_revert_with_output(gas, Bytes::default())
 }
 
-pub(crate) fn interpreter_result_revert_with_output(
-    gas: &mut Gas,
-    output: Bytes,
-) -> InterpreterResult {
+pub fn interpreter_result_revert_with_output(gas: &mut Gas, output: Bytes) -> InterpreterResult {
     InterpreterResult {
         result: InstructionResult::Revert,
         gas: *gas,
@@ -35,7 +33,7 @@ pub(crate) fn interpreter_result_revert_with_output(
     }
 }
 
-pub(crate) fn interpreter_result_revert_out_of_gas(gas: &mut Gas) -> InterpreterResult {
+pub fn interpreter_result_revert_out_of_gas(gas: &mut Gas) -> InterpreterResult {
     gas.spend_all();
     InterpreterResult {
         result: InstructionResult::OutOfGas,
@@ -44,7 +42,7 @@ pub(crate) fn interpreter_result_revert_out_of_gas(gas: &mut Gas) -> Interpreter
     }
 }
 
-pub(crate) fn record_cost_return(gas: &mut Gas, cost: u64) -> Option<InterpreterResult> {
+pub fn record_cost_return(gas: &mut Gas, cost: u64) -> Option<InterpreterResult> {
     if !gas.record_cost(cost) {
         Some(interpreter_result_revert_out_of_gas(gas))
     } else {
@@ -105,14 +103,31 @@ macro_rules! interpreter_revert {
 
 pub(crate) use interpreter_revert;
 
+/// Fails the enclosing precompile the way Nitro fails a method returning a non-Solidity error:
+/// reverting without data, and before ArbOS 11 consuming all gas as well.
+macro_rules! interpreter_error {
+    ($context:expr, $gas:expr) => {{
+        if !$context
+            .arbos_version()
+            .supports($crate::arbos_version::ArbosFeature::PrecompileErrorsRevert)
+        {
+            $gas.spend_all();
+        }
+        return Some($crate::macros::interpreter_result_revert(&mut $gas));
+    }};
+}
+
+pub(crate) use interpreter_error;
+
+/// Charges the log cost against `$gas` and appends `$log` to the journal, returning out-of-gas
+/// from the enclosing precompile if the charge fails.
+#[macro_export]
 macro_rules! emit_event {
     ($context:expr, $log:expr, $gas:expr) => {
-        let log_cost = revm::interpreter::gas::log_cost(
-            $log.data.topics().len() as u8,
-            $log.data.data.len() as u64,
-        );
+        let log_cost =
+            $crate::macros::log_cost($log.data.topics().len() as u8, $log.data.data.len() as u64);
         if let Some(log_cost) = log_cost {
-            $crate::macros::try_record_cost!(&mut $gas, log_cost)
+            $crate::try_record_cost!(&mut $gas, log_cost)
         } else {
             return Some($crate::macros::interpreter_result_revert_out_of_gas(
                 &mut $gas,
@@ -0,0 +1,403 @@
+//! Multi-dimensional gas accounting.
+//!
+//! Nitro prices gas along several resource dimensions rather than as a single number. This
+//! module mirrors those dimensions and provides [`MultiGasTracer`], an inspector that splits
+//! the gas used by a transaction's execution across them, per EVM opcode and per Stylus hostio.
+
+use std::collections::BTreeMap;
+
+use arbutil::evm::api::EvmApiMethod;
+use revm::{
+    Inspector,
+    bytecode::opcode,
+    interpreter::{
+        CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, Interpreter,
+        gas::{CALL_STIPEND, CREATE, LOG, LOGDATA, LOGTOPIC, NEWACCOUNT, SSTORE_SET},
+        interpreter::EthInterpreter,
+        interpreter_types::Jumps,
+    },
+    primitives::Log,
+};
+
+use crate::{
+    ArbitrumContextTr,
+    constants::STYLUS_DISCRIMINANT,
+    inspector::{PrecompileInspector, StylusInspector},
+    local_context::ArbitrumLocalContextTr,
+};
+
+/// Resource dimension a unit of gas is charged for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourceKind {
+    Unknown,
+    Computation,
+    HistoryGrowth,
+    StorageAccess,
+    StorageGrowth,
+    L1Calldata,
+    L2Calldata,
+    WasmComputation,
+}
+
+impl ResourceKind {
+    /// Number of resource kinds.
+    pub const COUNT: usize = 8;
+
+    /// All resource kinds, in declaration order.
+    pub const ALL: [ResourceKind; Self::COUNT] = [
+        ResourceKind::Unknown,
+        ResourceKind::Computation,
+        ResourceKind::HistoryGrowth,
+        ResourceKind::StorageAccess,
+        ResourceKind::StorageGrowth,
+        ResourceKind::L1Calldata,
+        ResourceKind::L2Calldata,
+        ResourceKind::WasmComputation,
+    ];
+}
+
+/// Gas split across [`ResourceKind`]s.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MultiGas([u64; ResourceKind::COUNT]);
+
+impl MultiGas {
+    /// Gas charged entirely to a single resource.
+    pub fn new(kind: ResourceKind, amount: u64) -> Self {
+        let mut gas = Self::default();
+        gas.add(kind, amount);
+        gas
+    }
+
+    /// Gas charged to `kind`.
+    pub fn get(&self, kind: ResourceKind) -> u64 {
+        self.0[kind as usize]
+    }
+
+    /// Charge `amount` more gas to `kind`.
+    pub fn add(&mut self, kind: ResourceKind, amount: u64) {
+        self.0[kind as usize] = self.0[kind as usize].saturating_add(amount);
+    }
+
+    /// Add every dimension of `other` to this one.
+    pub fn add_all(&mut self, other: &MultiGas) {
+        for kind in ResourceKind::ALL {
+            self.add(kind, other.get(kind));
+        }
+    }
+
+    /// Sum over all dimensions.
+    pub fn total(&self) -> u64 {
+        self.0.iter().fold(0, |acc, gas| acc.saturating_add(*gas))
+    }
+
+    /// Non-zero dimensions.
+    pub fn iter(&self) -> impl Iterator<Item = (ResourceKind, u64)> + '_ {
+        ResourceKind::ALL
+            .into_iter()
+            .map(|kind| (kind, self.get(kind)))
+            .filter(|(_, gas)| *gas > 0)
+    }
+}
+
+/// Split the static and dynamic cost of an EVM opcode (excluding gas forwarded to a
+/// sub-call) across resources.
+pub fn opcode_multi_gas(op: u8, cost: u64) -> MultiGas {
+    // Charges of at least `growth` only arise when the opcode grows state.
+    let split = |growth: u64, rest: ResourceKind| {
+        let mut gas = MultiGas::default();
+        if cost >= growth {
+            gas.add(ResourceKind::StorageGrowth, growth);
+            gas.add(rest, cost - growth);
+        } else {
+            gas.add(rest, cost);
+        }
+        gas
+    };
+
+    match op {
+        opcode::SSTORE => split(SSTORE_SET, ResourceKind::StorageAccess),
+        opcode::CALL | opcode::SELFDESTRUCT => split(NEWACCOUNT, ResourceKind::StorageAccess),
+        opcode::CREATE | opcode::CREATE2 => split(CREATE, ResourceKind::Computation),
+        opcode::SLOAD
+        | opcode::BALANCE
+        | opcode::EXTCODESIZE
+        | opcode::EXTCODECOPY
+        | opcode::EXTCODEHASH
+        | opcode::CALLCODE
+        | opcode::DELEGATECALL
+        | opcode::STATICCALL => MultiGas::new(ResourceKind::StorageAccess, cost),
+        opcode::LOG0..=opcode::LOG4 => MultiGas::new(ResourceKind::HistoryGrowth, cost),
+        _ => MultiGas::new(ResourceKind::Computation, cost),
+    }
+}
+
+/// Name of the Stylus hostio that issued `method`.
+pub fn hostio_name(method: EvmApiMethod) -> &'static str {
+    match method {
+        EvmApiMethod::GetBytes32 => "storage_load_bytes32",
+        EvmApiMethod::SetTrieSlots => "storage_flush_cache",
+        EvmApiMethod::GetTransientBytes32 => "transient_load_bytes32",
+        EvmApiMethod::SetTransientBytes32 => "transient_store_bytes32",
+        EvmApiMethod::AccountBalance => "account_balance",
+        EvmApiMethod::AccountCode => "account_code",
+        EvmApiMethod::AccountCodeHash => "account_codehash",
+        EvmApiMethod::AddPages => "pay_for_memory_grow",
+        EvmApiMethod::EmitLog => "emit_log",
+        _ => "other",
+    }
+}
+
+/// Resource a Stylus hostio's EVM cost is charged to.
+pub fn hostio_resource(method: EvmApiMethod) -> ResourceKind {
+    match method {
+        EvmApiMethod::GetBytes32
+        | EvmApiMethod::SetTrieSlots
+        | EvmApiMethod::AccountBalance
+        | EvmApiMethod::AccountCode
+        | EvmApiMethod::AccountCodeHash => ResourceKind::StorageAccess,
+        EvmApiMethod::EmitLog => ResourceKind::HistoryGrowth,
+        _ => ResourceKind::Computation,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FrameKind {
+    /// Precompiles and calls that never start an interpreter.
+    Native,
+    Evm,
+    Stylus,
+}
+
+#[derive(Debug)]
+struct TracedFrame {
+    kind: FrameKind,
+    is_create: bool,
+    gas_limit: u64,
+    /// Gas attributed to opcodes and hostios of this frame.
+    attributed: u64,
+    /// Gas used by sub-calls of this frame.
+    children: u64,
+    /// Opcode currently executing and the gas remaining before it.
+    step: (u8, u64),
+    /// Opcode that may still hand gas to a sub-call, with its full cost.
+    pending: Option<(u8, u64)>,
+}
+
+/// Inspector that attributes gas to Nitro's resource dimensions.
+///
+/// EVM gas is split per opcode by [`opcode_multi_gas`]. Stylus programs report the EVM cost of
+/// their storage, account, memory and log hostios; the rest of a program's gas is charged to
+/// [`ResourceKind::WasmComputation`]. Gas used by precompiles and any gas a frame uses outside
+/// its opcodes (e.g. code deposit on creation) is charged to the frame as a whole. The L1
+/// poster gas of the transaction is reported as [`ResourceKind::L1Calldata`]. Intrinsic gas and
+/// refunds are not included.
+#[derive(Debug, Default)]
+pub struct MultiGasTracer {
+    frames: Vec<TracedFrame>,
+    /// Gas used so far, across all dimensions.
+    pub total: MultiGas,
+    /// Gas used per EVM opcode.
+    pub opcodes: BTreeMap<u8, MultiGas>,
+    /// Gas used per Stylus hostio.
+    pub hostios: BTreeMap<&'static str, MultiGas>,
+}
+
+impl MultiGasTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn charge(&mut self, gas: &MultiGas) {
+        self.total.add_all(gas);
+        if let Some(frame) = self.frames.last_mut() {
+            frame.attributed = frame.attributed.saturating_add(gas.total());
+        }
+    }
+
+    fn charge_opcode(&mut self, op: u8, cost: u64) {
+        let gas = opcode_multi_gas(op, cost);
+        self.opcodes.entry(op).or_default().add_all(&gas);
+        self.charge(&gas);
+    }
+
+    fn charge_hostio(&mut self, name: &'static str, gas: MultiGas) {
+        self.hostios.entry(name).or_default().add_all(&gas);
+        self.charge(&gas);
+    }
+
+    /// Charge the opcode that last ran in the innermost frame, less `forwarded` gas.
+    fn settle_pending(&mut self, forwarded: u64) {
+        if let Some((op, cost)) = self
+            .frames
+            .last_mut()
+            .and_then(|frame| frame.pending.take())
+        {
+            self.charge_opcode(op, cost.saturating_sub(forwarded));
+        }
+    }
+
+    /// Charge hostios recorded by the innermost Stylus frame.
+    fn drain_hostios<CTX: ArbitrumContextTr>(&mut self, context: &mut CTX) {
+        for (method, gas) in context.local_mut().take_hostio_gas() {
+            self.charge_hostio(
+                hostio_name(method),
+                MultiGas::new(hostio_resource(method), gas),
+            );
+        }
+    }
+
+    fn enter<CTX: ArbitrumContextTr>(
+        &mut self,
+        context: &mut CTX,
+        gas_limit: u64,
+        forwarded: u64,
+        is_create: bool,
+    ) {
+        self.settle_pending(forwarded);
+        self.drain_hostios(context);
+        self.frames.push(TracedFrame {
+            kind: FrameKind::Native,
+            is_create,
+            gas_limit,
+            attributed: 0,
+            children: 0,
+            step: (0, 0),
+            pending: None,
+        });
+    }
+
+    fn exit<CTX: ArbitrumContextTr>(&mut self, context: &mut CTX, remaining: u64, success: bool) {
+        self.settle_pending(0);
+        self.drain_hostios(context);
+
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+
+        let used = frame.gas_limit.saturating_sub(remaining);
+        let rest = used
+            .saturating_sub(frame.children)
+            .saturating_sub(frame.attributed);
+        let kind = match frame.kind {
+            FrameKind::Stylus => ResourceKind::WasmComputation,
+            FrameKind::Evm if frame.is_create && success => ResourceKind::StorageGrowth,
+            FrameKind::Evm | FrameKind::Native => ResourceKind::Computation,
+        };
+        self.total.add(kind, rest);
+
+        match self.frames.last_mut() {
+            Some(parent) => parent.children = parent.children.saturating_add(used),
+            None => {
+                if let Some(poster_gas) = context.local().poster_gas() {
+                    self.total.add(ResourceKind::L1Calldata, poster_gas);
+                }
+            }
+        }
+    }
+}
+
+// Hostio gas is drained from the local context as frames are entered and exited instead.
+impl<CTX> StylusInspector<CTX> for MultiGasTracer {}
+
+impl<CTX> PrecompileInspector<CTX> for MultiGasTracer {}
+
+impl<CTX: ArbitrumContextTr> Inspector<CTX, EthInterpreter> for MultiGasTracer {
+    fn initialize_interp(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.kind = if interp.bytecode.bytes().starts_with(STYLUS_DISCRIMINANT) {
+                FrameKind::Stylus
+            } else {
+                FrameKind::Evm
+            };
+        }
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        self.settle_pending(0);
+        if let Some(frame) = self.frames.last_mut() {
+            frame.step = (interp.bytecode.opcode(), interp.gas.remaining());
+        }
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        let (op, gas_before) = frame.step;
+        let cost = gas_before.saturating_sub(interp.gas.remaining());
+
+        match op {
+            opcode::CALL
+            | opcode::CALLCODE
+            | opcode::DELEGATECALL
+            | opcode::STATICCALL
+            | opcode::CREATE
+            | opcode::CREATE2 => frame.pending = Some((op, cost)),
+            _ => self.charge_opcode(op, cost),
+        }
+    }
+
+    fn log(&mut self, _context: &mut CTX, log: Log) {
+        // EVM logs are charged through their opcode.
+        if self
+            .frames
+            .last()
+            .is_some_and(|frame| frame.kind == FrameKind::Stylus)
+        {
+            let cost = LOG
+                + LOGTOPIC * log.data.topics().len() as u64
+                + LOGDATA * log.data.data.len() as u64;
+            self.charge_hostio(
+                hostio_name(EvmApiMethod::EmitLog),
+                MultiGas::new(ResourceKind::HistoryGrowth, cost),
+            );
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        // The value stipend is added to the callee's gas without being charged to the caller.
+        let stipend = if matches!(inputs.scheme, CallScheme::Call | CallScheme::CallCode)
+            && inputs
+                .value
+                .transfer()
+                .is_some_and(|value| !value.is_zero())
+        {
+            CALL_STIPEND
+        } else {
+            0
+        };
+        self.enter(
+            context,
+            inputs.gas_limit,
+            inputs.gas_limit.saturating_sub(stipend),
+            false,
+        );
+        None
+    }
+
+    fn call_end(&mut self, context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.exit(
+            context,
+            outcome.result.gas.remaining(),
+            outcome.result.is_ok(),
+        );
+    }
+
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.enter(context, inputs.gas_limit, inputs.gas_limit, true);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.exit(
+            context,
+            outcome.result.gas.remaining(),
+            outcome.result.is_ok(),
+        );
+    }
+}
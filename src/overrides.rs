@@ -0,0 +1,121 @@
+//! `eth_call`-style state and block overrides.
+//!
+//! RPC servers answering `eth_call` with a `stateOverride` or `blockOverrides` can apply them to
+//! the context before calling `transact_one`, instead of wrapping the database. Overridden state
+//! is written straight into the journal and committed, so the transaction sees it as state from
+//! before it started: it is cold, and storage written by the transaction is priced against the
+//! overridden values.
+
+use std::collections::HashMap;
+
+use revm::{
+    Database,
+    context::{BlockEnv, ContextTr, JournalTr},
+    inspector::JournalExt,
+    primitives::{Address, B256, Bytes, U256},
+    state::{Bytecode, EvmStorageSlot},
+};
+
+/// Overrides of one account's state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountOverride {
+    pub balance: Option<U256>,
+    pub nonce: Option<u64>,
+    pub code: Option<Bytes>,
+    pub storage: Option<StorageOverride>,
+}
+
+/// Overrides of an account's storage.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StorageOverride {
+    /// Replace the whole storage, so that slots not listed read as zero (`state`).
+    Replace(HashMap<U256, U256>),
+    /// Set the listed slots, leaving the rest as they are (`stateDiff`).
+    Diff(HashMap<U256, U256>),
+}
+
+/// Account overrides by address.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Overrides of the block environment.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockOverrides {
+    pub number: Option<U256>,
+    pub timestamp: Option<U256>,
+    pub basefee: Option<u64>,
+    pub gas_limit: Option<u64>,
+    pub beneficiary: Option<Address>,
+    pub difficulty: Option<U256>,
+    pub prevrandao: Option<B256>,
+}
+
+impl BlockOverrides {
+    /// Set the overridden fields of `block`.
+    pub fn apply(&self, block: &mut BlockEnv) {
+        if let Some(number) = self.number {
+            block.number = number;
+        }
+        if let Some(timestamp) = self.timestamp {
+            block.timestamp = timestamp;
+        }
+        if let Some(basefee) = self.basefee {
+            block.basefee = basefee;
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            block.gas_limit = gas_limit;
+        }
+        if let Some(beneficiary) = self.beneficiary {
+            block.beneficiary = beneficiary;
+        }
+        if let Some(difficulty) = self.difficulty {
+            block.difficulty = difficulty;
+        }
+        if let Some(prevrandao) = self.prevrandao {
+            block.prevrandao = Some(prevrandao);
+        }
+    }
+}
+
+/// Write `overrides` into the journal of `ctx` and commit them, ahead of the next transaction.
+pub fn apply_state_overrides<CTX>(
+    ctx: &mut CTX,
+    overrides: &StateOverride,
+) -> Result<(), <CTX::Db as Database>::Error>
+where
+    CTX: ContextTr<Journal: JournalExt>,
+{
+    let journal = ctx.journal_mut();
+    for (address, account_override) in overrides {
+        journal.load_account(*address)?;
+        if let Some(code) = &account_override.code {
+            journal.set_code(*address, Bytecode::new_raw(code.clone()));
+        }
+
+        let account = journal
+            .evm_state_mut()
+            .get_mut(address)
+            .expect("account was just loaded");
+        if let Some(balance) = account_override.balance {
+            account.info.balance = balance;
+        }
+        if let Some(nonce) = account_override.nonce {
+            account.info.nonce = nonce;
+        }
+        let slots = match &account_override.storage {
+            Some(StorageOverride::Replace(slots)) => {
+                // Slots missing from a created account read as zero instead of from the database
+                account.storage.clear();
+                account.mark_created();
+                Some(slots)
+            }
+            Some(StorageOverride::Diff(slots)) => Some(slots),
+            None => None,
+        };
+        for (key, value) in slots.into_iter().flatten() {
+            account.storage.insert(*key, EvmStorageSlot::new(*value, 0));
+        }
+        account.mark_touch();
+    }
+    journal.commit_tx();
+    Ok(())
+}
@@ -1,16 +1,18 @@
-use alloy_sol_types::{SolCall, sol};
+use alloy_sol_types::sol;
 use revm::{
     interpreter::{Gas, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, Bytes, U256, address},
+    primitives::{Address, U256},
 };
 
 use crate::{
-    ArbitrumContextTr, generate_state_mut_table,
-    macros::{interpreter_return, interpreter_revert},
-    precompile_impl,
+    ArbitrumContextTr,
+    constants::ARB_ADDRESS_TABLE_ADDRESS,
+    generate_state_mut_table,
+    macros::interpreter_revert,
+    precompile_dispatch, precompile_impl,
     precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
+        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, errors::revert_message,
     },
     state::{ArbState, ArbStateGetter, try_state},
 };
@@ -86,12 +88,12 @@ interface ArbAddressTable {
 pub fn arb_address_table_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbAddressTable")),
-        address!("0x0000000000000000000000000000000000000066"),
+        ARB_ADDRESS_TABLE_ADDRESS,
         precompile_impl!(ArbAddressTablePrecompile),
     )
 }
 
-struct ArbAddressTablePrecompile;
+pub(super) struct ArbAddressTablePrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAddressTablePrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -117,58 +119,39 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAddressTablePrecompi
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
 
-        let selector = selector_or_revert!(gas, input);
-
-        match selector {
-            ArbAddressTable::addressExistsCall::SELECTOR => {
-                let call = decode_call!(gas, ArbAddressTable::addressExistsCall, input);
-
-                let exists = try_state!(
+        precompile_dispatch!(gas, input, {
+            ArbAddressTable::addressExistsCall => |call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .address_table()
                         .address_exists(call.addr)
-                );
-
-                let output = ArbAddressTable::addressExistsCall::abi_encode_returns(&exists);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAddressTable::compressCall::SELECTOR => {
-                let call = decode_call!(gas, ArbAddressTable::compressCall, input);
-
-                let compressed = try_state!(
+                )
+            },
+            ArbAddressTable::compressCall => |call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .address_table()
                         .compress(call.addr)
-                );
-
-                interpreter_return!(
-                    gas,
-                    Bytes::from(ArbAddressTable::compressCall::abi_encode_returns(
-                        &compressed
-                    ))
-                );
-            }
-            ArbAddressTable::decompressCall::SELECTOR => {
-                let call = decode_call!(gas, ArbAddressTable::decompressCall, input);
-
+                )
+            },
+            ArbAddressTable::decompressCall => |call| {
                 let offset: u64 = if let Ok(offset) = call.offset.try_into() {
                     offset
                 } else {
                     interpreter_revert!(
                         gas,
-                        Bytes::from("invalid offset in ArbAddressTable.Decompress")
+                        revert_message("invalid offset in ArbAddressTable.Decompress")
                     );
                 };
 
                 if offset > call.buf.len() as u64 {
                     interpreter_revert!(
                         gas,
-                        Bytes::from("invalid offset in ArbAddressTable.Decompress")
+                        revert_message("invalid offset in ArbAddressTable.Decompress")
                     );
                 }
 
@@ -181,18 +164,12 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAddressTablePrecompi
                         .address_table()
                         .decompress(data)
                 );
-                let new_offset = offset.saturating_add(consumed);
 
-                let output = ArbAddressTable::decompressCall::abi_encode_returns(
-                    &ArbAddressTable::decompressReturn::from((
-                        decompressed,
-                        U256::from(new_offset),
-                    )),
-                );
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAddressTable::lookupCall::SELECTOR => {
-                let call = decode_call!(gas, ArbAddressTable::lookupCall, input);
+                // Despite the interface's wording, Nitro returns the number of bytes read
+                // rather than the offset after them
+                ArbAddressTable::decompressReturn::from((decompressed, U256::from(consumed)))
+            },
+            ArbAddressTable::lookupCall => |call| {
                 let index = if let Some(index) = try_state!(
                     gas,
                     context
@@ -202,24 +179,24 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAddressTablePrecompi
                 ) {
                     index
                 } else {
-                    interpreter_revert!(gas, Bytes::from("address does not exist in AddressTable"));
+                    interpreter_revert!(
+                        gas,
+                        revert_message("address does not exist in AddressTable")
+                    );
                 };
 
-                let output = ArbAddressTable::lookupCall::abi_encode_returns(&U256::from(index));
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAddressTable::lookupIndexCall::SELECTOR => {
-                let call = decode_call!(gas, ArbAddressTable::lookupIndexCall, input);
-
+                U256::from(index)
+            },
+            ArbAddressTable::lookupIndexCall => |call| {
                 let index = if let Ok(index) = call.index.try_into() {
                     index
                 } else {
                     interpreter_revert!(
                         gas,
-                        Bytes::from("invalid index in ArbAddressTable.LookupIndex")
+                        revert_message("invalid index in ArbAddressTable.LookupIndex")
                     );
                 };
-                let addr = if let Some(addr) = try_state!(
+                if let Some(addr) = try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
@@ -228,14 +205,13 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAddressTablePrecompi
                 ) {
                     addr
                 } else {
-                    interpreter_revert!(gas, Bytes::from("index does not exist in AddressTable"));
-                };
-
-                let output = ArbAddressTable::lookupIndexCall::abi_encode_returns(&addr);
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAddressTable::registerCall::SELECTOR => {
-                let call = decode_call!(gas, ArbAddressTable::registerCall, input);
+                    interpreter_revert!(
+                        gas,
+                        revert_message("index does not exist in AddressTable")
+                    );
+                }
+            },
+            ArbAddressTable::registerCall => |call| {
                 let index = try_state!(
                     gas,
                     context
@@ -244,10 +220,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAddressTablePrecompi
                         .register(call.addr)
                 );
 
-                let output = ArbAddressTable::registerCall::abi_encode_returns(&U256::from(index));
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAddressTable::sizeCall::SELECTOR => {
+                U256::from(index)
+            },
+            ArbAddressTable::sizeCall => |_call| {
                 let size = try_state!(
                     gas,
                     context
@@ -256,10 +231,8 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAddressTablePrecompi
                         .size()
                 );
 
-                let output = ArbAddressTable::sizeCall::abi_encode_returns(&U256::from(size));
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown function selector")),
-        }
+                U256::from(size)
+            },
+        })
     }
 }
@@ -1,18 +1,18 @@
-use alloy_sol_types::{SolCall, sol};
+use alloy_sol_types::sol;
 use revm::{
     interpreter::{Gas, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, Bytes, U256, address},
+    primitives::{Address, U256},
 };
 
 use crate::{
     ArbitrumContextTr,
-    constants::ARBOS_BATCH_POSTER_ADDRESS,
+    constants::{ARB_AGGREGATOR_ADDRESS, ARBOS_BATCH_POSTER_ADDRESS},
     generate_state_mut_table,
-    macros::{interpreter_return, interpreter_revert},
-    precompile_impl,
+    macros::interpreter_revert,
+    precompile_dispatch, precompile_impl,
     precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
+        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, errors::revert_message,
     },
     state::{ArbState, ArbStateGetter, try_state},
 };
@@ -77,12 +77,12 @@ interface ArbAggregator {
 pub fn arb_aggregator_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbAggregator")),
-        address!("0x000000000000000000000000000000000000006d"),
+        ARB_AGGREGATOR_ADDRESS,
         precompile_impl!(ArbAggregatorPrecompile),
     )
 }
 
-struct ArbAggregatorPrecompile;
+pub(super) struct ArbAggregatorPrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAggregatorPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -108,10 +108,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAggregatorPrecompile
         gas_limit: u64,
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
-        let selector = selector_or_revert!(gas, input);
 
-        match selector {
-            ArbAggregator::addBatchPosterCall::SELECTOR => {
+        precompile_dispatch!(gas, input, {
+            ArbAggregator::addBatchPosterCall => |call| {
                 let is_chain_owner = try_state!(
                     gas,
                     context
@@ -120,110 +119,88 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbAggregatorPrecompile
                 );
 
                 if !is_chain_owner {
-                    interpreter_revert!(gas, Bytes::from("must be called by chain owner"));
+                    interpreter_revert!(gas, revert_message("must be called by chain owner"));
                 }
 
-                let call = decode_call!(gas, ArbAggregator::addBatchPosterCall, input);
-
-                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                let mut l1_pricing = arb_state.l1_pricing();
-                let mut batch_poster_table = l1_pricing.batch_poster_table();
-
-                if !try_state!(
+                try_state!(
                     gas,
-                    batch_poster_table.add_if_missing(call.newBatchPoster, call.newBatchPoster)
-                ) {
-                    interpreter_return!(gas, Bytes::default());
-                }
+                    context
+                        .arb_state(Some(&mut gas), is_static)
+                        .l1_pricing()
+                        .batch_poster_table()
+                        .add_if_missing(call.newBatchPoster, call.newBatchPoster)
+                );
 
-                interpreter_return!(gas, Bytes::new());
-            }
-            ArbAggregator::getBatchPostersCall::SELECTOR => {
-                let posters = try_state!(
+                ArbAggregator::addBatchPosterReturn {}
+            },
+            ArbAggregator::getBatchPostersCall => |_call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .l1_pricing()
                         .batch_poster_table()
                         .all()
-                );
-
-                let output = ArbAggregator::getBatchPostersCall::abi_encode_returns(&posters);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAggregator::getDefaultAggregatorCall::SELECTOR => {
-                let output = ArbAggregator::getDefaultAggregatorCall::abi_encode_returns(
-                    &ARBOS_BATCH_POSTER_ADDRESS,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAggregator::getFeeCollectorCall::SELECTOR => {
-                let call = decode_call!(gas, ArbAggregator::getFeeCollectorCall, input);
-
+                )
+            },
+            ArbAggregator::getDefaultAggregatorCall => |_call| {
+                ARBOS_BATCH_POSTER_ADDRESS
+            },
+            ArbAggregator::getFeeCollectorCall => |call| {
                 let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                 let mut l1_pricing = arb_state.l1_pricing();
                 let mut batch_poster_table = l1_pricing.batch_poster_table();
 
-                let fee_collector =
-                    try_state!(gas, batch_poster_table.fee_collector(call.batchPoster));
-
-                let output = ArbAggregator::getFeeCollectorCall::abi_encode_returns(&fee_collector);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAggregator::getPreferredAggregatorCall::SELECTOR => {
-                let output = ArbAggregator::getPreferredAggregatorCall::abi_encode_returns(
-                    &ArbAggregator::getPreferredAggregatorReturn::from((
-                        ARBOS_BATCH_POSTER_ADDRESS,
-                        true,
-                    )),
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAggregator::setFeeCollectorCall::SELECTOR => {
-                let call = decode_call!(gas, ArbAggregator::setFeeCollectorCall, input);
-
+                try_state!(gas, batch_poster_table.fee_collector(call.batchPoster))
+            },
+            ArbAggregator::getPreferredAggregatorCall => |_call| {
+                ArbAggregator::getPreferredAggregatorReturn::from((
+                    ARBOS_BATCH_POSTER_ADDRESS,
+                    true,
+                ))
+            },
+            ArbAggregator::setFeeCollectorCall => |call| {
                 let mut arb_state = context.arb_state(Some(&mut gas), is_static);
 
-                let is_chain_owner = { try_state!(gas, arb_state.is_chain_owner(caller_address)) };
-
-                let mut l1_pricing = arb_state.l1_pricing();
-                let mut batch_poster_table = l1_pricing.batch_poster_table();
-
-                let current_fee_collector =
-                    { try_state!(gas, batch_poster_table.fee_collector(call.batchPoster)) };
+                // The poster must exist before anyone may change where its fees go
+                let current_fee_collector = try_state!(
+                    gas,
+                    arb_state
+                        .l1_pricing()
+                        .batch_poster_table()
+                        .fee_collector(call.batchPoster)
+                );
 
+                // Owners are only looked up when the caller is neither the poster nor its
+                // collector, as Nitro does
                 if caller_address != call.batchPoster
                     && caller_address != current_fee_collector
-                    && !is_chain_owner
+                    && !try_state!(gas, arb_state.is_chain_owner(caller_address))
                 {
                     interpreter_revert!(
                         gas,
-                        Bytes::from(
-                            "only the batch poster, its fee collector, or a chain owner may change its fee collector"
+                        revert_message(
+                            "only a batch poster (or its fee collector / chain owner) may change its fee collector"
                         )
                     );
                 }
 
                 try_state!(
                     gas,
-                    batch_poster_table.set_fee_collector(call.batchPoster, call.newFeeCollector)
+                    arb_state
+                        .l1_pricing()
+                        .batch_poster_table()
+                        .set_fee_collector(call.batchPoster, call.newFeeCollector)
                 );
 
-                interpreter_return!(gas, Bytes::new());
-            }
-            ArbAggregator::getTxBaseFeeCall::SELECTOR => {
-                let output = ArbAggregator::getTxBaseFeeCall::abi_encode_returns(&U256::ZERO);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbAggregator::setTxBaseFeeCall::SELECTOR => {
-                interpreter_return!(gas, Bytes::new());
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Function not implemented")),
-        }
+                ArbAggregator::setFeeCollectorReturn {}
+            },
+            ArbAggregator::getTxBaseFeeCall => |_call| {
+                U256::ZERO
+            },
+            ArbAggregator::setTxBaseFeeCall => |_call| {
+                ArbAggregator::setTxBaseFeeReturn {}
+            },
+        }, unknown => revert_message("Function not implemented"))
     }
 }
@@ -1,18 +1,19 @@
 use crate::{
-    ArbitrumContextTr, generate_state_mut_table,
-    macros::{emit_event, interpreter_return, interpreter_revert},
-    precompile_impl,
-    precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
-    },
+    ArbitrumContextTr,
+    config::ArbitrumConfigTr,
+    constants::ARB_DEBUG_ADDRESS,
+    generate_state_mut_table,
+    macros::{emit_event, interpreter_error, interpreter_revert},
+    precompile_dispatch, precompile_impl,
+    precompiles::{ArbPrecompileLogic, ExtendedPrecompile, StateMutability, errors::custom_error},
     state::{ArbState, ArbStateGetter, try_state},
 };
-use alloy_sol_types::{SolCall, SolError, sol};
+use alloy_sol_types::sol;
 use revm::{
     context::JournalTr,
     interpreter::{Gas, InstructionResult, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, B256, Bytes, Log, U256, address, alloy_primitives::IntoLogData},
+    primitives::{Address, B256, Bytes, Log, U256, alloy_primitives::IntoLogData},
 };
 
 sol! {
@@ -57,12 +58,15 @@ interface ArbDebug {
 pub fn arb_debug_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbDebug")),
-        address!("0x00000000000000000000000000000000000000ff"),
+        ARB_DEBUG_ADDRESS,
         precompile_impl!(ArbDebugPrecompile),
     )
 }
 
-struct ArbDebugPrecompile;
+pub(super) struct ArbDebugPrecompile;
+
+/// The message of the error thrown by `customRevert`, as in Nitro.
+const CUSTOM_REVERT_MESSAGE: &str = r"This spider family wards off bugs: /\oo/\ //\(oo)//\ /\oo/\";
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbDebugPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -79,19 +83,22 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbDebugPrecompile {
     fn inner(
         context: &mut CTX,
         input: &[u8],
-        _target_address: &Address,
+        target_address: &Address,
         caller_address: Address,
-        _call_value: U256,
+        call_value: U256,
         is_static: bool,
         gas_limit: u64,
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
-        let selector = selector_or_revert!(gas, input);
 
-        match selector {
-            ArbDebug::becomeChainOwnerCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbDebug::becomeChainOwnerCall, input);
+        // Outside of debug mode Nitro fails every call, taking all gas
+        if !context.cfg().debug_mode() {
+            gas.spend_all();
+            interpreter_revert!(gas);
+        }
 
+        precompile_dispatch!(gas, input, {
+            ArbDebug::becomeChainOwnerCall => |_call| {
                 try_state!(
                     gas,
                     context
@@ -100,105 +107,85 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbDebugPrecompile {
                         .add(caller_address)
                 );
 
-                interpreter_return!(gas);
-            }
-            ArbDebug::eventsCall::SELECTOR => {
-                let call = decode_call!(gas, ArbDebug::eventsCall, input);
-
-                events(
+                ArbDebug::becomeChainOwnerReturn {}
+            },
+            ArbDebug::eventsCall => |call| {
+                if is_static {
+                    return Some(InterpreterResult {
+                        result: InstructionResult::StateChangeDuringStaticCall,
+                        gas,
+                        output: Bytes::default(),
+                    });
+                }
+
+                if let Some(failure) = events(
                     context,
+                    target_address,
                     caller_address,
-                    is_static,
-                    gas_limit,
+                    &mut gas,
                     call.flag,
-                    B256::from(call.value),
-                );
-
-                interpreter_return!(
-                    gas,
-                    ArbDebug::eventsCall::abi_encode_returns(&ArbDebug::eventsReturn::from((
-                        address!("0x00000000000000000000000000000000000000ff"),
-                        U256::from(gas_limit),
-                    )))
-                );
-            }
-            ArbDebug::eventsViewCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbDebug::eventsViewCall, input);
-
-                events(
-                    context,
-                    caller_address,
-                    is_static,
-                    gas_limit,
-                    true,
-                    B256::ZERO,
-                )
-            }
-            ArbDebug::legacyErrorCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbDebug::legacyErrorCall, input);
-
-                interpreter_revert!(gas, Bytes::from("example legacy error"));
-            }
-            ArbDebug::panicCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbDebug::panicCall, input);
-
+                    call.value,
+                ) {
+                    return Some(failure);
+                }
+
+                ArbDebug::eventsReturn::from((caller_address, call_value))
+            },
+            ArbDebug::eventsViewCall => |_call| {
+                // Nitro runs view methods read-only, so the events it tries to emit fail with
+                // write protection
+                interpreter_error!(context, gas);
+            },
+            ArbDebug::legacyErrorCall => |_call| {
+                interpreter_error!(context, gas);
+            },
+            ArbDebug::panicCall => |_call| {
                 panic!("called ArbDebug's debug-only Panic method");
-            }
-            ArbDebug::customRevertCall::SELECTOR => {
-                let call = decode_call!(gas, ArbDebug::customRevertCall, input);
-
+            },
+            ArbDebug::customRevertCall => |call| {
                 let error =
-                    ArbDebug::Custom::new((call.number, "example custom revert".to_string(), true));
+                    ArbDebug::Custom::new((call.number, CUSTOM_REVERT_MESSAGE.to_string(), true));
 
-                interpreter_revert!(gas, error.abi_encode());
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown function selector")),
-        }
+                interpreter_revert!(gas, custom_error(&error));
+            },
+        })
     }
 }
 
+/// Emits the `Basic` and `Mixed` events, charging their log costs to `gas`. Returns the
+/// precompile's result if it runs out of gas doing so.
 fn events<CTX: ArbitrumContextTr>(
     context: &mut CTX,
+    target_address: &Address,
     caller_address: Address,
-    is_static: bool,
-    gas_limit: u64,
+    gas: &mut Gas,
     flag: bool,
     value: B256,
 ) -> Option<InterpreterResult> {
-    let mut gas = Gas::new(gas_limit);
-
-    if is_static {
-        return Some(InterpreterResult {
-            result: InstructionResult::StateChangeDuringStaticCall,
-            gas,
-            output: Bytes::default(),
-        });
-    }
-
     emit_event!(
         context,
         Log {
-            address: address!("0x00000000000000000000000000000000000000ff"),
+            address: *target_address,
             data: ArbDebug::Basic { flag: !flag, value }.to_log_data(),
         },
-        gas
+        *gas
     );
 
     emit_event!(
         context,
         Log {
-            address: address!("0x00000000000000000000000000000000000000ff"),
+            address: *target_address,
             data: ArbDebug::Mixed {
                 flag,
-                value,
                 not: !flag,
-                conn: address!("0x00000000000000000000000000000000000000aa"),
-                caller: caller_address
+                value,
+                conn: *target_address,
+                caller: caller_address,
             }
             .to_log_data(),
         },
-        gas
+        *gas
     );
 
-    interpreter_return!(gas);
+    None
 }
@@ -1,19 +1,17 @@
 use crate::{
     ArbitrumContextTr,
-    config::ArbitrumConfigTr,
-    constants::ARBOS_L1_PRICER_FUNDS_ADDRESS,
-    generate_state_mut_table,
-    macros::{interpreter_return, interpreter_revert},
-    precompile_impl,
+    arbos_version::ArbosFeature,
+    constants::{ARB_GAS_INFO_ADDRESS, ARBOS_L1_PRICER_FUNDS_ADDRESS},
+    generate_state_mut_table, precompile_dispatch, precompile_impl,
     precompiles::{ArbPrecompileLogic, ExtendedPrecompile, StateMutability},
     state::{ArbState, ArbStateGetter, try_state, types::StorageBackedTr},
 };
-use alloy_sol_types::{SolCall, sol};
+use alloy_sol_types::sol;
 use revm::{
     context::Block,
     interpreter::{Gas, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, Bytes, I256, U256, address},
+    primitives::{Address, I256, U256},
 };
 
 const ARBOS_GAS_INFO_ASSUMED_SIMPLE_TX_SIZE: u64 = 140;
@@ -130,6 +128,14 @@ interface ArbGasInfo {
     /// @notice Returns the L1 pricing surplus as of the last update (may be negative).
     /// Available in ArbOS version 20
     function getLastL1PricingSurplus() external view returns (int256);
+
+    /// @notice Returns the maximum gas a single transaction may use
+    /// Available in ArbOS version 50
+    function getMaxTxGasLimit() external view returns (uint64);
+
+    /// @notice Returns the maximum gas a block may use
+    /// Available in ArbOS version 50
+    function getMaxBlockGasLimit() external view returns (uint64);
 }
 
 }
@@ -137,12 +143,12 @@ interface ArbGasInfo {
 pub fn arb_gas_info_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbGasInfo")),
-        address!("0x000000000000000000000000000000000000006c"),
+        ARB_GAS_INFO_ADDRESS,
         precompile_impl!(ArbGasInfoPrecompile),
     )
 }
 
-struct ArbGasInfoPrecompile;
+pub(super) struct ArbGasInfoPrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -171,6 +177,8 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
             getL1PricingFundsDueForRewardsCall(View),
             getL1PricingUnitsSinceUpdateCall(View),
             getLastL1PricingSurplusCall(View),
+            getMaxTxGasLimitCall(View),
+            getMaxBlockGasLimitCall(View),
         }
     };
 
@@ -184,28 +192,13 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
         gas_limit: u64,
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
-        // decode selector
-        if input.len() < 4 {
-            interpreter_revert!(gas, Bytes::from("Input too short"));
-        }
-
-        // decode selector
-        let selector: [u8; 4] = input[0..4].try_into().unwrap();
-
-        match selector {
-            ArbGasInfo::getAmortizedCostCapBipsCall::SELECTOR => {
-                let amortized_cost_cap_bips = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().amortized_cost_cap_bips().get())
-                };
-
-                let output = ArbGasInfo::getAmortizedCostCapBipsCall::abi_encode_returns(
-                    &amortized_cost_cap_bips,
-                );
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getGasAccountingParamsCall::SELECTOR => {
+        precompile_dispatch!(gas, input, {
+            ArbGasInfo::getAmortizedCostCapBipsCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().amortized_cost_cap_bips().get())
+            },
+            ArbGasInfo::getGasAccountingParamsCall => |_call| {
                 let (speed_limit_per_second, max_tx_gas_limit) = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     let mut l2_pricing = arb_state.l2_pricing();
@@ -217,96 +210,66 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                     (speed_limit_per_second, max_tx_gas_limit)
                 };
 
-                let output = ArbGasInfo::getGasAccountingParamsCall::abi_encode_returns(
-                    &ArbGasInfo::getGasAccountingParamsReturn::from((
-                        U256::from(speed_limit_per_second),
-                        U256::from(max_tx_gas_limit),
-                        U256::from(max_tx_gas_limit),
-                    )),
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getGasBacklogCall::SELECTOR => {
-                let gas_backlog = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l2_pricing().gas_backlog().get())
-                };
-
-                let output = ArbGasInfo::getGasBacklogCall::abi_encode_returns(&gas_backlog);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getL1BaseFeeEstimateCall::SELECTOR => {
-                let l1_base_fee_estimate = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().price_per_unit().get())
-                };
-
-                let output =
-                    ArbGasInfo::getL1BaseFeeEstimateCall::abi_encode_returns(&l1_base_fee_estimate);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getL1BaseFeeEstimateInertiaCall::SELECTOR => {
-                let pricing_inertia = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().inertia().get())
-                };
-
-                let output = ArbGasInfo::getL1BaseFeeEstimateInertiaCall::abi_encode_returns(
-                    &pricing_inertia,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getL1FeesAvailableCall::SELECTOR => {
-                let l1_fees_available = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().l1_fees_available().get())
-                };
-
-                let output =
-                    ArbGasInfo::getL1FeesAvailableCall::abi_encode_returns(&l1_fees_available);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getL1PricingEquilibrationUnitsCall::SELECTOR => {
-                let equilibration_units = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().equilibration_units().get())
-                };
-
-                let output = ArbGasInfo::getL1PricingEquilibrationUnitsCall::abi_encode_returns(
-                    &equilibration_units,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getL1PricingFundsDueForRewardsCall::SELECTOR => {
+                ArbGasInfo::getGasAccountingParamsReturn::from((
+                    U256::from(speed_limit_per_second),
+                    U256::from(max_tx_gas_limit),
+                    U256::from(max_tx_gas_limit),
+                ))
+            },
+            ArbGasInfo::getGasBacklogCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l2_pricing().gas_backlog().get())
+            },
+            ArbGasInfo::getGasBacklogToleranceCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l2_pricing().backlog_tolerance().get())
+            },
+            ArbGasInfo::getMaxTxGasLimitCall => |_call| {
+                // Before ArbOS 50 the per-block limit doubled as the per-transaction one.
+                let per_tx = context
+                    .arbos_version()
+                    .supports(ArbosFeature::PerTxGasLimit);
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                let mut l2_pricing = arb_state.l2_pricing();
+                if per_tx {
+                    try_state!(gas, l2_pricing.per_tx_gas_limit().get())
+                } else {
+                    try_state!(gas, l2_pricing.per_block_gas_limit().get())
+                }
+            },
+            ArbGasInfo::getMaxBlockGasLimitCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l2_pricing().per_block_gas_limit().get())
+            },
+            ArbGasInfo::getL1BaseFeeEstimateCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().price_per_unit().get())
+            },
+            ArbGasInfo::getL1BaseFeeEstimateInertiaCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().inertia().get())
+            },
+            ArbGasInfo::getL1FeesAvailableCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().l1_fees_available().get())
+            },
+            ArbGasInfo::getL1PricingEquilibrationUnitsCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().equilibration_units().get())
+            },
+            ArbGasInfo::getL1PricingFundsDueForRewardsCall => |_call| {
                 let funds_due_for_rewards = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     try_state!(gas, arb_state.l1_pricing().funds_due_for_rewards().get())
                 };
 
-                let output = ArbGasInfo::getL1PricingFundsDueForRewardsCall::abi_encode_returns(
-                    &U256::from(funds_due_for_rewards),
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getL1PricingSurplusCall::SELECTOR => {
-                let l1_pricing_surplus = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().last_surplus().get())
-                };
-
-                let output =
-                    ArbGasInfo::getL1PricingSurplusCall::abi_encode_returns(&l1_pricing_surplus);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getLastL1PricingSurplusCall::SELECTOR => {
+                U256::from(funds_due_for_rewards)
+            },
+            ArbGasInfo::getL1PricingUnitsSinceUpdateCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().units_since_update().get())
+            },
+            ArbGasInfo::getL1PricingSurplusCall => |_call| {
                 let funds_due_for_refund = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     try_state!(
@@ -326,7 +289,10 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
 
                 let need_funds = funds_due_for_refund.wrapping_add(funds_due_for_rewards);
 
-                let have_funds = if context.cfg().arbos_version() < 10 {
+                let have_funds = if !context
+                    .arbos_version()
+                    .supports(ArbosFeature::L1FeesAvailable)
+                {
                     let arb_pricer_funds = context
                         .balance(ARBOS_L1_PRICER_FUNDS_ADDRESS)
                         .unwrap_or_default();
@@ -336,48 +302,29 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                     try_state!(gas, arb_state.l1_pricing().l1_fees_available().get())
                 };
 
-                let surplus = I256::from(have_funds) - need_funds;
-
-                let output = ArbGasInfo::getLastL1PricingSurplusCall::abi_encode_returns(&surplus);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getLastL1PricingUpdateTimeCall::SELECTOR => {
-                let last_update_time = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().last_update_time().get())
-                };
-
-                let output = ArbGasInfo::getLastL1PricingUpdateTimeCall::abi_encode_returns(
-                    &last_update_time,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getMinimumGasPriceCall::SELECTOR => {
-                let minimum_gas_price = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l2_pricing().min_base_fee_wei().get())
-                };
-
-                let output =
-                    ArbGasInfo::getMinimumGasPriceCall::abi_encode_returns(&minimum_gas_price);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getPerBatchGasChargeCall::SELECTOR => {
+                I256::from(have_funds) - need_funds
+            },
+            ArbGasInfo::getLastL1PricingSurplusCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().last_surplus().get())
+            },
+            ArbGasInfo::getLastL1PricingUpdateTimeCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().last_update_time().get())
+            },
+            ArbGasInfo::getMinimumGasPriceCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l2_pricing().min_base_fee_wei().get())
+            },
+            ArbGasInfo::getPerBatchGasChargeCall => |_call| {
                 let per_batch_gas_charge = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     try_state!(gas, arb_state.l1_pricing().per_batch_gas_cost().get())
                 };
 
-                let output = ArbGasInfo::getPerBatchGasChargeCall::abi_encode_returns(
-                    &(per_batch_gas_charge as i64),
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getPricesInArbGasCall::SELECTOR => {
+                per_batch_gas_charge as i64
+            },
+            ArbGasInfo::getPricesInArbGasCall => |_call| {
                 let l1_gas_price = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     try_state!(gas, arb_state.l1_pricing().price_per_unit().get())
@@ -389,7 +336,10 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                     revm::interpreter::gas::NON_ZERO_BYTE_MULTIPLIER_ISTANBUL,
                 ));
 
-                if context.cfg().arbos_version() < 4 {
+                let (gas_per_l2_tx, gas_for_l1_calldata) = if !context
+                    .arbos_version()
+                    .supports(ArbosFeature::GasInfoL2TxPricing)
+                {
                     let mut gas_for_l1_calldata = U256::ZERO;
                     if l2_gas_price > 0 {
                         gas_for_l1_calldata =
@@ -397,16 +347,7 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                     }
 
                     let per_l2_tx = U256::from(ARBOS_GAS_INFO_ASSUMED_SIMPLE_TX_SIZE);
-
-                    let output = ArbGasInfo::getPricesInArbGasCall::abi_encode_returns(
-                        &ArbGasInfo::getPricesInArbGasReturn::from((
-                            per_l2_tx,
-                            gas_for_l1_calldata,
-                            U256::from(revm::interpreter::gas::SSTORE_SET),
-                        )),
-                    );
-
-                    interpreter_return!(gas, Bytes::from(output));
+                    (per_l2_tx, gas_for_l1_calldata)
                 } else {
                     let wei_per_l2_tx = wei_for_l1_calldata
                         .saturating_mul(U256::from(ARBOS_GAS_INFO_ASSUMED_SIMPLE_TX_SIZE));
@@ -417,19 +358,16 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                             wei_for_l1_calldata.wrapping_div(U256::from(l2_gas_price));
                         gas_per_l2_tx = wei_per_l2_tx.wrapping_div(U256::from(l2_gas_price));
                     }
+                    (gas_per_l2_tx, gas_for_l1_calldata)
+                };
 
-                    let output = ArbGasInfo::getPricesInArbGasCall::abi_encode_returns(
-                        &ArbGasInfo::getPricesInArbGasReturn::from((
-                            gas_per_l2_tx,
-                            gas_for_l1_calldata,
-                            U256::from(revm::interpreter::gas::SSTORE_SET),
-                        )),
-                    );
-
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-            }
-            ArbGasInfo::getPricesInArbGasWithAggregatorCall::SELECTOR => {
+                ArbGasInfo::getPricesInArbGasReturn::from((
+                    gas_per_l2_tx,
+                    gas_for_l1_calldata,
+                    U256::from(revm::interpreter::gas::SSTORE_SET),
+                ))
+            },
+            ArbGasInfo::getPricesInArbGasWithAggregatorCall => |_call| {
                 let l1_gas_price = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     try_state!(gas, arb_state.l1_pricing().price_per_unit().get())
@@ -441,7 +379,10 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                     revm::interpreter::gas::NON_ZERO_BYTE_MULTIPLIER_ISTANBUL,
                 ));
 
-                if context.cfg().arbos_version() < 4 {
+                let (gas_per_l2_tx, gas_for_l1_calldata) = if !context
+                    .arbos_version()
+                    .supports(ArbosFeature::GasInfoL2TxPricing)
+                {
                     let mut gas_for_l1_calldata = U256::ZERO;
                     if l2_gas_price > 0 {
                         gas_for_l1_calldata =
@@ -449,16 +390,7 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                     }
 
                     let per_l2_tx = U256::from(ARBOS_GAS_INFO_ASSUMED_SIMPLE_TX_SIZE);
-
-                    let output = ArbGasInfo::getPricesInArbGasCall::abi_encode_returns(
-                        &ArbGasInfo::getPricesInArbGasReturn::from((
-                            per_l2_tx,
-                            gas_for_l1_calldata,
-                            U256::from(revm::interpreter::gas::SSTORE_SET),
-                        )),
-                    );
-
-                    interpreter_return!(gas, Bytes::from(output));
+                    (per_l2_tx, gas_for_l1_calldata)
                 } else {
                     let wei_per_l2_tx = wei_for_l1_calldata
                         .saturating_mul(U256::from(ARBOS_GAS_INFO_ASSUMED_SIMPLE_TX_SIZE));
@@ -469,20 +401,16 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                             wei_for_l1_calldata.wrapping_div(U256::from(l2_gas_price));
                         gas_per_l2_tx = wei_per_l2_tx.wrapping_div(U256::from(l2_gas_price));
                     }
+                    (gas_per_l2_tx, gas_for_l1_calldata)
+                };
 
-                    let output =
-                        ArbGasInfo::getPricesInArbGasWithAggregatorCall::abi_encode_returns(
-                            &ArbGasInfo::getPricesInArbGasWithAggregatorReturn::from((
-                                gas_per_l2_tx,
-                                gas_for_l1_calldata,
-                                U256::from(revm::interpreter::gas::SSTORE_SET),
-                            )),
-                        );
-
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-            }
-            ArbGasInfo::getPricesInWeiCall::SELECTOR => {
+                ArbGasInfo::getPricesInArbGasWithAggregatorReturn::from((
+                    gas_per_l2_tx,
+                    gas_for_l1_calldata,
+                    U256::from(revm::interpreter::gas::SSTORE_SET),
+                ))
+            },
+            ArbGasInfo::getPricesInWeiCall => |_call| {
                 let l1_gas_price = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     try_state!(gas, arb_state.l1_pricing().price_per_unit().get())
@@ -504,20 +432,16 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                 let wei_for_l2_storage = U256::from(revm::interpreter::gas::SSTORE_SET)
                     .saturating_mul(U256::from(l2_gas_price));
 
-                let output = ArbGasInfo::getPricesInWeiCall::abi_encode_returns(
-                    &ArbGasInfo::getPricesInWeiReturn::from((
-                        wei_per_l2_tx,
-                        wei_for_l1_calldata,
-                        wei_for_l2_storage,
-                        U256::from(per_arb_gas_base),
-                        per_arb_gas_congestion,
-                        U256::from(per_arb_gas_total),
-                    )),
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getPricesInWeiWithAggregatorCall::SELECTOR => {
+                ArbGasInfo::getPricesInWeiReturn::from((
+                    wei_per_l2_tx,
+                    wei_for_l1_calldata,
+                    wei_for_l2_storage,
+                    U256::from(per_arb_gas_base),
+                    per_arb_gas_congestion,
+                    U256::from(per_arb_gas_total),
+                ))
+            },
+            ArbGasInfo::getPricesInWeiWithAggregatorCall => |_call| {
                 let l1_gas_price = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     try_state!(gas, arb_state.l1_pricing().price_per_unit().get())
@@ -539,69 +463,34 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbGasInfoPrecompile {
                 let wei_for_l2_storage = U256::from(revm::interpreter::gas::SSTORE_SET)
                     .saturating_mul(U256::from(l2_gas_price));
 
-                let output = ArbGasInfo::getPricesInWeiWithAggregatorCall::abi_encode_returns(
-                    &ArbGasInfo::getPricesInWeiWithAggregatorReturn::from((
-                        wei_per_l2_tx,
-                        wei_for_l1_calldata,
-                        wei_for_l2_storage,
-                        U256::from(per_arb_gas_base),
-                        per_arb_gas_congestion,
-                        U256::from(per_arb_gas_total),
-                    )),
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getCurrentTxL1GasFeesCall::SELECTOR => {
-                let output = ArbGasInfo::getCurrentTxL1GasFeesCall::abi_encode_returns(&U256::ZERO);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getPricingInertiaCall::SELECTOR => {
-                let pricing_inertia = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l2_pricing().pricing_inertia().get())
-                };
-
-                let output =
-                    ArbGasInfo::getPricingInertiaCall::abi_encode_returns(&pricing_inertia);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getL1RewardRateCall::SELECTOR => {
-                let l1_reward_rate = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().per_unit_reward().get())
-                };
-
-                let output = ArbGasInfo::getL1RewardRateCall::abi_encode_returns(&l1_reward_rate);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getL1RewardRecipientCall::SELECTOR => {
-                let l1_reward_recipient = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().reward_recipient().get())
-                };
-
-                let output =
-                    ArbGasInfo::getL1RewardRecipientCall::abi_encode_returns(&l1_reward_recipient);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbGasInfo::getL1GasPriceEstimateCall::SELECTOR => {
-                let l1_gas_price_estimate = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.l1_pricing().price_per_unit().get())
-                };
-
-                let output = ArbGasInfo::getL1GasPriceEstimateCall::abi_encode_returns(
-                    &l1_gas_price_estimate,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown function selector")),
-        }
+                ArbGasInfo::getPricesInWeiWithAggregatorReturn::from((
+                    wei_per_l2_tx,
+                    wei_for_l1_calldata,
+                    wei_for_l2_storage,
+                    U256::from(per_arb_gas_base),
+                    per_arb_gas_congestion,
+                    U256::from(per_arb_gas_total),
+                ))
+            },
+            ArbGasInfo::getCurrentTxL1GasFeesCall => |_call| {
+                U256::ZERO
+            },
+            ArbGasInfo::getPricingInertiaCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l2_pricing().pricing_inertia().get())
+            },
+            ArbGasInfo::getL1RewardRateCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().per_unit_reward().get())
+            },
+            ArbGasInfo::getL1RewardRecipientCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().reward_recipient().get())
+            },
+            ArbGasInfo::getL1GasPriceEstimateCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                try_state!(gas, arb_state.l1_pricing().price_per_unit().get())
+            },
+        })
     }
 }
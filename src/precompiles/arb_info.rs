@@ -1,17 +1,21 @@
-use alloy_sol_types::{SolCall, sol};
+use alloy_sol_types::sol;
 use revm::{
-    interpreter::{Gas, InterpreterResult, gas::COLD_SLOAD_COST},
+    context::{ContextTr, JournalTr},
+    interpreter::{
+        Gas, InterpreterResult,
+        gas::{COLD_SLOAD_COST, COPY},
+    },
     precompile::PrecompileId,
-    primitives::{Address, Bytes, U256, address},
+    primitives::{Address, U256},
 };
 
 use crate::{
-    ArbitrumContextTr, generate_state_mut_table,
-    macros::{interpreter_return, interpreter_revert},
-    precompile_impl,
-    precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
-    },
+    ArbitrumContextTr,
+    constants::ARB_INFO_ADDRESS,
+    generate_state_mut_table,
+    macros::interpreter_revert,
+    precompile_dispatch, precompile_impl,
+    precompiles::{ArbPrecompileLogic, ExtendedPrecompile, StateMutability},
     try_record_cost,
 };
 
@@ -31,12 +35,15 @@ interface ArbInfo {
 pub fn arb_info_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbInfo")),
-        address!("0x0000000000000000000000000000000000000065"),
+        ARB_INFO_ADDRESS,
         precompile_impl!(ArbInfoPrecompile),
     )
 }
 
-struct ArbInfoPrecompile;
+pub(super) struct ArbInfoPrecompile;
+
+/// Gas charged by `getBalance`, the price of the BALANCE opcode under EIP-1884.
+const GET_BALANCE_GAS_COST: u64 = 700;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbInfoPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -56,39 +63,46 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbInfoPrecompile {
         gas_limit: u64,
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
-        let selector = selector_or_revert!(gas, input);
-
-        match selector {
-            ArbInfo::getBalanceCall::SELECTOR => {
-                let call = decode_call!(gas, ArbInfo::getBalanceCall, input);
-
-                try_record_cost!(gas, 700);
 
-                let balance = context.balance(call.account).unwrap_or_default().data;
+        precompile_dispatch!(gas, input, {
+            ArbInfo::getBalanceCall => |call| {
+                try_record_cost!(gas, GET_BALANCE_GAS_COST);
 
-                let output = ArbInfo::getBalanceCall::abi_encode_returns(&balance);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbInfo::getCodeCall::SELECTOR => {
-                let call = decode_call!(gas, ArbInfo::getCodeCall, input);
+                let Some(balance) =
+                    without_warming(context, |context| context.balance(call.account))
+                else {
+                    interpreter_revert!(gas);
+                };
 
+                balance.data
+            },
+            ArbInfo::getCodeCall => |call| {
                 try_record_cost!(gas, COLD_SLOAD_COST);
 
-                let code = context
-                    .load_account_code(call.account)
-                    .unwrap_or_default()
-                    .data;
-
-                let words = code.len().div_ceil(32);
-                let additional_gas = words as u64 * revm::interpreter::gas::COPY;
-                try_record_cost!(gas, additional_gas);
+                let Some(code) =
+                    without_warming(context, |context| context.load_account_code(call.account))
+                else {
+                    interpreter_revert!(gas);
+                };
+                let code = code.data;
 
-                let output = ArbInfo::getCodeCall::abi_encode_returns(&code);
+                try_record_cost!(gas, COPY * code.len().div_ceil(32) as u64);
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown function selector")),
-        }
+                code
+            },
+        })
     }
 }
+
+/// Runs `read` against the journal without warming the accounts it loads, as Nitro reads
+/// account info through the state database rather than the access list. A `None` result means
+/// the database failed, with the error recorded in the context.
+fn without_warming<CTX: ContextTr, T>(
+    context: &mut CTX,
+    read: impl FnOnce(&mut CTX) -> Option<T>,
+) -> Option<T> {
+    let checkpoint = context.journal_mut().checkpoint();
+    let result = read(context);
+    context.journal_mut().checkpoint_revert(checkpoint);
+    result
+}
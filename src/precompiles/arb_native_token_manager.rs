@@ -1,22 +1,24 @@
 use core::panic;
 
 use crate::{
-    ArbitrumContextTr, generate_state_mut_table,
-    macros::{interpreter_return, interpreter_revert},
-    precompile_impl,
+    ArbitrumContextTr,
+    constants::ARB_NATIVE_TOKEN_MANAGER_ADDRESS,
+    generate_state_mut_table,
+    macros::interpreter_revert,
+    precompile_dispatch, precompile_impl,
     precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
+        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, errors::revert_message,
     },
     state::{ArbState, ArbStateGetter, ArbosStateError, try_state},
     try_record_cost,
 };
 
-use alloy_sol_types::{SolCall, sol};
+use alloy_sol_types::sol;
 use revm::{
     context::JournalTr,
     interpreter::{Gas, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, Bytes, U256, address},
+    primitives::{Address, Bytes, U256},
 };
 
 sol! {
@@ -59,7 +61,7 @@ interface ArbNativeTokenManager {
 pub fn arb_native_token_manager_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbNativeTokenManager")),
-        address!("0x0000000000000000000000000000000000000073"),
+        ARB_NATIVE_TOKEN_MANAGER_ADDRESS,
         precompile_impl!(ArbNativeTokenManagerPrecompile),
     )
 }
@@ -67,7 +69,7 @@ pub fn arb_native_token_manager_precompile<CTX: ArbitrumContextTr>() -> Extended
 const MINT_BURN_GAS_COST: u64 =
     revm::interpreter::gas::WARM_STORAGE_READ_COST + revm::interpreter::gas::CALLVALUE;
 
-struct ArbNativeTokenManagerPrecompile;
+pub(super) struct ArbNativeTokenManagerPrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbNativeTokenManagerPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -87,62 +89,53 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbNativeTokenManagerPr
         gas_limit: u64,
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
-        let selector = selector_or_revert!(gas, input);
 
-        match selector {
-            ArbNativeTokenManager::mintNativeTokenCall::SELECTOR => {
+        precompile_dispatch!(gas, input, {
+            ArbNativeTokenManager::mintNativeTokenCall => |call| {
                 if !try_state!(gas, has_access(context, &mut gas, caller_address)) {
                     interpreter_revert!(gas);
                 }
 
                 try_record_cost!(gas, MINT_BURN_GAS_COST);
 
-                let call = decode_call!(gas, ArbNativeTokenManager::mintNativeTokenCall, input);
                 context
                     .journal_mut()
                     .balance_incr(caller_address, call.amount)
                     .expect("Failed to mint native token");
 
-                let output = ArbNativeTokenManager::mintNativeTokenCall::abi_encode_returns(
-                    &ArbNativeTokenManager::mintNativeTokenReturn {},
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbNativeTokenManager::burnNativeTokenCall::SELECTOR => {
+                ArbNativeTokenManager::mintNativeTokenReturn {}
+            },
+            ArbNativeTokenManager::burnNativeTokenCall => |call| {
                 if !try_state!(gas, has_access(context, &mut gas, caller_address)) {
                     interpreter_revert!(gas);
                 }
 
                 try_record_cost!(gas, MINT_BURN_GAS_COST);
 
-                let call = decode_call!(gas, ArbNativeTokenManager::burnNativeTokenCall, input);
                 let balance = context.balance(caller_address).unwrap_or_default().data;
 
                 if balance.checked_sub(call.amount).is_none() {
-                    interpreter_revert!(gas, Bytes::from("burn amount exceeds balance"));
+                    interpreter_revert!(gas, revert_message("burn amount exceeds balance"));
                 };
 
                 match context
                     .journal_mut()
                     .transfer(caller_address, *target_address, call.amount)
                 {
-                    Ok(None) => {
-                        let output = ArbNativeTokenManager::burnNativeTokenCall::abi_encode_returns(
-                            &ArbNativeTokenManager::burnNativeTokenReturn {},
-                        );
-                        interpreter_return!(gas, Bytes::from(output));
+                    Ok(None) => {}
+                    Ok(Some(err)) => {
+                        return Some(InterpreterResult {
+                            result: err.into(),
+                            gas,
+                            output: Bytes::default(),
+                        });
                     }
-                    Ok(Some(err)) => Some(InterpreterResult {
-                        result: err.into(),
-                        gas,
-                        output: Bytes::default(),
-                    }),
                     Err(e) => panic!("Failed to burn native token: {}", e),
                 }
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown function selector")),
-        }
+
+                ArbNativeTokenManager::burnNativeTokenReturn {}
+            },
+        })
     }
 }
 
@@ -1,24 +1,31 @@
 use alloy_sol_types::{SolCall, sol};
 use revm::{
-    context::JournalTr,
+    context::{Block, JournalTr},
     interpreter::{Gas, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, Bytes, Log, U256, address, alloy_primitives::IntoLogData},
+    primitives::{Address, Bytes, Log, U256, alloy_primitives::IntoLogData},
 };
 
 use crate::{
     ArbitrumContextTr,
+    arbos_version::ArbosFeature,
     config::ArbitrumConfigTr,
-    constants::{ARBOS_L1_PRICER_FUNDS_ADDRESS, COST_SCALAR_PERCENT},
+    constants::{ARB_OWNER_ADDRESS, ARBOS_L1_PRICER_FUNDS_ADDRESS, COST_SCALAR_PERCENT},
     generate_state_mut_table,
-    macros::{emit_event, interpreter_return, interpreter_revert},
-    precompile_impl,
+    local_context::{ArbitrumLocalContextTr, OwnerAction},
+    macros::{emit_event, interpreter_revert},
+    precompile_dispatch, precompile_impl,
     precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
+        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, errors::revert_message,
     },
     state::{ArbState, ArbStateGetter, try_state, types::StorageBackedTr},
 };
 
+/// Highest brotli level Nitro compresses with (`arbcompress.LEVEL_WELL`).
+const MAX_BROTLI_COMPRESSION_LEVEL: u64 = 11;
+/// One hundred percent, in basis points.
+const MAX_BIPS: u64 = 10_000;
+
 sol! {
 ///
 /// @title Provides owners with tools for managing the rollup.
@@ -112,6 +119,17 @@ interface ArbOwner {
         uint64 sec
     ) external;
 
+    /// @notice Set the L2 gas backlog directly, bypassing the pool calculus
+    function setGasBacklog(
+        uint64 backlog
+    ) external;
+
+    /// @notice Set the maximum size a block can be
+    /// Available in ArbOS version 50
+    function setMaxBlockGasLimit(
+        uint64 limit
+    ) external;
+
     /// @notice Get the network fee collector
     function getNetworkFeeAccount() external view returns (address);
 
@@ -259,6 +277,17 @@ interface ArbOwner {
         bool enable
     ) external;
 
+    ///
+    /// @notice Applies a batch of calls to this precompile in order, reverting all of them if
+    /// any fails, and emits a single OwnerActs for the batch. Not part of Nitro's ArbOwner: only
+    /// available when enabled in the chain's configuration
+    /// @param calls calldata of each call, which may not itself be a multicall
+    /// @return results the output of each call
+    ///
+    function multicall(
+        bytes[] calldata calls
+    ) external returns (bytes[] memory results);
+
     /// Emitted when a successful call is made to this precompile
     event OwnerActs(bytes4 indexed method, address indexed owner, bytes data);
 }
@@ -287,7 +316,7 @@ pub(crate) fn require_chain_owner<CTX: ArbitrumContextTr>(
         const NOT_CHAIN_OWNER: &str = "unauthorized caller to access-controlled method";
         return Some(crate::macros::interpreter_result_revert_with_output(
             gas,
-            NOT_CHAIN_OWNER.into(),
+            revert_message(NOT_CHAIN_OWNER),
         ));
     }
     None
@@ -296,11 +325,11 @@ pub(crate) fn require_chain_owner<CTX: ArbitrumContextTr>(
 pub fn arb_owner_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbOwner")),
-        address!("0x0000000000000000000000000000000000000070"),
+        ARB_OWNER_ADDRESS,
         precompile_impl!(ArbOwnerPrecompile),
     )
 }
-struct ArbOwnerPrecompile;
+pub(super) struct ArbOwnerPrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -321,6 +350,8 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
             setMaxTxGasLimitCall(NonPayable),
             setL2GasPricingInertiaCall(NonPayable),
             setL2GasBacklogToleranceCall(NonPayable),
+            setGasBacklogCall(NonPayable),
+            setMaxBlockGasLimitCall(NonPayable),
             getNetworkFeeAccountCall(View),
             getInfraFeeAccountCall(View),
             setNetworkFeeAccountCall(NonPayable),
@@ -350,13 +381,18 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
             removeWasmCacheManagerCall(NonPayable),
             setChainConfigCall(NonPayable),
             setCalldataPriceIncreaseCall(NonPayable),
+            multicallCall(NonPayable),
         }
     };
 
+    fn method_enabled(context: &CTX, selector: [u8; 4]) -> bool {
+        selector != ArbOwner::multicallCall::SELECTOR || context.cfg().owner_multicall()
+    }
+
     fn inner(
         context: &mut CTX,
         input: &[u8],
-        _target_address: &Address,
+        target_address: &Address,
         caller_address: Address,
         _call_value: U256,
         is_static: bool,
@@ -370,15 +406,12 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
 
         fn run_arbos_owner<CTX: ArbitrumContextTr>(
             context: &mut CTX,
-            selector: [u8; 4],
             input: &[u8],
             is_static: bool,
             mut gas: Gas,
         ) -> Option<InterpreterResult> {
-            match selector {
-                ArbOwner::addChainOwnerCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::addChainOwnerCall, input);
-
+            precompile_dispatch!(gas, input, {
+                ArbOwner::addChainOwnerCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -387,15 +420,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .add(call.newOwner)
                     );
 
-                    let output = ArbOwner::addChainOwnerCall::abi_encode_returns(
-                        &ArbOwner::addChainOwnerReturn {},
-                    );
-
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::addNativeTokenOwnerCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::addNativeTokenOwnerCall, input);
-
+                    ArbOwner::addChainOwnerReturn {}
+                },
+                ArbOwner::addNativeTokenOwnerCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -404,15 +431,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .add(call.newOwner)
                     );
 
-                    let output = ArbOwner::addNativeTokenOwnerCall::abi_encode_returns(
-                        &ArbOwner::addNativeTokenOwnerReturn {},
-                    );
-
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::addWasmCacheManagerCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::addWasmCacheManagerCall, input);
-
+                    ArbOwner::addNativeTokenOwnerReturn {}
+                },
+                ArbOwner::addWasmCacheManagerCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -422,43 +443,25 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .add(call.manager)
                     );
 
-                    let output = ArbOwner::addWasmCacheManagerCall::abi_encode_returns(
-                        &ArbOwner::addWasmCacheManagerReturn {},
-                    );
-
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::isChainOwnerCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::isChainOwnerCall, input);
-
-                    let is_owner = try_state!(
+                    ArbOwner::addWasmCacheManagerReturn {}
+                },
+                ArbOwner::isChainOwnerCall => |call| {
+                    try_state!(
                         gas,
                         context
                             .arb_state(Some(&mut gas), is_static)
                             .is_chain_owner(call.addr)
-                    );
-
-                    let output = ArbOwner::isChainOwnerCall::abi_encode_returns(&is_owner);
-
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::isNativeTokenOwnerCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::isNativeTokenOwnerCall, input);
-
-                    let is_owner = try_state!(
+                    )
+                },
+                ArbOwner::isNativeTokenOwnerCall => |call| {
+                    try_state!(
                         gas,
                         context
                             .arb_state(Some(&mut gas), is_static)
                             .is_native_token_owner(call.addr)
-                    );
-
-                    let output = ArbOwner::isNativeTokenOwnerCall::abi_encode_returns(&is_owner);
-
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::removeChainOwnerCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::removeChainOwnerCall, input);
-
+                    )
+                },
+                ArbOwner::removeChainOwnerCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -467,14 +470,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .remove(&call.ownerToRemove)
                     );
 
-                    let output = ArbOwner::removeChainOwnerCall::abi_encode_returns(
-                        &ArbOwner::removeChainOwnerReturn {},
-                    );
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::removeNativeTokenOwnerCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::removeNativeTokenOwnerCall, input);
-
+                    ArbOwner::removeChainOwnerReturn {}
+                },
+                ArbOwner::removeNativeTokenOwnerCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -483,14 +481,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .remove(&call.ownerToRemove)
                     );
 
-                    let output = ArbOwner::removeNativeTokenOwnerCall::abi_encode_returns(
-                        &ArbOwner::removeNativeTokenOwnerReturn {},
-                    );
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::removeWasmCacheManagerCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::removeWasmCacheManagerCall, input);
-
+                    ArbOwner::removeNativeTokenOwnerReturn {}
+                },
+                ArbOwner::removeWasmCacheManagerCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -500,45 +493,27 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .remove(&call.manager)
                     );
 
-                    let output = ArbOwner::removeWasmCacheManagerCall::abi_encode_returns(
-                        &ArbOwner::removeWasmCacheManagerReturn {},
-                    );
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::getAllChainOwnersCall::SELECTOR => {
-                    let _ = decode_call!(gas, ArbOwner::getAllChainOwnersCall, input);
-                    let chains_owners = try_state!(
+                    ArbOwner::removeWasmCacheManagerReturn {}
+                },
+                ArbOwner::getAllChainOwnersCall => |_call| {
+                    try_state!(
                         gas,
                         context
                             .arb_state(Some(&mut gas), is_static)
                             .chain_owners()
                             .all()
-                    );
-
-                    let output =
-                        ArbOwner::getAllChainOwnersCall::abi_encode_returns(&chains_owners);
-
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::getAllNativeTokenOwnersCall::SELECTOR => {
-                    let _ = decode_call!(gas, ArbOwner::getAllNativeTokenOwnersCall, input);
-                    let native_token_owners = try_state!(
+                    )
+                },
+                ArbOwner::getAllNativeTokenOwnersCall => |_call| {
+                    try_state!(
                         gas,
                         context
                             .arb_state(Some(&mut gas), is_static)
                             .native_token_owners()
                             .all()
-                    );
-
-                    let output = ArbOwner::getAllNativeTokenOwnersCall::abi_encode_returns(
-                        &native_token_owners,
-                    );
-
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::setCalldataPriceIncreaseCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setCalldataPriceIncreaseCall, input);
-
+                    )
+                },
+                ArbOwner::setCalldataPriceIncreaseCall => |call| {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     let mut l1_pricing = arb_state.l1_pricing();
                     try_state!(
@@ -548,11 +523,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .set(if call.enable { 1 } else { 0 })
                     );
 
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setNativeTokenManagementFromCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setNativeTokenManagementFromCall, input);
-
+                    ArbOwner::setCalldataPriceIncreaseReturn {}
+                },
+                ArbOwner::setNativeTokenManagementFromCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -561,10 +534,12 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .set(call.timestamp)
                     );
 
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setL1BaseFeeEstimateInertiaCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setL1BaseFeeEstimateInertiaCall, input);
+                    ArbOwner::setNativeTokenManagementFromReturn {}
+                },
+                ArbOwner::setL1BaseFeeEstimateInertiaCall => |call| {
+                    if call.inertia == 0 {
+                        interpreter_revert!(gas, revert_message("inertia must be nonzero"));
+                    }
 
                     try_state!(
                         gas,
@@ -574,11 +549,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .inertia()
                             .set(call.inertia)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setL2BaseFeeCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setL2BaseFeeCall, input);
-
+                    ArbOwner::setL1BaseFeeEstimateInertiaReturn {}
+                },
+                ArbOwner::setL2BaseFeeCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -587,11 +560,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .base_fee_wei()
                             .set(call.priceInWei)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setMinimumL2BaseFeeCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setMinimumL2BaseFeeCall, input);
-
+                    ArbOwner::setL2BaseFeeReturn {}
+                },
+                ArbOwner::setMinimumL2BaseFeeCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -600,13 +571,11 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .min_base_fee_wei()
                             .set(call.priceInWei)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setSpeedLimitCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setSpeedLimitCall, input);
-
+                    ArbOwner::setMinimumL2BaseFeeReturn {}
+                },
+                ArbOwner::setSpeedLimitCall => |call| {
                     if call.limit == 0 {
-                        interpreter_revert!(gas, Bytes::from("speed limit must be nonzero"));
+                        interpreter_revert!(gas, revert_message("speed limit must be nonzero"));
                     }
                     try_state!(
                         gas,
@@ -616,12 +585,13 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .speed_limit_per_second()
                             .set(call.limit)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setMaxTxGasLimitCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setMaxTxGasLimitCall, input);
-
-                    if context.cfg().arbos_version() < 50 {
+                    ArbOwner::setSpeedLimitReturn {}
+                },
+                ArbOwner::setMaxTxGasLimitCall => |call| {
+                    if !context
+                        .arbos_version()
+                        .supports(ArbosFeature::PerTxGasLimit)
+                    {
                         try_state!(
                             gas,
                             context
@@ -641,13 +611,11 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                         );
                     }
 
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setL2GasPricingInertiaCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setL2GasPricingInertiaCall, input);
-
+                    ArbOwner::setMaxTxGasLimitReturn {}
+                },
+                ArbOwner::setL2GasPricingInertiaCall => |call| {
                     if call.sec == 0 {
-                        interpreter_revert!(gas, Bytes::from("price inertia must be nonzero"));
+                        interpreter_revert!(gas, revert_message("price inertia must be nonzero"));
                     }
                     try_state!(
                         gas,
@@ -657,11 +625,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .pricing_inertia()
                             .set(call.sec)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setL2GasBacklogToleranceCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setL2GasBacklogToleranceCall, input);
-
+                    ArbOwner::setL2GasPricingInertiaReturn {}
+                },
+                ArbOwner::setL2GasBacklogToleranceCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -670,38 +636,49 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .backlog_tolerance()
                             .set(call.sec)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::getNetworkFeeAccountCall::SELECTOR => {
-                    let _ = decode_call!(gas, ArbOwner::getNetworkFeeAccountCall, input);
-                    let network_fee_account = try_state!(
+                    ArbOwner::setL2GasBacklogToleranceReturn {}
+                },
+                ArbOwner::setGasBacklogCall => |call| {
+                    try_state!(
                         gas,
                         context
                             .arb_state(Some(&mut gas), is_static)
-                            .network_fee_account()
-                            .get()
+                            .l2_pricing()
+                            .gas_backlog()
+                            .set(call.backlog)
                     );
-                    let output = ArbOwner::getNetworkFeeAccountCall::abi_encode_returns(
-                        &network_fee_account,
+                    ArbOwner::setGasBacklogReturn {}
+                },
+                ArbOwner::setMaxBlockGasLimitCall => |call| {
+                    try_state!(
+                        gas,
+                        context
+                            .arb_state(Some(&mut gas), is_static)
+                            .l2_pricing()
+                            .per_block_gas_limit()
+                            .set(call.limit)
                     );
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::getInfraFeeAccountCall::SELECTOR => {
-                    let _ = decode_call!(gas, ArbOwner::getInfraFeeAccountCall, input);
-                    let infra_fee_account = try_state!(
+                    ArbOwner::setMaxBlockGasLimitReturn {}
+                },
+                ArbOwner::getNetworkFeeAccountCall => |_call| {
+                    try_state!(
+                        gas,
+                        context
+                            .arb_state(Some(&mut gas), is_static)
+                            .network_fee_account()
+                            .get()
+                    )
+                },
+                ArbOwner::getInfraFeeAccountCall => |_call| {
+                    try_state!(
                         gas,
                         context
                             .arb_state(Some(&mut gas), is_static)
                             .infra_fee_account()
                             .get()
-                    );
-                    let output =
-                        ArbOwner::getInfraFeeAccountCall::abi_encode_returns(&infra_fee_account);
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::setNetworkFeeAccountCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setNetworkFeeAccountCall, input);
-
+                    )
+                },
+                ArbOwner::setNetworkFeeAccountCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -709,11 +686,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .network_fee_account()
                             .set(call.newNetworkFeeAccount)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setInfraFeeAccountCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setInfraFeeAccountCall, input);
-
+                    ArbOwner::setNetworkFeeAccountReturn {}
+                },
+                ArbOwner::setInfraFeeAccountCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -721,19 +696,21 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .infra_fee_account()
                             .set(call.newInfraFeeAccount)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::scheduleArbOSUpgradeCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::scheduleArbOSUpgradeCall, input);
-
+                    ArbOwner::setInfraFeeAccountReturn {}
+                },
+                ArbOwner::scheduleArbOSUpgradeCall => |call| {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                     try_state!(gas, arb_state.upgrade_version().set(call.newVersion));
                     try_state!(gas, arb_state.upgrade_timestamp().set(call.timestamp));
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setL1PricingEquilibrationUnitsCall::SELECTOR => {
-                    let call =
-                        decode_call!(gas, ArbOwner::setL1PricingEquilibrationUnitsCall, input);
+                    ArbOwner::scheduleArbOSUpgradeReturn {}
+                },
+                ArbOwner::setL1PricingEquilibrationUnitsCall => |call| {
+                    if call.equilibrationUnits.is_zero() {
+                        interpreter_revert!(
+                            gas,
+                            revert_message("equilibration units must be nonzero")
+                        );
+                    }
 
                     try_state!(
                         gas,
@@ -743,10 +720,12 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .equilibration_units()
                             .set(call.equilibrationUnits)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setL1PricingInertiaCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setL1PricingInertiaCall, input);
+                    ArbOwner::setL1PricingEquilibrationUnitsReturn {}
+                },
+                ArbOwner::setL1PricingInertiaCall => |call| {
+                    if call.inertia == 0 {
+                        interpreter_revert!(gas, revert_message("inertia must be nonzero"));
+                    }
 
                     try_state!(
                         gas,
@@ -756,11 +735,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .inertia()
                             .set(call.inertia)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setL1PricingRewardRecipientCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setL1PricingRewardRecipientCall, input);
-
+                    ArbOwner::setL1PricingInertiaReturn {}
+                },
+                ArbOwner::setL1PricingRewardRecipientCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -769,11 +746,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .reward_recipient()
                             .set(call.recipient)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setL1PricingRewardRateCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setL1PricingRewardRateCall, input);
-
+                    ArbOwner::setL1PricingRewardRecipientReturn {}
+                },
+                ArbOwner::setL1PricingRewardRateCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -782,11 +757,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .per_unit_reward()
                             .set(call.weiPerUnit)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setL1PricePerUnitCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setL1PricePerUnitCall, input);
-
+                    ArbOwner::setL1PricingRewardRateReturn {}
+                },
+                ArbOwner::setL1PricePerUnitCall => |call| {
                     try_state!(
                         gas,
                         context
@@ -795,13 +768,11 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .price_per_unit()
                             .set(call.pricePerUnit)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setPerBatchGasChargeCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setPerBatchGasChargeCall, input);
-
+                    ArbOwner::setL1PricePerUnitReturn {}
+                },
+                ArbOwner::setPerBatchGasChargeCall => |call| {
                     if call.cost < 0 {
-                        interpreter_revert!(gas, Bytes::from("negative cost not allowed"));
+                        interpreter_revert!(gas, revert_message("negative cost not allowed"));
                     }
                     try_state!(
                         gas,
@@ -811,10 +782,15 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .per_batch_gas_cost()
                             .set(call.cost as u64)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setBrotliCompressionLevelCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setBrotliCompressionLevelCall, input);
+                    ArbOwner::setPerBatchGasChargeReturn {}
+                },
+                ArbOwner::setBrotliCompressionLevelCall => |call| {
+                    if call.level > MAX_BROTLI_COMPRESSION_LEVEL {
+                        interpreter_revert!(
+                            gas,
+                            revert_message("invalid brotli compression level")
+                        );
+                    }
 
                     try_state!(
                         gas,
@@ -823,10 +799,15 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .brotli_compression_level()
                             .set(call.level)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setAmortizedCostCapBipsCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setAmortizedCostCapBipsCall, input);
+                    ArbOwner::setBrotliCompressionLevelReturn {}
+                },
+                ArbOwner::setAmortizedCostCapBipsCall => |call| {
+                    if call.cap > MAX_BIPS {
+                        interpreter_revert!(
+                            gas,
+                            revert_message("amortized cost cap must not exceed 10000 bips")
+                        );
+                    }
 
                     try_state!(
                         gas,
@@ -836,11 +817,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .amortized_cost_cap_bips()
                             .set(call.cap)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::releaseL1PricerSurplusFundsCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::releaseL1PricerSurplusFundsCall, input);
-
+                    ArbOwner::setAmortizedCostCapBipsReturn {}
+                },
+                ArbOwner::releaseL1PricerSurplusFundsCall => |call| {
                     let balance = context
                         .balance(ARBOS_L1_PRICER_FUNDS_ADDRESS)
                         .unwrap_or_default()
@@ -861,17 +840,13 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                         try_state!(gas, l1_pricing.l1_fees_available().set(new_total));
                     }
 
-                    let output =
-                        ArbOwner::releaseL1PricerSurplusFundsCall::abi_encode_returns(&amount);
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-                ArbOwner::setInkPriceCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setInkPriceCall, input);
-
+                    amount
+                },
+                ArbOwner::setInkPriceCall => |call| {
                     if call.price == 0 || call.price > 0xFFFFFF {
                         interpreter_revert!(
                             gas,
-                            Bytes::from("ink price must be a positive uint24")
+                            revert_message("ink price must be a positive uint24")
                         );
                     }
 
@@ -892,11 +867,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmMaxStackDepthCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmMaxStackDepthCall, input);
-
+                    ArbOwner::setInkPriceReturn {}
+                },
+                ArbOwner::setWasmMaxStackDepthCall => |call| {
                     let mut params = try_state!(
                         gas,
                         context
@@ -914,11 +887,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmFreePagesCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmFreePagesCall, input);
-
+                    ArbOwner::setWasmMaxStackDepthReturn {}
+                },
+                ArbOwner::setWasmFreePagesCall => |call| {
                     let mut params = try_state!(
                         gas,
                         context
@@ -936,11 +907,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmPageGasCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmPageGasCall, input);
-
+                    ArbOwner::setWasmFreePagesReturn {}
+                },
+                ArbOwner::setWasmPageGasCall => |call| {
                     let mut params = try_state!(
                         gas,
                         context
@@ -958,11 +927,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmPageLimitCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmPageLimitCall, input);
-
+                    ArbOwner::setWasmPageGasReturn {}
+                },
+                ArbOwner::setWasmPageLimitCall => |call| {
                     let mut params = try_state!(
                         gas,
                         context
@@ -980,11 +947,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmMaxSizeCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmMaxSizeCall, input);
-
+                    ArbOwner::setWasmPageLimitReturn {}
+                },
+                ArbOwner::setWasmMaxSizeCall => |call| {
                     let mut params = try_state!(
                         gas,
                         context
@@ -1002,13 +967,11 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmMinInitGasCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmMinInitGasCall, input);
-
+                    ArbOwner::setWasmMaxSizeReturn {}
+                },
+                ArbOwner::setWasmMinInitGasCall => |call| {
                     if call.cached > u16::from(u8::MAX) {
-                        interpreter_revert!(gas, Bytes::from("cached gas too large"));
+                        interpreter_revert!(gas, revert_message("cached gas too large"));
                     }
                     let mut params = try_state!(
                         gas,
@@ -1028,13 +991,14 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmInitCostScalarCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmInitCostScalarCall, input);
-
+                    ArbOwner::setWasmMinInitGasReturn {}
+                },
+                ArbOwner::setWasmInitCostScalarCall => |call| {
                     if call.percent == 0 {
-                        interpreter_revert!(gas, Bytes::from("init cost scalar must be nonzero"));
+                        interpreter_revert!(
+                            gas,
+                            revert_message("init cost scalar must be nonzero")
+                        );
                     }
                     let mut params = try_state!(
                         gas,
@@ -1057,11 +1021,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmExpiryDaysCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmExpiryDaysCall, input);
-
+                    ArbOwner::setWasmInitCostScalarReturn {}
+                },
+                ArbOwner::setWasmExpiryDaysCall => |call| {
                     let mut params = try_state!(
                         gas,
                         context
@@ -1080,11 +1042,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmKeepaliveDaysCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmKeepaliveDaysCall, input);
-
+                    ArbOwner::setWasmExpiryDaysReturn {}
+                },
+                ArbOwner::setWasmKeepaliveDaysCall => |call| {
                     let mut params = try_state!(
                         gas,
                         context
@@ -1103,11 +1063,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setWasmBlockCacheSizeCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setWasmBlockCacheSizeCall, input);
-
+                    ArbOwner::setWasmKeepaliveDaysReturn {}
+                },
+                ArbOwner::setWasmBlockCacheSizeCall => |call| {
                     let mut params = try_state!(
                         gas,
                         context
@@ -1125,11 +1083,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .stylus_params()
                             .set(&params)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                ArbOwner::setChainConfigCall::SELECTOR => {
-                    let call = decode_call!(gas, ArbOwner::setChainConfigCall, input);
-
+                    ArbOwner::setWasmBlockCacheSizeReturn {}
+                },
+                ArbOwner::setChainConfigCall => |call| {
                     let hash = revm::primitives::keccak256(&call.chainConfig);
                     let value = U256::from_be_bytes(hash.0);
                     try_state!(
@@ -1139,27 +1095,86 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPrecompile {
                             .chain_config()
                             .set(value)
                     );
-                    interpreter_return!(gas, Bytes::new());
-                }
-                _ => interpreter_revert!(gas, Bytes::from("Unknown selector")),
-            }
+                    ArbOwner::setChainConfigReturn {}
+                },
+                ArbOwner::multicallCall => |call| {
+                    let mut results = Vec::with_capacity(call.calls.len());
+                    for calldata in &call.calls {
+                        let Some(selector) = calldata.first_chunk::<4>().copied() else {
+                            interpreter_revert!(gas, revert_message("Input too short"));
+                        };
+                        if selector == ArbOwner::multicallCall::SELECTOR {
+                            interpreter_revert!(gas, revert_message("multicall cannot be nested"));
+                        }
+
+                        let outcome = run_arbos_owner(
+                            context,
+                            calldata,
+                            is_static,
+                            Gas::new(gas.remaining()),
+                        )?;
+                        gas.set_spent(gas.limit() - outcome.gas.remaining());
+                        // The precompile's frame reverts the calls applied before the failed one
+                        if !outcome.result.is_ok() {
+                            return Some(InterpreterResult { gas, ..outcome });
+                        }
+                        results.push(outcome.output);
+                    }
+
+                    results
+                },
+            }, unknown => revert_message("Unknown selector"))
         }
 
-        let result = run_arbos_owner(context, selector, input, is_static, gas);
-
-        emit_event!(
-            context,
-            Log {
-                address: address!("0x0000000000000000000000000000000000000070"),
-                data: ArbOwner::OwnerActs {
-                    method: selector.into(),
-                    owner: caller_address,
-                    data: Bytes::from(input[4..].to_vec())
-                }
-                .to_log_data(),
-            },
-            gas
-        );
+        let result = run_arbos_owner(context, input, is_static, gas);
+
+        let legacy = context.cfg().legacy_owner_acts();
+        let succeeded = result
+            .as_ref()
+            .is_some_and(|outcome| outcome.result.is_ok());
+
+        // Nitro only logs owner actions that succeeded, and since ArbOS 11 skips read-only calls.
+        if legacy
+            || (succeeded
+                && (!is_static
+                    || !context
+                        .arbos_version()
+                        .supports(ArbosFeature::StaticOwnerActsSuppressed)))
+        {
+            let data = if legacy {
+                Bytes::copy_from_slice(&input[4..])
+            } else {
+                Bytes::copy_from_slice(input)
+            };
+
+            emit_event!(
+                context,
+                Log {
+                    address: *target_address,
+                    data: ArbOwner::OwnerActs {
+                        method: selector.into(),
+                        owner: caller_address,
+                        data,
+                    }
+                    .to_log_data(),
+                },
+                gas
+            );
+        }
+
+        // Keep a record of the state the owner changed, so the run can be audited later
+        let mutates = <Self as ArbPrecompileLogic<CTX>>::STATE_MUT_TABLE
+            .iter()
+            .any(|(sel, purity)| *sel == selector && *purity >= StateMutability::NonPayable);
+        if succeeded && !is_static && mutates {
+            let block_number = context.block().number().saturating_to();
+            context.local_mut().record_owner_action(OwnerAction {
+                block_number,
+                owner: caller_address,
+                method: selector.into(),
+                params: Bytes::copy_from_slice(&input[4..]),
+            });
+        }
 
         result
     }
@@ -1,17 +1,19 @@
-use alloy_sol_types::{SolCall, sol};
+use alloy_sol_types::sol;
 use revm::{
     context::JournalTr,
     interpreter::{Gas, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, Bytes, Log, U256, address, alloy_primitives::IntoLogData},
+    primitives::{Address, Log, U256, alloy_primitives::IntoLogData},
 };
 
 use crate::{
-    ArbitrumContextTr, generate_state_mut_table,
-    macros::{emit_event, interpreter_return, interpreter_revert},
-    precompile_impl,
+    ArbitrumContextTr,
+    constants::ARB_OWNER_PUBLIC_ADDRESS,
+    generate_state_mut_table,
+    macros::emit_event,
+    precompile_dispatch, precompile_impl,
     precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
+        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, errors::revert_message,
     },
     state::{ArbState, ArbStateGetter, try_state, types::StorageBackedTr},
 };
@@ -48,6 +50,10 @@ interface ArbOwnerPublic {
     /// Available in ArbOS version 41
     function getAllNativeTokenOwners() external view returns (address[] memory);
 
+    /// @notice Gets the time from which native token management is enabled
+    /// Available in ArbOS version 41
+    function getNativeTokenManagementFrom() external view returns (uint64);
+
     /// @notice Gets the network fee collector
     function getNetworkFeeAccount() external view returns (address);
 
@@ -79,12 +85,12 @@ interface ArbOwnerPublic {
 pub fn arb_owner_public_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbOwnerPublic")),
-        address!("0x000000000000000000000000000000000000006b"),
+        ARB_OWNER_PUBLIC_ADDRESS,
         precompile_impl!(ArbOwnerPublicPrecompile),
     )
 }
 
-struct ArbOwnerPublicPrecompile;
+pub(super) struct ArbOwnerPublicPrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPublicPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -94,6 +100,7 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPublicPrecompil
             getAllChainOwnersCall(View),
             isNativeTokenOwnerCall(View),
             getAllNativeTokenOwnersCall(View),
+            getNativeTokenManagementFromCall(View),
             getNetworkFeeAccountCall(View),
             getInfraFeeAccountCall(View),
             getBrotliCompressionLevelCall(View),
@@ -112,113 +119,79 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPublicPrecompil
         gas_limit: u64,
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
-        let selector = selector_or_revert!(gas, input);
-
-        match selector {
-            ArbOwnerPublic::isChainOwnerCall::SELECTOR => {
-                let call = decode_call!(gas, ArbOwnerPublic::isChainOwnerCall, input);
 
-                let is_owner = try_state!(
+        precompile_dispatch!(gas, input, {
+            ArbOwnerPublic::isChainOwnerCall => |call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .is_chain_owner(call.addr)
-                );
-
-                let output = ArbOwnerPublic::isChainOwnerCall::abi_encode_returns(&is_owner);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbOwnerPublic::isNativeTokenOwnerCall::SELECTOR => {
-                let call = decode_call!(gas, ArbOwnerPublic::isNativeTokenOwnerCall, input);
-
-                let is_owner = try_state!(
+                )
+            },
+            ArbOwnerPublic::isNativeTokenOwnerCall => |call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .is_native_token_owner(call.addr)
-                );
-
-                let output = ArbOwnerPublic::isNativeTokenOwnerCall::abi_encode_returns(&is_owner);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbOwnerPublic::getAllChainOwnersCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbOwnerPublic::getAllChainOwnersCall, input);
-                let chains_owners = try_state!(
+                )
+            },
+            ArbOwnerPublic::getAllChainOwnersCall => |_call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .chain_owners()
                         .all()
-                );
-
-                let output =
-                    ArbOwnerPublic::getAllChainOwnersCall::abi_encode_returns(&chains_owners);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbOwnerPublic::getAllNativeTokenOwnersCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbOwnerPublic::getAllNativeTokenOwnersCall, input);
-                let native_token_owners = try_state!(
+                )
+            },
+            ArbOwnerPublic::getAllNativeTokenOwnersCall => |_call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .native_token_owners()
                         .all()
-                );
-
-                let output = ArbOwnerPublic::getAllNativeTokenOwnersCall::abi_encode_returns(
-                    &native_token_owners,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbOwnerPublic::getNetworkFeeAccountCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbOwnerPublic::getNetworkFeeAccountCall, input);
-                let network_fee_account = try_state!(
+                )
+            },
+            ArbOwnerPublic::getNativeTokenManagementFromCall => |_call| {
+                try_state!(
+                    gas,
+                    context
+                        .arb_state(Some(&mut gas), is_static)
+                        .native_token_enabled_time()
+                        .get()
+                )
+            },
+            ArbOwnerPublic::getNetworkFeeAccountCall => |_call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .network_fee_account()
                         .get()
-                );
-
-                let output = ArbOwnerPublic::getNetworkFeeAccountCall::abi_encode_returns(
-                    &network_fee_account,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbOwnerPublic::getInfraFeeAccountCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbOwnerPublic::getInfraFeeAccountCall, input);
-                let infra_fee_account = try_state!(
+                )
+            },
+            ArbOwnerPublic::getInfraFeeAccountCall => |_call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .infra_fee_account()
                         .get()
-                );
-                let output =
-                    ArbOwnerPublic::getInfraFeeAccountCall::abi_encode_returns(&infra_fee_account);
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbOwnerPublic::getBrotliCompressionLevelCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbOwnerPublic::getBrotliCompressionLevelCall, input);
-                let compression_level = try_state!(
+                )
+            },
+            ArbOwnerPublic::getBrotliCompressionLevelCall => |_call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .brotli_compression_level()
                         .get()
-                );
-                let output = ArbOwnerPublic::getBrotliCompressionLevelCall::abi_encode_returns(
-                    &compression_level,
-                );
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbOwnerPublic::getScheduledUpgradeCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbOwnerPublic::getScheduledUpgradeCall, input);
+                )
+            },
+            ArbOwnerPublic::getScheduledUpgradeCall => |_call| {
                 let upgrade_version = try_state!(
                     gas,
                     context
@@ -233,34 +206,17 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPublicPrecompil
                         .upgrade_timestamp()
                         .get()
                 );
-                let output = ArbOwnerPublic::getScheduledUpgradeCall::abi_encode_returns(
-                    &ArbOwnerPublic::getScheduledUpgradeReturn {
-                        arbosVersion: upgrade_version,
-                        scheduledForTimestamp: upgrade_timestamp,
-                    },
-                );
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbOwnerPublic::isCalldataPriceIncreaseEnabledCall::SELECTOR => {
-                let _ = decode_call!(
-                    gas,
-                    ArbOwnerPublic::isCalldataPriceIncreaseEnabledCall,
-                    input
-                );
-                let enabled = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    let value = try_state!(gas, arb_state.l1_pricing().gas_floor_per_token().get());
-                    value != 0
-                };
-                let output = ArbOwnerPublic::isCalldataPriceIncreaseEnabledCall::abi_encode_returns(
-                    &enabled,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbOwnerPublic::rectifyChainOwnerCall::SELECTOR => {
-                let call = decode_call!(gas, ArbOwnerPublic::rectifyChainOwnerCall, input);
-
+                ArbOwnerPublic::getScheduledUpgradeReturn {
+                    arbosVersion: upgrade_version,
+                    scheduledForTimestamp: upgrade_timestamp,
+                }
+            },
+            ArbOwnerPublic::isCalldataPriceIncreaseEnabledCall => |_call| {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                let value = try_state!(gas, arb_state.l1_pricing().gas_floor_per_token().get());
+                value != 0
+            },
+            ArbOwnerPublic::rectifyChainOwnerCall => |call| {
                 let mut arb_state = context.arb_state(Some(&mut gas), is_static);
                 try_state!(gas, arb_state.chain_owners().rectify(call.ownerToRectify));
 
@@ -274,9 +230,8 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbOwnerPublicPrecompil
 
                 emit_event!(context, log, gas);
 
-                interpreter_return!(gas);
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown selector")),
-        }
+                ArbOwnerPublic::rectifyChainOwnerReturn {}
+            },
+        }, unknown => revert_message("Unknown selector"))
     }
 }
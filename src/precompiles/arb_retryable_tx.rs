@@ -1,28 +1,36 @@
-use alloy_sol_types::{SolCall, SolError, sol};
+use alloy_sol_types::sol;
 use revm::{
-    context::{Block, JournalTr},
+    context::{Block, Cfg, JournalTr},
     interpreter::{Gas, InterpreterResult, gas::ISTANBUL_SLOAD_GAS},
     precompile::PrecompileId,
-    primitives::{
-        Address, B256, Bytes, Log, U256, address, alloy_primitives::IntoLogData, keccak256,
-    },
+    primitives::{Address, B256, Bytes, Log, U256, alloy_primitives::IntoLogData},
 };
 
 use crate::{
     ArbitrumContextTr,
-    config::ArbitrumConfigTr,
+    arbos_version::ArbosFeature,
+    constants::ARB_RETRYABLE_TX_ADDRESS,
     generate_state_mut_table,
-    macros::{emit_event, interpreter_return, interpreter_revert},
-    precompile_impl,
+    local_context::ArbitrumLocalContextTr,
+    macros::{emit_event, interpreter_revert},
+    precompile_dispatch, precompile_impl,
     precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
+        ArbPrecompileLogic, ExtendedPrecompile, StateMutability,
+        errors::{custom_error, revert_message},
+    },
+    state::{
+        ArbState, ArbStateGetter, retryable::RETRYABLE_LIFETIME_SECONDS, try_state,
+        types::StorageBackedTr,
     },
-    state::{ArbState, ArbStateGetter, try_state, types::StorageBackedTr},
+    transaction::ArbitrumTxTr,
     try_record_cost,
 };
 
-const ARBOS_STATE_RETRYABLE_LIFETIME_SECONDS: u64 = 7 * 24 * 60 * 60; // 1 week
-const RETRYABLE_REAP_PRICE: u64 = 58_000;
+// Go-ethereum's CopyGas, TxGas and the EIP-2200 storage costs ArbOS charges with.
+const COPY: u64 = 3;
+const TX_GAS: u64 = 21_000;
+const STORAGE_READ_COST: u64 = 800;
+const STORAGE_WRITE_COST: u64 = 20_000;
 
 sol! {
 ///
@@ -136,12 +144,12 @@ interface ArbRetryableTx {
 pub fn arb_retryable_tx_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbRetryableTx")),
-        address!("0x000000000000000000000000000000000000006e"),
+        ARB_RETRYABLE_TX_ADDRESS,
         precompile_impl!(ArbRetryableTxPrecompile),
     )
 }
 
-struct ArbRetryableTxPrecompile;
+pub(super) struct ArbRetryableTxPrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbRetryableTxPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -168,199 +176,122 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbRetryableTxPrecompil
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
 
-        let selector = selector_or_revert!(gas, input);
-
-        match selector {
-            ArbRetryableTx::cancelCall::SELECTOR => {
-                let call = decode_call!(gas, ArbRetryableTx::cancelCall, input);
+        precompile_dispatch!(gas, input, {
+            ArbRetryableTx::cancelCall => |call| {
+                if is_current_retryable(context, call.ticketId) {
+                    interpreter_revert!(gas, revert_message("retryable cannot modify itself"));
+                }
 
                 let current_time = context.block().timestamp().saturating_to::<u64>();
-
                 let beneficiary = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    let mut retryable = arb_state.retryable(call.ticketId);
-                    let timeout = try_state!(gas, retryable.timeout().get());
-
-                    if timeout == 0 || timeout < current_time {
-                        if context.cfg().arbos_version() >= 3 {
-                            let output = ArbRetryableTx::NoTicketWithID {}.abi_encode();
-
-                            interpreter_revert!(gas, Bytes::from(output));
-                        }
-
-                        interpreter_revert!(gas, Bytes::from("ticketId not found"));
+                    let mut retryable_state = arb_state.retryable_state();
+                    match try_state!(
+                        gas,
+                        retryable_state.open_retryable(call.ticketId, current_time)
+                    ) {
+                        Some(mut retryable) => try_state!(gas, retryable.beneficiary().get()),
+                        None => interpreter_revert!(gas, not_found_error(context)),
                     }
-
-                    try_state!(gas, retryable.beneficiary().get())
                 };
 
                 if caller_address != beneficiary {
                     interpreter_revert!(
                         gas,
-                        Bytes::from("only the beneficiary may cancel a retryable")
+                        revert_message("only the beneficiary may cancel a retryable")
                     );
                 }
 
-                // move any funds in escrow to the beneficiary (should be none if the retry
-                // succeeded -- see EndTxHook)
-                let escrow_address = { retryable_escrow_address(call.ticketId) };
-
-                let escrow_balance = context.balance(escrow_address).unwrap_or_default().data;
-
-                if !escrow_balance.is_zero()
-                    && let Some(error) = context
-                        .journal_mut()
-                        .transfer(escrow_address, beneficiary, escrow_balance)
-                        .unwrap()
+                // No refund is given for the storage freed: retryables rent their space.
                 {
-                    return Some(InterpreterResult {
-                        result: error.into(),
+                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                    try_state!(
                         gas,
-                        output: Bytes::default(),
-                    });
+                        arb_state.retryable_state().delete_retryable(call.ticketId)
+                    );
                 }
 
-                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                let mut retryable = arb_state.retryable(call.ticketId);
-                try_state!(gas, retryable.clear());
-
-                let output = ArbRetryableTx::cancelCall::abi_encode_returns(
-                    &ArbRetryableTx::cancelReturn {},
+                emit_event!(
+                    context,
+                    Log {
+                        address: *target_address,
+                        data: ArbRetryableTx::Canceled {
+                            ticketId: call.ticketId,
+                        }
+                        .into_log_data()
+                    },
+                    gas
                 );
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbRetryableTx::getBeneficiaryCall::SELECTOR => {
-                let call = decode_call!(gas, ArbRetryableTx::getBeneficiaryCall, input);
-
+                ArbRetryableTx::cancelReturn {}
+            },
+            ArbRetryableTx::getBeneficiaryCall => |call| {
                 let current_time = context.block().timestamp().saturating_to::<u64>();
-                let beneficiary = {
+                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                let mut retryable_state = arb_state.retryable_state();
+                match try_state!(
+                    gas,
+                    retryable_state.open_retryable(call.ticketId, current_time)
+                ) {
+                    Some(mut retryable) => try_state!(gas, retryable.beneficiary().get()),
+                    None => interpreter_revert!(gas, not_found_error(context)),
+                }
+            },
+            ArbRetryableTx::getCurrentRedeemerCall => |_call| {
+                // The refund address of the redeem being executed, or zero outside of one.
+                context
+                    .tx()
+                    .retry_tx()
+                    .map_or(Address::ZERO, |retry_tx| retry_tx.refund_to)
+            },
+            ArbRetryableTx::getLifetimeCall => |_call| {
+                U256::from(RETRYABLE_LIFETIME_SECONDS)
+            },
+            ArbRetryableTx::getTimeoutCall => |call| {
+                let current_time = context.block().timestamp().saturating_to::<u64>();
+                let timeout = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    let mut retryable = arb_state.retryable(call.ticketId);
-                    let timeout = try_state!(gas, retryable.timeout().get());
-                    if timeout == 0 || timeout < current_time {
-                        if context.cfg().arbos_version() >= 3 {
-                            let output = ArbRetryableTx::NoTicketWithID {}.abi_encode();
-
-                            interpreter_revert!(gas, Bytes::from(output));
-                        }
-
-                        interpreter_revert!(gas, Bytes::from("ticketId not found"));
+                    let mut retryable_state = arb_state.retryable_state();
+                    match try_state!(
+                        gas,
+                        retryable_state.open_retryable(call.ticketId, current_time)
+                    ) {
+                        Some(mut retryable) => try_state!(gas, retryable.calculate_timeout()),
+                        None => interpreter_revert!(gas, not_found_error(context)),
                     }
-
-                    try_state!(gas, retryable.beneficiary().get())
                 };
 
-                let output = ArbRetryableTx::getBeneficiaryCall::abi_encode_returns(&beneficiary);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbRetryableTx::getCurrentRedeemerCall::SELECTOR => {
-                let output =
-                    ArbRetryableTx::getCurrentRedeemerCall::abi_encode_returns(&Address::ZERO);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbRetryableTx::getLifetimeCall::SELECTOR => {
-                let output = ArbRetryableTx::getLifetimeCall::abi_encode_returns(&U256::from(
-                    ARBOS_STATE_RETRYABLE_LIFETIME_SECONDS,
-                ));
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbRetryableTx::getTimeoutCall::SELECTOR => {
-                let call = decode_call!(gas, ArbRetryableTx::getTimeoutCall, input);
-
+                U256::from(timeout)
+            },
+            ArbRetryableTx::keepaliveCall => |call| {
                 let current_time = context.block().timestamp().saturating_to::<u64>();
-                let (timeout, windows_left) = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    let mut retryable = arb_state.retryable(call.ticketId);
-                    let timeout = try_state!(gas, retryable.timeout().get());
-                    let windows_left = try_state!(gas, retryable.timeout_windows_left().get());
-                    (timeout, windows_left)
-                };
-
-                if timeout == 0 || timeout < current_time {
-                    if context.cfg().arbos_version() >= 3 {
-                        let output = ArbRetryableTx::NoTicketWithID {}.abi_encode();
-
-                        interpreter_revert!(gas, Bytes::from(output));
-                    }
-
-                    interpreter_revert!(gas, Bytes::from("ticketId not found"));
-                }
 
-                let calculated_timeout = calculate_retryable_timeout(timeout, windows_left);
-
-                let output = ArbRetryableTx::getTimeoutCall::abi_encode_returns(&U256::from(
-                    calculated_timeout,
-                ));
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbRetryableTx::keepaliveCall::SELECTOR => {
-                let call = decode_call!(gas, ArbRetryableTx::keepaliveCall, input);
-
-                let current_time = context.block().timestamp().saturating_to::<u64>();
-                let (timeout, windows_left) = {
+                // Charge for the expiry update.
+                let nbytes = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    let mut retryable = arb_state.retryable(call.ticketId);
-
-                    let timeout = try_state!(gas, retryable.timeout().get());
-                    let windows_left = try_state!(gas, retryable.timeout_windows_left().get());
-
-                    (timeout, windows_left)
+                    try_state!(
+                        gas,
+                        arb_state
+                            .retryable_state()
+                            .retryable_size_bytes(call.ticketId, current_time)
+                    )
                 };
-
-                if timeout == 0 || timeout < current_time {
-                    if context.cfg().arbos_version() >= 3 {
-                        let output = ArbRetryableTx::NoTicketWithID {}.abi_encode();
-
-                        interpreter_revert!(gas, Bytes::from(output));
-                    }
-
-                    interpreter_revert!(gas, Bytes::from("ticketId not found"));
+                if nbytes == 0 {
+                    interpreter_revert!(gas, not_found_error(context));
                 }
-
-                let calldata_len = {
-                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    try_state!(gas, arb_state.retryable(call.ticketId).calldata().get()).len()
-                        as u64
-                };
-
-                let nbytes = retryable_size_bytes(calldata_len);
-
                 let update_cost = nbytes.div_ceil(32) * revm::interpreter::gas::SSTORE_SET / 100;
-
                 try_record_cost!(gas, update_cost);
 
-                let window = current_time.saturating_add(ARBOS_STATE_RETRYABLE_LIFETIME_SECONDS);
-
-                let effective_timeout = calculate_retryable_timeout(timeout, windows_left);
-                let new_timeout =
-                    effective_timeout.saturating_add(ARBOS_STATE_RETRYABLE_LIFETIME_SECONDS);
-
-                if effective_timeout > window {
-                    interpreter_revert!(gas, Bytes::from("timeout too far into the future"));
-                }
-
-                let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                try_state!(
-                    gas,
-                    arb_state
-                        .timeout_queue()
-                        .push(U256::from_be_slice(call.ticketId.as_slice()))
-                );
-
-                let mut retryable = arb_state.retryable(call.ticketId);
-                try_state!(
-                    gas,
-                    retryable
-                        .timeout_windows_left()
-                        .set(windows_left.saturating_add(1))
-                );
-
-                try_record_cost!(gas, RETRYABLE_REAP_PRICE);
+                let limit = current_time.saturating_add(RETRYABLE_LIFETIME_SECONDS);
+                let new_timeout = {
+                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                    try_state!(
+                        gas,
+                        arb_state
+                            .retryable_state()
+                            .keepalive(call.ticketId, current_time, limit)
+                    )
+                };
 
                 emit_event!(
                     context,
@@ -375,61 +306,76 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbRetryableTxPrecompil
                     gas
                 );
 
-                let output =
-                    ArbRetryableTx::keepaliveCall::abi_encode_returns(&U256::from(new_timeout));
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbRetryableTx::redeemCall::SELECTOR => {
-                let call = decode_call!(gas, ArbRetryableTx::redeemCall, input);
+                U256::from(new_timeout)
+            },
+            ArbRetryableTx::redeemCall => |call| {
+                if is_current_retryable(context, call.ticketId) {
+                    interpreter_revert!(gas, revert_message("retryable cannot modify itself"));
+                }
 
                 let current_time = context.block().timestamp().saturating_to::<u64>();
 
-                let (timeout, calldata_len) = {
+                // Charge for reading the retryable into the retry transaction.
+                let byte_count = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    let mut retryable = arb_state.retryable(call.ticketId);
-                    let timeout = try_state!(gas, retryable.timeout().get());
-                    let calldata_len = try_state!(gas, retryable.calldata().get()).len() as u64;
-
-                    (timeout, calldata_len)
+                    try_state!(
+                        gas,
+                        arb_state
+                            .retryable_state()
+                            .retryable_size_bytes(call.ticketId, current_time)
+                    )
                 };
+                try_record_cost!(
+                    gas,
+                    ISTANBUL_SLOAD_GAS.saturating_mul(byte_count.div_ceil(32))
+                );
 
-                if timeout == 0 || timeout < current_time {
-                    if context.cfg().arbos_version() >= 3 {
-                        let output = ArbRetryableTx::NoTicketWithID {}.abi_encode();
-
-                        interpreter_revert!(gas, Bytes::from(output));
-                    }
+                let chain_id = context.cfg().chain_id();
+                let base_fee = U256::from(context.block().basefee());
+                let max_refund = U256::MAX;
 
-                    interpreter_revert!(gas, Bytes::from("ticketId not found"));
+                // Gas still needed after the donation: the event, returning the result and
+                // updating the gas pool.
+                let event_cost = revm::interpreter::gas::log_cost(4, 4 * 32).unwrap_or(u64::MAX);
+                let future_gas_costs = event_cost + COPY + STORAGE_READ_COST + STORAGE_WRITE_COST;
+                if gas.remaining() < future_gas_costs {
+                    try_record_cost!(gas, future_gas_costs);
+                }
+                let gas_to_donate = gas.remaining() - future_gas_costs;
+                if gas_to_donate < TX_GAS {
+                    interpreter_revert!(
+                        gas,
+                        revert_message("not enough gas to run redeem attempt")
+                    );
                 }
 
-                // Charge for accessing retryable storage slots.
-                let byte_count = retryable_size_bytes(calldata_len);
-                let write_words = byte_count.div_ceil(32);
-                try_record_cost!(gas, ISTANBUL_SLOAD_GAS.saturating_mul(write_words));
-
-                let nonce = {
+                let (nonce, retry_tx) = {
                     let mut arb_state = context.arb_state(Some(&mut gas), is_static);
-                    let mut retryable = arb_state.retryable(call.ticketId);
-                    let num_tries = try_state!(gas, retryable.num_tries().get());
-                    try_state!(gas, retryable.num_tries().set(num_tries.saturating_add(1)));
-                    num_tries
-                };
-
-                // Derive a deterministic hash for the scheduled retry attempt.
-                let retry_tx_hash = {
-                    let mut hash_input =
-                        Vec::with_capacity(call.ticketId.as_slice().len() + 8 + 20);
-                    hash_input.extend_from_slice(call.ticketId.as_slice());
-                    hash_input.extend_from_slice(&nonce.to_be_bytes());
-                    hash_input.extend_from_slice(caller_address.as_slice());
-                    keccak256(&hash_input)
+                    let mut retryable_state = arb_state.retryable_state();
+                    let Some(mut retryable) = try_state!(
+                        gas,
+                        retryable_state.open_retryable(call.ticketId, current_time)
+                    ) else {
+                        interpreter_revert!(gas, not_found_error(context));
+                    };
+                    let nonce = try_state!(gas, retryable.increment_num_tries()) - 1;
+                    let retry_tx = try_state!(
+                        gas,
+                        retryable.make_tx(
+                            chain_id,
+                            nonce,
+                            base_fee,
+                            gas_to_donate,
+                            call.ticketId,
+                            caller_address,
+                            max_refund,
+                            U256::ZERO,
+                        )
+                    );
+                    (nonce, retry_tx)
                 };
-
-                let gas_to_donate = gas.remaining();
-                let max_refund = U256::MAX;
-                let submission_fee_refund = U256::ZERO;
+                let retry_tx_hash = retry_tx.hash();
+                context.local_mut().schedule_redeem(retry_tx);
 
                 emit_event!(
                     context,
@@ -442,44 +388,42 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbRetryableTxPrecompil
                             donatedGas: gas_to_donate,
                             gasDonor: caller_address,
                             maxRefund: max_refund,
-                            submissionFeeRefund: submission_fee_refund,
+                            submissionFeeRefund: U256::ZERO,
                         }
                         .into_log_data()
                     },
                     gas
                 );
 
-                let output = ArbRetryableTx::redeemCall::abi_encode_returns(&retry_tx_hash);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbRetryableTx::submitRetryableCall::SELECTOR => {
-                let _ = decode_call!(gas, ArbRetryableTx::submitRetryableCall, input);
+                // The donated gas is burned here and paid back to the retry transaction, which
+                // runs once this transaction ends.
+                try_record_cost!(gas, gas_to_donate);
 
-                let output = ArbRetryableTx::NotCallable {}.abi_encode();
-
-                interpreter_revert!(gas, Bytes::from(output));
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown function selector")),
-        }
+                retry_tx_hash
+            },
+            ArbRetryableTx::submitRetryableCall => |_call| {
+                interpreter_revert!(gas, custom_error(&ArbRetryableTx::NotCallable {}));
+            },
+        })
     }
 }
 
-fn retryable_size_bytes(calldata_length: u64) -> u64 {
-    let calldata_words = calldata_length.div_ceil(32);
-    // 6 storage words for the fixed fields plus a length word and the calldata contents.
-    6 * 32 + 32 + 32 * calldata_words
-}
-
-fn calculate_retryable_timeout(timeout: u64, windows_left: u64) -> u64 {
-    timeout.saturating_add(windows_left.saturating_mul(ARBOS_STATE_RETRYABLE_LIFETIME_SECONDS))
+/// Whether `ticket_id` is the retryable being redeemed by the current transaction.
+fn is_current_retryable<CTX: ArbitrumContextTr>(context: &CTX, ticket_id: B256) -> bool {
+    context
+        .tx()
+        .retry_tx()
+        .is_some_and(|retry_tx| retry_tx.ticket_id == ticket_id)
 }
 
-fn retryable_escrow_address(ticket_id: B256) -> Address {
-    let mut hasher_input = Vec::with_capacity(32 + "retryable escrow".len());
-    hasher_input.extend_from_slice(b"retryable escrow");
-    hasher_input.extend_from_slice(ticket_id.as_ref());
-
-    let hash = keccak256(&hasher_input);
-    Address::from_slice(&hash[12..32])
+/// Revert data for an unknown or expired ticket.
+fn not_found_error<CTX: ArbitrumContextTr>(context: &CTX) -> Bytes {
+    if context
+        .arbos_version()
+        .supports(ArbosFeature::RetryableNotFoundError)
+    {
+        custom_error(&ArbRetryableTx::NoTicketWithID {})
+    } else {
+        revert_message("ticketId not found")
+    }
 }
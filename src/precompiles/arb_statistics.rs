@@ -1,15 +1,15 @@
-use alloy_sol_types::{SolCall, sol};
+use alloy_sol_types::sol;
 use revm::{
     interpreter::{Gas, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, Bytes, U256, address},
+    primitives::{Address, U256},
 };
 
 use crate::{
-    ArbitrumContextTr, generate_state_mut_table,
-    macros::{interpreter_return, interpreter_revert},
-    precompile_impl,
-    precompiles::{ArbPrecompileLogic, ExtendedPrecompile, StateMutability, selector_or_revert},
+    ArbitrumContextTr,
+    constants::ARB_STATISTICS_ADDRESS,
+    generate_state_mut_table, precompile_dispatch, precompile_impl,
+    precompiles::{ArbPrecompileLogic, ExtendedPrecompile, StateMutability},
 };
 
 sol! {
@@ -43,12 +43,12 @@ interface ArbStatistics {
 pub fn arb_statistics_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbStatistics")),
-        address!("0x000000000000000000000000000000000000006f"),
+        ARB_STATISTICS_ADDRESS,
         precompile_impl!(ArbStatisticsPrecompile),
     )
 }
 
-struct ArbStatisticsPrecompile;
+pub(super) struct ArbStatisticsPrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbStatisticsPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -68,24 +68,17 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbStatisticsPrecompile
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
 
-        let selector = selector_or_revert!(gas, input);
-
-        match selector {
-            ArbStatistics::getStatsCall::SELECTOR => {
-                let output = ArbStatistics::getStatsCall::abi_encode_returns(
-                    &ArbStatistics::getStatsReturn::from((
-                        context.block_number(),
-                        U256::ZERO,
-                        U256::ZERO,
-                        U256::ZERO,
-                        U256::ZERO,
-                        U256::ZERO,
-                    )),
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown function selector")),
-        }
+        precompile_dispatch!(gas, input, {
+            ArbStatistics::getStatsCall => |_call| {
+                ArbStatistics::getStatsReturn::from((
+                    context.block_number(),
+                    U256::ZERO,
+                    U256::ZERO,
+                    U256::ZERO,
+                    U256::ZERO,
+                    U256::ZERO,
+                ))
+            },
+        })
     }
 }
@@ -1,19 +1,33 @@
-use alloy_sol_types::{SolCall, SolError, sol};
+use alloy_sol_types::{SolCall, sol};
 use revm::{
-    interpreter::{Gas, InterpreterResult},
+    context::{Block, JournalTr, Transaction},
+    interpreter::{
+        Gas, InterpreterResult,
+        gas::{KECCAK256, KECCAK256WORD},
+    },
     precompile::PrecompileId,
-    primitives::{Address, B256, Bytes, FixedBytes, U256, address, fixed_bytes},
+    primitives::{Address, Bytes, Log, U256, alloy_primitives::IntoLogData, keccak256},
 };
 
 use crate::{
     ArbitrumContextTr,
-    config::ArbitrumConfigTr,
+    aliasing::{apply_l1_to_l2_alias, undo_alias},
+    arbos_version::ArbosFeature,
+    constants::{
+        ARB_SYS_ADDRESS, ARBITRUM_CONTRACT_TX_TYPE, ARBITRUM_RETRY_TX_TYPE,
+        ARBITRUM_UNSIGNED_TX_TYPE,
+    },
     generate_state_mut_table,
-    macros::{interpreter_return, interpreter_revert},
-    precompile_impl,
+    local_context::ArbitrumLocalContextTr,
+    macros::{emit_event, interpreter_error, interpreter_return, interpreter_revert},
+    precompile_dispatch, precompile_impl,
     precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
+        ArbPrecompileLogic, ExtendedPrecompile, StateMutability,
+        errors::{custom_error, revert_message},
     },
+    state::{ArbState, ArbStateGetter, block_hashes::l2_block_hash, try_state},
+    transaction::ArbitrumTxTr,
+    try_record_cost,
 };
 
 sol! {
@@ -167,11 +181,11 @@ interface ArbSys {
 pub fn arb_sys_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbSys")),
-        address!("0x0000000000000000000000000000000000000064"),
+        ARB_SYS_ADDRESS,
         precompile_impl!(ArbSysPrecompile),
     )
 }
-struct ArbSysPrecompile;
+pub(super) struct ArbSysPrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbSysPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -194,129 +208,238 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbSysPrecompile {
     fn inner(
         context: &mut CTX,
         input: &[u8],
-        _target_address: &Address,
-        _caller_address: Address,
-        _call_value: U256,
-        _is_static: bool,
+        target_address: &Address,
+        caller_address: Address,
+        call_value: U256,
+        is_static: bool,
         gas_limit: u64,
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
 
-        let selector = selector_or_revert!(gas, input);
-
-        match selector {
-            ArbSys::arbBlockNumberCall::SELECTOR => {
-                let output =
-                    ArbSys::arbBlockNumberCall::abi_encode_returns(&context.block_number());
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::arbChainIDCall::SELECTOR => {
-                let output = ArbSys::arbChainIDCall::abi_encode_returns(&context.chain_id());
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::arbOSVersionCall::SELECTOR => {
-                let output = ArbSys::arbOSVersionCall::abi_encode_returns(&U256::from(
-                    context.cfg().arbos_version() + 55,
-                ));
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::arbBlockHashCall::SELECTOR => {
-                let call = decode_call!(gas, ArbSys::arbBlockHashCall, input);
-
+        precompile_dispatch!(gas, input, {
+            ArbSys::arbBlockNumberCall => |_call| {
+                context.block_number()
+            },
+            ArbSys::arbChainIDCall => |_call| {
+                context.chain_id()
+            },
+            ArbSys::arbOSVersionCall => |_call| {
+                U256::from(context.arbos_version().as_u16() + 55)
+            },
+            ArbSys::arbBlockHashCall => |call| {
                 let current_block = context.block_number().saturating_to::<u64>();
                 let requested_block: u64 = call.arbBlockNum.saturating_to();
 
                 if requested_block >= current_block || requested_block + 256 < current_block {
-                    if context.cfg().arbos_version() >= 33 {
+                    if context
+                        .arbos_version()
+                        .supports(ArbosFeature::InvalidBlockNumberError)
+                    {
                         interpreter_revert!(
                             gas,
-                            ArbSys::InvalidBlockNumber {
+                            custom_error(&ArbSys::InvalidBlockNumber {
                                 requested: call.arbBlockNum,
                                 current: U256::from(current_block),
-                            }
-                            .abi_encode()
+                            })
                         );
                     }
 
-                    interpreter_return!(gas, Bytes::from("invalid block number for ArbBlockHAsh"));
+                    // Nitro's "invalid block number for ArbBlockHAsh" error
+                    interpreter_error!(context, gas);
                 }
 
-                let hash = context.block_hash(requested_block).unwrap_or_default();
-
-                let output = ArbSys::arbBlockHashCall::abi_encode_returns(&hash);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::getStorageGasAvailableCall::SELECTOR => {
-                let output = ArbSys::getStorageGasAvailableCall::abi_encode_returns(&U256::ZERO);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::isTopLevelCallCall::SELECTOR => {
-                let output = ArbSys::isTopLevelCallCall::abi_encode_returns(&false);
+                l2_block_hash(context, requested_block).unwrap_or_default()
+            },
+            ArbSys::getStorageGasAvailableCall => |_call| {
+                U256::ZERO
+            },
+            ArbSys::isTopLevelCallCall => |_call| {
+                context.local().call_depth() <= 2
+            },
+            ArbSys::mapL1SenderContractAddressToL2AliasCall => |call| {
+                apply_l1_to_l2_alias(call.sender)
+            },
+            ArbSys::wasMyCallersAddressAliasedCall => |_call| {
+                was_callers_address_aliased(context)
+            },
+            ArbSys::myCallersAddressWithoutAliasingCall => |_call| {
+                let depth = context.local().call_depth();
+                let mut address = depth
+                    .checked_sub(1)
+                    .and_then(|depth| context.local().caller_at_depth(depth))
+                    .unwrap_or_default();
+                if was_callers_address_aliased(context) {
+                    address = undo_alias(address);
+                }
+                address
+            },
+            ArbSys::sendTxToL1Call => |call| {
+                return send_tx_to_l1(
+                    context,
+                    gas,
+                    *target_address,
+                    caller_address,
+                    call_value,
+                    call.destination,
+                    call.data,
+                );
+            },
+            ArbSys::withdrawEthCall => |call| {
+                return send_tx_to_l1(
+                    context,
+                    gas,
+                    *target_address,
+                    caller_address,
+                    call_value,
+                    call.destination,
+                    Bytes::new(),
+                );
+            },
+            ArbSys::sendMerkleTreeStateCall => |_call| {
+                // Only calls simulated off-chain can come from address zero, keeping the
+                // outbox partials out of on-chain execution.
+                if caller_address != Address::ZERO {
+                    interpreter_error!(context, gas);
+                }
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::mapL1SenderContractAddressToL2AliasCall::SELECTOR => {
-                let call =
-                    decode_call!(gas, ArbSys::mapL1SenderContractAddressToL2AliasCall, input);
+                let (size, root, partials) = {
+                    let mut arb_state = context.arb_state(Some(&mut gas), is_static);
+                    let mut accumulator = arb_state.send_merkle_accumulator();
+                    (
+                        try_state!(gas, accumulator.size()),
+                        try_state!(gas, accumulator.root()),
+                        try_state!(gas, accumulator.partials()),
+                    )
+                };
+
+                ArbSys::sendMerkleTreeStateReturn {
+                    size: U256::from(size),
+                    root,
+                    partials,
+                }
+            },
+        })
+    }
+}
 
-                let aliased_address = remap_l1_address(&call.sender);
+/// Records an L2-to-L1 send in the outbox accumulator, burns the attached value and emits the
+/// `L2ToL1Tx` event along with a `SendMerkleUpdate` for every interior node completed.
+fn send_tx_to_l1<CTX: ArbitrumContextTr>(
+    context: &mut CTX,
+    mut gas: Gas,
+    arb_sys: Address,
+    caller: Address,
+    value: U256,
+    destination: Address,
+    data: Bytes,
+) -> Option<InterpreterResult> {
+    let block_number = context.block_number();
+    let timestamp = context.block().timestamp();
+    let l1_block_number = {
+        let mut arb_state = context.arb_state(Some(&mut gas), false);
+        try_state!(gas, arb_state.blockhashes().l1_block_number().get())
+    };
 
-                let output = ArbSys::mapL1SenderContractAddressToL2AliasCall::abi_encode_returns(
-                    &aliased_address,
-                );
+    let mut preimage = Vec::with_capacity(40 + 4 * 32 + data.len());
+    preimage.extend_from_slice(caller.as_slice());
+    preimage.extend_from_slice(destination.as_slice());
+    preimage.extend_from_slice(&block_number.to_be_bytes::<32>());
+    preimage.extend_from_slice(&U256::from(l1_block_number).to_be_bytes::<32>());
+    preimage.extend_from_slice(&timestamp.to_be_bytes::<32>());
+    preimage.extend_from_slice(&value.to_be_bytes::<32>());
+    preimage.extend_from_slice(&data);
+    try_record_cost!(
+        gas,
+        KECCAK256 + KECCAK256WORD * preimage.len().div_ceil(32) as u64
+    );
+    let send_hash = keccak256(&preimage);
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::wasMyCallersAddressAliasedCall::SELECTOR => {
-                let output = ArbSys::wasMyCallersAddressAliasedCall::abi_encode_returns(&false);
+    let (events, size) = {
+        let mut arb_state = context.arb_state(Some(&mut gas), false);
+        let mut accumulator = arb_state.send_merkle_accumulator();
+        let events = try_state!(gas, accumulator.append(send_hash));
+        (events, try_state!(gas, accumulator.size()))
+    };
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::myCallersAddressWithoutAliasingCall::SELECTOR => {
-                let address = Address::ZERO;
-                let output =
-                    ArbSys::myCallersAddressWithoutAliasingCall::abi_encode_returns(&address);
+    // The value sent to ArbSys leaves L2 and is released on L1 by the outbox.
+    if !value.is_zero() {
+        let Ok(mut account) = context.journal_mut().load_account_with_code_mut(arb_sys) else {
+            interpreter_revert!(gas, revert_message("failed to load ArbSys account"));
+        };
+        let balance = account.data.info.balance;
+        account.data.set_balance(balance.saturating_sub(value));
+    }
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::sendTxToL1Call::SELECTOR => {
-                let output = ArbSys::sendTxToL1Call::abi_encode_returns(&U256::ONE);
+    for event in events {
+        emit_event!(
+            context,
+            Log {
+                address: arb_sys,
+                data: ArbSys::SendMerkleUpdate {
+                    reserved: U256::ZERO,
+                    hash: event.hash,
+                    position: event.position(),
+                }
+                .to_log_data(),
+            },
+            gas
+        );
+    }
 
-                interpreter_return!(gas, Bytes::from(output));
+    let leaf_num = U256::from(size - 1);
+    emit_event!(
+        context,
+        Log {
+            address: arb_sys,
+            data: ArbSys::L2ToL1Tx {
+                caller,
+                destination,
+                hash: U256::from_be_bytes(send_hash.0),
+                position: leaf_num,
+                arbBlockNum: block_number,
+                ethBlockNum: U256::from(l1_block_number),
+                timestamp,
+                callvalue: value,
+                data,
             }
-            ArbSys::withdrawEthCall::SELECTOR => {
-                let output = ArbSys::withdrawEthCall::abi_encode_returns(&U256::ONE);
+            .to_log_data(),
+        },
+        gas
+    );
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            ArbSys::sendMerkleTreeStateCall::SELECTOR => {
-                let output = ArbSys::sendMerkleTreeStateCall::abi_encode_returns(
-                    &ArbSys::sendMerkleTreeStateReturn {
-                        size: U256::ZERO,
-                        root: B256::ZERO,
-                        partials: vec![],
-                    },
-                );
+    let id = if context
+        .arbos_version()
+        .supports(ArbosFeature::SendTxLeafNumber)
+    {
+        leaf_num
+    } else {
+        U256::from_be_bytes(send_hash.0)
+    };
+    let output = ArbSys::sendTxToL1Call::abi_encode_returns(&id);
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown function selector")),
-        }
-    }
+    interpreter_return!(gas, Bytes::from(output));
 }
 
-const ADDRESS_ALIAS_OFFSET: FixedBytes<32> =
-    fixed_bytes!("0x0000000000000000000000001111000000000000000000000000000000001111");
+/// Whether the caller of the contract invoking ArbSys is the tx origin, with depth counting the
+/// precompile frame itself.
+fn is_top_level<CTX: ArbitrumContextTr>(context: &CTX) -> bool {
+    let depth = context.local().call_depth();
+    depth < 2 || context.local().caller_at_depth(depth - 1) == Some(context.tx().caller())
+}
 
-fn remap_l1_address(l1_addr: &Address) -> Address {
-    let mut sum: U256 = U256::from_be_bytes(B256::left_padding_from(l1_addr.as_slice()).0);
-    sum = sum.saturating_add(U256::from_be_bytes(ADDRESS_ALIAS_OFFSET.0));
-    let sum_bytes: [u8; 32] = sum.to_be_bytes();
-    let aliased_bytes = &sum_bytes[12..32];
-    Address::from_slice(aliased_bytes)
+fn was_callers_address_aliased<CTX: ArbitrumContextTr>(context: &CTX) -> bool {
+    let top_level = if context
+        .arbos_version()
+        .supports(ArbosFeature::TopLevelCallerByOrigin)
+    {
+        is_top_level(context)
+    } else {
+        context.local().call_depth() == 2
+    };
+    top_level
+        && (context.tx().from_l1()
+            || matches!(
+                context.tx().tx_type(),
+                ARBITRUM_UNSIGNED_TX_TYPE | ARBITRUM_CONTRACT_TX_TYPE | ARBITRUM_RETRY_TX_TYPE
+            ))
 }
@@ -2,13 +2,16 @@
 
 use crate::{
     ArbitrumContextTr,
+    arbos_version::ArbosFeature,
     config::ArbitrumConfigTr,
-    constants::{COST_SCALAR_PERCENT, MIN_CACHED_GAS_UNITS, MIN_INIT_GAS_UNITS},
+    constants::{ARB_WASM_ADDRESS, COST_SCALAR_PERCENT, MIN_CACHED_GAS_UNITS, MIN_INIT_GAS_UNITS},
     generate_state_mut_table,
-    macros::{emit_event, interpreter_return, interpreter_revert, try_or_halt},
-    precompile_impl,
+    local_context::{ActivatedProgram, ArbitrumLocalContextTr},
+    macros::{emit_event, interpreter_revert, try_or_halt},
+    precompile_dispatch, precompile_impl,
     precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
+        ArbPrecompileLogic, ExtendedPrecompile, StateMutability,
+        errors::{custom_error, revert_message},
     },
     state::{
         ArbState, ArbStateGetter, program::activate_program, try_state, types::StorageBackedTr,
@@ -17,13 +20,12 @@ use crate::{
     try_record_cost,
 };
 
-use alloy_sol_types::{SolCall, SolError, sol};
-use arbutil::evm::ARBOS_VERSION_STYLUS_CHARGING_FIXES;
+use alloy_sol_types::{SolCall, sol};
 use revm::{
     context::{Block, JournalTr},
     interpreter::{Gas, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, Bytes, Log, U256, address, alloy_primitives::IntoLogData},
+    primitives::{Address, Bytes, Log, U256, alloy_primitives::IntoLogData},
 };
 use std::fmt::Debug;
 
@@ -48,6 +50,13 @@ interface IArbWasm {
         bytes32 codehash
     ) external view returns (uint16 version);
 
+    /// @notice Gets the hash of the module the program with codehash was most recently activated into
+    /// @dev Not part of Nitro's ArbWasm, so only known when enabled in the config
+    /// @return moduleHash the module hash (reverts if the program is not active)
+    function moduleHash(
+        bytes32 codehash
+    ) external view returns (bytes32 moduleHash);
+
     /// @notice Extends a program's expiration date.
     /// Reverts if too soon or if the program is not up to date.
     function codehashKeepalive(
@@ -164,11 +173,11 @@ const STYLUS_ACTIVATION_FIXED_COST: u64 = 1659168;
 pub fn arb_wasm_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbWasm")),
-        address!("0x0000000000000000000000000000000000000071"),
+        ARB_WASM_ADDRESS,
         precompile_impl!(ArbWasmPrecompile),
     )
 }
-struct ArbWasmPrecompile;
+pub(super) struct ArbWasmPrecompile;
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -176,6 +185,7 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
             activateProgramCall(Payable),
             stylusVersionCall(View),
             codehashVersionCall(View),
+            moduleHashCall(View),
             codehashKeepaliveCall(Payable),
             codehashAsmSizeCall(View),
             programVersionCall(View),
@@ -196,6 +206,10 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
         }
     };
 
+    fn method_enabled(context: &CTX, selector: [u8; 4]) -> bool {
+        selector != IArbWasm::moduleHashCall::SELECTOR || context.cfg().arb_wasm_module_hash()
+    }
+
     fn inner(
         context: &mut CTX,
         input: &[u8],
@@ -207,12 +221,8 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
 
-        let selector = selector_or_revert!(gas, input);
-
-        match selector {
-            IArbWasm::activateProgramCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasm::activateProgramCall, input);
-
+        precompile_dispatch!(gas, input, {
+            IArbWasm::activateProgramCall => |call| {
                 let params = try_state!(
                     gas,
                     context
@@ -240,7 +250,7 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
 
                     // program is already activated
                     if program_info.version == params.version && !expired {
-                        interpreter_revert!(gas, IArbWasm::ProgramUpToDate {}.abi_encode());
+                        interpreter_revert!(gas, custom_error(&IArbWasm::ProgramUpToDate {}));
                     }
 
                     program_info.cached
@@ -258,10 +268,10 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                 let bytecode = match stylus_code(&bytecode) {
                     Ok(Some(code)) => code,
                     Ok(None) => {
-                        interpreter_revert!(gas, IArbWasm::ProgramNotWasm {}.abi_encode());
+                        interpreter_revert!(gas, custom_error(&IArbWasm::ProgramNotWasm {}));
                     }
                     Err(err) => {
-                        interpreter_revert!(gas, err);
+                        interpreter_revert!(gas, revert_message(String::from_utf8_lossy(&err)));
                     }
                 };
 
@@ -272,11 +282,10 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                 if call_value < data_fee {
                     interpreter_revert!(
                         gas,
-                        IArbWasm::ProgramInsufficientValue {
+                        custom_error(&IArbWasm::ProgramInsufficientValue {
                             have: call_value,
                             want: data_fee
-                        }
-                        .abi_encode()
+                        })
                     );
                 }
 
@@ -328,17 +337,20 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                     },
                     gas
                 );
-
-                let output = IArbWasm::activateProgramCall::abi_encode_returns(
-                    &IArbWasm::activateProgramReturn {
-                        version: activation_info.version,
-                        dataFee: data_fee,
-                    },
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::stylusVersionCall::SELECTOR => {
+                context.local_mut().record_activation(ActivatedProgram {
+                    code_hash,
+                    program: call.program,
+                    version: activation_info.version,
+                    data_fee,
+                    auto_activated: false,
+                });
+
+                IArbWasm::activateProgramReturn {
+                    version: activation_info.version,
+                    dataFee: data_fee,
+                }
+            },
+            IArbWasm::stylusVersionCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -348,13 +360,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output = IArbWasm::stylusVersionCall::abi_encode_returns(&params.version);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::codehashVersionCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasm::codehashVersionCall, input);
-
+                params.version
+            },
+            IArbWasm::codehashVersionCall => |call| {
                 let params = try_state!(
                     gas,
                     context
@@ -372,14 +380,36 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get_active_program(&params, &call.codehash)
                 );
 
-                let output =
-                    IArbWasm::codehashVersionCall::abi_encode_returns(&program_info.version);
+                program_info.version
+            },
+            IArbWasm::moduleHashCall => |call| {
+                let params = try_state!(
+                    gas,
+                    context
+                        .arb_state(Some(&mut gas), is_static)
+                        .programs()
+                        .stylus_params()
+                        .get()
+                );
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::codehashKeepaliveCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasm::codehashKeepaliveCall, input);
+                try_state!(
+                    gas,
+                    context
+                        .arb_state(Some(&mut gas), is_static)
+                        .programs()
+                        .get_active_program(&params, &call.codehash)
+                );
 
+                try_state!(
+                    gas,
+                    context
+                        .arb_state(Some(&mut gas), is_static)
+                        .programs()
+                        .module_hash(&call.codehash)
+                        .get()
+                )
+            },
+            IArbWasm::codehashKeepaliveCall => |call| {
                 let params = try_state!(
                     gas,
                     context
@@ -400,21 +430,19 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                 if program_info.age < params.keepalive_days as u32 * 24 * 60 * 60 {
                     interpreter_revert!(
                         gas,
-                        IArbWasm::ProgramKeepaliveTooSoon {
+                        custom_error(&IArbWasm::ProgramKeepaliveTooSoon {
                             ageInSeconds: program_info.age as u64
-                        }
-                        .abi_encode()
+                        })
                     );
                 }
 
                 if program_info.version != params.version {
                     interpreter_revert!(
                         gas,
-                        IArbWasm::ProgramNeedsUpgrade {
+                        custom_error(&IArbWasm::ProgramNeedsUpgrade {
                             version: program_info.version,
                             stylusVersion: params.version,
-                        }
-                        .abi_encode()
+                        })
                     );
                 }
 
@@ -434,11 +462,10 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                 if call_value < U256::from(data_fee) {
                     interpreter_revert!(
                         gas,
-                        IArbWasm::ProgramInsufficientValue {
+                        custom_error(&IArbWasm::ProgramInsufficientValue {
                             have: call_value,
                             want: U256::from(data_fee),
-                        }
-                        .abi_encode()
+                        })
                     );
                 }
 
@@ -501,11 +528,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                     gas
                 );
 
-                interpreter_return!(gas);
-            }
-            IArbWasm::codehashAsmSizeCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasm::codehashAsmSizeCall, input);
-
+                IArbWasm::codehashKeepaliveReturn {}
+            },
+            IArbWasm::codehashAsmSizeCall => |call| {
                 let params = try_state!(
                     gas,
                     context
@@ -523,15 +548,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get_active_program(&params, &call.codehash)
                 );
 
-                let output = IArbWasm::codehashAsmSizeCall::abi_encode_returns(
-                    &program_info.asm_estimated_kb,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::programVersionCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasm::programVersionCall, input);
-
+                program_info.asm_estimated_kb
+            },
+            IArbWasm::programVersionCall => |call| {
                 let params = try_state!(
                     gas,
                     context
@@ -556,14 +575,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get_active_program(&params, &code_hash)
                 );
 
-                let output =
-                    IArbWasm::programVersionCall::abi_encode_returns(&program_info.version);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::programInitGasCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasm::programInitGasCall, input);
-
+                program_info.version
+            },
+            IArbWasm::programInitGasCall => |call| {
                 let params = try_state!(
                     gas,
                     context
@@ -599,18 +613,12 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                     params.init_cost_scalar,
                 );
 
-                let output = IArbWasm::programInitGasCall::abi_encode_returns(
-                    &IArbWasm::programInitGasReturn {
-                        gas: init_gas,
-                        gasWhenCached: cached_gas,
-                    },
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::programMemoryFootprintCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasm::programMemoryFootprintCall, input);
-
+                IArbWasm::programInitGasReturn {
+                    gas: init_gas,
+                    gasWhenCached: cached_gas,
+                }
+            },
+            IArbWasm::programMemoryFootprintCall => |call| {
                 let params = try_state!(
                     gas,
                     context
@@ -635,15 +643,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get_active_program(&params, &code_hash)
                 );
 
-                let output = IArbWasm::programMemoryFootprintCall::abi_encode_returns(
-                    &program_info.footprint,
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::programTimeLeftCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasm::programTimeLeftCall, input);
-
+                program_info.footprint
+            },
+            IArbWasm::programTimeLeftCall => |call| {
                 let params = try_state!(
                     gas,
                     context
@@ -668,12 +670,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get_active_program(&params, &code_hash)
                 );
 
-                let output =
-                    IArbWasm::programTimeLeftCall::abi_encode_returns(&(program_info.age as u64));
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::inkPriceCall::SELECTOR => {
+                program_info.age as u64
+            },
+            IArbWasm::inkPriceCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -683,11 +682,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output = IArbWasm::inkPriceCall::abi_encode_returns(&params.ink_price);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::maxStackDepthCall::SELECTOR => {
+                params.ink_price
+            },
+            IArbWasm::maxStackDepthCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -697,12 +694,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output =
-                    IArbWasm::maxStackDepthCall::abi_encode_returns(&params.max_stack_depth);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::freePagesCall::SELECTOR => {
+                params.max_stack_depth
+            },
+            IArbWasm::freePagesCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -712,11 +706,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output = IArbWasm::freePagesCall::abi_encode_returns(&params.free_pages);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::pageGasCall::SELECTOR => {
+                params.free_pages
+            },
+            IArbWasm::pageGasCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -726,11 +718,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output = IArbWasm::pageGasCall::abi_encode_returns(&params.page_gas);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::pageRampCall::SELECTOR => {
+                params.page_gas
+            },
+            IArbWasm::pageRampCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -740,11 +730,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output = IArbWasm::pageRampCall::abi_encode_returns(&params.page_ramp);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::pageLimitCall::SELECTOR => {
+                params.page_ramp
+            },
+            IArbWasm::pageLimitCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -754,11 +742,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output = IArbWasm::pageLimitCall::abi_encode_returns(&params.page_limit);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::minInitGasCall::SELECTOR => {
+                params.page_limit
+            },
+            IArbWasm::minInitGasCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -768,19 +754,19 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                if context.cfg().arbos_version() < ARBOS_VERSION_STYLUS_CHARGING_FIXES as u16 {
+                if !context
+                    .arbos_version()
+                    .supports(ArbosFeature::StylusChargingFixes)
+                {
                     interpreter_revert!(gas);
                 }
 
-                let output =
-                    IArbWasm::minInitGasCall::abi_encode_returns(&IArbWasm::minInitGasReturn {
-                        gas: params.min_init_gas as u64 * MIN_INIT_GAS_UNITS,
-                        cached: params.min_cached_init_gas as u64 * MIN_CACHED_GAS_UNITS,
-                    });
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::initCostScalarCall::SELECTOR => {
+                IArbWasm::minInitGasReturn {
+                    gas: params.min_init_gas as u64 * MIN_INIT_GAS_UNITS,
+                    cached: params.min_cached_init_gas as u64 * MIN_CACHED_GAS_UNITS,
+                }
+            },
+            IArbWasm::initCostScalarCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -790,13 +776,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output = IArbWasm::initCostScalarCall::abi_encode_returns(
-                    &(params.init_cost_scalar as u64 * COST_SCALAR_PERCENT),
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::expiryDaysCall::SELECTOR => {
+                params.init_cost_scalar as u64 * COST_SCALAR_PERCENT
+            },
+            IArbWasm::expiryDaysCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -806,11 +788,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output = IArbWasm::expiryDaysCall::abi_encode_returns(&params.expiry_days);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::keepaliveDaysCall::SELECTOR => {
+                params.expiry_days
+            },
+            IArbWasm::keepaliveDaysCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -820,12 +800,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output =
-                    IArbWasm::keepaliveDaysCall::abi_encode_returns(&params.keepalive_days);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasm::blockCacheSizeCall::SELECTOR => {
+                params.keepalive_days
+            },
+            IArbWasm::blockCacheSizeCall => |_call| {
                 let params = try_state!(
                     gas,
                     context
@@ -835,13 +812,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmPrecompile {
                         .get()
                 );
 
-                let output =
-                    IArbWasm::blockCacheSizeCall::abi_encode_returns(&params.block_cache_size);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown function selector")),
-        }
+                params.block_cache_size
+            },
+        })
     }
 }
 
@@ -853,7 +826,7 @@ mod tests {
         Database, DatabaseCommit, Journal,
         context::{BlockEnv, ContextTr},
         database::{CacheDB, EmptyDB, EmptyDBTyped},
-        primitives::keccak256,
+        primitives::{address, keccak256},
         state::Bytecode,
     };
     use stylus::brotli;
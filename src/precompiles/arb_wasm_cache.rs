@@ -1,17 +1,21 @@
-use alloy_sol_types::{SolCall, SolError, sol};
+use alloy_sol_types::sol;
 use revm::{
     context::JournalTr,
     interpreter::{Gas, InterpreterResult},
     precompile::PrecompileId,
-    primitives::{Address, Bytes, Log, U256, address, alloy_primitives::IntoLogData},
+    primitives::{Address, Log, U256, alloy_primitives::IntoLogData},
 };
 
 use crate::{
-    ArbitrumContextTr, generate_state_mut_table,
-    macros::{emit_event, interpreter_return, interpreter_revert},
-    precompile_impl,
+    ArbitrumContextTr,
+    constants::ARB_WASM_CACHE_ADDRESS,
+    generate_state_mut_table,
+    local_context::ArbitrumLocalContextTr,
+    macros::{emit_event, interpreter_revert},
+    precompile_dispatch, precompile_impl,
     precompiles::{
-        ArbPrecompileLogic, ExtendedPrecompile, StateMutability, decode_call, selector_or_revert,
+        ArbPrecompileLogic, ExtendedPrecompile, StateMutability,
+        errors::{custom_error, revert_message},
     },
     state::{ArbState, ArbStateGetter, ArbosStateError, try_state, types::StorageBackedTr},
     try_record_cost,
@@ -73,12 +77,12 @@ interface IArbWasmCache {
 pub fn arb_wasm_cache_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
     ExtendedPrecompile::new(
         PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbWasmCache")),
-        address!("0x0000000000000000000000000000000000000072"),
+        ARB_WASM_CACHE_ADDRESS,
         precompile_impl!(ArbWasmCache),
     )
 }
 
-struct ArbWasmCache {}
+pub(super) struct ArbWasmCache {}
 
 impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmCache {
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
@@ -103,48 +107,34 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmCache {
     ) -> Option<InterpreterResult> {
         let mut gas = Gas::new(gas_limit);
 
-        let selector = selector_or_revert!(gas, input);
-
-        match selector {
-            IArbWasmCache::isCacheManagerCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasmCache::isCacheManagerCall, input);
+        precompile_dispatch!(gas, input, {
+            IArbWasmCache::isCacheManagerCall => |call| {
                 let manager = call.manager;
 
-                let is_manager = try_state!(
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .programs()
                         .cache_managers()
                         .contains(manager)
-                );
-
-                let output = IArbWasmCache::isCacheManagerCall::abi_encode_returns(&is_manager);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasmCache::allCacheManagersCall::SELECTOR => {
-                let _call = decode_call!(gas, IArbWasmCache::allCacheManagersCall, input);
-
-                let managers = try_state!(
+                )
+            },
+            IArbWasmCache::allCacheManagersCall => |_call| {
+                try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), is_static)
                         .programs()
                         .cache_managers()
                         .all()
-                );
-
-                let output = IArbWasmCache::allCacheManagersCall::abi_encode_returns(&managers);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasmCache::cacheCodehashCall::SELECTOR => {
+                )
+            },
+            IArbWasmCache::cacheCodehashCall => |call| {
                 if !try_state!(gas, has_access(context, caller_address, &mut gas)) {
                     interpreter_revert!(gas);
                 }
 
-                let call = decode_call!(gas, IArbWasmCache::cacheCodehashCall, input);
                 let codehash = call.codehash;
 
                 let params = try_state!(
@@ -167,41 +157,33 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmCache {
                 } else {
                     interpreter_revert!(
                         gas,
-                        IArbWasmCache::ProgramNeedsUpgrade {
+                        custom_error(&IArbWasmCache::ProgramNeedsUpgrade {
                             version: 0,
                             stylusVersion: params.version
-                        }
-                        .abi_encode()
+                        })
                     );
                 };
 
-                let output = IArbWasmCache::cacheCodehashCall::abi_encode_returns(
-                    &IArbWasmCache::cacheCodehashReturn {},
-                );
+                // no-op when already cached
+                if !program_info.cached {
+                    program_info.cached = true;
 
-                if program_info.cached {
-                    // already cached, no-op
-                    interpreter_return!(gas, Bytes::from(output));
+                    try_state!(
+                        gas,
+                        context
+                            .arb_state(Some(&mut gas), is_static)
+                            .programs()
+                            .save_program_info(&codehash, &program_info)
+                    );
                 }
 
-                program_info.cached = true;
-
-                try_state!(
-                    gas,
-                    context
-                        .arb_state(Some(&mut gas), is_static)
-                        .programs()
-                        .save_program_info(&codehash, &program_info)
-                );
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasmCache::cacheProgramCall::SELECTOR => {
+                IArbWasmCache::cacheCodehashReturn {}
+            },
+            IArbWasmCache::cacheProgramCall => |call| {
                 if !try_state!(gas, has_access(context, caller_address, &mut gas)) {
                     interpreter_revert!(gas);
                 }
 
-                let call = decode_call!(gas, IArbWasmCache::cacheProgramCall, input);
                 let addr = call.addr;
 
                 let params = try_state!(
@@ -226,59 +208,52 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmCache {
                         .get_active_program(&params, &code_hash)
                 );
 
-                let output = IArbWasmCache::cacheProgramCall::abi_encode_returns(
-                    &IArbWasmCache::cacheProgramReturn {},
-                );
-
-                if program_info.cached {
-                    // already cached, no-op
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-
-                // emit event cost
-                emit_event!(
-                    context,
-                    Log {
-                        address: *target_address,
-                        data: IArbWasmCache::UpdateProgramCache {
-                            manager: caller_address,
-                            codehash: code_hash,
-                            cached: true
-                        }
-                        .into_log_data()
-                    },
-                    gas
-                );
+                // no-op when already cached
+                if !program_info.cached {
+                    // emit event cost
+                    emit_event!(
+                        context,
+                        Log {
+                            address: *target_address,
+                            data: IArbWasmCache::UpdateProgramCache {
+                                manager: caller_address,
+                                codehash: code_hash,
+                                cached: true
+                            }
+                            .into_log_data()
+                        },
+                        gas
+                    );
 
-                try_record_cost!(gas, program_info.init_cost as u64);
+                    try_record_cost!(gas, program_info.init_cost as u64);
 
-                try_state!(
-                    gas,
-                    context
-                        .arb_state(Some(&mut gas), is_static)
-                        .programs()
-                        .module_hash(&code_hash)
-                        .get()
-                );
+                    try_state!(
+                        gas,
+                        context
+                            .arb_state(Some(&mut gas), is_static)
+                            .programs()
+                            .module_hash(&code_hash)
+                            .get()
+                    );
 
-                program_info.cached = true;
+                    program_info.cached = true;
 
-                try_state!(
-                    gas,
-                    context
-                        .arb_state(Some(&mut gas), is_static)
-                        .programs()
-                        .save_program_info(&code_hash, &program_info)
-                );
+                    try_state!(
+                        gas,
+                        context
+                            .arb_state(Some(&mut gas), is_static)
+                            .programs()
+                            .save_program_info(&code_hash, &program_info)
+                    );
+                }
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasmCache::evictCodehashCall::SELECTOR => {
+                IArbWasmCache::cacheProgramReturn {}
+            },
+            IArbWasmCache::evictCodehashCall => |call| {
                 if !try_state!(gas, has_access(context, caller_address, &mut gas)) {
                     interpreter_revert!(gas);
                 }
 
-                let call = decode_call!(gas, IArbWasmCache::evictCodehashCall, input);
                 let code_hash = call.codehash;
 
                 let _ = try_state!(
@@ -299,58 +274,52 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmCache {
                 )
                 .unwrap_or_default();
 
-                let output = IArbWasmCache::evictCodehashCall::abi_encode_returns(
-                    &IArbWasmCache::evictCodehashReturn {},
-                );
-
-                if !program_info.cached {
-                    // if not cached, no-op
-                    interpreter_return!(gas, Bytes::from(output));
-                }
-
-                // emit event cost
-                emit_event!(
-                    context,
-                    Log {
-                        address: *target_address,
-                        data: IArbWasmCache::UpdateProgramCache {
-                            manager: caller_address,
-                            codehash: code_hash,
-                            cached: false
-                        }
-                        .into_log_data()
-                    },
-                    gas
-                );
+                // no-op when not cached
+                if program_info.cached {
+                    // emit event cost
+                    emit_event!(
+                        context,
+                        Log {
+                            address: *target_address,
+                            data: IArbWasmCache::UpdateProgramCache {
+                                manager: caller_address,
+                                codehash: code_hash,
+                                cached: false
+                            }
+                            .into_log_data()
+                        },
+                        gas
+                    );
 
-                try_record_cost!(gas, program_info.init_cost as u64);
+                    try_record_cost!(gas, program_info.init_cost as u64);
 
-                try_state!(
-                    gas,
-                    context
-                        .arb_state(Some(&mut gas), is_static)
-                        .programs()
-                        .module_hash(&code_hash)
-                        .get()
-                );
+                    try_state!(
+                        gas,
+                        context
+                            .arb_state(Some(&mut gas), is_static)
+                            .programs()
+                            .module_hash(&code_hash)
+                            .get()
+                    );
 
-                program_info.cached = false;
+                    program_info.cached = false;
 
-                try_state!(
-                    gas,
-                    context
-                        .arb_state(Some(&mut gas), is_static)
-                        .programs()
-                        .save_program_info(&code_hash, &program_info)
-                );
+                    try_state!(
+                        gas,
+                        context
+                            .arb_state(Some(&mut gas), is_static)
+                            .programs()
+                            .save_program_info(&code_hash, &program_info)
+                    );
+                    context.local().program_cache().unpin(&code_hash);
+                }
 
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            IArbWasmCache::codehashIsCachedCall::SELECTOR => {
-                let call = decode_call!(gas, IArbWasmCache::codehashIsCachedCall, input);
+                IArbWasmCache::evictCodehashReturn {}
+            },
+            IArbWasmCache::codehashIsCachedCall => |call| {
                 let codehash = call.codehash;
 
-                let is_cached = if let Some(program_info) = try_state!(
+                if let Some(program_info) = try_state!(
                     gas,
                     context
                         .arb_state(Some(&mut gas), true)
@@ -360,14 +329,9 @@ impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbWasmCache {
                     program_info.cached
                 } else {
                     false
-                };
-
-                let output = IArbWasmCache::codehashIsCachedCall::abi_encode_returns(&is_cached);
-
-                interpreter_return!(gas, Bytes::from(output));
-            }
-            _ => interpreter_revert!(gas, Bytes::from("Unknown selector")),
-        }
+                }
+            },
+        }, unknown => revert_message("Unknown selector"))
     }
 }
 
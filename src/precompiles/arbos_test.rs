@@ -0,0 +1,73 @@
+use alloy_sol_types::sol;
+use revm::{
+    interpreter::{Gas, InterpreterResult},
+    precompile::PrecompileId,
+    primitives::{Address, U256},
+};
+
+use crate::{
+    ArbitrumContextTr,
+    constants::ARBOS_TEST_ADDRESS,
+    generate_state_mut_table,
+    macros::interpreter_error,
+    precompile_dispatch, precompile_impl,
+    precompiles::{ArbPrecompileLogic, ExtendedPrecompile, StateMutability},
+};
+
+sol! {
+/// @title Deprecated - Provides a method of burning arbitrary amounts of gas
+/// @notice This exists for historical reasons. Pre-Nitro, `ArbosTest` had additional methods only the zero address could call.
+/// These have been removed since users don't use them and calls to missing methods revert.
+/// Precompiled contract that exists in every Arbitrum chain at 0x0000000000000000000000000000000000000069.
+interface ArbosTest {
+    /// @notice Unproductively burns the amount of L2 ArbGas
+    function burnArbGas(uint256 gasAmount) external pure;
+}
+
+}
+
+pub fn arbos_test_precompile<CTX: ArbitrumContextTr>() -> ExtendedPrecompile<CTX> {
+    ExtendedPrecompile::new(
+        PrecompileId::Custom(std::borrow::Cow::Borrowed("ArbosTest")),
+        ARBOS_TEST_ADDRESS,
+        precompile_impl!(ArbosTestPrecompile),
+    )
+}
+
+pub(super) struct ArbosTestPrecompile;
+
+impl<CTX: ArbitrumContextTr> ArbPrecompileLogic<CTX> for ArbosTestPrecompile {
+    const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
+        ArbosTest => {
+            burnArbGasCall(Pure),
+        }
+    };
+
+    fn inner(
+        context: &mut CTX,
+        input: &[u8],
+        _target_address: &Address,
+        _caller_address: Address,
+        _call_value: U256,
+        _is_static: bool,
+        gas_limit: u64,
+    ) -> Option<InterpreterResult> {
+        let mut gas = Gas::new(gas_limit);
+
+        precompile_dispatch!(gas, input, {
+            ArbosTest::burnArbGasCall => |call| {
+                let Ok(amount) = u64::try_from(call.gasAmount) else {
+                    interpreter_error!(context, gas);
+                };
+
+                // Nitro ignores the failed burn, so burning more than is left succeeds with
+                // nothing remaining.
+                if !gas.record_cost(amount) {
+                    gas.spend_all();
+                }
+
+                ArbosTest::burnArbGasReturn {}
+            },
+        })
+    }
+}
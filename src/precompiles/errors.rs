@@ -0,0 +1,70 @@
+//! Revert data of the Arbitrum precompiles.
+//!
+//! Precompiles revert either with a custom error declared in their interface or with a message
+//! encoded as Solidity's `Error(string)`, the way `revert("...")` encodes it, so callers and
+//! tooling decode precompile reverts exactly as they decode those of contracts.
+
+use alloy_sol_types::{Revert, SolError};
+use revm::primitives::Bytes;
+
+use crate::state::ArbosStateError;
+
+/// `message` encoded as Solidity's `Error(string)`.
+pub fn revert_message(message: impl Into<String>) -> Bytes {
+    Revert {
+        reason: message.into(),
+    }
+    .abi_encode()
+    .into()
+}
+
+/// A custom error declared in a precompile interface, encoded with its selector.
+pub fn custom_error<E: SolError>(error: &E) -> Bytes {
+    error.abi_encode().into()
+}
+
+/// Revert data for a failed read or write of ArbOS state: the custom error ArbWasm declares for
+/// Stylus program errors, or the error's message for any other.
+pub fn state_error(error: ArbosStateError) -> Bytes {
+    match error {
+        ArbosStateError::ProgramNotActivated
+        | ArbosStateError::ProgramNeedsUpgrade(..)
+        | ArbosStateError::ProgramExpired(_) => error.into(),
+        other => revert_message(other.to_string()),
+    }
+}
+
+/// The message of revert data encoded as `Error(string)`, or `None` for a custom error or any
+/// other data.
+pub fn decode_revert_message(output: &[u8]) -> Option<String> {
+    Revert::abi_decode(output).ok().map(|revert| revert.reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_round_trip() {
+        let output = revert_message("must be called by chain owner");
+        assert_eq!(&output[..4], Revert::SELECTOR);
+        assert_eq!(
+            decode_revert_message(&output).as_deref(),
+            Some("must be called by chain owner")
+        );
+        assert_eq!(decode_revert_message(b"raw"), None);
+    }
+
+    #[test]
+    fn program_errors_stay_custom() {
+        let output = state_error(ArbosStateError::ProgramNotActivated);
+        assert_eq!(output, Bytes::from(ArbosStateError::ProgramNotActivated));
+        assert_eq!(decode_revert_message(&output), None);
+
+        let output = state_error(ArbosStateError::StateChangeDuringStaticCall);
+        assert_eq!(
+            decode_revert_message(&output),
+            Some(ArbosStateError::StateChangeDuringStaticCall.to_string())
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Selector-level fuzzing of the Arbitrum precompiles.
+//!
+//! Every selector in each precompile's state mutability table, plus unknown ones, is called with
+//! generated calldata: ABI-shaped words of zeroes, small integers, addresses and extremes, raw
+//! bytes, and truncated selectors, statically or not, by a chain owner or a stranger, under
+//! varying gas limits. Calls are made through the [fuzzing harness](super::fuzzing), so no call
+//! may panic or fail fatally and each must keep the invariants it checks, and also:
+//!
+//! * unknown selectors succeed with empty output when their arguments are paid for;
+//! * successful pure and view calls return the same when repeated.
+//!
+//! The target is ignored by default. Run it with `cargo test precompiles::fuzz -- --ignored`,
+//! setting `PRECOMPILE_FUZZ_ROUNDS` for longer runs; failures report the seed to replay.
+
+use alloy_sol_types::SolCall;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use revm::{
+    interpreter::gas::VERYLOW,
+    primitives::{Address, U256, hex},
+};
+
+use super::{
+    ArbPrecompileLogic, Precompile, StateMutability, arb_address_table, arb_aggregator, arb_debug,
+    arb_gas_info, arb_info, arb_native_token_manager, arb_owner, arb_owner_public,
+    arb_retryable_tx, arb_statistics, arb_sys, arb_wasm, arb_wasm_cache, arbos_test,
+    fuzzing::{FuzzCall, FuzzContext, FuzzInput, OWNER, setup},
+};
+use crate::config::ArbitrumConfig;
+
+const GAS_LIMITS: [u64; 6] = [0, 100, 800, 2_100, 30_000, 1_000_000];
+const DEFAULT_ROUNDS: u64 = 16;
+/// Most ABI words generated for a call.
+const MAX_WORDS: usize = 8;
+
+/// A precompile and the selectors it dispatches.
+struct Target {
+    precompile: Precompile<FuzzContext>,
+    table: &'static [([u8; 4], StateMutability)],
+}
+
+macro_rules! targets {
+    ($($module:ident::$constructor:ident => $logic:ty),* $(,)?) => {
+        vec![$(Target {
+            precompile: Precompile::Extended($module::$constructor::<FuzzContext>()),
+            table: <$logic as ArbPrecompileLogic<FuzzContext>>::STATE_MUT_TABLE,
+        }),*]
+    };
+}
+
+fn targets() -> Vec<Target> {
+    targets![
+        arb_address_table::arb_address_table_precompile => arb_address_table::ArbAddressTablePrecompile,
+        arb_aggregator::arb_aggregator_precompile => arb_aggregator::ArbAggregatorPrecompile,
+        arb_debug::arb_debug_precompile => arb_debug::ArbDebugPrecompile,
+        arb_gas_info::arb_gas_info_precompile => arb_gas_info::ArbGasInfoPrecompile,
+        arb_info::arb_info_precompile => arb_info::ArbInfoPrecompile,
+        arb_native_token_manager::arb_native_token_manager_precompile => arb_native_token_manager::ArbNativeTokenManagerPrecompile,
+        arb_owner::arb_owner_precompile => arb_owner::ArbOwnerPrecompile,
+        arb_owner_public::arb_owner_public_precompile => arb_owner_public::ArbOwnerPublicPrecompile,
+        arb_retryable_tx::arb_retryable_tx_precompile => arb_retryable_tx::ArbRetryableTxPrecompile,
+        arb_statistics::arb_statistics_precompile => arb_statistics::ArbStatisticsPrecompile,
+        arb_sys::arb_sys_precompile => arb_sys::ArbSysPrecompile,
+        arb_wasm::arb_wasm_precompile => arb_wasm::ArbWasmPrecompile,
+        arb_wasm_cache::arb_wasm_cache_precompile => arb_wasm_cache::ArbWasmCache,
+        arbos_test::arbos_test_precompile => arbos_test::ArbosTestPrecompile,
+    ]
+}
+
+/// Context with initialized ArbOS state, in debug mode so that ArbDebug is reachable.
+fn debug_context() -> FuzzContext {
+    setup(ArbitrumConfig {
+        debug_mode: true,
+        ..Default::default()
+    })
+}
+
+/// Calldata for `selector`: truncated, followed by raw bytes, or followed by ABI words.
+fn calldata(rng: &mut StdRng, selector: [u8; 4]) -> Vec<u8> {
+    match rng.gen_range(0..8) {
+        0 => selector[..rng.gen_range(0..4)].to_vec(),
+        1 => {
+            let mut input = selector.to_vec();
+            let mut tail = vec![0u8; rng.gen_range(0..200)];
+            rng.fill(&mut tail[..]);
+            input.extend_from_slice(&tail);
+            input
+        }
+        _ => {
+            let mut data = [0u8; MAX_WORDS * 33];
+            rng.fill(&mut data[..]);
+            let mut words = FuzzInput::new(&data);
+            let mut input = selector.to_vec();
+            for _ in 0..rng.gen_range(0..MAX_WORDS) {
+                input.extend_from_slice(&words.word());
+            }
+            input
+        }
+    }
+}
+
+/// Call `target` with generated calldata for `selector` and check the dispatcher's invariants.
+fn fuzz_call(
+    context: &mut FuzzContext,
+    rng: &mut StdRng,
+    seed: u64,
+    target: &Target,
+    selector: [u8; 4],
+) {
+    let input = calldata(rng, selector);
+    let purity = target
+        .table
+        .iter()
+        .find(|(known, _)| *known == selector)
+        .map(|(_, purity)| *purity);
+    let is_static = rng.gen_bool(0.3);
+    let gas_limit = GAS_LIMITS[rng.gen_range(0..GAS_LIMITS.len())];
+    let caller = if rng.gen_bool(0.5) {
+        OWNER
+    } else {
+        Address::from(rng.r#gen::<[u8; 20]>())
+    };
+    let value = if purity == Some(StateMutability::Payable) && !is_static {
+        U256::from(rng.gen_range(0..3u64))
+    } else {
+        U256::ZERO
+    };
+    let address = *target.precompile.address();
+    let call = FuzzCall {
+        precompile: &target.precompile,
+        input: &input,
+        caller,
+        value,
+        is_static,
+    };
+    let describe = || {
+        format!(
+            "seed {seed}: {address} input 0x{} static {is_static} gas {gas_limit}",
+            hex::encode(&input)
+        )
+    };
+
+    let result = call.checked(context, gas_limit, describe);
+    // Unknown selectors only pay for their arguments
+    let args_cost = VERYLOW * (input.len() as u64).saturating_sub(4).div_ceil(32);
+    if purity.is_none() && input.len() >= 4 && gas_limit >= args_cost {
+        assert!(result.result.is_ok(), "{}", describe());
+        assert!(result.output.is_empty(), "{}", describe());
+    }
+    if result.result.is_ok() && purity.is_some_and(|purity| purity <= StateMutability::View) {
+        let repeated = call.checked(context, gas_limit, describe);
+        assert_eq!(repeated.result, result.result, "{}", describe());
+        assert_eq!(repeated.output, result.output, "{}", describe());
+    }
+}
+
+#[test]
+#[ignore = "fuzz target, run with --ignored"]
+fn fuzz_precompile_selectors() {
+    let rounds = std::env::var("PRECOMPILE_FUZZ_ROUNDS")
+        .ok()
+        .and_then(|rounds| rounds.parse().ok())
+        .unwrap_or(DEFAULT_ROUNDS);
+    let targets = targets();
+
+    for seed in 0..rounds {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut context = debug_context();
+        for target in &targets {
+            for (selector, _) in target.table {
+                // ArbDebug's panic method panics by design
+                if *selector == arb_debug::ArbDebug::panicCall::SELECTOR {
+                    continue;
+                }
+                fuzz_call(&mut context, &mut rng, seed, target, *selector);
+            }
+            let unknown = rng.r#gen::<[u8; 4]>();
+            fuzz_call(&mut context, &mut rng, seed, target, unknown);
+        }
+    }
+}
+
+#[test]
+fn every_precompile_is_a_fuzz_target() {
+    let registry = super::ArbitrumPrecompileProvider::<FuzzContext>::registry(Default::default());
+    let targets = targets();
+    let extended = registry
+        .address_iter()
+        .filter(|address| matches!(registry.get(address), Some(Precompile::Extended(_))))
+        .count();
+    assert_eq!(targets.len(), extended);
+    for target in &targets {
+        assert!(registry.contains(target.precompile.address()));
+        assert!(!target.table.is_empty());
+    }
+}
@@ -0,0 +1,625 @@
+//! Harness for fuzzing the Arbitrum precompiles, shared by the cargo-fuzz target in `fuzz/` and
+//! the selector-level fuzz run by the tests.
+//!
+//! [`fuzz_precompiles`] fuzzes ArbOwner, ArbWasm, ArbRetryableTx and ArbSys from raw bytes.
+//! Calldata is generated per method from the signatures of the `sol!` interfaces, so that it
+//! always decodes and reaches the method's logic rather than stopping at the dispatcher. Each
+//! call is made twice from the same state, with a gas limit and a larger one. Every call made
+//! through the harness must keep:
+//!
+//! * a result is always returned, charging no more than the gas limit;
+//! * failed calls leave no trace in the journal or the logs;
+//! * static calls emit no logs.
+//!
+//! [`fuzz_precompiles`] additionally checks that:
+//!
+//! * a call succeeding with less gas succeeds with more;
+//! * pure and view calls succeeding with less gas return the same with more.
+//!
+//! Every function here is deterministic in its input, so a crashing input replays as is.
+
+use std::convert::Infallible;
+
+use alloy_sol_types::SolCall;
+use revm::{
+    Journal,
+    context::{BlockEnv, ContextTr, JournalTr},
+    database::EmptyDBTyped,
+    interpreter::InterpreterResult,
+    primitives::{Address, U256, address, hex},
+};
+
+use super::{
+    ArbPrecompileLogic, Precompile, StateMutability, arb_owner, arb_retryable_tx, arb_sys,
+    arb_wasm, journal_position,
+};
+use crate::{
+    ArbitrumContext, ArbitrumTransaction,
+    config::ArbitrumConfig,
+    local_context::ArbitrumLocalContext,
+    state::{ArbState, ArbStateGetter, ArbosStateParams},
+};
+
+pub(crate) type FuzzContext = ArbitrumContext<EmptyDBTyped<Infallible>>;
+
+/// Chain owner of the fuzzed ArbOS state.
+pub const OWNER: Address = address!("0x00000000000000000000000000000000000000f0");
+/// ArbOS versions calls are made under, covering the upgrades the fuzzed precompiles change at.
+const ARBOS_VERSIONS: [u16; 6] = [11, 20, 30, 32, 41, 50];
+/// Most elements generated for a dynamic array, bytes or string.
+const MAX_LEN: usize = 8;
+
+/// A precompile covered by [`fuzz_precompiles`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FuzzTarget {
+    ArbOwner,
+    ArbWasm,
+    ArbRetryableTx,
+    ArbSys,
+}
+
+impl FuzzTarget {
+    pub const ALL: [FuzzTarget; 4] = [
+        FuzzTarget::ArbOwner,
+        FuzzTarget::ArbWasm,
+        FuzzTarget::ArbRetryableTx,
+        FuzzTarget::ArbSys,
+    ];
+
+    fn precompile(self) -> Precompile<FuzzContext> {
+        Precompile::Extended(match self {
+            FuzzTarget::ArbOwner => arb_owner::arb_owner_precompile(),
+            FuzzTarget::ArbWasm => arb_wasm::arb_wasm_precompile(),
+            FuzzTarget::ArbRetryableTx => arb_retryable_tx::arb_retryable_tx_precompile(),
+            FuzzTarget::ArbSys => arb_sys::arb_sys_precompile(),
+        })
+    }
+
+    pub fn address(self) -> Address {
+        *self.precompile().address()
+    }
+
+    /// The methods of the precompile's interface.
+    pub fn methods(self) -> Vec<Method> {
+        macro_rules! methods {
+            ($logic:ty, $module:ident::$iface:ident => [$($call:ident),* $(,)?]) => {{
+                let table = <$logic as ArbPrecompileLogic<FuzzContext>>::STATE_MUT_TABLE;
+                vec![$(Method::of::<$module::$iface::$call>(table)),*]
+            }};
+        }
+
+        match self {
+            FuzzTarget::ArbOwner => {
+                methods!(arb_owner::ArbOwnerPrecompile, arb_owner::ArbOwner => [
+                    addChainOwnerCall, removeChainOwnerCall, isChainOwnerCall, getAllChainOwnersCall,
+                    setNativeTokenManagementFromCall, addNativeTokenOwnerCall,
+                    removeNativeTokenOwnerCall, isNativeTokenOwnerCall, getAllNativeTokenOwnersCall,
+                    setL1BaseFeeEstimateInertiaCall, setL2BaseFeeCall, setMinimumL2BaseFeeCall,
+                    setSpeedLimitCall, setMaxTxGasLimitCall, setL2GasPricingInertiaCall,
+                    setL2GasBacklogToleranceCall, setGasBacklogCall, setMaxBlockGasLimitCall,
+                    getNetworkFeeAccountCall, getInfraFeeAccountCall, setNetworkFeeAccountCall,
+                    setInfraFeeAccountCall, scheduleArbOSUpgradeCall,
+                    setL1PricingEquilibrationUnitsCall, setL1PricingInertiaCall,
+                    setL1PricingRewardRecipientCall, setL1PricingRewardRateCall,
+                    setL1PricePerUnitCall, setPerBatchGasChargeCall, setBrotliCompressionLevelCall,
+                    setAmortizedCostCapBipsCall, releaseL1PricerSurplusFundsCall, setInkPriceCall,
+                    setWasmMaxStackDepthCall, setWasmFreePagesCall, setWasmPageGasCall,
+                    setWasmPageLimitCall, setWasmMaxSizeCall, setWasmMinInitGasCall,
+                    setWasmInitCostScalarCall, setWasmExpiryDaysCall, setWasmKeepaliveDaysCall,
+                    setWasmBlockCacheSizeCall, addWasmCacheManagerCall, removeWasmCacheManagerCall,
+                    setChainConfigCall, setCalldataPriceIncreaseCall, multicallCall,
+                ])
+            }
+            FuzzTarget::ArbWasm => methods!(arb_wasm::ArbWasmPrecompile, arb_wasm::IArbWasm => [
+                activateProgramCall, stylusVersionCall, codehashVersionCall, moduleHashCall,
+                codehashKeepaliveCall, codehashAsmSizeCall, programVersionCall,
+                programInitGasCall, programMemoryFootprintCall, programTimeLeftCall, inkPriceCall,
+                maxStackDepthCall, freePagesCall, pageGasCall, pageRampCall, pageLimitCall,
+                minInitGasCall, initCostScalarCall, expiryDaysCall, keepaliveDaysCall,
+                blockCacheSizeCall,
+            ]),
+            FuzzTarget::ArbRetryableTx => methods!(
+                arb_retryable_tx::ArbRetryableTxPrecompile,
+                arb_retryable_tx::ArbRetryableTx => [
+                    redeemCall, getLifetimeCall, getTimeoutCall, keepaliveCall,
+                    getBeneficiaryCall, cancelCall, getCurrentRedeemerCall, submitRetryableCall,
+                ]
+            ),
+            FuzzTarget::ArbSys => methods!(arb_sys::ArbSysPrecompile, arb_sys::ArbSys => [
+                arbBlockNumberCall, arbBlockHashCall, arbChainIDCall, arbOSVersionCall,
+                getStorageGasAvailableCall, isTopLevelCallCall,
+                mapL1SenderContractAddressToL2AliasCall, wasMyCallersAddressAliasedCall,
+                myCallersAddressWithoutAliasingCall, withdrawEthCall, sendTxToL1Call,
+                sendMerkleTreeStateCall,
+            ]),
+        }
+    }
+}
+
+/// A method of a precompile interface.
+#[derive(Clone, Copy, Debug)]
+pub struct Method {
+    /// Solidity signature, e.g. `addChainOwner(address)`.
+    pub signature: &'static str,
+    pub selector: [u8; 4],
+    pub mutability: StateMutability,
+    decodes: fn(&[u8]) -> bool,
+}
+
+impl Method {
+    fn of<C: SolCall>(table: &[([u8; 4], StateMutability)]) -> Self {
+        let mutability = table
+            .iter()
+            .find(|(selector, _)| *selector == C::SELECTOR)
+            .map_or(StateMutability::NonPayable, |(_, mutability)| *mutability);
+        Self {
+            signature: C::SIGNATURE,
+            selector: C::SELECTOR,
+            mutability,
+            decodes: |calldata| C::abi_decode(calldata).is_ok(),
+        }
+    }
+
+    /// Whether `calldata` decodes as a call to this method.
+    pub fn decodes(&self, calldata: &[u8]) -> bool {
+        (self.decodes)(calldata)
+    }
+
+    /// ABI-encoded calldata for this method, with arguments drawn from `input`.
+    pub fn calldata(&self, input: &mut FuzzInput<'_>) -> Vec<u8> {
+        let params = &self.signature[self.signature.find('(').unwrap_or(0)..];
+        let AbiType::Tuple(params) = AbiType::parse(params) else {
+            unreachable!("a parameter list parses as a tuple")
+        };
+        let mut calldata = self.selector.to_vec();
+        calldata.extend(encode_tuple(&params, input));
+        calldata
+    }
+}
+
+/// Fuzz bytes, read from the front and reading as zeroes once used up.
+#[derive(Clone, Debug)]
+pub struct FuzzInput<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> FuzzInput<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn byte(&mut self) -> u8 {
+        match self.data.split_first() {
+            Some((byte, rest)) => {
+                self.data = rest;
+                *byte
+            }
+            None => 0,
+        }
+    }
+
+    pub fn fill(&mut self, out: &mut [u8]) {
+        for byte in out {
+            *byte = self.byte();
+        }
+    }
+
+    pub fn u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill(&mut bytes);
+        u32::from_be_bytes(bytes)
+    }
+
+    /// A length of at most `max`.
+    pub fn length(&mut self, max: usize) -> usize {
+        usize::from(self.byte()) % (max + 1)
+    }
+
+    /// An ABI word biased towards the values precompiles treat specially.
+    pub fn word(&mut self) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        match self.byte() % 6 {
+            0 => {}
+            1 => word[31] = self.byte() % 64,
+            2 => self.fill(&mut word[24..]),
+            3 => {
+                let address = if self.byte() % 2 == 0 {
+                    OWNER
+                } else {
+                    let mut address = Address::ZERO;
+                    self.fill(address.as_mut_slice());
+                    address
+                };
+                word[12..].copy_from_slice(address.as_slice());
+            }
+            4 => word = [0xff; 32],
+            _ => self.fill(&mut word),
+        }
+        word
+    }
+}
+
+/// A Solidity ABI type, as written in a signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum AbiType {
+    Uint(usize),
+    Int(usize),
+    Address,
+    Bool,
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<AbiType>),
+    FixedArray(Box<AbiType>, usize),
+    Tuple(Vec<AbiType>),
+}
+
+impl AbiType {
+    /// Parse a type of a `sol!` signature, which are well formed.
+    fn parse(ty: &str) -> Self {
+        // Array suffixes apply to everything before them
+        if let Some(inner) = ty.strip_suffix("[]") {
+            return AbiType::Array(Box::new(Self::parse(inner)));
+        }
+        if let Some(open) = ty.strip_suffix(']').and_then(|ty| ty.rfind('[')) {
+            let size = ty[open + 1..ty.len() - 1]
+                .parse()
+                .expect("invalid array size");
+            return AbiType::FixedArray(Box::new(Self::parse(&ty[..open])), size);
+        }
+        if let Some(components) = ty.strip_prefix('(').and_then(|ty| ty.strip_suffix(')')) {
+            return AbiType::Tuple(split_components(components).map(Self::parse).collect());
+        }
+        let bits = |digits: &str| digits.parse().unwrap_or(256);
+        match ty {
+            "address" => AbiType::Address,
+            "bool" => AbiType::Bool,
+            "bytes" => AbiType::Bytes,
+            "string" => AbiType::String,
+            _ if ty.starts_with("uint") => AbiType::Uint(bits(&ty[4..])),
+            _ if ty.starts_with("int") => AbiType::Int(bits(&ty[3..])),
+            _ if ty.starts_with("bytes") => AbiType::FixedBytes(ty[5..].parse().unwrap()),
+            _ => panic!("unsupported ABI type `{ty}`"),
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            AbiType::Bytes | AbiType::String | AbiType::Array(_) => true,
+            AbiType::FixedArray(inner, _) => inner.is_dynamic(),
+            AbiType::Tuple(components) => components.iter().any(Self::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// Size of the type in the head of the tuple holding it.
+    fn head_size(&self) -> usize {
+        match self {
+            _ if self.is_dynamic() => 32,
+            AbiType::FixedArray(inner, size) => inner.head_size() * size,
+            AbiType::Tuple(components) => components.iter().map(Self::head_size).sum(),
+            _ => 32,
+        }
+    }
+}
+
+/// The comma-separated components of a tuple, leaving nested tuples whole.
+fn split_components(components: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0;
+    let mut start = 0;
+    let mut parts = Vec::new();
+    for (i, c) in components.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&components[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !components.is_empty() {
+        parts.push(&components[start..]);
+    }
+    parts.into_iter()
+}
+
+fn length_word(len: usize) -> [u8; 32] {
+    U256::from(len).to_be_bytes()
+}
+
+fn encode_tuple(types: &[AbiType], input: &mut FuzzInput<'_>) -> Vec<u8> {
+    let head_size: usize = types.iter().map(AbiType::head_size).sum();
+    let mut head = Vec::with_capacity(head_size);
+    let mut tail = Vec::new();
+    for ty in types {
+        let encoded = encode(ty, input);
+        if ty.is_dynamic() {
+            head.extend(length_word(head_size + tail.len()));
+            tail.extend(encoded);
+        } else {
+            head.extend(encoded);
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+fn encode(ty: &AbiType, input: &mut FuzzInput<'_>) -> Vec<u8> {
+    match ty {
+        AbiType::Uint(bits) => {
+            let mut word = input.word();
+            word[..32 - bits / 8].fill(0);
+            word.to_vec()
+        }
+        AbiType::Int(bits) => {
+            let mut word = input.word();
+            let sign = if word[32 - bits / 8] & 0x80 == 0 {
+                0
+            } else {
+                0xff
+            };
+            word[..32 - bits / 8].fill(sign);
+            word.to_vec()
+        }
+        AbiType::Address => {
+            let mut word = input.word();
+            word[..12].fill(0);
+            word.to_vec()
+        }
+        AbiType::Bool => length_word(usize::from(input.byte() % 2)).to_vec(),
+        AbiType::FixedBytes(size) => {
+            let mut word = input.word();
+            word[*size..].fill(0);
+            word.to_vec()
+        }
+        AbiType::Bytes | AbiType::String => {
+            let mut data = vec![0; input.length(MAX_LEN * 32)];
+            input.fill(&mut data);
+            if *ty == AbiType::String {
+                // Strings must be valid UTF-8
+                data.iter_mut().for_each(|byte| *byte &= 0x7f);
+            }
+            let mut encoded = length_word(data.len()).to_vec();
+            encoded.extend(&data);
+            encoded.resize(32 + data.len().div_ceil(32) * 32, 0);
+            encoded
+        }
+        AbiType::Array(inner) => {
+            let len = input.length(MAX_LEN);
+            let mut encoded = length_word(len).to_vec();
+            encoded.extend(encode_tuple(&vec![inner.as_ref().clone(); len], input));
+            encoded
+        }
+        AbiType::FixedArray(inner, size) => {
+            encode_tuple(&vec![inner.as_ref().clone(); *size], input)
+        }
+        AbiType::Tuple(components) => encode_tuple(components, input),
+    }
+}
+
+/// Context configured by `cfg`, with initialized ArbOS state owned by [`OWNER`].
+pub(crate) fn setup(cfg: ArbitrumConfig) -> FuzzContext {
+    let mut context = ArbitrumContext {
+        journaled_state: Journal::new(EmptyDBTyped::<Infallible>::default()),
+        block: BlockEnv::default(),
+        cfg,
+        tx: ArbitrumTransaction::default(),
+        chain: (),
+        local: ArbitrumLocalContext::default(),
+        error: Ok(()),
+    };
+
+    let mut arb_state = context.arb_state(None, false);
+    arb_state
+        .initialize(&ArbosStateParams::default())
+        .expect("failed to initialize ArbOS state");
+    arb_state
+        .chain_owners()
+        .add(OWNER)
+        .expect("failed to add chain owner");
+    drop(arb_state);
+    context
+}
+
+/// A call to a fuzzed precompile.
+pub(crate) struct FuzzCall<'a> {
+    pub precompile: &'a Precompile<FuzzContext>,
+    pub input: &'a [u8],
+    pub caller: Address,
+    pub value: U256,
+    pub is_static: bool,
+}
+
+impl FuzzCall<'_> {
+    /// Make the call with `gas_limit` and panic, reporting `describe`, if it breaks an invariant
+    /// every call must keep.
+    pub(crate) fn checked(
+        &self,
+        context: &mut FuzzContext,
+        gas_limit: u64,
+        describe: impl Fn() -> String,
+    ) -> InterpreterResult {
+        let before = journal_position(context);
+        let result = self
+            .precompile
+            .call(
+                context,
+                self.input,
+                self.precompile.address(),
+                self.caller,
+                self.value,
+                self.is_static,
+                gas_limit,
+            )
+            .unwrap_or_else(|err| panic!("{}: fatal error {err}", describe()))
+            .unwrap_or_else(|| panic!("{}: no result", describe()));
+        let after = journal_position(context);
+
+        assert_eq!(result.gas.limit(), gas_limit, "{}", describe());
+        assert!(result.gas.remaining() <= gas_limit, "{}", describe());
+        if !result.result.is_ok() {
+            assert_eq!(after, before, "{}: failed call left a trace", describe());
+        }
+        if self.is_static {
+            assert_eq!(
+                after.1,
+                before.1,
+                "{}: static call emitted a log",
+                describe()
+            );
+        }
+        result
+    }
+}
+
+/// Make one call drawn from `data` twice, with more gas the second time, and panic if it breaks
+/// an invariant of the [module documentation](self). Meant as the body of a fuzz target.
+pub fn fuzz_precompiles(data: &[u8]) {
+    let mut input = FuzzInput::new(data);
+    let version = ARBOS_VERSIONS[usize::from(input.byte()) % ARBOS_VERSIONS.len()];
+    let target = FuzzTarget::ALL[usize::from(input.byte()) % FuzzTarget::ALL.len()];
+    let methods = target.methods();
+    let method = methods[usize::from(input.byte()) % methods.len()];
+
+    let calldata = method.calldata(&mut input);
+    assert!(
+        method.decodes(&calldata),
+        "{}: generated calldata 0x{} does not decode",
+        method.signature,
+        hex::encode(&calldata)
+    );
+
+    let caller = if input.byte() % 2 == 0 {
+        OWNER
+    } else {
+        let mut caller = Address::ZERO;
+        input.fill(caller.as_mut_slice());
+        caller
+    };
+    let is_static = method.mutability <= StateMutability::View && input.byte() % 2 == 0;
+    let value = if method.mutability == StateMutability::Payable {
+        U256::from(input.byte() % 3)
+    } else {
+        U256::ZERO
+    };
+    let low_gas = u64::from(input.u32() % 2_000_000);
+    let high_gas = low_gas + 1 + u64::from(input.u32() % 2_000_000);
+
+    let precompile = target.precompile();
+    let describe = |gas: u64| {
+        format!(
+            "{} at ArbOS {version}: input 0x{} caller {caller} static {is_static} gas {gas}",
+            method.signature,
+            hex::encode(&calldata)
+        )
+    };
+    let call = FuzzCall {
+        precompile: &precompile,
+        input: &calldata,
+        caller,
+        value,
+        is_static,
+    };
+    let mut context = setup(ArbitrumConfig {
+        arbos_version: version,
+        ..Default::default()
+    });
+    let mut call_with = |gas: u64| {
+        // Both calls start from the same state
+        let checkpoint = context.journal_mut().checkpoint();
+        let result = call.checked(&mut context, gas, || describe(gas));
+        context.journal_mut().checkpoint_revert(checkpoint);
+        result
+    };
+
+    let low = call_with(low_gas);
+    let high = call_with(high_gas);
+    if low.result.is_ok() {
+        assert!(
+            high.result.is_ok(),
+            "{}: failed with more gas, {:?}",
+            describe(high_gas),
+            high.result
+        );
+        if method.mutability <= StateMutability::View {
+            assert_eq!(high.output, low.output, "{}", describe(high_gas));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calldata_matches_the_sol_encoding() {
+        let method = FuzzTarget::ArbOwner
+            .methods()
+            .into_iter()
+            .find(|method| method.signature == "multicall(bytes[])")
+            .unwrap();
+        // Two elements: empty, and a single byte with the top bit set
+        let mut input = FuzzInput::new(&[2, 0, 1, 0xab]);
+
+        let calldata = method.calldata(&mut input);
+
+        let expected = arb_owner::ArbOwner::multicallCall {
+            calls: vec![Default::default(), vec![0xab].into()],
+        }
+        .abi_encode();
+        assert_eq!(calldata, expected);
+    }
+
+    #[test]
+    fn methods_cover_the_dispatch_tables() {
+        for target in FuzzTarget::ALL {
+            let mut selectors: Vec<_> = target.methods().iter().map(|m| m.selector).collect();
+            let mut table: Vec<_> = match target {
+                FuzzTarget::ArbOwner => <arb_owner::ArbOwnerPrecompile as ArbPrecompileLogic<
+                    FuzzContext,
+                >>::STATE_MUT_TABLE,
+                FuzzTarget::ArbWasm => <arb_wasm::ArbWasmPrecompile as ArbPrecompileLogic<
+                    FuzzContext,
+                >>::STATE_MUT_TABLE,
+                FuzzTarget::ArbRetryableTx => {
+                    <arb_retryable_tx::ArbRetryableTxPrecompile as ArbPrecompileLogic<
+                        FuzzContext,
+                    >>::STATE_MUT_TABLE
+                }
+                FuzzTarget::ArbSys => {
+                    <arb_sys::ArbSysPrecompile as ArbPrecompileLogic<FuzzContext>>::STATE_MUT_TABLE
+                }
+            }
+            .iter()
+            .map(|(selector, _)| *selector)
+            .collect();
+            selectors.sort();
+            table.sort();
+            assert_eq!(selectors, table, "{target:?}");
+        }
+    }
+
+    #[test]
+    fn generated_calldata_decodes() {
+        for target in FuzzTarget::ALL {
+            for method in target.methods() {
+                for seed in 0..32u8 {
+                    let data: Vec<u8> = (0..512).map(|i| seed.wrapping_mul(31) ^ i as u8).collect();
+                    let calldata = method.calldata(&mut FuzzInput::new(&data));
+                    assert!(method.decodes(&calldata), "{}", method.signature);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fuzz_precompiles_runs_on_fixed_inputs() {
+        for seed in 0..64u8 {
+            let data: Vec<u8> = (0..256).map(|i| seed.wrapping_add(i as u8)).collect();
+            fuzz_precompiles(&data);
+        }
+        fuzz_precompiles(&[]);
+    }
+}
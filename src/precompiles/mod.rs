@@ -4,16 +4,18 @@ use std::{
 };
 
 use revm::{
-    context::{Cfg, ContextTr, LocalContextTr},
+    context::{Cfg, ContextTr, JournalTr, LocalContextTr},
     handler::PrecompileProvider,
     interpreter::{
         CallInput, CallInputs, Gas, InstructionResult, InterpreterResult, gas::ISTANBUL_SLOAD_GAS,
     },
     precompile::{PrecompileError, PrecompileId, PrecompileSpecId, Precompiles},
     primitives::{
-        Address, Bytes, HashMap, HashSet, SHORT_ADDRESS_CAP, U256, hardfork::SpecId, short_address,
+        Address, B256, Bytes, FixedBytes, HashMap, HashSet, SHORT_ADDRESS_CAP, U256,
+        hardfork::SpecId, keccak256, short_address,
     },
 };
+use tracing::warn;
 
 mod arb_address_table;
 mod arb_aggregator;
@@ -28,9 +30,22 @@ mod arb_statistics;
 mod arb_sys;
 pub mod arb_wasm;
 mod arb_wasm_cache;
+mod arbos_test;
+pub mod errors;
+#[cfg(test)]
+mod fuzz;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
+
+pub(crate) use arb_owner::ArbOwner;
+pub(crate) use arb_retryable_tx::ArbRetryableTx;
+pub(crate) use arb_wasm::IArbWasm;
 
 use crate::{
     ArbitrumContextTr,
+    config::ArbitrumConfigTr,
+    inspector::PrecompileCall,
+    local_context::{ArbitrumLocalContextTr, CachedPrecompileResult, JournalGeneration},
     macros::{interpreter_return, interpreter_revert},
     precompiles::{arb_wasm::arb_wasm_precompile, arb_wasm_cache::arb_wasm_cache_precompile},
     state::{ArbState, ArbStateGetter, try_state, types::StorageBackedTr},
@@ -43,7 +58,7 @@ macro_rules! selector_or_revert {
             $gas.spend_all();
             return Some(crate::macros::interpreter_result_return_with_output(
                 &mut $gas,
-                revm::primitives::Bytes::from_static(b"Input too short"),
+                $crate::precompiles::errors::revert_message("Input too short"),
             ));
         }
 
@@ -53,7 +68,7 @@ macro_rules! selector_or_revert {
                 $gas.spend_all();
                 return Some(crate::macros::interpreter_result_return_with_output(
                     &mut $gas,
-                    revm::primitives::Bytes::from_static(b"Invalid selector"),
+                    $crate::precompiles::errors::revert_message("Invalid selector"),
                 ));
             }
         }
@@ -64,9 +79,18 @@ pub(crate) use selector_or_revert;
 
 pub struct ArbitrumPrecompileProvider<CTX: ArbitrumContextTr> {
     registry: Arc<PrecompileRegistry<CTX>>,
+    /// Precompiles registered by the embedder, kept across spec changes.
+    extra: Arc<Vec<Precompile<CTX>>>,
     spec: SpecId,
 }
 
+/// An extra precompile registered at an address already taken by another precompile.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("a precompile is already registered at {address}")]
+pub struct PrecompileConflict {
+    pub address: Address,
+}
+
 impl<CTX: ArbitrumContextTr> ArbitrumPrecompileProvider<CTX> {
     #[inline]
     pub fn warm_addresses(&self) -> Box<impl Iterator<Item = Address>> {
@@ -79,6 +103,46 @@ impl<CTX: ArbitrumContextTr> ArbitrumPrecompileProvider<CTX> {
     }
 
     pub fn new(spec: SpecId) -> Self {
+        Self {
+            registry: Arc::new(Self::registry(spec)),
+            extra: Arc::default(),
+            spec,
+        }
+    }
+
+    pub fn builder() -> ArbitrumPrecompileProviderBuilder<CTX> {
+        ArbitrumPrecompileProviderBuilder::default()
+    }
+
+    /// Register `precompiles` next to the Ethereum and Arbitrum ones, e.g. the extended
+    /// precompiles of an Orbit chain.
+    ///
+    /// Fails on an address already taken, by an Arbitrum precompile, by an Ethereum precompile
+    /// of any hardfork, or by another extra precompile.
+    pub fn with_extra(
+        mut self,
+        precompiles: impl IntoIterator<Item = Precompile<CTX>>,
+    ) -> Result<Self, PrecompileConflict> {
+        let mut extra = self.extra.as_ref().clone();
+        for precompile in precompiles {
+            let address = *precompile.address();
+            if self.registry.contains(&address)
+                || Precompiles::latest().contains(&address)
+                || extra.iter().any(|other| *other.address() == address)
+            {
+                return Err(PrecompileConflict { address });
+            }
+            extra.push(precompile);
+        }
+
+        self.extra = Arc::new(extra);
+        self.registry = Arc::new(Self::registry_with_extra(self.spec, &self.extra));
+        Ok(self)
+    }
+
+    /// The Ethereum precompiles of `spec` and the Arbitrum precompiles, at their canonical
+    /// addresses.
+    fn registry(spec: SpecId) -> PrecompileRegistry<CTX> {
         let mut registry = PrecompileRegistry::new(PrecompileSpecId::from_spec_id(spec));
 
         registry.register_many([
@@ -98,18 +162,69 @@ impl<CTX: ArbitrumContextTr> ArbitrumPrecompileProvider<CTX> {
             Precompile::Extended(arb_sys::arb_sys_precompile::<CTX>()),
             Precompile::Extended(arb_wasm_precompile::<CTX>()),
             Precompile::Extended(arb_wasm_cache_precompile::<CTX>()),
+            Precompile::Extended(arbos_test::arbos_test_precompile::<CTX>()),
         ]);
+        registry
+    }
+
+    fn registry_with_extra(spec: SpecId, extra: &[Precompile<CTX>]) -> PrecompileRegistry<CTX> {
+        let mut registry = Self::registry(spec);
+        registry.register_many(extra.iter().cloned());
+        registry
+    }
+}
+
+/// Builds an [`ArbitrumPrecompileProvider`] with extra precompiles.
+pub struct ArbitrumPrecompileProviderBuilder<CTX: ArbitrumContextTr> {
+    spec: SpecId,
+    extra: Vec<Precompile<CTX>>,
+}
+
+impl<CTX: ArbitrumContextTr> Default for ArbitrumPrecompileProviderBuilder<CTX> {
+    fn default() -> Self {
         Self {
-            registry: Arc::new(registry),
-            spec,
+            spec: SpecId::default(),
+            extra: Vec::new(),
         }
     }
 }
 
+impl<CTX: ArbitrumContextTr> ArbitrumPrecompileProviderBuilder<CTX> {
+    pub fn spec(mut self, spec: SpecId) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    /// Register `precompile` next to the Ethereum and Arbitrum ones.
+    pub fn precompile(mut self, precompile: Precompile<CTX>) -> Self {
+        self.extra.push(precompile);
+        self
+    }
+
+    /// Register an extended precompile at `address`, run by `handler`.
+    pub fn extended(
+        self,
+        id: PrecompileId,
+        address: Address,
+        handler: ExtendedPrecompileFn<CTX>,
+    ) -> Self {
+        self.precompile(Precompile::Extended(ExtendedPrecompile::new(
+            id, address, handler,
+        )))
+    }
+
+    /// Build the provider, failing if two precompiles share an address, see
+    /// [`ArbitrumPrecompileProvider::with_extra`].
+    pub fn build(self) -> Result<ArbitrumPrecompileProvider<CTX>, PrecompileConflict> {
+        ArbitrumPrecompileProvider::new(self.spec).with_extra(self.extra)
+    }
+}
+
 impl<CTX: ArbitrumContextTr> Clone for ArbitrumPrecompileProvider<CTX> {
     fn clone(&self) -> Self {
         Self {
             registry: Arc::clone(&self.registry),
+            extra: Arc::clone(&self.extra),
             spec: self.spec,
         }
     }
@@ -117,13 +232,7 @@ impl<CTX: ArbitrumContextTr> Clone for ArbitrumPrecompileProvider<CTX> {
 
 impl<CTX: ArbitrumContextTr> Default for ArbitrumPrecompileProvider<CTX> {
     fn default() -> Self {
-        let spec = SpecId::default();
-        let registry = PrecompileRegistry::new(PrecompileSpecId::from_spec_id(spec));
-
-        Self {
-            registry: Arc::new(registry),
-            spec,
-        }
+        Self::new(SpecId::default())
     }
 }
 
@@ -137,20 +246,25 @@ impl<CTX: ArbitrumContextTr> PrecompileProvider<CTX> for ArbitrumPrecompileProvi
             return false;
         }
 
-        self.registry = Arc::new(PrecompileRegistry::new(PrecompileSpecId::from_spec_id(
-            new_spec,
-        )));
+        self.registry = Arc::new(Self::registry_with_extra(new_spec, &self.extra));
         self.spec = new_spec;
         true
     }
 
     /// Executes a precompile if one exists for the address.
+    ///
+    /// The registry is keyed by canonical address, so the Arbitrum precompiles are looked up
+    /// through the config's precompile address block.
     fn run(
         &mut self,
         ctx: &mut CTX,
         inputs: &CallInputs,
     ) -> Result<Option<InterpreterResult>, String> {
-        let Some(precompile) = self.registry.get(&inputs.bytecode_address) else {
+        let Some((address, precompile)) = ctx
+            .cfg()
+            .canonical_precompile_address(inputs.bytecode_address)
+            .and_then(|address| Some((address, self.registry.get(&address)?)))
+        else {
             return Ok(None);
         };
 
@@ -163,25 +277,83 @@ impl<CTX: ArbitrumContextTr> PrecompileProvider<CTX> for ArbitrumPrecompileProvi
             }));
         }
 
-        // extract input bytes
+        // Input passed as bytes is shared rather than copied. Input in the caller's memory is
+        // copied out once, as the precompile gets the context the buffer is borrowed from
         let input_bytes = match &inputs.input {
             CallInput::SharedBuffer(range) => ctx
                 .local()
                 .shared_memory_buffer_slice(range.clone())
-                .map(|s| s.to_vec())
-                .unwrap_or_default(),
-            CallInput::Bytes(b) => b.to_vec(),
+                .map(|slice| Bytes::copy_from_slice(&slice))
+                .ok_or_else(|| {
+                    warn!(
+                        target: "arbos-revm::precompiles",
+                        %address,
+                        ?range,
+                        "Precompile input lies outside the caller's memory"
+                    );
+                    format!("precompile input {range:?} lies outside the caller's memory")
+                })?,
+            CallInput::Bytes(bytes) => bytes.clone(),
         };
 
-        precompile.call(
-            ctx,
-            &input_bytes,
-            &inputs.target_address,
-            inputs.caller,
-            inputs.call_value(),
-            inputs.is_static,
-            inputs.gas_limit,
-        )
+        let gas_override = input_bytes
+            .first_chunk::<4>()
+            .and_then(|selector| ctx.cfg().precompile_gas_override(address, *selector));
+        let result = match gas_override {
+            // The method isn't run without the gas to pay for it
+            Some(cost) if cost > inputs.gas_limit => {
+                let mut gas = Gas::new(inputs.gas_limit);
+                gas.spend_all();
+                Some(InterpreterResult {
+                    result: InstructionResult::PrecompileOOG,
+                    output: Bytes::new(),
+                    gas,
+                })
+            }
+            // Otherwise it runs with the caller's gas, and is charged the overridden cost
+            // whatever its own unless it halted, which keeps the gas spent
+            _ => {
+                let mut result = precompile.call(
+                    ctx,
+                    &input_bytes,
+                    &inputs.target_address,
+                    inputs.caller,
+                    inputs.call_value(),
+                    inputs.is_static,
+                    inputs.gas_limit,
+                )?;
+                if let (Some(cost), Some(result)) = (gas_override, &mut result)
+                    && result.result.is_ok_or_revert()
+                {
+                    result.gas = Gas::new(inputs.gas_limit);
+                    _ = result.gas.record_cost(cost);
+                }
+                result
+            }
+        };
+
+        if let Some(result) = &result {
+            let mut selector = FixedBytes::<4>::ZERO;
+            let len = input_bytes.len().min(4);
+            selector[..len].copy_from_slice(&input_bytes[..len]);
+            let gas_used = inputs.gas_limit.saturating_sub(result.gas.remaining());
+            ctx.local_mut()
+                .record_precompile_gas(inputs.bytecode_address, selector, gas_used);
+
+            // Kept for the inspector, which only sees the call frame
+            if let Precompile::Extended(precompile) = precompile {
+                ctx.local_mut().set_precompile_call(Some(PrecompileCall {
+                    address: inputs.bytecode_address,
+                    id: precompile.id().clone(),
+                    selector,
+                    input: input_bytes,
+                    output: result.output.clone(),
+                    result: result.result,
+                    gas_used,
+                }));
+            }
+        }
+        Ok(result)
     }
 
     fn warm_addresses(&self) -> Box<impl Iterator<Item = Address>> {
@@ -431,16 +603,40 @@ impl<CTX: ContextTr> Precompile<CTX> {
                 Ok(Some(result))
             }
             Self::Extended(ext) => {
-                ext.execute(ctx, input, target, caller, value, is_static, gas_limit)
+                // Roll back partial state writes and logs unless the precompile succeeded, so
+                // handlers don't need to order their writes around later failures.
+                let checkpoint = ctx.journal_mut().checkpoint();
+                let result = ext.execute(ctx, input, target, caller, value, is_static, gas_limit);
+                match &result {
+                    Ok(Some(outcome)) if outcome.result.is_ok() => {
+                        ctx.journal_mut().checkpoint_commit()
+                    }
+                    _ => {
+                        ctx.journal_mut().checkpoint_revert(checkpoint);
+                        ctx.local_mut().record_journal_revert();
+                    }
+                }
+                result
             }
         }
     }
 }
 
-pub(crate) trait ArbPrecompileLogic<CTX: ArbitrumContextTr> {
+/// Shared gas and mutability preamble for Arbitrum-style precompiles.
+///
+/// Implementors provide a selector mutability table and the `inner` dispatch, usually written
+/// with [`precompile_dispatch!`](crate::precompile_dispatch), and register the result with
+/// [`precompile_impl!`](crate::precompile_impl).
+pub trait ArbPrecompileLogic<CTX: ArbitrumContextTr> {
     /// File-local state mutability table
     const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)];
 
+    /// Whether the method with `selector` in [`Self::STATE_MUT_TABLE`] may be called under the
+    /// context's configuration. Disabled methods are treated as unknown selectors.
+    fn method_enabled(_context: &CTX, _selector: [u8; 4]) -> bool {
+        true
+    }
+
     /// Inner execution
     fn inner(
         context: &mut CTX,
@@ -474,8 +670,8 @@ pub(crate) trait ArbPrecompileLogic<CTX: ArbitrumContextTr> {
             .iter()
             .find(|(sel, _)| *sel == selector)
         {
-            Some((_, p)) => *p,
-            None => interpreter_return!(gas),
+            Some((_, p)) if Self::method_enabled(context, selector) => *p,
+            _ => interpreter_return!(gas),
         };
 
         if purity != StateMutability::Pure {
@@ -493,8 +689,29 @@ pub(crate) trait ArbPrecompileLogic<CTX: ArbitrumContextTr> {
             );
         }
 
+        // Identical pure and view calls that left no trace in the journal return the same while
+        // it is unchanged
+        let cache_key = (purity <= StateMutability::View
+            && context.cfg().cache_precompile_results())
+        .then(|| {
+            precompile_call_key(
+                context,
+                input,
+                target_address,
+                caller_address,
+                call_value,
+                is_static,
+            )
+        });
+        let generation = cache_key.map(|_| journal_generation(context));
+        let cached = cache_key.zip(generation).and_then(|(key, generation)| {
+            cached_outcome(context, &key, generation, gas.remaining())
+        });
+
         // call the inner logic
-        let outcome = if let Some(outcome) = Self::inner(
+        let outcome = if let Some(outcome) = cached {
+            outcome
+        } else if let Some(outcome) = Self::inner(
             context,
             input,
             target_address,
@@ -503,6 +720,20 @@ pub(crate) trait ArbPrecompileLogic<CTX: ArbitrumContextTr> {
             is_static,
             gas.remaining(),
         ) {
+            if let (Some(key), Some(before)) = (cache_key, generation)
+                && outcome.result.is_ok()
+                && journal_generation(context) == before
+            {
+                context.local_mut().cache_precompile_result(
+                    key,
+                    CachedPrecompileResult {
+                        generation: before,
+                        result: outcome.result,
+                        output: outcome.output.clone(),
+                        gas_used: gas.remaining().saturating_sub(outcome.gas.remaining()),
+                    },
+                );
+            }
             outcome
         } else {
             // final precompile return value must be error
@@ -524,21 +755,66 @@ pub(crate) trait ArbPrecompileLogic<CTX: ArbitrumContextTr> {
     }
 }
 
-macro_rules! decode_call {
-    ($gas:expr, $call:path, $input:expr) => {{
-        match <$call as alloy_sol_types::SolCall>::abi_decode($input) {
-            Ok(value) => value,
-            Err(_) => {
-                return Some(crate::macros::interpreter_result_revert_with_output(
-                    &mut $gas,
-                    revm::primitives::Bytes::from_static(b"Invalid calldata"),
-                ));
-            }
-        }
-    }};
+/// Identifies a precompile call by everything besides state that a pure or view method reads,
+/// including the caller's caller that ArbSys reports to the contract calling it.
+fn precompile_call_key<CTX: ArbitrumContextTr>(
+    context: &CTX,
+    input: &[u8],
+    target_address: &Address,
+    caller_address: Address,
+    call_value: U256,
+    is_static: bool,
+) -> B256 {
+    let depth = context.local().call_depth();
+    let callers_caller = depth
+        .checked_sub(1)
+        .and_then(|depth| context.local().caller_at_depth(depth))
+        .unwrap_or_default();
+
+    let mut preimage = Vec::with_capacity(101 + input.len());
+    preimage.extend_from_slice(target_address.as_slice());
+    preimage.extend_from_slice(caller_address.as_slice());
+    preimage.extend_from_slice(callers_caller.as_slice());
+    preimage.extend_from_slice(&call_value.to_be_bytes::<32>());
+    preimage.extend_from_slice(&(depth as u64).to_be_bytes());
+    preimage.push(is_static as u8);
+    preimage.extend_from_slice(input);
+    keccak256(preimage)
+}
+
+/// Lengths of the journal and the logs, which grow as the transaction changes state and shrink
+/// when it reverts.
+fn journal_position<CTX: ArbitrumContextTr>(context: &mut CTX) -> (usize, usize) {
+    let checkpoint = context.journal_mut().checkpoint();
+    context.journal_mut().checkpoint_commit();
+    (checkpoint.journal_i, checkpoint.log_i)
 }
 
-pub(crate) use decode_call;
+/// Generation of the journal, as cached precompile results are keyed.
+fn journal_generation<CTX: ArbitrumContextTr>(context: &mut CTX) -> JournalGeneration {
+    let (journal_len, _) = journal_position(context);
+    (context.local().journal_reverts(), journal_len)
+}
+
+/// The cached outcome of the call identified by `key`, if it was made at journal generation
+/// `generation` and its gas fits in `gas_limit`.
+fn cached_outcome<CTX: ArbitrumContextTr>(
+    context: &CTX,
+    key: &B256,
+    generation: JournalGeneration,
+    gas_limit: u64,
+) -> Option<InterpreterResult> {
+    let cached = context
+        .local()
+        .precompile_result(key)
+        .filter(|cached| cached.generation == generation)?;
+    let mut gas = Gas::new(gas_limit);
+    gas.record_cost(cached.gas_used).then(|| InterpreterResult {
+        result: cached.result,
+        output: cached.output.clone(),
+        gas,
+    })
+}
 
 #[macro_export]
 macro_rules! generate_state_mut_table {
@@ -549,11 +825,11 @@ macro_rules! generate_state_mut_table {
             ),* $(,)?
         }
     ) => {{
-        const TABLE: &[([u8;4], StateMutability)] = &[
+        const TABLE: &[([u8;4], $crate::precompiles::StateMutability)] = &[
             $(
                 (
-                    <$iface::$call as alloy_sol_types::SolCall>::SELECTOR,
-                    StateMutability::$mut
+                    <$iface::$call as $crate::alloy_sol_types::SolCall>::SELECTOR,
+                    $crate::precompiles::StateMutability::$mut
                 )
             ),*
         ];
@@ -573,7 +849,7 @@ pub enum StateMutability {
 macro_rules! precompile_impl {
     ($logic:ty) => {
         |context, input, target_address, caller_address, call_value, is_static, gas_limit| {
-            Ok(<$logic>::run(
+            Ok(<$logic as $crate::precompiles::ArbPrecompileLogic<_>>::run(
                 context,
                 input,
                 target_address,
@@ -585,3 +861,75 @@ macro_rules! precompile_impl {
         }
     };
 }
+
+/// Dispatches precompile calldata to the handler registered for its selector, as every Arbitrum
+/// precompile does.
+///
+/// Each arm names a `sol!` call type, binds the decoded call and evaluates a block producing
+/// the call's ABI return value, which is encoded as the precompile output. Handlers can leave
+/// early with `return Some(..)`, e.g. via [`emit_event!`](crate::emit_event) or
+/// [`try_record_cost!`](crate::try_record_cost). Undecodable calldata and unknown selectors
+/// revert, the latter with `unknown` when given, and calldata too short for a selector reverts
+/// consuming all gas.
+///
+/// ```ignore
+/// fn inner(context: &mut CTX, input: &[u8], /* .. */ gas_limit: u64) -> Option<InterpreterResult> {
+///     let mut gas = Gas::new(gas_limit);
+///     precompile_dispatch!(gas, input, {
+///         IExample::valueCall => |_call| { U256::from(42) },
+///         IExample::setValueCall => |call| {
+///             store(context, call.value);
+///             IExample::setValueReturn {}
+///         },
+///     }, unknown => revert_message("Unknown selector"))
+/// }
+/// ```
+#[macro_export]
+macro_rules! precompile_dispatch {
+    (@unknown) => {
+        $crate::precompiles::errors::revert_message("Unknown function selector")
+    };
+    (@unknown $unknown:expr) => {
+        $unknown
+    };
+    ($gas:ident, $input:expr, {
+        $( $call:path => |$arg:pat_param| $body:block ),* $(,)?
+    } $(, unknown => $unknown:expr)?) => {{
+        let input: &[u8] = $input;
+        if input.len() < 4 {
+            $gas.spend_all();
+            return Some($crate::macros::interpreter_result_revert_with_output(
+                &mut $gas,
+                $crate::precompiles::errors::revert_message("Input too short"),
+            ));
+        }
+        let selector = [input[0], input[1], input[2], input[3]];
+        $(
+            if selector == <$call as $crate::alloy_sol_types::SolCall>::SELECTOR {
+                let $arg = match <$call as $crate::alloy_sol_types::SolCall>::abi_decode(input) {
+                    Ok(value) => value,
+                    Err(_) => {
+                        return Some($crate::macros::interpreter_result_revert_with_output(
+                            &mut $gas,
+                            $crate::precompiles::errors::revert_message("Invalid calldata"),
+                        ));
+                    }
+                };
+                // Handlers that always fail leave no return value to encode
+                #[allow(unreachable_code)]
+                let output = {
+                    let ret = $body;
+                    <$call as $crate::alloy_sol_types::SolCall>::abi_encode_returns(&ret)
+                };
+                return Some($crate::macros::interpreter_result_return_with_output(
+                    &mut $gas,
+                    output.into(),
+                ));
+            }
+        )*
+        Some($crate::macros::interpreter_result_revert_with_output(
+            &mut $gas,
+            $crate::precompile_dispatch!(@unknown $($unknown)?),
+        ))
+    }};
+}
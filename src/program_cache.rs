@@ -0,0 +1,375 @@
+//! Compiled Stylus program cache that can be shared between EVM instances.
+//!
+//! Entries are tagged with the settings they were compiled under and with the cache generation
+//! at insertion time. A lookup under different settings, or after [`ProgramCache::invalidate`],
+//! is a miss, and the stale entry is replaced when the program is compiled again. This lets
+//! instances with different configurations share one handle without serving each other's
+//! modules.
+//!
+//! Code hashes can be pinned with [`ProgramCache::pin`]. Pinned programs are held outside the LRU
+//! and never evicted, so known hot programs stay compiled regardless of cache pressure. Programs
+//! cached in ArbOS state through `ArbWasmCache` are pinned when first called, and unpinned when
+//! evicted through it, like Nitro keeps them in its long-term cache rather than its LRU.
+//!
+//! A cache can be backed by a [`StylusModuleCache`], such as the [`DiskModuleCache`], holding
+//! the native code of compiled programs beyond the life of the process. A program missing from
+//! memory is then still activated, but its native code is loaded instead of compiled, so nodes
+//! don't recompile every program after a restart.
+
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use lru::LruCache;
+use revm::primitives::{B256, hex, keccak256};
+use stylus::prover::{machine::Module, programs::StylusData};
+use tracing::{trace, warn};
+
+use crate::stylus_executor::native_target;
+
+/// Serialized native code, prover module and activation data of a compiled program.
+pub type ProgramCacheEntry = (Vec<u8>, Module, StylusData);
+
+/// Settings that change how a program is compiled and activated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProgramCacheTag {
+    pub stylus_version: u16,
+    pub arbos_version: u16,
+    pub debug: bool,
+}
+
+/// How Stylus calls were served by the program cache over a run of transactions, such as a
+/// block, to help size the cache and block limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProgramCacheStats {
+    /// Calls whose program was already compiled in the cache.
+    pub cached_calls: u64,
+    /// Calls whose program had to be compiled, or loaded from the module cache.
+    pub cold_calls: u64,
+    /// Time spent compiling or loading programs for cold calls.
+    pub compile_time: Duration,
+    /// Ink used by the programs called.
+    pub ink_used: u64,
+}
+
+/// Persistent store of the native code of compiled programs, keyed by code hash and the
+/// settings they were compiled under.
+pub trait StylusModuleCache: Send + Sync {
+    /// Native code of the program with `code_hash` compiled under `tag`, if stored.
+    fn load(&self, code_hash: &B256, tag: ProgramCacheTag) -> Option<Vec<u8>>;
+
+    /// Store the native code of the program with `code_hash` compiled under `tag`. Failing to
+    /// store only costs a recompilation later, so errors are not reported.
+    fn store(&self, code_hash: &B256, tag: ProgramCacheTag, native_code: &[u8]);
+}
+
+/// [`StylusModuleCache`] keeping each program's native code in a file under a directory, behind
+/// a checksum so that a truncated or corrupted file is recompiled rather than loaded.
+#[derive(Clone, Debug)]
+pub struct DiskModuleCache {
+    dir: PathBuf,
+}
+
+impl DiskModuleCache {
+    /// Cache native code under `dir`, which is created on the first store.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// File holding the program with `code_hash` compiled under `tag`:
+    /// `<dir>/<target>/<code hash in hex>-<stylus version>-<arbos version>[-debug]`.
+    pub fn path(&self, code_hash: &B256, tag: ProgramCacheTag) -> PathBuf {
+        let mut name = format!(
+            "{}-{}-{}",
+            hex::encode(code_hash),
+            tag.stylus_version,
+            tag.arbos_version
+        );
+        if tag.debug {
+            name.push_str("-debug");
+        }
+        self.dir.join(native_target()).join(name)
+    }
+}
+
+impl StylusModuleCache for DiskModuleCache {
+    fn load(&self, code_hash: &B256, tag: ProgramCacheTag) -> Option<Vec<u8>> {
+        let path = self.path(code_hash, tag);
+        let mut contents = fs::read(&path).ok()?;
+        if contents.len() < 32 || keccak256(&contents[32..]).as_slice() != &contents[..32] {
+            warn!(
+                target: "arbos-revm::stylus",
+                path = %path.display(),
+                "Ignoring corrupted Stylus module cache file"
+            );
+            return None;
+        }
+        Some(contents.split_off(32))
+    }
+
+    fn store(&self, code_hash: &B256, tag: ProgramCacheTag, native_code: &[u8]) {
+        let path = self.path(code_hash, tag);
+        let mut contents = Vec::with_capacity(32 + native_code.len());
+        contents.extend_from_slice(keccak256(native_code).as_slice());
+        contents.extend_from_slice(native_code);
+
+        // Write to a temporary file first so that readers never see a partial file
+        let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&tmp, &contents))
+            .and_then(|()| fs::rename(&tmp, &path));
+        match result {
+            Ok(()) => trace!(
+                target: "arbos-revm::stylus",
+                path = %path.display(),
+                "Stored Stylus module"
+            ),
+            Err(e) => {
+                let _ = fs::remove_file(&tmp);
+                warn!(
+                    target: "arbos-revm::stylus",
+                    path = %path.display(),
+                    error = %e,
+                    "Failed to store Stylus module"
+                );
+            }
+        }
+    }
+}
+
+struct TaggedEntry {
+    generation: u64,
+    tag: ProgramCacheTag,
+    entry: ProgramCacheEntry,
+}
+
+struct Inner {
+    generation: u64,
+    entries: LruCache<B256, TaggedEntry>,
+    /// Pinned code hashes, with their entry once compiled.
+    pinned: HashMap<B256, Option<TaggedEntry>>,
+    module_cache: Option<Arc<dyn StylusModuleCache>>,
+}
+
+impl Inner {
+    /// Entry for `code_hash` compiled under `tag` in the current generation, dropping a stale
+    /// one.
+    fn lookup(&mut self, code_hash: &B256, tag: ProgramCacheTag) -> Option<ProgramCacheEntry> {
+        let generation = self.generation;
+        let slot = match self.pinned.get_mut(code_hash) {
+            Some(slot) => slot,
+            None => {
+                return match self.entries.get(code_hash) {
+                    Some(cached) if cached.generation == generation && cached.tag == tag => {
+                        Some(cached.entry.clone())
+                    }
+                    Some(_) => {
+                        self.entries.pop(code_hash);
+                        None
+                    }
+                    None => None,
+                };
+            }
+        };
+        match slot {
+            Some(cached) if cached.generation == generation && cached.tag == tag => {
+                Some(cached.entry.clone())
+            }
+            _ => {
+                *slot = None;
+                None
+            }
+        }
+    }
+
+    fn store(&mut self, code_hash: B256, tag: ProgramCacheTag, entry: ProgramCacheEntry) {
+        let cached = TaggedEntry {
+            generation: self.generation,
+            tag,
+            entry,
+        };
+        match self.pinned.get_mut(&code_hash) {
+            Some(slot) => *slot = Some(cached),
+            None => {
+                self.entries.put(code_hash, cached);
+            }
+        }
+    }
+}
+
+/// Cloneable handle to an LRU cache of compiled programs keyed by code hash.
+#[derive(Clone)]
+pub struct ProgramCache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+lazy_static::lazy_static! {
+    static ref GLOBAL_PROGRAM_CACHE: ProgramCache =
+        ProgramCache::new(NonZeroUsize::new(ProgramCache::DEFAULT_CAPACITY).unwrap());
+}
+
+impl ProgramCache {
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Create a cache that is not shared with any other handle.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                generation: 0,
+                entries: LruCache::new(capacity),
+                pinned: HashMap::new(),
+                module_cache: None,
+            })),
+        }
+    }
+
+    /// Back this cache, and every handle to it, with `module_cache`: programs missing from
+    /// memory load their native code from it, and newly compiled native code is stored in it.
+    pub fn with_module_cache(self, module_cache: Arc<dyn StylusModuleCache>) -> Self {
+        self.set_module_cache(Some(module_cache));
+        self
+    }
+
+    /// Replace the module cache backing this cache, or remove it with `None`.
+    pub fn set_module_cache(&self, module_cache: Option<Arc<dyn StylusModuleCache>>) {
+        self.inner.lock().unwrap().module_cache = module_cache;
+    }
+
+    pub fn module_cache(&self) -> Option<Arc<dyn StylusModuleCache>> {
+        self.inner.lock().unwrap().module_cache.clone()
+    }
+
+    /// Handle to the process-wide cache used by default.
+    pub fn global() -> Self {
+        GLOBAL_PROGRAM_CACHE.clone()
+    }
+
+    /// Whether both handles refer to the same cache.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Current generation. Only entries inserted in this generation are served.
+    pub fn generation(&self) -> u64 {
+        self.inner.lock().unwrap().generation
+    }
+
+    /// Start a new generation, making every existing entry stale. Stale entries are dropped as
+    /// they are looked up or evicted. The module cache is left as is. Returns the new generation.
+    pub fn invalidate(&self) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        inner.generation
+    }
+
+    /// Cached program for `code_hash` compiled under `tag` in the current generation.
+    pub fn get(&self, code_hash: &B256, tag: ProgramCacheTag) -> Option<ProgramCacheEntry> {
+        self.inner.lock().unwrap().lookup(code_hash, tag)
+    }
+
+    /// Cache a program compiled under `tag`, replacing any entry for the same code hash.
+    pub fn insert(&self, code_hash: B256, tag: ProgramCacheTag, entry: ProgramCacheEntry) {
+        self.inner.lock().unwrap().store(code_hash, tag, entry);
+    }
+
+    /// Cached program for `code_hash`, compiling it with `compile` on a miss. The lock is held
+    /// while compiling so that concurrent callers compile each program once.
+    pub fn get_or_try_insert(
+        &self,
+        code_hash: B256,
+        tag: ProgramCacheTag,
+        compile: impl FnOnce() -> Result<ProgramCacheEntry, String>,
+    ) -> Result<ProgramCacheEntry, String> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(entry) = inner.lookup(&code_hash, tag) {
+            return Ok(entry);
+        }
+
+        let entry = compile()?;
+        inner.store(code_hash, tag, entry.clone());
+        Ok(entry)
+    }
+
+    /// Keep the program for `code_hash` cached for good: it is moved out of the LRU if already
+    /// cached, and held outside it once compiled. Returns whether the code hash was newly pinned.
+    pub fn pin(&self, code_hash: B256) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.pinned.contains_key(&code_hash) {
+            return false;
+        }
+        let cached = inner.entries.pop(&code_hash);
+        inner.pinned.insert(code_hash, cached);
+        true
+    }
+
+    /// Return the program for `code_hash` to the LRU, where it can be evicted again. Returns
+    /// whether the code hash was pinned.
+    pub fn unpin(&self, code_hash: &B256) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.pinned.remove(code_hash) {
+            Some(cached) => {
+                if let Some(cached) = cached {
+                    inner.entries.put(*code_hash, cached);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_pinned(&self, code_hash: &B256) -> bool {
+        self.inner.lock().unwrap().pinned.contains_key(code_hash)
+    }
+
+    /// Pinned code hashes, whether or not their program has been compiled yet.
+    pub fn pinned(&self) -> Vec<B256> {
+        self.inner.lock().unwrap().pinned.keys().copied().collect()
+    }
+
+    /// Number of entries held, pinned ones included, including stale ones not yet dropped.
+    pub fn len(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+        inner.entries.len() + inner.pinned.values().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every entry. Pinned code hashes stay pinned and are held again once recompiled.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.clear();
+        inner.pinned.values_mut().for_each(|cached| *cached = None);
+    }
+}
+
+impl Default for ProgramCache {
+    fn default() -> Self {
+        Self::global()
+    }
+}
+
+impl fmt::Debug for ProgramCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_struct("ProgramCache")
+            .field("generation", &inner.generation)
+            .field("len", &inner.entries.len())
+            .field("capacity", &inner.entries.cap())
+            .field("pinned", &inner.pinned.len())
+            .field("module_cache", &inner.module_cache.is_some())
+            .finish()
+    }
+}
@@ -1,23 +1,168 @@
-use revm::{context_interface::result::HaltReason, interpreter::InstructionResult};
+use revm::{
+    context_interface::result::{ExecutionResult, HaltReason, OutOfGasError},
+    interpreter::InstructionResult,
+    primitives::{Address, B256, U256},
+};
 
+use crate::local_context::PrecompileGasUsage;
+
+/// Why an Arbitrum call failed: an EVM halt, or a failure of a Stylus program or of the
+/// transaction against ArbOS's gas limit told apart from the EVM halt or revert it is reported
+/// as.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct ArbitrumHaltReason(pub HaltReason);
+pub enum ArbitrumHaltReason {
+    /// An EVM halt.
+    Base(HaltReason),
+    /// A Stylus program ran out of ink, reported as running out of gas.
+    OutOfInk,
+    /// A Stylus program overflowed its WASM stack, reported as a stack overflow.
+    WasmStackOverflow,
+    /// A Stylus program was called while not activated and auto-activation is disabled,
+    /// reported as running out of gas.
+    ProgramNotActivated,
+    /// A Stylus program failed to compile when called, reported as a revert.
+    ActivationFailed,
+    /// A Stylus program failed after requesting a hostio this EVM does not serve, reported as
+    /// a revert.
+    HostioUnavailable,
+    /// The transaction ran out of the gas ArbOS lets a transaction use, which its gas limit
+    /// exceeded, reported as running out of gas.
+    TxGasLimitExceeded,
+}
+
+impl ArbitrumHaltReason {
+    /// The EVM halt this is reported as, if it is not reported as a revert.
+    pub fn halt_reason(&self) -> Option<HaltReason> {
+        match self {
+            Self::Base(reason) => Some(reason.clone()),
+            Self::OutOfInk | Self::ProgramNotActivated | Self::TxGasLimitExceeded => {
+                Some(HaltReason::OutOfGas(OutOfGasError::Basic))
+            }
+            Self::WasmStackOverflow => Some(HaltReason::StackOverflow),
+            Self::ActivationFailed | Self::HostioUnavailable => None,
+        }
+    }
+
+    /// Whether this is a failure of a Stylus program rather than an EVM halt or ArbOS's gas
+    /// limit.
+    pub fn is_stylus(&self) -> bool {
+        !matches!(self, Self::Base(_) | Self::TxGasLimitExceeded)
+    }
+}
 
 impl From<HaltReason> for ArbitrumHaltReason {
     fn from(value: HaltReason) -> Self {
-        Self(value)
+        Self::Base(value)
     }
 }
 
-impl From<ArbitrumHaltReason> for HaltReason {
+impl From<ArbitrumHaltReason> for InstructionResult {
     fn from(val: ArbitrumHaltReason) -> Self {
-        val.0
+        match val {
+            ArbitrumHaltReason::Base(reason) => reason.into(),
+            ArbitrumHaltReason::OutOfInk
+            | ArbitrumHaltReason::ProgramNotActivated
+            | ArbitrumHaltReason::TxGasLimitExceeded => InstructionResult::OutOfGas,
+            ArbitrumHaltReason::WasmStackOverflow => InstructionResult::StackOverflow,
+            ArbitrumHaltReason::ActivationFailed | ArbitrumHaltReason::HostioUnavailable => {
+                InstructionResult::Revert
+            }
+        }
     }
 }
 
-impl From<ArbitrumHaltReason> for InstructionResult {
-    fn from(val: ArbitrumHaltReason) -> Self {
-        val.0.into()
+/// Breakdown of the gas a transaction used, for gas profiling.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArbitrumExecutionDetails {
+    /// Ink used by Stylus programs, across all their frames.
+    pub ink_used: u64,
+    /// Most Stylus pages open at once.
+    pub pages_allocated: u16,
+    /// Gas charged to start Stylus programs, for memory growth and initialization.
+    pub stylus_init_gas: u64,
+    /// L1 data fee charged to the transaction, in wei.
+    pub l1_data_fee: U256,
+    /// The L1 data fee in L2 gas.
+    pub poster_gas: u64,
+    /// Gas used by each precompile method called.
+    pub precompile_gas: PrecompileGasUsage,
+    /// Failure of the Stylus program the transaction called, when it ended the call.
+    pub stylus_failure: Option<ArbitrumHaltReason>,
+    /// Gas of the transaction's limit above what ArbOS lets a transaction use, held back from
+    /// execution and refunded.
+    pub compute_hold_gas: u64,
+    /// [`ArbitrumHaltReason::TxGasLimitExceeded`] when the transaction ran out of the gas ArbOS
+    /// lets it use rather than out of its own gas limit.
+    pub gas_limit_failure: Option<ArbitrumHaltReason>,
+}
+
+/// Gas a transaction needs once the Stylus programs it calls are activated. Calling a program
+/// that is not activated, or whose activation expired or predates the current Stylus version,
+/// auto-activates it for free, but on a live chain the call fails until the program is
+/// activated through ArbWasm, so the transaction's gas alone under-reports what it costs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbitrumGasEstimate {
+    /// Outcome of the transaction, with its programs auto-activated.
+    pub result: ExecutionResult<HaltReason>,
+    /// Activations the transaction needs first, one per program in the order they were called.
+    pub activations: Vec<ActivationEstimate>,
+}
+
+impl ArbitrumGasEstimate {
+    /// Gas used by the transaction and the activations it needs.
+    pub fn total_gas_used(&self) -> u64 {
+        self.activations
+            .iter()
+            .fold(self.result.gas_used(), |total, activation| {
+                total.saturating_add(activation.gas_used)
+            })
+    }
+
+    /// Data fee of the activations, in wei.
+    pub fn total_data_fee(&self) -> U256 {
+        self.activations
+            .iter()
+            .map(|activation| activation.data_fee)
+            .sum()
+    }
+}
+
+/// Activation through `ArbWasm.activateProgram` of a program a transaction calls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActivationEstimate {
+    /// Account the program was called through.
+    pub program: Address,
+    pub code_hash: B256,
+    /// Gas used by the activation transaction.
+    pub gas_used: u64,
+    /// Data fee the activation charges, in wei, paid as the call's value.
+    pub data_fee: U256,
+    /// Whether the activation succeeded. A program failing activation charges no data fee and
+    /// cannot be called on a live chain.
+    pub success: bool,
+}
+
+/// Gas limit a transaction needs, split as Nitro's `NodeInterface.gasEstimateComponents` splits
+/// it between execution and the L1 data fee.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbitrumGasEstimateComponents {
+    /// Gas limit the transaction needs, covering its execution and its L1 data fee.
+    pub gas_estimate: u64,
+    /// Part of the gas limit paying the L1 data fee, padded against L1 and L2 price changes.
+    pub gas_estimate_for_l1: u64,
+    /// L2 basefee the estimate was made at, in wei.
+    pub base_fee: U256,
+    /// ArbOS's estimate of the L1 basefee, in wei per unit of L1 data.
+    pub l1_base_fee_estimate: U256,
+    /// Outcome of the transaction with the execution gas found, or with the most gas tried if it
+    /// failed with any.
+    pub result: ExecutionResult<HaltReason>,
+}
+
+impl ArbitrumGasEstimateComponents {
+    /// Part of the gas limit the transaction executes with.
+    pub fn gas_estimate_for_l2(&self) -> u64 {
+        self.gas_estimate.saturating_sub(self.gas_estimate_for_l1)
     }
 }
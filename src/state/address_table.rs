@@ -5,18 +5,12 @@ use crate::{
         StorageBackedU64, StorageBackedU256, map_address, substorage,
     },
 };
-use alloy_rlp::{BufMut, Decodable, Encodable, Error, Header};
+use alloy_rlp::{Decodable, Encodable, Error, Header};
 use revm::{
     interpreter::Gas,
     primitives::{Address, B256, Bytes, U256},
 };
 
-#[derive(Debug, Clone)]
-enum RLPItem {
-    Address(Address),
-    Index(u64),
-}
-
 pub struct AddressTable<'a, CTX>
 where
     CTX: ArbitrumContextTr,
@@ -181,74 +175,36 @@ where
         .map(Some)
     }
 
+    /// Compress `address` as Nitro does: the RLP encoding of its index if it is registered,
+    /// otherwise of its 20 bytes.
     pub fn compress(&mut self, address: Address) -> Result<Bytes, ArbosStateError> {
-        if let Some(index) = self.lookup(address)? {
-            // encode as index
-            let item = RLPItem::Index(index); // stored as 1-based
-            let mut out = Vec::new();
-            item.encode(&mut out);
-            Ok(Bytes::from(out))
-        } else {
-            // encode as address
-            let item = RLPItem::Address(address);
-            let mut out = Vec::new();
-            item.encode(&mut out);
-            Ok(Bytes::from(out))
+        let mut out = Vec::new();
+        match self.lookup(address)? {
+            Some(index) => index.encode(&mut out),
+            None => address.encode(&mut out),
         }
+        Ok(Bytes::from(out))
     }
 
+    /// Read an address compressed by [`AddressTable::compress`] from the start of `data`,
+    /// returning it with the number of bytes it took. An RLP string of 20 bytes is the address
+    /// itself, anything else has to be the canonical RLP encoding of a registered index.
     pub fn decompress(&mut self, data: &[u8]) -> Result<(Address, u64), ArbosStateError> {
-        let mut remaining = data;
-        let item = RLPItem::decode(&mut remaining)
-            .map_err(|e| ArbosStateError::DecompressError(format!("RLP decode error: {e:?}")))?;
-        let consumed = data.len().saturating_sub(remaining.len()) as u64;
-
-        match item {
-            RLPItem::Address(addr) => Ok((addr, consumed)),
-            RLPItem::Index(idx) => {
-                let addr = self.lookup_index(idx)?.ok_or_else(|| {
-                    ArbosStateError::DecompressError(
-                        "invalid index in compressed address".to_string(),
-                    )
-                })?;
-                Ok((addr, consumed))
-            }
-        }
-    }
-}
+        let decode_error = |e: Error| ArbosStateError::DecompressError(format!("{e}"));
 
-impl Encodable for RLPItem {
-    fn encode(&self, out: &mut dyn BufMut) {
-        let mut payload = Vec::new();
-        match self {
-            Self::Address(addr) => {
-                // prefix discriminant then the address RLP
-                0u8.encode(&mut payload);
-                addr.encode(&mut payload);
-            }
-            Self::Index(idx) => {
-                // prefix discriminant then the index RLP
-                1u8.encode(&mut payload);
-                idx.encode(&mut payload);
-            }
+        let mut remaining = data;
+        let payload = Header::decode_bytes(&mut remaining, false).map_err(decode_error)?;
+        if payload.len() == Address::len_bytes() {
+            let address = Address::from_slice(payload);
+            return Ok((address, (data.len() - remaining.len()) as u64));
         }
-        // wrap the payload with a bytes header so decode_bytes can parse it
-        let header = Header {
-            list: false,
-            payload_length: payload.len(),
-        };
-        header.encode(out);
-        out.put_slice(&payload);
-    }
-}
 
-impl Decodable for RLPItem {
-    fn decode(data: &mut &[u8]) -> Result<Self, Error> {
-        let mut payload = Header::decode_bytes(data, true)?;
-        match u8::decode(&mut payload)? {
-            0 => Ok(Self::Address(Address::decode(&mut payload)?)),
-            1 => Ok(Self::Index(u64::decode(&mut payload)?)),
-            _ => Err(Error::Custom("unknown type")),
-        }
+        let mut remaining = data;
+        let index = u64::decode(&mut remaining).map_err(decode_error)?;
+        let consumed = (data.len() - remaining.len()) as u64;
+        let address = self.lookup_index(index)?.ok_or_else(|| {
+            ArbosStateError::DecompressError("invalid index in compressed address".to_string())
+        })?;
+        Ok((address, consumed))
     }
 }
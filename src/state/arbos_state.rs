@@ -1,22 +1,32 @@
 use revm::{
     context::{Block, Cfg, JournalTr},
+    context_interface::journaled_state::JournalCheckpoint,
     interpreter::{Gas, gas::COLD_ACCOUNT_ACCESS_COST},
-    primitives::{Address, B256, U256},
+    primitives::{Address, B256, Bytes, I256, U256},
+    state::Bytecode,
 };
 
 use crate::{
     ArbitrumContextTr,
+    arbos_version::ArbosVersion,
+    config::ArbitrumConfigTr,
     constants::{
-        ARBOS_CHAIN_CONFIG_KEY, ARBOS_CHAIN_OWNERS_KEY, ARBOS_STATE_ADDRESS,
+        ARBOS_CHAIN_CONFIG_KEY, ARBOS_CHAIN_OWNERS_KEY, ARBOS_L1_PRICER_FUNDS_ADDRESS,
         ARBOS_STATE_ADDRESS_TABLE_KEY, ARBOS_STATE_BLOCKHASHES_KEY, ARBOS_STATE_FEATURES_KEY,
         ARBOS_STATE_L1_PRICING_KEY, ARBOS_STATE_L2_PRICING_KEY, ARBOS_STATE_NATIVE_TOKEN_OWNER_KEY,
-        ARBOS_STATE_PROGRAMS_KEY, ARBOS_STATE_RETRYABLES_KEY,
+        ARBOS_STATE_PROGRAMS_KEY, ARBOS_STATE_RETRYABLES_KEY, ARBOS_STATE_SEND_MERKLE_KEY,
+        INITIAL_L1_EQUILIBRATION_UNITS_V6, INITIAL_MAX_WASM_SIZE, INITIAL_PER_BATCH_GAS_COST_V6,
+        INITIAL_PER_BATCH_GAS_COST_V12, INITIAL_PER_BLOCK_GAS_LIMIT_V6,
+        INITIAL_PER_TX_GAS_LIMIT_V50, INITIAL_SPEED_LIMIT_PER_SECOND_V6, PRECOMPILE_MARKER_CODE,
+        PRECOMPILE_MIN_ARBOS_VERSIONS, V2_MIN_INIT_GAS,
     },
+    local_context::ArbitrumLocalContextTr,
     state::{
         address_table::AddressTable,
         block_hashes::BlockHashes,
         l1_pricing::L1Pricing,
         l2_pricing::L2Pricing,
+        merkle_accumulator::MerkleAccumulator,
         program::{DataPricerParams, Programs, StylusParams},
         retryable::{Retryable, RetryableState},
         types::{
@@ -26,6 +36,7 @@ use crate::{
     },
 };
 
+const ARBOS_STATE_VERSION_OFFSET: u8 = 0;
 const ARBOS_STATE_UPGRADE_VERSION_OFFSET: u8 = 1;
 const ARBOS_STATE_UPGRADE_TIMESTAMP_OFFSET: u8 = 2;
 const ARBOS_STATE_NETWORK_FEE_ACCOUNT_OFFSET: u8 = 3;
@@ -50,6 +61,7 @@ pub trait ArbStateGetter<CTX: ArbitrumContextTr> {
     fn is_chain_owner(&mut self, address: Address) -> Result<bool, ArbosStateError>;
     fn is_native_token_owner(&mut self, address: Address) -> Result<bool, ArbosStateError>;
     fn code_hash(&mut self, address: Address) -> Result<B256, ArbosStateError>;
+    fn arbos_version(&mut self) -> StorageBackedU64<'_, CTX>;
     fn upgrade_timestamp(&mut self) -> StorageBackedU64<'_, CTX>;
     fn upgrade_version(&mut self) -> StorageBackedU64<'_, CTX>;
     fn network_fee_account(&mut self) -> StorageBackedAddress<'_, CTX>;
@@ -67,17 +79,40 @@ pub trait ArbStateGetter<CTX: ArbitrumContextTr> {
     fn features(&mut self) -> StorageBackedU256<'_, CTX>;
     fn chain_config(&mut self) -> StorageBackedU256<'_, CTX>;
     fn blockhashes(&mut self) -> BlockHashes<'_, CTX>;
+    fn send_merkle_accumulator(&mut self) -> MerkleAccumulator<'_, CTX>;
 }
 
 pub trait ArbState<'a, CTX: ArbitrumContextTr> {
     type ArbStateGetterType: ArbStateGetter<CTX>;
+    /// Open ArbOS state, charging its storage accesses to `gas` if given, as precompiles do.
     fn arb_state(
         &'a mut self,
         gas: Option<&'a mut Gas>,
         is_static: bool,
     ) -> Self::ArbStateGetterType;
+
+    /// Open ArbOS state for ArbOS's own bookkeeping, like Nitro's `OpenSystemArbosState`: block
+    /// starts, fee calculation and Stylus execution read and write pricing state through it
+    /// without consuming the gas of the transaction being executed.
+    fn system_arb_state(&'a mut self, is_static: bool) -> Self::ArbStateGetterType;
+
+    /// Mark a point that ArbOS state, with the rest of the journal, can be reverted to within
+    /// the current transaction. Snapshots nest, and each must be reverted to or committed,
+    /// innermost first. Use `ArbitrumEvm::simulate` to discard whole transactions.
+    fn snapshot(&mut self) -> ArbStateSnapshot;
+
+    /// Undo every change made since `snapshot` was taken, releasing it.
+    fn revert_to(&mut self, snapshot: ArbStateSnapshot);
+
+    /// Keep the changes made since `snapshot` was taken, releasing it.
+    fn commit_snapshot(&mut self, snapshot: ArbStateSnapshot);
 }
 
+/// Journal checkpoint marking a point ArbOS state can be reverted to, see
+/// [`ArbState::snapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArbStateSnapshot(JournalCheckpoint);
+
 impl<'a, CTX: ArbitrumContextTr + 'a> ArbState<'a, CTX> for CTX {
     type ArbStateGetterType = ArbStateWrapper<'a, CTX>;
     fn arb_state(
@@ -87,6 +122,23 @@ impl<'a, CTX: ArbitrumContextTr + 'a> ArbState<'a, CTX> for CTX {
     ) -> Self::ArbStateGetterType {
         ArbStateWrapper::new(self, gas, is_static)
     }
+
+    fn system_arb_state(&'a mut self, is_static: bool) -> Self::ArbStateGetterType {
+        ArbStateWrapper::new(self, None, is_static)
+    }
+
+    fn snapshot(&mut self) -> ArbStateSnapshot {
+        ArbStateSnapshot(self.journal_mut().checkpoint())
+    }
+
+    fn revert_to(&mut self, snapshot: ArbStateSnapshot) {
+        self.journal_mut().checkpoint_revert(snapshot.0);
+        self.local_mut().record_journal_revert();
+    }
+
+    fn commit_snapshot(&mut self, _snapshot: ArbStateSnapshot) {
+        self.journal_mut().checkpoint_commit();
+    }
 }
 
 pub struct ArbStateWrapper<'a, CTX: ArbitrumContextTr> {
@@ -96,6 +148,7 @@ pub struct ArbStateWrapper<'a, CTX: ArbitrumContextTr> {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArbosStateParams {
     pub upgrade_version: u64,
     pub upgrade_timestamp: u64,
@@ -128,11 +181,12 @@ impl Default for ArbosStateParams {
 
 impl<'a, CTX: ArbitrumContextTr> ArbStateWrapper<'a, CTX> {
     pub fn new(context: &'a mut CTX, mut gas: Option<&'a mut Gas>, is_static: bool) -> Self {
-        if let Err(err) = context.journal_mut().load_account_info_skip_cold_load(
-            ARBOS_STATE_ADDRESS,
-            false,
-            false,
-        ) {
+        let state_address = context.cfg().arbos_state_address();
+        if let Err(err) =
+            context
+                .journal_mut()
+                .load_account_info_skip_cold_load(state_address, false, false)
+        {
             // Consume all gas so downstream callers see a consistent failure state rather than a
             // panic.
             if let Some(gas) = gas.as_deref_mut() {
@@ -166,10 +220,200 @@ where
 
         self.programs()
             .initialize(&params.stylus_params, &params.data_pricer_params)?;
+        self.retryable_state().initialize()?;
+
+        self.initialize_accounts()?;
+
+        Ok(())
+    }
+
+    /// Give the precompiles available at the current ArbOS version marker code, and the ArbOS
+    /// state account a nonce, as Nitro does so that neither is treated as an empty account.
+    pub fn initialize_accounts(&mut self) -> Result<(), ArbosStateError> {
+        if self.is_static {
+            return Err(ArbosStateError::StateChangeDuringStaticCall);
+        }
+
+        let version = self.context.arbos_version();
+        self.install_precompiles(|introduced_in| version >= ArbosVersion::new(introduced_in))?;
+
+        let state_address = self.context.cfg().arbos_state_address();
+        let mut arbos_state = self
+            .context
+            .journal_mut()
+            .load_account_with_code_mut(state_address)
+            .map_err(|err| ArbosStateError::Context(err.to_string()))?
+            .data;
+        if arbos_state.info.nonce == 0 {
+            arbos_state.bump_nonce();
+        }
 
         Ok(())
     }
 
+    /// Give marker code to the precompiles whose introducing ArbOS version `introduced` accepts.
+    fn install_precompiles(
+        &mut self,
+        introduced: impl Fn(u16) -> bool,
+    ) -> Result<(), ArbosStateError> {
+        let precompiles: Vec<_> = PRECOMPILE_MIN_ARBOS_VERSIONS
+            .iter()
+            .filter(|(_, introduced_in)| introduced(*introduced_in))
+            .map(|(address, _)| self.context.cfg().precompile_address(*address))
+            .collect();
+        let journal = self.context.journal_mut();
+
+        for address in precompiles {
+            journal
+                .load_account(address)
+                .map_err(|err| ArbosStateError::Context(err.to_string()))?;
+            journal.set_code(
+                address,
+                Bytecode::new_raw(Bytes::from_static(PRECOMPILE_MARKER_CODE)),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Upgrade to the scheduled ArbOS version once its timestamp is reached, as Nitro's
+    /// `UpgradeArbosVersionIfNecessary` does when StartBlock opens a block at
+    /// `current_timestamp`. Uninitialized state, at version 0, is left as is.
+    pub fn upgrade_arbos_version_if_necessary(
+        &mut self,
+        current_timestamp: u64,
+    ) -> Result<(), ArbosStateError> {
+        let version = self.arbos_version().get()?;
+        let upgrade_to = self.upgrade_version().get()?;
+        let upgrade_timestamp = self.upgrade_timestamp().get()?;
+        if version != 0 && version < upgrade_to && current_timestamp >= upgrade_timestamp {
+            self.upgrade_arbos_version(upgrade_to, false)?;
+        }
+        Ok(())
+    }
+
+    /// Upgrade ArbOS state to version `upgrade_to`, running the migration of each version in
+    /// between and giving code to the precompiles it introduces, as Nitro's `UpgradeArbosVersion`
+    /// does. `first_time` is set when upgrading freshly initialized state at genesis, which also
+    /// replaces the initial pricing parameters with the ones of ArbOS 6.
+    ///
+    /// Versions reserved for Orbit chains migrate nothing. The EIP-2935 block hash history
+    /// contract ArbOS 40 deploys is not installed.
+    pub fn upgrade_arbos_version(
+        &mut self,
+        upgrade_to: u64,
+        first_time: bool,
+    ) -> Result<(), ArbosStateError> {
+        if self.is_static {
+            return Err(ArbosStateError::StateChangeDuringStaticCall);
+        }
+
+        let mut version = self.arbos_version().get()?;
+        while version < upgrade_to {
+            let next = version + 1;
+            self.migrate_to(next, first_time)?;
+            self.install_precompiles(|introduced_in| u64::from(introduced_in) == next)?;
+            version = next;
+        }
+
+        if first_time && upgrade_to >= 6 {
+            let mut l1_pricing = self.l1_pricing();
+            if upgrade_to < 11 {
+                l1_pricing
+                    .per_batch_gas_cost()
+                    .set(INITIAL_PER_BATCH_GAS_COST_V6)?;
+            }
+            l1_pricing
+                .equilibration_units()
+                .set(U256::from(INITIAL_L1_EQUILIBRATION_UNITS_V6))?;
+            let mut l2_pricing = self.l2_pricing();
+            l2_pricing
+                .speed_limit_per_second()
+                .set(INITIAL_SPEED_LIMIT_PER_SECOND_V6)?;
+            l2_pricing
+                .per_block_gas_limit()
+                .set(INITIAL_PER_BLOCK_GAS_LIMIT_V6)?;
+        }
+
+        self.arbos_version().set(version)
+    }
+
+    /// Apply the state changes of upgrading from the ArbOS version before `version` to it.
+    fn migrate_to(&mut self, version: u64, first_time: bool) -> Result<(), ArbosStateError> {
+        match version {
+            2 => self.l1_pricing().last_surplus().set(I256::ZERO),
+            3 => {
+                let mut l1_pricing = self.l1_pricing();
+                l1_pricing.per_batch_gas_cost().set(0)?;
+                l1_pricing.amortized_cost_cap_bips().set(u64::MAX)
+            }
+            4..=9 => Ok(()),
+            10 => {
+                let balance = self
+                    .context
+                    .journal_mut()
+                    .load_account(ARBOS_L1_PRICER_FUNDS_ADDRESS)
+                    .map_err(|err| ArbosStateError::Context(err.to_string()))?
+                    .data
+                    .info
+                    .balance;
+                self.l1_pricing().l1_fees_available().set(balance)
+            }
+            11 => {
+                let mut l1_pricing = self.l1_pricing();
+                l1_pricing
+                    .per_batch_gas_cost()
+                    .set(INITIAL_PER_BATCH_GAS_COST_V12)?;
+                // ArbOS 3 meant to disable the cap with u64::MAX, but it is 0 that disables it
+                if l1_pricing.amortized_cost_cap_bips().get()? == u64::MAX {
+                    l1_pricing.amortized_cost_cap_bips().set(0)?;
+                }
+                // Lets owners whose list entry was lost rectify their mapping
+                if !first_time {
+                    self.chain_owners().clear_list()?;
+                }
+                Ok(())
+            }
+            12..=19 => Ok(()),
+            20 => self.brotli_compression_level().set(1),
+            21..=29 => Ok(()),
+            30 => {
+                let stylus_params = StylusParams {
+                    version: 1,
+                    max_wasm_size: 0,
+                    ..Default::default()
+                };
+                self.programs()
+                    .initialize(&stylus_params, &DataPricerParams::default())
+            }
+            31 => {
+                let mut programs = self.programs();
+                let mut params = programs.stylus_params().get()?;
+                // State initialized with the current Stylus params is already at version 2
+                if params.version == 1 {
+                    params.version = 2;
+                    params.min_init_gas = V2_MIN_INIT_GAS;
+                    programs.stylus_params().set(&params)?;
+                }
+                Ok(())
+            }
+            32..=39 => Ok(()),
+            40 => {
+                // The maximum wasm size was a constant before ArbOS 40
+                let mut programs = self.programs();
+                let mut params = programs.stylus_params().get()?;
+                params.max_wasm_size = INITIAL_MAX_WASM_SIZE;
+                programs.stylus_params().set(&params)
+            }
+            41..=49 => Ok(()),
+            50 => self
+                .l2_pricing()
+                .per_tx_gas_limit()
+                .set(INITIAL_PER_TX_GAS_LIMIT_V50),
+            _ => Err(ArbosStateError::UnrecognizedArbosVersion(version)),
+        }
+    }
+
     pub fn get(&mut self) -> Result<ArbosStateParams, ArbosStateError> {
         // Read values from storage
         let mut params = ArbosStateParams {
@@ -248,6 +492,14 @@ where
             state_slot(ARBOS_STATE_GENESIS_BLOCK_NUM_OFFSET),
         )
     }
+    fn arbos_version(&mut self) -> StorageBackedU64<'_, CTX> {
+        StorageBackedU64::new(
+            self.context,
+            self.gas.as_deref_mut(),
+            self.is_static,
+            state_slot(ARBOS_STATE_VERSION_OFFSET),
+        )
+    }
     fn upgrade_version(&mut self) -> StorageBackedU64<'_, CTX> {
         StorageBackedU64::new(
             self.context,
@@ -282,6 +534,15 @@ where
         )
     }
 
+    fn send_merkle_accumulator(&mut self) -> MerkleAccumulator<'_, CTX> {
+        MerkleAccumulator::new(
+            self.context,
+            self.gas.as_deref_mut(),
+            self.is_static,
+            state_subkey(ARBOS_STATE_SEND_MERKLE_KEY),
+        )
+    }
+
     fn chain_owners<'b>(&'b mut self) -> StorageBackedAddressSet<'b, CTX> {
         StorageBackedAddressSet::new(
             self.context,
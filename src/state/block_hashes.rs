@@ -1,5 +1,7 @@
 use crate::{
     ArbitrumContextTr,
+    arbos_version::{ArbosFeature, ArbosVersion},
+    local_context::ArbitrumLocalContextTr,
     state::types::{
         ArbosStateError, StorageBackedB256, StorageBackedTr, StorageBackedU64, map_address,
     },
@@ -7,9 +9,51 @@ use crate::{
 
 use revm::{
     interpreter::Gas,
-    primitives::{B256, U256, keccak256},
+    primitives::{B256, BLOCK_HASH_HISTORY, U256, keccak256},
 };
 
+/// Hashes of the most recent L2 blocks, one per slot of a ring buffer as long as the BLOCKHASH
+/// window. Unlike the L1 hashes in [`BlockHashes`], these are not part of ArbOS state: the
+/// block builder records each hash once the block is sealed.
+#[derive(Clone, Debug)]
+pub struct RecentBlockHashes {
+    hashes: Box<[Option<(u64, B256)>; BLOCK_HASH_HISTORY as usize]>,
+}
+
+impl Default for RecentBlockHashes {
+    fn default() -> Self {
+        Self {
+            hashes: Box::new([None; BLOCK_HASH_HISTORY as usize]),
+        }
+    }
+}
+
+impl RecentBlockHashes {
+    /// Record the hash of block `number`, taking the slot of the block
+    /// [`BLOCK_HASH_HISTORY`] before it.
+    pub fn record(&mut self, number: u64, hash: B256) {
+        self.hashes[(number % BLOCK_HASH_HISTORY) as usize] = Some((number, hash));
+    }
+
+    /// The recorded hash of block `number`, unless it was never recorded or has been
+    /// overwritten since.
+    pub fn get(&self, number: u64) -> Option<B256> {
+        self.hashes[(number % BLOCK_HASH_HISTORY) as usize]
+            .filter(|(recorded, _)| *recorded == number)
+            .map(|(_, hash)| hash)
+    }
+}
+
+/// Hash of L2 block `number`, read from the recorded recent block hashes and from the database
+/// for blocks that were not recorded. `None` if the database fails, with the error kept in the
+/// context.
+pub fn l2_block_hash<CTX: ArbitrumContextTr>(context: &mut CTX, number: u64) -> Option<B256> {
+    match context.local().recent_block_hashes().get(number) {
+        Some(hash) => Some(hash),
+        None => context.block_hash(number),
+    }
+}
+
 pub struct BlockHashes<'a, CTX>
 where
     CTX: ArbitrumContextTr,
@@ -57,7 +101,7 @@ where
         &mut self,
         number: u64,
         block_hash: B256,
-        arbos_version: u64,
+        arbos_version: ArbosVersion,
     ) -> Result<(), ArbosStateError> {
         let mut next_number = self.l1_block_number().get()?;
         if number < next_number {
@@ -71,7 +115,7 @@ where
             // fill in hashes for any "skipped over" blocks
             next_number += 1;
             let mut next_num_buf = [0u8; 8];
-            if arbos_version >= 8 {
+            if arbos_version.supports(ArbosFeature::L1BlockHashNumbering) {
                 next_num_buf.copy_from_slice(&next_number.to_le_bytes());
             }
 
@@ -0,0 +1,320 @@
+//! Export and import of ArbOS state as a structured dump, independent of the database holding
+//! it, for test fixtures and for diffing against other implementations.
+//!
+//! Programs are stored by code hash and cannot be listed from storage, so the dump only holds
+//! those exported through [`ArbStateWrapper::export_programs`]. Retryables are found through
+//! their timeout queue, which holds every live ticket.
+
+use std::collections::HashSet;
+
+use revm::primitives::{Address, B256, U256};
+
+use crate::{
+    ArbitrumContextTr,
+    state::{
+        ArbStateGetter, ArbStateWrapper, ArbosStateParams,
+        pricing::{L1PricingSnapshot, L2PricingSnapshot},
+        program::ProgramInfo,
+        types::{ArbosStateError, StorageBackedTr},
+    },
+};
+
+/// ArbOS state, as exported by [`ArbStateWrapper::export`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArbosStateDump {
+    pub arbos_version: u64,
+    /// Parameters ArbOS state was initialized with, as they are now.
+    pub params: ArbosStateParams,
+    pub chain_owners: Vec<Address>,
+    pub native_token_owners: Vec<Address>,
+    pub l1_pricing: L1PricingDump,
+    pub l2_pricing: L2PricingSnapshot,
+    pub cache_managers: Vec<Address>,
+    pub programs: Vec<ProgramDump>,
+    /// Live retryable tickets, in the order they time out.
+    pub retryables: Vec<RetryableDump>,
+    /// Addresses registered in the address table, by index.
+    pub address_table: Vec<Address>,
+}
+
+/// L1 pricing state, with the batch posters it pays.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct L1PricingDump {
+    pub snapshot: L1PricingSnapshot,
+    pub reward_recipient: Address,
+    pub per_batch_gas_cost: u64,
+    pub amortized_cost_cap_bips: u64,
+    pub gas_floor_per_token: u64,
+    pub batch_posters: Vec<BatchPosterDump>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatchPosterDump {
+    pub address: Address,
+    pub pay_recipient: Address,
+    pub funds_due: U256,
+}
+
+/// An activated Stylus program.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProgramDump {
+    pub code_hash: B256,
+    pub module_hash: B256,
+    pub info: ProgramInfo,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetryableDump {
+    pub id: B256,
+    pub num_tries: u64,
+    pub from: Address,
+    pub to: Address,
+    pub callvalue: U256,
+    pub beneficiary: Address,
+    pub calldata: Vec<u8>,
+    pub timeout: u64,
+    /// Extra lifetimes bought by keepalives, each with its own entry in the timeout queue.
+    pub timeout_windows_left: u64,
+}
+
+impl<CTX: ArbitrumContextTr> ArbStateWrapper<'_, CTX> {
+    /// Dump ArbOS state, without the programs, see [`Self::export_programs`].
+    pub fn export(&mut self) -> Result<ArbosStateDump, ArbosStateError> {
+        let params = ArbosStateParams {
+            upgrade_version: self.upgrade_version().get()?,
+            upgrade_timestamp: self.upgrade_timestamp().get()?,
+            network_fee_account: self.network_fee_account().get()?,
+            infra_fee_account: self.infra_fee_account().get()?,
+            chain_id: self.chain_id().get()?,
+            genesis_block_num: self.genesis_block_num().get()?,
+            brotli_compression_level: self.brotli_compression_level().get()?,
+            native_token_enabled_time: self.native_token_enabled_time().get()?,
+            stylus_params: self.programs().stylus_params().get()?,
+            data_pricer_params: self.programs().data_pricer().get()?,
+        };
+
+        let address_table = {
+            let mut table = self.address_table();
+            let size = table.size()?;
+            let mut addresses = Vec::new();
+            for index in 0..size {
+                addresses.extend(table.lookup_index(index)?);
+            }
+            addresses
+        };
+
+        Ok(ArbosStateDump {
+            arbos_version: self.arbos_version().get()?,
+            params,
+            chain_owners: self.chain_owners().all()?,
+            native_token_owners: self.native_token_owners().all()?,
+            l1_pricing: self.export_l1_pricing()?,
+            l2_pricing: self.l2_pricing().snapshot()?,
+            cache_managers: self.programs().cache_managers().all()?,
+            programs: Vec::new(),
+            retryables: self.export_retryables()?,
+            address_table,
+        })
+    }
+
+    /// Dump the programs among `code_hashes` that were ever activated.
+    pub fn export_programs(
+        &mut self,
+        code_hashes: &[B256],
+    ) -> Result<Vec<ProgramDump>, ArbosStateError> {
+        let mut programs = Vec::new();
+        for code_hash in code_hashes {
+            let mut state = self.programs();
+            let Some(info) = state.program_info(code_hash)? else {
+                continue;
+            };
+            programs.push(ProgramDump {
+                code_hash: *code_hash,
+                module_hash: state.module_hash(code_hash).get()?,
+                info,
+            });
+        }
+        Ok(programs)
+    }
+
+    fn export_l1_pricing(&mut self) -> Result<L1PricingDump, ArbosStateError> {
+        let mut l1_pricing = self.l1_pricing();
+        let mut batch_posters = Vec::new();
+        let mut table = l1_pricing.batch_poster_table();
+        for address in table.all()? {
+            let mut poster = table.get(address);
+            batch_posters.push(BatchPosterDump {
+                address,
+                pay_recipient: poster.pay_recipient().get()?,
+                funds_due: poster.funds_due().get()?,
+            });
+        }
+
+        Ok(L1PricingDump {
+            snapshot: l1_pricing.snapshot()?,
+            reward_recipient: l1_pricing.reward_recipient().get()?,
+            per_batch_gas_cost: l1_pricing.per_batch_gas_cost().get()?,
+            amortized_cost_cap_bips: l1_pricing.amortized_cost_cap_bips().get()?,
+            gas_floor_per_token: l1_pricing.gas_floor_per_token().get()?,
+            batch_posters,
+        })
+    }
+
+    fn export_retryables(&mut self) -> Result<Vec<RetryableDump>, ArbosStateError> {
+        let queued = self.timeout_queue().all()?;
+        let mut seen = HashSet::new();
+        let mut retryables = Vec::new();
+        for id in queued {
+            let id = B256::from(id);
+            // Keepalives queue a ticket more than once, and deleted tickets stay queued
+            if !seen.insert(id) {
+                continue;
+            }
+            let mut retryable = self.retryable(id);
+            let timeout = retryable.timeout().get()?;
+            if timeout == 0 {
+                continue;
+            }
+            retryables.push(RetryableDump {
+                id,
+                num_tries: retryable.num_tries().get()?,
+                from: retryable.from().get()?,
+                to: retryable.to().get()?,
+                callvalue: retryable.callvalue().get()?,
+                beneficiary: retryable.beneficiary().get()?,
+                calldata: retryable.calldata().get()?,
+                timeout,
+                timeout_windows_left: retryable.timeout_windows_left().get()?,
+            });
+        }
+        Ok(retryables)
+    }
+
+    /// Write `dump` into uninitialized ArbOS state. Owners, batch posters, retryables and the
+    /// address table are added to what the state holds rather than replacing it.
+    pub fn import(&mut self, dump: &ArbosStateDump) -> Result<(), ArbosStateError> {
+        self.initialize(&dump.params)?;
+        self.arbos_version().set(dump.arbos_version)?;
+
+        for owner in &dump.chain_owners {
+            self.chain_owners().add(*owner)?;
+        }
+        for owner in &dump.native_token_owners {
+            self.native_token_owners().add(*owner)?;
+        }
+
+        self.import_l1_pricing(&dump.l1_pricing)?;
+        self.import_l2_pricing(&dump.l2_pricing)?;
+
+        for manager in &dump.cache_managers {
+            self.programs().cache_managers().add(*manager)?;
+        }
+        for program in &dump.programs {
+            let mut programs = self.programs();
+            programs
+                .module_hash(&program.code_hash)
+                .set(program.module_hash)?;
+            programs.restore_program_info(&program.code_hash, &program.info)?;
+        }
+
+        for ticket in &dump.retryables {
+            let mut retryables = self.retryable_state();
+            retryables.create_retryable(
+                ticket.id,
+                ticket.timeout,
+                ticket.from,
+                ticket.to,
+                ticket.callvalue,
+                ticket.beneficiary,
+                &ticket.calldata,
+            )?;
+            for _ in 0..ticket.timeout_windows_left {
+                retryables
+                    .timeout_queue()
+                    .push(U256::from_be_bytes(ticket.id.0))?;
+            }
+            let mut retryable = retryables.retryable(ticket.id);
+            retryable.num_tries().set(ticket.num_tries)?;
+            retryable
+                .timeout_windows_left()
+                .set(ticket.timeout_windows_left)?;
+        }
+
+        for address in &dump.address_table {
+            self.address_table().register(*address)?;
+        }
+        Ok(())
+    }
+
+    fn import_l1_pricing(&mut self, dump: &L1PricingDump) -> Result<(), ArbosStateError> {
+        let mut l1_pricing = self.l1_pricing();
+        let snapshot = &dump.snapshot;
+        l1_pricing.price_per_unit().set(snapshot.price_per_unit)?;
+        l1_pricing
+            .units_since_update()
+            .set(snapshot.units_since_update)?;
+        l1_pricing
+            .last_update_time()
+            .set(snapshot.last_update_time)?;
+        l1_pricing.last_surplus().set(snapshot.last_surplus)?;
+        l1_pricing.inertia().set(snapshot.inertia)?;
+        l1_pricing
+            .equilibration_units()
+            .set(snapshot.equilibration_units)?;
+        l1_pricing.per_unit_reward().set(snapshot.per_unit_reward)?;
+        l1_pricing
+            .funds_due_for_rewards()
+            .set(snapshot.funds_due_for_rewards)?;
+        l1_pricing
+            .l1_fees_available()
+            .set(snapshot.l1_fees_available)?;
+        l1_pricing.reward_recipient().set(dump.reward_recipient)?;
+        l1_pricing
+            .per_batch_gas_cost()
+            .set(dump.per_batch_gas_cost)?;
+        l1_pricing
+            .amortized_cost_cap_bips()
+            .set(dump.amortized_cost_cap_bips)?;
+        l1_pricing
+            .gas_floor_per_token()
+            .set(dump.gas_floor_per_token)?;
+
+        let mut table = l1_pricing.batch_poster_table();
+        for poster in &dump.batch_posters {
+            table.add_if_missing(poster.address, poster.pay_recipient)?;
+            table
+                .get(poster.address)
+                .pay_recipient()
+                .set(poster.pay_recipient)?;
+            table.set_funds_due(poster.address, poster.funds_due)?;
+        }
+        Ok(())
+    }
+
+    fn import_l2_pricing(&mut self, snapshot: &L2PricingSnapshot) -> Result<(), ArbosStateError> {
+        let mut l2_pricing = self.l2_pricing();
+        l2_pricing
+            .speed_limit_per_second()
+            .set(snapshot.speed_limit_per_second)?;
+        l2_pricing
+            .per_block_gas_limit()
+            .set(snapshot.per_block_gas_limit)?;
+        l2_pricing
+            .per_tx_gas_limit()
+            .set(snapshot.per_tx_gas_limit)?;
+        l2_pricing.base_fee_wei().set(snapshot.base_fee_wei)?;
+        l2_pricing
+            .min_base_fee_wei()
+            .set(snapshot.min_base_fee_wei)?;
+        l2_pricing.gas_backlog().set(snapshot.gas_backlog)?;
+        l2_pricing.pricing_inertia().set(snapshot.pricing_inertia)?;
+        l2_pricing
+            .backlog_tolerance()
+            .set(snapshot.backlog_tolerance)
+    }
+}
@@ -0,0 +1,274 @@
+//! Initializing ArbOS state at genesis from a Nitro chain config.
+//!
+//! A Nitro chain config is the go-ethereum chain config JSON with an `arbitrum` section, e.g.
+//! `{"chainId": 412346, "arbitrum": {"InitialArbOSVersion": 32, "InitialChainOwner": "0x..",
+//! "GenesisBlockNum": 0}}`. [`ArbStateWrapper::initialize_genesis`] writes the state Nitro's
+//! `InitializeArbosState` writes for it. Arbitrum One, Nova and Sepolia have presets in
+//! [`ArbitrumChainParams`].
+
+use std::str::FromStr;
+
+use revm::primitives::{Address, U256, address, keccak256};
+use serde_json::Value;
+
+use crate::{
+    ArbitrumContextTr,
+    constants::{
+        ARBOS_BATCH_POSTER_ADDRESS, INITIAL_BACKLOG_TOLERANCE, INITIAL_BASE_FEE_WEI,
+        INITIAL_L1_EQUILIBRATION_UNITS_V0, INITIAL_L1_PER_UNIT_REWARD, INITIAL_L1_PRICING_INERTIA,
+        INITIAL_MINIMUM_BASE_FEE_WEI, INITIAL_PER_BLOCK_GAS_LIMIT_V0, INITIAL_PRICING_INERTIA,
+        INITIAL_SPEED_LIMIT_PER_SECOND_V0,
+    },
+    state::{
+        ArbStateGetter, ArbStateWrapper, ArbosStateParams,
+        types::{ArbosStateError, StorageBackedTr},
+    },
+};
+
+/// The parts of a Nitro chain config that ArbOS state is initialized from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArbitrumChainParams {
+    pub chain_id: u64,
+    pub initial_arbos_version: u64,
+    /// First chain owner, which also collects the network fee and L1 pricing rewards from ArbOS
+    /// 2. May be the zero address.
+    pub initial_chain_owner: Address,
+    pub genesis_block_num: u64,
+}
+
+impl ArbitrumChainParams {
+    /// Arbitrum One, which started at ArbOS 6 from the classic chain's last block.
+    pub fn arbitrum_one() -> Self {
+        Self {
+            chain_id: 42161,
+            initial_arbos_version: 6,
+            initial_chain_owner: address!("0xd345e41ae2cb00311956aa7109fc801ae8c81a52"),
+            genesis_block_num: 22207817,
+        }
+    }
+
+    /// Arbitrum Nova, which started at ArbOS 1.
+    pub fn arbitrum_nova() -> Self {
+        Self {
+            chain_id: 42170,
+            initial_arbos_version: 1,
+            initial_chain_owner: address!("0x9c040726f2a657226ed95712245dee84b650a1b5"),
+            genesis_block_num: 0,
+        }
+    }
+
+    /// Arbitrum Sepolia, which started at ArbOS 10.
+    pub fn arbitrum_sepolia() -> Self {
+        Self {
+            chain_id: 421614,
+            initial_arbos_version: 10,
+            initial_chain_owner: address!("0x71b61c2e250afa05dfc36304d6c91501be0965d8"),
+            genesis_block_num: 0,
+        }
+    }
+
+    /// Parse the chain ID and `arbitrum` section of the serialized chain config `chain_config`.
+    pub fn from_chain_config(chain_config: &str) -> Result<Self, ArbosStateError> {
+        let config: Value = serde_json::from_str(chain_config)
+            .map_err(|err| invalid_chain_config(err.to_string()))?;
+        let arbitrum = config
+            .get("arbitrum")
+            .ok_or_else(|| invalid_chain_config("missing arbitrum section".into()))?;
+        let initial_chain_owner = match arbitrum.get("InitialChainOwner") {
+            Some(owner) => owner
+                .as_str()
+                .and_then(|owner| Address::from_str(owner).ok())
+                .ok_or_else(|| invalid_chain_config("invalid InitialChainOwner".into()))?,
+            None => Address::ZERO,
+        };
+
+        Ok(Self {
+            chain_id: u64_field(&config, "chainId")?
+                .ok_or_else(|| invalid_chain_config("missing chainId".into()))?,
+            initial_arbos_version: u64_field(arbitrum, "InitialArbOSVersion")?
+                .ok_or_else(|| invalid_chain_config("missing InitialArbOSVersion".into()))?,
+            initial_chain_owner,
+            genesis_block_num: u64_field(arbitrum, "GenesisBlockNum")?.unwrap_or_default(),
+        })
+    }
+}
+
+fn invalid_chain_config(reason: String) -> ArbosStateError {
+    ArbosStateError::Context(format!("invalid chain config: {reason}"))
+}
+
+/// The unsigned integer `field` of `object`, if present.
+fn u64_field(object: &Value, field: &str) -> Result<Option<u64>, ArbosStateError> {
+    object
+        .get(field)
+        .map(|value| {
+            value
+                .as_u64()
+                .ok_or_else(|| invalid_chain_config(format!("invalid {field}")))
+        })
+        .transpose()
+}
+
+impl From<&ArbitrumChainParams> for ArbosStateParams {
+    fn from(chain: &ArbitrumChainParams) -> Self {
+        Self {
+            network_fee_account: if chain.initial_arbos_version >= 2 {
+                chain.initial_chain_owner
+            } else {
+                Address::ZERO
+            },
+            chain_id: U256::from(chain.chain_id),
+            genesis_block_num: chain.genesis_block_num,
+            // Nitro compresses at level 0 until an owner raises it
+            brotli_compression_level: 0,
+            ..Default::default()
+        }
+    }
+}
+
+impl ArbosStateParams {
+    /// Parameters Nitro's `InitializeArbosState` sets from the serialized chain config
+    /// `chain_config`.
+    pub fn from_chain_config(chain_config: &str) -> Result<Self, ArbosStateError> {
+        ArbitrumChainParams::from_chain_config(chain_config).map(|chain| Self::from(&chain))
+    }
+}
+
+impl<CTX: ArbitrumContextTr> ArbStateWrapper<'_, CTX> {
+    /// Initialize ArbOS state for the chain with the serialized config `chain_config` and the L1
+    /// basefee of its init message, as Nitro's `InitializeArbosState` does: the chain owner, the
+    /// initial L1 and L2 pricing parameters and the chain ID are set at ArbOS 1, and the state is
+    /// then upgraded to the initial ArbOS version.
+    pub fn initialize_genesis(
+        &mut self,
+        chain_config: &str,
+        initial_l1_base_fee: U256,
+    ) -> Result<(), ArbosStateError> {
+        let chain = ArbitrumChainParams::from_chain_config(chain_config)?;
+        self.initialize_chain(&chain, initial_l1_base_fee)?;
+        // Stored as ArbOwner's setChainConfig stores it
+        self.chain_config()
+            .set(U256::from_be_bytes(keccak256(chain_config).0))
+    }
+
+    /// Initialize ArbOS state for `chain` as [`Self::initialize_genesis`] does, e.g. for one of
+    /// the presets of [`ArbitrumChainParams`], leaving the chain config hash unset.
+    pub fn initialize_chain(
+        &mut self,
+        chain: &ArbitrumChainParams,
+        initial_l1_base_fee: U256,
+    ) -> Result<(), ArbosStateError> {
+        if self.arbos_version().get()? != 0 {
+            return Err(ArbosStateError::Context(
+                "ArbOS state is already initialized".into(),
+            ));
+        }
+        let owner = chain.initial_chain_owner;
+
+        self.arbos_version().set(1)?;
+        self.initialize(&ArbosStateParams::from(chain))?;
+
+        let mut l1_pricing = self.l1_pricing();
+        l1_pricing
+            .batch_poster_table()
+            .add(ARBOS_BATCH_POSTER_ADDRESS, ARBOS_BATCH_POSTER_ADDRESS)?;
+        l1_pricing
+            .reward_recipient()
+            .set(if chain.initial_arbos_version >= 2 {
+                owner
+            } else {
+                ARBOS_BATCH_POSTER_ADDRESS
+            })?;
+        l1_pricing
+            .equilibration_units()
+            .set(U256::from(INITIAL_L1_EQUILIBRATION_UNITS_V0))?;
+        l1_pricing.inertia().set(INITIAL_L1_PRICING_INERTIA)?;
+        l1_pricing
+            .per_unit_reward()
+            .set(INITIAL_L1_PER_UNIT_REWARD)?;
+        l1_pricing.price_per_unit().set(initial_l1_base_fee)?;
+
+        let mut l2_pricing = self.l2_pricing();
+        l2_pricing
+            .speed_limit_per_second()
+            .set(INITIAL_SPEED_LIMIT_PER_SECOND_V0)?;
+        l2_pricing
+            .per_block_gas_limit()
+            .set(INITIAL_PER_BLOCK_GAS_LIMIT_V0)?;
+        l2_pricing
+            .base_fee_wei()
+            .set(U256::from(INITIAL_BASE_FEE_WEI))?;
+        l2_pricing.gas_backlog().set(0)?;
+        l2_pricing.pricing_inertia().set(INITIAL_PRICING_INERTIA)?;
+        l2_pricing
+            .backlog_tolerance()
+            .set(INITIAL_BACKLOG_TOLERANCE)?;
+        l2_pricing
+            .min_base_fee_wei()
+            .set(U256::from(INITIAL_MINIMUM_BASE_FEE_WEI))?;
+
+        self.chain_owners().add(owner)?;
+
+        self.upgrade_arbos_version(chain.initial_arbos_version, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_params_are_parsed() {
+        let config = r#"{
+            "chainId": 412346,
+            "homesteadBlock": 0,
+            "arbitrum": {
+                "EnableArbOS": true,
+                "InitialArbOSVersion": 32,
+                "InitialChainOwner": "0x3f1eae7d46d88f08fc2f8ed27fcb2ab183eb2d0e",
+                "GenesisBlockNum": 0
+            }
+        }"#;
+        let chain = ArbitrumChainParams::from_chain_config(config).unwrap();
+        assert_eq!(chain.chain_id, 412346);
+        assert_eq!(chain.initial_arbos_version, 32);
+        assert_eq!(
+            chain.initial_chain_owner,
+            Address::from_str("0x3f1eae7d46d88f08fc2f8ed27fcb2ab183eb2d0e").unwrap()
+        );
+
+        let params = ArbosStateParams::from_chain_config(config).unwrap();
+        assert_eq!(params.chain_id, U256::from(412346));
+        assert_eq!(params.network_fee_account, chain.initial_chain_owner);
+    }
+
+    #[test]
+    fn known_chains_follow_their_initial_version() {
+        let one = ArbitrumChainParams::arbitrum_one();
+        assert_eq!(one.chain_id, 42161);
+        assert_eq!(
+            ArbosStateParams::from(&one).network_fee_account,
+            one.initial_chain_owner
+        );
+
+        // The network fee only went to the owner from ArbOS 2
+        let nova = ArbitrumChainParams::arbitrum_nova();
+        assert_eq!(nova.chain_id, 42170);
+        assert_eq!(
+            ArbosStateParams::from(&nova).network_fee_account,
+            Address::ZERO
+        );
+
+        assert_eq!(ArbitrumChainParams::arbitrum_sepolia().chain_id, 421614);
+    }
+
+    #[test]
+    fn missing_fields_are_rejected() {
+        assert!(ArbitrumChainParams::from_chain_config(r#"{"chainId": 1}"#).is_err());
+        assert!(
+            ArbitrumChainParams::from_chain_config(r#"{"arbitrum": {"InitialArbOSVersion": 6}}"#)
+                .is_err()
+        );
+        assert!(ArbitrumChainParams::from_chain_config("not json").is_err());
+    }
+}
@@ -1,7 +1,17 @@
-use revm::primitives::{Address, B256, U256};
+use std::{num::NonZeroUsize, sync::Mutex};
+
+use lru::LruCache;
+use revm::{
+    context::JournalTr,
+    primitives::{Address, B256, I256, U256, keccak256},
+};
+use stylus::brotli::{self, Dictionary};
 
 use crate::{
     ArbitrumContextTr,
+    arbos_version::{ArbosFeature, ArbosVersion},
+    constants::ARBOS_L1_PRICER_FUNDS_ADDRESS,
+    l1_fee::TX_DATA_NON_ZERO_GAS,
     state::types::{
         ArbosStateError, StorageBackedAddress, StorageBackedAddressSet, StorageBackedI256,
         StorageBackedTr, StorageBackedU64, StorageBackedU256, map_address, substorage,
@@ -23,6 +33,57 @@ const ARBOS_L1_PRICING_AMORTIZED_COST_CAP_BIPS_OFFSET: u64 = 10;
 const ARBOS_L1_PRICING_L1_FEES_AVAILABLE_OFFSET: u64 = 11;
 const ARBOS_L1_PRICING_GAS_FLOOR_PER_TOKEN_OFFSET: u64 = 12;
 
+const ONE_IN_BIPS: u64 = 10_000;
+
+/// Brotli window Nitro compresses transactions with (`arbcompress.WINDOW_SIZE`).
+const BROTLI_WINDOW_SIZE: u32 = 22;
+
+/// Highest brotli compression level.
+const MAX_BROTLI_COMPRESSION_LEVEL: u64 = 11;
+
+/// Calldata whose L1 data units are kept, by hash and compression level.
+const CALLDATA_UNITS_CACHE_CAPACITY: usize = 1024;
+
+lazy_static::lazy_static! {
+    static ref CALLDATA_UNITS_CACHE: Mutex<LruCache<(B256, u64), u64>> = Mutex::new(
+        LruCache::new(NonZeroUsize::new(CALLDATA_UNITS_CACHE_CAPACITY).unwrap())
+    );
+}
+
+/// L1 data units of posting `calldata`, as Nitro prices a transaction's bytes: 16 units per
+/// byte once compressed with brotli at `brotli_compression_level`, ArbOS's
+/// `brotli_compression_level`. Units are cached, so a transaction estimated and then executed
+/// is compressed once and priced the same both times.
+pub fn units_for_calldata(calldata: &[u8], brotli_compression_level: u64) -> u64 {
+    let key = (keccak256(calldata), brotli_compression_level);
+    if let Some(units) = CALLDATA_UNITS_CACHE.lock().unwrap().get(&key) {
+        return *units;
+    }
+
+    let level = brotli_compression_level.min(MAX_BROTLI_COMPRESSION_LEVEL) as u32;
+    let compressed_len = brotli::compress(calldata, level, BROTLI_WINDOW_SIZE, Dictionary::Empty)
+        .map_or(calldata.len(), |compressed| compressed.len());
+    let units = (compressed_len as u64).saturating_mul(TX_DATA_NON_ZERO_GAS);
+
+    CALLDATA_UNITS_CACHE.lock().unwrap().put(key, units);
+    units
+}
+
+/// Convert to a signed value, saturating at `I256::MAX`.
+fn signed(value: U256) -> I256 {
+    I256::try_from(value).unwrap_or(I256::MAX)
+}
+
+/// Division by a positive divisor rounding towards negative infinity, like Go's `big.Int.Div`.
+fn div_floor(dividend: I256, divisor: I256) -> I256 {
+    let quotient = dividend / divisor;
+    if dividend.is_negative() && quotient.saturating_mul(divisor) != dividend {
+        quotient - I256::ONE
+    } else {
+        quotient
+    }
+}
+
 pub struct L1Pricing<'a, CTX>
 where
     CTX: ArbitrumContextTr,
@@ -109,6 +170,157 @@ impl<'a, CTX: ArbitrumContextTr> L1Pricing<'a, CTX> {
         let slot = self.slot(ARBOS_L1_PRICING_GAS_FLOOR_PER_TOKEN_OFFSET);
         StorageBackedU64::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
     }
+
+    /// Pay `amount` out of the L1 pricer's funds pool, returning the fees left available.
+    fn transfer_from_l1_fees_available(
+        &mut self,
+        recipient: Address,
+        amount: U256,
+    ) -> Result<U256, ArbosStateError> {
+        let available = self.l1_fees_available().get()?;
+        let Some(updated) = available.checked_sub(amount) else {
+            return Err(ArbosStateError::Context(
+                "insufficient L1 fees available".to_string(),
+            ));
+        };
+        if let Some(error) = self
+            .context
+            .journal_mut()
+            .transfer(ARBOS_L1_PRICER_FUNDS_ADDRESS, recipient, amount)
+            .map_err(|e| ArbosStateError::Context(format!("{e:?}")))?
+        {
+            return Err(ArbosStateError::Context(format!(
+                "failed to pay from L1 pricer funds: {error:?}"
+            )));
+        }
+        self.l1_fees_available().set(updated)?;
+        Ok(updated)
+    }
+
+    /// Account for `wei_spent` by `batch_poster` posting a batch at `update_time`, mirroring
+    /// Nitro's `UpdateForBatchPosterSpending`. The units collected since the last update are
+    /// allocated to the batch pro rata by time, the poster and the reward recipient are paid
+    /// what the collected fees allow, and the price per unit moves towards equilibrium.
+    ///
+    /// Follows the accounting of ArbOS 10 and later, which tracks the fees available instead of
+    /// using the pricer's balance.
+    pub fn update_for_batch_poster_spending(
+        &mut self,
+        update_time: u64,
+        current_time: u64,
+        batch_poster: Address,
+        mut wei_spent: U256,
+        l1_base_fee: U256,
+        arbos_version: ArbosVersion,
+    ) -> Result<(), ArbosStateError> {
+        self.batch_poster_table()
+            .add_if_missing(batch_poster, batch_poster)?;
+
+        let mut last_update_time = self.last_update_time().get()?;
+        if last_update_time == 0 && update_time > 0 {
+            // This is the first update, so there is no previous update time.
+            last_update_time = update_time - 1;
+        }
+        if update_time > current_time || update_time < last_update_time {
+            return Err(ArbosStateError::InvalidTime);
+        }
+
+        // Allocate to this update the share of units collected up to `update_time`.
+        let (numerator, denominator) = match current_time - last_update_time {
+            0 => (1, 1),
+            elapsed => (update_time - last_update_time, elapsed),
+        };
+        let units_since_update = self.units_since_update().get()?;
+        let units_allocated = (u128::from(units_since_update) * u128::from(numerator)
+            / u128::from(denominator)) as u64;
+        self.units_since_update()
+            .set(units_since_update - units_allocated)?;
+
+        if arbos_version.supports(ArbosFeature::AmortizedCostCap) {
+            let cap_bips = self.amortized_cost_cap_bips().get()?;
+            if cap_bips != 0 {
+                // The poster takes the loss on anything spent beyond the cap.
+                let cap = l1_base_fee
+                    .saturating_mul(U256::from(units_allocated))
+                    .saturating_mul(U256::from(cap_bips))
+                    / U256::from(ONE_IN_BIPS);
+                wei_spent = wei_spent.min(cap);
+            }
+        }
+
+        let funds_due = self
+            .batch_poster_table()
+            .get(batch_poster)
+            .funds_due()
+            .get()?;
+        self.batch_poster_table()
+            .set_funds_due(batch_poster, funds_due.saturating_add(wei_spent))?;
+
+        let per_unit_reward = self.per_unit_reward().get()?;
+        let reward = U256::from(units_allocated).saturating_mul(U256::from(per_unit_reward));
+        let funds_due_for_rewards = self
+            .funds_due_for_rewards()
+            .get()?
+            .saturating_add(signed(reward));
+
+        // Pay rewards, as much as possible.
+        let mut available = self.l1_fees_available().get()?;
+        let reward_payment = reward.min(available);
+        self.funds_due_for_rewards()
+            .set(funds_due_for_rewards.saturating_sub(signed(reward_payment)))?;
+        let reward_recipient = self.reward_recipient().get()?;
+        available = self.transfer_from_l1_fees_available(reward_recipient, reward_payment)?;
+
+        // Settle what is owed to the batch poster, as much as possible.
+        let mut poster = self.batch_poster_table();
+        let due_to_poster = poster.get(batch_poster).funds_due().get()?;
+        let poster_payment = due_to_poster.min(available);
+        if !poster_payment.is_zero() {
+            let pay_to = poster.fee_collector(batch_poster)?;
+            available = self.transfer_from_l1_fees_available(pay_to, poster_payment)?;
+            self.batch_poster_table()
+                .set_funds_due(batch_poster, due_to_poster - poster_payment)?;
+        }
+
+        self.last_update_time().set(update_time)?;
+
+        if units_allocated > 0 {
+            let total_funds_due = self.batch_poster_table().total_funds_due().get()?;
+            let funds_due_for_rewards = self.funds_due_for_rewards().get()?;
+            let surplus = signed(available)
+                .saturating_sub(total_funds_due.saturating_add(funds_due_for_rewards));
+
+            let inertia = self.inertia().get()?;
+            let equilibration_units = signed(self.equilibration_units().get()?);
+            if inertia == 0 || equilibration_units.is_zero() {
+                return Err(ArbosStateError::Context(
+                    "L1 pricing inertia and equilibration units must be nonzero".to_string(),
+                ));
+            }
+            let inertia_units = div_floor(equilibration_units, signed(U256::from(inertia)));
+            let units = signed(U256::from(units_allocated));
+            let price = signed(self.price_per_unit().get()?);
+            let old_surplus = self.last_surplus().get()?;
+
+            let desired_derivative = div_floor(surplus.saturating_neg(), equilibration_units);
+            let actual_derivative = div_floor(surplus.saturating_sub(old_surplus), units);
+            let change_derivative_by = desired_derivative.saturating_sub(actual_derivative);
+            let price_change = div_floor(
+                change_derivative_by.saturating_mul(units),
+                inertia_units.saturating_add(units),
+            );
+
+            self.last_surplus().set(surplus)?;
+            let new_price = price.saturating_add(price_change);
+            let new_price = if new_price.is_negative() {
+                U256::ZERO
+            } else {
+                new_price.into_raw()
+            };
+            self.price_per_unit().set(new_price)?;
+        }
+        Ok(())
+    }
 }
 
 const ARBOS_BATCH_POSTER_ADDRS_KEY: &[u8] = &[0];
@@ -160,7 +372,21 @@ impl<'a, CTX: ArbitrumContextTr> BatchPosterTable<'a, CTX> {
     }
 
     pub fn contains(&mut self, batch_poster: Address) -> Result<bool, ArbosStateError> {
-        Ok(self.all()?.contains(&batch_poster))
+        self.posters_address_set().contains(batch_poster)
+    }
+
+    /// State of `batch_poster`, which must be in the table, as Nitro's `OpenPoster` without
+    /// creating it.
+    pub fn open(
+        &mut self,
+        batch_poster: Address,
+    ) -> Result<BatchPosterState<'_, CTX>, ArbosStateError> {
+        if !self.contains(batch_poster)? {
+            return Err(ArbosStateError::Context(
+                "batch poster does not exist".into(),
+            ));
+        }
+        Ok(self.get(batch_poster))
     }
 
     pub fn add_if_missing(
@@ -176,11 +402,18 @@ impl<'a, CTX: ArbitrumContextTr> BatchPosterTable<'a, CTX> {
         Ok(true)
     }
 
+    /// Add `batch_poster`, paying its fees to `pay_recipient`. Fails if it is already in the
+    /// table.
     pub fn add(
         &mut self,
         batch_poster: Address,
         pay_recipient: Address,
     ) -> Result<(), ArbosStateError> {
+        if self.contains(batch_poster)? {
+            return Err(ArbosStateError::Context(
+                "tried to add a batch poster that already exists".into(),
+            ));
+        }
         self.posters_address_set().add(batch_poster)?;
         self.get(batch_poster).pay_recipient().set(pay_recipient)
     }
@@ -190,16 +423,34 @@ impl<'a, CTX: ArbitrumContextTr> BatchPosterTable<'a, CTX> {
         StorageBackedI256::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
     }
 
+    /// Set what is owed to `batch_poster`, keeping the total owed to all posters in step.
+    pub fn set_funds_due(
+        &mut self,
+        batch_poster: Address,
+        value: U256,
+    ) -> Result<(), ArbosStateError> {
+        let previous = self.get(batch_poster).funds_due().get()?;
+        let total = self.total_funds_due().get()?;
+        self.total_funds_due().set(
+            total
+                .saturating_add(signed(value))
+                .saturating_sub(signed(previous)),
+        )?;
+        self.get(batch_poster).funds_due().set(value)
+    }
+
+    /// Address the fees of `batch_poster` are paid to. Fails if it is not a batch poster.
     pub fn fee_collector(&mut self, batch_poster: Address) -> Result<Address, ArbosStateError> {
-        self.get(batch_poster).pay_recipient().get()
+        self.open(batch_poster)?.pay_recipient().get()
     }
 
+    /// Pay the fees of `batch_poster` to `new_fee_collector`. Fails if it is not a batch poster.
     pub fn set_fee_collector(
         &mut self,
         batch_poster: Address,
         new_fee_collector: Address,
     ) -> Result<(), ArbosStateError> {
-        self.get(batch_poster)
+        self.open(batch_poster)?
             .pay_recipient()
             .set(new_fee_collector)
     }
@@ -2,9 +2,23 @@ use revm::primitives::{B256, U256};
 
 use crate::{
     ArbitrumContextTr,
-    state::types::{StorageBackedTr, StorageBackedU64, StorageBackedU256, map_address},
+    state::types::{
+        ArbosStateError, StorageBackedTr, StorageBackedU64, StorageBackedU256, map_address,
+    },
 };
 
+const ONE_IN_BIPS: u64 = 10_000;
+
+/// Approximate `e^(bips / 10000)` in basis points with a Horner-form Taylor series of `accuracy`
+/// terms, as Nitro's `ApproxExpBasisPoints` does for non-negative exponents.
+fn approx_exp_basis_points(bips: u64, accuracy: u64) -> u64 {
+    let mut result = ONE_IN_BIPS + bips / accuracy;
+    for i in 1..accuracy {
+        result = ONE_IN_BIPS + result.saturating_mul(bips) / ((accuracy - i) * ONE_IN_BIPS);
+    }
+    result.min(i64::MAX as u64)
+}
+
 pub struct L2Pricing<'a, CTX>
 where
     CTX: ArbitrumContextTr,
@@ -74,4 +88,38 @@ impl<'a, CTX: ArbitrumContextTr> L2Pricing<'a, CTX> {
         let slot = self.slot(7);
         StorageBackedU64::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
     }
+
+    /// Add the gas a transaction used to the backlog, as Nitro's `AddToGasPool` does with a
+    /// negative amount.
+    pub fn grow_backlog(&mut self, gas: u64) -> Result<(), ArbosStateError> {
+        let backlog = self.gas_backlog().get()?;
+        self.gas_backlog().set(backlog.saturating_add(gas))
+    }
+
+    /// Drain the gas backlog by the gas the speed limit allows over `time_passed` seconds and
+    /// reprice: the basefee grows exponentially with the backlog beyond the tolerance, and is
+    /// the minimum basefee otherwise.
+    pub fn update_pricing_model(&mut self, time_passed: u64) -> Result<(), ArbosStateError> {
+        let speed_limit = self.speed_limit_per_second().get()?;
+        let drained = time_passed.saturating_mul(speed_limit).min(i64::MAX as u64);
+        let backlog = self.gas_backlog().get()?.saturating_sub(drained);
+        self.gas_backlog().set(backlog)?;
+
+        let inertia = self.pricing_inertia().get()?;
+        let tolerance = self.backlog_tolerance().get()?;
+        let min_base_fee = self.min_base_fee_wei().get()?;
+        let allowed = tolerance.saturating_mul(speed_limit);
+        let base_fee = if backlog > allowed {
+            let excess = (backlog - allowed).min(i64::MAX as u64);
+            let exponent_bips = excess.saturating_mul(ONE_IN_BIPS).min(i64::MAX as u64)
+                / inertia
+                    .saturating_mul(speed_limit)
+                    .clamp(1, i64::MAX as u64);
+            min_base_fee.saturating_mul(U256::from(approx_exp_basis_points(exponent_bips, 4)))
+                / U256::from(ONE_IN_BIPS)
+        } else {
+            min_base_fee
+        };
+        self.base_fee_wei().set(base_fee)
+    }
 }
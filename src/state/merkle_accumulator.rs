@@ -0,0 +1,146 @@
+use crate::{
+    ArbitrumContextTr,
+    state::types::{
+        ArbosStateError, StorageBackedB256, StorageBackedTr, StorageBackedU64, map_address,
+    },
+};
+
+use revm::{
+    interpreter::Gas,
+    primitives::{B256, U256, keccak256},
+};
+
+/// Interior node created while appending a leaf, reported as a `SendMerkleUpdate` event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTreeNodeEvent {
+    pub level: u64,
+    pub num_leaves: u64,
+    pub hash: B256,
+}
+
+impl MerkleTreeNodeEvent {
+    /// Position of the node as encoded in events: `(level << 192) + leaf`.
+    pub fn position(&self) -> U256 {
+        (U256::from(self.level) << 192) + U256::from(self.num_leaves)
+    }
+}
+
+/// Number of partial subtree hashes kept for a tree of `size` leaves.
+pub fn num_partials(size: u64) -> u64 {
+    (u64::BITS - size.leading_zeros()) as u64
+}
+
+/// Append-only Merkle accumulator of L2-to-L1 sends. The size is stored at offset 0 and the
+/// partial subtree hash of each level at offset `2 + level`.
+pub struct MerkleAccumulator<'a, CTX>
+where
+    CTX: ArbitrumContextTr,
+{
+    context: &'a mut CTX,
+    gas: Option<&'a mut Gas>,
+    is_static: bool,
+    slot: B256,
+}
+
+impl<'a, CTX> MerkleAccumulator<'a, CTX>
+where
+    CTX: ArbitrumContextTr,
+{
+    pub fn new(
+        context: &'a mut CTX,
+        gas: Option<&'a mut Gas>,
+        is_static: bool,
+        slot: B256,
+    ) -> Self {
+        Self {
+            context,
+            gas,
+            is_static,
+            slot,
+        }
+    }
+
+    fn size_storage(&mut self) -> StorageBackedU64<'_, CTX> {
+        let slot = map_address(&self.slot, &B256::ZERO);
+        StorageBackedU64::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
+    }
+
+    fn partial(&mut self, level: u64) -> StorageBackedB256<'_, CTX> {
+        let slot = map_address(&self.slot, &B256::from(U256::from(2 + level)));
+        StorageBackedB256::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
+    }
+
+    pub fn size(&mut self) -> Result<u64, ArbosStateError> {
+        self.size_storage().get()
+    }
+
+    /// Append a leaf, returning the interior nodes completed by it. The item is hashed again
+    /// before insertion so that leaves cannot be confused with interior nodes.
+    pub fn append(&mut self, item_hash: B256) -> Result<Vec<MerkleTreeNodeEvent>, ArbosStateError> {
+        let size = self.size()? + 1;
+        self.size_storage().set(size)?;
+
+        let mut events = Vec::new();
+        let mut level = 0;
+        let mut so_far = keccak256(item_hash);
+        loop {
+            if level == num_partials(size - 1) {
+                self.partial(level).set(so_far)?;
+                return Ok(events);
+            }
+
+            let this_level = self.partial(level).get()?;
+            if this_level.is_zero() {
+                self.partial(level).set(so_far)?;
+                return Ok(events);
+            }
+
+            so_far = keccak256([this_level.as_slice(), so_far.as_slice()].concat());
+            self.partial(level).set(B256::ZERO)?;
+            level += 1;
+            events.push(MerkleTreeNodeEvent {
+                level,
+                num_leaves: size - 1,
+                hash: so_far,
+            });
+        }
+    }
+
+    /// Partial subtree hashes, lowest level first. Empty levels are zero.
+    pub fn partials(&mut self) -> Result<Vec<B256>, ArbosStateError> {
+        let size = self.size()?;
+        (0..num_partials(size))
+            .map(|level| self.partial(level).get())
+            .collect()
+    }
+
+    /// Root of the tree, padding with zero hashes up to the next power of two.
+    pub fn root(&mut self) -> Result<B256, ArbosStateError> {
+        let mut hash_so_far: Option<B256> = None;
+        let mut capacity_in_hash = 0u64;
+        let mut capacity = 1u64;
+
+        for partial in self.partials()? {
+            if !partial.is_zero() {
+                match hash_so_far {
+                    None => {
+                        hash_so_far = Some(partial);
+                        capacity_in_hash = capacity;
+                    }
+                    Some(mut hash) => {
+                        while capacity_in_hash < capacity {
+                            hash = keccak256([hash.as_slice(), B256::ZERO.as_slice()].concat());
+                            capacity_in_hash *= 2;
+                        }
+                        hash_so_far =
+                            Some(keccak256([partial.as_slice(), hash.as_slice()].concat()));
+                        capacity_in_hash = 2 * capacity;
+                    }
+                }
+            }
+            capacity = capacity.saturating_mul(2);
+        }
+
+        Ok(hash_so_far.unwrap_or_default())
+    }
+}
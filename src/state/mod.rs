@@ -1,12 +1,23 @@
 pub mod address_table;
 pub mod arbos_state;
 pub mod block_hashes;
+pub mod dump;
+pub mod genesis;
 pub mod l1_pricing;
 pub mod l2_pricing;
+pub mod merkle_accumulator;
+pub mod parameters;
+pub mod pricing;
 pub mod program;
 pub mod retryable;
 pub mod types;
-pub use arbos_state::{ArbState, ArbStateGetter, ArbStateWrapper, ArbosStateParams};
+pub use arbos_state::{
+    ArbState, ArbStateGetter, ArbStateSnapshot, ArbStateWrapper, ArbosStateParams,
+};
+pub use dump::ArbosStateDump;
+pub use genesis::ArbitrumChainParams;
+pub use parameters::ChainParameters;
+pub use pricing::{L1PricingSnapshot, L2PricingSnapshot, PricingReport, PricingSnapshot};
 pub use types::ArbosStateError;
 
 macro_rules! try_state {
@@ -21,7 +32,7 @@ macro_rules! try_state {
             Err(err) => {
                 return Some(crate::macros::interpreter_result_revert_with_output(
                     &mut $gas,
-                    err.into(),
+                    crate::precompiles::errors::state_error(err),
                 ));
             }
         }
@@ -0,0 +1,127 @@
+use alloy_sol_types::sol;
+
+use crate::{
+    ArbitrumContextTr,
+    state::{
+        ArbStateGetter, ArbStateWrapper,
+        types::{ArbosStateError, StorageBackedTr},
+    },
+};
+
+sol! {
+    /// Every chain parameter the chain owners govern through ArbOwner, as ArbOwner, ArbOwnerPublic
+    /// and ArbGasInfo report them one call at a time. ABI-encode it to snapshot the chain's
+    /// parameters in one go.
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct ChainParameters {
+        address[] chainOwners;
+        address[] nativeTokenOwners;
+        uint64 nativeTokenEnabledTime;
+        address networkFeeAccount;
+        address infraFeeAccount;
+        uint64 upgradeVersion;
+        uint64 upgradeTimestamp;
+        uint64 brotliCompressionLevel;
+
+        uint64 speedLimitPerSecond;
+        uint64 perBlockGasLimit;
+        uint64 perTxGasLimit;
+        uint256 l2BaseFeeWei;
+        uint256 minimumL2BaseFeeWei;
+        uint64 l2PricingInertia;
+        uint64 l2BacklogTolerance;
+        uint64 gasBacklog;
+
+        uint256 l1PricePerUnit;
+        uint64 l1PricingInertia;
+        uint256 l1EquilibrationUnits;
+        address l1RewardRecipient;
+        uint64 l1RewardRate;
+        uint64 perBatchGasCharge;
+        uint64 amortizedCostCapBips;
+        uint64 gasFloorPerToken;
+
+        uint32 inkPrice;
+        uint32 wasmMaxStackDepth;
+        uint16 wasmFreePages;
+        uint16 wasmPageGas;
+        uint16 wasmPageLimit;
+        uint32 wasmMaxSize;
+        uint8 wasmMinInitGas;
+        uint8 wasmMinCachedInitGas;
+        uint8 wasmInitCostScalar;
+        uint8 wasmCachedCostScalar;
+        uint16 wasmExpiryDays;
+        uint16 wasmKeepaliveDays;
+        uint16 wasmBlockCacheSize;
+    }
+}
+
+impl<CTX: ArbitrumContextTr> ArbStateWrapper<'_, CTX> {
+    pub fn chain_parameters(&mut self) -> Result<ChainParameters, ArbosStateError> {
+        let mut l2_pricing = self.l2_pricing();
+        let speed_limit_per_second = l2_pricing.speed_limit_per_second().get()?;
+        let per_block_gas_limit = l2_pricing.per_block_gas_limit().get()?;
+        let per_tx_gas_limit = l2_pricing.per_tx_gas_limit().get()?;
+        let l2_base_fee_wei = l2_pricing.base_fee_wei().get()?;
+        let minimum_l2_base_fee_wei = l2_pricing.min_base_fee_wei().get()?;
+        let l2_pricing_inertia = l2_pricing.pricing_inertia().get()?;
+        let l2_backlog_tolerance = l2_pricing.backlog_tolerance().get()?;
+        let gas_backlog = l2_pricing.gas_backlog().get()?;
+
+        let mut l1_pricing = self.l1_pricing();
+        let l1_price_per_unit = l1_pricing.price_per_unit().get()?;
+        let l1_pricing_inertia = l1_pricing.inertia().get()?;
+        let l1_equilibration_units = l1_pricing.equilibration_units().get()?;
+        let l1_reward_recipient = l1_pricing.reward_recipient().get()?;
+        let l1_reward_rate = l1_pricing.per_unit_reward().get()?;
+        let per_batch_gas_charge = l1_pricing.per_batch_gas_cost().get()?;
+        let amortized_cost_cap_bips = l1_pricing.amortized_cost_cap_bips().get()?;
+        let gas_floor_per_token = l1_pricing.gas_floor_per_token().get()?;
+
+        let stylus = self.programs().stylus_params().get()?;
+
+        Ok(ChainParameters {
+            chainOwners: self.chain_owners().all()?,
+            nativeTokenOwners: self.native_token_owners().all()?,
+            nativeTokenEnabledTime: self.native_token_enabled_time().get()?,
+            networkFeeAccount: self.network_fee_account().get()?,
+            infraFeeAccount: self.infra_fee_account().get()?,
+            upgradeVersion: self.upgrade_version().get()?,
+            upgradeTimestamp: self.upgrade_timestamp().get()?,
+            brotliCompressionLevel: self.brotli_compression_level().get()?,
+
+            speedLimitPerSecond: speed_limit_per_second,
+            perBlockGasLimit: per_block_gas_limit,
+            perTxGasLimit: per_tx_gas_limit,
+            l2BaseFeeWei: l2_base_fee_wei,
+            minimumL2BaseFeeWei: minimum_l2_base_fee_wei,
+            l2PricingInertia: l2_pricing_inertia,
+            l2BacklogTolerance: l2_backlog_tolerance,
+            gasBacklog: gas_backlog,
+
+            l1PricePerUnit: l1_price_per_unit,
+            l1PricingInertia: l1_pricing_inertia,
+            l1EquilibrationUnits: l1_equilibration_units,
+            l1RewardRecipient: l1_reward_recipient,
+            l1RewardRate: l1_reward_rate,
+            perBatchGasCharge: per_batch_gas_charge,
+            amortizedCostCapBips: amortized_cost_cap_bips,
+            gasFloorPerToken: gas_floor_per_token,
+
+            inkPrice: stylus.ink_price,
+            wasmMaxStackDepth: stylus.max_stack_depth,
+            wasmFreePages: stylus.free_pages,
+            wasmPageGas: stylus.page_gas,
+            wasmPageLimit: stylus.page_limit,
+            wasmMaxSize: stylus.max_wasm_size,
+            wasmMinInitGas: stylus.min_init_gas,
+            wasmMinCachedInitGas: stylus.min_cached_init_gas,
+            wasmInitCostScalar: stylus.init_cost_scalar,
+            wasmCachedCostScalar: stylus.cached_cost_scalar,
+            wasmExpiryDays: stylus.expiry_days,
+            wasmKeepaliveDays: stylus.keepalive_days,
+            wasmBlockCacheSize: stylus.block_cache_size,
+        })
+    }
+}
@@ -0,0 +1,196 @@
+use revm::primitives::{I256, U256};
+
+use crate::{
+    ArbitrumContextTr,
+    state::{
+        ArbStateGetter, ArbStateWrapper,
+        l1_pricing::L1Pricing,
+        l2_pricing::L2Pricing,
+        types::{ArbosStateError, StorageBackedTr},
+    },
+};
+
+/// Point-in-time view of the L2 gas pricing model.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct L2PricingSnapshot {
+    pub speed_limit_per_second: u64,
+    pub per_block_gas_limit: u64,
+    pub per_tx_gas_limit: u64,
+    pub base_fee_wei: U256,
+    pub min_base_fee_wei: U256,
+    pub gas_backlog: u64,
+    pub pricing_inertia: u64,
+    pub backlog_tolerance: u64,
+}
+
+/// Point-in-time view of the L1 pricing model.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct L1PricingSnapshot {
+    pub price_per_unit: U256,
+    pub units_since_update: u64,
+    pub last_update_time: u64,
+    pub last_surplus: I256,
+    pub inertia: u64,
+    pub equilibration_units: U256,
+    pub per_unit_reward: u64,
+    pub funds_due_for_rewards: I256,
+    pub l1_fees_available: U256,
+}
+
+/// Both pricing models, read together.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PricingSnapshot {
+    pub l1: L1PricingSnapshot,
+    pub l2: L2PricingSnapshot,
+}
+
+/// Pricing state before and after a block, explaining why fees moved.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PricingReport {
+    pub block_number: u64,
+    pub timestamp: u64,
+    /// Gas consumed by the block, the input that drives the backlog.
+    pub gas_used: u64,
+    pub before: PricingSnapshot,
+    pub after: PricingSnapshot,
+}
+
+impl PricingReport {
+    pub fn new(
+        block_number: u64,
+        timestamp: u64,
+        gas_used: u64,
+        before: PricingSnapshot,
+        after: PricingSnapshot,
+    ) -> Self {
+        Self {
+            block_number,
+            timestamp,
+            gas_used,
+            before,
+            after,
+        }
+    }
+
+    pub fn backlog_before(&self) -> u64 {
+        self.before.l2.gas_backlog
+    }
+
+    pub fn backlog_after(&self) -> u64 {
+        self.after.l2.gas_backlog
+    }
+
+    /// Signed change in the L2 gas backlog over the block.
+    pub fn backlog_delta(&self) -> i128 {
+        i128::from(self.backlog_after()) - i128::from(self.backlog_before())
+    }
+
+    pub fn base_fee_before(&self) -> U256 {
+        self.before.l2.base_fee_wei
+    }
+
+    pub fn base_fee_after(&self) -> U256 {
+        self.after.l2.base_fee_wei
+    }
+
+    pub fn base_fee_changed(&self) -> bool {
+        self.base_fee_before() != self.base_fee_after()
+    }
+
+    /// Whether the base fee ended the block pinned to the configured minimum.
+    pub fn base_fee_at_floor(&self) -> bool {
+        self.after.l2.base_fee_wei <= self.after.l2.min_base_fee_wei
+    }
+
+    /// L1 units accumulated since the previous price update, net of any update in this block.
+    pub fn l1_units_delta(&self) -> i128 {
+        i128::from(self.after.l1.units_since_update) - i128::from(self.before.l1.units_since_update)
+    }
+
+    pub fn l1_price_updated(&self) -> bool {
+        self.before.l1.price_per_unit != self.after.l1.price_per_unit
+            || self.before.l1.last_update_time != self.after.l1.last_update_time
+    }
+}
+
+impl<CTX: ArbitrumContextTr> L2Pricing<'_, CTX> {
+    pub fn snapshot(&mut self) -> Result<L2PricingSnapshot, ArbosStateError> {
+        Ok(L2PricingSnapshot {
+            speed_limit_per_second: self.speed_limit_per_second().get()?,
+            per_block_gas_limit: self.per_block_gas_limit().get()?,
+            per_tx_gas_limit: self.per_tx_gas_limit().get()?,
+            base_fee_wei: self.base_fee_wei().get()?,
+            min_base_fee_wei: self.min_base_fee_wei().get()?,
+            gas_backlog: self.gas_backlog().get()?,
+            pricing_inertia: self.pricing_inertia().get()?,
+            backlog_tolerance: self.backlog_tolerance().get()?,
+        })
+    }
+}
+
+impl<CTX: ArbitrumContextTr> L1Pricing<'_, CTX> {
+    pub fn snapshot(&mut self) -> Result<L1PricingSnapshot, ArbosStateError> {
+        Ok(L1PricingSnapshot {
+            price_per_unit: self.price_per_unit().get()?,
+            units_since_update: self.units_since_update().get()?,
+            last_update_time: self.last_update_time().get()?,
+            last_surplus: self.last_surplus().get()?,
+            inertia: self.inertia().get()?,
+            equilibration_units: self.equilibration_units().get()?,
+            per_unit_reward: self.per_unit_reward().get()?,
+            funds_due_for_rewards: self.funds_due_for_rewards().get()?,
+            l1_fees_available: self.l1_fees_available().get()?,
+        })
+    }
+}
+
+impl<CTX: ArbitrumContextTr> ArbStateWrapper<'_, CTX> {
+    pub fn pricing_snapshot(&mut self) -> Result<PricingSnapshot, ArbosStateError> {
+        Ok(PricingSnapshot {
+            l1: self.l1_pricing().snapshot()?,
+            l2: self.l2_pricing().snapshot()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(before: PricingSnapshot, after: PricingSnapshot) -> PricingReport {
+        PricingReport::new(1, 1, 0, before, after)
+    }
+
+    #[test]
+    fn report_tracks_backlog_and_base_fee() {
+        let mut before = PricingSnapshot::default();
+        before.l2.gas_backlog = 10;
+        before.l2.base_fee_wei = U256::from(100);
+        before.l2.min_base_fee_wei = U256::from(100);
+
+        let mut after = before.clone();
+        after.l2.gas_backlog = 4;
+        after.l2.base_fee_wei = U256::from(120);
+
+        let report = report(before, after);
+        assert_eq!(report.backlog_delta(), -6);
+        assert!(report.base_fee_changed());
+        assert!(!report.base_fee_at_floor());
+    }
+
+    #[test]
+    fn report_detects_l1_price_update() {
+        let before = PricingSnapshot::default();
+        let mut after = before.clone();
+        after.l1.units_since_update = 7;
+
+        let unchanged = report(before.clone(), after.clone());
+        assert_eq!(unchanged.l1_units_delta(), 7);
+        assert!(!unchanged.l1_price_updated());
+
+        after.l1.last_update_time = 42;
+        assert!(report(before, after).l1_price_updated());
+    }
+}
@@ -22,15 +22,17 @@ use crate::{
         INITIAL_RECENT_CACHE_SIZE, INITIAL_STYLUS_VERSION,
     },
     local_context::ArbitrumLocalContextTr,
+    program_cache::ProgramCacheTag,
     state::types::{
         ArbosStateError, StorageBackedAddressSet, StorageBackedB256, StorageBackedTr,
         StorageBackedU32, StorageBackedU64, map_address, substorage,
     },
-    stylus_executor::{cache_program, stylus_activate, stylus_compile},
+    stylus_executor::{cached_native_code, stylus_activate},
 };
 
 // stylus params type
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StylusParams {
     pub version: u16,
     pub ink_price: u32,
@@ -167,6 +169,7 @@ const DATA_PRICER_MIN_PRICE_OFFSET: u8 = 3;
 const DATA_PRICER_INERTIA_OFFSET: u8 = 4;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataPricerParams {
     pub demand: u32,
     pub bytes_per_second: u32,
@@ -187,6 +190,24 @@ impl Default for DataPricerParams {
     }
 }
 
+impl DataPricerParams {
+    /// Adds `temp_bytes` of demand at `time`, after crediting the bytes allowed since the last
+    /// update, and returns the fee charged for them.
+    pub fn update(&mut self, temp_bytes: u32, time: u64) -> u64 {
+        let credit = self
+            .bytes_per_second
+            .saturating_mul(time.saturating_sub(self.last_update_time) as u32);
+        self.demand = self.demand.saturating_sub(credit);
+        self.demand = self.demand.saturating_add(temp_bytes);
+        self.last_update_time = time;
+
+        let exponent = (self.demand as f64) / (self.inertia as f64);
+        let multiplier = f64::exp(exponent);
+        let cost_per_byte = (self.min_price as f64 * multiplier).floor() as u64;
+        cost_per_byte.saturating_mul(temp_bytes as u64)
+    }
+}
+
 pub struct DataPricer<'a, CTX>
 where
     CTX: ArbitrumContextTr,
@@ -256,27 +277,20 @@ where
     }
 
     pub fn update(&mut self, temp_bytes: u32, time: u64) -> Result<u64, ArbosStateError> {
-        let bytes_per_second = self.bytes_per_second().get()?;
-
-        let mut demand = self.demand().get()?;
-
-        let last_update_time = self.last_update_time().get()?;
-
-        let min_price = self.min_price().get()?;
-
-        let inertia = self.inertia().get()?;
+        let mut params = DataPricerParams {
+            bytes_per_second: self.bytes_per_second().get()?,
+            demand: self.demand().get()?,
+            last_update_time: self.last_update_time().get()?,
+            min_price: self.min_price().get()?,
+            inertia: self.inertia().get()?,
+        };
 
-        let credit = bytes_per_second.saturating_mul(time.saturating_sub(last_update_time) as u32);
-        demand = demand.saturating_sub(credit);
-        demand = demand.saturating_add(temp_bytes);
+        let fee = params.update(temp_bytes, time);
 
-        self.demand().set(demand)?;
-        self.last_update_time().set(time)?;
+        self.demand().set(params.demand)?;
+        self.last_update_time().set(params.last_update_time)?;
 
-        let exponent = (demand as f64) / (inertia as f64);
-        let multiplier = f64::exp(exponent);
-        let cost_per_byte = (min_price as f64 * multiplier).floor() as u64;
-        Ok(cost_per_byte.saturating_mul(temp_bytes as u64))
+        Ok(fee)
     }
 
     pub fn set(&mut self, params: &DataPricerParams) -> Result<(), ArbosStateError> {
@@ -300,7 +314,8 @@ where
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProgramInfo {
     pub version: u16,
     pub init_cost: u16,
@@ -401,6 +416,38 @@ where
         &mut self,
         code_hash: &B256,
         info: &ProgramInfo,
+    ) -> Result<(), ArbosStateError> {
+        let activated_at = self
+            .context
+            .timestamp()
+            .to::<u32>()
+            .saturating_sub(ARBOS_GENESIS_TIMESTAMP)
+            / 3600;
+        self.store_program_info(code_hash, info, activated_at)
+    }
+
+    /// Save `info` as read by [`Self::program_info`], activated `info.age` seconds ago.
+    pub fn restore_program_info(
+        &mut self,
+        code_hash: &B256,
+        info: &ProgramInfo,
+    ) -> Result<(), ArbosStateError> {
+        let activated_at = self
+            .context
+            .timestamp()
+            .to::<u32>()
+            .saturating_sub(info.age)
+            .saturating_sub(ARBOS_GENESIS_TIMESTAMP)
+            / 3600;
+        self.store_program_info(code_hash, info, activated_at)
+    }
+
+    /// Save `info`, activated `activated_at` hours after ArbOS genesis.
+    fn store_program_info(
+        &mut self,
+        code_hash: &B256,
+        info: &ProgramInfo,
+        activated_at: u32,
     ) -> Result<(), ArbosStateError> {
         let slot = map_address(&self.program_data_subkey(), code_hash);
         let mut data = [0u8; 32];
@@ -409,12 +456,6 @@ where
         data[4..6].copy_from_slice(&info.cached_cost.to_be_bytes());
         data[6..8].copy_from_slice(&info.footprint.to_be_bytes());
         data[8..11].copy_from_slice(&info.asm_estimated_kb.to_be_bytes()[1..4]);
-        let activated_at = self
-            .context
-            .timestamp()
-            .to::<u32>()
-            .saturating_sub(ARBOS_GENESIS_TIMESTAMP)
-            / 3600;
         data[11..14].copy_from_slice(&activated_at.to_be_bytes()[1..4]);
         data[14] = if info.cached { 1 } else { 0 };
 
@@ -499,10 +540,16 @@ where
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ActivationInfo {
     pub version: u16,
     pub data_fee: u64,
     pub module_hash: B256,
+    pub init_cost: u16,
+    pub cached_init_cost: u16,
+    pub footprint: u16,
+    /// Estimated size of the compiled program, in bytes.
+    pub asm_estimate: u32,
 }
 
 use super::{ArbState, ArbStateGetter};
@@ -531,18 +578,30 @@ pub fn activate_program<CTX: ArbitrumContextTr>(
 
     let open_pages = context.local().stylus_pages_open();
 
-    let serialized = stylus_compile(wasm_bytecode, &compile_config)?;
-
     let (module, stylus_data) = stylus_activate(
         None,
         wasm_bytecode,
         code_hash,
-        context.cfg().arbos_version(),
+        context.arbos_version().as_u16(),
         params.version,
         params.page_limit.saturating_sub(open_pages),
         debug,
     )?;
 
+    let tag = ProgramCacheTag {
+        stylus_version: compile_config.version,
+        arbos_version: context.arbos_version().as_u16(),
+        debug,
+    };
+    let serialized = cached_native_code(
+        context.local().program_cache().module_cache().as_deref(),
+        context.cfg().wasm_store_dir(),
+        &module,
+        wasm_bytecode,
+        code_hash,
+        tag,
+    )?;
+
     let module_hash = B256::from_slice(module.hash().as_slice());
     context
         .arb_state(None, false)
@@ -578,12 +637,19 @@ pub fn activate_program<CTX: ArbitrumContextTr>(
         .map_err(|e| format!("failed to save program info: {e:?}"))?;
 
     if cached {
-        cache_program(code_hash, serialized, module, stylus_data);
+        context
+            .local()
+            .program_cache()
+            .insert(code_hash, tag, (serialized, module, stylus_data));
     }
 
     Ok(ActivationInfo {
         version: compile_config.version,
         data_fee,
         module_hash,
+        init_cost: program_info.init_cost,
+        cached_init_cost: program_info.cached_cost,
+        footprint: program_info.footprint,
+        asm_estimate: stylus_data.asm_estimate,
     })
 }
@@ -1,4 +1,7 @@
-use revm::primitives::{Address, B256, Bytes, U256};
+use revm::{
+    context::JournalTr,
+    primitives::{Address, B256, Bytes, U256, keccak256},
+};
 
 use crate::{
     ArbitrumContextTr,
@@ -6,11 +9,39 @@ use crate::{
         ArbosStateError, StorageBackedAddress, StorageBackedBytes, StorageBackedQueue,
         StorageBackedTr, StorageBackedU64, StorageBackedU256, map_address, substorage,
     },
+    transaction::ArbitrumRetryTx,
 };
 
 const ARBOS_STATE_RETRYABLE_TIMEOUT_QUEUE_KEY: &[u8] = &[0];
 const ARBOS_STATE_RETRYABLE_CALLDATA_KEY: &[u8] = &[1];
 
+/// Lifetime of a retryable ticket, and the time added by each keepalive.
+pub const RETRYABLE_LIFETIME_SECONDS: u64 = 7 * 24 * 60 * 60; // 1 week
+/// Gas charged up front for reaping a ticket from the timeout queue.
+pub const RETRYABLE_REAP_PRICE: u64 = 58_000;
+
+/// Address holding a ticket's callvalue until it is redeemed or deleted.
+pub fn retryable_escrow_address(ticket_id: B256) -> Address {
+    let mut hasher_input = Vec::with_capacity(32 + "retryable escrow".len());
+    hasher_input.extend_from_slice(b"retryable escrow");
+    hasher_input.extend_from_slice(ticket_id.as_ref());
+
+    let hash = keccak256(&hasher_input);
+    Address::from_slice(&hash[12..32])
+}
+
+/// Fee for storing a ticket with `calldata_length` bytes of calldata.
+pub fn retryable_submission_fee(calldata_length: usize, l1_base_fee: U256) -> U256 {
+    l1_base_fee.saturating_mul(U256::from(1400 + 6 * calldata_length as u64))
+}
+
+/// Storage used by a ticket with `calldata_length` bytes of calldata.
+pub fn retryable_size_bytes(calldata_length: u64) -> u64 {
+    let calldata_words = calldata_length.div_ceil(32);
+    // 6 storage words for the fixed fields plus a length word and the calldata contents.
+    6 * 32 + 32 + 32 * calldata_words
+}
+
 pub struct RetryableState<'a, CTX>
 where
     CTX: ArbitrumContextTr,
@@ -36,15 +67,166 @@ impl<'a, CTX: ArbitrumContextTr> RetryableState<'a, CTX> {
         }
     }
 
+    pub fn initialize(&mut self) -> Result<(), ArbosStateError> {
+        self.timeout_queue().initialize()
+    }
+
     pub fn timeout_queue(&mut self) -> StorageBackedQueue<'_, CTX> {
         let slot = substorage(&self.slot, ARBOS_STATE_RETRYABLE_TIMEOUT_QUEUE_KEY);
         StorageBackedQueue::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
     }
 
+    /// Storage of ticket `id`, whether or not it exists or has expired.
     pub fn retryable(&mut self, id: B256) -> Retryable<'_, CTX> {
         let slot = substorage(&self.slot, id.as_slice());
         Retryable::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
     }
+
+    /// Store a new ticket and enqueue it for expiry at `timeout`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_retryable(
+        &mut self,
+        id: B256,
+        timeout: u64,
+        from: Address,
+        to: Address,
+        callvalue: U256,
+        beneficiary: Address,
+        calldata: &[u8],
+    ) -> Result<(), ArbosStateError> {
+        self.timeout_queue().push(U256::from_be_bytes(id.0))?;
+
+        let mut retryable = self.retryable(id);
+        retryable.num_tries().set(0)?;
+        retryable.from().set(from)?;
+        retryable.to().set(to)?;
+        retryable.callvalue().set(callvalue)?;
+        retryable.beneficiary().set(beneficiary)?;
+        retryable.calldata().set(calldata)?;
+        retryable.timeout().set(timeout)?;
+        retryable.timeout_windows_left().set(0)
+    }
+
+    /// Ticket `id` if it exists and has not expired at `current_time`.
+    pub fn open_retryable(
+        &mut self,
+        id: B256,
+        current_time: u64,
+    ) -> Result<Option<Retryable<'_, CTX>>, ArbosStateError> {
+        let mut retryable = self.retryable(id);
+        let timeout = retryable.timeout().get()?;
+        if timeout == 0 || timeout < current_time {
+            return Ok(None);
+        }
+        Ok(Some(retryable))
+    }
+
+    /// Storage used by ticket `id`, or zero if it does not exist.
+    pub fn retryable_size_bytes(
+        &mut self,
+        id: B256,
+        current_time: u64,
+    ) -> Result<u64, ArbosStateError> {
+        let Some(mut retryable) = self.open_retryable(id, current_time)? else {
+            return Ok(0);
+        };
+        Ok(retryable_size_bytes(retryable.calldata().size()?))
+    }
+
+    /// Delete ticket `id`, paying anything left in its escrow to the beneficiary. Returns
+    /// whether the ticket existed.
+    pub fn delete_retryable(&mut self, id: B256) -> Result<bool, ArbosStateError> {
+        let mut retryable = self.retryable(id);
+        if retryable.timeout().get()? == 0 {
+            return Ok(false);
+        }
+        let beneficiary = retryable.beneficiary().get()?;
+
+        let escrow = retryable_escrow_address(id);
+        let journal = self.context.journal_mut();
+        let balance = journal
+            .load_account(escrow)
+            .map_err(|e| ArbosStateError::Context(format!("{e:?}")))?
+            .data
+            .info
+            .balance;
+        if !balance.is_zero()
+            && let Some(error) = journal
+                .transfer(escrow, beneficiary, balance)
+                .map_err(|e| ArbosStateError::Context(format!("{e:?}")))?
+        {
+            return Err(ArbosStateError::Context(format!(
+                "failed to refund retryable escrow: {error:?}"
+            )));
+        }
+
+        self.retryable(id).clear()?;
+        Ok(true)
+    }
+
+    /// Extend ticket `id` by one lifetime, unless its timeout is already past `limit`. Returns
+    /// the new timeout.
+    pub fn keepalive(
+        &mut self,
+        id: B256,
+        current_time: u64,
+        limit: u64,
+    ) -> Result<u64, ArbosStateError> {
+        let Some(mut retryable) = self.open_retryable(id, current_time)? else {
+            return Err(ArbosStateError::RetryableNotFound);
+        };
+        let timeout = retryable.calculate_timeout()?;
+        if timeout > limit {
+            return Err(ArbosStateError::RetryableTimeoutTooFar);
+        }
+        let windows_left = retryable.timeout_windows_left().get()?;
+        retryable.timeout_windows_left().set(windows_left + 1)?;
+
+        // Add a duplicate entry to the queue; only the last one deletes the ticket.
+        self.timeout_queue().push(U256::from_be_bytes(id.0))?;
+
+        // Pay in advance for reaping the duplicate entry.
+        if let Some(gas) = self.gas.as_deref_mut()
+            && !gas.record_cost(RETRYABLE_REAP_PRICE)
+        {
+            return Err(ArbosStateError::OutOfGas);
+        }
+
+        Ok(timeout + RETRYABLE_LIFETIME_SECONDS)
+    }
+
+    /// Process the entry at the head of the timeout queue if it has expired, either consuming
+    /// one of the ticket's extra lifetimes or deleting it.
+    pub fn try_to_reap_one_retryable(&mut self, current_time: u64) -> Result<(), ArbosStateError> {
+        let Some(id) = self.timeout_queue().peek()? else {
+            return Ok(());
+        };
+        let id = B256::from(id);
+
+        let mut retryable = self.retryable(id);
+        let timeout = retryable.timeout().get()?;
+        if timeout == 0 {
+            // Already deleted; drop the stale entry.
+            self.timeout_queue().pop()?;
+            return Ok(());
+        }
+        let windows_left = retryable.timeout_windows_left().get()?;
+        if timeout >= current_time {
+            return Ok(());
+        }
+
+        self.timeout_queue().pop()?;
+        if windows_left == 0 {
+            self.delete_retryable(id)?;
+            return Ok(());
+        }
+
+        let mut retryable = self.retryable(id);
+        retryable
+            .timeout()
+            .set(timeout + RETRYABLE_LIFETIME_SECONDS)?;
+        retryable.timeout_windows_left().set(windows_left - 1)
+    }
 }
 
 pub struct Retryable<'a, CTX>
@@ -117,13 +299,56 @@ impl<'a, CTX: ArbitrumContextTr> Retryable<'a, CTX> {
         StorageBackedU64::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
     }
 
+    /// Timeout including the lifetimes added by keepalives.
+    pub fn calculate_timeout(&mut self) -> Result<u64, ArbosStateError> {
+        let timeout = self.timeout().get()?;
+        let windows_left = self.timeout_windows_left().get()?;
+        Ok(timeout.saturating_add(windows_left.saturating_mul(RETRYABLE_LIFETIME_SECONDS)))
+    }
+
+    /// Count a redeem attempt, returning the number of attempts including this one.
+    pub fn increment_num_tries(&mut self) -> Result<u64, ArbosStateError> {
+        let num_tries = self.num_tries().get()? + 1;
+        self.num_tries().set(num_tries)?;
+        Ok(num_tries)
+    }
+
+    /// Build the retry transaction for a redeem attempt with sequence number `nonce`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn make_tx(
+        &mut self,
+        chain_id: u64,
+        nonce: u64,
+        gas_fee_cap: U256,
+        gas: u64,
+        ticket_id: B256,
+        refund_to: Address,
+        max_refund: U256,
+        submission_fee_refund: U256,
+    ) -> Result<ArbitrumRetryTx, ArbosStateError> {
+        Ok(ArbitrumRetryTx {
+            chain_id,
+            nonce,
+            from: self.from().get()?,
+            gas_fee_cap,
+            gas,
+            to: self.to().get()?,
+            value: self.callvalue().get()?,
+            data: Bytes::from(self.calldata().get()?),
+            ticket_id,
+            refund_to,
+            max_refund,
+            submission_fee_refund,
+        })
+    }
+
     pub fn clear(&mut self) -> Result<(), ArbosStateError> {
         self.num_tries().set(0)?;
         self.timeout().set(0)?;
         self.callvalue().set(U256::ZERO)?;
         self.to().set(Address::ZERO)?;
         self.from().set(Address::ZERO)?;
-        self.calldata().set(&Bytes::new())?;
+        self.calldata().clear()?;
         self.beneficiary().set(Address::ZERO)?;
         self.timeout_windows_left().set(0)
     }
@@ -5,7 +5,9 @@ use revm::{
     context::JournalTr,
     interpreter::{
         Gas, InstructionResult, InterpreterAction, InterpreterResult,
-        gas::{ISTANBUL_SLOAD_GAS, SSTORE_RESET, SSTORE_SET},
+        gas::{
+            COLD_SLOAD_COST, ISTANBUL_SLOAD_GAS, SSTORE_RESET, SSTORE_SET, WARM_STORAGE_READ_COST,
+        },
     },
     primitives::{
         Address, B256, Bytes, FixedBytes, I256, U256,
@@ -15,7 +17,7 @@ use revm::{
     },
 };
 
-use crate::{ArbitrumContextTr, constants::ARBOS_STATE_ADDRESS};
+use crate::{ArbitrumContextTr, config::ArbitrumConfigTr};
 use tracing::trace;
 
 sol! {
@@ -34,6 +36,10 @@ pub enum ArbosStateError {
     ProgramExpired(u32),
     RectifyMappingNotOwner,
     RectifyMappingNoChange,
+    RetryableNotFound,
+    RetryableTimeoutTooFar,
+    InvalidTime,
+    UnrecognizedArbosVersion(u64),
     Context(String),
 }
 
@@ -68,6 +74,12 @@ impl Display for ArbosStateError {
             Self::RectifyMappingNoChange => {
                 write!(f, "RectifyMapping: Owner address is correctly mapped")
             }
+            Self::RetryableNotFound => write!(f, "ticketId not found"),
+            Self::RetryableTimeoutTooFar => write!(f, "timeout too far into the future"),
+            Self::InvalidTime => write!(f, "invalid time"),
+            Self::UnrecognizedArbosVersion(version) => {
+                write!(f, "unrecognized ArbOS version {version}")
+            }
             Self::Context(err) => {
                 write!(f, "Context error: {err}")
             }
@@ -330,21 +342,38 @@ where
     }
 
     fn get(&mut self) -> Result<T, ArbosStateError> {
-        if let Some(gas) = &mut self.gas
+        let access_costs = self
+            .context
+            .cfg()
+            .arbos_storage_access_costs_from()
+            .is_some_and(|from| self.context.arbos_version() >= from);
+        if !access_costs
+            && let Some(gas) = &mut self.gas
             && !gas.record_cost(ISTANBUL_SLOAD_GAS)
         {
             return Err(ArbosStateError::OutOfGas);
         }
 
-        // Assuming your journal sload returns something with `.data: U256`
-        let word: U256 = self
+        // The journal's warm set tells cold slots apart, like for SLOAD
+        let state_address = self.context.cfg().arbos_state_address();
+        let load = self
             .context
             .journal_mut()
-            .sload(ARBOS_STATE_ADDRESS, self.slot.into())
-            .map(|s| s.data)
+            .sload(state_address, self.slot.into())
             .map_err(|err| ArbosStateError::Context(err.to_string()))?;
 
-        Ok(T::from_word(word))
+        if access_costs && let Some(gas) = &mut self.gas {
+            let cost = if load.is_cold {
+                COLD_SLOAD_COST
+            } else {
+                WARM_STORAGE_READ_COST
+            };
+            if !gas.record_cost(cost) {
+                return Err(ArbosStateError::OutOfGas);
+            }
+        }
+
+        Ok(T::from_word(load.data))
     }
 
     fn set(&mut self, value: T) -> Result<(), ArbosStateError> {
@@ -364,16 +393,15 @@ where
             }
         }
 
+        let state_address = self.context.cfg().arbos_state_address();
         self.context
-            .sstore(ARBOS_STATE_ADDRESS, self.slot.into(), value)
+            .sstore(state_address, self.slot.into(), value)
             .unwrap();
 
-        // Mark ARBOS_STATE_ADDRESS as touched so CacheDB::commit persists its storage changes.
+        // Mark the ArbOS state account as touched so CacheDB::commit persists its storage changes.
         // Without this, sstore modifies the journal but the account remains untouched,
         // causing CacheDB::commit to skip the account and discard all storage writes.
-        self.context
-            .journal_mut()
-            .touch_account(ARBOS_STATE_ADDRESS);
+        self.context.journal_mut().touch_account(state_address);
 
         Ok(())
     }
@@ -565,6 +593,24 @@ where
         Ok(())
     }
 
+    /// Empty the list of members but keep the by-address index, as Nitro's `ClearList` does.
+    pub fn clear_list(&mut self) -> Result<(), ArbosStateError> {
+        let size = self.size()?;
+        for i in 1..=size {
+            let slot = map_address(&self.slot, &B256::from(U256::from(i as u64)));
+            StorageBackedAddress::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
+                .set(Address::ZERO)?;
+        }
+        let size_slot = self.size_slot();
+        StorageBackedU256::new(
+            self.context,
+            self.gas.as_deref_mut(),
+            self.is_static,
+            size_slot,
+        )
+        .set(U256::ZERO)
+    }
+
     pub fn rectify(&mut self, address: Address) -> Result<(), ArbosStateError> {
         if self.contains(address)? {
             return Err(ArbosStateError::RectifyMappingNotOwner);
@@ -644,7 +690,7 @@ where
         let mut out = Vec::with_capacity(size as usize);
         let mut offset = 0;
         while offset < size {
-            let chunk_slot = map_address(&self.slot, &B256::from(U256::from(offset + 1)));
+            let chunk_slot = map_address(&self.slot, &B256::from(U256::from(offset / 32 + 1)));
             let chunk = StorageBackedB256::new(
                 self.context,
                 self.gas.as_deref_mut(),
@@ -673,7 +719,7 @@ where
 
         let mut offset = 0;
         while offset < value.len() {
-            let chunk_slot = map_address(&self.slot, &B256::from(U256::from(offset + 1)));
+            let chunk_slot = map_address(&self.slot, &B256::from(U256::from(offset / 32 + 1)));
 
             let to_copy = std::cmp::min(value.len() - offset, 32);
             let mut chunk_bytes = [0u8; 32];
@@ -690,6 +736,29 @@ where
         }
         Ok(())
     }
+
+    /// Length in bytes, read without loading the contents.
+    pub fn size(&mut self) -> Result<u64, ArbosStateError> {
+        let size_slot = map_address(&self.slot, &B256::from(U256::from(0u64)));
+        StorageBackedU64::new(
+            self.context,
+            self.gas.as_deref_mut(),
+            self.is_static,
+            size_slot,
+        )
+        .get()
+    }
+
+    /// Zero the length and every word of the contents.
+    pub fn clear(&mut self) -> Result<(), ArbosStateError> {
+        let words = self.size()?.div_ceil(32);
+        for index in 0..=words {
+            let slot = map_address(&self.slot, &B256::from(U256::from(index)));
+            StorageBackedB256::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
+                .set(B256::ZERO)?;
+        }
+        Ok(())
+    }
 }
 
 pub struct StorageBackedQueue<'a, CTX>
@@ -720,140 +789,70 @@ where
         }
     }
 
-    fn head_slot(&self) -> B256 {
-        map_address(&self.slot, &B256::from(U256::from(0u64)))
+    /// Offset of the next element to be pushed.
+    fn tail(&mut self) -> StorageBackedU64<'_, CTX> {
+        let slot = map_address(&self.slot, &B256::from(U256::from(0u64)));
+        StorageBackedU64::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
     }
 
-    fn tail_slot(&self) -> B256 {
-        map_address(&self.slot, &B256::from(U256::from(1u64)))
+    /// Offset of the next element to be popped.
+    fn head(&mut self) -> StorageBackedU64<'_, CTX> {
+        let slot = map_address(&self.slot, &B256::from(U256::from(1u64)));
+        StorageBackedU64::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
     }
 
-    pub fn size(&mut self) -> Result<u64, ArbosStateError> {
-        let head_slot = self.head_slot();
-        let tail_slot = self.tail_slot();
+    fn element(&mut self, offset: u64) -> StorageBackedU256<'_, CTX> {
+        let slot = map_address(&self.slot, &B256::from(U256::from(offset)));
+        StorageBackedU256::new(self.context, self.gas.as_deref_mut(), self.is_static, slot)
+    }
 
-        let head = StorageBackedU64::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            head_slot,
-        )
-        .get()?;
-        let tail = StorageBackedU64::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            tail_slot,
-        )
-        .get()?;
-        Ok(tail.saturating_sub(head))
+    /// Place both offsets past the two words holding them, so elements start at offset 2.
+    pub fn initialize(&mut self) -> Result<(), ArbosStateError> {
+        self.tail().set(2)?;
+        self.head().set(2)
     }
 
-    pub fn peek(&mut self) -> Result<Option<U256>, ArbosStateError> {
-        let head_slot = self.head_slot();
-        let tail_slot = self.tail_slot();
+    pub fn size(&mut self) -> Result<u64, ArbosStateError> {
+        let tail = self.tail().get()?;
+        let head = self.head().get()?;
+        Ok(tail.saturating_sub(head))
+    }
 
-        let head = StorageBackedU64::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            head_slot,
-        )
-        .get()?;
-        let tail = StorageBackedU64::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            tail_slot,
-        )
-        .get()?;
+    /// Elements from head to tail, left in the queue.
+    pub fn all(&mut self) -> Result<Vec<U256>, ArbosStateError> {
+        let tail = self.tail().get()?;
+        let head = self.head().get()?;
+        (head..tail)
+            .map(|offset| self.element(offset).get())
+            .collect()
+    }
 
+    pub fn peek(&mut self) -> Result<Option<U256>, ArbosStateError> {
+        let tail = self.tail().get()?;
+        let head = self.head().get()?;
         if head >= tail {
             return Ok(None);
         }
-        let elem_slot = map_address(&self.slot, &B256::from(U256::from(head)));
-
-        let v = StorageBackedU256::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            elem_slot,
-        )
-        .get()?;
-        Ok(Some(v))
+        self.element(head).get().map(Some)
     }
 
+    /// Remove the element at the head, clearing its storage.
     pub fn pop(&mut self) -> Result<Option<U256>, ArbosStateError> {
-        let head_slot = self.head_slot();
-        let tail_slot = self.tail_slot();
-
-        let head = StorageBackedU64::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            head_slot,
-        )
-        .get()?;
-        let tail = StorageBackedU64::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            tail_slot,
-        )
-        .get()?;
-
+        let tail = self.tail().get()?;
+        let head = self.head().get()?;
         if head >= tail {
             return Ok(None);
         }
-        let elem_slot = map_address(&self.slot, &B256::from(U256::from(head)));
-        let v = StorageBackedU256::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            elem_slot,
-        )
-        .get()?;
+        self.head().set(head + 1)?;
 
-        // increment head
-        let new_head = head.saturating_add(1);
-        StorageBackedU64::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            head_slot,
-        )
-        .set(new_head)?;
-        Ok(Some(v))
+        let value = self.element(head).get()?;
+        self.element(head).set(U256::ZERO)?;
+        Ok(Some(value))
     }
 
     pub fn push(&mut self, value: U256) -> Result<(), ArbosStateError> {
-        let tail_slot = self.tail_slot();
-
-        let tail = StorageBackedU64::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            tail_slot,
-        )
-        .get()?;
-        let new_tail = tail.saturating_add(1);
-        StorageBackedU64::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            tail_slot,
-        )
-        .set(new_tail)?;
-
-        let elem_slot = map_address(&self.slot, &B256::from(U256::from(tail)));
-        StorageBackedU256::new(
-            self.context,
-            self.gas.as_deref_mut(),
-            self.is_static,
-            elem_slot,
-        )
-        .set(value)?;
-
-        Ok(())
+        let tail = self.tail().get()?;
+        self.tail().set(tail + 1)?;
+        self.element(tail).set(value)
     }
 }
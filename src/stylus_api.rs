@@ -11,13 +11,16 @@ use revm::{
         EvmTr, FrameResult, ItemOrResult, PrecompileProvider, instructions::InstructionProvider,
     },
     interpreter::{
-        CallInput, CallInputs, CreateInputs, FrameInput, Gas, InputsImpl, InstructionResult,
-        InterpreterAction, InterpreterResult,
-        gas::{initcode_cost, warm_cold_cost},
+        CallInput, CallInputs, CallScheme, CallValue, CreateInputs, FrameInput, Gas, InputsImpl,
+        InstructionResult, InterpreterAction, InterpreterResult, SStoreResult,
+        gas::{
+            CALLVALUE, NEWACCOUNT, WARM_STORAGE_READ_COST, initcode_cost, sload_cost, sstore_cost,
+            sstore_refund, warm_cold_cost,
+        },
         interpreter::EthInterpreter,
         interpreter_action::FrameInit,
     },
-    primitives::{Address, Log, hardfork::SpecId},
+    primitives::{Address, HashMap, Log, U256, hardfork::SpecId},
 };
 use tracing::{debug, trace, warn};
 
@@ -57,6 +60,52 @@ impl RequestHandler<VecReader> for StylusHandler {
     }
 }
 
+/// Per-frame view of a Stylus program's storage.
+///
+/// Reads are served from the cache once a slot has been loaded, and writes are held back until
+/// the frame finishes successfully or hands control to another contract, at which point they are
+/// flushed to the journal. Writes of failed frames are simply dropped.
+#[derive(Clone, Debug, Default)]
+pub struct StylusStorageCache {
+    address: Address,
+    slots: HashMap<U256, CachedSlot>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CachedSlot {
+    /// Value at the start of the transaction, known once the slot has been written.
+    original: Option<U256>,
+    /// Value currently held by the journal.
+    committed: U256,
+    /// Value as observed by the program.
+    value: U256,
+}
+
+impl StylusStorageCache {
+    pub fn new(address: Address) -> Self {
+        Self {
+            address,
+            slots: HashMap::default(),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn get(&self, key: &U256) -> Option<U256> {
+        self.slots.get(key).map(|slot| slot.value)
+    }
+
+    /// Slots whose cached value differs from the journal.
+    pub fn pending_writes(&self) -> impl Iterator<Item = (U256, U256)> + '_ {
+        self.slots
+            .iter()
+            .filter(|(_, slot)| slot.value != slot.committed)
+            .map(|(key, slot)| (*key, slot.value))
+    }
+}
+
 pub fn wasm_account_touch<CTX>(context: CTX, is_cold: bool, with_code: bool) -> u64
 where
     CTX: ArbitrumContextTr,
@@ -69,12 +118,151 @@ where
     code_cost + warm_cold_cost(is_cold)
 }
 
+/// Cost of a call hostio before any gas is forwarded: account access, value transfer, and the
+/// creation of a previously empty account receiving value, as for the CALL opcode.
+pub fn wasm_call_cost(is_cold: bool, is_empty: bool, transfers_value: bool) -> u64 {
+    let mut cost = warm_cold_cost(is_cold);
+    if transfers_value {
+        cost += CALLVALUE;
+        if is_empty {
+            cost += NEWACCOUNT;
+        }
+    }
+    cost
+}
+
+/// `gas * 63 / 64` without overflowing, the amount a Stylus program may forward to a sub-call.
+pub fn all_but_one_64th(gas: u64) -> u64 {
+    gas / 64 * 63 + gas % 64 * 63 / 64
+}
+
 impl<CTX, INSP, P, I> ArbitrumEvm<CTX, INSP, P, I>
 where
     CTX: ArbitrumContextTr,
     I: InstructionProvider<Context = CTX, InterpreterTypes = EthInterpreter>,
     P: PrecompileProvider<CTX, Output = InterpreterResult>,
 {
+    /// Load a storage slot through the current frame's storage cache, returning the value and
+    /// its gas cost.
+    fn storage_load(&mut self, address: Address, key: U256) -> Option<(U256, u64)> {
+        let spec = self.ctx().cfg().spec().into();
+
+        let cached = self
+            .ctx()
+            .local_mut()
+            .stylus_storage_cache()
+            .filter(|cache| cache.address == address)
+            .and_then(|cache| cache.get(&key));
+        if let Some(value) = cached {
+            return Some((value, WARM_STORAGE_READ_COST));
+        }
+
+        let loaded = self.ctx().sload(address, key)?;
+        if let Some(cache) = self
+            .ctx()
+            .local_mut()
+            .stylus_storage_cache()
+            .filter(|cache| cache.address == address)
+        {
+            cache.slots.insert(
+                key,
+                CachedSlot {
+                    original: None,
+                    committed: loaded.data,
+                    value: loaded.data,
+                },
+            );
+        }
+
+        Some((loaded.data, sload_cost(spec, loaded.is_cold)))
+    }
+
+    /// Store a storage slot through the current frame's storage cache, returning the gas cost
+    /// and refund the write would incur as an SSTORE.
+    fn storage_store(&mut self, address: Address, key: U256, value: U256) -> Option<(u64, i64)> {
+        let spec: SpecId = self.ctx().cfg().spec().into();
+
+        let has_cache = self
+            .ctx()
+            .local_mut()
+            .stylus_storage_cache()
+            .is_some_and(|cache| cache.address == address);
+        if !has_cache {
+            let result = self.ctx().sstore(address, key, value)?;
+            return Some((
+                sstore_cost(spec, &result.data, result.is_cold),
+                sstore_refund(spec, &result.data),
+            ));
+        }
+
+        let entry = self
+            .ctx()
+            .local_mut()
+            .stylus_storage_cache()
+            .and_then(|cache| cache.slots.get(&key).copied());
+
+        let (original, committed, present, is_cold) = match entry {
+            Some(CachedSlot {
+                original: Some(original),
+                committed,
+                value,
+            }) => (original, committed, value, false),
+            _ => {
+                // Writing the journal's current value back is a no-op that reveals the slot's
+                // original value and warms it.
+                let current = self.ctx().sload(address, key)?;
+                let probe = self.ctx().sstore(address, key, current.data)?;
+                let present = entry.map_or(current.data, |slot| slot.value);
+                let is_cold = entry.is_none() && current.is_cold;
+                (probe.data.original_value, current.data, present, is_cold)
+            }
+        };
+
+        let result = SStoreResult {
+            original_value: original,
+            present_value: present,
+            new_value: value,
+        };
+
+        if let Some(cache) = self.ctx().local_mut().stylus_storage_cache() {
+            cache.slots.insert(
+                key,
+                CachedSlot {
+                    original: Some(original),
+                    committed,
+                    value,
+                },
+            );
+        }
+
+        Some((
+            sstore_cost(spec, &result, is_cold),
+            sstore_refund(spec, &result),
+        ))
+    }
+
+    /// Write the current frame's pending storage writes to the journal and drop its cached reads,
+    /// so that other frames observe up-to-date storage.
+    pub(crate) fn flush_storage_cache(&mut self) {
+        let Some(cache) = self.ctx().local_mut().stylus_storage_cache() else {
+            return;
+        };
+        let address = cache.address;
+        let writes: Vec<_> = cache.pending_writes().collect();
+        cache.slots.clear();
+
+        for (key, value) in writes {
+            if self.ctx().sstore(address, key, value).is_none() {
+                warn!(
+                    target: "arbos-revm::stylus-api",
+                    target_address = %address,
+                    slot = %key,
+                    "Failed to flush cached Stylus storage write"
+                );
+            }
+        }
+    }
+
     /// Handle contract calls (ContractCall, DelegateCall, StaticCall)
     pub(crate) fn handle_contract_call(
         &mut self,
@@ -126,41 +314,104 @@ where
                 call_value = %value,
                 "Rejecting Stylus call with value in static context"
             );
+            // Nitro fails the call for write protection before it costs anything
             return (
-                Status::WriteProtection.into(),
+                CallStatus::Failure.into(),
+                VecReader::new(vec![]),
+                ArbGas(0),
+            );
+        }
+
+        self.flush_storage_cache();
+
+        let transfers_value = matches!(req_type, EvmApiMethod::ContractCall) && !value.is_zero();
+
+        let (is_cold, is_empty) = match self.ctx().journal_mut().load_account(bytecode_address) {
+            Ok(account) => (account.is_cold, account.data.is_empty()),
+            Err(error) => {
+                *self.ctx().error() = Err(ContextError::Db(error));
+                return (
+                    CallStatus::Failure.into(),
+                    VecReader::new(vec![]),
+                    ArbGas(gas_left),
+                );
+            }
+        };
+
+        let base_cost = wasm_call_cost(is_cold, is_empty, transfers_value);
+        if gas_left < base_cost {
+            debug!(
+                target: "arbos-revm::stylus-api",
+                target_address = %target_address,
+                bytecode_address = %bytecode_address,
+                gas_left,
+                base_cost,
+                "Insufficient gas for Stylus call"
+            );
+            return (
+                CallStatus::Failure.into(),
                 VecReader::new(vec![]),
                 ArbGas(gas_left),
             );
         }
 
+        // Nitro retains 1/64th of the gas left after the base cost, rounding the forwarded
+        // amount down (`gas * 63 / 64`) rather than rounding the retained amount down as the
+        // CALL opcode does.
+        let start_gas = gas_left - base_cost;
         let gas_limit = if self
             .ctx()
             .cfg()
             .spec()
             .into()
-            //.into_eth_spec()
             .is_enabled_in(SpecId::TANGERINE)
         {
-            min(gas_left - gas_left / 64, gas_limit)
+            min(all_but_one_64th(start_gas), gas_limit)
         } else {
-            gas_limit
+            min(start_gas, gas_limit)
         };
 
-        let mut gas = Gas::new(gas_limit);
-        if !gas.record_cost(warm_cold_cost(
-            self.ctx()
-                .journal_mut()
-                .load_account(bytecode_address)
-                .unwrap()
-                .is_cold,
-        )) {
-            return (
-                Status::OutOfGas.into(),
-                VecReader::new(vec![]),
-                ArbGas(gas.spent()),
-            );
+        if transfers_value {
+            let Some(balance) = self.ctx().balance(caller) else {
+                // The database failed, with the error recorded in the context
+                return (
+                    CallStatus::Failure.into(),
+                    VecReader::new(vec![]),
+                    ArbGas(gas_left),
+                );
+            };
+            let balance = balance.data;
+            if balance < value {
+                debug!(
+                    target: "arbos-revm::stylus-api",
+                    target_address = %target_address,
+                    caller = %caller,
+                    balance = %balance,
+                    call_value = %value,
+                    "Insufficient balance for Stylus call"
+                );
+                // The callee never runs, so only the base cost is consumed.
+                return (
+                    CallStatus::Failure.into(),
+                    VecReader::new(vec![]),
+                    ArbGas(base_cost),
+                );
+            }
         }
 
+        // Unlike the CALL opcode, Nitro adds no stipend to calls that pay: `doCall` hands the
+        // capped gas to `evm.Call` as is.
+        let mut gas = Gas::new(gas_limit);
+
+        let (scheme, call_value) = match req_type {
+            EvmApiMethod::DelegateCall => (
+                CallScheme::DelegateCall,
+                CallValue::Apparent(input.call_value),
+            ),
+            EvmApiMethod::StaticCall => (CallScheme::StaticCall, CallValue::Transfer(U256::ZERO)),
+            _ => (CallScheme::Call, CallValue::Transfer(value)),
+        };
+
         let first_frame_input = FrameInput::Call(Box::new(CallInputs {
             input: CallInput::Bytes(calldata),
             return_memory_offset: 0..0,
@@ -168,8 +419,8 @@ where
             bytecode_address,
             target_address,
             caller,
-            value: revm::interpreter::CallValue::Transfer(value),
-            scheme: revm::interpreter::CallScheme::Call,
+            value: call_value,
+            scheme,
             is_static,
             known_bytecode: None,
         }));
@@ -182,11 +433,10 @@ where
                 .get()
                 .process_next_action(&mut self.0.ctx, next_action);
 
-        let original_frame_stack = mem::replace(&mut self.0.frame_stack, FrameStack::new());
-
         gas.spend_all();
 
         if let Ok(ItemOrResult::Item(frame_init)) = frame_result {
+            let original_frame_stack = mem::replace(&mut self.0.frame_stack, FrameStack::new());
             let result = call_handler(self, frame_init);
 
             self.0.frame_stack = original_frame_stack;
@@ -197,18 +447,28 @@ where
                 .memory
                 .free_child_context();
 
-            if let Ok(FrameResult::Call(call_outcome)) = result {
+            if let Ok(FrameResult::Call(mut call_outcome)) = result {
                 gas.erase_cost(call_outcome.gas().remaining());
 
                 let instruction_result = *call_outcome.instruction_result();
+                // Frames rejected at the call depth limit come back here as `CallTooDeep` with
+                // their gas untouched, and are reported like any other failed call.
                 let status = if instruction_result.is_ok() {
-                    Status::Success
+                    self.0
+                        .frame_stack
+                        .get()
+                        .interpreter
+                        .gas
+                        .record_refund(call_outcome.gas().refunded());
+                    CallStatus::Success
                 } else {
-                    Status::Failure
+                    CallStatus::Failure
                 };
 
                 let status_label = status.as_str();
-                let output = call_outcome.output().to_vec();
+                // Hand the frame's return data to the guest without copying; the buffer is
+                // uniquely owned once the child frame has been popped.
+                let output = Vec::from(mem::take(&mut call_outcome.result.output));
 
                 debug!(
                     target: "arbos-revm::stylus-api",
@@ -217,13 +477,17 @@ where
                     ?instruction_result,
                     status = status_label,
                     output_len = output.len(),
-                    output = %String::from_utf8_or_hex(output.clone()),
-                    gas_spent = gas.spent(),
+                    output = %String::from_utf8_or_hex(output.as_slice()),
+                    gas_spent = gas.spent() + base_cost,
                     gas_remaining = call_outcome.gas().remaining(),
                     "Stylus host call finished"
                 );
 
-                return (status.into(), VecReader::new(output), ArbGas(gas.spent()));
+                return (
+                    status.into(),
+                    VecReader::new(output),
+                    ArbGas(gas.spent() + base_cost),
+                );
             }
         }
 
@@ -231,17 +495,18 @@ where
             target: "arbos-revm::stylus-api",
             target_address = %target_address,
             bytecode_address = %bytecode_address,
-            gas_spent = gas.spent(),
+            gas_spent = gas.spent() + base_cost,
             "Stylus host call returning failure response without call outcome"
         );
         (
-            Status::Failure.into(),
+            CallStatus::Failure.into(),
             VecReader::new(vec![]),
-            ArbGas(gas.spent()),
+            ArbGas(gas.spent() + base_cost),
         )
     }
 
-    /// Handle contract creation (Create1, Create2)
+    /// Handle contract creation (Create1, Create2), charged like the CREATE and CREATE2 opcodes:
+    /// the base cost, the EIP-3860 init code words and, for CREATE2, hashing the init code.
     pub(crate) fn handle_contract_creation(
         &mut self,
         input: InputsImpl,
@@ -296,6 +561,14 @@ where
             VecReader::new(vec![]),
             ArbGas(gas_remaining),
         );
+        // Failures that halt the CREATE opcode fail the hostio, taking all the gas left
+        let halt = |message: &str| {
+            (
+                [vec![0x00], message.as_bytes().to_vec()].concat(),
+                VecReader::new(vec![]),
+                ArbGas(gas_remaining),
+            )
+        };
 
         if is_create_2 && !spec.is_enabled_in(SpecId::PETERSBURG) {
             debug!(
@@ -319,7 +592,7 @@ where
                     max_initcode_size,
                     "Init code too large for Stylus create"
                 );
-                return error_response;
+                return halt("max initcode size exceeded");
             }
             gas_cost = initcode_cost(len);
         }
@@ -330,7 +603,7 @@ where
             {
                 gas_cost = check_cost;
             } else {
-                return error_response;
+                return halt("out of gas");
             };
             CreateScheme::Create2 {
                 salt: salt.unwrap(),
@@ -348,34 +621,29 @@ where
                 gas_remaining,
                 "Insufficient gas for Stylus create"
             );
-            return (
-                [vec![0x00], "out of gas".as_bytes().to_vec()].concat(),
-                VecReader::new(vec![]),
-                ArbGas(0),
-            );
+            return halt("out of gas");
         }
 
         let gas_limit = gas_remaining - gas_cost;
 
+        // The program keeps 1/64th of the remaining gas, which is returned to it whatever the
+        // outcome of the child.
         let gas_stipend = if spec.is_enabled_in(SpecId::TANGERINE) {
             gas_limit / 64
         } else {
             0
         };
 
-        let mut gas = Gas::new(gas_limit);
-        _ = gas.record_cost(gas_stipend);
+        self.flush_storage_cache();
 
         let first_frame_input = FrameInput::Create(Box::new(CreateInputs {
             caller: input.target_address,
             scheme,
             value,
             init_code,
-            gas_limit: gas.remaining(),
+            gas_limit: gas_limit - gas_stipend,
         }));
 
-        gas.spend_all();
-
         let next_action = InterpreterAction::NewFrame(first_frame_input);
 
         let frame_result: Result<_, ContextError<<<CTX as ContextTr>::Db as Database>::Error>> =
@@ -384,65 +652,87 @@ where
                 .get()
                 .process_next_action(&mut self.0.ctx, next_action);
 
-        let original_frame_stack = mem::replace(&mut self.0.frame_stack, FrameStack::new());
-
-        if let Ok(ItemOrResult::Item(frame_init)) = frame_result {
-            let result = call_handler(self, frame_init);
+        let outcome = match frame_result {
+            Ok(ItemOrResult::Item(frame_init)) => {
+                let original_frame_stack = mem::replace(&mut self.0.frame_stack, FrameStack::new());
+                let result = call_handler(self, frame_init);
 
-            self.0.frame_stack = original_frame_stack;
-            self.0
-                .frame_stack()
-                .get()
-                .interpreter
-                .memory
-                .free_child_context();
+                self.0.frame_stack = original_frame_stack;
+                self.0
+                    .frame_stack()
+                    .get()
+                    .interpreter
+                    .memory
+                    .free_child_context();
 
-            if let Ok(FrameResult::Create(create_outcome)) = result {
-                if InstructionResult::Revert == *create_outcome.instruction_result() {
-                    let output = create_outcome.output().to_vec();
-                    debug!(
-                        target: "arbos-revm::stylus-api",
-                        target_address = %input.target_address,
-                        output_len = output.len(),
-                        output = %String::from_utf8_or_hex(output.clone()),
-                        gas_spent = gas.spent(),
-                        gas_remaining = create_outcome.gas().remaining(),
-                        "Stylus create reverted"
-                    );
-                    return (
-                        [vec![0x00], output].concat(),
-                        VecReader::new(vec![]),
-                        ArbGas(gas.spent()),
-                    );
+                match result {
+                    Ok(FrameResult::Create(create_outcome)) => Some(create_outcome),
+                    _ => None,
                 }
+            }
+            Ok(ItemOrResult::Result(FrameResult::Create(create_outcome))) => Some(create_outcome),
+            _ => None,
+        };
 
-                if let Some(address) = create_outcome.address {
-                    gas.erase_cost(create_outcome.gas().remaining() + gas_stipend);
+        let Some(mut create_outcome) = outcome else {
+            warn!(
+                target: "arbos-revm::stylus-api",
+                target_address = %input.target_address,
+                "Stylus create returning default failure response"
+            );
+            return error_response;
+        };
 
-                    debug!(
-                        target: "arbos-revm::stylus-api",
-                        target_address = %input.target_address,
-                        new_address = %address,
-                        gas_spent = gas.spent(),
-                        gas_remaining = create_outcome.gas().remaining(),
-                        "Stylus create succeeded"
-                    );
+        let instruction_result = *create_outcome.instruction_result();
 
-                    return (
-                        [vec![0x01], address.to_vec()].concat(),
-                        VecReader::new(vec![]),
-                        ArbGas(gas.spent()),
-                    );
-                }
-            }
+        // Mirror the CREATE opcode: unused gas comes back on success and on failures that
+        // happen before or are signalled by the init code itself (reverts, insufficient
+        // balance, depth), while exceptional halts consume everything given to the child.
+        let gas_returned = if instruction_result.is_ok() || instruction_result.is_revert() {
+            create_outcome.gas().remaining()
+        } else {
+            0
+        };
+        let cost = gas_remaining.saturating_sub(gas_returned + gas_stipend);
+
+        if instruction_result.is_ok() {
+            self.0
+                .frame_stack
+                .get()
+                .interpreter
+                .gas
+                .record_refund(create_outcome.gas().refunded());
         }
 
-        warn!(
+        let address = create_outcome
+            .address
+            .filter(|_| instruction_result.is_ok())
+            .unwrap_or(Address::ZERO);
+
+        // Only reverted init code leaves return data for the program to read.
+        let output = if instruction_result == InstructionResult::Revert {
+            Vec::from(mem::take(&mut create_outcome.result.output))
+        } else {
+            vec![]
+        };
+
+        debug!(
             target: "arbos-revm::stylus-api",
             target_address = %input.target_address,
-            "Stylus create returning default failure response"
+            new_address = %address,
+            ?instruction_result,
+            output_len = output.len(),
+            output = %String::from_utf8_or_hex(output.as_slice()),
+            gas_spent = cost,
+            gas_remaining = create_outcome.gas().remaining(),
+            "Stylus create finished"
         );
-        error_response
+
+        (
+            [vec![0x01], address.to_vec()].concat(),
+            VecReader::new(output),
+            ArbGas(cost),
+        )
     }
 
     /// Handle log emission with closure-based log handling
@@ -470,12 +760,22 @@ where
         (vec![], VecReader::new(vec![]), ArbGas(0))
     }
 
-    pub(crate) fn request(
+    /// Answer a request of a Stylus program with `handler`, unless its payload is malformed.
+    /// Requests are answered with and without an inspector through here, so no handler is given
+    /// a payload too short for the fields it reads.
+    pub(crate) fn dispatch_request(
         &mut self,
         input: InputsImpl,
         is_static: bool,
         req_type: EvmApiMethod,
         data: Vec<u8>,
+        handler: impl FnOnce(
+            &mut Self,
+            InputsImpl,
+            bool,
+            EvmApiMethod,
+            Vec<u8>,
+        ) -> (Vec<u8>, VecReader, ArbGas),
     ) -> (Vec<u8>, VecReader, ArbGas) {
         trace!(
             target: "arbos-revm::stylus-api",
@@ -487,6 +787,27 @@ where
             "Stylus host request dispatch"
         );
 
+        if let Some(response) = malformed_request(req_type, &data) {
+            debug!(
+                target: "arbos-revm::stylus-api",
+                ?req_type,
+                target_address = %input.target_address,
+                payload_len = data.len(),
+                "Rejecting malformed Stylus host request"
+            );
+            return response;
+        }
+
+        handler(self, input, is_static, req_type, data)
+    }
+
+    pub(crate) fn request(
+        &mut self,
+        input: InputsImpl,
+        is_static: bool,
+        req_type: EvmApiMethod,
+        data: Vec<u8>,
+    ) -> (Vec<u8>, VecReader, ArbGas) {
         match req_type {
             EvmApiMethod::ContractCall | EvmApiMethod::DelegateCall | EvmApiMethod::StaticCall => {
                 self.handle_contract_call(input, is_static, req_type, data, |evm, frame_init| {
@@ -531,18 +852,11 @@ where
         let context = self.ctx();
         let mut data = data;
 
-        let spec = context.cfg().spec();
-
         match req_type {
             EvmApiMethod::GetBytes32 => {
                 let slot = buffer::take_u256(&mut data);
-                if let Some(result) = context.sload(input.target_address, slot) {
-                    let gas = revm::interpreter::gas::sload_cost(spec.into(), result.is_cold);
-                    (
-                        result.to_be_bytes_vec(),
-                        VecReader::new(vec![]),
-                        ArbGas(gas),
-                    )
+                if let Some((value, gas)) = self.storage_load(input.target_address, slot) {
+                    (value.to_be_bytes_vec(), VecReader::new(vec![]), ArbGas(gas))
                 } else {
                     (vec![], VecReader::new(vec![]), ArbGas(0))
                 }
@@ -565,16 +879,14 @@ where
                 }
 
                 let mut total_cost = 0;
+                let mut total_refund = 0;
                 while !data.is_empty() {
                     let (key, value) = (buffer::take_u256(&mut data), buffer::take_u256(&mut data));
 
-                    match context.sstore(input.target_address, key, value) {
-                        Some(result) => {
-                            total_cost += revm::interpreter::gas::sstore_cost(
-                                spec.clone().into(),
-                                &result.data,
-                                result.is_cold,
-                            );
+                    match self.storage_store(input.target_address, key, value) {
+                        Some((cost, refund)) => {
+                            total_cost += cost;
+                            total_refund += refund;
 
                             if gas_left < total_cost {
                                 debug!(
@@ -606,6 +918,15 @@ where
                     }
                 }
 
+                // Refunds accrue on the Stylus frame and are handed back with its result, so the
+                // end-of-tx refund cap sees them exactly like EVM SSTORE refunds.
+                self.0
+                    .frame_stack
+                    .get()
+                    .interpreter
+                    .gas
+                    .record_refund(total_refund);
+
                 (
                     Status::Success.into(),
                     VecReader::new(vec![]),
@@ -613,6 +934,9 @@ where
                 )
             }
 
+            // Transient storage lives in the journal under the frame's storage address, so it is
+            // shared with EVM frames of the same account, rolled back with reverted frames and
+            // cleared when the transaction ends (EIP-1153).
             EvmApiMethod::GetTransientBytes32 => {
                 let slot = buffer::take_u256(&mut data);
                 let result = context.tload(input.target_address, slot);
@@ -634,7 +958,10 @@ where
             }
             EvmApiMethod::AccountBalance => {
                 let address = buffer::take_address(&mut data);
-                let balance = context.balance(address).unwrap();
+                // A database failure is recorded in the context, which fails the transaction
+                let Some(balance) = context.balance(address) else {
+                    return (vec![0; 32], VecReader::new(vec![]), ArbGas(0));
+                };
                 let gas = wasm_account_touch(context, balance.is_cold, false);
                 (
                     balance.to_be_bytes_vec(),
@@ -643,16 +970,35 @@ where
                 )
             }
 
+            // As in Nitro, the worst case is charged before the code is loaded, and none is
+            // loaded if the program cannot pay for it.
             EvmApiMethod::AccountCode => {
                 let address = buffer::take_address(&mut data);
-                let code = context.load_account_code(address).unwrap();
-                let gas = wasm_account_touch(context, code.is_cold, true);
+                let gas_left = buffer::take_u64(&mut data);
+                let is_cold = match context.journal_mut().load_account(address) {
+                    Ok(account) => account.is_cold,
+                    Err(error) => {
+                        *context.error() = Err(ContextError::Db(error));
+                        return (vec![], VecReader::new(vec![]), ArbGas(0));
+                    }
+                };
+                let gas = wasm_account_touch(&mut *context, is_cold, true);
+                if gas_left < gas {
+                    return (vec![], VecReader::new(vec![]), ArbGas(gas));
+                }
+                // A database failure is recorded in the context, which fails the transaction
+                let Some(code) = context.load_account_code(address) else {
+                    return (vec![], VecReader::new(vec![]), ArbGas(gas));
+                };
                 (vec![], VecReader::new(code.to_vec()), ArbGas(gas))
             }
 
             EvmApiMethod::AccountCodeHash => {
                 let address = buffer::take_address(&mut data);
-                let code_hash = context.load_account_code_hash(address).unwrap();
+                // A database failure is recorded in the context, which fails the transaction
+                let Some(code_hash) = context.load_account_code_hash(address) else {
+                    return (vec![0; 32], VecReader::new(vec![]), ArbGas(0));
+                };
                 let gas = wasm_account_touch(context, code_hash.is_cold, false);
                 (code_hash.to_vec(), VecReader::new(vec![]), ArbGas(gas))
             }
@@ -662,12 +1008,14 @@ where
                 let open = context.local().stylus_pages_open();
                 let ever = context.local().stylus_pages_ever();
 
-                let stylus_params = context
-                    .arb_state(None, true)
+                let Ok(stylus_params) = context
+                    .system_arb_state(true)
                     .programs()
                     .stylus_params()
                     .get()
-                    .unwrap();
+                else {
+                    return (Status::Failure.into(), VecReader::new(vec![]), ArbGas(0));
+                };
 
                 let free_pages = stylus_params.free_pages;
                 let page_gas = stylus_params.page_gas;
@@ -682,9 +1030,70 @@ where
                 //println!("CaptureHostIO: {:?}", String::from_utf8_lossy(&data));
                 (Status::Success.into(), VecReader::new(vec![]), ArbGas(0))
             }
-            _ => unimplemented!("EVM API method not implemented: {:?}", req_type),
+            _ => {
+                warn!(
+                    target: "arbos-revm::stylus-api",
+                    ?req_type,
+                    "Unsupported Stylus host request"
+                );
+                context.local_mut().set_hostio_unavailable();
+                (Status::Failure.into(), VecReader::new(vec![]), ArbGas(0))
+            }
+        }
+    }
+}
+
+/// Most topics a log can have.
+const MAX_LOG_TOPICS: usize = 4;
+
+/// The failure response for a `req_type` request whose payload is too short for its fields or
+/// whose lengths disagree, or `None` if it is well formed. The payload carries values chosen by
+/// the program, so it is checked before any field is read.
+fn malformed_request(req_type: EvmApiMethod, data: &[u8]) -> Option<(Vec<u8>, VecReader, ArbGas)> {
+    let well_formed = match req_type {
+        // address, value, gas left, gas limit, calldata
+        EvmApiMethod::ContractCall | EvmApiMethod::DelegateCall | EvmApiMethod::StaticCall => {
+            data.len() >= 20 + 32 + 8 + 8
         }
+        // gas, value, init code
+        EvmApiMethod::Create1 => data.len() >= 8 + 32,
+        // gas, value, salt, init code
+        EvmApiMethod::Create2 => data.len() >= 8 + 32 + 32,
+        // topic count, topics, data
+        EvmApiMethod::EmitLog => data
+            .get(..4)
+            .map(|count| u32::from_be_bytes(count.try_into().unwrap()) as usize)
+            .is_some_and(|count| count <= MAX_LOG_TOPICS && data.len() >= 4 + 32 * count),
+        // gas left, then key-value pairs
+        EvmApiMethod::SetTrieSlots => data.len() >= 8 && (data.len() - 8) % 64 == 0,
+        EvmApiMethod::GetBytes32 | EvmApiMethod::GetTransientBytes32 => data.len() >= 32,
+        EvmApiMethod::SetTransientBytes32 => data.len() >= 64,
+        EvmApiMethod::AccountBalance | EvmApiMethod::AccountCodeHash => data.len() >= 20,
+        EvmApiMethod::AccountCode => data.len() >= 20 + 8,
+        EvmApiMethod::AddPages => data.len() >= 2,
+        _ => true,
+    };
+    if well_formed {
+        return None;
     }
+
+    let response = match req_type {
+        EvmApiMethod::ContractCall | EvmApiMethod::DelegateCall | EvmApiMethod::StaticCall => {
+            CallStatus::Failure.into()
+        }
+        EvmApiMethod::Create1 | EvmApiMethod::Create2 => {
+            [vec![0x00], b"malformed request".to_vec()].concat()
+        }
+        EvmApiMethod::EmitLog => b"malformed log".to_vec(),
+        // Requests answered with a word have no failure status, so read as zero
+        EvmApiMethod::GetBytes32
+        | EvmApiMethod::GetTransientBytes32
+        | EvmApiMethod::AccountBalance
+        | EvmApiMethod::AccountCodeHash => vec![0; 32],
+        EvmApiMethod::AccountCode => vec![],
+        _ => Status::Failure.into(),
+    };
+    Some((response, VecReader::new(vec![]), ArbGas(0)))
 }
 
 enum Status {
@@ -694,24 +1103,146 @@ enum Status {
     WriteProtection,
 }
 
-impl Status {
+impl From<Status> for Vec<u8> {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::Success => vec![0],
+            Status::Failure => vec![1],
+            Status::OutOfGas => vec![2],
+            Status::WriteProtection => vec![3],
+        }
+    }
+}
+
+/// Outcome of a contract call request. Nitro reports every failed call with the same status
+/// byte, whether the callee reverted or halted, or the call was rejected before it ran (static
+/// value transfer, base cost, balance, depth); the cause only shows up in the logs.
+enum CallStatus {
+    Success,
+    Failure,
+}
+
+impl CallStatus {
     fn as_str(&self) -> &'static str {
         match self {
             Self::Success => "success",
             Self::Failure => "failure",
-            Self::OutOfGas => "out_of_gas",
-            Self::WriteProtection => "write_protection",
         }
     }
 }
 
-impl From<Status> for Vec<u8> {
-    fn from(status: Status) -> Self {
+impl From<CallStatus> for Vec<u8> {
+    fn from(status: CallStatus) -> Self {
         match status {
-            Status::Success => vec![0],
-            Status::Failure => vec![1],
-            Status::OutOfGas => vec![2],
-            Status::WriteProtection => vec![3],
+            CallStatus::Success => vec![0],
+            CallStatus::Failure => vec![2],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use revm::{
+        Journal, context::BlockEnv, database::EmptyDBTyped, handler::instructions::EthInstructions,
+        inspector::NoOpInspector, primitives::Bytes,
+    };
+
+    use super::*;
+    use crate::{
+        ArbitrumContext, ArbitrumTransaction, config::ArbitrumConfig,
+        local_context::ArbitrumLocalContext, precompiles::ArbitrumPrecompileProvider,
+    };
+
+    type TestContext = ArbitrumContext<EmptyDBTyped<Infallible>>;
+
+    #[test]
+    fn test_well_formed_requests_pass() {
+        let mut log = 2u32.to_be_bytes().to_vec();
+        log.extend_from_slice(&[0u8; 64 + 5]);
+        assert!(malformed_request(EvmApiMethod::EmitLog, &log).is_none());
+        assert!(malformed_request(EvmApiMethod::SetTrieSlots, &[0u8; 8 + 128]).is_none());
+        assert!(malformed_request(EvmApiMethod::ContractCall, &[0u8; 68]).is_none());
+        assert!(malformed_request(EvmApiMethod::AccountCode, &[0u8; 28]).is_none());
+    }
+
+    #[test]
+    fn test_short_requests_fail() {
+        let (status, _, gas) = malformed_request(EvmApiMethod::StaticCall, &[0u8; 67]).unwrap();
+        assert_eq!(status, Vec::from(CallStatus::Failure));
+        assert_eq!(gas.0, 0);
+
+        let (status, _, _) = malformed_request(EvmApiMethod::SetTrieSlots, &[0u8; 8 + 63]).unwrap();
+        assert_eq!(status, Vec::from(Status::Failure));
+
+        let (word, _, _) = malformed_request(EvmApiMethod::AccountBalance, &[0u8; 19]).unwrap();
+        assert_eq!(word, vec![0; 32]);
+
+        let (result, _, _) = malformed_request(EvmApiMethod::Create2, &[0u8; 40]).unwrap();
+        assert_eq!(result[0], 0x00);
+
+        // An account code request also carries the gas left
+        let (code, _, _) = malformed_request(EvmApiMethod::AccountCode, &[0u8; 20]).unwrap();
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn test_logs_with_bad_topics_fail() {
+        // More topics than a log can have
+        let mut log = 5u32.to_be_bytes().to_vec();
+        log.extend_from_slice(&[0u8; 5 * 32]);
+        assert!(malformed_request(EvmApiMethod::EmitLog, &log).is_some());
+
+        // Fewer topic bytes than the count claims
+        let mut log = u32::MAX.to_be_bytes().to_vec();
+        log.extend_from_slice(&[0u8; 32]);
+        assert!(malformed_request(EvmApiMethod::EmitLog, &log).is_some());
+    }
+
+    #[test]
+    fn test_malformed_requests_fail_under_an_inspector() {
+        let context = ArbitrumContext {
+            journaled_state: Journal::new(EmptyDBTyped::<Infallible>::default()),
+            block: BlockEnv::default(),
+            cfg: ArbitrumConfig::default(),
+            tx: ArbitrumTransaction::default(),
+            chain: (),
+            local: ArbitrumLocalContext::default(),
+            error: Ok(()),
+        };
+        let mut evm: ArbitrumEvm<TestContext, NoOpInspector, ArbitrumPrecompileProvider<_>> =
+            ArbitrumEvm::new_with_inspector(
+                context,
+                NoOpInspector {},
+                EthInstructions::default(),
+                ArbitrumPrecompileProvider::default(),
+            );
+        let input = InputsImpl {
+            target_address: Address::repeat_byte(0x0a),
+            bytecode_address: None,
+            caller_address: Address::repeat_byte(0x0b),
+            input: CallInput::Bytes(Bytes::new()),
+            call_value: U256::ZERO,
+        };
+
+        // Each would be read past its end by the handler of its kind
+        for (req_type, data) in [
+            (EvmApiMethod::ContractCall, vec![0u8; 10]),
+            (EvmApiMethod::Create2, vec![0u8; 8]),
+            (EvmApiMethod::EmitLog, vec![0u8; 2]),
+            (EvmApiMethod::GetBytes32, vec![0u8; 31]),
+        ] {
+            let (_, _, gas) = evm.dispatch_request(
+                input.clone(),
+                false,
+                req_type,
+                data,
+                |evm, input, is_static, req_type, data| {
+                    evm.inspect_request(input, is_static, req_type, data)
+                },
+            );
+            assert_eq!(gas.0, 0, "{req_type:?}");
         }
     }
 }
@@ -1,8 +1,10 @@
 use std::{
     cmp::max,
-    mem,
-    num::NonZeroUsize,
+    fs, mem,
+    path::{Path, PathBuf},
+    str::FromStr,
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use arbutil::{
@@ -15,18 +17,22 @@ use arbutil::{
     },
 };
 
-use lru::LruCache;
 use revm::{
-    Inspector,
-    context::{Block, Cfg, ContextSetters, ContextTr, JournalTr, LocalContextTr, Transaction},
-    handler::{EvmTr, PrecompileProvider, instructions::InstructionProvider},
-    inspector::{InspectorEvmTr, JournalExt},
+    Database, Inspector,
+    context::{
+        Block, Cfg, ContextError, ContextSetters, ContextTr, JournalTr, LocalContextTr, Transaction,
+    },
+    handler::{
+        EvmTr, PrecompileProvider,
+        instructions::{EthInstructions, InstructionProvider},
+    },
+    inspector::{InspectorEvmTr, JournalExt, NoOpInspector},
     interpreter::{
-        CallInput, FrameInput, Gas, InputsImpl, InstructionResult, InterpreterAction,
-        InterpreterResult, gas::memory_gas, interpreter::EthInterpreter,
-        interpreter_types::InputsTr,
+        CallInput, CallInputs, CallScheme, CallValue, FrameInput, Gas, InputsImpl,
+        InstructionResult, InterpreterAction, InterpreterResult, SharedMemory, gas::memory_gas,
+        interpreter::EthInterpreter, interpreter_action::FrameInit, interpreter_types::InputsTr,
     },
-    primitives::{Address, B256, Bytes, FixedBytes, Log, U256, alloy_primitives::U64, keccak256},
+    primitives::{Address, B256, Bytes, Log, U256, alloy_primitives::U64, hex, keccak256},
 };
 use stylus::{
     brotli::{self, Dictionary},
@@ -42,44 +48,68 @@ use stylus::{
     run::RunProgram,
 };
 
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use tracing::{debug, trace, warn};
+use wasmer_types::compilation::target::{CpuFeature, Target, Triple};
 
 use crate::{
     ArbitrumEvm, Utf8OrHex,
+    arbos_version::ArbosFeature,
     config::ArbitrumConfigTr,
     constants::{
-        ARBOS_VERSION_STYLUS_FIXES, COST_SCALAR_PERCENT, MEMORY_EXPONENTS, MIN_CACHED_GAS_UNITS,
-        MIN_INIT_GAS_UNITS, STYLUS_DISCRIMINANT,
+        COST_SCALAR_PERCENT, INITIAL_DATA_PRICER_LAST_UPDATE_TIME, MEMORY_EXPONENTS,
+        MIN_CACHED_GAS_UNITS, MIN_INIT_GAS_UNITS, STYLUS_DISCRIMINANT, WASM_PAGE_SIZE,
     },
     context::ArbitrumContextTr,
-    local_context::ArbitrumLocalContextTr,
-    state::{ArbState, ArbStateGetter, program::ProgramInfo, types::ArbosStateError},
-    stylus_api::StylusHandler,
+    handler::load_arbos_version,
+    inspector::{PrecompileInspector, StylusHostio, StylusInspector},
+    local_context::{ActivatedProgram, ArbitrumLocalContextTr},
+    precompiles::ArbitrumPrecompileProvider,
+    program_cache::{ProgramCacheEntry, ProgramCacheTag, StylusModuleCache},
+    result::ArbitrumHaltReason,
+    state::{
+        ArbState, ArbStateGetter,
+        program::{ActivationInfo, DataPricerParams, ProgramInfo},
+        types::ArbosStateError,
+    },
+    stylus_api::{StylusHandler, StylusStorageCache},
 };
 
-type ProgramCacheEntry = (Vec<u8>, Module, StylusData);
-
-lazy_static::lazy_static! {
-    pub static ref PROGRAM_CACHE: Mutex<LruCache<FixedBytes<32>, ProgramCacheEntry>> = Mutex::new(LruCache::new(NonZeroUsize::new(1024).unwrap()));
+/// How a Stylus program finished, beyond what its frame's [`InterpreterResult`] can express.
+///
+/// Set on the local context when a program returns, so that [`Inspector::call_end`] can tell a
+/// wasm trap apart from an ordinary revert.
+#[derive(Clone, Debug)]
+pub struct StylusOutcome {
+    pub kind: UserOutcomeKind,
+    pub ink_used: u64,
+    /// Error reported by the runtime when the program failed.
+    pub failure: Option<String>,
 }
 
 type EvmApiHandler<'a> =
     Arc<Box<dyn Fn(EvmApiMethod, Vec<u8>) -> (Vec<u8>, VecReader, arbutil::evm::api::Gas) + 'a>>;
 
-pub fn build_evm_data<CTX>(context: &CTX, input: InputsImpl) -> EvmData
+/// EVM data a Stylus program with `module_hash` is called with through `input`.
+pub fn build_evm_data<CTX>(context: &CTX, input: InputsImpl, module_hash: B256) -> EvmData
 where
     CTX: ArbitrumContextTr,
 {
     let config_env = context.cfg();
-    let arbos_env = context.cfg();
 
     let block_env = context.block();
     let tx_env = context.tx();
 
-    let base_fee = block_env.basefee();
+    // Like geth's NoBaseFee mode, calls priced at zero see a zero basefee when the basefee check
+    // is disabled, so that gas estimation matches the price the program observes.
+    let base_fee = if config_env.is_base_fee_check_disabled() && tx_env.gas_price() == 0 {
+        0
+    } else {
+        block_env.basefee()
+    };
 
     let evm_data: EvmData = EvmData {
-        arbos_version: arbos_env.arbos_version() as u64,
+        arbos_version: context.arbos_version().into(),
         block_basefee: Bytes32::from(U256::from(base_fee).to_be_bytes()),
         chainid: config_env.chain_id(),
         block_coinbase: Bytes20::try_from(block_env.beneficiary().as_slice()).unwrap(),
@@ -87,15 +117,14 @@ where
         block_number: U64::wrapping_from(block_env.number()).to::<u64>(),
         block_timestamp: U64::wrapping_from(block_env.timestamp()).to::<u64>(),
         contract_address: Bytes20::try_from(input.target_address.as_slice()).unwrap(),
-        module_hash: Bytes32::try_from(keccak256(input.target_address.as_slice()).as_slice())
-            .unwrap(),
+        module_hash: Bytes32::from(module_hash.0),
         msg_sender: Bytes20::try_from(input.caller_address.as_slice()).unwrap(),
         msg_value: Bytes32::try_from(input.call_value.to_be_bytes_vec()).unwrap(),
         tx_gas_price: Bytes32::from(
             U256::from(tx_env.effective_gas_price(base_fee as u128)).to_be_bytes(),
         ),
         tx_origin: Bytes20::try_from(tx_env.caller().as_slice()).unwrap(),
-        reentrant: 0,
+        reentrant: context.local().is_reentrant(input.target_address).into(),
         return_data_len: 0,
         cached: true,
         tracing: true,
@@ -185,7 +214,13 @@ where
                   req_data: Vec<u8>|
                   -> (Vec<u8>, VecReader, arbutil::evm::api::Gas) {
                 let mut evm = evm.lock().unwrap();
-                request_handler(&mut evm, input.clone(), is_static, req_type, req_data)
+                evm.dispatch_request(
+                    input.clone(),
+                    is_static,
+                    req_type,
+                    req_data,
+                    &request_handler,
+                )
             }
         };
 
@@ -194,8 +229,11 @@ where
         EvmApiRequestor::new(StylusHandler::new(unsafe_callback.clone()))
     }
 
-    /// Extract common Stylus execution context from frame input
-    fn extract_stylus_context(&mut self) -> Option<(StylusExecutionContext, B256)> {
+    /// Extract common Stylus execution context from frame input, or the result failing the
+    /// frame when its calldata can't be read
+    fn extract_stylus_context(
+        &mut self,
+    ) -> Option<Result<(StylusExecutionContext, B256), InterpreterResult>> {
         let frame_input = {
             let frame = self.frame_stack().get();
             match frame.input {
@@ -217,12 +255,34 @@ where
 
         let calldata = match &frame_input.input {
             CallInput::Bytes(calldata) => calldata.clone(),
+            // The caller's memory is borrowed again by the frames the program calls into, so
+            // the calldata is copied out of it once, before the program runs. Calldata passed
+            // as bytes is shared
             CallInput::SharedBuffer(range) => {
-                if let Some(slice) = self.ctx().local().shared_memory_buffer_slice(range.clone()) {
-                    Bytes::from(slice.to_vec())
-                } else {
-                    Bytes::new()
-                }
+                let calldata = self
+                    .ctx()
+                    .local()
+                    .shared_memory_buffer_slice(range.clone())
+                    .map(|slice| Bytes::copy_from_slice(&slice));
+                let Some(calldata) = calldata else {
+                    warn!(
+                        target: "arbos-revm::stylus",
+                        bytecode_address = %bytecode_address,
+                        ?range,
+                        "Stylus calldata lies outside the caller's memory"
+                    );
+                    *self.ctx().error() = Err(ContextError::Custom(format!(
+                        "Stylus calldata {range:?} lies outside the caller's memory"
+                    )));
+                    let mut gas = Gas::new(frame_input.gas_limit);
+                    gas.spend_all();
+                    return Some(Err(InterpreterResult {
+                        result: InstructionResult::FatalExternalError,
+                        output: Bytes::new(),
+                        gas,
+                    }));
+                };
+                calldata
             }
         };
 
@@ -236,7 +296,7 @@ where
             calldata,
         };
 
-        Some((context, code_hash))
+        Some(Ok((context, code_hash)))
     }
 
     /// Core Stylus execution logic shared between inspected and non-inspected modes
@@ -264,42 +324,66 @@ where
         );
         let mut gas = Gas::new(stylus_ctx.gas_limit);
 
-        let (serialized, _module, stylus_data, stylus_params) = {
-            // Use read lock to get cached program if available
-            // if not available drop the read lock and acquire write lock to compile and insert
-            let maybe_cached = {
-                let mut cache = PROGRAM_CACHE.lock().unwrap();
-                if let Some((serialized, module, stylus_data)) = cache.get(&code_hash).cloned() {
-                    trace!(
+        // Each program calling out keeps its instance alive until the call returns, so nested
+        // programs are bounded before another one is instantiated
+        let stylus_depth = self.ctx().local().stylus_call_depth();
+        if let Some(max_depth) = self.ctx().cfg().max_stylus_call_depth()
+            && stylus_depth >= max_depth
+        {
+            debug!(
+                target: "arbos-revm::stylus",
+                bytecode_address = %stylus_ctx.bytecode_address,
+                stylus_depth,
+                max_depth,
+                "Stylus call depth limit reached"
+            );
+            return Some(InterpreterAction::Return(InterpreterResult {
+                result: InstructionResult::CallTooDeep,
+                output: Bytes::new(),
+                gas,
+            }));
+        }
+
+        let (serialized, module, stylus_data, stylus_params) = {
+            let context = self.ctx();
+
+            let stylus_params = match context
+                .system_arb_state(true)
+                .programs()
+                .stylus_params()
+                .get()
+            {
+                Ok(params) => params,
+                Err(e) => {
+                    debug!(
                         target: "arbos-revm::stylus",
-                        code_hash = %code_hash,
-                        "Using cached Stylus program"
+                        bytecode_address = %stylus_ctx.bytecode_address,
+                        error = ?e,
+                        "Failed to fetch Stylus parameters"
                     );
-                    Some((serialized, module, stylus_data))
-                } else {
-                    None
+                    return Some(e.into());
                 }
             };
 
-            if let Some((serialized, module, stylus_data)) = maybe_cached {
-                let stylus_params = {
-                    let context = self.ctx();
-
-                    match context
-                        .arb_state(None, true)
-                        .programs()
-                        .stylus_params()
-                        .get()
-                    {
-                        Ok(params) => params,
-                        Err(e) => return Some(e.into()),
-                    }
-                };
+            let debug = context.cfg().debug_mode();
+            let tag = ProgramCacheTag {
+                stylus_version: stylus_params.version,
+                arbos_version: context.arbos_version().as_u16(),
+                debug,
+            };
+            let cache = context.local().program_cache().clone();
 
+            if let Some((serialized, module, stylus_data)) = cache.get(&code_hash, tag) {
+                trace!(
+                    target: "arbos-revm::stylus",
+                    code_hash = %code_hash,
+                    "Using cached Stylus program"
+                );
+                context
+                    .local_mut()
+                    .record_program_load(true, Duration::ZERO);
                 (serialized, module, stylus_data, stylus_params)
             } else {
-                let context = self.ctx();
-
                 let bytecode = context
                     .journal_mut()
                     .code(stylus_ctx.bytecode_address)
@@ -324,53 +408,26 @@ where
                     }
                 };
 
-                let stylus_params = {
-                    match context
-                        .arb_state(None, true)
-                        .programs()
-                        .stylus_params()
-                        .get()
-                    {
-                        Ok(params) => params,
-                        Err(e) => {
-                            debug!(
-                                target: "arbos-revm::stylus",
-                                bytecode_address = %stylus_ctx.bytecode_address,
-                                error = ?e,
-                                "Failed to fetch Stylus parameters"
-                            );
-                            return Some(e.into());
-                        }
-                    }
-                };
-
-                let compile_config =
-                    CompileConfig::version(stylus_params.version, context.cfg().debug_mode());
-
-                let debug = context.cfg().debug_mode();
+                let wasm_store = context.cfg().wasm_store_dir().map(Path::to_path_buf);
+                let module_cache = cache.module_cache();
 
-                let mut cache = PROGRAM_CACHE.lock().unwrap();
-                match cache.try_get_or_insert::<_, String>(code_hash, || {
-                    let serialized = stylus_compile(&bytecode, &compile_config)?;
-
-                    let (module, stylus_data) = stylus_activate(
-                        None,
+                let started = Instant::now();
+                match cache.get_or_try_insert(code_hash, tag, || {
+                    compile_program(
                         &bytecode,
                         code_hash,
-                        context.cfg().arbos_version(),
-                        stylus_params.version,
+                        tag,
                         stylus_params.page_limit,
-                        debug,
-                    )?;
-
-                    Ok((serialized, module, stylus_data))
+                        module_cache.as_deref(),
+                        wasm_store.as_deref(),
+                    )
                 }) {
-                    Ok((serialized, module, stylus_data)) => (
-                        serialized.clone(),
-                        module.clone(),
-                        *stylus_data,
-                        stylus_params,
-                    ),
+                    Ok((serialized, module, stylus_data)) => {
+                        context
+                            .local_mut()
+                            .record_program_load(false, started.elapsed());
+                        (serialized, module, stylus_data, stylus_params)
+                    }
                     Err(e) => {
                         warn!(
                             target: "arbos-revm::stylus",
@@ -379,6 +436,7 @@ where
                             error = %e,
                             "Stylus compile/activate failed"
                         );
+                        self.record_stylus_failure(ArbitrumHaltReason::ActivationFailed);
                         return Some(InterpreterAction::Return(InterpreterResult {
                             result: InstructionResult::Revert,
                             output: e.as_bytes().to_vec().into(),
@@ -389,6 +447,29 @@ where
             }
         };
 
+        // Calldata is not capped by default, as in Nitro: callers paid to expand their memory to
+        // hold it, and `read_args` charges for copying it into the program's memory
+        let limit_buffers = self.ctx().cfg().limit_stylus_buffers();
+        let max_buffer_size = usize::from(stylus_params.page_limit) * WASM_PAGE_SIZE;
+        if limit_buffers
+            && (stylus_ctx.calldata.len() > max_buffer_size
+                || !gas.record_cost(memory_gas(stylus_ctx.calldata.len())))
+        {
+            debug!(
+                target: "arbos-revm::stylus",
+                bytecode_address = %stylus_ctx.bytecode_address,
+                calldata_len = stylus_ctx.calldata.len(),
+                max_buffer_size,
+                "Stylus calldata exceeds the addressable memory or the gas to hold it"
+            );
+            gas.spend_all();
+            return Some(InterpreterAction::Return(InterpreterResult {
+                result: InstructionResult::OutOfGas,
+                output: Bytes::new(),
+                gas,
+            }));
+        }
+
         let (stylus_config, compile_config, evm_data) = {
             let context = self.ctx();
 
@@ -410,6 +491,7 @@ where
                     call_value: stylus_ctx.call_value,
                     bytecode_address: Some(stylus_ctx.target_address),
                 },
+                B256::from_slice(module.hash().as_slice()),
             );
 
             (stylus_config, compile_config, evm_data)
@@ -417,11 +499,18 @@ where
 
         let program_info = match self
             .ctx()
-            .arb_state(None, false)
+            .system_arb_state(false)
             .programs()
             .get_active_program(&stylus_params, &code_hash)
         {
-            Ok(info) => info,
+            Ok(info) => {
+                // Programs cached through ArbWasmCache stay compiled for good, like in Nitro's
+                // long-term cache beside its LRU, until evicted through it again
+                if info.cached {
+                    self.ctx().local().program_cache().pin(code_hash);
+                }
+                info
+            }
             Err(e) => {
                 if !self.ctx().cfg().disable_auto_activate() {
                     trace!(
@@ -430,6 +519,13 @@ where
                         code_hash = %code_hash,
                         "Program not active, building auto-activation info"
                     );
+                    self.ctx().local_mut().record_activation(ActivatedProgram {
+                        code_hash,
+                        program: stylus_ctx.bytecode_address,
+                        version: stylus_params.version,
+                        data_fee: U256::ZERO,
+                        auto_activated: true,
+                    });
                     ProgramInfo {
                         version: stylus_params.version,
                         init_cost: stylus_data.init_cost,
@@ -448,6 +544,7 @@ where
                                 code_hash = %code_hash,
                                 "Stylus program not activated"
                             );
+                            self.record_stylus_failure(ArbitrumHaltReason::ProgramNotActivated);
                             gas.spend_all();
                             return Some(InterpreterAction::Return(InterpreterResult {
                                 result: InstructionResult::OutOfGas,
@@ -483,7 +580,7 @@ where
         let inputs = InputsImpl {
             target_address: stylus_ctx.target_address,
             caller_address: stylus_ctx.caller_address,
-            input: CallInput::Bytes(Bytes::from(stylus_ctx.calldata.to_vec())),
+            input: CallInput::Bytes(stylus_ctx.calldata.clone()),
             call_value: stylus_ctx.call_value,
             bytecode_address: Some(stylus_ctx.target_address),
         };
@@ -539,9 +636,9 @@ where
         }
 
         {
-            self.ctx()
-                .local_mut()
-                .add_stylus_pages_open(stylus_data.footprint);
+            let local = self.ctx().local_mut();
+            local.add_stylus_pages_open(stylus_data.footprint);
+            local.push_stylus_storage_cache(StylusStorageCache::new(stylus_ctx.target_address));
         }
 
         let evm_api =
@@ -557,12 +654,12 @@ where
             .gas_to_ink(arbutil::evm::api::Gas(gas.remaining()));
         gas.spend_all();
 
-        let bytecode = match inputs.input() {
-            CallInput::Bytes(bytes) => bytes,
-            CallInput::SharedBuffer(_) => todo!(),
-        };
+        // Only unavailable hostios requested by this program tell its failure apart
+        self.ctx().local_mut().take_hostio_unavailable();
 
-        let outcome = match instance.run_main(bytecode, stylus_config, ink_limit) {
+        // Calldata was resolved when the execution context was built, including inputs that
+        // point into the parent's shared memory buffer.
+        let outcome = match instance.run_main(&stylus_ctx.calldata, stylus_config, ink_limit) {
             Err(e) | Ok(UserOutcome::Failure(e)) => {
                 debug!(
                     target: "arbos-revm::stylus",
@@ -579,7 +676,31 @@ where
         let ink_left = instance.ink_left().into();
         let mut gas_left = stylus_config.pricing.ink_to_gas(ink_left).0;
 
+        let failure = match &outcome {
+            UserOutcome::Failure(e) => Some(format!("{e:#}")),
+            _ => None,
+        };
         let (kind, data) = outcome.into_data();
+        let hostio_unavailable = self.ctx().local_mut().take_hostio_unavailable();
+        match kind {
+            UserOutcomeKind::OutOfInk => self.record_stylus_failure(ArbitrumHaltReason::OutOfInk),
+            UserOutcomeKind::OutOfStack => {
+                self.record_stylus_failure(ArbitrumHaltReason::WasmStackOverflow)
+            }
+            UserOutcomeKind::Failure if hostio_unavailable => {
+                self.record_stylus_failure(ArbitrumHaltReason::HostioUnavailable)
+            }
+            _ => {}
+        }
+
+        let ink_used = ink_limit.0.saturating_sub(ink_left.0);
+        let local = self.ctx().local_mut();
+        local.record_stylus_run(call_cost, ink_used);
+        local.set_stylus_outcome(Some(StylusOutcome {
+            kind,
+            ink_used,
+            failure,
+        }));
 
         trace!(
             target: "arbos-revm::stylus",
@@ -587,7 +708,7 @@ where
             target_address = %stylus_ctx.target_address,
             outcome = ?kind,
             output_len = data.len(),
-            output = %String::from_utf8_or_hex(data.clone()),
+            output = %String::from_utf8_or_hex(data.as_slice()),
             ink_left = ink_left.0,
             gas_left,
             "Stylus program finished"
@@ -605,12 +726,38 @@ where
         };
 
         gas.erase_cost(gas_left);
+        if result.is_ok() {
+            gas.record_refund(self.frame_stack().get().interpreter.gas.refunded());
+            self.flush_storage_cache();
+        }
+        self.ctx().local_mut().pop_stylus_storage_cache();
 
         self.ctx()
             .local_mut()
             .set_stylus_pages_open(stylus_open_pages);
 
-        if !data.is_empty() && self.ctx().cfg().arbos_version() >= ARBOS_VERSION_STYLUS_FIXES {
+        if limit_buffers && data.len() > max_buffer_size {
+            debug!(
+                target: "arbos-revm::stylus",
+                bytecode_address = %stylus_ctx.bytecode_address,
+                output_len = data.len(),
+                max_buffer_size,
+                "Stylus output exceeds the addressable memory"
+            );
+            gas.spend_all();
+            return Some(InterpreterAction::Return(InterpreterResult {
+                result: InstructionResult::OutOfGas,
+                output: Default::default(),
+                gas,
+            }));
+        }
+
+        if !data.is_empty()
+            && self
+                .ctx()
+                .arbos_version()
+                .supports(ArbosFeature::StylusFixes)
+        {
             let evm_cost = memory_gas(data.len());
 
             if gas.limit() < evm_cost {
@@ -645,14 +792,86 @@ where
         }))
     }
 
+    /// Record `failure` in the execution details when the failing program is the one the
+    /// transaction called, so that it ends the transaction.
+    fn record_stylus_failure(&mut self, failure: ArbitrumHaltReason) {
+        if self.frame_stack().get().depth == 0 {
+            self.ctx().local_mut().set_stylus_failure(failure);
+        }
+    }
+
     pub fn frame_run_stylus(&mut self) -> Option<InterpreterAction> {
-        let (stylus_ctx, code_hash) = self.extract_stylus_context()?;
+        let (stylus_ctx, code_hash) = match self.extract_stylus_context()? {
+            Ok(extracted) => extracted,
+            Err(result) => return Some(InterpreterAction::Return(result)),
+        };
         self.execute_stylus_program(
             stylus_ctx,
             code_hash,
             |evm, inputs, is_static, req_type, data| evm.request(inputs, is_static, req_type, data),
         )
     }
+
+    /// Run the Stylus program at `program` as a top-level call from the transaction's caller,
+    /// bypassing the handler: nothing is validated or charged, no nonce is bumped, and state
+    /// changes are left uncommitted in the journal.
+    pub fn execute_stylus_call(
+        &mut self,
+        program: Address,
+        calldata: Bytes,
+        gas_limit: u64,
+    ) -> Result<InterpreterResult, ContextError<<CTX::Db as Database>::Error>> {
+        let code = self
+            .ctx()
+            .journal_mut()
+            .code(program)
+            .map_err(ContextError::Db)?
+            .data;
+        if !code.starts_with(STYLUS_DISCRIMINANT) {
+            return Ok(InterpreterResult {
+                result: InstructionResult::Revert,
+                output: Bytes::from("specified bytecode is not a Stylus program"),
+                gas: Gas::new(gas_limit),
+            });
+        }
+
+        let spec = self.ctx().cfg().spec();
+        let (ctx, precompiles) = self.ctx_precompiles();
+        precompiles.set_spec(spec);
+        let addresses = precompiles
+            .warm_addresses()
+            .map(|address| ctx.cfg().precompile_address(address))
+            .collect();
+        ctx.journal_mut().warm_precompiles(addresses);
+
+        ctx.local_mut().clear();
+        if ctx.cfg().arbos_version_from_state() {
+            load_arbos_version(ctx);
+        }
+
+        let frame_init = FrameInit {
+            depth: 0,
+            memory: SharedMemory::new_with_buffer(ctx.local().shared_memory_buffer().clone()),
+            frame_input: FrameInput::Call(Box::new(CallInputs {
+                input: CallInput::Bytes(calldata),
+                return_memory_offset: 0..0,
+                gas_limit,
+                bytecode_address: program,
+                known_bytecode: None,
+                target_address: program,
+                caller: ctx.tx().caller(),
+                value: CallValue::Transfer(U256::ZERO),
+                scheme: CallScheme::Call,
+                is_static: false,
+            })),
+        };
+
+        self.frame_stack().clear();
+        let result = self.run_exec_loop(frame_init);
+        self.frame_stack().clear();
+
+        result.map(|frame_result| frame_result.interpreter_result().clone())
+    }
 }
 
 impl<CTX, INSP, P, I> ArbitrumEvm<CTX, INSP, P, I>
@@ -662,10 +881,13 @@ where
     I: InstructionProvider<Context = CTX, InterpreterTypes = EthInterpreter>,
     P: PrecompileProvider<CTX, Output = InterpreterResult>,
     CTX: ContextSetters,
-    INSP: Inspector<CTX>,
+    INSP: Inspector<CTX> + StylusInspector<CTX> + PrecompileInspector<CTX>,
 {
     pub fn inspect_frame_run_stylus(&mut self) -> Option<InterpreterAction> {
-        let (stylus_ctx, code_hash) = self.extract_stylus_context()?;
+        let (stylus_ctx, code_hash) = match self.extract_stylus_context()? {
+            Ok(extracted) => extracted,
+            Err(result) => return Some(InterpreterAction::Return(result)),
+        };
         self.execute_stylus_program(
             stylus_ctx,
             code_hash,
@@ -682,7 +904,10 @@ where
         req_type: EvmApiMethod,
         data: Vec<u8>,
     ) -> (Vec<u8>, VecReader, ArbGas) {
-        match req_type {
+        let address = input.target_address;
+        let request = data.clone();
+
+        let (result, raw_data, gas) = match req_type {
             EvmApiMethod::ContractCall | EvmApiMethod::DelegateCall | EvmApiMethod::StaticCall => {
                 self.handle_contract_call(input, is_static, req_type, data, |evm, frame_init| {
                     evm.inspect_run_exec_loop(frame_init)
@@ -704,7 +929,45 @@ where
                     inspector.log(context, log);
                 })
             }
-            _ => self.request_inner(input, is_static, req_type, data),
+            _ => {
+                let (result, raw_data, gas) = self.request_inner(input, is_static, req_type, data);
+                self.ctx().local_mut().record_hostio_gas(req_type, gas.0);
+                (result, raw_data, gas)
+            }
+        };
+
+        let ink = self.hostio_ink(gas);
+        let (context, inspector) = self.ctx_inspector();
+        inspector.hostio(
+            context,
+            &StylusHostio {
+                address,
+                method: req_type,
+                input: &request,
+                output: &result,
+                gas: gas.0,
+                ink,
+            },
+        );
+        (result, raw_data, gas)
+    }
+
+    /// `gas` charged for a hostio, in ink at the current Stylus ink price.
+    fn hostio_ink(&mut self, gas: ArbGas) -> u64 {
+        match self
+            .ctx()
+            .system_arb_state(true)
+            .programs()
+            .stylus_params()
+            .get()
+        {
+            Ok(params) => {
+                StylusConfig::new(params.version, params.max_stack_depth, params.ink_price)
+                    .pricing
+                    .gas_to_ink(gas)
+                    .0
+            }
+            Err(_) => 0,
         }
     }
 }
@@ -743,13 +1006,72 @@ pub fn stylus_code(bytecode: &[u8]) -> Result<Option<Bytes>, Vec<u8>> {
     }
 }
 
-/// Compile Stylus bytecode
+/// Target Stylus programs are compiled to native code for. Besides the host, Nitro compiles
+/// programs for the validator architectures under fixed CPU features, so that native code can be
+/// shipped to validators.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StylusCompileTarget {
+    /// The machine compiling, with every feature of its CPU.
+    #[default]
+    Host,
+    /// x86-64 validators, described by [`StylusCompileTarget::AMD64_DESCRIPTION`].
+    Amd64,
+    /// AArch64 validators, described by [`StylusCompileTarget::ARM64_DESCRIPTION`].
+    Arm64,
+}
+
+impl StylusCompileTarget {
+    /// Nitro's default description of the `amd64` target: a triple and CPU features.
+    pub const AMD64_DESCRIPTION: &str = "x86_64-linux-unknown+sse4.2+lzcnt+bmi";
+    /// Nitro's default description of the `arm64` target.
+    pub const ARM64_DESCRIPTION: &str = "arm64-linux-unknown+neon";
+
+    /// Name of the target in Nitro's wasm database. Native code for the host is named after its
+    /// architecture, as by [`native_target`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Host => native_target(),
+            Self::Amd64 => "amd64",
+            Self::Arm64 => "arm64",
+        }
+    }
+
+    /// The wasmer target to compile for.
+    pub fn target(self) -> Result<Target, String> {
+        let description = match self {
+            Self::Host => return Ok(Target::default()),
+            Self::Amd64 => Self::AMD64_DESCRIPTION,
+            Self::Arm64 => Self::ARM64_DESCRIPTION,
+        };
+        let mut parts = description.split('+');
+        let triple =
+            Triple::from_str(parts.next().unwrap_or_default()).map_err(|e| e.to_string())?;
+        let mut features = CpuFeature::set();
+        for feature in parts {
+            features.insert(CpuFeature::from_str(feature).map_err(|e| e.to_string())?);
+        }
+        Ok(Target::new(triple, features))
+    }
+}
+
+/// Compile Stylus bytecode for the host
 pub fn stylus_compile(bytecode: &Bytes, compile_config: &CompileConfig) -> Result<Vec<u8>, String> {
+    stylus_compile_for(bytecode, compile_config, StylusCompileTarget::Host)
+}
+
+/// Compile Stylus bytecode for `target`. Native code for another target can't be executed here,
+/// only shipped to nodes of that architecture.
+pub fn stylus_compile_for(
+    bytecode: &Bytes,
+    compile_config: &CompileConfig,
+    target: StylusCompileTarget,
+) -> Result<Vec<u8>, String> {
     let serialized = native::compile(
         bytecode,
         compile_config.version,
         compile_config.debug.debug_funcs,
-        wasmer_types::compilation::target::Target::default(),
+        target.target()?,
         false,
     )
     .map_err(|e| e.to_string())?;
@@ -757,6 +1079,302 @@ pub fn stylus_compile(bytecode: &Bytes, compile_config: &CompileConfig) -> Resul
     Ok(serialized)
 }
 
+/// Path of the native code for the module with `module_hash` in the wasm store at `store`:
+/// `<store>/<target>/<module hash in hex>`, where the target is named as in Nitro's wasm
+/// database (`amd64`, `arm64` or `host`). Node operators can export Nitro's activated programs
+/// into this layout to reuse them.
+pub fn wasm_store_path(store: &Path, module_hash: B256) -> PathBuf {
+    store.join(native_target()).join(hex::encode(module_hash))
+}
+
+/// Name of the target native code is compiled for, as in Nitro's wasm database.
+pub fn native_target() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        _ => "host",
+    }
+}
+
+/// Native code for an activated `module`, loaded from the wasm store at `store` when it holds
+/// the module and compiled from `bytecode` otherwise.
+pub fn stylus_native_code(
+    store: Option<&Path>,
+    module: &Module,
+    bytecode: &Bytes,
+    compile_config: &CompileConfig,
+) -> Result<Vec<u8>, String> {
+    if let Some(store) = store {
+        let path = wasm_store_path(store, B256::from_slice(module.hash().as_slice()));
+        match fs::read(&path) {
+            Ok(serialized) => return Ok(serialized),
+            Err(e) => trace!(
+                target: "arbos-revm::stylus",
+                path = %path.display(),
+                error = %e,
+                "Compiling Stylus program missing from the wasm store"
+            ),
+        }
+    }
+    stylus_compile(bytecode, compile_config)
+}
+
+/// Compiled Stylus program with the settings it was compiled under, so that another node can
+/// verify or reuse the native code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramArtifact {
+    pub code_hash: B256,
+    pub module_hash: B256,
+    pub stylus_version: u16,
+    pub arbos_version: u16,
+    pub debug: bool,
+    /// Target the native code was compiled for, as named by [`StylusCompileTarget::name`].
+    pub target: &'static str,
+    pub native_code: Vec<u8>,
+    pub native_code_hash: B256,
+}
+
+/// Compiled program for the code with `code_hash` under the current Stylus parameters, ArbOS
+/// version and debug mode, taken from the program cache or compiled into it. Programs are
+/// compiled for the configured [`StylusCompileTarget`], bypassing the cache for another target
+/// than the host.
+pub fn program_artifact<CTX>(context: &mut CTX, code_hash: B256) -> Result<ProgramArtifact, String>
+where
+    CTX: ArbitrumContextTr<Journal: JournalExt>,
+{
+    let stylus_params = context
+        .system_arb_state(true)
+        .programs()
+        .stylus_params()
+        .get()
+        .map_err(String::from)?;
+
+    let tag = ProgramCacheTag {
+        stylus_version: stylus_params.version,
+        arbos_version: context.arbos_version().as_u16(),
+        debug: context.cfg().debug_mode(),
+    };
+    let target = context.cfg().stylus_compile_target();
+    if target != StylusCompileTarget::Host {
+        // The cache holds native code for the host only
+        let bytecode = load_stylus_code(context, code_hash)?;
+        let (module, _) = stylus_activate(
+            None,
+            &bytecode,
+            code_hash,
+            tag.arbos_version,
+            tag.stylus_version,
+            stylus_params.page_limit,
+            tag.debug,
+        )?;
+        let compile_config = CompileConfig::version(tag.stylus_version, tag.debug);
+        let native_code = stylus_compile_for(&bytecode, &compile_config, target)?;
+        return Ok(ProgramArtifact {
+            code_hash,
+            module_hash: B256::from_slice(module.hash().as_slice()),
+            stylus_version: tag.stylus_version,
+            arbos_version: tag.arbos_version,
+            debug: tag.debug,
+            target: target.name(),
+            native_code_hash: keccak256(&native_code),
+            native_code,
+        });
+    }
+
+    let cache = context.local().program_cache().clone();
+    let wasm_store = context.cfg().wasm_store_dir().map(Path::to_path_buf);
+    let module_cache = cache.module_cache();
+
+    let (native_code, module, _) = cache.get_or_try_insert(code_hash, tag, || {
+        let bytecode = load_stylus_code(context, code_hash)?;
+        compile_program(
+            &bytecode,
+            code_hash,
+            tag,
+            stylus_params.page_limit,
+            module_cache.as_deref(),
+            wasm_store.as_deref(),
+        )
+    })?;
+
+    Ok(ProgramArtifact {
+        code_hash,
+        module_hash: B256::from_slice(module.hash().as_slice()),
+        stylus_version: tag.stylus_version,
+        arbos_version: tag.arbos_version,
+        debug: tag.debug,
+        target: native_target(),
+        native_code_hash: keccak256(&native_code),
+        native_code,
+    })
+}
+
+/// Compile the Stylus programs with `code_hashes` into the program cache ahead of execution,
+/// under the current Stylus parameters, ArbOS version and debug mode. Programs already cached
+/// are skipped and the rest are compiled in parallel, so that newly deployed programs can be
+/// compiled off the critical path. No program is activated onchain.
+///
+/// Returns the outcome for each code hash, in order.
+pub fn precompile_programs<CTX>(
+    context: &mut CTX,
+    code_hashes: impl IntoIterator<Item = B256>,
+) -> Vec<(B256, Result<(), String>)>
+where
+    CTX: ArbitrumContextTr<Journal: JournalExt>,
+{
+    let stylus_params = match context
+        .system_arb_state(true)
+        .programs()
+        .stylus_params()
+        .get()
+    {
+        Ok(params) => params,
+        Err(e) => {
+            let e = String::from(e);
+            return code_hashes
+                .into_iter()
+                .map(|code_hash| (code_hash, Err(e.clone())))
+                .collect();
+        }
+    };
+
+    let tag = ProgramCacheTag {
+        stylus_version: stylus_params.version,
+        arbos_version: context.arbos_version().as_u16(),
+        debug: context.cfg().debug_mode(),
+    };
+    let cache = context.local().program_cache().clone();
+    let wasm_store = context.cfg().wasm_store_dir().map(Path::to_path_buf);
+    let module_cache = cache.module_cache();
+
+    // The context can't be shared across threads, so the code is loaded up front
+    let programs: Vec<_> = code_hashes
+        .into_iter()
+        .map(|code_hash| {
+            let bytecode = if cache.get(&code_hash, tag).is_some() {
+                Ok(None)
+            } else {
+                load_stylus_code(context, code_hash).map(Some)
+            };
+            (code_hash, bytecode)
+        })
+        .collect();
+
+    programs
+        .into_par_iter()
+        .map(|(code_hash, bytecode)| {
+            let result = bytecode.and_then(|bytecode| {
+                let Some(bytecode) = bytecode else {
+                    return Ok(());
+                };
+                let entry = compile_program(
+                    &bytecode,
+                    code_hash,
+                    tag,
+                    stylus_params.page_limit,
+                    module_cache.as_deref(),
+                    wasm_store.as_deref(),
+                )?;
+                cache.insert(code_hash, tag, entry);
+                Ok(())
+            });
+            if let Err(e) = &result {
+                debug!(
+                    target: "arbos-revm::stylus",
+                    code_hash = %code_hash,
+                    error = %e,
+                    "Stylus ahead-of-time compile failed"
+                );
+            }
+            (code_hash, result)
+        })
+        .collect()
+}
+
+/// Decompressed wasm of the Stylus program with `code_hash`, looked up among the loaded
+/// accounts before the database.
+fn load_stylus_code<CTX>(context: &mut CTX, code_hash: B256) -> Result<Bytes, String>
+where
+    CTX: ArbitrumContextTr<Journal: JournalExt>,
+{
+    let loaded = context
+        .journal_mut()
+        .evm_state()
+        .values()
+        .find_map(|account| {
+            account
+                .info
+                .code
+                .clone()
+                .filter(|_| account.info.code_hash == code_hash)
+        });
+    let bytecode = match loaded {
+        Some(bytecode) => bytecode,
+        None => context
+            .db_mut()
+            .code_by_hash(code_hash)
+            .map_err(|e| e.to_string())?,
+    };
+    match stylus_code(&bytecode.original_bytes()) {
+        Ok(Some(code)) => Ok(code),
+        Ok(None) => Err("specified bytecode is not a Stylus program".into()),
+        Err(e) => Err(String::from_utf8_or_hex(e)),
+    }
+}
+
+/// Activate the Stylus `bytecode` with `code_hash` under `tag` and produce its native code.
+fn compile_program(
+    bytecode: &Bytes,
+    code_hash: B256,
+    tag: ProgramCacheTag,
+    page_limit: u16,
+    module_cache: Option<&dyn StylusModuleCache>,
+    wasm_store: Option<&Path>,
+) -> Result<ProgramCacheEntry, String> {
+    let (module, stylus_data) = stylus_activate(
+        None,
+        bytecode,
+        code_hash,
+        tag.arbos_version,
+        tag.stylus_version,
+        page_limit,
+        tag.debug,
+    )?;
+
+    let serialized =
+        cached_native_code(module_cache, wasm_store, &module, bytecode, code_hash, tag)?;
+
+    Ok((serialized, module, stylus_data))
+}
+
+/// Native code for the activated `module` of the program with `code_hash` under `tag`, loaded
+/// from `module_cache` when it holds the program and from the wasm store or compiled otherwise,
+/// in which case it is stored in `module_cache`.
+pub(crate) fn cached_native_code(
+    module_cache: Option<&dyn StylusModuleCache>,
+    wasm_store: Option<&Path>,
+    module: &Module,
+    bytecode: &Bytes,
+    code_hash: B256,
+    tag: ProgramCacheTag,
+) -> Result<Vec<u8>, String> {
+    if let Some(serialized) = module_cache.and_then(|cache| cache.load(&code_hash, tag)) {
+        trace!(
+            target: "arbos-revm::stylus",
+            code_hash = %code_hash,
+            "Using Stylus program from the module cache"
+        );
+        return Ok(serialized);
+    }
+
+    let compile_config = CompileConfig::version(tag.stylus_version, tag.debug);
+    let serialized = stylus_native_code(wasm_store, module, bytecode, &compile_config)?;
+    if let Some(cache) = module_cache {
+        cache.store(&code_hash, tag, &serialized);
+    }
+    Ok(serialized)
+}
+
 pub fn stylus_activate(
     mut gas: Option<&mut Gas>,
     bytecode: &Bytes,
@@ -794,16 +1412,72 @@ pub fn stylus_activate(
     Ok((module, stylus_data))
 }
 
-pub fn ink_to_gas_ceil(pricing: PricingParams, ink: Ink) -> u64 {
-    ink.0.div_ceil(pricing.ink_price as u64)
+/// Activate a program without a context, reporting what on-chain activation would record.
+///
+/// `bytes` may be deployed Stylus code (discriminant, dictionary byte and compressed wasm) or a
+/// raw wasm module. The data fee is the one charged by a fresh data pricer with the initial
+/// parameters, so it is a lower bound on the fee a live chain would charge.
+pub fn activate_wasm(
+    bytes: &[u8],
+    stylus_version: u16,
+    arbos_version: u16,
+    page_limit: u16,
+) -> Result<ActivationInfo, String> {
+    let wasm = match stylus_code(bytes) {
+        Ok(Some(wasm)) => wasm,
+        Ok(None) => Bytes::copy_from_slice(bytes),
+        Err(err) => return Err(String::from_utf8_lossy(&err).into_owned()),
+    };
+
+    let (module, stylus_data) = stylus_activate(
+        None,
+        &wasm,
+        keccak256(bytes),
+        arbos_version,
+        stylus_version,
+        page_limit,
+        false,
+    )?;
+
+    let data_fee = DataPricerParams::default().update(
+        stylus_data.asm_estimate,
+        INITIAL_DATA_PRICER_LAST_UPDATE_TIME,
+    );
+
+    Ok(ActivationInfo {
+        version: stylus_version,
+        data_fee,
+        module_hash: B256::from_slice(module.hash().as_slice()),
+        init_cost: stylus_data.init_cost,
+        cached_init_cost: stylus_data.cached_init_cost,
+        footprint: stylus_data.footprint,
+        asm_estimate: stylus_data.asm_estimate,
+    })
 }
 
-pub fn cache_program(
-    code_hash: B256,
-    serialized: Vec<u8>,
-    module: Module,
-    stylus_data: StylusData,
+/// Run the Stylus program at `program` against `ctx` with the default instructions and
+/// precompiles, without a transaction or handler. See [`ArbitrumEvm::execute_stylus_call`].
+/// The context is handed back with the program's state changes in its journal.
+pub fn execute_stylus_call<CTX: ArbitrumContextTr>(
+    ctx: CTX,
+    program: Address,
+    calldata: Bytes,
+    gas_limit: u64,
+) -> (
+    Result<InterpreterResult, ContextError<<CTX::Db as Database>::Error>>,
+    CTX,
 ) {
-    let mut cache = PROGRAM_CACHE.lock().unwrap();
-    cache.get_or_insert(code_hash, || (serialized, module, stylus_data));
+    let mut evm: ArbitrumEvm<CTX, NoOpInspector, ArbitrumPrecompileProvider<CTX>> =
+        ArbitrumEvm::new_with_inspector(
+            ctx,
+            NoOpInspector {},
+            EthInstructions::default(),
+            ArbitrumPrecompileProvider::default(),
+        );
+    let result = evm.execute_stylus_call(program, calldata, gas_limit);
+    (result, evm.0.ctx)
+}
+
+pub fn ink_to_gas_ceil(pricing: PricingParams, ink: Ink) -> u64 {
+    ink.0.div_ceil(pricing.ink_price as u64)
 }
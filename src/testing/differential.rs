@@ -0,0 +1,610 @@
+//! Differential execution against Nitro: a transaction traced on a Nitro node is replayed from
+//! the state it started from, and its outcome compared with Nitro's.
+//!
+//! A trace is a JSON object assembled from the node's RPC answers for the transaction:
+//!
+//! ```json
+//! {
+//!   "arbosVersion": 32,
+//!   "block": { "number": "0x…", "timestamp": "0x…", "miner": "0x…", "baseFeePerGas": "0x…" },
+//!   "transaction": { "from": "0x…", "to": "0x…", "input": "0x…", "gas": "0x…", "nonce": "0x…" },
+//!   "rawTransaction": "0x…",
+//!   "prestate": { "0x…": { "balance": "0x…", "code": "0x…", "storage": { "0x…": "0x…" } } },
+//!   "stateDiff": { "pre": { … }, "post": { … } },
+//!   "receipt": { "status": "0x1", "gasUsed": "0x…", "logs": [{ "address": "0x…", … }] }
+//! }
+//! ```
+//!
+//! * `block` is the header from `eth_getBlockByNumber`, `transaction` is from
+//!   `eth_getTransactionByHash` and `receipt` from `eth_getTransactionReceipt`.
+//! * `rawTransaction` is from `eth_getRawTransactionByHash`. It prices the L1 data, and without
+//!   it the gas used comes out short of Nitro's by the poster's share.
+//! * `prestate` is `debug_traceTransaction` with `prestateTracer`, and `stateDiff` the same with
+//!   `diffMode` set. The prestate includes the ArbOS state account's slots, so the replay runs
+//!   against the chain's real ArbOS state, at the version it holds.
+//! * `arbosVersion` is optional, and only used when the prestate holds no ArbOS version.
+//!
+//! Nitro's state root commits to the whole chain, which a trace does not carry, so roots cannot
+//! be compared. A differing root comes down to a differing account, so the accounts in the state
+//! diff are compared instead, field by field and slot by slot.
+
+use std::{collections::BTreeMap, convert::Infallible, fmt};
+
+use revm::{
+    DatabaseRef, ExecuteCommitEvm, Journal,
+    context::{BlockEnv, TxEnv, result::ExecutionResult},
+    context_interface::transaction::{AccessList, AccessListItem},
+    database::{CacheDB, EmptyDBTyped},
+    handler::instructions::EthInstructions,
+    inspector::NoOpInspector,
+    interpreter::interpreter::EthInterpreter,
+    primitives::{Address, B256, Bytes, KECCAK_EMPTY, Log, TxKind, U256},
+};
+use serde_json::Value;
+
+use crate::{
+    ArbitrumContext, ArbitrumEvm, ArbitrumTransaction,
+    config::ArbitrumConfig,
+    instructions::arbitrum_instructions,
+    local_context::ArbitrumLocalContext,
+    overrides::{AccountOverride, StateOverride, StorageOverride},
+    precompiles::ArbitrumPrecompileProvider,
+};
+
+type ReplayDb = CacheDB<EmptyDBTyped<Infallible>>;
+type ReplayContext = ArbitrumContext<ReplayDb>;
+type ReplayEvm = ArbitrumEvm<
+    ReplayContext,
+    NoOpInspector,
+    ArbitrumPrecompileProvider<ReplayContext>,
+    EthInstructions<EthInterpreter, ReplayContext>,
+>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TraceError {
+    #[error("malformed trace JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid trace: {0}")]
+    Invalid(String),
+}
+
+/// An account as `prestateTracer` writes it, with the fields it left out unset.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TraceAccount {
+    pub balance: Option<U256>,
+    pub nonce: Option<u64>,
+    pub code: Option<Bytes>,
+    pub storage: BTreeMap<U256, U256>,
+}
+
+/// The accounts a transaction changed, before and after, as `prestateTracer` writes them in
+/// `diffMode`: `post` holds only the fields that changed, leaves out slots set to zero, and
+/// leaves out deleted accounts altogether.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    pub pre: BTreeMap<Address, TraceAccount>,
+    pub post: BTreeMap<Address, TraceAccount>,
+}
+
+impl StateDiff {
+    /// The changed accounts as they should be after the transaction, with every field and slot
+    /// the transaction changed set, zeroes included.
+    pub fn expected_post(&self) -> BTreeMap<Address, TraceAccount> {
+        let mut accounts = self.post.clone();
+        for (address, pre) in &self.pre {
+            let account = accounts.entry(*address).or_insert_with(|| TraceAccount {
+                balance: Some(U256::ZERO),
+                nonce: Some(0),
+                code: Some(Bytes::new()),
+                storage: BTreeMap::new(),
+            });
+            for slot in pre.storage.keys() {
+                account.storage.entry(*slot).or_insert(U256::ZERO);
+            }
+        }
+        accounts
+    }
+}
+
+/// A transaction executed by Nitro, with the state it ran against and its outcome.
+#[derive(Clone, Debug)]
+pub struct NitroTrace {
+    /// ArbOS version to run under when the prestate holds none.
+    pub arbos_version: Option<u16>,
+    pub block: BlockEnv,
+    pub transaction: ArbitrumTransaction,
+    pub prestate: BTreeMap<Address, TraceAccount>,
+    pub state_diff: StateDiff,
+    pub success: bool,
+    pub gas_used: u64,
+    pub logs: Vec<Log>,
+}
+
+impl NitroTrace {
+    /// Parse a trace in the format described in the [module documentation](self).
+    pub fn from_json(json: &str) -> Result<Self, TraceError> {
+        let trace: Value = serde_json::from_str(json)?;
+        let block = field(&trace, "block")?;
+        let diff = field(&trace, "stateDiff")?;
+        let receipt = field(&trace, "receipt")?;
+
+        let tx = transaction(field(&trace, "transaction")?)?;
+        let transaction = match trace.get("rawTransaction") {
+            Some(raw) => ArbitrumTransaction::new_with_enveloped(tx, bytes(raw)?),
+            None => ArbitrumTransaction::new(tx),
+        };
+
+        Ok(Self {
+            arbos_version: optional(&trace, "arbosVersion", |value| {
+                u16::try_from(u64_quantity(value)?)
+                    .map_err(|_| invalid("`arbosVersion` is out of range"))
+            })?,
+            block: BlockEnv {
+                number: quantity(field(block, "number")?)?,
+                timestamp: quantity(field(block, "timestamp")?)?,
+                beneficiary: optional(block, "miner", address)?.unwrap_or_default(),
+                basefee: optional(block, "baseFeePerGas", u64_quantity)?.unwrap_or_default(),
+                gas_limit: optional(block, "gasLimit", u64_quantity)?.unwrap_or(u64::MAX),
+                difficulty: optional(block, "difficulty", quantity)?.unwrap_or(U256::from(1)),
+                prevrandao: Some(B256::from(U256::from(1))),
+                ..Default::default()
+            },
+            transaction,
+            prestate: accounts(field(&trace, "prestate")?)?,
+            state_diff: StateDiff {
+                pre: accounts(field(diff, "pre")?)?,
+                post: accounts(field(diff, "post")?)?,
+            },
+            success: u64_quantity(field(receipt, "status")?)? == 1,
+            gas_used: u64_quantity(field(receipt, "gasUsed")?)?,
+            logs: array(receipt, "logs")?
+                .iter()
+                .map(log)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// A difference between the replay and Nitro's execution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The replay rejected a transaction Nitro included.
+    Rejected {
+        reason: String,
+    },
+    Status {
+        expected: bool,
+        actual: bool,
+    },
+    GasUsed {
+        expected: u64,
+        actual: u64,
+    },
+    LogCount {
+        expected: usize,
+        actual: usize,
+    },
+    Log {
+        index: usize,
+        expected: Log,
+        actual: Log,
+    },
+    Balance {
+        address: Address,
+        expected: U256,
+        actual: U256,
+    },
+    Nonce {
+        address: Address,
+        expected: u64,
+        actual: u64,
+    },
+    Code {
+        address: Address,
+        expected: Bytes,
+        actual: Bytes,
+    },
+    Storage {
+        address: Address,
+        slot: U256,
+        expected: U256,
+        actual: U256,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = |success: &bool| if *success { "success" } else { "failure" };
+        match self {
+            Self::Rejected { reason } => write!(f, "transaction rejected: {reason}"),
+            Self::Status { expected, actual } => {
+                write!(
+                    f,
+                    "status {}, expected {}",
+                    status(actual),
+                    status(expected)
+                )
+            }
+            Self::GasUsed { expected, actual } => {
+                write!(f, "gas used {actual}, expected {expected}")
+            }
+            Self::LogCount { expected, actual } => {
+                write!(f, "{actual} logs, expected {expected}")
+            }
+            Self::Log {
+                index,
+                expected,
+                actual,
+            } => {
+                write!(f, "log {index} is {actual:?}, expected {expected:?}")
+            }
+            Self::Balance {
+                address,
+                expected,
+                actual,
+            } => {
+                write!(f, "{address} balance {actual}, expected {expected}")
+            }
+            Self::Nonce {
+                address,
+                expected,
+                actual,
+            } => {
+                write!(f, "{address} nonce {actual}, expected {expected}")
+            }
+            Self::Code {
+                address,
+                expected,
+                actual,
+            } => {
+                write!(f, "{address} code {actual}, expected {expected}")
+            }
+            Self::Storage {
+                address,
+                slot,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "{address} slot {slot:#x} is {actual:#x}, expected {expected:#x}"
+                )
+            }
+        }
+    }
+}
+
+/// Every difference found by a replay, in the order status, gas, logs, then state by address.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MismatchReport {
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl MismatchReport {
+    /// Whether the replay matched Nitro's execution.
+    pub fn is_match(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for MismatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_match() {
+            return write!(f, "matches Nitro");
+        }
+        write!(f, "{} mismatches with Nitro:", self.mismatches.len())?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n  {mismatch}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Replay `trace` from its prestate and diff the outcome against Nitro's.
+pub fn replay(trace: &NitroTrace) -> MismatchReport {
+    let mut evm = replay_evm(trace);
+    let mut report = MismatchReport::default();
+    let result = match evm.transact_commit(trace.transaction.clone()) {
+        Ok(result) => result,
+        Err(err) => {
+            report.mismatches.push(Mismatch::Rejected {
+                reason: err.to_string(),
+            });
+            return report;
+        }
+    };
+
+    diff_result(trace, &result, &mut report.mismatches);
+    diff_state(
+        trace,
+        evm.0.ctx.journaled_state.db(),
+        &mut report.mismatches,
+    );
+    report
+}
+
+fn replay_evm(trace: &NitroTrace) -> ReplayEvm {
+    let mut cfg: ArbitrumConfig = ArbitrumConfig::default();
+    cfg.arbos_version_from_state = true;
+    if let Some(version) = trace.arbos_version {
+        cfg.arbos_version = version;
+    }
+    if let Some(chain_id) = trace.transaction.base.chain_id {
+        cfg.inner.chain_id = chain_id;
+    }
+
+    let context = ArbitrumContext {
+        journaled_state: Journal::new(CacheDB::new(EmptyDBTyped::default())),
+        block: trace.block.clone(),
+        cfg,
+        tx: ArbitrumTransaction::default(),
+        chain: (),
+        local: ArbitrumLocalContext::default(),
+        error: Ok(()),
+    };
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        NoOpInspector {},
+        arbitrum_instructions(),
+        ArbitrumPrecompileProvider::default(),
+    );
+
+    let prestate: StateOverride = trace
+        .prestate
+        .iter()
+        .map(|(address, account)| {
+            let storage = account.storage.iter().map(|(k, v)| (*k, *v)).collect();
+            let account = AccountOverride {
+                balance: account.balance,
+                nonce: account.nonce,
+                code: account.code.clone(),
+                storage: Some(StorageOverride::Diff(storage)),
+            };
+            (*address, account)
+        })
+        .collect();
+    let Ok(()) = evm.apply_state_overrides(&prestate);
+    evm
+}
+
+fn diff_result(trace: &NitroTrace, result: &ExecutionResult, mismatches: &mut Vec<Mismatch>) {
+    if result.is_success() != trace.success {
+        mismatches.push(Mismatch::Status {
+            expected: trace.success,
+            actual: result.is_success(),
+        });
+    }
+    if result.gas_used() != trace.gas_used {
+        mismatches.push(Mismatch::GasUsed {
+            expected: trace.gas_used,
+            actual: result.gas_used(),
+        });
+    }
+
+    let logs = result.logs();
+    if logs.len() != trace.logs.len() {
+        mismatches.push(Mismatch::LogCount {
+            expected: trace.logs.len(),
+            actual: logs.len(),
+        });
+    }
+    for (index, (expected, actual)) in trace.logs.iter().zip(logs).enumerate() {
+        if expected != actual {
+            mismatches.push(Mismatch::Log {
+                index,
+                expected: expected.clone(),
+                actual: actual.clone(),
+            });
+        }
+    }
+}
+
+fn diff_state(trace: &NitroTrace, db: &ReplayDb, mismatches: &mut Vec<Mismatch>) {
+    for (address, expected) in trace.state_diff.expected_post() {
+        let Ok(info) = db.basic_ref(address);
+        let info = info.unwrap_or_default();
+
+        if let Some(expected) = expected.balance
+            && info.balance != expected
+        {
+            mismatches.push(Mismatch::Balance {
+                address,
+                expected,
+                actual: info.balance,
+            });
+        }
+        if let Some(expected) = expected.nonce
+            && info.nonce != expected
+        {
+            mismatches.push(Mismatch::Nonce {
+                address,
+                expected,
+                actual: info.nonce,
+            });
+        }
+        if let Some(expected) = expected.code {
+            let actual = match info.code {
+                Some(code) => code.original_bytes(),
+                None if info.code_hash == KECCAK_EMPTY => Bytes::new(),
+                None => {
+                    let Ok(code) = db.code_by_hash_ref(info.code_hash);
+                    code.original_bytes()
+                }
+            };
+            if actual != expected {
+                mismatches.push(Mismatch::Code {
+                    address,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        for (slot, expected) in expected.storage {
+            let Ok(actual) = db.storage_ref(address, slot);
+            if actual != expected {
+                mismatches.push(Mismatch::Storage {
+                    address,
+                    slot,
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> TraceError {
+    TraceError::Invalid(message.into())
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, TraceError> {
+    value
+        .get(key)
+        .ok_or_else(|| invalid(format!("missing `{key}`")))
+}
+
+/// The field `key` parsed with `parse`, or `None` when it is missing or null.
+fn optional<T>(
+    value: &Value,
+    key: &str,
+    parse: impl FnOnce(&Value) -> Result<T, TraceError>,
+) -> Result<Option<T>, TraceError> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => parse(value).map(Some),
+    }
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> Result<&'a [Value], TraceError> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(&[]),
+        Some(Value::Array(items)) => Ok(items),
+        Some(_) => Err(invalid(format!("`{key}` is not an array"))),
+    }
+}
+
+fn string(value: &Value) -> Result<&str, TraceError> {
+    value
+        .as_str()
+        .ok_or_else(|| invalid(format!("{value} is not a string")))
+}
+
+/// A number written as a hex quantity, as RPC answers write them, or as a plain JSON number.
+fn quantity(value: &Value) -> Result<U256, TraceError> {
+    if let Some(number) = value.as_u64() {
+        return Ok(U256::from(number));
+    }
+    let string = string(value)?;
+    match string.strip_prefix("0x") {
+        Some(digits) => U256::from_str_radix(digits, 16),
+        None => U256::from_str_radix(string, 10),
+    }
+    .map_err(|err| invalid(format!("invalid number `{string}`: {err}")))
+}
+
+fn u64_quantity(value: &Value) -> Result<u64, TraceError> {
+    let number = quantity(value)?;
+    u64::try_from(number).map_err(|_| invalid(format!("{number} does not fit in 64 bits")))
+}
+
+fn u128_quantity(value: &Value) -> Result<u128, TraceError> {
+    let number = quantity(value)?;
+    u128::try_from(number).map_err(|_| invalid(format!("{number} does not fit in 128 bits")))
+}
+
+fn bytes(value: &Value) -> Result<Bytes, TraceError> {
+    let string = string(value)?;
+    string
+        .parse()
+        .map_err(|err| invalid(format!("invalid bytes `{string}`: {err}")))
+}
+
+fn address(value: &Value) -> Result<Address, TraceError> {
+    let string = string(value)?;
+    string
+        .parse()
+        .map_err(|err| invalid(format!("invalid address `{string}`: {err}")))
+}
+
+fn b256(value: &Value) -> Result<B256, TraceError> {
+    let string = string(value)?;
+    string
+        .parse()
+        .map_err(|err| invalid(format!("invalid hash `{string}`: {err}")))
+}
+
+fn accounts(value: &Value) -> Result<BTreeMap<Address, TraceAccount>, TraceError> {
+    let entries = value
+        .as_object()
+        .ok_or_else(|| invalid("accounts must be an object"))?;
+    let mut accounts = BTreeMap::new();
+    for (key, account) in entries {
+        let address = key
+            .parse()
+            .map_err(|err| invalid(format!("invalid address `{key}`: {err}")))?;
+        let mut storage = BTreeMap::new();
+        if let Some(slots) = account.get("storage").and_then(Value::as_object) {
+            for (slot, value) in slots {
+                storage.insert(quantity(&Value::from(slot.as_str()))?, quantity(value)?);
+            }
+        }
+        let account = TraceAccount {
+            balance: optional(account, "balance", quantity)?,
+            nonce: optional(account, "nonce", u64_quantity)?,
+            code: optional(account, "code", bytes)?,
+            storage,
+        };
+        accounts.insert(address, account);
+    }
+    Ok(accounts)
+}
+
+fn transaction(tx: &Value) -> Result<TxEnv, TraceError> {
+    let tx_type = optional(tx, "type", u64_quantity)?.unwrap_or_default();
+    let mut access_list = Vec::new();
+    for item in array(tx, "accessList")? {
+        access_list.push(AccessListItem {
+            address: address(field(item, "address")?)?,
+            storage_keys: array(item, "storageKeys")?
+                .iter()
+                .map(b256)
+                .collect::<Result<_, _>>()?,
+        });
+    }
+
+    // Dynamic fee transactions report the effective gas price, so their fee cap is used instead
+    let gas_price = match optional(tx, "maxFeePerGas", u128_quantity)? {
+        Some(max_fee) => max_fee,
+        None => u128_quantity(field(tx, "gasPrice")?)?,
+    };
+
+    Ok(TxEnv {
+        tx_type: u8::try_from(tx_type).map_err(|_| invalid("`type` is out of range"))?,
+        caller: address(field(tx, "from")?)?,
+        kind: match optional(tx, "to", address)? {
+            Some(to) => TxKind::Call(to),
+            None => TxKind::Create,
+        },
+        data: optional(tx, "input", bytes)?.unwrap_or_default(),
+        value: optional(tx, "value", quantity)?.unwrap_or_default(),
+        gas_limit: u64_quantity(field(tx, "gas")?)?,
+        gas_price,
+        gas_priority_fee: optional(tx, "maxPriorityFeePerGas", u128_quantity)?,
+        nonce: optional(tx, "nonce", u64_quantity)?.unwrap_or_default(),
+        chain_id: optional(tx, "chainId", u64_quantity)?,
+        access_list: AccessList(access_list),
+        ..Default::default()
+    })
+}
+
+fn log(value: &Value) -> Result<Log, TraceError> {
+    let topics = array(value, "topics")?
+        .iter()
+        .map(b256)
+        .collect::<Result<_, _>>()?;
+    Ok(Log::new_unchecked(
+        address(field(value, "address")?)?,
+        topics,
+        optional(value, "data", bytes)?.unwrap_or_default(),
+    ))
+}
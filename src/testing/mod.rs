@@ -0,0 +1,3 @@
+//! Tooling for checking this crate's execution against Nitro's.
+
+pub mod differential;
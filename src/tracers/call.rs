@@ -0,0 +1,465 @@
+//! Nitro's `callTracer`.
+
+use alloy_sol_types::{Panic, Revert, SolError};
+use revm::{
+    Inspector,
+    bytecode::opcode::OpCode,
+    context::{ContextTr, LocalContextTr, Transaction},
+    interpreter::{
+        CallInput, CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome,
+        InstructionResult, Interpreter, InterpreterResult, interpreter::EthInterpreter,
+        interpreter_types::Jumps,
+    },
+    primitives::{Address, B256, Bytes, Log, U256},
+};
+
+use super::{CallKind, JsonObject, big, bytes, callee, caller, quantity, string, write_array};
+use crate::{
+    ArbitrumContextTr,
+    inspector::{PrecompileInspector, StylusInspector},
+};
+
+/// Options of Nitro's `callTracer`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CallTracerConfig {
+    /// Only trace the transaction's top-level call, `onlyTopCall`.
+    pub only_top_call: bool,
+    /// Record the logs each call emitted, `withLog`.
+    pub with_log: bool,
+}
+
+/// A log emitted by a call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    /// Number of calls the emitting call had made when the log was emitted.
+    pub position: u64,
+}
+
+/// A call and the calls it made, as traced by [`CallTracer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallFrame {
+    pub kind: CallKind,
+    pub from: Address,
+    pub gas: u64,
+    pub gas_used: u64,
+    /// The account called, or the account created, unless its creation failed.
+    pub to: Option<Address>,
+    pub input: Bytes,
+    /// Returned data, only kept for reverts when the call failed.
+    pub output: Bytes,
+    /// The error the call failed with, as go-ethereum words it.
+    pub error: Option<String>,
+    /// The reason the call reverted with, decoded from `Error(string)` or `Panic(uint256)`.
+    pub revert_reason: Option<String>,
+    pub calls: Vec<CallFrame>,
+    /// Logs of the call, dropped when it or one of its callers failed.
+    pub logs: Vec<CallLog>,
+    /// Value sent, or passed on by `DELEGATECALL`. Unset for `STATICCALL`.
+    pub value: Option<U256>,
+}
+
+impl CallFrame {
+    /// The frame as Nitro's `callTracer` writes it.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out, self.gas_used);
+        out
+    }
+
+    fn write_json(&self, out: &mut String, gas_used: u64) {
+        let mut object = JsonObject::new(out);
+        object.field("from", &bytes(self.from));
+        object.field("gas", &quantity(self.gas));
+        object.field("gasUsed", &quantity(gas_used));
+        if let Some(to) = self.to {
+            object.field("to", &bytes(to));
+        }
+        object.field("input", &bytes(&self.input));
+        if !self.output.is_empty() {
+            object.field("output", &bytes(&self.output));
+        }
+        if let Some(error) = &self.error {
+            object.field("error", &string(error));
+        }
+        if let Some(reason) = &self.revert_reason {
+            object.field("revertReason", &string(reason));
+        }
+        if !self.calls.is_empty() {
+            write_array(object.key("calls"), &self.calls, |out, call| {
+                call.write_json(out, call.gas_used)
+            });
+        }
+        if !self.logs.is_empty() {
+            write_array(object.key("logs"), &self.logs, |out, log| {
+                let mut object = JsonObject::new(out);
+                object.field("address", &bytes(log.address));
+                write_array(object.key("topics"), &log.topics, |out, topic| {
+                    out.push_str(&bytes(topic))
+                });
+                object.field("data", &bytes(&log.data));
+                object.field("position", &quantity(log.position));
+                object.finish();
+            });
+        }
+        if let Some(value) = self.value {
+            object.field("value", &big(value));
+        }
+        object.field("type", &string(self.kind.name()));
+        object.finish();
+    }
+
+    /// Record how the call ended, as go-ethereum's `callTracer` does.
+    fn exit(&mut self, result: &InterpreterResult, created: Option<Address>, step: Step) {
+        self.gas_used = self.gas.saturating_sub(result.gas.remaining());
+        let is_create = matches!(self.kind, CallKind::Create | CallKind::Create2);
+        if result.result.is_ok() {
+            self.output = result.output.clone();
+            if is_create {
+                self.to = created;
+            }
+            return;
+        }
+
+        self.error = Some(error_message(result.result, step));
+        if is_create {
+            self.to = None;
+        }
+        if result.result == InstructionResult::Revert && !result.output.is_empty() {
+            self.output = result.output.clone();
+            self.revert_reason = revert_reason(&result.output);
+        }
+    }
+
+    fn clear_failed_logs(&mut self, caller_failed: bool) {
+        let failed = caller_failed || self.error.is_some();
+        if failed {
+            self.logs.clear();
+        }
+        for call in &mut self.calls {
+            call.clear_failed_logs(failed);
+        }
+    }
+}
+
+/// The last opcode run and the stack it ran on, to word the errors it fails with.
+#[derive(Clone, Copy, Debug, Default)]
+struct Step {
+    opcode: u8,
+    stack_len: usize,
+}
+
+/// go-ethereum's message for the error a call failed with.
+fn error_message(result: InstructionResult, step: Step) -> String {
+    let opcode = OpCode::new(step.opcode);
+    match result {
+        InstructionResult::Revert => "execution reverted".into(),
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG => "out of gas".into(),
+        InstructionResult::InvalidFEOpcode => "invalid opcode: INVALID".into(),
+        InstructionResult::OpcodeNotFound => match opcode {
+            Some(opcode) => format!("invalid opcode: {}", opcode.as_str()),
+            None => format!("invalid opcode: opcode {:#x} not defined", step.opcode),
+        },
+        InstructionResult::InvalidJump => "invalid jump destination".into(),
+        InstructionResult::StackUnderflow => format!(
+            "stack underflow ({} <=> {})",
+            step.stack_len,
+            opcode.map_or(0, |opcode| opcode.inputs())
+        ),
+        InstructionResult::StackOverflow => format!(
+            "stack limit reached {} ({})",
+            step.stack_len,
+            opcode.map_or(1024, |opcode| {
+                1024 + opcode.inputs() as usize - opcode.outputs() as usize
+            })
+        ),
+        InstructionResult::OutOfOffset => "return data out of bounds".into(),
+        InstructionResult::CallTooDeep => "max call depth exceeded".into(),
+        InstructionResult::OutOfFunds => "insufficient balance for transfer".into(),
+        InstructionResult::CreateCollision => "contract address collision".into(),
+        InstructionResult::NonceOverflow => "nonce uint64 overflow".into(),
+        InstructionResult::CreateContractSizeLimit => "max code size exceeded".into(),
+        InstructionResult::CreateInitCodeSizeLimit => "max initcode size exceeded".into(),
+        InstructionResult::CreateContractStartingWithEF => {
+            "invalid code: must not begin with 0xef".into()
+        }
+        InstructionResult::StateChangeDuringStaticCall
+        | InstructionResult::CallNotAllowedInsideStatic => "write protection".into(),
+        other => format!("{other:?}"),
+    }
+}
+
+/// The reason in revert data, decoded as go-ethereum's `abi.UnpackRevert` does.
+fn revert_reason(output: &[u8]) -> Option<String> {
+    if let Ok(revert) = Revert::abi_decode(output) {
+        return Some(revert.reason);
+    }
+    let code = Panic::abi_decode(output).ok()?.code;
+    let reason = match u64::try_from(code) {
+        Ok(0x00) => "generic panic",
+        Ok(0x01) => "assert(false)",
+        Ok(0x11) => "arithmetic underflow or overflow",
+        Ok(0x12) => "division or modulo by zero",
+        Ok(0x21) => "enum overflow",
+        Ok(0x22) => "invalid encoded storage byte array accessed",
+        Ok(0x31) => "out-of-bounds array access; popping on an empty array",
+        Ok(0x32) => "out-of-bounds access of an array or bytesN",
+        Ok(0x41) => "out of memory",
+        Ok(0x51) => "uninitialized function",
+        _ => return Some(format!("unknown panic code: 0x{code:x}")),
+    };
+    Some(reason.into())
+}
+
+/// An inspector recording the calls of a transaction in the format of Nitro's `callTracer`.
+///
+/// The top-level call is given the transaction's gas limit. Its gas used is only known once the
+/// transaction has been charged for, so it is passed to [`CallTracer::to_json`].
+#[derive(Clone, Debug, Default)]
+pub struct CallTracer {
+    config: CallTracerConfig,
+    /// Calls that have not returned yet.
+    stack: Vec<CallFrame>,
+    depth: usize,
+    step: Step,
+    trace: Option<CallFrame>,
+}
+
+impl CallTracer {
+    pub fn new(config: CallTracerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// The top-level call of the last transaction traced, once it has returned.
+    pub fn trace(&self) -> Option<&CallFrame> {
+        self.trace.as_ref()
+    }
+
+    /// The trace as Nitro's `callTracer` writes it, for a transaction that used `gas_used` gas.
+    pub fn to_json(&self, gas_used: u64) -> Option<String> {
+        let trace = self.trace.as_ref()?;
+        let mut out = String::new();
+        trace.write_json(&mut out, gas_used);
+        Some(out)
+    }
+
+    fn enter(&mut self, mut frame: CallFrame, tx_gas_limit: u64) {
+        if self.depth == 0 {
+            self.stack.clear();
+            self.trace = None;
+            frame.gas = tx_gas_limit;
+        }
+        if self.depth == 0 || !self.config.only_top_call {
+            self.stack.push(frame);
+        }
+        self.depth += 1;
+    }
+
+    fn exit(&mut self, result: &InterpreterResult, created: Option<Address>) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth > 0 && self.config.only_top_call {
+            return;
+        }
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        frame.exit(result, created, self.step);
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => {
+                frame.clear_failed_logs(false);
+                self.trace = Some(frame);
+            }
+        }
+    }
+}
+
+impl<CTX> StylusInspector<CTX> for CallTracer {}
+
+impl<CTX> PrecompileInspector<CTX> for CallTracer {}
+
+impl<CTX: ArbitrumContextTr> Inspector<CTX, EthInterpreter> for CallTracer {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        self.step = Step {
+            opcode: interp.bytecode.opcode(),
+            stack_len: interp.stack.len(),
+        };
+    }
+
+    fn log(&mut self, _context: &mut CTX, log: Log) {
+        if !self.config.with_log || (self.config.only_top_call && self.depth > 1) {
+            return;
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.logs.push(CallLog {
+                address: log.address,
+                topics: log.data.topics().to_vec(),
+                data: log.data.data.clone(),
+                position: frame.calls.len() as u64,
+            });
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let input = match &inputs.input {
+            CallInput::SharedBuffer(range) => context
+                .local()
+                .shared_memory_buffer_slice(range.clone())
+                .map(|slice| Bytes::copy_from_slice(&slice))
+                .unwrap_or_default(),
+            CallInput::Bytes(bytes) => bytes.clone(),
+        };
+        let value = (inputs.scheme != CallScheme::StaticCall).then(|| inputs.call_value());
+        let frame = CallFrame {
+            kind: CallKind::of_call(inputs.scheme),
+            from: caller(inputs),
+            gas: inputs.gas_limit,
+            gas_used: 0,
+            to: Some(callee(inputs)),
+            input,
+            output: Bytes::new(),
+            error: None,
+            revert_reason: None,
+            calls: Vec::new(),
+            logs: Vec::new(),
+            value,
+        };
+        self.enter(frame, context.tx().gas_limit());
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.exit(&outcome.result, None);
+    }
+
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let frame = CallFrame {
+            kind: CallKind::of_create(inputs.scheme),
+            from: inputs.caller,
+            gas: inputs.gas_limit,
+            gas_used: 0,
+            to: None,
+            input: inputs.init_code.clone(),
+            output: Bytes::new(),
+            error: None,
+            revert_reason: None,
+            calls: Vec::new(),
+            logs: Vec::new(),
+            value: Some(inputs.value),
+        };
+        self.enter(frame, context.tx().gas_limit());
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.exit(&outcome.result, outcome.address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revert_reasons_are_decoded() {
+        let revert = Revert {
+            reason: "nope".into(),
+        }
+        .abi_encode();
+        assert_eq!(revert_reason(&revert).as_deref(), Some("nope"));
+
+        let panic = Panic {
+            code: U256::from(0x11),
+        }
+        .abi_encode();
+        assert_eq!(
+            revert_reason(&panic).as_deref(),
+            Some("arithmetic underflow or overflow")
+        );
+
+        let panic = Panic {
+            code: U256::from(0x99),
+        }
+        .abi_encode();
+        assert_eq!(
+            revert_reason(&panic).as_deref(),
+            Some("unknown panic code: 0x99")
+        );
+
+        assert_eq!(revert_reason(&[0xde, 0xad, 0xbe, 0xef]), None);
+    }
+
+    #[test]
+    fn errors_are_worded_as_go_ethereum_does() {
+        let step = Step {
+            opcode: 0x01,
+            stack_len: 1,
+        };
+        assert_eq!(
+            error_message(InstructionResult::Revert, step),
+            "execution reverted"
+        );
+        assert_eq!(
+            error_message(InstructionResult::MemoryOOG, step),
+            "out of gas"
+        );
+        assert_eq!(
+            error_message(InstructionResult::StackUnderflow, step),
+            "stack underflow (1 <=> 2)"
+        );
+        let step = Step {
+            opcode: 0x0c,
+            stack_len: 0,
+        };
+        assert_eq!(
+            error_message(InstructionResult::OpcodeNotFound, step),
+            "invalid opcode: opcode 0xc not defined"
+        );
+    }
+
+    #[test]
+    fn failed_calls_drop_their_logs() {
+        let log = CallLog {
+            address: Address::ZERO,
+            topics: Vec::new(),
+            data: Bytes::new(),
+            position: 0,
+        };
+        let frame = |error: Option<&str>, calls| CallFrame {
+            kind: CallKind::Call,
+            from: Address::ZERO,
+            gas: 0,
+            gas_used: 0,
+            to: None,
+            input: Bytes::new(),
+            output: Bytes::new(),
+            error: error.map(Into::into),
+            revert_reason: None,
+            calls,
+            logs: vec![log.clone()],
+            value: None,
+        };
+        let mut trace = frame(
+            None,
+            vec![frame(Some("execution reverted"), vec![frame(None, vec![])])],
+        );
+        trace.clear_failed_logs(false);
+        assert_eq!(trace.logs.len(), 1);
+        assert!(trace.calls[0].logs.is_empty());
+        assert!(trace.calls[0].calls[0].logs.is_empty());
+    }
+}
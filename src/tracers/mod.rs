@@ -0,0 +1,212 @@
+//! Traces in the JSON formats of Nitro's native tracers.
+//!
+//! Each tracer is an inspector that records a transaction as it runs and writes it out the way
+//! Nitro's `debug_traceTransaction` does, field for field and in the same order, so consumers of
+//! Nitro traces can read them unchanged:
+//!
+//! * [`CallTracer`], Nitro's `callTracer`: the tree of calls with their gas, outputs, errors and
+//!   optionally their logs.
+//! * [`PrestateTracer`], Nitro's `prestateTracer` in its default mode: the accounts and storage
+//!   slots the transaction touched, as they were before it ran.
+//! * [`StylusTracer`], Nitro's `stylusTracer`: the hostios of Stylus programs with the ink left
+//!   before and after each, nesting the hostios of the calls they made.
+//!
+//! Nitro's `callTracer` also lists the balance transfers ArbOS makes outside of EVM frames, such
+//! as fee payments and deposits, under `beforeEVMTransfers` and `afterEVMTransfers`. Those are
+//! not reported to inspectors, so they are left out.
+
+mod call;
+mod prestate;
+mod stylus;
+
+pub use call::{CallFrame, CallLog, CallTracer, CallTracerConfig};
+pub use prestate::PrestateTracer;
+pub use stylus::{HostioTraceInfo, StylusTracer};
+
+use revm::{
+    context::CreateScheme,
+    interpreter::{CallInputs, CallScheme},
+    primitives::{Address, U256, hex},
+};
+
+/// Kind of a call frame, named as go-ethereum names its opcode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallKind {
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+    Create,
+    Create2,
+}
+
+impl CallKind {
+    /// Name of the opcode, e.g. `DELEGATECALL`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CallKind::Call => "CALL",
+            CallKind::CallCode => "CALLCODE",
+            CallKind::DelegateCall => "DELEGATECALL",
+            CallKind::StaticCall => "STATICCALL",
+            CallKind::Create => "CREATE",
+            CallKind::Create2 => "CREATE2",
+        }
+    }
+
+    fn of_call(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call => CallKind::Call,
+            CallScheme::CallCode => CallKind::CallCode,
+            CallScheme::DelegateCall => CallKind::DelegateCall,
+            CallScheme::StaticCall => CallKind::StaticCall,
+        }
+    }
+
+    fn of_create(scheme: CreateScheme) -> Self {
+        match scheme {
+            CreateScheme::Create2 { .. } => CallKind::Create2,
+            _ => CallKind::Create,
+        }
+    }
+}
+
+/// The account go-ethereum reports a call to: the code's address for `DELEGATECALL` and
+/// `CALLCODE`, which run it on behalf of the caller.
+fn callee(inputs: &CallInputs) -> Address {
+    match inputs.scheme {
+        CallScheme::DelegateCall | CallScheme::CallCode => inputs.bytecode_address,
+        CallScheme::Call | CallScheme::StaticCall => inputs.target_address,
+    }
+}
+
+/// The account go-ethereum reports a call from: the contract making a `DELEGATECALL` rather
+/// than the sender it passes on.
+fn caller(inputs: &CallInputs) -> Address {
+    match inputs.scheme {
+        CallScheme::DelegateCall => inputs.target_address,
+        _ => inputs.caller,
+    }
+}
+
+/// Writes a JSON object one field at a time, in the order they are given.
+struct JsonObject<'a> {
+    out: &'a mut String,
+    empty: bool,
+}
+
+impl<'a> JsonObject<'a> {
+    fn new(out: &'a mut String) -> Self {
+        out.push('{');
+        Self { out, empty: true }
+    }
+
+    /// Start the field `key`, returning the buffer its value is to be written to.
+    fn key(&mut self, key: &str) -> &mut String {
+        if !self.empty {
+            self.out.push(',');
+        }
+        self.empty = false;
+        self.out.push('"');
+        self.out.push_str(key);
+        self.out.push_str("\":");
+        self.out
+    }
+
+    fn field(&mut self, key: &str, value: &str) {
+        self.key(key).push_str(value);
+    }
+
+    fn finish(self) {
+        self.out.push('}');
+    }
+}
+
+/// Write `items` as a JSON array.
+fn write_array<T>(out: &mut String, items: &[T], mut write: impl FnMut(&mut String, &T)) {
+    out.push('[');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write(out, item);
+    }
+    out.push(']');
+}
+
+/// A JSON string escaped as Go's `encoding/json` escapes it, including the HTML characters.
+fn string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Bytes in 0x-prefixed hex, which is also how addresses and hashes are written.
+fn prefixed_hex(value: impl AsRef<[u8]>) -> String {
+    format!("0x{}", hex::encode(value))
+}
+
+/// Bytes as a 0x-prefixed hex string.
+fn bytes(value: impl AsRef<[u8]>) -> String {
+    format!("\"{}\"", prefixed_hex(value))
+}
+
+/// A number as a 0x-prefixed hex string without leading zeroes.
+fn quantity(value: u64) -> String {
+    format!("\"{value:#x}\"")
+}
+
+/// A big number as a 0x-prefixed hex string without leading zeroes.
+fn big(value: U256) -> String {
+    format!("\"0x{value:x}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_escaped_as_go_does() {
+        assert_eq!(string("plain"), r#""plain""#);
+        assert_eq!(string("a\"b\\c\n"), r#""a\"b\\c\n""#);
+        assert_eq!(string("<&>"), r#""\u003c\u0026\u003e""#);
+        assert_eq!(string("\u{1}\u{8}"), r#""\u0001\b""#);
+    }
+
+    #[test]
+    fn numbers_have_no_leading_zeroes() {
+        assert_eq!(quantity(0), r#""0x0""#);
+        assert_eq!(quantity(21_000), r#""0x5208""#);
+        assert_eq!(big(U256::ZERO), r#""0x0""#);
+        assert_eq!(big(U256::from(255)), r#""0xff""#);
+        assert_eq!(bytes(b""), r#""0x""#);
+    }
+
+    #[test]
+    fn objects_keep_their_field_order() {
+        let mut out = String::new();
+        let mut object = JsonObject::new(&mut out);
+        object.field("b", "1");
+        write_array(object.key("a"), &[1, 2], |out, n| {
+            out.push_str(&n.to_string())
+        });
+        object.finish();
+        assert_eq!(out, r#"{"b":1,"a":[1,2]}"#);
+    }
+}
@@ -0,0 +1,189 @@
+//! Nitro's `prestateTracer`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use arbutil::evm::api::EvmApiMethod;
+use revm::{
+    DatabaseRef, Inspector,
+    bytecode::opcode,
+    context::{Block, ContextTr, Transaction},
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+        interpreter::EthInterpreter, interpreter_types::Jumps,
+    },
+    primitives::{Address, B256, Bytes, KECCAK_EMPTY, U256},
+};
+
+use super::{JsonObject, big, bytes, prefixed_hex};
+use crate::{
+    ArbitrumContextTr,
+    inspector::{PrecompileInspector, StylusHostio, StylusInspector},
+};
+
+/// An inspector recording the accounts and storage slots a transaction touches, to write their
+/// state before it ran in the format of Nitro's `prestateTracer`.
+///
+/// The sender, the recipient and the block's coinbase are touched by every transaction, as are
+/// the accounts of every call and creation. Within frames, go-ethereum's rules are followed:
+/// `SLOAD` and `SSTORE` touch a slot of the running account, while `BALANCE`, `EXTCODESIZE`,
+/// `EXTCODECOPY`, `EXTCODEHASH` and `SELFDESTRUCT` touch the account on top of the stack. The
+/// storage and account queries of Stylus programs touch the same.
+#[derive(Clone, Debug, Default)]
+pub struct PrestateTracer {
+    accounts: BTreeMap<Address, BTreeSet<U256>>,
+    /// Accounts created by the transaction, left out if they were empty before it.
+    created: BTreeSet<Address>,
+    depth: usize,
+}
+
+impl PrestateTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The accounts touched by the last transaction traced, with the storage slots touched.
+    pub fn accounts(&self) -> &BTreeMap<Address, BTreeSet<U256>> {
+        &self.accounts
+    }
+
+    /// The touched accounts as Nitro's `prestateTracer` writes them, read from `pre`, the state
+    /// before the transaction ran.
+    pub fn to_json<DB: DatabaseRef>(&self, pre: &DB) -> Result<String, DB::Error> {
+        let mut out = String::new();
+        let mut object = JsonObject::new(&mut out);
+        for (address, slots) in &self.accounts {
+            let info = pre.basic_ref(*address)?.unwrap_or_default();
+            let code = match &info.code {
+                Some(code) => code.original_bytes(),
+                None if info.code_hash == KECCAK_EMPTY => Bytes::new(),
+                None => pre.code_by_hash_ref(info.code_hash)?.original_bytes(),
+            };
+            let is_empty = info.balance.is_zero() && info.nonce == 0 && code.is_empty();
+            if is_empty && self.created.contains(address) {
+                continue;
+            }
+
+            let mut account = JsonObject::new(object.key(&prefixed_hex(address)));
+            account.field("balance", &big(info.balance));
+            if !code.is_empty() {
+                account.field("code", &bytes(&code));
+                account.field("codeHash", &bytes(info.code_hash));
+            }
+            if info.nonce != 0 {
+                account.field("nonce", &info.nonce.to_string());
+            }
+            if !slots.is_empty() {
+                let mut storage = JsonObject::new(account.key("storage"));
+                for slot in slots {
+                    let value = pre.storage_ref(*address, *slot)?;
+                    storage.field(
+                        &prefixed_hex(slot.to_be_bytes::<32>()),
+                        &bytes(value.to_be_bytes::<32>()),
+                    );
+                }
+                storage.finish();
+            }
+            account.finish();
+        }
+        object.finish();
+        Ok(out)
+    }
+
+    fn touch(&mut self, address: Address) {
+        self.accounts.entry(address).or_default();
+    }
+
+    fn touch_slot(&mut self, address: Address, slot: U256) {
+        self.accounts.entry(address).or_default().insert(slot);
+    }
+
+    fn enter<CTX: ArbitrumContextTr>(&mut self, context: &mut CTX) {
+        if self.depth == 0 {
+            self.accounts.clear();
+            self.created.clear();
+            self.touch(context.tx().caller());
+            self.touch(context.block().beneficiary());
+        }
+        self.depth += 1;
+    }
+}
+
+impl<CTX> StylusInspector<CTX> for PrestateTracer {
+    fn hostio(&mut self, _context: &mut CTX, hostio: &StylusHostio<'_>) {
+        match hostio.method {
+            EvmApiMethod::GetBytes32 => {
+                if let Some(slot) = hostio.input.get(..32) {
+                    self.touch_slot(hostio.address, U256::from_be_slice(slot));
+                }
+            }
+            // Gas left, then the slots and their new values
+            EvmApiMethod::SetTrieSlots => {
+                for entry in hostio.input.get(8..).unwrap_or_default().chunks_exact(64) {
+                    self.touch_slot(hostio.address, U256::from_be_slice(&entry[..32]));
+                }
+            }
+            EvmApiMethod::AccountBalance
+            | EvmApiMethod::AccountCode
+            | EvmApiMethod::AccountCodeHash => {
+                if let Some(address) = hostio.input.get(..20) {
+                    self.touch(Address::from_slice(address));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<CTX> PrecompileInspector<CTX> for PrestateTracer {}
+
+impl<CTX: ArbitrumContextTr> Inspector<CTX, EthInterpreter> for PrestateTracer {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some(top) = interp.stack.data().last().copied() else {
+            return;
+        };
+        match interp.bytecode.opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                self.touch_slot(interp.input.target_address, top);
+            }
+            opcode::BALANCE
+            | opcode::EXTCODESIZE
+            | opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::SELFDESTRUCT => {
+                self.touch(Address::from_word(B256::from(top.to_be_bytes::<32>())));
+            }
+            _ => {}
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.enter(context);
+        self.touch(inputs.caller);
+        self.touch(inputs.target_address);
+        self.touch(inputs.bytecode_address);
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, _outcome: &mut CallOutcome) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.enter(context);
+        self.touch(inputs.caller);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.depth = self.depth.saturating_sub(1);
+        if let Some(address) = outcome.address {
+            self.touch(address);
+            self.created.insert(address);
+        }
+    }
+}
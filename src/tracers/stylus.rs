@@ -0,0 +1,266 @@
+//! Nitro's `stylusTracer`.
+
+use std::mem;
+
+use arbutil::evm::api::EvmApiMethod;
+use revm::{
+    Inspector,
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, interpreter::EthInterpreter,
+    },
+    primitives::{Address, Bytes},
+};
+
+use super::{CallKind, JsonObject, bytes, callee, string, write_array};
+use crate::{
+    ArbitrumContextTr,
+    inspector::{PrecompileInspector, StylusHostio, StylusInspector},
+};
+
+/// A hostio called by a Stylus program, or a call made from one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HostioTraceInfo {
+    /// Name of the hostio, e.g. `storage_load_bytes32`, or of the call's opcode prefixed with
+    /// `evm_`, e.g. `evm_call`.
+    pub name: String,
+    /// Arguments of the hostio, as encoded by the program's runtime.
+    pub args: Bytes,
+    /// Outputs of the hostio, as encoded by the program's runtime.
+    pub outs: Bytes,
+    /// Ink left before the hostio.
+    pub start_ink: u64,
+    /// Ink left after the hostio.
+    pub end_ink: u64,
+    /// The account called, for calls.
+    pub address: Option<Address>,
+    /// The hostios of the account called, for calls.
+    pub steps: Option<Vec<HostioTraceInfo>>,
+}
+
+impl HostioTraceInfo {
+    /// Decode a hostio captured by the program's runtime: the ink left before and after it,
+    /// the lengths of its name, arguments and outputs as 16-bit integers, then each of those.
+    fn decode(capture: &[u8]) -> Option<Self> {
+        let (start_ink, rest) = capture.split_first_chunk::<8>()?;
+        let (end_ink, rest) = rest.split_first_chunk::<8>()?;
+        let (name_len, rest) = rest.split_first_chunk::<2>()?;
+        let (args_len, rest) = rest.split_first_chunk::<2>()?;
+        let (outs_len, rest) = rest.split_first_chunk::<2>()?;
+        let (name, rest) = rest.split_at_checked(u16::from_be_bytes(*name_len).into())?;
+        let (args, rest) = rest.split_at_checked(u16::from_be_bytes(*args_len).into())?;
+        let outs = rest.get(..usize::from(u16::from_be_bytes(*outs_len)))?;
+        Some(Self {
+            name: String::from_utf8_lossy(name).into_owned(),
+            args: Bytes::copy_from_slice(args),
+            outs: Bytes::copy_from_slice(outs),
+            start_ink: u64::from_be_bytes(*start_ink),
+            end_ink: u64::from_be_bytes(*end_ink),
+            address: None,
+            steps: None,
+        })
+    }
+
+    fn write_json(&self, out: &mut String) {
+        let mut object = JsonObject::new(out);
+        object.field("name", &string(&self.name));
+        object.field("args", &bytes(&self.args));
+        object.field("outs", &bytes(&self.outs));
+        object.field("startInk", &self.start_ink.to_string());
+        object.field("endInk", &self.end_ink.to_string());
+        if let Some(address) = self.address {
+            object.field("address", &bytes(address));
+        }
+        if let Some(steps) = &self.steps {
+            write_array(object.key("steps"), steps, |out, step| step.write_json(out));
+        }
+        object.finish();
+    }
+}
+
+/// An inspector recording the hostios of Stylus programs in the format of Nitro's
+/// `stylusTracer`.
+///
+/// Hostios are taken from the traces Stylus runtimes capture of them, which are reported to
+/// [`StylusInspector::hostio`] as [`EvmApiMethod::CaptureHostIO`] requests. Calls made below the
+/// top-level call are recorded as they are entered, and hold the hostios run in them as steps.
+#[derive(Clone, Debug, Default)]
+pub struct StylusTracer {
+    /// Hostios of the running call.
+    open: Vec<HostioTraceInfo>,
+    /// Hostios of the calls below the running one.
+    stack: Vec<Vec<HostioTraceInfo>>,
+    depth: usize,
+}
+
+impl StylusTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The hostios of the last transaction traced.
+    pub fn hostios(&self) -> &[HostioTraceInfo] {
+        &self.open
+    }
+
+    /// The trace as Nitro's `stylusTracer` writes it.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        write_array(&mut out, &self.open, |out, hostio| hostio.write_json(out));
+        out
+    }
+
+    fn enter(&mut self, kind: CallKind, address: Option<Address>) {
+        if self.depth == 0 {
+            self.open.clear();
+            self.stack.clear();
+        } else {
+            self.open.push(HostioTraceInfo {
+                name: format!("evm_{}", kind.name().to_lowercase()),
+                address,
+                ..Default::default()
+            });
+            self.stack.push(mem::take(&mut self.open));
+        }
+        self.depth += 1;
+    }
+
+    fn exit(&mut self, created: Option<Address>) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 {
+            return;
+        }
+        let Some(caller) = self.stack.pop() else {
+            return;
+        };
+        let steps = mem::replace(&mut self.open, caller);
+        if let Some(call) = self.open.last_mut() {
+            call.steps = Some(steps);
+            if created.is_some() {
+                call.address = created;
+            }
+        }
+    }
+}
+
+impl<CTX> StylusInspector<CTX> for StylusTracer {
+    fn hostio(&mut self, _context: &mut CTX, hostio: &StylusHostio<'_>) {
+        if matches!(hostio.method, EvmApiMethod::CaptureHostIO)
+            && let Some(info) = HostioTraceInfo::decode(hostio.input)
+        {
+            self.open.push(info);
+        }
+    }
+}
+
+impl<CTX> PrecompileInspector<CTX> for StylusTracer {}
+
+impl<CTX: ArbitrumContextTr> Inspector<CTX, EthInterpreter> for StylusTracer {
+    fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.enter(CallKind::of_call(inputs.scheme), Some(callee(inputs)));
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, _outcome: &mut CallOutcome) {
+        self.exit(None);
+    }
+
+    fn create(&mut self, _context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.enter(CallKind::of_create(inputs.scheme), None);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.exit(outcome.address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(name: &str, args: &[u8], outs: &[u8], start_ink: u64, end_ink: u64) -> Vec<u8> {
+        let mut capture = Vec::new();
+        capture.extend(start_ink.to_be_bytes());
+        capture.extend(end_ink.to_be_bytes());
+        capture.extend((name.len() as u16).to_be_bytes());
+        capture.extend((args.len() as u16).to_be_bytes());
+        capture.extend((outs.len() as u16).to_be_bytes());
+        capture.extend(name.as_bytes());
+        capture.extend(args);
+        capture.extend(outs);
+        capture
+    }
+
+    fn report(tracer: &mut StylusTracer, capture: &[u8]) {
+        tracer.hostio(
+            &mut (),
+            &StylusHostio {
+                address: Address::ZERO,
+                method: EvmApiMethod::CaptureHostIO,
+                input: capture,
+                output: &[],
+                gas: 0,
+                ink: 0,
+            },
+        );
+    }
+
+    #[test]
+    fn captured_hostios_are_decoded() {
+        let encoded = capture("msg_value", &[], &[0x2a; 32], 100, 90);
+        let info = HostioTraceInfo::decode(&encoded).unwrap();
+        assert_eq!(info.name, "msg_value");
+        assert!(info.args.is_empty());
+        assert_eq!(info.outs, Bytes::from(vec![0x2a; 32]));
+        assert_eq!((info.start_ink, info.end_ink), (100, 90));
+
+        assert!(HostioTraceInfo::decode(&encoded[..encoded.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn calls_hold_the_hostios_run_in_them() {
+        let callee = Address::repeat_byte(0x42);
+        let mut tracer = StylusTracer::new();
+        tracer.enter(CallKind::Call, Some(Address::repeat_byte(0x01)));
+        report(
+            &mut tracer,
+            &capture("user_entrypoint", &[0, 0, 0, 4], &[], 10, 10),
+        );
+        tracer.enter(CallKind::StaticCall, Some(callee));
+        report(&mut tracer, &capture("msg_sender", &[], &[0x01; 20], 8, 7));
+        tracer.exit(None);
+        report(
+            &mut tracer,
+            &capture("static_call_contract", &[0xaa], &[], 9, 5),
+        );
+        tracer.exit(None);
+
+        let [entrypoint, call, static_call] = tracer.hostios() else {
+            panic!("expected three hostios, got {:?}", tracer.hostios());
+        };
+        assert_eq!(entrypoint.name, "user_entrypoint");
+        assert_eq!(call.name, "evm_staticcall");
+        assert_eq!(call.address, Some(callee));
+        assert_eq!(call.steps.as_ref().unwrap()[0].name, "msg_sender");
+        assert_eq!(static_call.name, "static_call_contract");
+
+        assert_eq!(
+            tracer.to_json(),
+            format!(
+                concat!(
+                    r#"[{{"name":"user_entrypoint","args":"0x00000004","outs":"0x","startInk":10,"endInk":10}},"#,
+                    r#"{{"name":"evm_staticcall","args":"0x","outs":"0x","startInk":0,"endInk":0,"address":"0x{}","steps":["#,
+                    r#"{{"name":"msg_sender","args":"0x","outs":"0x{}","startInk":8,"endInk":7}}]}},"#,
+                    r#"{{"name":"static_call_contract","args":"0xaa","outs":"0x","startInk":9,"endInk":5}}]"#
+                ),
+                "42".repeat(20),
+                "01".repeat(20),
+            )
+        );
+    }
+}
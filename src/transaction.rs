@@ -1,6 +1,7 @@
 use std::ops::{Deref, DerefMut};
 
 use alloy_rlp::{Encodable, RlpDecodable, RlpEncodable};
+use alloy_sol_types::{SolCall, sol};
 use revm::{
     context::{
         Transaction, TxEnv,
@@ -11,8 +12,13 @@ use revm::{
     primitives::{Address, B256, Bytes, TxKind, U256, keccak256},
 };
 
-use crate::constants::{
-    ARBITRUM_DEPOSIT_TX_TYPE, ARBITRUM_INTERNAL_TX_TYPE, ARBOS_ADDRESS, ARBOS_STATE_ADDRESS,
+use crate::{
+    aliasing::apply_l1_to_l2_alias,
+    constants::{
+        ARB_RETRYABLE_TX_ADDRESS, ARBITRUM_DEPOSIT_TX_TYPE, ARBITRUM_INTERNAL_TX_TYPE,
+        ARBITRUM_RETRY_TX_TYPE, ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE, ARBOS_ADDRESS,
+    },
+    precompiles::ArbRetryableTx,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -20,6 +26,23 @@ use crate::constants::{
 pub enum ArbitrumTransactionError {
     Base(InvalidTransaction),
     L1FeeCalculationFailed,
+    /// A transaction of type `tx_type` carries another chain's ID.
+    InvalidChainId {
+        tx_type: u8,
+        expected: u64,
+        got: u64,
+    },
+    /// A signed typed transaction of type `tx_type` carries no chain ID.
+    MissingChainId {
+        tx_type: u8,
+    },
+    /// A legacy transaction without EIP-155 replay protection, while those are rejected.
+    UnprotectedTx,
+    /// A retry transaction whose ticket was redeemed, cancelled or expired after the retry was
+    /// scheduled.
+    RetryableNotFound {
+        ticket_id: B256,
+    },
 }
 
 impl TransactionError for ArbitrumTransactionError {}
@@ -29,6 +52,23 @@ impl std::fmt::Display for ArbitrumTransactionError {
         match self {
             Self::Base(e) => e.fmt(f),
             Self::L1FeeCalculationFailed => write!(f, "L1 fee calculation failed"),
+            Self::InvalidChainId {
+                tx_type,
+                expected,
+                got,
+            } => write!(
+                f,
+                "invalid chain ID {got} for transaction type {tx_type:#x}, expected {expected}"
+            ),
+            Self::MissingChainId { tx_type } => {
+                write!(f, "missing chain ID for transaction type {tx_type:#x}")
+            }
+            Self::UnprotectedTx => {
+                write!(f, "only replay-protected (EIP-155) transactions allowed")
+            }
+            Self::RetryableNotFound { ticket_id } => {
+                write!(f, "retryable ticket {ticket_id} not found")
+            }
         }
     }
 }
@@ -57,6 +97,11 @@ pub struct ArbitrumTransaction {
     pub enveloped_tx: Option<Bytes>,
     /// The poster address that submitted this transaction to L1 (batch poster)
     pub poster: Option<Address>,
+    /// The retryable redeem this transaction executes, for retry transactions (type 0x68)
+    pub retry_tx: Option<ArbitrumRetryTx>,
+    /// Whether the message came from L1 with an unaliased sender, so the caller executes under
+    /// its L2 alias
+    pub from_l1: bool,
 }
 
 impl ArbitrumTransaction {
@@ -65,6 +110,8 @@ impl ArbitrumTransaction {
             base,
             enveloped_tx: None,
             poster: None,
+            retry_tx: None,
+            from_l1: false,
         }
     }
 
@@ -74,6 +121,8 @@ impl ArbitrumTransaction {
             base,
             enveloped_tx: Some(enveloped_tx),
             poster: None,
+            retry_tx: None,
+            from_l1: false,
         }
     }
 
@@ -87,6 +136,17 @@ impl ArbitrumTransaction {
             base,
             enveloped_tx: Some(enveloped_tx),
             poster: Some(poster),
+            retry_tx: None,
+            from_l1: false,
+        }
+    }
+
+    /// Create a transaction for a message from the L1 address `base.caller`, which executes
+    /// under its L2 alias
+    pub fn new_from_l1(base: TxEnv) -> Self {
+        Self {
+            from_l1: true,
+            ..Self::new(base)
         }
     }
 }
@@ -128,7 +188,11 @@ impl Transaction for ArbitrumTransaction {
     }
 
     fn caller(&self) -> Address {
-        self.base.caller()
+        if self.from_l1 {
+            apply_l1_to_l2_alias(self.base.caller())
+        } else {
+            self.base.caller()
+        }
     }
 
     fn gas_limit(&self) -> u64 {
@@ -179,8 +243,9 @@ impl Transaction for ArbitrumTransaction {
         self.base.max_fee_per_blob_gas()
     }
 
+    /// ArbOS drops the priority fee, so a transaction never pays more than the basefee.
     fn effective_gas_price(&self, base_fee: u128) -> u128 {
-        self.base.effective_gas_price(base_fee)
+        self.base.effective_gas_price(base_fee).min(base_fee)
     }
 
     fn authorization_list_len(&self) -> usize {
@@ -200,6 +265,12 @@ pub trait ArbitrumTxTr: Transaction {
 
     /// Returns the poster address that submitted this transaction.
     fn poster(&self) -> Option<Address>;
+
+    /// Returns the retryable redeem this transaction executes, if it is a retry transaction.
+    fn retry_tx(&self) -> Option<&ArbitrumRetryTx>;
+
+    /// Returns whether the caller is the L2 alias of the L1 address that sent the message.
+    fn from_l1(&self) -> bool;
 }
 
 impl ArbitrumTxTr for ArbitrumTransaction {
@@ -210,6 +281,14 @@ impl ArbitrumTxTr for ArbitrumTransaction {
     fn poster(&self) -> Option<Address> {
         self.poster
     }
+
+    fn retry_tx(&self) -> Option<&ArbitrumRetryTx> {
+        self.retry_tx.as_ref()
+    }
+
+    fn from_l1(&self) -> bool {
+        self.from_l1
+    }
 }
 
 impl Deref for ArbitrumTransaction {
@@ -235,6 +314,14 @@ impl ArbitrumTxTr for TxEnv {
     fn poster(&self) -> Option<Address> {
         None
     }
+
+    fn retry_tx(&self) -> Option<&ArbitrumRetryTx> {
+        None
+    }
+
+    fn from_l1(&self) -> bool {
+        false
+    }
 }
 
 /// Arbitrum Deposit Transaction (type 0x64)
@@ -289,14 +376,207 @@ impl ArbitrumDepositTx {
     }
 }
 
+impl From<ArbitrumDepositTx> for ArbitrumTransaction {
+    /// Build the transaction environment the handler executes a deposit from. Deposits carry no
+    /// gas, so the gas limit and price are zero.
+    fn from(tx: ArbitrumDepositTx) -> Self {
+        Self::new(TxEnv {
+            tx_type: ArbitrumDepositTx::TX_TYPE,
+            caller: tx.from,
+            kind: TxKind::Call(tx.to),
+            value: tx.value,
+            chain_id: Some(tx.chain_id),
+            gas_limit: 0,
+            gas_price: 0,
+            ..Default::default()
+        })
+    }
+}
+
+/// Arbitrum Retry Transaction (type 0x68)
+///
+/// A redeem attempt of a retryable ticket, scheduled by an auto-redeem or by
+/// `ArbRetryableTx.redeem`. These transactions:
+/// - Are paid for with gas donated when they were scheduled
+/// - Take their callvalue from the ticket's escrow
+/// - Have no signature (system-generated)
+#[derive(Clone, Debug, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArbitrumRetryTx {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Sequence number of the attempt among the ticket's redeems
+    pub nonce: u64,
+    /// Sender of the original retryable
+    pub from: Address,
+    /// Base fee the donated gas was paid at
+    pub gas_fee_cap: U256,
+    /// Donated gas
+    pub gas: u64,
+    /// Call destination
+    pub to: Address,
+    /// Callvalue taken from escrow
+    pub value: U256,
+    /// Calldata
+    pub data: Bytes,
+    /// Ticket being redeemed
+    pub ticket_id: B256,
+    /// Address refunded for unused gas
+    pub refund_to: Address,
+    /// Upper bound on the refund
+    pub max_refund: U256,
+    /// Submission fee refunded if the attempt succeeds
+    pub submission_fee_refund: U256,
+}
+
+impl ArbitrumRetryTx {
+    /// Transaction type identifier
+    pub const TX_TYPE: u8 = ARBITRUM_RETRY_TX_TYPE;
+
+    /// Compute the hash of the retry transaction
+    pub fn hash(&self) -> B256 {
+        let mut buf = Vec::new();
+        buf.push(Self::TX_TYPE);
+        self.encode(&mut buf);
+        keccak256(&buf)
+    }
+}
+
+impl From<ArbitrumRetryTx> for ArbitrumTransaction {
+    /// Build the transaction environment the handler executes a redeem from. The gas was paid
+    /// for when the redeem was scheduled, at the fee cap recorded in the transaction.
+    fn from(tx: ArbitrumRetryTx) -> Self {
+        let mut transaction = Self::new(TxEnv {
+            tx_type: ArbitrumRetryTx::TX_TYPE,
+            caller: tx.from,
+            kind: TxKind::Call(tx.to),
+            value: tx.value,
+            data: tx.data.clone(),
+            chain_id: Some(tx.chain_id),
+            gas_limit: tx.gas,
+            gas_price: tx.gas_fee_cap.saturating_to(),
+            ..Default::default()
+        });
+        transaction.retry_tx = Some(tx);
+        transaction
+    }
+}
+
+/// Arbitrum Submit Retryable Transaction (type 0x69)
+///
+/// Creates a retryable ticket from an L1 message. Its hash is the ticket ID.
+#[derive(Clone, Debug, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArbitrumSubmitRetryableTx {
+    /// Chain ID
+    pub chain_id: u64,
+    /// L1 request ID
+    pub request_id: B256,
+    /// Sender on L1, aliased
+    pub from: Address,
+    /// L1 base fee the submission fee is priced at
+    pub l1_base_fee: U256,
+    /// ETH minted to `from`
+    pub deposit_value: U256,
+    /// Gas fee cap of the auto-redeem
+    pub gas_fee_cap: U256,
+    /// Gas limit of the auto-redeem
+    pub gas: u64,
+    /// Call destination of the ticket
+    pub retry_to: Address,
+    /// Callvalue of the ticket
+    pub retry_value: U256,
+    /// Address allowed to cancel the ticket
+    pub beneficiary: Address,
+    /// Most the sender is willing to pay for submission
+    pub max_submission_fee: U256,
+    /// Address refunded for excess fees
+    pub fee_refund_addr: Address,
+    /// Calldata of the ticket
+    pub retry_data: Bytes,
+}
+
+impl ArbitrumSubmitRetryableTx {
+    /// Transaction type identifier
+    pub const TX_TYPE: u8 = ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE;
+
+    /// Compute the hash of the submission, which is the ID of the ticket it creates
+    pub fn hash(&self) -> B256 {
+        let mut buf = Vec::new();
+        buf.push(Self::TX_TYPE);
+        self.encode(&mut buf);
+        keccak256(&buf)
+    }
+}
+
+impl From<ArbitrumSubmitRetryableTx> for ArbitrumTransaction {
+    /// Build the transaction environment the handler executes a submission from: a call to
+    /// `ArbRetryableTx.submitRetryable` carrying the message, as Nitro presents it to tracers.
+    fn from(tx: ArbitrumSubmitRetryableTx) -> Self {
+        let input = ArbRetryableTx::submitRetryableCall {
+            requestId: tx.request_id,
+            l1BaseFee: tx.l1_base_fee,
+            deposit: tx.deposit_value,
+            callvalue: tx.retry_value,
+            gasFeeCap: tx.gas_fee_cap,
+            gasLimit: tx.gas,
+            maxSubmissionFee: tx.max_submission_fee,
+            feeRefundAddress: tx.fee_refund_addr,
+            beneficiary: tx.beneficiary,
+            retryTo: tx.retry_to,
+            retryData: tx.retry_data,
+        }
+        .abi_encode();
+
+        Self::new(TxEnv {
+            tx_type: ArbitrumSubmitRetryableTx::TX_TYPE,
+            caller: tx.from,
+            kind: TxKind::Call(ARB_RETRYABLE_TX_ADDRESS),
+            data: input.into(),
+            chain_id: Some(tx.chain_id),
+            gas_limit: tx.gas,
+            gas_price: tx.gas_fee_cap.saturating_to(),
+            ..Default::default()
+        })
+    }
+}
+
+sol! {
+    /// Instructions ArbOS sends itself through internal transactions. The interface has no
+    /// deployed contract; its encodings are the payloads of type 0x6A transactions.
+    interface ArbosActs {
+        function startBlock(
+            uint256 l1BaseFee,
+            uint64 l1BlockNumber,
+            uint64 l2BlockNumber,
+            uint64 timePassed
+        ) external;
+        function batchPostingReport(
+            uint256 batchTimestamp,
+            address batchPosterAddress,
+            uint64 batchNumber,
+            uint64 batchDataGas,
+            uint256 l1BaseFeeWei
+        ) external;
+        function batchPostingReportV2(
+            uint256 batchTimestamp,
+            address batchPosterAddress,
+            uint64 batchNumber,
+            uint64 batchCallDataLength,
+            uint64 batchCallDataNonZeros,
+            uint64 batchExtraGas,
+            uint256 l1BaseFeeWei
+        ) external;
+    }
+}
+
 /// Arbitrum Internal Transaction (type 0x6A)
 ///
 /// System-level transactions for ArbOS state updates. These transactions:
 /// - Have no gas cost (gas is 0)
 /// - Have no signature (system-generated)
 /// - Skip nonce checks
-/// - Sender is always ARBOS_ADDRESS
-/// - Target is always ARBOS_STATE_ADDRESS
+/// - Sender and target are always ARBOS_ADDRESS
 #[derive(Clone, Debug, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArbitrumInternalTx {
@@ -312,17 +592,54 @@ impl ArbitrumInternalTx {
 
     // Method selectors for internal transactions
     /// StartBlock method selector
-    pub const START_BLOCK_METHOD: [u8; 4] = [0x00, 0x00, 0x00, 0x01];
+    pub const START_BLOCK_METHOD: [u8; 4] = ArbosActs::startBlockCall::SELECTOR;
     /// BatchPostingReport method selector
-    pub const BATCH_POSTING_REPORT_METHOD: [u8; 4] = [0x00, 0x00, 0x00, 0x02];
+    pub const BATCH_POSTING_REPORT_METHOD: [u8; 4] = ArbosActs::batchPostingReportCall::SELECTOR;
     /// BatchPostingReportV2 method selector (ArbOS 50+)
-    pub const BATCH_POSTING_REPORT_V2_METHOD: [u8; 4] = [0x00, 0x00, 0x00, 0x03];
+    pub const BATCH_POSTING_REPORT_V2_METHOD: [u8; 4] =
+        ArbosActs::batchPostingReportV2Call::SELECTOR;
 
     /// Create a new internal transaction
     pub fn new(chain_id: u64, data: Bytes) -> Self {
         Self { chain_id, data }
     }
 
+    /// Create the StartBlock instruction that opens every L2 block.
+    pub fn start_block(
+        chain_id: u64,
+        l1_base_fee: U256,
+        l1_block_number: u64,
+        l2_block_number: u64,
+        time_passed: u64,
+    ) -> Self {
+        let call = ArbosActs::startBlockCall {
+            l1BaseFee: l1_base_fee,
+            l1BlockNumber: l1_block_number,
+            l2BlockNumber: l2_block_number,
+            timePassed: time_passed,
+        };
+        Self::new(chain_id, call.abi_encode().into())
+    }
+
+    /// Create a BatchPostingReport instruction for a batch posted by `batch_poster`.
+    pub fn batch_posting_report(
+        chain_id: u64,
+        batch_timestamp: u64,
+        batch_poster: Address,
+        batch_number: u64,
+        batch_data_gas: u64,
+        l1_base_fee: U256,
+    ) -> Self {
+        let call = ArbosActs::batchPostingReportCall {
+            batchTimestamp: U256::from(batch_timestamp),
+            batchPosterAddress: batch_poster,
+            batchNumber: batch_number,
+            batchDataGas: batch_data_gas,
+            l1BaseFeeWei: l1_base_fee,
+        };
+        Self::new(chain_id, call.abi_encode().into())
+    }
+
     /// Compute the hash of the internal transaction
     pub fn hash(&self) -> B256 {
         let mut buf = Vec::new();
@@ -356,6 +673,22 @@ impl ArbitrumInternalTx {
     }
 }
 
+impl From<ArbitrumInternalTx> for ArbitrumTransaction {
+    /// Build the transaction environment the handler executes an internal transaction from.
+    fn from(tx: ArbitrumInternalTx) -> Self {
+        Self::new(TxEnv {
+            tx_type: ArbitrumInternalTx::TX_TYPE,
+            caller: ARBOS_ADDRESS,
+            kind: TxKind::Call(ARBOS_ADDRESS),
+            data: tx.data,
+            chain_id: Some(tx.chain_id),
+            gas_limit: 0,
+            gas_price: 0,
+            ..Default::default()
+        })
+    }
+}
+
 /// Enum wrapper for Arbitrum-specific transaction types
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -502,7 +835,7 @@ impl Transaction for ArbitrumTypedTransaction {
         match self {
             Self::Standard(tx) => tx.kind(),
             Self::Deposit(tx) => TxKind::Call(tx.to),
-            Self::Internal(_) => TxKind::Call(ARBOS_STATE_ADDRESS),
+            Self::Internal(_) => TxKind::Call(ARBOS_ADDRESS),
         }
     }
 
@@ -600,4 +933,11 @@ impl ArbitrumTxTr for ArbitrumTypedTransaction {
             Self::Deposit(_) | Self::Internal(_) => None,
         }
     }
+
+    fn retry_tx(&self) -> Option<&ArbitrumRetryTx> {
+        match self {
+            Self::Standard(tx) => tx.retry_tx(),
+            Self::Deposit(_) | Self::Internal(_) => None,
+        }
+    }
 }
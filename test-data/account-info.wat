@@ -3,18 +3,21 @@
 ;; Protocol:
 ;;   0x00 + address (20 bytes) = get account balance (returns 32 bytes)
 ;;   0x01 + address (20 bytes) = get account code hash (returns 32 bytes)
+;;   0x02 + address (20 bytes) = get account code (returns up to 256 bytes)
 
 (module
     (import "vm_hooks" "read_args"       (func $read_args       (param i32)))
     (import "vm_hooks" "write_result"    (func $write_result    (param i32 i32)))
     (import "vm_hooks" "account_balance" (func $account_balance (param i32 i32)))
     (import "vm_hooks" "account_codehash" (func $account_codehash (param i32 i32)))
+    (import "vm_hooks" "account_code"    (func $account_code    (param i32 i32 i32 i32) (result i32)))
     (memory (export "memory") 1 1)
 
     ;; Memory layout:
     ;; 0-255:   Input args buffer
     ;; 256-275: Address buffer (20 bytes)
     ;; 276-307: Result buffer (32 bytes)
+    ;; 308-563: Code buffer (256 bytes)
 
     (func (export "user_entrypoint") (param $args_len i32) (result i32)
         (local $selector i32)
@@ -48,6 +51,18 @@
             )
         )
 
+        ;; 0x02 = get account code
+        (if (i32.eq (local.get $selector) (i32.const 2))
+            (then
+                ;; account_code(address, offset, size, dest) returns the bytes written
+                (call $write_result
+                    (i32.const 308)
+                    (call $account_code (i32.const 256) (i32.const 0) (i32.const 256) (i32.const 308))
+                )
+                (return (i32.const 0))
+            )
+        )
+
         ;; Unknown selector - return empty
         (call $write_result (i32.const 0) (i32.const 0))
         (i32.const 0)
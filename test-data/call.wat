@@ -3,6 +3,8 @@
 ;;   0x00 + address (20 bytes) + calldata = call_contract
 ;;   0x01 + address (20 bytes) + calldata = static_call_contract
 ;;   0x02 + address (20 bytes) + calldata = delegate_call_contract
+;;   0x03 + address (20 bytes) + value (32 bytes) + calldata = call_contract with value,
+;;        returning the status byte followed by the return data
 ;;
 ;; Returns the call result (return data from called contract)
 
@@ -174,6 +176,41 @@
             )
         )
 
+        ;; call_contract with value (0x03)
+        (if (i32.eq (local.get $selector) (i32.const 3))
+            (then
+                ;; The first 32 bytes of the calldata region hold the value
+                (memory.copy (i32.const 276) (i32.const 312) (i32.const 32))
+
+                (local.set $call_result
+                    (call $call_contract
+                        (i32.const 256)           ;; target address
+                        (i32.const 344)           ;; calldata pointer (after the value)
+                        (i32.sub (local.get $calldata_len) (i32.const 32)) ;; calldata length
+                        (i32.const 276)           ;; value
+                        (i64.const 1000000)       ;; gas limit
+                        (i32.const 308)           ;; return data length output
+                    )
+                )
+
+                ;; Status byte at 2047, return data right after it
+                (i32.store8 (i32.const 2047) (local.get $call_result))
+                (local.set $return_len (call $return_data_size))
+                (if (i32.gt_s (local.get $return_len) (i32.const 0))
+                    (then
+                        (drop (call $read_return_data
+                            (i32.const 2048)          ;; dest
+                            (i32.const 0)             ;; offset
+                            (local.get $return_len)   ;; size
+                        ))
+                    )
+                )
+
+                (call $write_result (i32.const 2047) (i32.add (i32.const 1) (local.get $return_len)))
+                (return (i32.const 0))
+            )
+        )
+
         ;; Unknown selector - return empty
         (call $write_result (i32.const 0) (i32.const 0))
         (i32.const 0)
@@ -4,6 +4,7 @@
 ;;   0x01 + value (32 bytes) + salt (32 bytes) + init_code = CREATE2 (returns 20 bytes address or zeros)
 ;;   0x02 = CREATE with minimal contract (zero value, predefined init code)
 ;;   0x03 + salt (32 bytes) = CREATE2 with minimal contract (zero value, predefined init code)
+;;   0x04 + value (32 bytes) + init_code = CREATE, returns 20 bytes address followed by the return data
 
 (module
     (import "vm_hooks" "read_args"       (func $read_args       (param i32)))
@@ -22,6 +23,7 @@
     ;; 340-343: Revert data length output (4 bytes / i32)
     ;; 344-600: Init code buffer
     ;; 601-1000: Revert data buffer
+    ;; 2000-:    Address + return data output buffer (selector 0x04)
 
     ;; Minimal init code at offset 700 (deploys contract that returns empty)
     ;; Init code: 6005600c60003960056000f360006000f3
@@ -152,6 +154,41 @@
             )
         )
 
+        ;; 0x04 = CREATE with custom value and init code, returning address and return data
+        (if (i32.eq (local.get $selector) (i32.const 4))
+            (then
+                ;; Copy value (32 bytes) from offset 1 to offset 256
+                (memory.copy (i32.const 256) (i32.const 1) (i32.const 32))
+
+                ;; Calculate init code length: args_len - 1 (selector) - 32 (value)
+                (local.set $init_code_len (i32.sub (local.get $args_len) (i32.const 33)))
+
+                ;; Copy init code from offset 33 to offset 344
+                (if (i32.gt_s (local.get $init_code_len) (i32.const 0))
+                    (then
+                        (memory.copy (i32.const 344) (i32.const 33) (local.get $init_code_len))
+                    )
+                )
+
+                (call $create1
+                    (i32.const 344)               ;; code pointer
+                    (local.get $init_code_len)    ;; code length
+                    (i32.const 256)               ;; endowment (value)
+                    (i32.const 320)               ;; contract address output
+                    (i32.const 340)               ;; revert data length output
+                )
+
+                ;; Address at 2000, return data right after it
+                (memory.copy (i32.const 2000) (i32.const 320) (i32.const 20))
+                (local.set $return_len
+                    (call $read_return_data (i32.const 2020) (i32.const 0) (call $return_data_size))
+                )
+
+                (call $write_result (i32.const 2000) (i32.add (i32.const 20) (local.get $return_len)))
+                (return (i32.const 0))
+            )
+        )
+
         ;; Unknown selector - return empty
         (call $write_result (i32.const 0) (i32.const 0))
         (i32.const 0)
@@ -0,0 +1,13 @@
+;; Infinite loop test program for arbos-revm
+;; Loops until it runs out of ink.
+
+(module
+    (memory (export "memory") 1 1)
+
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (loop $forever
+            br $forever
+        )
+        i32.const 0
+    )
+)
@@ -0,0 +1,15 @@
+;; Unbounded recursion test program for arbos-revm
+;; Recurses until it overflows the WASM stack.
+
+(module
+    (memory (export "memory") 1 1)
+
+    (func $recurse
+        call $recurse
+    )
+
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        call $recurse
+        i32.const 0
+    )
+)
@@ -0,0 +1,73 @@
+;; Reentrancy test program for arbos-revm
+;; Protocol:
+;;   0x00 = return msg_reentrant (1 byte)
+;;   0x01 + address (20 bytes) = call the address with calldata 0x00, then return
+;;        msg_reentrant (1 byte) followed by the call's return data
+;;   0x02 + address (20 bytes) = call the identity precompile, then do as 0x01
+
+(module
+    (import "vm_hooks" "read_args"        (func $read_args        (param i32)))
+    (import "vm_hooks" "write_result"     (func $write_result     (param i32 i32)))
+    (import "vm_hooks" "msg_reentrant"    (func $msg_reentrant    (result i32)))
+    (import "vm_hooks" "call_contract"    (func $call_contract    (param i32 i32 i32 i32 i64 i32) (result i32)))
+    (import "vm_hooks" "read_return_data" (func $read_return_data (param i32 i32 i32) (result i32)))
+    (memory (export "memory") 1 1)
+
+    ;; Memory layout:
+    ;; 0-63:    Input args buffer
+    ;; 64:      Calldata of the call (0x00)
+    ;; 96-127:  Value buffer for the call (32 bytes of zeros)
+    ;; 128-131: Return data length (4 bytes / i32)
+    ;; 160-179: Address of the identity precompile
+    ;; 256-:    Result: msg_reentrant, then the return data
+
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (local $return_len i32)
+
+        ;; Read args into memory at offset 0
+        (call $read_args (i32.const 0))
+
+        (i32.store8 (i32.const 256) (call $msg_reentrant))
+
+        ;; 0x02 = call the identity precompile first
+        (if (i32.eq (i32.load8_u (i32.const 0)) (i32.const 2))
+            (then
+                (i32.store8 (i32.const 179) (i32.const 4))
+                (drop (call $call_contract
+                    (i32.const 160)           ;; identity precompile
+                    (i32.const 64)            ;; calldata pointer
+                    (i32.const 0)             ;; calldata length
+                    (i32.const 96)            ;; value (zeros = no value)
+                    (i64.const 1000000)       ;; gas limit
+                    (i32.const 128)           ;; return data length output
+                ))
+            )
+        )
+
+        ;; 0x01 or 0x02 = call the address at offset 1
+        (if (i32.ge_u (i32.load8_u (i32.const 0)) (i32.const 1))
+            (then
+                ;; call_contract(contract, calldata, calldata_len, value, gas, return_data_len)
+                (drop (call $call_contract
+                    (i32.const 1)             ;; target address
+                    (i32.const 64)            ;; calldata pointer
+                    (i32.const 1)             ;; calldata length
+                    (i32.const 96)            ;; value (zeros = no value)
+                    (i64.const 1000000)       ;; gas limit
+                    (i32.const 128)           ;; return data length output
+                ))
+                (local.set $return_len (i32.load (i32.const 128)))
+                (drop (call $read_return_data
+                    (i32.const 257)           ;; dest
+                    (i32.const 0)             ;; offset
+                    (local.get $return_len)   ;; size
+                ))
+                (call $write_result (i32.const 256) (i32.add (local.get $return_len) (i32.const 1)))
+                (return (i32.const 0))
+            )
+        )
+
+        (call $write_result (i32.const 256) (i32.const 1))
+        (i32.const 0)
+    )
+)
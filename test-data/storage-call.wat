@@ -0,0 +1,44 @@
+;; Storage cache ordering test program for arbos-revm
+;; Protocol:
+;;   key (32 bytes) + value (32 bytes) + address (20 bytes) = cache a write of value to key
+;;        without flushing it, delegate call address with the key as calldata, then load the
+;;        key again. Returns the first 32 bytes of the return data followed by the loaded value
+
+(module
+    (import "vm_hooks" "read_args"              (func $read_args              (param i32)))
+    (import "vm_hooks" "write_result"           (func $write_result           (param i32 i32)))
+    (import "vm_hooks" "storage_load_bytes32"   (func $storage_load_bytes32   (param i32 i32)))
+    (import "vm_hooks" "storage_cache_bytes32"  (func $storage_cache_bytes32  (param i32 i32)))
+    (import "vm_hooks" "delegate_call_contract" (func $delegate_call_contract (param i32 i32 i32 i64 i32) (result i32)))
+    (import "vm_hooks" "read_return_data"       (func $read_return_data       (param i32 i32 i32) (result i32)))
+    (memory (export "memory") 1 1)
+
+    ;; Memory layout:
+    ;; 0-127:   Input args buffer (key at 0, value at 32, address at 64)
+    ;; 128-159: Return data of the call (32 bytes)
+    ;; 160-191: Value loaded after the call (32 bytes)
+    ;; 192-195: Return data length (4 bytes / i32)
+
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        (call $read_args (i32.const 0))
+
+        ;; Cache the new value, leaving it to the call to flush
+        (call $storage_cache_bytes32 (i32.const 0) (i32.const 32))
+
+        ;; delegate_call_contract(contract, calldata, calldata_len, gas, return_data_len)
+        (drop (call $delegate_call_contract
+            (i32.const 64)            ;; target address
+            (i32.const 0)             ;; calldata pointer (the key)
+            (i32.const 32)            ;; calldata length
+            (i64.const 1000000)       ;; gas limit
+            (i32.const 192)           ;; return data length output
+        ))
+        (drop (call $read_return_data (i32.const 128) (i32.const 0) (i32.const 32)))
+
+        ;; Load the key again
+        (call $storage_load_bytes32 (i32.const 0) (i32.const 160))
+
+        (call $write_result (i32.const 128) (i32.const 64))
+        (i32.const 0)
+    )
+)
@@ -0,0 +1,10 @@
+;; Trap test program for arbos-revm
+;; Traps unconditionally with an unreachable instruction.
+
+(module
+    (memory (export "memory") 1 1)
+
+    (func (export "user_entrypoint") (param $args_len i32) (result i32)
+        unreachable
+    )
+)
@@ -1,7 +1,7 @@
 // Copyright 2024, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE.md
 
-//! Account info tests for Stylus programs (balance, code hash).
+//! Account info tests for Stylus programs (balance, code hash, code).
 
 use revm::{
     context::result::ExecutionResult,
@@ -10,7 +10,7 @@ use revm::{
 
 mod test_utils;
 use test_utils::{
-    create_call_tx, create_evm, deploy_wat_program, execute_tx, fund_account,
+    create_call_tx, create_evm, deploy_evm_code, deploy_wat_program, execute_tx, fund_account,
     setup_context_with_arbos_state,
 };
 
@@ -302,6 +302,47 @@ fn test_e2e_account_codehash_nonexistent() {
     }
 }
 
+// ============================================================================
+// Account Code Tests
+// ============================================================================
+
+/// Code of `query_address` as read by the program, after deploying `code` there.
+fn query_account_code(query_address: Address, code: &[u8]) -> Vec<u8> {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/account-info.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(1_000_000_000_u64));
+    if !code.is_empty() {
+        deploy_evm_code(&mut context, query_address, code);
+    }
+
+    let mut evm = create_evm(context);
+
+    let mut args = vec![0x02u8]; // code selector
+    args.extend_from_slice(query_address.as_slice());
+
+    let tx = create_call_tx(program_address, args, 10_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => output.data().to_vec(),
+        other => panic!("account code query failed: {:?}", other),
+    }
+}
+
+#[test]
+fn test_e2e_account_code_contract() {
+    // PUSH1 0 PUSH1 0 RETURN
+    let code = [0x60, 0x00, 0x60, 0x00, 0xf3];
+    assert_eq!(query_account_code(Address::repeat_byte(0xC0), &code), code);
+}
+
+#[test]
+fn test_e2e_account_code_nonexistent() {
+    assert!(query_account_code(Address::repeat_byte(0xDE), &[]).is_empty());
+}
+
 // ============================================================================
 // Cross-Contract Balance Query Tests
 // ============================================================================
@@ -356,3 +397,63 @@ fn test_e2e_balance_via_nested_call() {
         }
     }
 }
+
+// ============================================================================
+// Access Pricing Tests
+// ============================================================================
+
+/// Gas used by a transaction querying `query_address` with the given selector.
+fn account_query_gas(selector: u8, query_address: Address) -> u64 {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/account-info.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(1_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let mut args = vec![selector];
+    args.extend_from_slice(query_address.as_slice());
+
+    let tx = create_call_tx(program_address, args, 10_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { gas_used, .. } => gas_used,
+        other => panic!("account query failed: {:?}", other),
+    }
+}
+
+/// The transaction sender is warm from the start, any other unseen address is cold.
+#[test]
+fn test_e2e_account_balance_cold_surcharge() {
+    let warm = account_query_gas(0x00, Address::repeat_byte(0x01));
+    let cold = account_query_gas(0x00, Address::repeat_byte(0xAB));
+    assert_eq!(
+        cold - warm,
+        2_600 - 100,
+        "cold balance lookups should pay the EIP-2929 surcharge"
+    );
+}
+
+#[test]
+fn test_e2e_account_codehash_cold_surcharge() {
+    let warm = account_query_gas(0x01, Address::repeat_byte(0x01));
+    let cold = account_query_gas(0x01, Address::repeat_byte(0xAB));
+    assert_eq!(
+        cold - warm,
+        2_600 - 100,
+        "cold code hash lookups should pay the EIP-2929 surcharge"
+    );
+}
+
+#[test]
+fn test_e2e_account_code_cold_surcharge() {
+    let warm = account_query_gas(0x02, Address::repeat_byte(0x01));
+    let cold = account_query_gas(0x02, Address::repeat_byte(0xAB));
+    assert_eq!(
+        cold - warm,
+        2_600 - 100,
+        "cold code lookups should pay the EIP-2929 surcharge"
+    );
+}
@@ -0,0 +1,135 @@
+//! Tests for listing the Stylus programs a transaction activated.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::{
+    local_context::ActivatedProgram,
+    state::{ArbState, ArbStateGetter},
+};
+use revm::{
+    context::{ContextTr, JournalTr, TxEnv},
+    primitives::{Address, B256, TxKind, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, TestEvm, create_call_tx, create_call_tx_with_nonce, create_evm, deploy_evm_code,
+    deploy_wat_program, execute_tx, fund_account, setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbWasm {
+        function activateProgram(address program)
+            external payable returns (uint16 version, uint256 dataFee);
+    }
+}
+
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const FORWARDER: Address = address!("0x00000000000000000000000000000000000000f0");
+const REVERTING_FORWARDER: Address = address!("0x00000000000000000000000000000000000000f1");
+
+/// Code forwarding its calldata and value to ArbWasm, then stopping or, if `revert`, reverting.
+fn forwarder(revert: bool) -> Vec<u8> {
+    // CALLDATACOPY(0, 0, CALLDATASIZE)
+    let mut code = vec![0x36, 0x60, 0x00, 0x60, 0x00, 0x37];
+    // CALL(gas, ArbWasm, CALLVALUE, 0, CALLDATASIZE, 0, 0) POP
+    code.extend_from_slice(&[
+        0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00, 0x34, 0x60, 0x71, 0x5a, 0xf1, 0x50,
+    ]);
+    if revert {
+        // REVERT(0, 0)
+        code.extend_from_slice(&[0x60, 0x00, 0x60, 0x00, 0xfd]);
+    }
+    code
+}
+
+/// Deploy the storage test program and the forwarders, returning the context, the program's
+/// address and code hash, and the Stylus version in force.
+fn setup(auto_activate: bool) -> (TestContext, Address, B256, u16) {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.disable_auto_activate = !auto_activate;
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/storage.wat"));
+    deploy_evm_code(&mut context, FORWARDER, &forwarder(false));
+    deploy_evm_code(&mut context, REVERTING_FORWARDER, &forwarder(true));
+    fund_account(&mut context, CALLER, U256::from(10u128.pow(18)));
+    let code_hash = context.journal_mut().code_hash(program).unwrap().data;
+    let version = context
+        .arb_state(None, true)
+        .programs()
+        .stylus_params()
+        .get()
+        .unwrap()
+        .version;
+    (context, program, code_hash, version)
+}
+
+/// Activate `program` through ArbWasm via `to`, paying more than enough for the data fee.
+fn activate(evm: &mut TestEvm, to: Address, program: Address, nonce: u64) -> bool {
+    let tx = TxEnv {
+        caller: CALLER,
+        kind: TxKind::Call(to),
+        data: ArbWasm::activateProgramCall { program }.abi_encode().into(),
+        value: U256::from(10u128.pow(17)),
+        gas_limit: 20_000_000,
+        gas_price: 1,
+        nonce,
+        ..Default::default()
+    };
+    execute_tx(evm, tx).is_success()
+}
+
+#[test]
+fn test_explicit_activation_is_listed() {
+    let (context, program, code_hash, version) = setup(false);
+    let mut evm = create_evm(context);
+
+    assert!(activate(&mut evm, FORWARDER, program, 0));
+    let activated = evm.activated_programs();
+    assert_eq!(activated.len(), 1);
+    assert_eq!(activated[0].code_hash, code_hash);
+    assert_eq!(activated[0].program, program);
+    assert_eq!(activated[0].version, version);
+    assert!(activated[0].data_fee > U256::ZERO);
+    assert!(!activated[0].auto_activated);
+
+    // The next transaction starts a fresh list
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    assert!(
+        execute_tx(
+            &mut evm,
+            create_call_tx_with_nonce(program, args, 10_000_000, 1)
+        )
+        .is_success()
+    );
+    assert!(evm.activated_programs().is_empty());
+}
+
+#[test]
+fn test_reverted_activation_is_not_listed() {
+    let (context, program, _, _) = setup(false);
+    let mut evm = create_evm(context);
+
+    activate(&mut evm, REVERTING_FORWARDER, program, 0);
+    assert!(evm.activated_programs().is_empty());
+}
+
+#[test]
+fn test_auto_activation_is_listed() {
+    let (context, program, code_hash, version) = setup(true);
+    let mut evm = create_evm(context);
+
+    // Selector 0x00 = read slot 0
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    assert!(execute_tx(&mut evm, create_call_tx(program, args, 10_000_000)).is_success());
+    assert_eq!(
+        evm.activated_programs(),
+        [ActivatedProgram {
+            code_hash,
+            program,
+            version,
+            data_fee: U256::ZERO,
+            auto_activated: true,
+        }]
+    );
+}
@@ -0,0 +1,80 @@
+//! Tests for activating Stylus programs without a context.
+
+use arbos_revm::{
+    state::{ArbState, ArbStateGetter, program::activate_program},
+    stylus_executor::{activate_wasm, stylus_code},
+};
+use revm::{
+    context::{ContextTr, JournalTr},
+    primitives::{Bytes, keccak256},
+};
+
+mod test_utils;
+use test_utils::{TestContext, deploy_wat_program, setup_context_with_arbos_state};
+
+/// Deploys `wat` and returns the deployed Stylus code.
+fn deployed_code(context: &mut TestContext, wat: &[u8]) -> Bytes {
+    let address = deploy_wat_program(context, wat);
+    context.journal_mut().code(address).unwrap().data
+}
+
+#[test]
+fn test_activate_wasm_reports_costs() {
+    let wasm = wasmer::wat2wasm(include_bytes!("../test-data/memory.wat")).unwrap();
+
+    let info = activate_wasm(&wasm, 1, 50, 128).expect("activation failed");
+
+    assert_eq!(info.version, 1);
+    assert_ne!(info.module_hash, Default::default());
+    assert!(info.asm_estimate > 0);
+    assert!(info.init_cost > 0);
+    assert!(info.footprint > 0);
+    assert!(info.data_fee > 0);
+}
+
+#[test]
+fn test_activate_wasm_accepts_deployed_code() {
+    let mut context = setup_context_with_arbos_state();
+    let wat = include_bytes!("../test-data/memory.wat");
+    let code = deployed_code(&mut context, wat);
+    let wasm = wasmer::wat2wasm(wat).unwrap();
+
+    let from_code = activate_wasm(&code, 1, 50, 128).expect("activation failed");
+    let from_wasm = activate_wasm(&wasm, 1, 50, 128).expect("activation failed");
+
+    assert_eq!(from_code.asm_estimate, from_wasm.asm_estimate);
+    assert_eq!(from_code.init_cost, from_wasm.init_cost);
+    assert_eq!(from_code.footprint, from_wasm.footprint);
+    assert_eq!(from_code.data_fee, from_wasm.data_fee);
+}
+
+#[test]
+fn test_activate_wasm_matches_onchain_activation() {
+    let mut context = setup_context_with_arbos_state();
+    let code = deployed_code(&mut context, include_bytes!("../test-data/memory.wat"));
+
+    let params = context
+        .arb_state(None, false)
+        .programs()
+        .stylus_params()
+        .get()
+        .unwrap();
+    let standalone = activate_wasm(
+        &code,
+        params.version,
+        context.cfg.arbos_version,
+        params.page_limit,
+    )
+    .expect("standalone activation failed");
+
+    let wasm = stylus_code(&code).unwrap().unwrap();
+    let onchain =
+        activate_program(&mut context, keccak256(&code), &wasm, false).expect("activation failed");
+
+    assert_eq!(standalone, onchain);
+}
+
+#[test]
+fn test_activate_wasm_rejects_invalid_module() {
+    assert!(activate_wasm(b"not wasm", 1, 50, 128).is_err());
+}
@@ -0,0 +1,114 @@
+//! Tests for relocating the ArbOS state account and the Arbitrum precompiles through config.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::{
+    constants::{ARB_SYS_ADDRESS, ARBOS_STATE_ADDRESS, PRECOMPILE_MARKER_CODE},
+    state::{ArbState, ArbStateGetter, arbos_state::ArbosStateParams, types::StorageBackedTr},
+};
+use revm::{
+    context::{ContextTr, JournalTr},
+    primitives::{Address, FixedBytes, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, create_call_tx_with_nonce, create_evm, execute_tx, fund_account, setup_context,
+};
+
+sol! {
+    interface ArbSys {
+        function arbBlockNumber() external view returns (uint256);
+    }
+}
+
+const STATE: Address = address!("0xA4B05EEEEEEEEEEEEEEEEEEEEEEEEEEEEEEEEEEE");
+const PRECOMPILE_BASE: Address = address!("0x0000000000000000000000000000000000010000");
+const RELOCATED_ARB_SYS: Address = address!("0x0000000000000000000000000000000000010064");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+
+fn setup_relocated() -> TestContext {
+    let mut context = setup_context();
+    context.cfg.arbos_state_address = STATE;
+    context.cfg.precompile_address_base = PRECOMPILE_BASE;
+    context
+        .arb_state(None, false)
+        .initialize(&ArbosStateParams::default())
+        .expect("failed to initialize ArbOS state");
+    context
+}
+
+fn code(context: &mut TestContext, address: Address) -> Vec<u8> {
+    context.journal_mut().code(address).unwrap().data.to_vec()
+}
+
+#[test]
+fn test_state_is_stored_at_configured_address() {
+    let mut context = setup_relocated();
+    context
+        .arb_state(None, false)
+        .chain_id()
+        .set(U256::from(42))
+        .unwrap();
+
+    assert_eq!(
+        context
+            .journal_mut()
+            .load_account(STATE)
+            .unwrap()
+            .data
+            .info
+            .nonce,
+        1
+    );
+    let default_account = context
+        .journal_mut()
+        .load_account(ARBOS_STATE_ADDRESS)
+        .unwrap()
+        .data;
+    assert_eq!(default_account.info.nonce, 0);
+    assert!(default_account.storage.is_empty());
+    assert_eq!(
+        context.arb_state(None, true).chain_id().get().unwrap(),
+        U256::from(42)
+    );
+}
+
+#[test]
+fn test_precompiles_get_marker_code_at_configured_block() {
+    let mut context = setup_relocated();
+
+    assert_eq!(
+        code(&mut context, RELOCATED_ARB_SYS),
+        PRECOMPILE_MARKER_CODE
+    );
+    assert!(code(&mut context, ARB_SYS_ADDRESS).is_empty());
+}
+
+#[test]
+fn test_precompiles_run_at_configured_block() {
+    let mut context = setup_relocated();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    let mut evm = create_evm(context);
+
+    let calldata = ArbSys::arbBlockNumberCall {}.abi_encode();
+    let result = execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(RELOCATED_ARB_SYS, calldata.clone(), 100_000, 0),
+    );
+    assert!(result.is_success());
+    assert_eq!(result.output().unwrap().len(), 32);
+    let key = (
+        RELOCATED_ARB_SYS,
+        FixedBytes(ArbSys::arbBlockNumberCall::SELECTOR),
+    );
+    assert!(evm.precompile_gas_used().contains_key(&key));
+
+    // The canonical address is an ordinary empty account once the block is relocated.
+    let result = execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(ARB_SYS_ADDRESS, calldata, 100_000, 1),
+    );
+    assert!(result.is_success());
+    assert!(result.output().unwrap().is_empty());
+    assert!(evm.precompile_gas_used().is_empty());
+}
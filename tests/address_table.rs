@@ -0,0 +1,216 @@
+//! Tests for registering and compressing addresses through ArbAddressTable.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::precompiles::errors::decode_revert_message;
+use revm::{
+    context::result::ExecutionResult,
+    primitives::{Address, Bytes, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx_with_nonce, create_evm, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbAddressTable {
+        function addressExists(address addr) external view returns (bool);
+        function compress(address addr) external returns (bytes memory);
+        function decompress(bytes calldata buf, uint256 offset) external view returns (address, uint256);
+        function lookup(address addr) external view returns (uint256);
+        function lookupIndex(uint256 index) external view returns (address);
+        function register(address addr) external returns (uint256);
+        function size() external view returns (uint256);
+    }
+}
+
+const ARB_ADDRESS_TABLE: Address = address!("0x0000000000000000000000000000000000000066");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const FIRST: Address = address!("0x00000000000000000000000000000000000000a1");
+const SECOND: Address = address!("0x00000000000000000000000000000000000000a2");
+const UNKNOWN: Address = address!("0x00000000000000000000000000000000000000e1");
+
+/// Calls ArbAddressTable from a funded account, tracking its nonce.
+struct Session {
+    evm: TestEvm,
+    nonce: u64,
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut context = setup_context_with_arbos_state();
+        fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+        Self {
+            evm: create_evm(context),
+            nonce: 0,
+        }
+    }
+
+    fn call(&mut self, calldata: Vec<u8>) -> ExecutionResult {
+        let tx = create_call_tx_with_nonce(ARB_ADDRESS_TABLE, calldata, 1_000_000, self.nonce);
+        self.nonce += 1;
+        execute_tx(&mut self.evm, tx)
+    }
+
+    fn output(&mut self, calldata: Vec<u8>) -> Bytes {
+        match self.call(calldata) {
+            ExecutionResult::Success { output, .. } => output.into_data(),
+            other => panic!("call failed: {other:?}"),
+        }
+    }
+
+    fn register(&mut self, addr: Address) -> U256 {
+        let output = self.output(ArbAddressTable::registerCall { addr }.abi_encode());
+        ArbAddressTable::registerCall::abi_decode_returns(&output).unwrap()
+    }
+
+    fn size(&mut self) -> U256 {
+        let output = self.output(ArbAddressTable::sizeCall {}.abi_encode());
+        ArbAddressTable::sizeCall::abi_decode_returns(&output).unwrap()
+    }
+
+    fn compress(&mut self, addr: Address) -> Bytes {
+        let output = self.output(ArbAddressTable::compressCall { addr }.abi_encode());
+        ArbAddressTable::compressCall::abi_decode_returns(&output).unwrap()
+    }
+
+    fn decompress(&mut self, buf: &[u8], offset: u64) -> ExecutionResult {
+        self.call(
+            ArbAddressTable::decompressCall {
+                buf: Bytes::copy_from_slice(buf),
+                offset: U256::from(offset),
+            }
+            .abi_encode(),
+        )
+    }
+}
+
+fn decompressed(result: ExecutionResult) -> (Address, U256) {
+    match result {
+        ExecutionResult::Success { output, .. } => {
+            let ret = ArbAddressTable::decompressCall::abi_decode_returns(output.data()).unwrap();
+            (ret._0, ret._1)
+        }
+        other => panic!("call failed: {other:?}"),
+    }
+}
+
+#[test]
+fn test_register_assigns_indexes_in_order() {
+    let mut session = Session::new();
+    let initial = session.size();
+
+    assert_eq!(session.register(FIRST), initial);
+    assert_eq!(session.register(SECOND), initial + U256::from(1));
+    // Registering again keeps the existing index
+    assert_eq!(session.register(FIRST), initial);
+    assert_eq!(session.size(), initial + U256::from(2));
+
+    let output = session.output(ArbAddressTable::lookupCall { addr: SECOND }.abi_encode());
+    assert_eq!(
+        ArbAddressTable::lookupCall::abi_decode_returns(&output).unwrap(),
+        initial + U256::from(1)
+    );
+    let output = session.output(
+        ArbAddressTable::lookupIndexCall {
+            index: initial + U256::from(1),
+        }
+        .abi_encode(),
+    );
+    assert_eq!(
+        ArbAddressTable::lookupIndexCall::abi_decode_returns(&output).unwrap(),
+        SECOND
+    );
+
+    for (addr, exists) in [(FIRST, true), (UNKNOWN, false)] {
+        let output = session.output(ArbAddressTable::addressExistsCall { addr }.abi_encode());
+        assert_eq!(
+            ArbAddressTable::addressExistsCall::abi_decode_returns(&output).unwrap(),
+            exists
+        );
+    }
+}
+
+#[test]
+fn test_unknown_addresses_and_indexes_revert() {
+    let mut session = Session::new();
+    let size = session.size();
+    match session.call(ArbAddressTable::lookupCall { addr: UNKNOWN }.abi_encode()) {
+        ExecutionResult::Revert { output, .. } => assert_eq!(
+            decode_revert_message(&output).as_deref(),
+            Some("address does not exist in AddressTable")
+        ),
+        other => panic!("lookup of an unknown address succeeded: {other:?}"),
+    }
+    assert!(matches!(
+        session.call(ArbAddressTable::lookupIndexCall { index: size }.abi_encode()),
+        ExecutionResult::Revert { .. }
+    ));
+}
+
+#[test]
+fn test_compress_uses_nitro_rlp_encoding() {
+    let mut session = Session::new();
+    let index: u64 = session.register(FIRST).try_into().unwrap();
+
+    // A registered address compresses to its index as an RLP integer
+    let compressed = session.compress(FIRST);
+    assert_eq!(compressed, Bytes::from(alloy_rlp::encode(index)));
+    assert!(compressed.len() <= 9);
+
+    // Any other address to its 20 bytes as an RLP string
+    let compressed = session.compress(UNKNOWN);
+    let mut expected = vec![0x94];
+    expected.extend_from_slice(UNKNOWN.as_slice());
+    assert_eq!(compressed, Bytes::from(expected));
+}
+
+#[test]
+fn test_decompress_round_trips_and_returns_bytes_read() {
+    let mut session = Session::new();
+    session.register(FIRST);
+    let registered = session.compress(FIRST);
+    let unregistered = session.compress(UNKNOWN);
+
+    // Both encodings back to back, after a byte of padding
+    let mut buf = vec![0xff];
+    buf.extend_from_slice(&registered);
+    buf.extend_from_slice(&unregistered);
+
+    assert_eq!(
+        decompressed(session.decompress(&buf, 1)),
+        (FIRST, U256::from(registered.len()))
+    );
+    assert_eq!(
+        decompressed(session.decompress(&buf, 1 + registered.len() as u64)),
+        (UNKNOWN, U256::from(21))
+    );
+}
+
+#[test]
+fn test_decompress_rejects_bad_input() {
+    let mut session = Session::new();
+    let size: u64 = session.size().try_into().unwrap();
+
+    // Offsets past the end of the buffer
+    assert!(matches!(
+        session.decompress(&[0x80], 2),
+        ExecutionResult::Revert { .. }
+    ));
+    // Indexes not in the table
+    assert!(matches!(
+        session.decompress(&alloy_rlp::encode(size + 5), 0),
+        ExecutionResult::Revert { .. }
+    ));
+    // Integers with leading zeroes
+    assert!(matches!(
+        session.decompress(&[0x82, 0x00, 0x01], 0),
+        ExecutionResult::Revert { .. }
+    ));
+    // Truncated addresses
+    assert!(matches!(
+        session.decompress(&[0x94, 0x01, 0x02], 0),
+        ExecutionResult::Revert { .. }
+    ));
+}
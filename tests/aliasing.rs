@@ -0,0 +1,54 @@
+//! Tests for L1-to-L2 address aliasing of messages from L1.
+
+use arbos_revm::{
+    ArbitrumTransaction,
+    aliasing::{apply_l1_to_l2_alias, undo_alias},
+};
+use revm::{
+    ExecuteEvm,
+    primitives::{Address, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    create_call_tx, create_evm, deploy_evm_code, fund_account, setup_context_with_arbos_state,
+};
+
+const L1_SENDER: Address = address!("0x0101010101010101010101010101010101010101");
+const L1_SENDER_ALIAS: Address = address!("0x1212010101010101010101010101010101011212");
+const ECHO_CALLER: Address = address!("0x00000000000000000000000000000000000000ca");
+
+/// Code that returns CALLER as a word.
+const RETURN_CALLER: &[u8] = &[0x33, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+
+#[test]
+fn test_alias_round_trips() {
+    assert_eq!(apply_l1_to_l2_alias(L1_SENDER), L1_SENDER_ALIAS);
+    assert_eq!(undo_alias(L1_SENDER_ALIAS), L1_SENDER);
+
+    // Aliasing wraps around the top of the address space
+    let high = address!("0xffffffffffffffffffffffffffffffffffffffff");
+    assert_eq!(
+        apply_l1_to_l2_alias(high),
+        address!("0x1111000000000000000000000000000000001110")
+    );
+    assert_eq!(undo_alias(apply_l1_to_l2_alias(high)), high);
+}
+
+#[test]
+fn test_l1_messages_execute_under_the_sender_alias() {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, L1_SENDER_ALIAS, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, ECHO_CALLER, RETURN_CALLER);
+    let mut evm = create_evm(context);
+
+    let tx = create_call_tx(ECHO_CALLER, Vec::new(), 100_000);
+    assert_eq!(tx.caller, L1_SENDER);
+    let result = evm
+        .transact_one(ArbitrumTransaction::new_from_l1(tx))
+        .expect("transaction execution failed");
+
+    assert!(result.is_success());
+    let caller = Address::from_slice(&result.output().unwrap()[12..32]);
+    assert_eq!(caller, L1_SENDER_ALIAS);
+}
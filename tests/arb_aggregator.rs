@@ -0,0 +1,189 @@
+//! Tests for managing batch posters and their fee collectors through ArbAggregator.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::{
+    constants::ARBOS_BATCH_POSTER_ADDRESS,
+    state::{ArbState, ArbStateGetter},
+};
+use revm::{
+    context::{TxEnv, result::ExecutionResult},
+    primitives::{Address, Bytes, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx_with_nonce, create_evm, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbAggregator {
+        function getDefaultAggregator() external view returns (address);
+        function getBatchPosters() external view returns (address[] memory);
+        function addBatchPoster(address newBatchPoster) external;
+        function getFeeCollector(address batchPoster) external view returns (address);
+        function setFeeCollector(address batchPoster, address newFeeCollector) external;
+    }
+}
+
+const ARB_AGGREGATOR: Address = address!("0x000000000000000000000000000000000000006d");
+const OWNER: Address = address!("0x0101010101010101010101010101010101010101");
+const POSTER: Address = address!("0x00000000000000000000000000000000000000b1");
+const COLLECTOR: Address = address!("0x00000000000000000000000000000000000000c1");
+const STRANGER: Address = address!("0x00000000000000000000000000000000000000e1");
+
+/// Calls ArbAggregator from accounts funded up front, tracking their nonces.
+struct Session {
+    evm: TestEvm,
+    nonces: Vec<(Address, u64)>,
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut context = setup_context_with_arbos_state();
+        for account in [OWNER, POSTER, COLLECTOR, STRANGER] {
+            fund_account(&mut context, account, U256::from(1_000_000_000_u64));
+        }
+        context
+            .arb_state(None, false)
+            .chain_owners()
+            .add(OWNER)
+            .expect("failed to add chain owner");
+        Self {
+            evm: create_evm(context),
+            nonces: Vec::new(),
+        }
+    }
+
+    fn call(&mut self, caller: Address, calldata: Vec<u8>) -> ExecutionResult {
+        let index = match self
+            .nonces
+            .iter()
+            .position(|(account, _)| *account == caller)
+        {
+            Some(index) => index,
+            None => {
+                self.nonces.push((caller, 0));
+                self.nonces.len() - 1
+            }
+        };
+        let nonce = self.nonces[index].1;
+        self.nonces[index].1 += 1;
+        let tx = TxEnv {
+            caller,
+            ..create_call_tx_with_nonce(ARB_AGGREGATOR, calldata, 1_000_000, nonce)
+        };
+        execute_tx(&mut self.evm, tx)
+    }
+
+    fn output(&mut self, caller: Address, calldata: Vec<u8>) -> Bytes {
+        match self.call(caller, calldata) {
+            ExecutionResult::Success { output, .. } => output.into_data(),
+            other => panic!("call failed: {other:?}"),
+        }
+    }
+
+    fn batch_posters(&mut self) -> Vec<Address> {
+        let output = self.output(STRANGER, ArbAggregator::getBatchPostersCall {}.abi_encode());
+        ArbAggregator::getBatchPostersCall::abi_decode_returns(&output).unwrap()
+    }
+
+    fn fee_collector(&mut self, batch_poster: Address) -> ExecutionResult {
+        self.call(
+            STRANGER,
+            ArbAggregator::getFeeCollectorCall {
+                batchPoster: batch_poster,
+            }
+            .abi_encode(),
+        )
+    }
+
+    fn set_fee_collector(&mut self, caller: Address, collector: Address) -> ExecutionResult {
+        self.call(
+            caller,
+            ArbAggregator::setFeeCollectorCall {
+                batchPoster: POSTER,
+                newFeeCollector: collector,
+            }
+            .abi_encode(),
+        )
+    }
+}
+
+fn add_batch_poster(batch_poster: Address) -> Vec<u8> {
+    ArbAggregator::addBatchPosterCall {
+        newBatchPoster: batch_poster,
+    }
+    .abi_encode()
+}
+
+fn returned_address(result: ExecutionResult) -> Address {
+    match result {
+        ExecutionResult::Success { output, .. } => {
+            ArbAggregator::getFeeCollectorCall::abi_decode_returns(output.data()).unwrap()
+        }
+        other => panic!("call failed: {other:?}"),
+    }
+}
+
+#[test]
+fn test_owner_adds_batch_posters() {
+    let mut session = Session::new();
+    let output = session.output(
+        STRANGER,
+        ArbAggregator::getDefaultAggregatorCall {}.abi_encode(),
+    );
+    assert_eq!(
+        ArbAggregator::getDefaultAggregatorCall::abi_decode_returns(&output).unwrap(),
+        ARBOS_BATCH_POSTER_ADDRESS
+    );
+
+    assert!(matches!(
+        session.call(STRANGER, add_batch_poster(POSTER)),
+        ExecutionResult::Revert { .. }
+    ));
+    assert!(!session.batch_posters().contains(&POSTER));
+
+    // Adding a poster twice leaves a single entry
+    for _ in 0..2 {
+        assert!(session.call(OWNER, add_batch_poster(POSTER)).is_success());
+    }
+    let posters = session.batch_posters();
+    assert_eq!(
+        posters.iter().filter(|poster| **poster == POSTER).count(),
+        1
+    );
+    assert_eq!(returned_address(session.fee_collector(POSTER)), POSTER);
+}
+
+#[test]
+fn test_fee_collector_of_unknown_poster_reverts() {
+    let mut session = Session::new();
+    assert!(matches!(
+        session.fee_collector(POSTER),
+        ExecutionResult::Revert { .. }
+    ));
+    assert!(matches!(
+        session.set_fee_collector(OWNER, COLLECTOR),
+        ExecutionResult::Revert { .. }
+    ));
+}
+
+#[test]
+fn test_fee_collector_changes_are_authorized() {
+    let mut session = Session::new();
+    assert!(session.call(OWNER, add_batch_poster(POSTER)).is_success());
+
+    assert!(matches!(
+        session.set_fee_collector(STRANGER, STRANGER),
+        ExecutionResult::Revert { .. }
+    ));
+
+    // The poster, then its new collector, then an owner may redirect the fees
+    assert!(session.set_fee_collector(POSTER, COLLECTOR).is_success());
+    assert_eq!(returned_address(session.fee_collector(POSTER)), COLLECTOR);
+    assert!(session.set_fee_collector(COLLECTOR, STRANGER).is_success());
+    assert_eq!(returned_address(session.fee_collector(POSTER)), STRANGER);
+    assert!(session.set_fee_collector(OWNER, POSTER).is_success());
+    assert_eq!(returned_address(session.fee_collector(POSTER)), POSTER);
+}
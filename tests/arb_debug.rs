@@ -0,0 +1,145 @@
+//! Tests for the debug-only ArbDebug precompile.
+
+use alloy_sol_types::{SolCall, SolError, SolEvent, sol};
+use revm::{
+    context::result::ExecutionResult,
+    primitives::{Address, B256, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    create_call_tx, create_call_tx_with_value, create_evm, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbDebug {
+        function events(bool flag, bytes32 value) external payable returns (address, uint256);
+        function eventsView() external view;
+        function customRevert(uint64 number) external pure;
+        function panic() external;
+        function legacyError() external pure;
+
+        event Basic(bool flag, bytes32 indexed value);
+        event Mixed(
+            bool indexed flag, bool not, bytes32 indexed value, address conn, address indexed caller
+        );
+
+        error Custom(uint64, string, bool);
+    }
+}
+
+const ARB_DEBUG: Address = address!("0x00000000000000000000000000000000000000ff");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+
+fn call(debug_mode: bool, calldata: Vec<u8>, value: U256) -> ExecutionResult {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.debug_mode = debug_mode;
+    fund_account(&mut context, CALLER, U256::from(10u128.pow(18)));
+    let mut evm = create_evm(context);
+
+    let tx = if value.is_zero() {
+        create_call_tx(ARB_DEBUG, calldata, 1_000_000)
+    } else {
+        create_call_tx_with_value(ARB_DEBUG, calldata, value, 1_000_000)
+    };
+    execute_tx(&mut evm, tx)
+}
+
+#[test]
+fn test_events_emits_basic_and_mixed() {
+    let value = B256::repeat_byte(0x42);
+    let paid = U256::from(1234);
+    let calldata = ArbDebug::eventsCall { flag: true, value }.abi_encode();
+
+    let ExecutionResult::Success {
+        logs,
+        output,
+        gas_used,
+        ..
+    } = call(true, calldata, paid)
+    else {
+        panic!("events failed");
+    };
+
+    let ret = ArbDebug::eventsCall::abi_decode_returns(output.data()).unwrap();
+    assert_eq!((ret._0, ret._1), (CALLER, paid));
+
+    assert_eq!(logs.len(), 2);
+    assert!(logs.iter().all(|log| log.address == ARB_DEBUG));
+
+    let basic = ArbDebug::Basic::decode_log_data(&logs[0].data).unwrap();
+    assert!(!basic.flag);
+    assert_eq!(basic.value, value);
+
+    let mixed = ArbDebug::Mixed::decode_log_data(&logs[1].data).unwrap();
+    assert!(mixed.flag && !mixed.not);
+    assert_eq!(mixed.value, value);
+    assert_eq!(mixed.conn, ARB_DEBUG);
+    assert_eq!(mixed.caller, CALLER);
+
+    // Both logs are paid for: 375 per log, 375 per topic and 8 per byte of data
+    let log_gas = (375 + 2 * 375 + 8 * 32) + (375 + 4 * 375 + 8 * 96);
+    assert!(gas_used >= 21_000 + log_gas);
+}
+
+#[test]
+fn test_events_view_fails_to_emit() {
+    let result = call(true, ArbDebug::eventsViewCall {}.abi_encode(), U256::ZERO);
+
+    let ExecutionResult::Revert { output, .. } = result else {
+        panic!("eventsView did not revert: {result:?}");
+    };
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_custom_revert() {
+    let calldata = ArbDebug::customRevertCall { number: 7 }.abi_encode();
+
+    let ExecutionResult::Revert { output, .. } = call(true, calldata, U256::ZERO) else {
+        panic!("customRevert did not revert");
+    };
+    let error = ArbDebug::Custom::abi_decode(&output).unwrap();
+    assert_eq!(error._0, 7);
+    assert_eq!(
+        error._1,
+        r"This spider family wards off bugs: /\oo/\ //\(oo)//\ /\oo/\"
+    );
+    assert!(error._2);
+}
+
+#[test]
+fn test_legacy_error_reverts_without_data() {
+    let result = call(true, ArbDebug::legacyErrorCall {}.abi_encode(), U256::ZERO);
+
+    let ExecutionResult::Revert { output, gas_used } = result else {
+        panic!("legacyError did not revert: {result:?}");
+    };
+    assert!(output.is_empty());
+    assert!(gas_used < 1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "called ArbDebug's debug-only Panic method")]
+fn test_panic_in_debug_mode() {
+    call(true, ArbDebug::panicCall {}.abi_encode(), U256::ZERO);
+}
+
+#[test]
+fn test_disabled_outside_debug_mode() {
+    for calldata in [
+        ArbDebug::eventsCall {
+            flag: true,
+            value: B256::ZERO,
+        }
+        .abi_encode(),
+        ArbDebug::panicCall {}.abi_encode(),
+    ] {
+        let result = call(false, calldata, U256::ZERO);
+
+        assert!(matches!(result, ExecutionResult::Revert { .. }));
+        assert_eq!(result.gas_used(), 1_000_000);
+        assert!(result.logs().is_empty());
+    }
+}
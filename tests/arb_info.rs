@@ -0,0 +1,108 @@
+//! Tests for the ArbInfo precompile reading account balances and code.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::constants::STYLUS_DISCRIMINANT;
+use revm::{
+    context::{ContextTr, JournalTr, result::ExecutionResult},
+    primitives::{Address, Bytes, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    create_call_tx, create_evm, deploy_evm_code, deploy_wat_program, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbInfo {
+        function getBalance(address account) external view returns (uint256);
+        function getCode(address account) external view returns (bytes memory);
+    }
+}
+
+const ARB_INFO: Address = address!("0x0000000000000000000000000000000000000065");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const PROBE: Address = address!("0x00000000000000000000000000000000000c0de0");
+
+/// Forwards its calldata to ArbInfo, then returns the gas the BALANCE opcode and its
+/// surrounding stack shuffling took for the account in the calldata's first argument.
+const BALANCE_PROBE: &[u8] = &[
+    0x60, 0x24, 0x60, 0x00, 0x60, 0x00, 0x37, // CALLDATACOPY(0, 0, 36)
+    0x60, 0x20, 0x60, 0x00, 0x60, 0x24, 0x60, 0x00, 0x60, 0x65, 0x5a, 0xfa, // STATICCALL
+    0x50, // POP
+    0x60, 0x04, 0x35, // CALLDATALOAD(4)
+    0x5a, 0x90, 0x31, 0x50, 0x5a, 0x90, 0x03, // GAS SWAP1 BALANCE POP GAS SWAP1 SUB
+    0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3, // MSTORE(0), RETURN(0, 32)
+];
+
+fn call(to: Address, calldata: Vec<u8>) -> ExecutionResult {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, PROBE, BALANCE_PROBE);
+    let mut evm = create_evm(context);
+    execute_tx(&mut evm, create_call_tx(to, calldata, 1_000_000))
+}
+
+fn output(result: ExecutionResult) -> Bytes {
+    match result {
+        ExecutionResult::Success { output, .. } => output.into_data(),
+        other => panic!("call failed: {other:?}"),
+    }
+}
+
+#[test]
+fn test_get_balance() {
+    let calldata = ArbInfo::getBalanceCall { account: CALLER }.abi_encode();
+    let balance =
+        ArbInfo::getBalanceCall::abi_decode_returns(&output(call(ARB_INFO, calldata))).unwrap();
+
+    // The caller's funds, less whatever it paid for the transaction's gas
+    assert!(balance > U256::ZERO && balance <= U256::from(1_000_000_000_u64));
+}
+
+#[test]
+fn test_get_balance_leaves_the_account_cold() {
+    let account = Address::repeat_byte(0xab);
+    let calldata = ArbInfo::getBalanceCall { account }.abi_encode();
+    let cost = U256::from_be_slice(&output(call(PROBE, calldata)));
+
+    // BALANCE still pays for a cold account after ArbInfo read it
+    assert!(cost >= U256::from(2600));
+
+    let calldata = ArbInfo::getBalanceCall { account: CALLER }.abi_encode();
+    let cost = U256::from_be_slice(&output(call(PROBE, calldata)));
+    assert!(cost < U256::from(2600));
+}
+
+#[test]
+fn test_get_code_of_a_stylus_program() {
+    let mut context = setup_context_with_arbos_state();
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/storage.wat"));
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    let expected = context.journal_mut().code(program).unwrap().data;
+    let mut evm = create_evm(context);
+
+    let calldata = ArbInfo::getCodeCall { account: program }.abi_encode();
+    let result = execute_tx(&mut evm, create_call_tx(ARB_INFO, calldata, 1_000_000));
+    let code = ArbInfo::getCodeCall::abi_decode_returns(&output(result)).unwrap();
+
+    assert!(code.starts_with(STYLUS_DISCRIMINANT));
+    assert_eq!(code, expected);
+}
+
+#[test]
+fn test_get_code_charges_per_word() {
+    let gas_used = |account| {
+        let calldata = ArbInfo::getCodeCall { account }.abi_encode();
+        call(ARB_INFO, calldata).gas_used()
+    };
+
+    // The empty account's address has as many zero bytes as the probe's, so their calldata
+    // costs the same
+    let empty = gas_used(address!("0x00000000000000000000000000000000000c0de1"));
+    let probe = gas_used(PROBE);
+
+    // 3 gas per word copying the code, and 3 more per word returning it
+    let words = BALANCE_PROBE.len().div_ceil(32) as u64;
+    assert_eq!(probe - empty, 6 * words);
+}
@@ -0,0 +1,341 @@
+//! Tests for the ArbSys precompile, one per method.
+
+use alloy_sol_types::{SolCall, SolError, sol};
+use arbos_revm::{
+    ArbitrumEvm, ArbitrumTransaction,
+    inspector::{PrecompileInspector, StylusInspector},
+    instructions::arbitrum_instructions,
+    precompiles::ArbitrumPrecompileProvider,
+};
+use revm::{
+    Database, ExecuteEvm, InspectEvm, Inspector,
+    context::{ContextTr, JournalTr, TxEnv, result::ExecutionResult},
+    interpreter::{CallInputs, CallOutcome, Gas, InstructionResult, InterpreterResult},
+    primitives::{Address, B256, Bytes, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, TestEvm, create_call_tx, create_evm, deploy_evm_code, fund_account,
+    setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbSys {
+        function arbBlockNumber() external view returns (uint256);
+        function arbBlockHash(uint256 arbBlockNum) external view returns (bytes32);
+        function arbChainID() external view returns (uint256);
+        function isTopLevelCall() external view returns (bool);
+        function wasMyCallersAddressAliased() external view returns (bool);
+        function myCallersAddressWithoutAliasing() external view returns (address);
+        function withdrawEth(address destination) external payable returns (uint256);
+
+        error InvalidBlockNumber(uint256 requested, uint256 current);
+    }
+}
+
+const ARB_SYS: Address = address!("0x0000000000000000000000000000000000000064");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const CALLER_ALIAS: Address = address!("0x1212010101010101010101010101010101011212");
+const BLOCK_NUMBER: u64 = 300;
+const PROBE: Address = address!("0x00000000000000000000000000000000000000b0");
+const RELAY: Address = address!("0x00000000000000000000000000000000000000b1");
+const OUTER_RELAY: Address = address!("0x00000000000000000000000000000000000000b2");
+const MOCKED: Address = address!("0x00000000000000000000000000000000000000b3");
+
+fn setup(arbos_version: u16) -> TestContext {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.arbos_version = arbos_version;
+    context.block.number = U256::from(BLOCK_NUMBER);
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    fund_account(&mut context, CALLER_ALIAS, U256::from(1_000_000_000_u64));
+    context
+}
+
+fn transact(evm: &mut TestEvm, tx: impl Into<ArbitrumTransaction>) -> ExecutionResult {
+    evm.transact_one(tx.into())
+        .expect("transaction execution failed")
+}
+
+fn call_tx(calldata: impl SolCall) -> TxEnv {
+    create_call_tx(ARB_SYS, calldata.abi_encode(), 1_000_000)
+}
+
+/// Output of a successful call to ArbSys.
+fn call(evm: &mut TestEvm, tx: impl Into<ArbitrumTransaction>) -> Bytes {
+    match transact(evm, tx) {
+        ExecutionResult::Success { output, .. } => output.into_data(),
+        other => panic!("ArbSys call failed: {:?}", other),
+    }
+}
+
+#[test]
+fn test_arb_block_number() {
+    let mut evm = create_evm(setup(42));
+
+    let output = call(&mut evm, call_tx(ArbSys::arbBlockNumberCall {}));
+    assert_eq!(U256::from_be_slice(&output), U256::from(BLOCK_NUMBER));
+}
+
+#[test]
+fn test_arb_block_hash() {
+    let mut evm = create_evm(setup(42));
+
+    let output = call(
+        &mut evm,
+        call_tx(ArbSys::arbBlockHashCall {
+            arbBlockNum: U256::from(BLOCK_NUMBER - 1),
+        }),
+    );
+    let expected = evm
+        .0
+        .ctx
+        .journal_mut()
+        .db_mut()
+        .block_hash(BLOCK_NUMBER - 1)
+        .unwrap();
+    assert_eq!(B256::from_slice(&output), expected);
+}
+
+#[test]
+fn test_arb_block_hash_rejects_blocks_out_of_range() {
+    let request = |arbos_version: u16, block: u64| {
+        let mut evm = create_evm(setup(arbos_version));
+        transact(
+            &mut evm,
+            call_tx(ArbSys::arbBlockHashCall {
+                arbBlockNum: U256::from(block),
+            }),
+        )
+    };
+
+    // Since ArbOS 33 with a Solidity error
+    let expected = ArbSys::InvalidBlockNumber {
+        requested: U256::from(BLOCK_NUMBER),
+        current: U256::from(BLOCK_NUMBER),
+    }
+    .abi_encode();
+    for block in [BLOCK_NUMBER, BLOCK_NUMBER - 257] {
+        let result = request(42, block);
+        assert!(matches!(result, ExecutionResult::Revert { .. }));
+        if block == BLOCK_NUMBER {
+            assert_eq!(result.output().unwrap().to_vec(), expected);
+        }
+    }
+
+    // Before it with Nitro's plain error, which consumed all gas before ArbOS 11
+    let result = request(20, BLOCK_NUMBER);
+    assert!(matches!(result, ExecutionResult::Revert { ref output, .. } if output.is_empty()));
+    assert!(result.gas_used() < 1_000_000);
+    let result = request(10, BLOCK_NUMBER);
+    assert!(matches!(result, ExecutionResult::Revert { .. }));
+    assert_eq!(result.gas_used(), 1_000_000);
+}
+
+#[test]
+fn test_arb_chain_id() {
+    let mut context = setup(42);
+    context.cfg.inner.chain_id = 42_161;
+    let mut evm = create_evm(context);
+
+    let output = call(&mut evm, call_tx(ArbSys::arbChainIDCall {}));
+    assert_eq!(U256::from_be_slice(&output), U256::from(42_161));
+}
+
+#[test]
+fn test_was_my_callers_address_aliased() {
+    let mut evm = create_evm(setup(42));
+
+    let output = call(&mut evm, call_tx(ArbSys::wasMyCallersAddressAliasedCall {}));
+    assert_eq!(U256::from_be_slice(&output), U256::ZERO);
+
+    let tx = ArbitrumTransaction::new_from_l1(call_tx(ArbSys::wasMyCallersAddressAliasedCall {}));
+    let output = call(&mut evm, tx);
+    assert_eq!(U256::from_be_slice(&output), U256::ONE);
+}
+
+#[test]
+fn test_was_my_callers_address_aliased_before_arbos_6() {
+    let mut context = setup(5);
+    let probe = probe_code(&[ArbSys::wasMyCallersAddressAliasedCall::SELECTOR]);
+    deploy_evm_code(&mut context, PROBE, &probe);
+    let mut evm = create_evm(context);
+
+    // Only a contract the transaction calls counts as top level, not the transaction itself
+    let tx = ArbitrumTransaction::new_from_l1(call_tx(ArbSys::wasMyCallersAddressAliasedCall {}));
+    let output = call(&mut evm, tx);
+    assert_eq!(U256::from_be_slice(&output), U256::ZERO);
+
+    let tx = ArbitrumTransaction::new_from_l1(create_call_tx(PROBE, vec![], 1_000_000));
+    let output = call(&mut evm, tx);
+    assert_eq!(U256::from_be_slice(&output), U256::ONE);
+}
+
+#[test]
+fn test_my_callers_address_without_aliasing() {
+    let mut evm = create_evm(setup(42));
+
+    let output = call(
+        &mut evm,
+        call_tx(ArbSys::myCallersAddressWithoutAliasingCall {}),
+    );
+    assert_eq!(Address::from_slice(&output[12..]), CALLER);
+
+    // L1 messages run as the alias, which is undone
+    let tx =
+        ArbitrumTransaction::new_from_l1(call_tx(ArbSys::myCallersAddressWithoutAliasingCall {}));
+    let output = call(&mut evm, tx);
+    assert_eq!(Address::from_slice(&output[12..]), CALLER);
+}
+
+#[test]
+fn test_withdraw_eth_burns_the_value() {
+    let mut evm = create_evm(setup(42));
+
+    let mut tx = call_tx(ArbSys::withdrawEthCall {
+        destination: Address::repeat_byte(0xd1),
+    });
+    tx.value = U256::from(1_000);
+    let output = call(&mut evm, tx);
+    assert_eq!(U256::from_be_slice(&output), U256::ZERO);
+
+    let balance = evm
+        .0
+        .ctx
+        .journal_mut()
+        .load_account(ARB_SYS)
+        .unwrap()
+        .data
+        .info
+        .balance;
+    assert!(balance.is_zero());
+}
+
+/// EVM runtime code that calls ArbSys with each of `selectors` in turn and returns the return
+/// data of the last call.
+fn probe_code(selectors: &[[u8; 4]]) -> Vec<u8> {
+    let mut code = Vec::new();
+    for selector in selectors {
+        code.push(0x63); // PUSH4 selector
+        code.extend_from_slice(selector);
+        code.extend_from_slice(&[0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52]); // mem[0..4] = selector
+        code.extend_from_slice(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x04, 0x60, 0x00, 0x60, 0x00, 0x60, 0x64, 0x5a, 0xf1,
+            0x50, // call ArbSys with it
+        ]);
+    }
+    code.extend_from_slice(&[0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, 0x3d, 0x60, 0x00, 0xf3]);
+    code
+}
+
+/// EVM runtime code that calls `target` and returns its return data.
+fn relay_code(target: Address) -> Vec<u8> {
+    let mut code = vec![
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73,
+    ];
+    code.extend_from_slice(target.as_slice());
+    code.extend_from_slice(&[
+        0x5a, 0xf1, 0x50, // call target
+        0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, 0x3d, 0x60, 0x00, 0xf3, // return the return data
+    ]);
+    code
+}
+
+/// Output of asking ArbSys `query` from a contract called through `relays` relays, after it
+/// called ArbSys once already.
+fn query_after_precompile_call(query: [u8; 4], relays: usize) -> Bytes {
+    let mut context = setup(42);
+    let selectors = [ArbSys::arbBlockNumberCall::SELECTOR, query];
+    deploy_evm_code(&mut context, PROBE, &probe_code(&selectors));
+    deploy_evm_code(&mut context, RELAY, &relay_code(PROBE));
+    deploy_evm_code(&mut context, OUTER_RELAY, &relay_code(RELAY));
+    let mut evm = create_evm(context);
+
+    let entry = [PROBE, RELAY, OUTER_RELAY][relays];
+    call(&mut evm, create_call_tx(entry, vec![], 1_000_000))
+}
+
+#[test]
+fn test_is_top_level_call() {
+    let mut evm = create_evm(setup(42));
+
+    let output = call(&mut evm, call_tx(ArbSys::isTopLevelCallCall {}));
+    assert_eq!(U256::from_be_slice(&output), U256::ONE);
+}
+
+#[test]
+fn test_is_top_level_call_after_precompile_call() {
+    let query = ArbSys::isTopLevelCallCall::SELECTOR;
+
+    // Called by the transaction's own frame
+    let output = query_after_precompile_call(query, 0);
+    assert_eq!(U256::from_be_slice(&output), U256::ONE);
+
+    // The earlier precompile call leaves the depth of later ones as it was
+    for relays in [1, 2] {
+        let output = query_after_precompile_call(query, relays);
+        assert_eq!(U256::from_be_slice(&output), U256::ZERO, "{relays} relays");
+    }
+}
+
+#[test]
+fn test_my_callers_address_after_precompile_call() {
+    let query = ArbSys::myCallersAddressWithoutAliasingCall::SELECTOR;
+
+    let output = query_after_precompile_call(query, 1);
+    assert_eq!(Address::from_slice(&output[12..]), RELAY);
+
+    let output = query_after_precompile_call(query, 2);
+    assert_eq!(Address::from_slice(&output[12..]), RELAY);
+}
+
+/// Answers calls to [`MOCKED`] itself, as cheatcodes and mocks do.
+struct Mocker;
+
+impl Inspector<TestContext> for Mocker {
+    fn call(&mut self, _context: &mut TestContext, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        (inputs.target_address == MOCKED).then(|| {
+            CallOutcome::new(
+                InterpreterResult::new(
+                    InstructionResult::Return,
+                    Bytes::new(),
+                    Gas::new(inputs.gas_limit),
+                ),
+                inputs.return_memory_offset.clone(),
+            )
+        })
+    }
+}
+
+impl StylusInspector<TestContext> for Mocker {}
+
+impl PrecompileInspector<TestContext> for Mocker {}
+
+#[test]
+fn test_my_callers_address_after_mocked_call() {
+    let mut context = setup(42);
+    // Call MOCKED, dropping the relay code's return, then ask ArbSys
+    let mut probe = relay_code(MOCKED);
+    probe.truncate(probe.len() - 10);
+    probe.extend(probe_code(&[
+        ArbSys::myCallersAddressWithoutAliasingCall::SELECTOR,
+    ]));
+    deploy_evm_code(&mut context, PROBE, &probe);
+    deploy_evm_code(&mut context, RELAY, &relay_code(PROBE));
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        Mocker,
+        arbitrum_instructions(),
+        ArbitrumPrecompileProvider::default(),
+    );
+
+    let result = evm
+        .inspect_one_tx(create_call_tx(RELAY, vec![], 1_000_000).into())
+        .expect("transaction execution failed");
+    match result {
+        ExecutionResult::Success { output, .. } => {
+            assert_eq!(Address::from_slice(&output.data()[12..]), RELAY);
+        }
+        other => panic!("call failed: {other:?}"),
+    }
+}
@@ -0,0 +1,75 @@
+//! Tests for the gas charged for reads of ArbOS state.
+
+use arbos_revm::state::{ArbState, ArbStateGetter, types::StorageBackedTr};
+use revm::{
+    context::{ContextTr, JournalTr},
+    interpreter::{
+        Gas,
+        gas::{COLD_SLOAD_COST, ISTANBUL_SLOAD_GAS, WARM_STORAGE_READ_COST},
+    },
+};
+
+mod test_utils;
+use test_utils::{TestContext, setup_context_with_arbos_state};
+
+/// ArbOS state with access costs applying from `from`, and no slot warm yet.
+fn setup(from: Option<u16>) -> TestContext {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.arbos_storage_access_costs_from = from;
+    context.journal_mut().commit_tx();
+    context
+}
+
+/// Gas charged for reading the L2 basefee.
+fn read_base_fee(context: &mut TestContext) -> u64 {
+    let mut gas = Gas::new(100_000);
+    context
+        .arb_state(Some(&mut gas), true)
+        .l2_pricing()
+        .base_fee_wei()
+        .get()
+        .unwrap();
+    gas.spent()
+}
+
+#[test]
+fn test_reads_are_flat_by_default() {
+    let mut context = setup(None);
+    assert_eq!(read_base_fee(&mut context), ISTANBUL_SLOAD_GAS);
+    assert_eq!(read_base_fee(&mut context), ISTANBUL_SLOAD_GAS);
+}
+
+#[test]
+fn test_reads_are_cold_then_warm_once_enabled() {
+    let version = setup(None).cfg.arbos_version;
+    let mut context = setup(Some(version));
+    assert_eq!(read_base_fee(&mut context), COLD_SLOAD_COST);
+    assert_eq!(read_base_fee(&mut context), WARM_STORAGE_READ_COST);
+
+    // The next transaction starts with every slot cold again
+    context.journal_mut().commit_tx();
+    assert_eq!(read_base_fee(&mut context), COLD_SLOAD_COST);
+}
+
+#[test]
+fn test_reads_are_flat_before_the_configured_version() {
+    let version = setup(None).cfg.arbos_version;
+    let mut context = setup(Some(version + 1));
+    assert_eq!(read_base_fee(&mut context), ISTANBUL_SLOAD_GAS);
+    assert_eq!(read_base_fee(&mut context), ISTANBUL_SLOAD_GAS);
+}
+
+#[test]
+fn test_cold_reads_run_out_of_gas() {
+    let version = setup(None).cfg.arbos_version;
+    let mut context = setup(Some(version));
+    let mut gas = Gas::new(COLD_SLOAD_COST - 1);
+    assert!(
+        context
+            .arb_state(Some(&mut gas), true)
+            .l2_pricing()
+            .base_fee_wei()
+            .get()
+            .is_err()
+    );
+}
@@ -0,0 +1,55 @@
+//! Tests for the ArbosTest precompile burning gas.
+
+use alloy_sol_types::{SolCall, sol};
+use revm::{
+    context::result::ExecutionResult,
+    primitives::{Address, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    create_call_tx, create_evm, execute_tx, fund_account, setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbosTest {
+        function burnArbGas(uint256 gasAmount) external pure;
+    }
+}
+
+const ARBOS_TEST: Address = address!("0x0000000000000000000000000000000000000069");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+
+fn burn(amount: U256, gas_limit: u64) -> ExecutionResult {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    let mut evm = create_evm(context);
+
+    let calldata = ArbosTest::burnArbGasCall { gasAmount: amount }.abi_encode();
+    execute_tx(&mut evm, create_call_tx(ARBOS_TEST, calldata, gas_limit))
+}
+
+#[test]
+fn test_burn_arb_gas_charges_the_amount() {
+    let baseline = burn(U256::ZERO, 1_000_000);
+    let burned = burn(U256::from(50_000), 1_000_000);
+
+    assert!(baseline.is_success() && burned.is_success());
+    assert_eq!(burned.gas_used() - baseline.gas_used(), 50_000);
+}
+
+#[test]
+fn test_burn_arb_gas_beyond_the_limit_uses_all_gas() {
+    let result = burn(U256::from(10_000_000), 100_000);
+
+    assert!(result.is_success());
+    assert_eq!(result.gas_used(), 100_000);
+}
+
+#[test]
+fn test_burn_arb_gas_rejects_amounts_over_u64() {
+    let result = burn(U256::from(u64::MAX) + U256::ONE, 100_000);
+
+    assert!(matches!(result, ExecutionResult::Revert { .. }));
+    assert!(result.gas_used() < 100_000);
+}
@@ -0,0 +1,65 @@
+//! Tests for selecting the ArbOS version from configuration or state.
+
+use arbos_revm::state::{ArbState, ArbStateGetter, types::StorageBackedTr};
+use revm::{
+    context::result::ExecutionResult,
+    primitives::{Address, U256, address, keccak256},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, create_call_tx, create_evm, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+const ARB_SYS: Address = address!("0x0000000000000000000000000000000000000064");
+
+/// Calls `ArbSys.arbOSVersion()`, which reports the version offset by 55.
+fn reported_arbos_version(context: TestContext) -> u64 {
+    let mut context = context;
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(1_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let calldata = keccak256("arbOSVersion()")[..4].to_vec();
+    let tx = create_call_tx(ARB_SYS, calldata, 1_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            U256::from_be_slice(output.data().as_ref()).to::<u64>() - 55
+        }
+        other => panic!("arbOSVersion failed: {:?}", other),
+    }
+}
+
+fn context_with_state_version(version: u64) -> TestContext {
+    let mut context = setup_context_with_arbos_state();
+    context
+        .arb_state(None, false)
+        .arbos_version()
+        .set(version)
+        .expect("failed to set ArbOS version");
+    context
+}
+
+#[test]
+fn test_config_version_used_by_default() {
+    let context = context_with_state_version(20);
+    let configured = context.cfg.arbos_version;
+    assert_eq!(reported_arbos_version(context), u64::from(configured));
+}
+
+#[test]
+fn test_state_version_used_when_enabled() {
+    let mut context = context_with_state_version(20);
+    context.cfg.arbos_version_from_state = true;
+    assert_eq!(reported_arbos_version(context), 20);
+}
+
+#[test]
+fn test_config_version_used_for_uninitialized_state() {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.arbos_version_from_state = true;
+    let configured = context.cfg.arbos_version;
+    assert_eq!(reported_arbos_version(context), u64::from(configured));
+}
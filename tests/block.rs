@@ -0,0 +1,106 @@
+//! Tests for executing whole L2 blocks.
+
+use arbos_revm::{
+    ArbitrumTransactionError,
+    block::StartBlockInfo,
+    constants::ARBITRUM_INTERNAL_TX_TYPE,
+    state::{ArbState, ArbStateGetter, types::StorageBackedTr},
+};
+use revm::{
+    context::{BlockEnv, result::InvalidTransaction},
+    primitives::{Address, U256},
+};
+
+mod test_utils;
+use test_utils::{
+    create_call_tx_with_nonce, create_evm, fund_account, setup_context_with_arbos_state,
+};
+
+fn block_env() -> BlockEnv {
+    BlockEnv {
+        number: U256::from(7),
+        timestamp: U256::from(1_000),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_block_runs_start_block_and_transactions() {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    let mut evm = create_evm(context);
+
+    let to = Address::repeat_byte(0x42);
+    let txs = [
+        create_call_tx_with_nonce(to, Vec::new(), 100_000, 0),
+        // Skips a nonce, so it is left out of the block
+        create_call_tx_with_nonce(to, Vec::new(), 100_000, 5),
+        create_call_tx_with_nonce(to, Vec::new(), 100_000, 1),
+    ];
+    let start_block = StartBlockInfo {
+        l1_base_fee: U256::ZERO,
+        l1_block_number: 5,
+        time_passed: 0,
+    };
+    let result = evm
+        .transact_block(txs.map(Into::into), block_env(), start_block)
+        .expect("block execution failed");
+
+    let [start, first, second] = result.receipts.as_slice() else {
+        panic!("expected 3 receipts, got {}", result.receipts.len());
+    };
+    assert_eq!(start.tx_type, ARBITRUM_INTERNAL_TX_TYPE);
+    assert!(start.result.is_success());
+    assert!(first.result.is_success() && second.result.is_success());
+    assert_eq!(second.cumulative_gas_used, 2 * 21_000);
+    assert_eq!(result.gas_used, 2 * 21_000);
+
+    assert!(matches!(
+        result.rejected.as_slice(),
+        [(
+            1,
+            ArbitrumTransactionError::Base(InvalidTransaction::NonceTooHigh { .. })
+        )]
+    ));
+
+    assert_eq!(result.pricing.block_number, 7);
+    assert_eq!(result.pricing.backlog_delta(), 2 * 21_000);
+    assert_eq!(result.header_info.l1_block_number, 5);
+    assert_eq!(result.header_info.send_count, 0);
+    let arbos_version = evm
+        .0
+        .ctx
+        .arb_state(None, true)
+        .arbos_version()
+        .get()
+        .unwrap();
+    assert_eq!(result.header_info.arbos_version, arbos_version);
+}
+
+#[test]
+fn test_start_block_drains_backlog() {
+    let mut context = setup_context_with_arbos_state();
+    let mut arb_state = context.arb_state(None, false);
+    let mut l2_pricing = arb_state.l2_pricing();
+    l2_pricing.speed_limit_per_second().set(1_000).unwrap();
+    l2_pricing.gas_backlog().set(50_000).unwrap();
+    drop(arb_state);
+    let mut evm = create_evm(context);
+
+    let start_block = StartBlockInfo {
+        time_passed: 20,
+        ..Default::default()
+    };
+    let result = evm
+        .transact_block([], block_env(), start_block)
+        .expect("block execution failed");
+
+    assert_eq!(result.receipts.len(), 1);
+    assert_eq!(result.gas_used, 0);
+    assert_eq!(result.pricing.backlog_before(), 50_000);
+    assert_eq!(result.pricing.backlog_after(), 30_000);
+}
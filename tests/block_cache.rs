@@ -0,0 +1,82 @@
+//! Tests for the block cache of recently called Stylus programs.
+
+use arbos_revm::local_context::RecentWasms;
+use revm::primitives::{Address, B256, U256};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx_with_nonce, create_evm, deploy_wat_program, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+#[test]
+fn test_repeat_calls_in_a_block_are_charged_cached_init_gas() {
+    let mut context = setup_context_with_arbos_state();
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/storage.wat"));
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    let mut evm = create_evm(context);
+
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    let call = |evm: &mut TestEvm, nonce| {
+        let result = execute_tx(
+            evm,
+            create_call_tx_with_nonce(program, args.clone(), 10_000_000, nonce),
+        );
+        assert!(result.is_success());
+        result.gas_used()
+    };
+
+    let first = call(&mut evm, 0);
+    let second = call(&mut evm, 1);
+    assert!(
+        second < first,
+        "repeat call charged {second}, first {first}"
+    );
+    assert_eq!(evm.recent_wasms().first_uses(), 1);
+    assert_eq!(evm.recent_wasms().discounted(), 1);
+
+    // A new block starts with an empty cache
+    evm.clear_recent_wasms();
+    assert!(evm.recent_wasms().is_empty());
+    assert!(call(&mut evm, 2) > second);
+    assert_eq!(evm.recent_wasms().first_uses(), 1);
+}
+
+#[test]
+fn test_block_cache_keeps_the_most_recent_programs() {
+    let [a, b, c] = [1, 2, 3].map(B256::repeat_byte);
+    let mut recent = RecentWasms::default();
+
+    assert!(!recent.insert(a, 2));
+    assert!(!recent.insert(b, 2));
+    assert!(recent.insert(a, 2));
+    // `b` is now the least recent and makes room for `c`
+    assert!(!recent.insert(c, 2));
+    assert!(!recent.contains(&b));
+    assert_eq!(recent.iter().copied().collect::<Vec<_>>(), vec![a, c]);
+    assert_eq!((recent.first_uses(), recent.discounted()), (3, 1));
+
+    // The size is fixed by the first call of the block
+    assert!(!recent.insert(b, 10));
+    assert_eq!(recent.len(), 2);
+
+    recent.clear();
+    assert!(recent.is_empty());
+    assert_eq!((recent.first_uses(), recent.discounted()), (0, 0));
+}
+
+#[test]
+fn test_block_cache_size_zero_keeps_one_program() {
+    let [a, b] = [1, 2].map(B256::repeat_byte);
+    let mut recent = RecentWasms::default();
+
+    assert!(!recent.insert(a, 0));
+    assert!(recent.insert(a, 0));
+    assert!(!recent.insert(b, 0));
+    assert!(!recent.insert(a, 0));
+}
@@ -0,0 +1,78 @@
+//! Tests for the recorded L2 block hashes read by `ArbSys.arbBlockHash`.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::state::block_hashes::RecentBlockHashes;
+use revm::{
+    Database,
+    context::{ContextTr, JournalTr, result::ExecutionResult},
+    primitives::{Address, B256, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx, create_evm, execute_tx, fund_account, setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbSys {
+        function arbBlockHash(uint256 arbBlockNum) external view returns (bytes32);
+    }
+}
+
+const ARB_SYS: Address = address!("0x0000000000000000000000000000000000000064");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const BLOCK_NUMBER: u64 = 300;
+
+fn setup() -> TestEvm {
+    let mut context = setup_context_with_arbos_state();
+    context.block.number = U256::from(BLOCK_NUMBER);
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    create_evm(context)
+}
+
+fn arb_block_hash(evm: &mut TestEvm, number: u64) -> B256 {
+    let calldata = ArbSys::arbBlockHashCall {
+        arbBlockNum: U256::from(number),
+    }
+    .abi_encode();
+    match execute_tx(evm, create_call_tx(ARB_SYS, calldata, 1_000_000)) {
+        ExecutionResult::Success { output, .. } => B256::from_slice(output.data()),
+        other => panic!("arbBlockHash failed: {other:?}"),
+    }
+}
+
+fn database_hash(evm: &mut TestEvm, number: u64) -> B256 {
+    evm.0.ctx.journal_mut().db_mut().block_hash(number).unwrap()
+}
+
+#[test]
+fn test_recorded_hashes_are_read_by_arb_block_hash() {
+    let mut evm = setup();
+    let hash = B256::repeat_byte(0x99);
+    evm.record_block_hash(BLOCK_NUMBER - 1, hash);
+
+    assert_eq!(arb_block_hash(&mut evm, BLOCK_NUMBER - 1), hash);
+}
+
+#[test]
+fn test_unrecorded_hashes_come_from_the_database() {
+    let mut evm = setup();
+    evm.record_block_hash(BLOCK_NUMBER - 1, B256::repeat_byte(0x99));
+
+    let expected = database_hash(&mut evm, BLOCK_NUMBER - 2);
+    assert_eq!(arb_block_hash(&mut evm, BLOCK_NUMBER - 2), expected);
+}
+
+#[test]
+fn test_recent_block_hashes_keep_the_last_256_blocks() {
+    let mut hashes = RecentBlockHashes::default();
+    for number in 0..300u64 {
+        hashes.record(number, B256::from(U256::from(number)));
+    }
+
+    assert_eq!(hashes.get(299), Some(B256::from(U256::from(299))));
+    assert_eq!(hashes.get(44), Some(B256::from(U256::from(44))));
+    // Block 43 shared its slot with block 299, which overwrote it
+    assert_eq!(hashes.get(43), None);
+    assert_eq!(hashes.get(300), None);
+}
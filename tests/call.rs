@@ -3,15 +3,16 @@
 
 //! Contract call tests for Stylus programs (call, static_call, delegate_call).
 
+use alloy_sol_types::{SolCall, sol};
 use revm::{
     context::result::ExecutionResult,
-    primitives::{Address, U256},
+    primitives::{Address, U256, address},
 };
 
 mod test_utils;
 use test_utils::{
     create_call_tx, create_call_tx_with_nonce, create_call_tx_with_value, create_evm,
-    deploy_wat_program, execute_tx, fund_account, setup_context_with_arbos_state,
+    deploy_evm_code, deploy_wat_program, execute_tx, fund_account, setup_context_with_arbos_state,
 };
 
 // ============================================================================
@@ -510,6 +511,41 @@ fn test_e2e_static_call_prevents_transient_storage_write() {
     }
 }
 
+/// Test that a call with value made in a static context fails without costing the caller its gas.
+#[test]
+fn test_e2e_static_call_with_value_fails_without_consuming_gas() {
+    let mut context = setup_context_with_arbos_state();
+
+    let caller_wat = include_bytes!("../test-data/call.wat");
+    let caller_address = deploy_wat_program(&mut context, caller_wat);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    // The program static calls itself, asking to send 1 wei on from the static context
+    let mut value_args = vec![0x03u8]; // call_contract with value selector
+    value_args.extend_from_slice(Address::repeat_byte(0x02).as_slice());
+    value_args.extend_from_slice(&U256::from(1).to_be_bytes::<32>());
+
+    let mut args = vec![0x01u8]; // static_call_contract selector
+    args.extend_from_slice(caller_address.as_slice());
+    args.extend_from_slice(&value_args);
+
+    let tx = create_call_tx(caller_address, args, 50_000_000);
+    let result = execute_tx(&mut evm, tx);
+
+    // The inner program survives the rejected call and returns its failure status
+    assert!(result.is_success(), "{result:?}");
+    assert_eq!(result.output().unwrap().as_ref(), &[1]);
+    assert!(
+        result.gas_used() < 500_000,
+        "rejected call should not consume the gas it was given: {}",
+        result.gas_used()
+    );
+}
+
 #[test]
 fn test_e2e_static_call_propagation() {
     let mut context = setup_context_with_arbos_state();
@@ -1103,3 +1139,394 @@ fn test_e2e_nested_call_value_not_forwarded() {
         }
     }
 }
+
+// ============================================================================
+// Gas Forwarding Tests
+// ============================================================================
+
+// EVM runtime code returning the gas available to it as a 32-byte word
+// GAS, PUSH1 0, MSTORE, PUSH1 32, PUSH1 0, RETURN
+const GAS_REPORTER_CODE: &[u8] = &[0x5a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+
+fn gas_seen_by_callee(tx_gas_limit: u64) -> u64 {
+    let mut context = setup_context_with_arbos_state();
+
+    let callee_address = Address::repeat_byte(0x5a);
+    deploy_evm_code(&mut context, callee_address, GAS_REPORTER_CODE);
+
+    let caller_wat = include_bytes!("../test-data/call.wat");
+    let caller_address = deploy_wat_program(&mut context, caller_wat);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let mut args = vec![0x00u8]; // call_contract selector
+    args.extend_from_slice(callee_address.as_slice());
+
+    let tx = create_call_tx(caller_address, args, tx_gas_limit);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            assert_eq!(output.data().len(), 32, "callee should return one word");
+            U256::from_be_slice(output.data().as_ref()).to::<u64>()
+        }
+        other => panic!("call failed: {:?}", other),
+    }
+}
+
+/// The requested gas is forwarded as-is when the caller can afford it.
+#[test]
+fn test_e2e_call_forwards_requested_gas() {
+    // call.wat requests 1,000,000 gas; GAS itself costs 2
+    assert_eq!(gas_seen_by_callee(10_000_000), 1_000_000 - 2);
+}
+
+/// A request above what the caller has left is capped at 63/64ths of it.
+#[test]
+fn test_e2e_call_caps_gas_at_63_64ths() {
+    let tx_gas_limit = 600_000;
+    let seen = gas_seen_by_callee(tx_gas_limit);
+    assert!(seen > 0, "callee should receive gas");
+    assert!(
+        seen < tx_gas_limit * 63 / 64,
+        "callee saw {seen} gas, more than 63/64ths of the transaction's"
+    );
+}
+
+// ============================================================================
+// Value Transfer Tests
+// ============================================================================
+
+// EVM runtime code returning CALLVALUE and the gas available after storing it
+// CALLVALUE, PUSH1 0, MSTORE, GAS, PUSH1 32, MSTORE, PUSH1 64, PUSH1 0, RETURN
+const VALUE_REPORTER_CODE: &[u8] = &[
+    0x34, 0x60, 0x00, 0x52, 0x5a, 0x60, 0x20, 0x52, 0x60, 0x40, 0x60, 0x00, 0xf3,
+];
+
+/// Calls the value reporter through call.wat selector 0x03, returning the status byte and
+/// return data.
+fn call_with_value(program_balance: U256, value: U256) -> (u8, Vec<u8>) {
+    let mut context = setup_context_with_arbos_state();
+
+    let callee_address = Address::repeat_byte(0x34);
+    deploy_evm_code(&mut context, callee_address, VALUE_REPORTER_CODE);
+
+    let caller_wat = include_bytes!("../test-data/call.wat");
+    let caller_address = deploy_wat_program(&mut context, caller_wat);
+    if !program_balance.is_zero() {
+        fund_account(&mut context, caller_address, program_balance);
+    }
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let mut args = vec![0x03u8];
+    args.extend_from_slice(callee_address.as_slice());
+    args.extend_from_slice(&value.to_be_bytes::<32>());
+
+    let tx = create_call_tx(caller_address, args, 10_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            let data = output.data();
+            assert!(!data.is_empty(), "output should start with a status byte");
+            (data[0], data[1..].to_vec())
+        }
+        other => panic!("value call aborted the program: {:?}", other),
+    }
+}
+
+#[test]
+fn test_e2e_call_with_value_transfers_without_stipend() {
+    let value = U256::from(1_000_u64);
+    let (status, data) = call_with_value(U256::from(1_000_000_u64), value);
+
+    assert_eq!(status, 0, "funded value call should succeed");
+    assert_eq!(data.len(), 64, "callee should return two words");
+    assert_eq!(U256::from_be_slice(&data[..32]), value);
+    // 1,000,000 requested, with no stipend as in Nitro, less CALLVALUE, PUSH1, MSTORE with one
+    // word of memory expansion, and GAS itself
+    assert_eq!(U256::from_be_slice(&data[32..]).to::<u64>(), 1_000_000 - 13);
+}
+
+#[test]
+fn test_e2e_call_with_value_insufficient_balance() {
+    let (status, data) = call_with_value(U256::from(10_u64), U256::from(1_000_u64));
+
+    assert_ne!(status, 0, "value call above the balance should fail");
+    assert!(data.is_empty(), "callee should not run");
+}
+
+// ============================================================================
+// Call Depth Tests
+// ============================================================================
+
+// EVM runtime code that, given calldata `n || target || payload`, calls itself with `n - 1`
+// until `n` reaches zero, then calls `target` with `payload`. Every level forwards all its gas
+// and returns the return data of the call it made.
+const DEPTH_RELAY_CODE: &[u8] = &[
+    0x60, 0x00, 0x35, 0x80, 0x15, 0x60, 0x2c, 0x57, // n = calldata[0]; if n == 0 jump to leaf
+    0x36, 0x60, 0x00, 0x60, 0x00, 0x37, // copy calldata to memory
+    0x60, 0x01, 0x90, 0x03, 0x60, 0x00, 0x52, // mem[0] = n - 1
+    0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00, 0x60, 0x00, 0x30, 0x5a, 0xf1, 0x50, // call self
+    0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, 0x3d, 0x60, 0x00, 0xf3, // return the return data
+    0x5b, 0x50, // leaf:
+    0x60, 0x40, 0x36, 0x03, 0x80, 0x60, 0x40, 0x60, 0x00, 0x37, // copy payload to memory
+    0x60, 0x00, 0x60, 0x00, 0x91, 0x60, 0x00, 0x60, 0x00, 0x60, 0x20, 0x35, 0x5a, 0xf1,
+    0x50, // call target with the payload
+    0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, 0x3d, 0x60, 0x00, 0xf3, // return the return data
+];
+
+// EVM runtime code returning 42 as a single word
+// PUSH1 42, PUSH1 0, MSTORE, PUSH1 32, PUSH1 0, RETURN
+const RETURN_42_CODE: &[u8] = &[0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+
+/// Runs call.wat at frame depth `program_depth` (the transaction's frame is depth 0) and has it
+/// call `callee` through selector 0x03, returning the status byte and return data it saw.
+fn call_at_depth(program_depth: u64, callee_is_stylus: bool) -> (u8, Vec<u8>) {
+    let mut context = setup_context_with_arbos_state();
+
+    let relay_address = Address::repeat_byte(0x5e);
+    deploy_evm_code(&mut context, relay_address, DEPTH_RELAY_CODE);
+
+    let callee_address = if callee_is_stylus {
+        deploy_wat_program(&mut context, include_bytes!("../test-data/add.wat"))
+    } else {
+        let address = Address::repeat_byte(0x2a);
+        deploy_evm_code(&mut context, address, RETURN_42_CODE);
+        address
+    };
+
+    let program_address = deploy_wat_program(&mut context, include_bytes!("../test-data/call.wat"));
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::MAX);
+
+    let mut evm = create_evm(context);
+
+    // The relay runs at depths 0..=n and calls the program from depth n.
+    let mut args = U256::from(program_depth - 1).to_be_bytes::<32>().to_vec();
+    args.extend_from_slice(&[0u8; 12]);
+    args.extend_from_slice(program_address.as_slice());
+    args.push(0x03);
+    args.extend_from_slice(callee_address.as_slice());
+    args.extend_from_slice(&[0u8; 32]);
+
+    // Each relay level keeps 1/64th of its gas, so reaching depth 1024 takes a lot of it.
+    let tx = create_call_tx(relay_address, args, 1_000_000_000_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            let data = output.data();
+            assert!(!data.is_empty(), "output should start with a status byte");
+            (data[0], data[1..].to_vec())
+        }
+        other => panic!("relay failed: {:?}", other),
+    }
+}
+
+#[test]
+fn test_e2e_call_below_depth_limit_succeeds() {
+    let (status, data) = call_at_depth(1023, false);
+
+    assert_eq!(status, 0, "call into depth 1024 should succeed");
+    assert_eq!(U256::from_be_slice(&data), U256::from(42));
+}
+
+#[test]
+fn test_e2e_call_past_depth_limit_fails() {
+    let (status, data) = call_at_depth(1024, false);
+
+    assert_eq!(status, 2, "call into depth 1025 should fail like Nitro");
+    assert!(data.is_empty(), "callee should not run");
+}
+
+#[test]
+fn test_e2e_stylus_to_stylus_depth_limit() {
+    let (status, _) = call_at_depth(1023, true);
+    assert_eq!(status, 0, "Stylus callee at depth 1024 should run");
+
+    let (status, data) = call_at_depth(1024, true);
+    assert_eq!(status, 2, "Stylus callee at depth 1025 should not run");
+    assert!(data.is_empty());
+}
+
+/// Calls write-args.wat through call.wat, with at most `max_depth` Stylus programs executing at
+/// once, returning the output of call.wat.
+fn stylus_call_with_max_depth(max_depth: Option<usize>) -> Vec<u8> {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.max_stylus_call_depth = max_depth;
+
+    let callee_address =
+        deploy_wat_program(&mut context, include_bytes!("../test-data/write-args.wat"));
+    let caller_address = deploy_wat_program(&mut context, include_bytes!("../test-data/call.wat"));
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(callee_address.as_slice());
+    args.extend_from_slice(b"nested");
+
+    match execute_tx(&mut evm, create_call_tx(caller_address, args, 50_000_000)) {
+        ExecutionResult::Success { output, .. } => output.into_data().to_vec(),
+        other => panic!("caller failed: {:?}", other),
+    }
+}
+
+#[test]
+fn test_e2e_nested_stylus_call_within_max_stylus_depth() {
+    assert_eq!(stylus_call_with_max_depth(None), b"nested");
+    assert_eq!(stylus_call_with_max_depth(Some(2)), b"nested");
+}
+
+#[test]
+fn test_e2e_nested_stylus_call_past_max_stylus_depth_fails() {
+    // The caller sees a failed call and carries on
+    assert_eq!(stylus_call_with_max_depth(Some(1)), vec![2]);
+}
+
+// ============================================================================
+// Calldata From EVM Memory
+// ============================================================================
+
+// EVM runtime code that, given calldata `target || payload`, copies the payload to memory at
+// 0x40 and calls `target` with it, forwarding all its gas and returning the return data
+const MEMORY_RELAY_CODE: &[u8] = &[
+    0x60, 0x20, 0x36, 0x03, 0x80, 0x60, 0x20, 0x60, 0x40, 0x37, // copy payload to memory
+    0x60, 0x00, 0x60, 0x00, 0x91, 0x60, 0x40, 0x60, 0x00, 0x60, 0x00, 0x35, 0x5a, 0xf1,
+    0x50, // call target with the payload
+    0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, 0x3d, 0x60, 0x00, 0xf3, // return the return data
+];
+
+/// Calls write-args.wat through [`MEMORY_RELAY_CODE`] with `payload`, returning what it echoed.
+fn echo_through_memory_relay(payload: &[u8]) -> Vec<u8> {
+    let mut context = setup_context_with_arbos_state();
+
+    let relay_address = Address::repeat_byte(0x3e);
+    deploy_evm_code(&mut context, relay_address, MEMORY_RELAY_CODE);
+    let callee_address =
+        deploy_wat_program(&mut context, include_bytes!("../test-data/write-args.wat"));
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let mut args = vec![0u8; 12];
+    args.extend_from_slice(callee_address.as_slice());
+    args.extend_from_slice(payload);
+
+    match execute_tx(&mut evm, create_call_tx(relay_address, args, 50_000_000)) {
+        ExecutionResult::Success { output, .. } => output.data().to_vec(),
+        other => panic!("relay failed: {:?}", other),
+    }
+}
+
+#[test]
+fn test_e2e_evm_call_passes_calldata_from_memory() {
+    assert_eq!(
+        echo_through_memory_relay(b"calldata from EVM memory"),
+        b"calldata from EVM memory"
+    );
+}
+
+#[test]
+fn test_e2e_evm_call_passes_empty_calldata_from_memory() {
+    assert!(echo_through_memory_relay(&[]).is_empty());
+}
+
+#[test]
+fn test_e2e_evm_call_passes_calldata_spanning_words_from_memory() {
+    // Not a whole number of words, so the range ends partway through one
+    let payload: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+    assert_eq!(echo_through_memory_relay(&payload), payload);
+}
+
+// ============================================================================
+// Return Data Tests
+// ============================================================================
+
+sol! {
+    interface ArbSys {
+        function arbChainID() external view returns (uint256);
+    }
+}
+
+const ARB_SYS: Address = address!("0x0000000000000000000000000000000000000064");
+
+// EVM runtime code reverting with "nope"
+// PUSH4 "nope", PUSH1 0, MSTORE, PUSH1 4, PUSH1 28, REVERT
+const REVERTER_CODE: &[u8] = &[
+    0x63, 0x6e, 0x6f, 0x70, 0x65, 0x60, 0x00, 0x52, 0x60, 0x04, 0x60, 0x1c, 0xfd,
+];
+
+#[test]
+fn test_e2e_call_returns_revert_data_to_stylus_caller() {
+    let mut context = setup_context_with_arbos_state();
+
+    let callee_address = Address::repeat_byte(0xfd);
+    deploy_evm_code(&mut context, callee_address, REVERTER_CODE);
+
+    let caller_wat = include_bytes!("../test-data/call.wat");
+    let caller_address = deploy_wat_program(&mut context, caller_wat);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    // call_contract selector with a zero value (0x03), which returns the status byte followed
+    // by the return data whatever the outcome
+    let mut args = vec![0x03u8];
+    args.extend_from_slice(callee_address.as_slice());
+    args.extend_from_slice(&[0u8; 32]);
+
+    let tx = create_call_tx(caller_address, args, 10_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            let data = output.data();
+            assert_ne!(data[0], 0, "reverted call should fail");
+            assert_eq!(&data[1..], b"nope");
+        }
+        other => panic!("call failed: {:?}", other),
+    }
+}
+
+#[test]
+fn test_e2e_call_returns_precompile_output_to_stylus_caller() {
+    let mut context = setup_context_with_arbos_state();
+
+    let caller_wat = include_bytes!("../test-data/call.wat");
+    let caller_address = deploy_wat_program(&mut context, caller_wat);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let calldata = ArbSys::arbChainIDCall {}.abi_encode();
+
+    let tx = create_call_tx_with_nonce(ARB_SYS, calldata.clone(), 1_000_000, 0);
+    let direct = match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => output.into_data(),
+        other => panic!("direct call failed: {:?}", other),
+    };
+    assert_eq!(direct.len(), 32, "chain id should be one word");
+
+    // static_call_contract selector (0x01) + ArbSys + calldata
+    let mut args = vec![0x01u8];
+    args.extend_from_slice(ARB_SYS.as_slice());
+    args.extend_from_slice(&calldata);
+
+    let tx = create_call_tx_with_nonce(caller_address, args, 10_000_000, 1);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            assert_eq!(output.data(), &direct);
+        }
+        other => panic!("call through the program failed: {:?}", other),
+    }
+}
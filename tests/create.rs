@@ -4,7 +4,7 @@
 //! Contract creation tests for Stylus programs (CREATE, CREATE2).
 
 use revm::{
-    context::result::ExecutionResult,
+    context::{Cfg, result::ExecutionResult},
     primitives::{Address, U256, keccak256},
 };
 
@@ -455,3 +455,213 @@ fn test_e2e_call_created_contract() {
         }
     }
 }
+
+// ============================================================================
+// Failure Semantics Tests
+// ============================================================================
+
+// Init code that reverts with the 4 bytes 0xdeadbeef
+const REVERTING_INIT_CODE: &[u8] = &[
+    0x63, 0xde, 0xad, 0xbe, 0xef, // PUSH4 0xdeadbeef
+    0x60, 0x00, // PUSH1 0
+    0x52, // MSTORE
+    0x60, 0x04, // PUSH1 4 (revert size)
+    0x60, 0x1c, // PUSH1 28 (revert offset)
+    0xfd, // REVERT
+];
+
+// Init code that hits the INVALID opcode
+const HALTING_INIT_CODE: &[u8] = &[0xfe];
+
+/// Runs selector 0x04 and splits the output into the created address and the return data.
+fn create_with_return_data(
+    program_balance: U256,
+    value: U256,
+    init_code: &[u8],
+) -> (Address, Address, Vec<u8>) {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/create.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+    if !program_balance.is_zero() {
+        fund_account(&mut context, program_address, program_balance);
+    }
+
+    let mut evm = create_evm(context);
+
+    let mut args = vec![0x04u8];
+    args.extend_from_slice(&value.to_be_bytes::<32>());
+    args.extend_from_slice(init_code);
+
+    let tx = create_call_tx(program_address, args, 50_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            let data = output.data();
+            assert!(data.len() >= 20, "output should start with an address");
+            (
+                program_address,
+                Address::from_slice(&data[..20]),
+                data[20..].to_vec(),
+            )
+        }
+        other => panic!("factory call failed: {:?}", other),
+    }
+}
+
+#[test]
+fn test_e2e_create1_address_derivation() {
+    let (program_address, created_address, return_data) =
+        create_with_return_data(U256::ZERO, U256::ZERO, MINIMAL_INIT_CODE);
+
+    assert_eq!(
+        created_address,
+        program_address.create(0),
+        "CREATE address should derive from the program address and nonce"
+    );
+    assert!(
+        return_data.is_empty(),
+        "successful create should leave no return data"
+    );
+}
+
+#[test]
+fn test_e2e_create1_reverting_init_code_returns_revert_data() {
+    let (_, created_address, return_data) =
+        create_with_return_data(U256::ZERO, U256::ZERO, REVERTING_INIT_CODE);
+
+    assert_eq!(
+        created_address,
+        Address::ZERO,
+        "reverted create should yield the zero address"
+    );
+    assert_eq!(
+        return_data,
+        vec![0xde, 0xad, 0xbe, 0xef],
+        "revert data of the init code should be readable by the program"
+    );
+}
+
+#[test]
+fn test_e2e_create1_halting_init_code() {
+    let (_, created_address, return_data) =
+        create_with_return_data(U256::ZERO, U256::ZERO, HALTING_INIT_CODE);
+
+    assert_eq!(
+        created_address,
+        Address::ZERO,
+        "halted create should yield the zero address"
+    );
+    assert!(return_data.is_empty(), "only reverts provide return data");
+}
+
+#[test]
+fn test_e2e_create1_insufficient_balance() {
+    let (_, created_address, return_data) =
+        create_with_return_data(U256::from(10_u64), U256::from(1_000_u64), MINIMAL_INIT_CODE);
+
+    assert_eq!(
+        created_address,
+        Address::ZERO,
+        "create with value above the program balance should fail"
+    );
+    assert!(return_data.is_empty());
+}
+
+#[test]
+fn test_e2e_create1_with_value_endows_child() {
+    let (program_address, created_address, _) = create_with_return_data(
+        U256::from(1_000_000_u64),
+        U256::from(1_000_u64),
+        MINIMAL_INIT_CODE,
+    );
+
+    assert_eq!(created_address, program_address.create(0));
+}
+
+#[test]
+fn test_e2e_create2_collision() {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/create.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let salt = [0x55u8; 32];
+    let mut args = vec![0x03u8];
+    args.extend_from_slice(&salt);
+
+    let tx = create_call_tx_with_nonce(program_address, args.clone(), 50_000_000, 0);
+    let first = match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => Address::from_slice(output.data().as_ref()),
+        other => panic!("first create2 failed: {:?}", other),
+    };
+    assert_ne!(first, Address::ZERO, "first create2 should succeed");
+
+    // Same deployer, salt and init code target the now occupied address
+    let tx = create_call_tx_with_nonce(program_address, args, 50_000_000, 1);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            assert_eq!(
+                Address::from_slice(output.data().as_ref()),
+                Address::ZERO,
+                "colliding create2 should yield the zero address"
+            );
+        }
+        other => panic!(
+            "colliding create2 should not abort the program: {:?}",
+            other
+        ),
+    }
+}
+
+/// Runs selector 0x00 with `init_code` and zero value.
+fn create_with_init_code(init_code: &[u8]) -> ExecutionResult {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/create.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
+    args.extend_from_slice(init_code);
+
+    execute_tx(&mut evm, create_call_tx(program_address, args, 50_000_000))
+}
+
+#[test]
+fn test_e2e_create_init_code_at_size_limit() {
+    // Init code of STOPs deploys an empty contract
+    let max_initcode_size = setup_context_with_arbos_state().cfg.max_code_size() * 2;
+
+    match create_with_init_code(&vec![0x00; max_initcode_size]) {
+        ExecutionResult::Success { output, .. } => {
+            assert_ne!(Address::from_slice(output.data().as_ref()), Address::ZERO);
+        }
+        other => panic!("create at the init code size limit failed: {:?}", other),
+    }
+}
+
+#[test]
+fn test_e2e_create_init_code_above_size_limit_fails() {
+    // EIP-3860: like the CREATE opcode, the create halts instead of yielding the zero address
+    let max_initcode_size = setup_context_with_arbos_state().cfg.max_code_size() * 2;
+
+    let result = create_with_init_code(&vec![0x00; max_initcode_size + 1]);
+    assert!(
+        !result.is_success(),
+        "create above the init code size limit should fail the program: {:?}",
+        result
+    );
+}
@@ -0,0 +1,70 @@
+//! Tests for L1-to-L2 ETH deposits (type 0x64).
+
+use arbos_revm::ArbitrumDepositTx;
+use revm::{
+    ExecuteEvm,
+    context::{ContextTr, JournalTr, result::ExecutionResult},
+    primitives::{Address, B256, U256, address},
+};
+
+mod test_utils;
+use test_utils::{TestEvm, create_evm, setup_context_with_arbos_state};
+
+const FROM: Address = address!("0x00000000000000000000000000000000000000a1");
+const TO: Address = address!("0x00000000000000000000000000000000000000b2");
+
+fn deposit(to: Address, value: u64) -> ArbitrumDepositTx {
+    ArbitrumDepositTx::new(1, B256::repeat_byte(0x42), FROM, to, U256::from(value))
+}
+
+fn account(evm: &mut TestEvm, address: Address) -> (U256, u64) {
+    let info = &evm
+        .0
+        .ctx
+        .journal_mut()
+        .load_account(address)
+        .unwrap()
+        .data
+        .info;
+    (info.balance, info.nonce)
+}
+
+#[test]
+fn test_deposit_mints_to_recipient() {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.inner.chain_id = 1;
+    let mut evm = create_evm(context);
+
+    let result = evm.transact_one(deposit(TO, 1_000).into()).unwrap();
+    assert!(matches!(
+        result,
+        ExecutionResult::Success { gas_used: 0, .. }
+    ));
+
+    assert_eq!(account(&mut evm, TO), (U256::from(1_000), 0));
+    // The sender neither keeps the minted value nor has its nonce bumped.
+    assert_eq!(account(&mut evm, FROM), (U256::ZERO, 0));
+}
+
+#[test]
+fn test_deposit_to_self() {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.inner.chain_id = 1;
+    let mut evm = create_evm(context);
+
+    evm.transact_one(deposit(FROM, 500).into()).unwrap();
+    evm.transact_one(deposit(FROM, 500).into()).unwrap();
+
+    assert_eq!(account(&mut evm, FROM), (U256::from(1_000), 0));
+}
+
+#[test]
+fn test_deposit_rejects_wrong_chain_id() {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.inner.chain_id = 2;
+    context.cfg.inner.tx_chain_id_check = true;
+    let mut evm = create_evm(context);
+
+    assert!(evm.transact_one(deposit(TO, 1_000).into()).is_err());
+    assert_eq!(account(&mut evm, TO), (U256::ZERO, 0));
+}
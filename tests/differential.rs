@@ -0,0 +1,144 @@
+//! Tests for replaying Nitro traces and reporting where the replay differs.
+
+use arbos_revm::testing::differential::{
+    Mismatch, NitroTrace, StateDiff, TraceAccount, TraceError, replay,
+};
+use revm::primitives::{Address, B256, Bytes, Log, U256, address};
+
+const SENDER: Address = address!("0x0101010101010101010101010101010101010101");
+const RECIPIENT: Address = address!("0x0202020202020202020202020202020202020202");
+
+/// A transfer of 100 wei, free of fees, with the outcome given by `receipt`.
+fn transfer_trace(receipt: &str) -> String {
+    format!(
+        r#"{{
+            "arbosVersion": 32,
+            "block": {{ "number": "0x64", "timestamp": "0x6553f100", "baseFeePerGas": "0x0" }},
+            "transaction": {{
+                "type": "0x0",
+                "from": "{SENDER}",
+                "to": "{RECIPIENT}",
+                "input": "0x",
+                "value": "0x64",
+                "gas": "0x186a0",
+                "gasPrice": "0x0",
+                "nonce": "0x0"
+            }},
+            "prestate": {{ "{SENDER}": {{ "balance": "0x3b9aca00" }} }},
+            "stateDiff": {{
+                "pre": {{ "{SENDER}": {{ "balance": "0x3b9aca00" }} }},
+                "post": {{
+                    "{SENDER}": {{ "balance": "0x3b9ac99c", "nonce": 1 }},
+                    "{RECIPIENT}": {{ "balance": "0x64" }}
+                }}
+            }},
+            "receipt": {receipt}
+        }}"#
+    )
+}
+
+#[test]
+fn test_matching_replay() {
+    let json = transfer_trace(r#"{ "status": "0x1", "gasUsed": "0x5208", "logs": [] }"#);
+    let trace = NitroTrace::from_json(&json).unwrap();
+
+    let report = replay(&trace);
+    assert!(report.is_match(), "{report}");
+}
+
+#[test]
+fn test_mismatches_are_reported() {
+    let topic = B256::repeat_byte(0x11);
+    let json = transfer_trace(&format!(
+        r#"{{
+            "status": "0x1",
+            "gasUsed": "0x7530",
+            "logs": [{{ "address": "{RECIPIENT}", "topics": ["{topic}"], "data": "0x" }}]
+        }}"#
+    ));
+    let mut trace = NitroTrace::from_json(&json).unwrap();
+    trace.state_diff.post.get_mut(&RECIPIENT).unwrap().balance = Some(U256::from(99));
+
+    let report = replay(&trace);
+    assert_eq!(
+        report.mismatches,
+        vec![
+            Mismatch::GasUsed {
+                expected: 30_000,
+                actual: 21_000
+            },
+            Mismatch::LogCount {
+                expected: 1,
+                actual: 0
+            },
+            Mismatch::Balance {
+                address: RECIPIENT,
+                expected: U256::from(99),
+                actual: U256::from(100)
+            },
+        ]
+    );
+    assert!(report.to_string().starts_with("3 mismatches with Nitro:"));
+}
+
+#[test]
+fn test_expected_post_fills_in_what_the_diff_leaves_out() {
+    let slot = U256::from(1);
+    let account = |balance: u64, storage: &[(U256, U256)]| TraceAccount {
+        balance: Some(U256::from(balance)),
+        storage: storage.iter().copied().collect(),
+        ..Default::default()
+    };
+    let diff = StateDiff {
+        pre: [
+            (SENDER, account(10, &[(slot, U256::from(7))])),
+            (RECIPIENT, account(5, &[])),
+        ]
+        .into(),
+        post: [(SENDER, account(9, &[]))].into(),
+    };
+
+    let post = diff.expected_post();
+
+    // Slots cleared by the transaction are left out of the diff's post state
+    assert_eq!(post[&SENDER].storage[&slot], U256::ZERO);
+    assert_eq!(post[&SENDER].balance, Some(U256::from(9)));
+    // So are accounts it deleted
+    assert_eq!(post[&RECIPIENT].balance, Some(U256::ZERO));
+    assert_eq!(post[&RECIPIENT].code, Some(Bytes::new()));
+}
+
+#[test]
+fn test_logs_are_parsed() {
+    let json = transfer_trace(&format!(
+        r#"{{
+            "status": "0x0",
+            "gasUsed": "0x5208",
+            "logs": [{{ "address": "{RECIPIENT}", "topics": ["{}"], "data": "0x2a" }}]
+        }}"#,
+        B256::ZERO
+    ));
+    let trace = NitroTrace::from_json(&json).unwrap();
+
+    assert!(!trace.success);
+    assert_eq!(
+        trace.logs,
+        vec![Log::new_unchecked(
+            RECIPIENT,
+            vec![B256::ZERO],
+            Bytes::from_static(&[0x2a])
+        )]
+    );
+}
+
+#[test]
+fn test_invalid_traces_are_rejected() {
+    assert!(matches!(
+        NitroTrace::from_json("{"),
+        Err(TraceError::Json(_))
+    ));
+    assert!(matches!(
+        NitroTrace::from_json(&transfer_trace(r#"{ "status": "0x1" }"#)),
+        Err(TraceError::Invalid(message)) if message == "missing `gasUsed`"
+    ));
+}
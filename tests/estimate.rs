@@ -0,0 +1,222 @@
+//! Tests for estimating the gas of transactions, alone and together with the Stylus activations
+//! they need.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::{
+    l1_fee::{calculate_estimated_poster_gas, calculate_estimated_tx_l1_cost, estimation_envelope},
+    state::{ArbState, ArbStateGetter, types::StorageBackedTr},
+};
+use revm::{
+    context::{ContextTr, JournalTr, TxEnv},
+    primitives::{Address, TxKind, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx, create_call_tx_with_nonce, create_evm, deploy_evm_code,
+    deploy_wat_program, execute_tx, fund_account, setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbWasm {
+        function activateProgram(address program)
+            external payable returns (uint16 version, uint256 dataFee);
+    }
+}
+
+const ARB_WASM: Address = address!("0x0000000000000000000000000000000000000071");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+
+/// Deploy the storage test program without activating it.
+fn setup() -> (TestEvm, Address) {
+    let mut context = setup_context_with_arbos_state();
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/storage.wat"));
+    fund_account(&mut context, CALLER, U256::from(10u128.pow(18)));
+    (create_evm(context), program)
+}
+
+/// Read slot zero of the storage test program.
+fn read_slot(program: Address, nonce: u64) -> TxEnv {
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    create_call_tx_with_nonce(program, args, 10_000_000, nonce)
+}
+
+#[test]
+fn test_estimate_includes_activation_of_inactive_program() {
+    let (mut evm, program) = setup();
+    let code_hash = evm.0.ctx.journal_mut().code_hash(program).unwrap().data;
+
+    let estimate = evm
+        .estimate_gas_with_activations(read_slot(program, 0))
+        .unwrap();
+    assert!(estimate.result.is_success());
+
+    let [activation] = estimate.activations.as_slice() else {
+        panic!(
+            "expected a single activation, got {:?}",
+            estimate.activations
+        );
+    };
+    assert_eq!(activation.program, program);
+    assert_eq!(activation.code_hash, code_hash);
+    assert!(activation.success);
+    assert!(activation.gas_used > 0);
+    assert!(activation.data_fee > U256::ZERO);
+    assert_eq!(
+        estimate.total_gas_used(),
+        estimate.result.gas_used() + activation.gas_used
+    );
+    assert_eq!(estimate.total_data_fee(), activation.data_fee);
+}
+
+#[test]
+fn test_estimate_of_active_program_matches_plain_execution() {
+    let (mut evm, program) = setup();
+    let activate = TxEnv {
+        caller: CALLER,
+        kind: TxKind::Call(ARB_WASM),
+        data: ArbWasm::activateProgramCall { program }.abi_encode().into(),
+        value: U256::from(10u128.pow(17)),
+        gas_limit: 20_000_000,
+        gas_price: 1,
+        nonce: 0,
+        ..Default::default()
+    };
+    assert!(execute_tx(&mut evm, activate).is_success());
+
+    let estimate = evm
+        .estimate_gas_with_activations(read_slot(program, 1))
+        .unwrap();
+    assert!(estimate.result.is_success());
+    assert!(estimate.activations.is_empty());
+    assert_eq!(estimate.total_gas_used(), estimate.result.gas_used());
+    assert_eq!(estimate.total_data_fee(), U256::ZERO);
+}
+
+const CONTRACT: Address = address!("0x2222222222222222222222222222222222222222");
+
+/// Deploy `code` without any L1 data price, so the estimate is all execution gas.
+fn setup_contract(code: &[u8]) -> TestEvm {
+    let mut context = setup_context_with_arbos_state();
+    deploy_evm_code(&mut context, CONTRACT, code);
+    fund_account(&mut context, CALLER, U256::from(10u128.pow(18)));
+    context
+        .arb_state(None, false)
+        .l1_pricing()
+        .price_per_unit()
+        .set(U256::ZERO)
+        .unwrap();
+    create_evm(context)
+}
+
+#[test]
+fn test_estimate_gas_covers_refunded_gas() {
+    // Set slot 0 and clear it again, refunding much of the gas the first store needs
+    let mut evm = setup_contract(&[
+        0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0x55, 0x00,
+    ]);
+    let tx = create_call_tx(CONTRACT, vec![], 1_000_000);
+
+    let estimate = evm.estimate_gas(tx.clone()).unwrap();
+    assert!(estimate.result.is_success());
+    assert_eq!(estimate.gas_estimate_for_l1, 0);
+    assert_eq!(estimate.gas_estimate, estimate.gas_estimate_for_l2());
+
+    // The gas used falls short once refunded, but the estimate is enough
+    let gas_used = estimate.result.gas_used();
+    assert!(estimate.gas_estimate > gas_used);
+    let short = execute_tx(
+        &mut evm,
+        TxEnv {
+            gas_limit: gas_used,
+            ..tx.clone()
+        },
+    );
+    assert!(!short.is_success());
+
+    let estimated = execute_tx(
+        &mut evm,
+        TxEnv {
+            gas_limit: estimate.gas_estimate,
+            nonce: 1,
+            ..tx
+        },
+    );
+    assert!(estimated.is_success());
+    assert_eq!(estimated.gas_used(), gas_used);
+}
+
+#[test]
+fn test_estimate_gas_leaves_state_untouched() {
+    let mut evm = setup_contract(&[
+        0x60, 0x01, 0x60, 0x00, 0x55, 0x00, // SSTORE(0, 1)
+    ]);
+    let balance = |evm: &mut TestEvm| {
+        let account = evm.0.ctx.journal_mut().load_account(CALLER).unwrap().data;
+        (account.info.balance, account.info.nonce)
+    };
+    let before = balance(&mut evm);
+
+    let estimate = evm
+        .estimate_gas(create_call_tx(CONTRACT, vec![], 1_000_000))
+        .unwrap();
+    assert!(estimate.result.is_success());
+    assert_eq!(balance(&mut evm), before);
+    let slot = evm
+        .0
+        .ctx
+        .journal_mut()
+        .sload(CONTRACT, U256::ZERO)
+        .unwrap()
+        .data;
+    assert_eq!(slot, U256::ZERO);
+}
+
+#[test]
+fn test_estimate_gas_of_failing_transaction_reports_outcome() {
+    // REVERT(0, 0)
+    let mut evm = setup_contract(&[0x60, 0x00, 0x60, 0x00, 0xfd]);
+
+    let estimate = evm
+        .estimate_gas(create_call_tx(CONTRACT, vec![], 1_000_000))
+        .unwrap();
+    assert!(!estimate.result.is_success());
+    assert_eq!(estimate.gas_estimate_for_l2(), 1_000_000);
+}
+
+#[test]
+fn test_estimate_gas_pads_l1_component() {
+    let l1_price = U256::from(1_000_000_000_u64);
+    let mut evm = setup_contract(&[0x00]);
+    evm.0
+        .ctx
+        .arb_state(None, false)
+        .l1_pricing()
+        .price_per_unit()
+        .set(l1_price)
+        .unwrap();
+    let min_base_fee = evm
+        .0
+        .ctx
+        .arb_state(None, true)
+        .l2_pricing()
+        .min_base_fee_wei()
+        .get()
+        .unwrap();
+
+    let tx = create_call_tx(CONTRACT, vec![0xff; 100], 1_000_000);
+    let estimate = evm.estimate_gas(tx.clone()).unwrap();
+    assert!(estimate.result.is_success());
+    assert_eq!(estimate.l1_base_fee_estimate, l1_price);
+
+    let envelope = estimation_envelope(&tx, evm.0.ctx.cfg.inner.chain_id);
+    let l1_cost = calculate_estimated_tx_l1_cost(&envelope, l1_price);
+    let expected = calculate_estimated_poster_gas(l1_cost, estimate.base_fee, min_base_fee);
+    assert!(expected > 0);
+    assert_eq!(estimate.gas_estimate_for_l1, expected);
+    assert_eq!(
+        estimate.gas_estimate,
+        estimate.gas_estimate_for_l2() + expected
+    );
+}
@@ -10,8 +10,8 @@ use revm::{
 
 mod test_utils;
 use test_utils::{
-    create_call_tx, create_evm, deploy_wat_program, execute_tx, fund_account,
-    setup_context_with_arbos_state,
+    create_call_tx, create_call_tx_with_nonce, create_evm, deploy_evm_code, deploy_wat_program,
+    execute_tx, fund_account, setup_context_with_arbos_state,
 };
 
 #[test]
@@ -397,3 +397,118 @@ fn test_e2e_evm_block_coinbase() {
         }
     }
 }
+
+#[test]
+fn test_e2e_evm_block_basefee_matches_evm() {
+    let mut context = setup_context_with_arbos_state();
+    context.block.basefee = 100;
+
+    let wat = include_bytes!("../test-data/evm-data.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+    // Runtime code returning BASEFEE
+    let evm_address = Address::repeat_byte(0x0e);
+    deploy_evm_code(
+        &mut context,
+        evm_address,
+        &[0x48, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3],
+    );
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(1_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let mut basefees = Vec::new();
+    for (nonce, (to, args)) in [(program_address, vec![0x07u8]), (evm_address, vec![])]
+        .into_iter()
+        .enumerate()
+    {
+        let mut tx = create_call_tx_with_nonce(to, args, 1_000_000, nonce as u64);
+        tx.gas_price = 100;
+        match execute_tx(&mut evm, tx) {
+            ExecutionResult::Success { output, .. } => {
+                basefees.push(U256::from_be_slice(output.data().as_ref()))
+            }
+            other => panic!("basefee call failed: {:?}", other),
+        }
+    }
+
+    assert_eq!(basefees[0], U256::from(100));
+    assert_eq!(basefees[0], basefees[1]);
+}
+
+/// Output of calling `reentrant.wat` with `args`, next to an EVM contract that calls its caller
+/// back with calldata 0x00 and returns what it returned.
+fn call_reentrant_program(args: impl FnOnce(Address, Address) -> Vec<u8>) -> Vec<u8> {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/reentrant.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+    let callback_address = Address::repeat_byte(0x0c);
+    deploy_evm_code(
+        &mut context,
+        callback_address,
+        &[
+            0x60, 0x00, 0x60, 0x00, 0x53, // MSTORE8(0, 0)
+            // CALL(GAS, CALLER, 0, 0, 1, 0, 0) POP
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x01, 0x60, 0x00, 0x60, 0x00, 0x33, 0x5a, 0xf1, 0x50,
+            0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, // RETURNDATACOPY(0, 0, RETURNDATASIZE)
+            0x3d, 0x60, 0x00, 0xf3, // RETURN(0, RETURNDATASIZE)
+        ],
+    );
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(1_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let tx = create_call_tx(
+        program_address,
+        args(program_address, callback_address),
+        10_000_000,
+    );
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => output.data().to_vec(),
+        other => panic!("reentrancy call failed: {:?}", other),
+    }
+}
+
+#[test]
+fn test_e2e_evm_msg_reentrant_on_first_entry() {
+    assert_eq!(call_reentrant_program(|_, _| vec![0x00]), vec![0]);
+}
+
+#[test]
+fn test_e2e_evm_msg_reentrant_when_called_back() {
+    let output = call_reentrant_program(|_, callback| {
+        let mut args = vec![0x01u8];
+        args.extend_from_slice(callback.as_slice());
+        args
+    });
+
+    // The outer frame was entered once, the inner one while the outer was still executing
+    assert_eq!(output, vec![0, 1]);
+}
+
+#[test]
+fn test_e2e_evm_msg_reentrant_when_called_back_after_precompile_call() {
+    let output = call_reentrant_program(|_, callback| {
+        let mut args = vec![0x02u8];
+        args.extend_from_slice(callback.as_slice());
+        args
+    });
+
+    // The precompile's frame leaves the program's own entry in place
+    assert_eq!(output, vec![0, 1]);
+}
+
+#[test]
+fn test_e2e_evm_msg_reentrant_on_self_call() {
+    let output = call_reentrant_program(|program, _| {
+        let mut args = vec![0x01u8];
+        args.extend_from_slice(program.as_slice());
+        args
+    });
+
+    assert_eq!(output, vec![0, 1]);
+}
@@ -0,0 +1,103 @@
+//! Tests for the gas breakdown returned alongside execution results.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::{
+    ArbitrumTransaction,
+    l1_fee::calculate_tx_l1_cost,
+    state::{ArbState, ArbStateGetter},
+};
+use revm::primitives::{Address, Bytes, U256, address};
+
+mod test_utils;
+use test_utils::{
+    create_call_tx, create_evm, deploy_evm_code, deploy_wat_program, fund_account,
+    setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbSys {
+        function arbBlockNumber() external view returns (uint256);
+    }
+}
+
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const CONTRACT: Address = address!("0x00000000000000000000000000000000000000d7");
+
+#[test]
+fn test_stylus_usage_is_reported() {
+    let mut context = setup_context_with_arbos_state();
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/storage.wat"));
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    let mut evm = create_evm(context);
+
+    // Selector 0x00 = read slot 0
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    let (result, details) = evm
+        .transact_one_with_details(create_call_tx(program, args, 10_000_000).into())
+        .expect("transaction execution failed");
+    assert!(result.is_success());
+
+    assert!(details.ink_used > 0);
+    assert_eq!(details.pages_allocated, 1);
+    assert!(details.stylus_init_gas > 0);
+    assert!(details.stylus_init_gas < result.gas_used());
+    assert!(details.precompile_gas.is_empty());
+    assert_eq!(evm.execution_details(), &details);
+}
+
+#[test]
+fn test_precompile_gas_is_reported() {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    // MSTORE(0, selector << 224) STATICCALL(gas, ArbSys, 0, 4, 0, 0) STOP
+    let mut code = vec![0x63];
+    code.extend_from_slice(&ArbSys::arbBlockNumberCall::SELECTOR);
+    code.extend_from_slice(&[
+        0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52, 0x60, 0x00, 0x60, 0x00, 0x60, 0x04, 0x60, 0x00, 0x60,
+        0x64, 0x5a, 0xfa, 0x00,
+    ]);
+    deploy_evm_code(&mut context, CONTRACT, &code);
+    let mut evm = create_evm(context);
+
+    let (result, details) = evm
+        .transact_one_with_details(create_call_tx(CONTRACT, Vec::new(), 1_000_000).into())
+        .expect("transaction execution failed");
+    assert!(result.is_success());
+
+    assert_eq!(&details.precompile_gas, evm.precompile_gas_used());
+    assert_eq!(details.precompile_gas.len(), 1);
+    assert_eq!(details.ink_used, 0);
+    assert_eq!(details.pages_allocated, 0);
+    assert_eq!(details.stylus_init_gas, 0);
+}
+
+#[test]
+fn test_l1_data_fee_is_reported() {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(10u128.pow(18)));
+    let price_per_unit = U256::from(1_000);
+    context
+        .arb_state(None, false)
+        .l1_pricing()
+        .price_per_unit()
+        .set(price_per_unit)
+        .unwrap();
+    let mut evm = create_evm(context);
+
+    let enveloped = Bytes::from(vec![0xab; 100]);
+    let tx = ArbitrumTransaction::new_with_enveloped(
+        create_call_tx(Address::repeat_byte(0x42), Vec::new(), 100_000),
+        enveloped.clone(),
+    );
+    let (result, details) = evm
+        .transact_one_with_details(tx)
+        .expect("transaction execution failed");
+    assert!(result.is_success());
+
+    assert_eq!(
+        details.l1_data_fee,
+        calculate_tx_l1_cost(&enveloped, price_per_unit, 1)
+    );
+    assert!(details.l1_data_fee > U256::ZERO);
+}
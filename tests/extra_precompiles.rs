@@ -0,0 +1,322 @@
+//! Tests for registering extra precompiles next to the Ethereum and Arbitrum ones.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::{
+    ArbitrumEvm,
+    constants::ARB_SYS_ADDRESS,
+    generate_state_mut_table,
+    instructions::arbitrum_instructions,
+    precompile_dispatch, precompile_impl,
+    precompiles::{
+        ArbPrecompileLogic, ArbitrumPrecompileProvider, ArbitrumPrecompileProviderBuilder,
+        ExtendedPrecompile, Precompile, PrecompileConflict, StateMutability,
+        errors::revert_message,
+    },
+};
+use revm::{
+    context::{ContextTr, JournalTr, result::ExecutionResult},
+    handler::PrecompileProvider,
+    inspector::NoOpInspector,
+    interpreter::{Gas, InstructionResult, InterpreterResult},
+    precompile::PrecompileId,
+    primitives::{Address, Bytes, Log, U256, address, hardfork::SpecId},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, TestEvm, create_call_tx, deploy_evm_code, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const ORBIT: Address = address!("0x00000000000000000000000000000000000000c1");
+const DOUBLER: Address = address!("0x00000000000000000000000000000000000000c3");
+const RELAY: Address = address!("0x00000000000000000000000000000000000000c2");
+const SCRIBBLER: Address = address!("0x00000000000000000000000000000000000000c4");
+const GAS_COST: u64 = 500;
+
+/// EVM runtime code that calls [`ORBIT`] with its calldata, copied to memory at 0x40, and
+/// returns the return data.
+const RELAY_CODE: &[u8] = &[
+    0x36, 0x80, 0x60, 0x00, 0x60, 0x40, 0x37, // copy calldata to memory
+    0x60, 0x00, 0x60, 0x00, 0x91, 0x60, 0x40, 0x60, 0x00, 0x60, 0xc1, 0x5a, 0xf1,
+    0x50, // call ORBIT with it
+    0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, 0x3d, 0x60, 0x00, 0xf3, // return the return data
+];
+
+/// Returns its input reversed, for [`GAS_COST`].
+fn reverse(
+    _context: &mut TestContext,
+    input: &[u8],
+    _target: &Address,
+    _caller: Address,
+    _value: U256,
+    _is_static: bool,
+    gas_limit: u64,
+) -> Result<Option<InterpreterResult>, String> {
+    let mut gas = Gas::new(gas_limit);
+    if !gas.record_cost(GAS_COST) {
+        return Ok(Some(InterpreterResult::new(
+            InstructionResult::PrecompileOOG,
+            Bytes::new(),
+            gas,
+        )));
+    }
+    let output = input.iter().rev().copied().collect();
+    Ok(Some(InterpreterResult::new(
+        InstructionResult::Return,
+        output,
+        gas,
+    )))
+}
+
+/// Sets slot 0 of its account and logs, then reverts if its input is `[1]`.
+fn scribble(
+    context: &mut TestContext,
+    input: &[u8],
+    target: &Address,
+    _caller: Address,
+    _value: U256,
+    _is_static: bool,
+    gas_limit: u64,
+) -> Result<Option<InterpreterResult>, String> {
+    let journal = context.journal_mut();
+    journal
+        .load_account(*target)
+        .map_err(|err| err.to_string())?;
+    journal
+        .sstore(*target, U256::ZERO, U256::from(1))
+        .map_err(|err| err.to_string())?;
+    journal.log(Log::new_unchecked(*target, Vec::new(), Bytes::new()));
+
+    let result = if input == [1] {
+        InstructionResult::Revert
+    } else {
+        InstructionResult::Return
+    };
+    Ok(Some(InterpreterResult::new(
+        result,
+        Bytes::new(),
+        Gas::new(gas_limit),
+    )))
+}
+
+sol! {
+    interface IDoubler {
+        function double(uint256 x) external pure returns (uint256);
+    }
+}
+
+/// A third-party precompile written with the crate's precompile macros.
+struct Doubler;
+
+impl ArbPrecompileLogic<TestContext> for Doubler {
+    const STATE_MUT_TABLE: &'static [([u8; 4], StateMutability)] = generate_state_mut_table! {
+        IDoubler => {
+            doubleCall(Pure),
+        }
+    };
+
+    fn inner(
+        _context: &mut TestContext,
+        input: &[u8],
+        _target_address: &Address,
+        _caller_address: Address,
+        _call_value: U256,
+        _is_static: bool,
+        gas_limit: u64,
+    ) -> Option<InterpreterResult> {
+        let mut gas = Gas::new(gas_limit);
+        precompile_dispatch!(gas, input, {
+            IDoubler::doubleCall => |call| { call.x * U256::from(2) },
+        })
+    }
+}
+
+/// Output of `Doubler::inner` for `input`.
+fn doubler_inner(input: &[u8]) -> InterpreterResult {
+    let mut context = setup_context_with_arbos_state();
+    <Doubler as ArbPrecompileLogic<TestContext>>::inner(
+        &mut context,
+        input,
+        &DOUBLER,
+        CALLER,
+        U256::ZERO,
+        false,
+        100_000,
+    )
+    .unwrap()
+}
+
+fn builder() -> ArbitrumPrecompileProviderBuilder<TestContext> {
+    ArbitrumPrecompileProvider::builder()
+}
+
+fn orbit_id() -> PrecompileId {
+    PrecompileId::Custom("Reverse".into())
+}
+
+#[test]
+fn test_extra_precompile_is_called() {
+    let precompiles = builder()
+        .extended(orbit_id(), ORBIT, reverse)
+        .build()
+        .unwrap();
+    assert!(precompiles.contains(&ORBIT));
+
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    let mut evm: TestEvm = ArbitrumEvm::new_with_inspector(
+        context,
+        NoOpInspector {},
+        arbitrum_instructions(),
+        precompiles,
+    );
+
+    match execute_tx(&mut evm, create_call_tx(ORBIT, vec![1, 2, 3], 100_000)) {
+        ExecutionResult::Success { output, .. } => {
+            assert_eq!(output.data().as_ref(), &[3, 2, 1]);
+        }
+        other => panic!("call failed: {other:?}"),
+    }
+}
+
+#[test]
+fn test_extra_precompile_reads_input_from_caller_memory() {
+    let precompiles = builder()
+        .extended(orbit_id(), ORBIT, reverse)
+        .build()
+        .unwrap();
+
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, RELAY, RELAY_CODE);
+    let mut evm: TestEvm = ArbitrumEvm::new_with_inspector(
+        context,
+        NoOpInspector {},
+        arbitrum_instructions(),
+        precompiles,
+    );
+
+    match execute_tx(&mut evm, create_call_tx(RELAY, vec![1, 2, 3, 4], 100_000)) {
+        ExecutionResult::Success { output, .. } => {
+            assert_eq!(output.data().as_ref(), &[4, 3, 2, 1]);
+        }
+        other => panic!("relay failed: {other:?}"),
+    }
+}
+
+#[test]
+fn test_precompile_written_with_the_macros_is_called() {
+    let precompiles = builder()
+        .extended(
+            PrecompileId::Custom("Doubler".into()),
+            DOUBLER,
+            precompile_impl!(Doubler),
+        )
+        .build()
+        .unwrap();
+
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    let mut evm: TestEvm = ArbitrumEvm::new_with_inspector(
+        context,
+        NoOpInspector {},
+        arbitrum_instructions(),
+        precompiles,
+    );
+
+    let calldata = IDoubler::doubleCall { x: U256::from(21) }.abi_encode();
+    match execute_tx(&mut evm, create_call_tx(DOUBLER, calldata, 100_000)) {
+        ExecutionResult::Success { output, .. } => {
+            assert_eq!(U256::from_be_slice(output.data()), U256::from(42));
+        }
+        other => panic!("call failed: {other:?}"),
+    }
+}
+
+#[test]
+fn test_dispatch_rejects_bad_calldata() {
+    // Too short for a selector, consuming all gas
+    let result = doubler_inner(&[0x01, 0x02]);
+    assert_eq!(result.result, InstructionResult::Revert);
+    assert_eq!(result.gas.remaining(), 0);
+
+    let result = doubler_inner(&[0x01, 0x02, 0x03, 0x04]);
+    assert_eq!(result.result, InstructionResult::Revert);
+    assert_eq!(result.output, revert_message("Unknown function selector"));
+    assert!(result.gas.remaining() > 0);
+
+    // The selector without its argument
+    let result = doubler_inner(&IDoubler::doubleCall::SELECTOR);
+    assert_eq!(result.result, InstructionResult::Revert);
+    assert_eq!(result.output, revert_message("Invalid calldata"));
+}
+
+#[test]
+fn test_extra_precompiles_conflicting_with_others_are_rejected() {
+    // Taken by an Arbitrum precompile, by Ethereum precompiles of the current and a later
+    // hardfork, and by another extra precompile
+    let p256_verify = address!("0x0000000000000000000000000000000000000100");
+    for address in [ARB_SYS_ADDRESS, Address::with_last_byte(1), p256_verify] {
+        let result = builder()
+            .spec(SpecId::CANCUN)
+            .extended(orbit_id(), address, reverse)
+            .build();
+        assert_eq!(result.err(), Some(PrecompileConflict { address }));
+    }
+
+    let result = builder()
+        .extended(orbit_id(), ORBIT, reverse)
+        .extended(orbit_id(), ORBIT, reverse)
+        .build();
+    assert_eq!(result.err(), Some(PrecompileConflict { address: ORBIT }));
+}
+
+#[test]
+fn test_extra_precompiles_survive_spec_changes() {
+    let mut precompiles = builder()
+        .spec(SpecId::CANCUN)
+        .extended(orbit_id(), ORBIT, reverse)
+        .build()
+        .unwrap();
+
+    assert!(precompiles.set_spec(SpecId::PRAGUE));
+    assert!(precompiles.contains(&ORBIT));
+    assert!(precompiles.contains(&ARB_SYS_ADDRESS));
+}
+
+#[test]
+fn test_failing_extra_precompile_leaves_no_state_or_logs() {
+    let precompile = Precompile::Extended(ExtendedPrecompile::new(
+        PrecompileId::Custom("Scribbler".into()),
+        SCRIBBLER,
+        scribble,
+    ));
+
+    for (input, succeeds) in [([0u8], true), ([1u8], false)] {
+        let mut context = setup_context_with_arbos_state();
+        let result = precompile
+            .call(
+                &mut context,
+                &input,
+                &SCRIBBLER,
+                CALLER,
+                U256::ZERO,
+                false,
+                100_000,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.result.is_ok(), succeeds);
+
+        let journal = context.journal_mut();
+        let slot = journal.sload(SCRIBBLER, U256::ZERO).unwrap().data;
+        assert_eq!(slot, U256::from(u8::from(succeeds)), "input {input:?}");
+        assert_eq!(
+            journal.logs().len(),
+            usize::from(succeeds),
+            "input {input:?}"
+        );
+    }
+}
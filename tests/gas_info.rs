@@ -10,8 +10,8 @@ use revm::{
 
 mod test_utils;
 use test_utils::{
-    create_call_tx, create_call_tx_with_nonce, create_evm, deploy_wat_program, execute_tx,
-    fund_account, setup_context_with_arbos_state,
+    create_call_tx, create_call_tx_with_nonce, create_evm, deploy_evm_code, deploy_wat_program,
+    execute_tx, fund_account, setup_context_with_arbos_state,
 };
 
 // ============================================================================
@@ -344,3 +344,41 @@ fn test_e2e_ink_price_consistency() {
         );
     }
 }
+
+/// Runtime code returning the `GASPRICE` an EVM contract sees.
+const EVM_GASPRICE_CODE: [u8; 9] = [0x3a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+
+#[test]
+fn test_e2e_tx_gas_price_drops_tip() {
+    let mut context = setup_context_with_arbos_state();
+    context.block.basefee = 100;
+
+    let wat = include_bytes!("../test-data/gas-info.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+    let evm_address = Address::repeat_byte(0x0e);
+    deploy_evm_code(&mut context, evm_address, &EVM_GASPRICE_CODE);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(1_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let mut gas_prices = Vec::new();
+    for (nonce, (to, args)) in [(program_address, vec![0x02u8]), (evm_address, vec![])]
+        .into_iter()
+        .enumerate()
+    {
+        let mut tx = create_call_tx_with_nonce(to, args, 1_000_000, nonce as u64);
+        tx.gas_price = 150;
+        match execute_tx(&mut evm, tx) {
+            ExecutionResult::Success { output, .. } => {
+                gas_prices.push(U256::from_be_slice(output.data().as_ref()))
+            }
+            other => panic!("gas price call failed: {:?}", other),
+        }
+    }
+
+    // The priority fee is dropped, so both see the basefee rather than the offered price.
+    assert_eq!(gas_prices[0], U256::from(100));
+    assert_eq!(gas_prices[0], gas_prices[1]);
+}
@@ -0,0 +1,128 @@
+//! Tests for initializing ArbOS state from a Nitro chain config.
+
+use arbos_revm::{
+    constants::{
+        ARBOS_BATCH_POSTER_ADDRESS, INITIAL_BASE_FEE_WEI, INITIAL_PER_BATCH_GAS_COST_V12,
+        INITIAL_SPEED_LIMIT_PER_SECOND_V0, INITIAL_SPEED_LIMIT_PER_SECOND_V6,
+    },
+    state::{ArbState, ArbStateGetter, ArbitrumChainParams, types::StorageBackedTr},
+};
+use revm::primitives::{Address, U256, address};
+
+mod test_utils;
+use test_utils::setup_context;
+
+const OWNER: Address = address!("0x3f1eae7d46d88f08fc2f8ed27fcb2ab183eb2d0e");
+
+fn chain_config(arbos_version: u64) -> String {
+    format!(
+        r#"{{"chainId":412346,"arbitrum":{{"EnableArbOS":true,"InitialArbOSVersion":{arbos_version},"InitialChainOwner":"{OWNER}","GenesisBlockNum":0}}}}"#
+    )
+}
+
+#[test]
+fn test_genesis_sets_owner_pricing_and_version() {
+    let mut context = setup_context();
+    let mut arb_state = context.arb_state(None, false);
+    arb_state
+        .initialize_genesis(&chain_config(32), U256::from(50_000_000_000_u64))
+        .expect("failed to initialize ArbOS state");
+
+    assert_eq!(arb_state.arbos_version().get().unwrap(), 32);
+    assert_eq!(arb_state.chain_id().get().unwrap(), U256::from(412346));
+    assert_eq!(arb_state.chain_owners().all().unwrap(), vec![OWNER]);
+    assert_eq!(arb_state.network_fee_account().get().unwrap(), OWNER);
+
+    let mut l1_pricing = arb_state.l1_pricing();
+    assert_eq!(l1_pricing.reward_recipient().get().unwrap(), OWNER);
+    assert_eq!(
+        l1_pricing.price_per_unit().get().unwrap(),
+        U256::from(50_000_000_000_u64)
+    );
+    assert_eq!(
+        l1_pricing.batch_poster_table().all().unwrap(),
+        vec![ARBOS_BATCH_POSTER_ADDRESS]
+    );
+    // Set by the ArbOS 11 migration
+    assert_eq!(
+        l1_pricing.per_batch_gas_cost().get().unwrap(),
+        INITIAL_PER_BATCH_GAS_COST_V12
+    );
+    assert_eq!(l1_pricing.amortized_cost_cap_bips().get().unwrap(), 0);
+
+    let mut l2_pricing = arb_state.l2_pricing();
+    assert_eq!(
+        l2_pricing.speed_limit_per_second().get().unwrap(),
+        INITIAL_SPEED_LIMIT_PER_SECOND_V6
+    );
+    assert_eq!(
+        l2_pricing.base_fee_wei().get().unwrap(),
+        U256::from(INITIAL_BASE_FEE_WEI)
+    );
+}
+
+#[test]
+fn test_arbos_1_pays_the_batch_poster() {
+    let mut context = setup_context();
+    let mut arb_state = context.arb_state(None, false);
+    arb_state
+        .initialize_genesis(&chain_config(1), U256::ZERO)
+        .expect("failed to initialize ArbOS state");
+
+    assert_eq!(
+        arb_state.network_fee_account().get().unwrap(),
+        Address::ZERO
+    );
+    assert_eq!(
+        arb_state.l1_pricing().reward_recipient().get().unwrap(),
+        ARBOS_BATCH_POSTER_ADDRESS
+    );
+    assert_eq!(
+        arb_state
+            .l2_pricing()
+            .speed_limit_per_second()
+            .get()
+            .unwrap(),
+        INITIAL_SPEED_LIMIT_PER_SECOND_V0
+    );
+}
+
+#[test]
+fn test_genesis_runs_once() {
+    let mut context = setup_context();
+    let mut arb_state = context.arb_state(None, false);
+    arb_state
+        .initialize_genesis(&chain_config(32), U256::ZERO)
+        .expect("failed to initialize ArbOS state");
+
+    assert!(
+        arb_state
+            .initialize_genesis(&chain_config(32), U256::ZERO)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_chain_preset_initializes_state() {
+    let chain = ArbitrumChainParams::arbitrum_one();
+    let mut context = setup_context();
+    let mut arb_state = context.arb_state(None, false);
+    arb_state
+        .initialize_chain(&chain, U256::ZERO)
+        .expect("failed to initialize ArbOS state");
+
+    assert_eq!(arb_state.arbos_version().get().unwrap(), 6);
+    assert_eq!(arb_state.chain_id().get().unwrap(), U256::from(42161));
+    assert_eq!(
+        arb_state.genesis_block_num().get().unwrap(),
+        chain.genesis_block_num
+    );
+    assert_eq!(
+        arb_state.chain_owners().all().unwrap(),
+        vec![chain.initial_chain_owner]
+    );
+    assert_eq!(
+        arb_state.network_fee_account().get().unwrap(),
+        chain.initial_chain_owner
+    );
+}
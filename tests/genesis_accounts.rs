@@ -0,0 +1,84 @@
+//! Tests for the accounts created alongside ArbOS state.
+
+use arbos_revm::{
+    constants::{ARBOS_STATE_ADDRESS, PRECOMPILE_MARKER_CODE},
+    state::{ArbState, arbos_state::ArbosStateParams},
+};
+use revm::{
+    context::{ContextTr, JournalTr},
+    primitives::{Address, address},
+};
+
+mod test_utils;
+use test_utils::{TestContext, setup_context, setup_context_with_arbos_state};
+
+const ARB_SYS: Address = address!("0x0000000000000000000000000000000000000064");
+const ARB_OWNER: Address = address!("0x0000000000000000000000000000000000000070");
+const ARB_WASM: Address = address!("0x0000000000000000000000000000000000000071");
+
+fn code(context: &mut TestContext, address: Address) -> Vec<u8> {
+    context.journal_mut().code(address).unwrap().data.to_vec()
+}
+
+#[test]
+fn test_precompiles_get_marker_code() {
+    let mut context = setup_context_with_arbos_state();
+
+    for address in [ARB_SYS, ARB_OWNER, ARB_WASM] {
+        assert_eq!(code(&mut context, address), PRECOMPILE_MARKER_CODE);
+    }
+}
+
+#[test]
+fn test_arbos_state_account_has_nonce() {
+    let mut context = setup_context_with_arbos_state();
+
+    let account = context
+        .journal_mut()
+        .load_account(ARBOS_STATE_ADDRESS)
+        .unwrap();
+    assert_eq!(account.data.info.nonce, 1);
+}
+
+#[test]
+fn test_precompiles_introduced_later_are_left_empty() {
+    let mut context = setup_context();
+    context.cfg.arbos_version = 20;
+    context
+        .arb_state(None, false)
+        .initialize(&ArbosStateParams::default())
+        .expect("failed to initialize ArbOS state");
+
+    assert_eq!(code(&mut context, ARB_SYS), PRECOMPILE_MARKER_CODE);
+    assert!(code(&mut context, ARB_WASM).is_empty());
+}
+
+#[test]
+fn test_reinitializing_keeps_nonce() {
+    let mut context = setup_context_with_arbos_state();
+    context
+        .arb_state(None, false)
+        .initialize(&ArbosStateParams::default())
+        .expect("failed to initialize ArbOS state");
+
+    let account = context
+        .journal_mut()
+        .load_account(ARBOS_STATE_ADDRESS)
+        .unwrap();
+    assert_eq!(account.data.info.nonce, 1);
+}
+
+#[test]
+fn test_accounts_are_committed() {
+    let mut context = setup_context_with_arbos_state();
+
+    let state = context.journal_mut().finalize();
+
+    let arb_sys = state.get(&ARB_SYS).expect("ArbSys account missing");
+    assert!(arb_sys.is_touched());
+    assert_eq!(
+        arb_sys.info.code.as_ref().unwrap().original_byte_slice(),
+        PRECOMPILE_MARKER_CODE
+    );
+    assert_eq!(state[&ARBOS_STATE_ADDRESS].info.nonce, 1);
+}
@@ -0,0 +1,125 @@
+//! Tests for the NUMBER and BLOCKHASH opcodes answering for L1 blocks once ArbOS state holds a
+//! version, and for L2 blocks before.
+
+use arbos_revm::{
+    arbos_version::ArbosVersion,
+    state::{ArbState, ArbStateGetter, types::StorageBackedTr},
+};
+use revm::{
+    context::result::ExecutionResult,
+    primitives::{Address, B256, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx_with_nonce, create_evm, deploy_evm_code, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const NUMBER_PROBE: Address = address!("0x0000000000000000000000000000000000000043");
+const BLOCKHASH_PROBE: Address = address!("0x0000000000000000000000000000000000000040");
+const L2_BLOCK_NUMBER: u64 = 300;
+const L1_BLOCK_NUMBER: u64 = 41;
+
+/// Returns NUMBER.
+const NUMBER_CODE: &[u8] = &[
+    0x43, // NUMBER
+    0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3, // MSTORE(0), RETURN(0, 32)
+];
+
+/// Returns the BLOCKHASH of the block number in its calldata.
+const BLOCKHASH_CODE: &[u8] = &[
+    0x60, 0x00, 0x35, 0x40, // BLOCKHASH(CALLDATALOAD(0))
+    0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3, // MSTORE(0), RETURN(0, 32)
+];
+
+fn l1_block_hash(number: u64) -> B256 {
+    B256::from(U256::from(number) << 8)
+}
+
+/// L2 block [`L2_BLOCK_NUMBER`], opened after StartBlock recorded L1 blocks 10 and
+/// [`L1_BLOCK_NUMBER`]. Without a version, ArbOS state counts as uninitialized.
+fn setup(with_arbos_version: bool) -> TestEvm {
+    let mut context = setup_context_with_arbos_state();
+    context.block.number = U256::from(L2_BLOCK_NUMBER);
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, NUMBER_PROBE, NUMBER_CODE);
+    deploy_evm_code(&mut context, BLOCKHASH_PROBE, BLOCKHASH_CODE);
+
+    let version = context.cfg.arbos_version;
+    let mut arb_state = context.arb_state(None, false);
+    if with_arbos_version {
+        arb_state.arbos_version().set(u64::from(version)).unwrap();
+    }
+    for number in [10, L1_BLOCK_NUMBER] {
+        arb_state
+            .blockhashes()
+            .record_new_l1_block(number, l1_block_hash(number), ArbosVersion::new(version))
+            .unwrap();
+    }
+    drop(arb_state);
+    create_evm(context)
+}
+
+fn call(evm: &mut TestEvm, to: Address, calldata: Vec<u8>, nonce: u64) -> U256 {
+    match execute_tx(
+        evm,
+        create_call_tx_with_nonce(to, calldata, 1_000_000, nonce),
+    ) {
+        ExecutionResult::Success { output, .. } => U256::from_be_slice(output.data()),
+        other => panic!("call failed: {other:?}"),
+    }
+}
+
+fn blockhash(evm: &mut TestEvm, number: u64, nonce: u64) -> B256 {
+    let calldata = U256::from(number).to_be_bytes::<32>().to_vec();
+    B256::from(call(evm, BLOCKHASH_PROBE, calldata, nonce))
+}
+
+#[test]
+fn test_number_is_the_next_l1_block_number() {
+    let mut evm = setup(true);
+
+    // ArbOS holds the number of the L1 block after the last one recorded
+    let number = call(&mut evm, NUMBER_PROBE, Vec::new(), 0);
+    assert_eq!(number, U256::from(L1_BLOCK_NUMBER + 1));
+}
+
+#[test]
+fn test_blockhash_reads_l1_block_hashes() {
+    let mut evm = setup(true);
+
+    assert_eq!(
+        blockhash(&mut evm, L1_BLOCK_NUMBER, 0),
+        l1_block_hash(L1_BLOCK_NUMBER)
+    );
+    assert_eq!(blockhash(&mut evm, 10, 1), l1_block_hash(10));
+
+    // The blocks skipped between 10 and 41 are filled in with derived hashes
+    let skipped = blockhash(&mut evm, 20, 2);
+    assert_ne!(skipped, B256::ZERO);
+    assert_ne!(skipped, l1_block_hash(20));
+}
+
+#[test]
+fn test_blockhash_is_zero_outside_the_l1_window() {
+    let mut evm = setup(true);
+
+    // The L1 block in progress and the L2 blocks are out of range
+    assert_eq!(blockhash(&mut evm, L1_BLOCK_NUMBER + 1, 0), B256::ZERO);
+    assert_eq!(blockhash(&mut evm, L2_BLOCK_NUMBER - 1, 1), B256::ZERO);
+    assert_eq!(blockhash(&mut evm, u64::MAX, 2), B256::ZERO);
+}
+
+#[test]
+fn test_number_and_blockhash_are_l2_without_arbos_version() {
+    let mut evm = setup(false);
+    let hash = B256::repeat_byte(0x99);
+    evm.record_block_hash(L2_BLOCK_NUMBER - 1, hash);
+
+    let number = call(&mut evm, NUMBER_PROBE, Vec::new(), 0);
+    assert_eq!(number, U256::from(L2_BLOCK_NUMBER));
+    assert_eq!(blockhash(&mut evm, L2_BLOCK_NUMBER - 1, 1), hash);
+    assert_eq!(blockhash(&mut evm, L1_BLOCK_NUMBER, 2), B256::ZERO);
+}
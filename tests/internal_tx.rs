@@ -0,0 +1,194 @@
+//! Tests for ArbOS internal transactions (type 0x6A): start-of-block updates and batch posting
+//! reports.
+
+use arbos_revm::{
+    ArbitrumInternalTx,
+    constants::ARBOS_L1_PRICER_FUNDS_ADDRESS,
+    state::{ArbState, ArbStateGetter, types::StorageBackedTr},
+};
+use revm::{
+    ExecuteEvm,
+    context::{ContextTr, JournalTr},
+    primitives::{Address, Bytes, I256, U256, address},
+};
+
+mod test_utils;
+use test_utils::{TestContext, TestEvm, create_evm, fund_account, setup_context_with_arbos_state};
+
+const POSTER: Address = address!("0x00000000000000000000000000000000000000b7");
+const TIMESTAMP: u64 = 1_000;
+const L1_FEES: u64 = 1_000_000;
+
+fn setup() -> TestContext {
+    let mut context = setup_context_with_arbos_state();
+    context.block.timestamp = U256::from(TIMESTAMP);
+    context
+}
+
+/// A context whose L1 pricer holds `fees` and has collected 1000 units since its last update.
+fn setup_l1_pricing(fees: u64) -> TestContext {
+    let mut context = setup();
+    fund_account(
+        &mut context,
+        ARBOS_L1_PRICER_FUNDS_ADDRESS,
+        U256::from(fees),
+    );
+    let mut arb_state = context.arb_state(None, false);
+    let mut l1_pricing = arb_state.l1_pricing();
+    l1_pricing
+        .l1_fees_available()
+        .set(U256::from(fees))
+        .unwrap();
+    l1_pricing.units_since_update().set(1_000).unwrap();
+    l1_pricing.inertia().set(10).unwrap();
+    l1_pricing
+        .equilibration_units()
+        .set(U256::from(1_000_000))
+        .unwrap();
+    drop(arb_state);
+    context
+}
+
+fn report(evm: &mut TestEvm, batch_data_gas: u64, l1_base_fee: u64) {
+    let tx = ArbitrumInternalTx::batch_posting_report(
+        1,
+        TIMESTAMP,
+        POSTER,
+        1,
+        batch_data_gas,
+        U256::from(l1_base_fee),
+    );
+    assert!(evm.transact_one(tx.into()).unwrap().is_success());
+}
+
+fn balance(evm: &mut TestEvm, address: Address) -> U256 {
+    evm.0
+        .ctx
+        .journal_mut()
+        .load_account(address)
+        .unwrap()
+        .data
+        .info
+        .balance
+}
+
+#[test]
+fn test_start_block_records_l1_block_number() {
+    let mut evm = create_evm(setup());
+
+    let tx = ArbitrumInternalTx::start_block(1, U256::ZERO, 5, 1, 0);
+    assert!(evm.transact_one(tx.into()).unwrap().is_success());
+
+    let mut arb_state = evm.0.ctx.arb_state(None, true);
+    let mut block_hashes = arb_state.blockhashes();
+    assert_eq!(block_hashes.l1_block_number().get().unwrap(), 5);
+    assert!(block_hashes.block_hash(4).is_ok());
+}
+
+#[test]
+fn test_start_block_drains_gas_backlog() {
+    let mut context = setup();
+    let mut arb_state = context.arb_state(None, false);
+    let mut l2_pricing = arb_state.l2_pricing();
+    l2_pricing.speed_limit_per_second().set(1_000).unwrap();
+    l2_pricing.pricing_inertia().set(100).unwrap();
+    l2_pricing.backlog_tolerance().set(10).unwrap();
+    l2_pricing.min_base_fee_wei().set(U256::from(100)).unwrap();
+    l2_pricing.gas_backlog().set(50_000).unwrap();
+    drop(arb_state);
+    let mut evm = create_evm(context);
+
+    // 20 seconds drain 20000 gas, leaving the backlog 20000 gas over the tolerance.
+    let tx = ArbitrumInternalTx::start_block(1, U256::ZERO, 0, 1, 20);
+    assert!(evm.transact_one(tx.into()).unwrap().is_success());
+
+    let mut arb_state = evm.0.ctx.arb_state(None, true);
+    let mut l2_pricing = arb_state.l2_pricing();
+    assert_eq!(l2_pricing.gas_backlog().get().unwrap(), 30_000);
+    let base_fee = l2_pricing.base_fee_wei().get().unwrap();
+    assert!(base_fee > U256::from(100) && base_fee < U256::from(200));
+    drop(arb_state);
+
+    // Once the backlog is within the tolerance the basefee returns to the minimum.
+    let tx = ArbitrumInternalTx::start_block(1, U256::ZERO, 0, 2, 30);
+    assert!(evm.transact_one(tx.into()).unwrap().is_success());
+
+    let mut arb_state = evm.0.ctx.arb_state(None, true);
+    let mut l2_pricing = arb_state.l2_pricing();
+    assert_eq!(l2_pricing.gas_backlog().get().unwrap(), 0);
+    assert_eq!(l2_pricing.base_fee_wei().get().unwrap(), U256::from(100));
+}
+
+#[test]
+fn test_batch_posting_report_pays_poster() {
+    let mut evm = create_evm(setup_l1_pricing(L1_FEES));
+
+    report(&mut evm, 100, 10);
+
+    assert_eq!(balance(&mut evm, POSTER), U256::from(1_000));
+    assert_eq!(
+        balance(&mut evm, ARBOS_L1_PRICER_FUNDS_ADDRESS),
+        U256::from(L1_FEES - 1_000)
+    );
+
+    let mut arb_state = evm.0.ctx.arb_state(None, true);
+    let mut l1_pricing = arb_state.l1_pricing();
+    assert_eq!(
+        l1_pricing.l1_fees_available().get().unwrap(),
+        U256::from(L1_FEES - 1_000)
+    );
+    assert_eq!(l1_pricing.units_since_update().get().unwrap(), 0);
+    assert_eq!(l1_pricing.last_update_time().get().unwrap(), TIMESTAMP);
+    assert_eq!(
+        l1_pricing.last_surplus().get().unwrap(),
+        I256::try_from(L1_FEES - 1_000).unwrap()
+    );
+    // A surplus pushes the price down, and it cannot go below zero.
+    assert_eq!(l1_pricing.price_per_unit().get().unwrap(), U256::ZERO);
+    assert!(l1_pricing.batch_poster_table().contains(POSTER).unwrap());
+}
+
+#[test]
+fn test_batch_posting_report_owes_what_it_cannot_pay() {
+    let mut evm = create_evm(setup_l1_pricing(400));
+
+    report(&mut evm, 100, 10);
+
+    assert_eq!(balance(&mut evm, POSTER), U256::from(400));
+    let mut arb_state = evm.0.ctx.arb_state(None, true);
+    let mut l1_pricing = arb_state.l1_pricing();
+    let mut batch_posters = l1_pricing.batch_poster_table();
+    assert_eq!(
+        batch_posters.get(POSTER).funds_due().get().unwrap(),
+        U256::from(600)
+    );
+    assert_eq!(
+        batch_posters.total_funds_due().get().unwrap(),
+        I256::try_from(600_u64).unwrap()
+    );
+}
+
+#[test]
+fn test_batch_posting_report_applies_amortized_cost_cap() {
+    let mut context = setup_l1_pricing(L1_FEES);
+    context
+        .arb_state(None, false)
+        .l1_pricing()
+        .amortized_cost_cap_bips()
+        .set(5_000)
+        .unwrap();
+    let mut evm = create_evm(context);
+
+    // 10000 wei spent, capped at half the basefee times the 1000 units allocated.
+    report(&mut evm, 1_000, 10);
+
+    assert_eq!(balance(&mut evm, POSTER), U256::from(5_000));
+}
+
+#[test]
+fn test_unknown_internal_method_is_rejected() {
+    let mut evm = create_evm(setup());
+
+    let tx = ArbitrumInternalTx::new(1, Bytes::from_static(&[0, 0, 0, 1]));
+    assert!(evm.transact_one(tx.into()).is_err());
+}
@@ -0,0 +1,83 @@
+//! Tests for charging Nitro's intrinsic gas.
+
+use arbos_revm::{
+    ArbitrumTransactionError,
+    arbos_version::ArbosVersion,
+    handler::intrinsic::{INIT_CODE_WORD_GAS, intrinsic_gas},
+};
+use revm::{
+    ExecuteEvm,
+    context::{
+        TxEnv,
+        result::{EVMError, InvalidTransaction},
+    },
+    primitives::{Address, TxKind, U256},
+};
+
+mod test_utils;
+use test_utils::{
+    create_call_tx, create_evm, execute_tx, fund_account, setup_context_with_arbos_state,
+};
+
+const CALLDATA: [u8; 6] = [0x00, 0x00, 0x12, 0x34, 0x00, 0x56];
+
+/// Gas used by `tx` run under ArbOS `version`.
+fn gas_used(version: u16, tx: TxEnv) -> u64 {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.arbos_version = version;
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    let mut evm = create_evm(context);
+    let result = execute_tx(&mut evm, tx);
+    assert!(result.is_success());
+    result.gas_used()
+}
+
+#[test]
+fn test_call_is_charged_intrinsic_gas() {
+    let tx = create_call_tx(Address::repeat_byte(0x42), CALLDATA.to_vec(), 100_000);
+    let expected = intrinsic_gas(ArbosVersion::new(42), &CALLDATA, false, 0, 0, 0);
+    assert_eq!(
+        gas_used(42, tx),
+        expected.initial_gas.max(expected.floor_gas)
+    );
+}
+
+#[test]
+fn test_initcode_charge_follows_arbos_version() {
+    // Initcode that stops at once, deploying no code, two words long
+    let initcode = vec![0x00; 40];
+    let create = || TxEnv {
+        kind: TxKind::Create,
+        data: initcode.clone().into(),
+        ..create_call_tx(Address::ZERO, Vec::new(), 200_000)
+    };
+
+    let before = gas_used(10, create());
+    let after = gas_used(11, create());
+    assert_eq!(after - before, 2 * INIT_CODE_WORD_GAS);
+}
+
+#[test]
+fn test_gas_limit_below_intrinsic_gas_is_rejected() {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    let mut evm = create_evm(context);
+
+    let intrinsic = intrinsic_gas(ArbosVersion::new(42), &CALLDATA, false, 0, 0, 0).initial_gas;
+    let tx = create_call_tx(Address::repeat_byte(0x42), CALLDATA.to_vec(), intrinsic - 1);
+    let error = evm.transact_one(tx.into()).unwrap_err();
+    assert!(matches!(
+        error,
+        EVMError::Transaction(ArbitrumTransactionError::Base(
+            InvalidTransaction::CallGasCostMoreThanGasLimit { .. }
+        ))
+    ));
+}
@@ -0,0 +1,180 @@
+//! Tests for the L2 basefee evolving across blocks with the gas backlog.
+
+use arbos_revm::{
+    ArbitrumTransactionError,
+    state::{ArbState, ArbStateGetter, types::StorageBackedTr},
+};
+use revm::{
+    ExecuteEvm,
+    context::{
+        TxEnv,
+        result::{EVMError, InvalidTransaction},
+    },
+    interpreter::Gas,
+    primitives::{Address, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, TestEvm, create_call_tx, create_call_tx_with_nonce, create_evm, execute_tx,
+    fund_account, setup_context_with_arbos_state,
+};
+
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const RECIPIENT: Address = address!("0x00000000000000000000000000000000000000b0");
+const MIN_BASE_FEE: u64 = 100;
+
+/// A context draining 1000 gas a second, tolerating 10 seconds of backlog.
+fn setup() -> TestContext {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    let mut arb_state = context.arb_state(None, false);
+    let mut l2_pricing = arb_state.l2_pricing();
+    l2_pricing.speed_limit_per_second().set(1_000).unwrap();
+    l2_pricing.pricing_inertia().set(100).unwrap();
+    l2_pricing.backlog_tolerance().set(10).unwrap();
+    l2_pricing
+        .min_base_fee_wei()
+        .set(U256::from(MIN_BASE_FEE))
+        .unwrap();
+    drop(arb_state);
+    context
+}
+
+fn backlog(evm: &mut TestEvm) -> u64 {
+    evm.0
+        .ctx
+        .arb_state(None, true)
+        .l2_pricing()
+        .gas_backlog()
+        .get()
+        .unwrap()
+}
+
+#[test]
+fn test_transactions_grow_the_backlog() {
+    let mut evm = create_evm(setup());
+
+    let first = execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(RECIPIENT, Vec::new(), 100_000, 0),
+    );
+    assert_eq!(backlog(&mut evm), first.gas_used());
+
+    let second = execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(RECIPIENT, Vec::new(), 100_000, 1),
+    );
+    assert_eq!(backlog(&mut evm), first.gas_used() + second.gas_used());
+}
+
+#[test]
+fn test_basefee_tracks_backlog_across_blocks() {
+    let mut evm = create_evm(setup());
+    for nonce in 0..2 {
+        execute_tx(
+            &mut evm,
+            create_call_tx_with_nonce(RECIPIENT, Vec::new(), 100_000, nonce),
+        );
+    }
+    assert_eq!(backlog(&mut evm), 42_000);
+
+    // 2 seconds drain 2000 gas, leaving the backlog 30000 gas over the tolerance.
+    let base_fee = evm.update_pricing_model(2).unwrap();
+    assert_eq!(backlog(&mut evm), 40_000);
+    assert!(base_fee > U256::from(MIN_BASE_FEE) && base_fee < U256::from(2 * MIN_BASE_FEE));
+
+    // A quiet minute drains the backlog and returns the basefee to the minimum.
+    let base_fee = evm.update_pricing_model(60).unwrap();
+    assert_eq!(backlog(&mut evm), 0);
+    assert_eq!(base_fee, U256::from(MIN_BASE_FEE));
+}
+
+/// A transfer paying `gas_price` per gas.
+fn transfer(gas_price: u128, nonce: u64) -> TxEnv {
+    TxEnv {
+        gas_price,
+        ..create_call_tx_with_nonce(RECIPIENT, Vec::new(), 100_000, nonce)
+    }
+}
+
+#[test]
+fn test_basefee_below_minimum_is_raised() {
+    let mut context = setup();
+    context.cfg.enforce_min_base_fee = true;
+    let mut evm = create_evm(context);
+
+    // Paying 1 wei per gas is below the 100 wei minimum the block is raised to
+    let error = evm
+        .transact_one(create_call_tx(RECIPIENT, Vec::new(), 100_000).into())
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        EVMError::Transaction(ArbitrumTransactionError::Base(
+            InvalidTransaction::GasPriceLessThanBasefee
+        ))
+    ));
+    assert_eq!(evm.0.ctx.block.basefee, MIN_BASE_FEE);
+
+    assert!(execute_tx(&mut evm, transfer(MIN_BASE_FEE.into(), 0)).is_success());
+}
+
+#[test]
+fn test_raised_minimum_constrains_later_blocks() {
+    let mut context = setup();
+    context.cfg.enforce_min_base_fee = true;
+    context.block.basefee = 2 * MIN_BASE_FEE;
+    let mut evm = create_evm(context);
+
+    // A basefee above the minimum is left as is
+    assert!(execute_tx(&mut evm, transfer((2 * MIN_BASE_FEE).into(), 0)).is_success());
+    assert_eq!(evm.0.ctx.block.basefee, 2 * MIN_BASE_FEE);
+
+    evm.0
+        .ctx
+        .arb_state(None, false)
+        .l2_pricing()
+        .min_base_fee_wei()
+        .set(U256::from(5 * MIN_BASE_FEE))
+        .unwrap();
+    let base_fee = evm.update_pricing_model(60).unwrap();
+    assert_eq!(base_fee, U256::from(5 * MIN_BASE_FEE));
+
+    // The next block reuses the stale basefee, which is raised to the new minimum
+    assert!(execute_tx(&mut evm, transfer((5 * MIN_BASE_FEE).into(), 1)).is_success());
+    assert_eq!(evm.0.ctx.block.basefee, 5 * MIN_BASE_FEE);
+}
+
+#[test]
+fn test_minimum_is_not_enforced_by_default() {
+    let mut evm = create_evm(setup());
+    assert!(execute_tx(&mut evm, transfer(1, 0)).is_success());
+    assert_eq!(evm.0.ctx.block.basefee, 0);
+}
+
+#[test]
+fn test_system_reads_do_not_charge_gas() {
+    let mut context = setup();
+    let mut gas = Gas::new(100_000);
+    let base_fee = context
+        .arb_state(Some(&mut gas), true)
+        .l2_pricing()
+        .base_fee_wei()
+        .get()
+        .unwrap();
+    assert!(gas.spent() > 0);
+
+    let mut arb_state = context.system_arb_state(true);
+    assert_eq!(
+        arb_state.l2_pricing().base_fee_wei().get().unwrap(),
+        base_fee
+    );
+    assert!(arb_state.l2_pricing().gas_backlog().set(1).is_err());
+
+    // A block's pricing update and fee charging leave the transfer's gas intrinsic
+    drop(arb_state);
+    let mut evm = create_evm(context);
+    let result = execute_tx(&mut evm, transfer(1_000, 0));
+    assert!(result.is_success());
+    assert_eq!(result.gas_used(), 21_000);
+}
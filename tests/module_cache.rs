@@ -0,0 +1,169 @@
+//! Tests for backing the program cache with a persistent Stylus module cache.
+
+use std::{
+    collections::HashMap,
+    fs,
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use arbos_revm::{
+    program_cache::{DiskModuleCache, ProgramCache, ProgramCacheTag, StylusModuleCache},
+    state::{ArbState, ArbStateGetter},
+};
+use revm::{
+    context::{ContextTr, JournalTr},
+    primitives::{Address, B256, U256},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, create_call_tx, create_evm, deploy_wat_program, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+/// An empty module cache directory unique to the calling test.
+fn cache_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("arbos-revm-modules-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Deploy the storage test program, returning the context, its address, its code hash and the
+/// tag it compiles under.
+fn setup() -> (TestContext, Address, B256, ProgramCacheTag) {
+    let mut context = setup_context_with_arbos_state();
+    let address = deploy_wat_program(&mut context, include_bytes!("../test-data/storage.wat"));
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    let code_hash = context.journal_mut().code_hash(address).unwrap().data;
+    let stylus_version = context
+        .arb_state(None, true)
+        .programs()
+        .stylus_params()
+        .get()
+        .unwrap()
+        .version;
+    let tag = ProgramCacheTag {
+        stylus_version,
+        arbos_version: context.cfg.arbos_version,
+        debug: false,
+    };
+    (context, address, code_hash, tag)
+}
+
+/// Call the program once through an EVM using a fresh cache backed by `module_cache`, as a
+/// restarted node would.
+fn run(module_cache: Arc<dyn StylusModuleCache>) -> ProgramCache {
+    let (context, address, _, _) = setup();
+    let cache = ProgramCache::new(NonZeroUsize::new(16).unwrap()).with_module_cache(module_cache);
+    let mut evm = create_evm(context).with_program_cache(cache.clone());
+    // Selector 0x00 = read slot 0
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    assert!(execute_tx(&mut evm, create_call_tx(address, args, 10_000_000)).is_success());
+    cache
+}
+
+/// In-memory module cache counting loads that found a program.
+#[derive(Default)]
+struct CountingModuleCache {
+    modules: Mutex<HashMap<(B256, ProgramCacheTag), Vec<u8>>>,
+    hits: Mutex<usize>,
+}
+
+impl StylusModuleCache for CountingModuleCache {
+    fn load(&self, code_hash: &B256, tag: ProgramCacheTag) -> Option<Vec<u8>> {
+        let native = self
+            .modules
+            .lock()
+            .unwrap()
+            .get(&(*code_hash, tag))
+            .cloned();
+        if native.is_some() {
+            *self.hits.lock().unwrap() += 1;
+        }
+        native
+    }
+
+    fn store(&self, code_hash: &B256, tag: ProgramCacheTag, native_code: &[u8]) {
+        self.modules
+            .lock()
+            .unwrap()
+            .insert((*code_hash, tag), native_code.to_vec());
+    }
+}
+
+#[test]
+fn test_compiled_programs_survive_restart() {
+    let dir = cache_dir("restart");
+    let disk = Arc::new(DiskModuleCache::new(&dir));
+    let (_, _, code_hash, tag) = setup();
+
+    let first = run(disk.clone());
+    let (native, _, _) = first.get(&code_hash, tag).expect("program not cached");
+    assert!(disk.path(&code_hash, tag).is_file());
+    assert_eq!(disk.load(&code_hash, tag), Some(native.clone()));
+
+    let second = run(disk);
+    let (reloaded, _, _) = second.get(&code_hash, tag).expect("program not cached");
+    assert_eq!(reloaded, native);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_corrupted_modules_are_recompiled() {
+    let dir = cache_dir("corrupted");
+    let disk = Arc::new(DiskModuleCache::new(&dir));
+    let (_, _, code_hash, tag) = setup();
+
+    let native = run(disk.clone()).get(&code_hash, tag).unwrap().0;
+    let path = disk.path(&code_hash, tag);
+    let mut contents = fs::read(&path).unwrap();
+    let last = contents.len() - 1;
+    contents[last] ^= 0xff;
+    fs::write(&path, contents).unwrap();
+    assert_eq!(disk.load(&code_hash, tag), None);
+
+    let recompiled = run(disk.clone()).get(&code_hash, tag).unwrap().0;
+    assert_eq!(recompiled, native);
+    assert_eq!(disk.load(&code_hash, tag), Some(native));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_module_cache_is_pluggable() {
+    let modules = Arc::new(CountingModuleCache::default());
+    let (_, _, code_hash, tag) = setup();
+
+    run(modules.clone());
+    assert_eq!(*modules.hits.lock().unwrap(), 0);
+    assert!(
+        modules
+            .modules
+            .lock()
+            .unwrap()
+            .contains_key(&(code_hash, tag))
+    );
+
+    run(modules.clone());
+    assert_eq!(*modules.hits.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_programs_are_keyed_by_compile_settings() {
+    let disk = DiskModuleCache::new(cache_dir("keys"));
+    let (_, _, code_hash, tag) = setup();
+
+    let debug = ProgramCacheTag { debug: true, ..tag };
+    assert_ne!(disk.path(&code_hash, tag), disk.path(&code_hash, debug));
+    disk.store(&code_hash, tag, b"native");
+    assert_eq!(disk.load(&code_hash, tag), Some(b"native".to_vec()));
+    assert_eq!(disk.load(&code_hash, debug), None);
+    fs::remove_dir_all(disk.dir()).unwrap();
+}
@@ -0,0 +1,121 @@
+//! Tests for the module hash stored when a Stylus program is activated.
+
+use alloy_sol_types::{SolCall, SolEvent, sol};
+use revm::{
+    context::{ContextTr, JournalTr, TxEnv, result::ExecutionResult},
+    primitives::{Address, B256, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx, create_call_tx_with_nonce, create_evm, deploy_wat_program, execute_tx,
+    fund_account, setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbWasm {
+        function activateProgram(address program)
+            external payable returns (uint16 version, uint256 dataFee);
+        function moduleHash(bytes32 codehash) external view returns (bytes32 moduleHash);
+        event ProgramActivated(
+            bytes32 indexed codehash,
+            bytes32 moduleHash,
+            address program,
+            uint256 dataFee,
+            uint16 version
+        );
+    }
+}
+
+const ARB_WASM: Address = address!("0x0000000000000000000000000000000000000071");
+
+/// Deploy the storage test program, returning the EVM, the program's address and code hash.
+fn setup(arb_wasm_module_hash: bool) -> (TestEvm, Address, B256) {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.arb_wasm_module_hash = arb_wasm_module_hash;
+    let address = deploy_wat_program(&mut context, include_bytes!("../test-data/storage.wat"));
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(10u128.pow(18)),
+    );
+    let code_hash = context.journal_mut().code_hash(address).unwrap().data;
+    (create_evm(context), address, code_hash)
+}
+
+/// Activate the program at `address`, returning the module hash of its `ProgramActivated` event.
+fn activate(evm: &mut TestEvm, address: Address) -> B256 {
+    let tx = TxEnv {
+        value: U256::from(10u128.pow(17)),
+        ..create_call_tx(
+            ARB_WASM,
+            ArbWasm::activateProgramCall { program: address }.abi_encode(),
+            20_000_000,
+        )
+    };
+    match execute_tx(evm, tx) {
+        ExecutionResult::Success { logs, .. } => {
+            let log = logs
+                .iter()
+                .find(|log| {
+                    log.topics().first() == Some(&ArbWasm::ProgramActivated::SIGNATURE_HASH)
+                })
+                .expect("no ProgramActivated log");
+            ArbWasm::ProgramActivated::decode_log_data(&log.data)
+                .unwrap()
+                .moduleHash
+        }
+        other => panic!("activation failed: {other:?}"),
+    }
+}
+
+fn module_hash(evm: &mut TestEvm, code_hash: B256, nonce: u64) -> ExecutionResult {
+    let calldata = ArbWasm::moduleHashCall {
+        codehash: code_hash,
+    }
+    .abi_encode();
+    execute_tx(
+        evm,
+        create_call_tx_with_nonce(ARB_WASM, calldata, 1_000_000, nonce),
+    )
+}
+
+#[test]
+fn test_module_hash_matches_activation() {
+    let (mut evm, address, code_hash) = setup(true);
+    let activated = activate(&mut evm, address);
+    assert_ne!(activated, B256::ZERO);
+    assert_eq!(
+        evm.program_artifact(code_hash).unwrap().module_hash,
+        activated
+    );
+
+    match module_hash(&mut evm, code_hash, 1) {
+        ExecutionResult::Success { output, .. } => assert_eq!(
+            ArbWasm::moduleHashCall::abi_decode_returns(output.data()).unwrap(),
+            activated
+        ),
+        other => panic!("moduleHash failed: {other:?}"),
+    }
+}
+
+#[test]
+fn test_module_hash_reverts_for_inactive_program() {
+    let (mut evm, _, code_hash) = setup(true);
+    assert!(matches!(
+        module_hash(&mut evm, code_hash, 0),
+        ExecutionResult::Revert { .. }
+    ));
+}
+
+#[test]
+fn test_module_hash_is_unknown_unless_enabled() {
+    let (mut evm, address, code_hash) = setup(false);
+    activate(&mut evm, address);
+
+    // Like any unknown selector, the call succeeds without doing anything
+    match module_hash(&mut evm, code_hash, 1) {
+        ExecutionResult::Success { output, .. } => assert!(output.data().is_empty()),
+        other => panic!("unknown selector failed: {other:?}"),
+    }
+}
@@ -0,0 +1,122 @@
+//! Tests for the multi-dimensional gas tracer.
+
+use arbos_revm::{
+    ArbitrumEvm,
+    multi_gas::{MultiGasTracer, ResourceKind},
+    precompiles::ArbitrumPrecompileProvider,
+};
+use revm::{
+    InspectEvm,
+    bytecode::opcode,
+    context::{TxEnv, result::ExecutionResult},
+    handler::instructions::EthInstructions,
+    primitives::{Address, U256},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, create_call_tx, deploy_evm_code, deploy_wat_program, fund_account,
+    setup_context_with_arbos_state,
+};
+
+/// Run `tx` with a [`MultiGasTracer`] and return the tracer with the gas used.
+fn trace(mut context: TestContext, tx: TxEnv) -> (MultiGasTracer, u64) {
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        MultiGasTracer::new(),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::<TestContext>::default(),
+    );
+    let result = evm
+        .inspect_one_tx(tx.into())
+        .expect("transaction execution failed");
+    let gas_used = match result {
+        ExecutionResult::Success { gas_used, .. } => gas_used,
+        other => panic!("transaction failed: {:?}", other),
+    };
+
+    (evm.into_inspector(), gas_used)
+}
+
+#[test]
+fn test_evm_opcodes_split_by_resource() {
+    let mut context = setup_context_with_arbos_state();
+
+    // SSTORE(0, 1); LOG0(0, 0); STOP
+    let address = Address::repeat_byte(0x42);
+    deploy_evm_code(
+        &mut context,
+        address,
+        &[
+            0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0xa0, 0x00,
+        ],
+    );
+
+    let (tracer, gas_used) = trace(context, create_call_tx(address, vec![], 1_000_000));
+
+    let sstore = tracer.opcodes[&opcode::SSTORE];
+    assert_eq!(sstore.get(ResourceKind::StorageGrowth), 20_000);
+    assert_eq!(sstore.get(ResourceKind::StorageAccess), 2_100);
+
+    let log = tracer.opcodes[&opcode::LOG0];
+    assert_eq!(log.get(ResourceKind::HistoryGrowth), 375);
+
+    let push = tracer.opcodes[&opcode::PUSH1];
+    assert_eq!(push.get(ResourceKind::Computation), 4 * 3);
+
+    assert_eq!(tracer.total.get(ResourceKind::WasmComputation), 0);
+    assert!(tracer.total.total() <= gas_used);
+}
+
+#[test]
+fn test_forwarded_call_gas_is_not_charged_to_call() {
+    let mut context = setup_context_with_arbos_state();
+
+    // SSTORE(0, 1); STOP
+    let callee = Address::repeat_byte(0x43);
+    deploy_evm_code(&mut context, callee, &[0x60, 0x01, 0x60, 0x00, 0x55, 0x00]);
+
+    // CALL(GAS, callee, 0, 0, 0, 0, 0); STOP
+    let mut relay_code = vec![0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00];
+    relay_code.push(0x73);
+    relay_code.extend_from_slice(callee.as_slice());
+    relay_code.extend_from_slice(&[0x5a, 0xf1, 0x00]);
+    let relay = Address::repeat_byte(0x44);
+    deploy_evm_code(&mut context, relay, &relay_code);
+
+    let (tracer, gas_used) = trace(context, create_call_tx(relay, vec![], 1_000_000));
+
+    // Cold account access only; the callee's SSTORE is charged to SSTORE.
+    assert_eq!(tracer.opcodes[&opcode::CALL].total(), 2_600);
+    assert_eq!(
+        tracer.opcodes[&opcode::SSTORE].get(ResourceKind::StorageGrowth),
+        20_000
+    );
+    assert!(tracer.total.total() <= gas_used);
+}
+
+#[test]
+fn test_stylus_hostios_and_wasm_computation() {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/storage.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let mut args = vec![0x01u8];
+    args.extend_from_slice(&[0xABu8; 32]);
+    args.extend_from_slice(&[0xCDu8; 32]);
+
+    let (tracer, gas_used) = trace(context, create_call_tx(program_address, args, 10_000_000));
+
+    let flush = tracer.hostios["storage_flush_cache"];
+    assert!(flush.get(ResourceKind::StorageAccess) >= 20_000);
+    assert!(tracer.total.get(ResourceKind::WasmComputation) > 0);
+    assert!(tracer.opcodes.is_empty());
+    assert!(tracer.total.total() <= gas_used);
+}
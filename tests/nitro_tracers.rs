@@ -0,0 +1,219 @@
+//! Tests for writing traces in the formats of Nitro's native tracers.
+
+use arbos_revm::{
+    ArbitrumContext, ArbitrumEvm, ArbitrumTransaction,
+    config::ArbitrumConfig,
+    local_context::ArbitrumLocalContext,
+    precompiles::ArbitrumPrecompileProvider,
+    state::{ArbState, arbos_state::ArbosStateParams},
+    tracers::{CallTracer, CallTracerConfig, PrestateTracer, StylusTracer},
+};
+use revm::{
+    DatabaseCommit, InspectEvm, Journal,
+    context::{BlockEnv, ContextTr, JournalTr},
+    database::{CacheDB, EmptyDB},
+    handler::instructions::EthInstructions,
+    primitives::{Address, Bytes, U256, hex, keccak256},
+    state::Bytecode,
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, create_call_tx, deploy_evm_code, deploy_wat_program, fund_account,
+    setup_context_with_arbos_state,
+};
+
+const CALLER: Address = Address::repeat_byte(0x01);
+const OUTER: Address = Address::repeat_byte(0x42);
+const INNER: Address = Address::repeat_byte(0x43);
+
+/// Reads slot 5, emits an empty log, then calls [`INNER`] with 0xffff gas.
+const OUTER_CODE: &[u8] = &[
+    0x60, 0x05, 0x54, 0x50, // SLOAD(5), POP
+    0x60, 0x00, 0x60, 0x00, 0xa0, // LOG0(0, 0)
+    0x60, 0x00, 0x60, 0x00, 0x60, 0x00, // no return data or args
+    0x60, 0x00, 0x60, 0x00, // args offset, no value
+    0x60, 0x43, 0x61, 0xff, 0xff, 0xf1, 0x50, 0x00, // CALL(0xffff, INNER), POP, STOP
+];
+
+/// Reverts without data.
+const INNER_CODE: &[u8] = &[0x60, 0x00, 0x60, 0x00, 0xfd];
+
+fn hex_address(address: Address) -> String {
+    format!("0x{}", hex::encode(address))
+}
+
+fn trace_calls(config: CallTracerConfig) -> (String, u64) {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, OUTER, OUTER_CODE);
+    deploy_evm_code(&mut context, INNER, INNER_CODE);
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        CallTracer::new(config),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::<TestContext>::default(),
+    );
+    let result = evm
+        .inspect_one_tx(create_call_tx(OUTER, Vec::new(), 100_000).into())
+        .expect("transaction execution failed");
+    assert!(result.is_success());
+
+    let gas_used = result.gas_used();
+    let json = evm
+        .into_inspector()
+        .to_json(gas_used)
+        .expect("no call traced");
+    (json, gas_used)
+}
+
+#[test]
+fn test_call_tracer_writes_nitro_json() {
+    let (json, gas_used) = trace_calls(CallTracerConfig {
+        only_top_call: false,
+        with_log: true,
+    });
+    let (caller, outer, inner) = (hex_address(CALLER), hex_address(OUTER), hex_address(INNER));
+    assert_eq!(
+        json,
+        format!(
+            concat!(
+                r#"{{"from":"{caller}","gas":"0x186a0","gasUsed":"{gas_used:#x}","to":"{outer}","input":"0x","#,
+                r#""calls":[{{"from":"{outer}","gas":"0xffff","gasUsed":"0x6","to":"{inner}","input":"0x","#,
+                r#""error":"execution reverted","value":"0x0","type":"CALL"}}],"#,
+                r#""logs":[{{"address":"{outer}","topics":[],"data":"0x","position":"0x0"}}],"#,
+                r#""value":"0x0","type":"CALL"}}"#
+            ),
+            caller = caller,
+            outer = outer,
+            inner = inner,
+            gas_used = gas_used,
+        )
+    );
+}
+
+#[test]
+fn test_call_tracer_only_top_call() {
+    let (json, gas_used) = trace_calls(CallTracerConfig {
+        only_top_call: true,
+        with_log: false,
+    });
+    assert_eq!(
+        json,
+        format!(
+            r#"{{"from":"{}","gas":"0x186a0","gasUsed":"{gas_used:#x}","to":"{}","input":"0x","value":"0x0","type":"CALL"}}"#,
+            hex_address(CALLER),
+            hex_address(OUTER),
+        )
+    );
+}
+
+#[test]
+fn test_prestate_tracer_reads_the_state_before_the_transaction() {
+    let mut context = ArbitrumContext {
+        journaled_state: Journal::new(CacheDB::new(EmptyDB::default())),
+        block: BlockEnv::default(),
+        cfg: ArbitrumConfig::default(),
+        tx: ArbitrumTransaction::default(),
+        chain: (),
+        local: ArbitrumLocalContext::default(),
+        error: Ok(()),
+    };
+    context
+        .arb_state(None, false)
+        .initialize(&ArbosStateParams::default())
+        .expect("failed to initialize ArbOS state");
+    let journal = context.journal_mut();
+    journal.load_account(CALLER).unwrap();
+    journal
+        .balance_incr(CALLER, U256::from(1_000_000_000_u64))
+        .unwrap();
+    for (address, code) in [(OUTER, OUTER_CODE), (INNER, INNER_CODE)] {
+        journal.load_account(address).unwrap();
+        journal.set_code(address, Bytecode::new_raw(Bytes::from_static(code)));
+    }
+    let state = context.journaled_state.finalize();
+    let db = context.journaled_state.db_mut();
+    db.commit(state);
+    db.insert_account_storage(OUTER, U256::from(5), U256::from(7))
+        .unwrap();
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        PrestateTracer::new(),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::default(),
+    );
+    let result = evm
+        .inspect_one_tx(create_call_tx(OUTER, Vec::new(), 100_000).into())
+        .expect("transaction execution failed");
+    assert!(result.is_success());
+
+    // The database still holds the state before the transaction
+    let json = evm
+        .0
+        .inspector
+        .to_json(evm.0.ctx.journaled_state.db())
+        .unwrap();
+    assert_eq!(
+        json,
+        format!(
+            concat!(
+                r#"{{"{coinbase}":{{"balance":"0x0"}},"#,
+                r#""{caller}":{{"balance":"0x3b9aca00"}},"#,
+                r#""{outer}":{{"balance":"0x0","code":"0x{outer_code}","codeHash":"{outer_hash}","#,
+                r#""storage":{{"0x{slot}":"0x{value}"}}}},"#,
+                r#""{inner}":{{"balance":"0x0","code":"0x{inner_code}","codeHash":"{inner_hash}"}}}}"#
+            ),
+            coinbase = hex_address(Address::ZERO),
+            caller = hex_address(CALLER),
+            outer = hex_address(OUTER),
+            outer_code = hex::encode(OUTER_CODE),
+            outer_hash = keccak256(OUTER_CODE),
+            slot = hex::encode(U256::from(5).to_be_bytes::<32>()),
+            value = hex::encode(U256::from(7).to_be_bytes::<32>()),
+            inner = hex_address(INNER),
+            inner_code = hex::encode(INNER_CODE),
+            inner_hash = keccak256(INNER_CODE),
+        )
+    );
+}
+
+#[test]
+fn test_stylus_tracer_records_captured_hostios() {
+    let mut context = setup_context_with_arbos_state();
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/storage.wat"));
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+
+    // Selector 0x01 = write slot 1 with 0x2a
+    let mut args = vec![0x01u8];
+    args.extend_from_slice(&U256::from(1).to_be_bytes::<32>());
+    args.extend_from_slice(&U256::from(0x2a).to_be_bytes::<32>());
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        StylusTracer::new(),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::<TestContext>::default(),
+    );
+    let result = evm
+        .inspect_one_tx(create_call_tx(program, args, 10_000_000).into())
+        .expect("transaction execution failed");
+    assert!(result.is_success());
+
+    let tracer = evm.into_inspector();
+    let load = tracer
+        .hostios()
+        .iter()
+        .find(|hostio| hostio.name == "storage_load_bytes32")
+        .expect("storage load not traced");
+    assert_eq!(load.args, Bytes::from(U256::from(1).to_be_bytes::<32>()));
+    assert!(load.start_ink >= load.end_ink);
+    assert!(tracer.hostios().iter().all(|hostio| hostio.steps.is_none()));
+    assert!(
+        tracer
+            .to_json()
+            .contains(r#"{"name":"storage_load_bytes32","args":"0x"#)
+    );
+}
@@ -0,0 +1,129 @@
+//! Tests for `eth_call`-style state and block overrides.
+
+use std::collections::HashMap;
+
+use arbos_revm::overrides::{AccountOverride, BlockOverrides, StateOverride, StorageOverride};
+use revm::primitives::{Address, Bytes, U256, address};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx, create_call_tx_with_nonce, create_evm, execute_tx,
+    setup_context_with_arbos_state,
+};
+
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const CONTRACT: Address = address!("0x00000000000000000000000000000000000000d7");
+
+/// Returns SLOAD(calldata[0..32])
+const SLOAD_CODE: [u8; 12] = [
+    0x60, 0x00, 0x35, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+];
+
+fn funded_caller() -> (Address, AccountOverride) {
+    (
+        CALLER,
+        AccountOverride {
+            balance: Some(U256::from(1_000_000_000_u64)),
+            ..Default::default()
+        },
+    )
+}
+
+/// Read storage `slot` of [`CONTRACT`] through [`SLOAD_CODE`].
+fn sload(evm: &mut TestEvm, slot: u64) -> U256 {
+    let calldata = U256::from(slot).to_be_bytes::<32>().to_vec();
+    let result = execute_tx(evm, create_call_tx(CONTRACT, calldata, 100_000));
+    assert!(result.is_success());
+    U256::from_be_slice(result.output().unwrap())
+}
+
+#[test]
+fn test_balance_and_nonce_overrides() {
+    let mut evm = create_evm(setup_context_with_arbos_state());
+    let overrides = StateOverride::from([(
+        CALLER,
+        AccountOverride {
+            balance: Some(U256::from(1_000_000_000_u64)),
+            nonce: Some(5),
+            ..Default::default()
+        },
+    )]);
+    evm.apply_state_overrides(&overrides).unwrap();
+
+    let tx = create_call_tx_with_nonce(Address::repeat_byte(0x42), Vec::new(), 100_000, 5);
+    assert!(execute_tx(&mut evm, tx).is_success());
+}
+
+#[test]
+fn test_code_and_storage_diff_overrides() {
+    let mut evm = create_evm(setup_context_with_arbos_state());
+    let overrides = StateOverride::from([
+        funded_caller(),
+        (
+            CONTRACT,
+            AccountOverride {
+                code: Some(Bytes::from_static(&SLOAD_CODE)),
+                storage: Some(StorageOverride::Diff(HashMap::from([(
+                    U256::from(3),
+                    U256::from(0x2a),
+                )]))),
+                ..Default::default()
+            },
+        ),
+    ]);
+    evm.apply_state_overrides(&overrides).unwrap();
+
+    assert_eq!(sload(&mut evm, 3), U256::from(0x2a));
+}
+
+#[test]
+fn test_storage_replace_clears_other_slots() {
+    let mut evm = create_evm(setup_context_with_arbos_state());
+    let code = AccountOverride {
+        code: Some(Bytes::from_static(&SLOAD_CODE)),
+        storage: Some(StorageOverride::Diff(HashMap::from([
+            (U256::from(0), U256::from(1)),
+            (U256::from(1), U256::from(2)),
+        ]))),
+        ..Default::default()
+    };
+    evm.apply_state_overrides(&StateOverride::from([funded_caller(), (CONTRACT, code)]))
+        .unwrap();
+
+    let replace = AccountOverride {
+        storage: Some(StorageOverride::Replace(HashMap::from([(
+            U256::from(1),
+            U256::from(7),
+        )]))),
+        ..Default::default()
+    };
+    evm.apply_state_overrides(&StateOverride::from([(CONTRACT, replace)]))
+        .unwrap();
+
+    assert_eq!(sload(&mut evm, 0), U256::ZERO);
+}
+
+#[test]
+fn test_block_overrides() {
+    let mut evm = create_evm(setup_context_with_arbos_state());
+    // Returns TIMESTAMP
+    let code = AccountOverride {
+        code: Some(Bytes::from_static(&[
+            0x42, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ])),
+        ..Default::default()
+    };
+    evm.apply_state_overrides(&StateOverride::from([funded_caller(), (CONTRACT, code)]))
+        .unwrap();
+    evm.apply_block_overrides(&BlockOverrides {
+        timestamp: Some(U256::from(1_700_000_000_u64)),
+        ..Default::default()
+    });
+
+    let result = execute_tx(&mut evm, create_call_tx(CONTRACT, Vec::new(), 100_000));
+    assert!(result.is_success());
+    assert_eq!(
+        U256::from_be_slice(result.output().unwrap()),
+        U256::from(1_700_000_000_u64)
+    );
+}
@@ -0,0 +1,90 @@
+//! Tests for the log of chain-owner actions applied through ArbOwner.
+
+use arbos_revm::{
+    local_context::OwnerAction,
+    state::{ArbState, ArbStateGetter},
+};
+use revm::primitives::{Address, Bytes, FixedBytes, U256, address, keccak256};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx_with_nonce, create_evm, deploy_evm_code, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+const ARB_OWNER: Address = address!("0x0000000000000000000000000000000000000070");
+const OWNER: Address = address!("0x0101010101010101010101010101010101010101");
+const REVERTER: Address = address!("0x00000000000000000000000000000000000000c7");
+
+/// Code that forwards its calldata to ArbOwner and then reverts.
+const CALL_ARB_OWNER_THEN_REVERT: &[u8] = &[
+    0x36, 0x60, 0x00, 0x60, 0x00, 0x37, // CALLDATACOPY(0, 0, CALLDATASIZE)
+    0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00, 0x60, 0x00, 0x60, 0x70, 0x5a, 0xf1,
+    0x50, // CALL(gas, ArbOwner, 0, 0, CALLDATASIZE, 0, 0) POP
+    0x60, 0x00, 0x60, 0x00, 0xfd, // REVERT(0, 0)
+];
+
+fn setup() -> TestEvm {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, OWNER, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, REVERTER, CALL_ARB_OWNER_THEN_REVERT);
+    let mut arb_state = context.arb_state(None, false);
+    arb_state.chain_owners().add(OWNER).unwrap();
+    arb_state.chain_owners().add(REVERTER).unwrap();
+    drop(arb_state);
+    create_evm(context)
+}
+
+fn calldata(signature: &str, arg: u64) -> (FixedBytes<4>, Vec<u8>) {
+    let selector = FixedBytes::from_slice(&keccak256(signature)[..4]);
+    let mut calldata = selector.to_vec();
+    calldata.extend_from_slice(&U256::from(arg).to_be_bytes::<32>());
+    (selector, calldata)
+}
+
+#[test]
+fn test_applied_owner_actions_are_logged() {
+    let mut evm = setup();
+    let (set_tolerance, tolerance_calldata) = calldata("setL2GasBacklogTolerance(uint64)", 42);
+    let (set_inertia, inertia_calldata) = calldata("setL2GasPricingInertia(uint64)", 77);
+    let (get_owners, _) = calldata("getAllChainOwners()", 0);
+
+    evm.0.ctx.block.number = U256::from(5);
+    let txs = [
+        (ARB_OWNER, tolerance_calldata.clone()),
+        // Reads change nothing and are left out
+        (ARB_OWNER, get_owners.to_vec()),
+        // Reverted actions are left out
+        (REVERTER, inertia_calldata.clone()),
+    ];
+    for (nonce, (to, calldata)) in txs.into_iter().enumerate() {
+        execute_tx(
+            &mut evm,
+            create_call_tx_with_nonce(to, calldata, 1_000_000, nonce as u64),
+        );
+    }
+    evm.0.ctx.block.number = U256::from(6);
+    let result = execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(ARB_OWNER, inertia_calldata.clone(), 1_000_000, 3),
+    );
+    assert!(result.is_success());
+
+    let expected = vec![
+        OwnerAction {
+            block_number: 5,
+            owner: OWNER,
+            method: set_tolerance,
+            params: Bytes::copy_from_slice(&tolerance_calldata[4..]),
+        },
+        OwnerAction {
+            block_number: 6,
+            owner: OWNER,
+            method: set_inertia,
+            params: Bytes::copy_from_slice(&inertia_calldata[4..]),
+        },
+    ];
+    assert_eq!(evm.owner_actions(), expected.as_slice());
+    assert_eq!(evm.take_owner_actions(), expected);
+    assert!(evm.owner_actions().is_empty());
+}
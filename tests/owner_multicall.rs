@@ -0,0 +1,153 @@
+//! Tests for applying batches of chain-owner calls through `ArbOwner.multicall`.
+
+use alloy_sol_types::{SolCall, SolEvent, sol};
+use arbos_revm::{
+    precompiles::errors::decode_revert_message,
+    state::{ArbState, ArbStateGetter, types::StorageBackedTr},
+};
+use revm::{
+    context::result::ExecutionResult,
+    primitives::{Address, Bytes, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx_with_nonce, create_evm, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbOwner {
+        function setL2GasBacklogTolerance(uint64 sec) external;
+        function setL2GasPricingInertia(uint64 sec) external;
+        function setSpeedLimit(uint64 limit) external;
+        function multicall(bytes[] calldata calls) external returns (bytes[] memory results);
+        event OwnerActs(bytes4 indexed method, address indexed owner, bytes data);
+    }
+}
+
+const ARB_OWNER: Address = address!("0x0000000000000000000000000000000000000070");
+const OWNER: Address = address!("0x0101010101010101010101010101010101010101");
+
+fn setup(owner_multicall: bool) -> TestEvm {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.owner_multicall = owner_multicall;
+    fund_account(&mut context, OWNER, U256::from(1_000_000_000_u64));
+    context
+        .arb_state(None, false)
+        .chain_owners()
+        .add(OWNER)
+        .unwrap();
+    create_evm(context)
+}
+
+fn multicall(evm: &mut TestEvm, calls: Vec<Vec<u8>>) -> ExecutionResult {
+    let calldata = ArbOwner::multicallCall {
+        calls: calls.into_iter().map(Bytes::from).collect(),
+    }
+    .abi_encode();
+    execute_tx(
+        evm,
+        create_call_tx_with_nonce(ARB_OWNER, calldata, 1_000_000, 0),
+    )
+}
+
+fn backlog_tolerance(value: u64) -> Vec<u8> {
+    ArbOwner::setL2GasBacklogToleranceCall { sec: value }.abi_encode()
+}
+
+fn pricing_inertia(value: u64) -> Vec<u8> {
+    ArbOwner::setL2GasPricingInertiaCall { sec: value }.abi_encode()
+}
+
+/// The backlog tolerance and pricing inertia held in state.
+fn l2_pricing(evm: &mut TestEvm) -> (u64, u64) {
+    let mut arb_state = evm.0.ctx.arb_state(None, true);
+    let mut l2_pricing = arb_state.l2_pricing();
+    (
+        l2_pricing.backlog_tolerance().get().unwrap(),
+        l2_pricing.pricing_inertia().get().unwrap(),
+    )
+}
+
+#[test]
+fn test_multicall_applies_every_call_under_one_owner_acts() {
+    let mut evm = setup(true);
+    let calls = vec![backlog_tolerance(42), pricing_inertia(77)];
+
+    let logs = match multicall(&mut evm, calls) {
+        ExecutionResult::Success { output, logs, .. } => {
+            let results = ArbOwner::multicallCall::abi_decode_returns(output.data()).unwrap();
+            assert_eq!(results, vec![Bytes::new(), Bytes::new()]);
+            logs
+        }
+        other => panic!("multicall failed: {other:?}"),
+    };
+    assert_eq!(l2_pricing(&mut evm), (42, 77));
+
+    let [log] = logs.as_slice() else {
+        panic!("expected a single OwnerActs log, got {logs:?}");
+    };
+    let event = ArbOwner::OwnerActs::decode_log_data(&log.data).unwrap();
+    assert_eq!(event.method, ArbOwner::multicallCall::SELECTOR);
+    assert_eq!(event.owner, OWNER);
+
+    let [action] = evm.owner_actions() else {
+        panic!("expected a single owner action");
+    };
+    assert_eq!(action.method, ArbOwner::multicallCall::SELECTOR);
+}
+
+#[test]
+fn test_multicall_reverts_every_call_if_one_fails() {
+    let mut evm = setup(true);
+    let before = l2_pricing(&mut evm);
+    let calls = vec![
+        backlog_tolerance(42),
+        ArbOwner::setSpeedLimitCall { limit: 0 }.abi_encode(),
+        pricing_inertia(77),
+    ];
+
+    match multicall(&mut evm, calls) {
+        ExecutionResult::Revert { output, .. } => assert_eq!(
+            decode_revert_message(&output).as_deref(),
+            Some("speed limit must be nonzero")
+        ),
+        other => panic!("multicall succeeded: {other:?}"),
+    }
+    assert_eq!(l2_pricing(&mut evm), before);
+    assert!(evm.owner_actions().is_empty());
+}
+
+#[test]
+fn test_multicall_cannot_be_nested() {
+    let mut evm = setup(true);
+    let nested = ArbOwner::multicallCall {
+        calls: vec![Bytes::from(backlog_tolerance(42))],
+    }
+    .abi_encode();
+
+    match multicall(&mut evm, vec![nested]) {
+        ExecutionResult::Revert { output, .. } => assert_eq!(
+            decode_revert_message(&output).as_deref(),
+            Some("multicall cannot be nested")
+        ),
+        other => panic!("nested multicall succeeded: {other:?}"),
+    }
+}
+
+#[test]
+fn test_multicall_is_unknown_unless_enabled() {
+    let mut evm = setup(false);
+    let before = l2_pricing(&mut evm);
+
+    // Like any unknown selector, the call succeeds without doing anything
+    match multicall(&mut evm, vec![backlog_tolerance(42)]) {
+        ExecutionResult::Success { output, logs, .. } => {
+            assert!(output.data().is_empty());
+            assert!(logs.is_empty());
+        }
+        other => panic!("unknown selector failed: {other:?}"),
+    }
+    assert_eq!(l2_pricing(&mut evm), before);
+}
@@ -0,0 +1,292 @@
+//! Round-trip tests for chain parameters set through ArbOwner and read back
+//! through ArbGasInfo and ArbOwnerPublic.
+
+use alloy_sol_types::SolValue;
+use arbos_revm::{
+    precompiles::errors::decode_revert_message,
+    state::{ArbState, ArbStateGetter, ChainParameters, types::StorageBackedTr},
+};
+use revm::{
+    context::result::ExecutionResult,
+    primitives::{Address, Bytes, I256, U256, address, keccak256},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, TestEvm, create_call_tx_with_nonce, create_evm, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+const ARB_OWNER: Address = address!("0x0000000000000000000000000000000000000070");
+const ARB_OWNER_PUBLIC: Address = address!("0x000000000000000000000000000000000000006b");
+const ARB_GAS_INFO: Address = address!("0x000000000000000000000000000000000000006c");
+
+/// Drives precompile calls from a chain owner, tracking the sender nonce.
+struct OwnerSession {
+    evm: TestEvm,
+    nonce: u64,
+}
+
+impl OwnerSession {
+    fn new(arbos_version: u16) -> Self {
+        Self::with_context(setup_context_with_arbos_state(), arbos_version)
+    }
+
+    fn with_context(mut context: TestContext, arbos_version: u16) -> Self {
+        context.cfg.arbos_version = arbos_version;
+
+        let owner = Address::repeat_byte(0x01);
+        fund_account(&mut context, owner, U256::from(1_000_000_000_u64));
+        context
+            .arb_state(None, false)
+            .chain_owners()
+            .add(owner)
+            .expect("failed to add chain owner");
+
+        Self {
+            evm: create_evm(context),
+            nonce: 0,
+        }
+    }
+
+    fn call(&mut self, to: Address, signature: &str, arg: Option<u64>) -> Bytes {
+        self.call_with_gas_used(to, signature, arg).0
+    }
+
+    fn call_with_gas_used(
+        &mut self,
+        to: Address,
+        signature: &str,
+        arg: Option<u64>,
+    ) -> (Bytes, u64) {
+        let mut calldata = keccak256(signature)[..4].to_vec();
+        if let Some(arg) = arg {
+            calldata.extend_from_slice(&U256::from(arg).to_be_bytes::<32>());
+        }
+
+        let tx = create_call_tx_with_nonce(to, calldata, 1_000_000, self.nonce);
+        self.nonce += 1;
+
+        match execute_tx(&mut self.evm, tx) {
+            ExecutionResult::Success {
+                output, gas_used, ..
+            } => (output.into_data(), gas_used),
+            other => panic!("{signature} failed: {:?}", other),
+        }
+    }
+
+    /// Call an ArbOwner setter, returning the revert message if it reverts.
+    fn try_set(&mut self, signature: &str, value: u64) -> Result<(), String> {
+        let mut calldata = keccak256(signature)[..4].to_vec();
+        calldata.extend_from_slice(&U256::from(value).to_be_bytes::<32>());
+
+        let tx = create_call_tx_with_nonce(ARB_OWNER, calldata, 1_000_000, self.nonce);
+        self.nonce += 1;
+
+        match execute_tx(&mut self.evm, tx) {
+            ExecutionResult::Success { .. } => Ok(()),
+            ExecutionResult::Revert { output, .. } => {
+                Err(decode_revert_message(&output).expect("revert without a message"))
+            }
+            other => panic!("{signature} halted: {:?}", other),
+        }
+    }
+
+    fn set(&mut self, signature: &str, value: u64) {
+        self.call(ARB_OWNER, signature, Some(value));
+    }
+
+    fn get(&mut self, to: Address, signature: &str) -> u64 {
+        U256::from_be_slice(&self.call(to, signature, None)).to::<u64>()
+    }
+}
+
+#[test]
+fn test_l2_pricing_setters_round_trip() {
+    let mut session = OwnerSession::new(50);
+
+    session.set("setL2GasBacklogTolerance(uint64)", 42);
+    assert_eq!(session.get(ARB_GAS_INFO, "getGasBacklogTolerance()"), 42);
+
+    session.set("setL2GasPricingInertia(uint64)", 77);
+    assert_eq!(session.get(ARB_GAS_INFO, "getPricingInertia()"), 77);
+
+    session.set("setL1BaseFeeEstimateInertia(uint64)", 9);
+    assert_eq!(
+        session.get(ARB_GAS_INFO, "getL1BaseFeeEstimateInertia()"),
+        9
+    );
+
+    session.set("setAmortizedCostCapBips(uint64)", 250);
+    assert_eq!(session.get(ARB_GAS_INFO, "getAmortizedCostCapBips()"), 250);
+
+    session.set("setMinimumL2BaseFee(uint256)", 12_345);
+    assert_eq!(session.get(ARB_GAS_INFO, "getMinimumGasPrice()"), 12_345);
+}
+
+#[test]
+fn test_set_gas_backlog_round_trip() {
+    let mut session = OwnerSession::new(50);
+
+    // The setter's own gas joins the backlog once its transaction ends
+    let (_, gas_used) =
+        session.call_with_gas_used(ARB_OWNER, "setGasBacklog(uint64)", Some(1_234_567));
+    assert_eq!(
+        session.get(ARB_GAS_INFO, "getGasBacklog()"),
+        1_234_567 + gas_used
+    );
+
+    let (_, gas_used) = session.call_with_gas_used(ARB_OWNER, "setGasBacklog(uint64)", Some(0));
+    assert_eq!(session.get(ARB_GAS_INFO, "getGasBacklog()"), gas_used);
+}
+
+#[test]
+fn test_gas_limits_round_trip() {
+    let mut session = OwnerSession::new(50);
+
+    session.set("setMaxTxGasLimit(uint64)", 7_000_000);
+    session.set("setMaxBlockGasLimit(uint64)", 64_000_000);
+
+    assert_eq!(session.get(ARB_GAS_INFO, "getMaxTxGasLimit()"), 7_000_000);
+    assert_eq!(
+        session.get(ARB_GAS_INFO, "getMaxBlockGasLimit()"),
+        64_000_000
+    );
+}
+
+#[test]
+fn test_max_tx_gas_limit_is_block_limit_before_arbos_50() {
+    let mut session = OwnerSession::new(40);
+
+    session.set("setMaxTxGasLimit(uint64)", 9_000_000);
+
+    assert_eq!(session.get(ARB_GAS_INFO, "getMaxTxGasLimit()"), 9_000_000);
+    assert_eq!(
+        session.get(ARB_GAS_INFO, "getMaxBlockGasLimit()"),
+        9_000_000
+    );
+}
+
+#[test]
+fn test_native_token_management_from_round_trip() {
+    let mut session = OwnerSession::new(50);
+
+    session.set("setNativeTokenManagementFrom(uint64)", 1_700_000_000);
+    assert_eq!(
+        session.get(ARB_OWNER_PUBLIC, "getNativeTokenManagementFrom()"),
+        1_700_000_000
+    );
+}
+
+#[test]
+fn test_l1_pricing_units_since_update_readable() {
+    let mut session = OwnerSession::new(50);
+
+    assert_eq!(
+        session.get(ARB_GAS_INFO, "getL1PricingUnitsSinceUpdate()"),
+        0
+    );
+}
+
+#[test]
+fn test_l1_pricing_surplus_is_live_and_last_is_stored() {
+    let mut context = setup_context_with_arbos_state();
+    {
+        let mut arb_state = context.arb_state(None, false);
+        let mut l1_pricing = arb_state.l1_pricing();
+        l1_pricing
+            .l1_fees_available()
+            .set(U256::from(1_000_000))
+            .unwrap();
+        l1_pricing
+            .funds_due_for_rewards()
+            .set(I256::try_from(250_000_i64).unwrap())
+            .unwrap();
+        l1_pricing
+            .last_surplus()
+            .set(I256::try_from(42_i64).unwrap())
+            .unwrap();
+    }
+    let mut session = OwnerSession::with_context(context, 50);
+
+    assert_eq!(session.get(ARB_GAS_INFO, "getL1PricingSurplus()"), 750_000);
+    assert_eq!(session.get(ARB_GAS_INFO, "getLastL1PricingSurplus()"), 42);
+}
+
+#[test]
+fn test_setters_reject_out_of_range_values() {
+    let mut session = OwnerSession::new(50);
+
+    for (signature, value, message) in [
+        (
+            "setL2GasPricingInertia(uint64)",
+            0,
+            "price inertia must be nonzero",
+        ),
+        (
+            "setL1BaseFeeEstimateInertia(uint64)",
+            0,
+            "inertia must be nonzero",
+        ),
+        ("setL1PricingInertia(uint64)", 0, "inertia must be nonzero"),
+        (
+            "setL1PricingEquilibrationUnits(uint256)",
+            0,
+            "equilibration units must be nonzero",
+        ),
+        ("setSpeedLimit(uint64)", 0, "speed limit must be nonzero"),
+        (
+            "setBrotliCompressionLevel(uint64)",
+            12,
+            "invalid brotli compression level",
+        ),
+        (
+            "setAmortizedCostCapBips(uint64)",
+            10_001,
+            "amortized cost cap must not exceed 10000 bips",
+        ),
+        (
+            "setInkPrice(uint32)",
+            0,
+            "ink price must be a positive uint24",
+        ),
+    ] {
+        assert_eq!(
+            session.try_set(signature, value),
+            Err(message.to_string()),
+            "{signature}"
+        );
+    }
+
+    // The bounds themselves are accepted.
+    session.set("setBrotliCompressionLevel(uint64)", 11);
+    session.set("setAmortizedCostCapBips(uint64)", 10_000);
+    assert_eq!(
+        session.get(ARB_GAS_INFO, "getAmortizedCostCapBips()"),
+        10_000
+    );
+}
+
+#[test]
+fn test_chain_parameters_snapshot() {
+    let mut session = OwnerSession::new(50);
+
+    session.set("setL2GasBacklogTolerance(uint64)", 42);
+    session.set("setAmortizedCostCapBips(uint64)", 250);
+    session.set("setInkPrice(uint32)", 20_000);
+    session.set("setWasmExpiryDays(uint16)", 30);
+
+    let parameters = session.evm.chain_parameters().unwrap();
+    assert_eq!(parameters.chainOwners, vec![Address::repeat_byte(0x01)]);
+    assert_eq!(parameters.l2BacklogTolerance, 42);
+    assert_eq!(parameters.amortizedCostCapBips, 250);
+    assert_eq!(parameters.inkPrice, 20_000);
+    assert_eq!(parameters.wasmExpiryDays, 30);
+    assert_eq!(
+        parameters.l2PricingInertia,
+        session.get(ARB_GAS_INFO, "getPricingInertia()")
+    );
+
+    let encoded = parameters.abi_encode();
+    assert_eq!(ChainParameters::abi_decode(&encoded).unwrap(), parameters);
+}
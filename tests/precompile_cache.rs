@@ -0,0 +1,190 @@
+//! Tests for reusing precompile results within a transaction.
+
+use alloy_sol_types::{SolCall, sol};
+use revm::primitives::{Address, U256, address};
+
+mod test_utils;
+use test_utils::{
+    create_call_tx, create_evm, deploy_evm_code, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbSys {
+        function arbBlockNumber() external view returns (uint256);
+        function myCallersAddressWithoutAliasing() external view returns (address);
+    }
+
+    interface ArbInfo {
+        function getBalance(address account) external view returns (uint256);
+    }
+}
+
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const CONTRACT: Address = address!("0x00000000000000000000000000000000000000c5");
+const PAYER: Address = address!("0x00000000000000000000000000000000000000c6");
+const PAYEE: Address = address!("0x00000000000000000000000000000000000000c7");
+const RELAY_X: Address = address!("0x00000000000000000000000000000000000000c8");
+const RELAY_Y: Address = address!("0x00000000000000000000000000000000000000c9");
+const PROBE: Address = address!("0x00000000000000000000000000000000000000ca");
+
+/// Code that STATICCALLs ArbSys `arbBlockNumber` three times and returns the last result.
+fn calls_arb_sys_thrice() -> Vec<u8> {
+    // PUSH4 selector PUSH1 0xe0 SHL PUSH1 0 MSTORE
+    let mut code = vec![0x63];
+    code.extend_from_slice(&ArbSys::arbBlockNumberCall::SELECTOR);
+    code.extend_from_slice(&[0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52]);
+    for _ in 0..3 {
+        // STATICCALL(gas, ArbSys, 0, 4, 32, 32) POP
+        code.extend_from_slice(&[
+            0x60, 0x20, 0x60, 0x20, 0x60, 0x04, 0x60, 0x00, 0x60, 0x64, 0x5a, 0xfa, 0x50,
+        ]);
+    }
+    // RETURN(32, 32)
+    code.extend_from_slice(&[0x60, 0x20, 0x60, 0x20, 0xf3]);
+    code
+}
+
+fn run(cache_precompile_results: bool) -> (u64, Vec<u8>, u64) {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.cache_precompile_results = cache_precompile_results;
+    context.block.number = U256::from(7);
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, CONTRACT, &calls_arb_sys_thrice());
+    let mut evm = create_evm(context);
+
+    let result = execute_tx(&mut evm, create_call_tx(CONTRACT, Vec::new(), 1_000_000));
+    assert!(result.is_success());
+    let precompile_gas = evm.precompile_gas_used().values().sum();
+    (
+        result.gas_used(),
+        result.output().unwrap().to_vec(),
+        precompile_gas,
+    )
+}
+
+#[test]
+fn test_cached_results_match_uncached_execution() {
+    let uncached = run(false);
+    let cached = run(true);
+
+    assert_eq!(cached, uncached);
+    assert_eq!(U256::from_be_slice(&cached.1), U256::from(7));
+}
+
+/// Code that pays [`PAYEE`] the word in its calldata, then asks ArbInfo for the payee's balance
+/// and returns it, reverting instead when it paid 5.
+fn pays_then_reads_balance() -> Vec<u8> {
+    // CALL(gas, PAYEE, calldata[0], 0, 0, 0, 0) POP
+    let mut code = vec![
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x35, 0x73,
+    ];
+    code.extend_from_slice(PAYEE.as_slice());
+    code.extend_from_slice(&[0x5a, 0xf1, 0x50]);
+    // mem[0..36] = getBalance(PAYEE)
+    code.push(0x63);
+    code.extend_from_slice(&ArbInfo::getBalanceCall::SELECTOR);
+    code.extend_from_slice(&[0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52, 0x73]);
+    code.extend_from_slice(PAYEE.as_slice());
+    code.extend_from_slice(&[0x60, 0x04, 0x52]);
+    // STATICCALL(gas, ArbInfo, 0, 36, 0, 32) POP
+    code.extend_from_slice(&[
+        0x60, 0x20, 0x60, 0x00, 0x60, 0x24, 0x60, 0x00, 0x60, 0x65, 0x5a, 0xfa, 0x50,
+    ]);
+    // Revert with the balance if calldata[0] == 5, else return it
+    let revert = code.len() as u8 + 14;
+    code.extend_from_slice(&[0x60, 0x00, 0x35, 0x60, 0x05, 0x14, 0x60, revert, 0x57]);
+    code.extend_from_slice(&[0x60, 0x20, 0x60, 0x00, 0xf3]);
+    code.extend_from_slice(&[0x5b, 0x60, 0x20, 0x60, 0x00, 0xfd]);
+    code
+}
+
+/// Code that has [`PAYER`] pay each of `amounts` in turn and returns the last balance it read.
+fn pays_in_turn(amounts: &[u8]) -> Vec<u8> {
+    let mut code = Vec::new();
+    for amount in amounts {
+        // mem[0..32] = amount; CALL(gas, PAYER, 0, 0, 32, 0, 32) POP
+        code.extend_from_slice(&[0x60, *amount, 0x60, 0x00, 0x52]);
+        code.extend_from_slice(&[
+            0x60, 0x20, 0x60, 0x00, 0x60, 0x20, 0x60, 0x00, 0x60, 0x00, 0x73,
+        ]);
+        code.extend_from_slice(PAYER.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x50]);
+    }
+    // RETURN(0, 32)
+    code.extend_from_slice(&[0x60, 0x20, 0x60, 0x00, 0xf3]);
+    code
+}
+
+#[test]
+fn test_cached_results_do_not_outlive_reverts() {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.cache_precompile_results = true;
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    fund_account(&mut context, PAYER, U256::from(1_000));
+    deploy_evm_code(&mut context, PAYER, &pays_then_reads_balance());
+    deploy_evm_code(&mut context, CONTRACT, &pays_in_turn(&[0, 5, 3]));
+    let mut evm = create_evm(context);
+
+    // The balance is read after paying nothing, then after paying 5 in a call that reverts, and
+    // last after paying 3, which leaves the journal as long as it was when 5 was read
+    let result = execute_tx(&mut evm, create_call_tx(CONTRACT, Vec::new(), 1_000_000));
+    assert!(result.is_success());
+    assert_eq!(U256::from_be_slice(result.output().unwrap()), U256::from(3));
+}
+
+/// Code that CALLs each of `targets` in turn and returns the first word the last one returned.
+fn calls_in_turn(targets: &[Address]) -> Vec<u8> {
+    let mut code = Vec::new();
+    for target in targets {
+        // CALL(gas, target, 0, 0, 0, 0, 32) POP
+        code.extend_from_slice(&[
+            0x60, 0x20, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73,
+        ]);
+        code.extend_from_slice(target.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x50]);
+    }
+    // RETURN(0, 32)
+    code.extend_from_slice(&[0x60, 0x20, 0x60, 0x00, 0xf3]);
+    code
+}
+
+/// Code that returns what ArbSys `myCallersAddressWithoutAliasing` tells it.
+fn returns_callers_caller() -> Vec<u8> {
+    // PUSH4 selector PUSH1 0xe0 SHL PUSH1 0 MSTORE
+    let mut code = vec![0x63];
+    code.extend_from_slice(&ArbSys::myCallersAddressWithoutAliasingCall::SELECTOR);
+    code.extend_from_slice(&[0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52]);
+    // STATICCALL(gas, ArbSys, 0, 4, 0, 32) POP RETURN(0, 32)
+    code.extend_from_slice(&[
+        0x60, 0x20, 0x60, 0x00, 0x60, 0x04, 0x60, 0x00, 0x60, 0x64, 0x5a, 0xfa, 0x50, 0x60, 0x20,
+        0x60, 0x00, 0xf3,
+    ]);
+    code
+}
+
+#[test]
+fn test_cached_results_are_keyed_by_callers_caller() {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.cache_precompile_results = true;
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, PROBE, &returns_callers_caller());
+    deploy_evm_code(&mut context, RELAY_X, &calls_in_turn(&[PROBE]));
+    deploy_evm_code(&mut context, RELAY_Y, &calls_in_turn(&[PROBE]));
+    deploy_evm_code(
+        &mut context,
+        CONTRACT,
+        &calls_in_turn(&[RELAY_X, RELAY_Y, RELAY_X, RELAY_Y]),
+    );
+    let mut evm = create_evm(context);
+
+    // By the second round every account is loaded, so the probe asks ArbSys at the same journal
+    // generation through either relay
+    let result = execute_tx(&mut evm, create_call_tx(CONTRACT, Vec::new(), 1_000_000));
+    assert!(result.is_success());
+    assert_eq!(
+        ArbSys::myCallersAddressWithoutAliasingCall::abi_decode_returns(result.output().unwrap())
+            .unwrap(),
+        RELAY_Y
+    );
+}
@@ -0,0 +1,86 @@
+//! Tests for the per-precompile gas usage recorded for each transaction.
+
+use alloy_sol_types::{SolCall, sol};
+use revm::primitives::{Address, FixedBytes, U256, address};
+
+mod test_utils;
+use test_utils::{
+    create_call_tx_with_nonce, create_evm, deploy_evm_code, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbSys {
+        function arbBlockNumber() external view returns (uint256);
+    }
+}
+
+const ARB_SYS: Address = address!("0x0000000000000000000000000000000000000064");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const CONTRACT: Address = address!("0x00000000000000000000000000000000000000c4");
+
+/// Code that stores the `arbBlockNumber` selector and STATICCALLs ArbSys with it twice.
+fn calls_arb_sys_twice() -> Vec<u8> {
+    // PUSH4 selector PUSH1 0xe0 SHL PUSH1 0 MSTORE
+    let mut code = vec![0x63];
+    code.extend_from_slice(&ArbSys::arbBlockNumberCall::SELECTOR);
+    code.extend_from_slice(&[0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52]);
+    for _ in 0..2 {
+        // STATICCALL(gas, ArbSys, 0, 4, 0, 0) POP
+        code.extend_from_slice(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x04, 0x60, 0x00, 0x60, 0x64, 0x5a, 0xfa, 0x50,
+        ]);
+    }
+    code.push(0x00);
+    code
+}
+
+#[test]
+fn test_precompile_gas_is_recorded_per_method() {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    let mut evm = create_evm(context);
+
+    let calldata = ArbSys::arbBlockNumberCall {}.abi_encode();
+    let result = execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(ARB_SYS, calldata, 100_000, 0),
+    );
+    assert!(result.is_success());
+
+    let usage = evm.precompile_gas_used();
+    assert_eq!(usage.len(), 1);
+    let key = (ARB_SYS, FixedBytes(ArbSys::arbBlockNumberCall::SELECTOR));
+    let gas = usage[&key];
+    assert!(gas > 0 && gas <= result.gas_used() - 21_000);
+}
+
+#[test]
+fn test_precompile_gas_accumulates_within_a_transaction() {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, CONTRACT, &calls_arb_sys_twice());
+    let mut evm = create_evm(context);
+
+    let calldata = ArbSys::arbBlockNumberCall {}.abi_encode();
+    execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(ARB_SYS, calldata, 100_000, 0),
+    );
+    let key = (ARB_SYS, FixedBytes(ArbSys::arbBlockNumberCall::SELECTOR));
+    let single_call = evm.precompile_gas_used()[&key];
+
+    let result = execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(CONTRACT, Vec::new(), 1_000_000, 1),
+    );
+    assert!(result.is_success());
+    assert_eq!(evm.precompile_gas_used()[&key], 2 * single_call);
+
+    // A transaction that calls no precompile starts from an empty map.
+    execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(CALLER, Vec::new(), 100_000, 2),
+    );
+    assert!(evm.precompile_gas_used().is_empty());
+}
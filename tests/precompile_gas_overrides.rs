@@ -0,0 +1,97 @@
+//! Tests for overriding the gas charged for precompile methods through the config.
+
+use std::collections::HashMap;
+
+use alloy_sol_types::{SolCall, sol};
+use revm::primitives::{Address, FixedBytes, U256, address};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx, create_evm, execute_tx, fund_account, setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbSys {
+        function arbBlockNumber() external view returns (uint256);
+        function arbChainID() external view returns (uint256);
+    }
+}
+
+const ARB_SYS: Address = address!("0x0000000000000000000000000000000000000064");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const OVERRIDE: u64 = 7_777;
+
+fn setup(overrides: &[([u8; 4], u64)]) -> TestEvm {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.precompile_gas_overrides = HashMap::from([(
+        ARB_SYS,
+        overrides
+            .iter()
+            .map(|(selector, gas)| (FixedBytes(*selector), *gas))
+            .collect(),
+    )]);
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    create_evm(context)
+}
+
+fn key(selector: [u8; 4]) -> (Address, FixedBytes<4>) {
+    (ARB_SYS, FixedBytes(selector))
+}
+
+#[test]
+fn test_overridden_method_is_charged_the_override() {
+    let selector = ArbSys::arbBlockNumberCall::SELECTOR;
+    let calldata = ArbSys::arbBlockNumberCall {}.abi_encode();
+
+    let mut evm = setup(&[]);
+    let baseline = execute_tx(&mut evm, create_call_tx(ARB_SYS, calldata.clone(), 100_000));
+    let own_cost = evm.precompile_gas_used()[&key(selector)];
+
+    let mut evm = setup(&[(selector, OVERRIDE)]);
+    let result = execute_tx(&mut evm, create_call_tx(ARB_SYS, calldata, 100_000));
+
+    assert!(result.is_success());
+    assert_eq!(result.output(), baseline.output());
+    assert_eq!(evm.precompile_gas_used()[&key(selector)], OVERRIDE);
+    assert_eq!(result.gas_used(), baseline.gas_used() - own_cost + OVERRIDE);
+}
+
+#[test]
+fn test_other_methods_keep_their_cost() {
+    let selector = ArbSys::arbChainIDCall::SELECTOR;
+    let calldata = ArbSys::arbChainIDCall {}.abi_encode();
+
+    let mut evm = setup(&[]);
+    execute_tx(&mut evm, create_call_tx(ARB_SYS, calldata.clone(), 100_000));
+    let own_cost = evm.precompile_gas_used()[&key(selector)];
+
+    let mut evm = setup(&[(ArbSys::arbBlockNumberCall::SELECTOR, OVERRIDE)]);
+    execute_tx(&mut evm, create_call_tx(ARB_SYS, calldata, 100_000));
+
+    assert_eq!(evm.precompile_gas_used()[&key(selector)], own_cost);
+}
+
+#[test]
+fn test_override_above_the_gas_limit_runs_out_of_gas() {
+    let selector = ArbSys::arbBlockNumberCall::SELECTOR;
+    let mut evm = setup(&[(selector, 1_000_000)]);
+
+    let calldata = ArbSys::arbBlockNumberCall {}.abi_encode();
+    let result = execute_tx(&mut evm, create_call_tx(ARB_SYS, calldata, 100_000));
+
+    assert!(!result.is_success());
+    assert_eq!(result.gas_used(), 100_000);
+}
+
+#[test]
+fn test_halting_call_keeps_the_gas_it_spent() {
+    let selector = ArbSys::arbBlockNumberCall::SELECTOR;
+    let mut evm = setup(&[(selector, 1)]);
+
+    // Covers the override and the intrinsic cost, but not the method's own storage read
+    let calldata = ArbSys::arbBlockNumberCall {}.abi_encode();
+    let result = execute_tx(&mut evm, create_call_tx(ARB_SYS, calldata, 21_500));
+
+    assert!(!result.is_success());
+    assert_eq!(result.gas_used(), 21_500);
+}
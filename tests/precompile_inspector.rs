@@ -0,0 +1,190 @@
+//! Tests for tracing calls to the Arbitrum precompiles.
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::{
+    ArbitrumEvm,
+    inspector::{PrecompileCall, PrecompileInspector, StylusInspector},
+    precompiles::ArbitrumPrecompileProvider,
+};
+use revm::{
+    InspectEvm, Inspector,
+    context::TxEnv,
+    handler::instructions::EthInstructions,
+    interpreter::{CallInputs, CallOutcome, InstructionResult},
+    precompile::PrecompileId,
+    primitives::{Address, FixedBytes, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, create_call_tx, deploy_evm_code, fund_account, setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbSys {
+        function arbBlockNumber() external view returns (uint256);
+    }
+
+    interface ArbOwner {
+        function getNetworkFeeAccount() external view returns (address);
+    }
+}
+
+const ARB_SYS: Address = address!("0x0000000000000000000000000000000000000064");
+const ARB_OWNER: Address = address!("0x0000000000000000000000000000000000000070");
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const CONTRACT: Address = address!("0x00000000000000000000000000000000000000c4");
+
+/// A hook call as seen by [`CallRecorder`].
+#[derive(Debug)]
+enum Event {
+    Call(Address),
+    Precompile(PrecompileCall),
+    CallEnd(Address),
+}
+
+/// Collects the call hooks and precompile calls reported while inspecting, in order.
+#[derive(Default)]
+struct CallRecorder(Vec<Event>);
+
+impl Inspector<TestContext> for CallRecorder {
+    fn call(&mut self, _context: &mut TestContext, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.0.push(Event::Call(inputs.target_address));
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut TestContext,
+        inputs: &CallInputs,
+        _outcome: &mut CallOutcome,
+    ) {
+        self.0.push(Event::CallEnd(inputs.target_address));
+    }
+}
+
+impl StylusInspector<TestContext> for CallRecorder {}
+
+impl PrecompileInspector<TestContext> for CallRecorder {
+    fn precompile_call(&mut self, _context: &mut TestContext, call: &PrecompileCall) {
+        self.0.push(Event::Precompile(call.clone()));
+    }
+}
+
+/// Run `tx` under a [`CallRecorder`], with `code` deployed at [`CONTRACT`].
+fn trace(tx: TxEnv, code: &[u8]) -> Vec<Event> {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    deploy_evm_code(&mut context, CONTRACT, code);
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        CallRecorder::default(),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::<TestContext>::default(),
+    );
+    evm.inspect_one_tx(tx)
+        .expect("transaction execution failed");
+    evm.into_inspector().0
+}
+
+fn precompile_name(call: &PrecompileCall) -> &str {
+    match &call.id {
+        PrecompileId::Custom(name) => name,
+        other => panic!("expected an Arbitrum precompile, got {other:?}"),
+    }
+}
+
+/// Code that stores the `arbBlockNumber` selector and STATICCALLs ArbSys with it.
+fn calls_arb_sys() -> Vec<u8> {
+    // PUSH4 selector PUSH1 0xe0 SHL PUSH1 0 MSTORE
+    let mut code = vec![0x63];
+    code.extend_from_slice(&ArbSys::arbBlockNumberCall::SELECTOR);
+    code.extend_from_slice(&[0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52]);
+    // STATICCALL(gas, ArbSys, 0, 4, 0, 0) POP STOP
+    code.extend_from_slice(&[
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x04, 0x60, 0x00, 0x60, 0x64, 0x5a, 0xfa, 0x50, 0x00,
+    ]);
+    code
+}
+
+/// The hooks in `events`, each with the address of its call.
+fn hooks(events: &[Event]) -> Vec<(&'static str, Address)> {
+    events
+        .iter()
+        .map(|event| match event {
+            Event::Call(address) => ("call", *address),
+            Event::Precompile(call) => ("precompile_call", call.address),
+            Event::CallEnd(address) => ("call_end", *address),
+        })
+        .collect()
+}
+
+/// The precompile calls in `events`.
+fn precompile_calls(events: &[Event]) -> Vec<&PrecompileCall> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Precompile(call) => Some(call),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_precompile_call_is_reported_within_its_frame() {
+    let calldata = ArbSys::arbBlockNumberCall {}.abi_encode();
+    let events = trace(create_call_tx(ARB_SYS, calldata.clone(), 100_000), &[]);
+    assert_eq!(
+        hooks(&events),
+        vec![
+            ("call", ARB_SYS),
+            ("precompile_call", ARB_SYS),
+            ("call_end", ARB_SYS),
+        ]
+    );
+
+    let [call] = precompile_calls(&events)[..] else {
+        panic!("expected a single precompile call");
+    };
+    assert_eq!(precompile_name(call), "ArbSys");
+    assert_eq!(
+        call.selector,
+        FixedBytes(ArbSys::arbBlockNumberCall::SELECTOR)
+    );
+    assert_eq!(call.input.as_ref(), calldata.as_slice());
+    assert_eq!(call.output.len(), 32);
+    assert!(call.result.is_ok());
+    assert!(call.gas_used > 0);
+}
+
+#[test]
+fn test_nested_precompile_call_is_reported() {
+    let events = trace(
+        create_call_tx(CONTRACT, Vec::new(), 100_000),
+        &calls_arb_sys(),
+    );
+    assert_eq!(
+        hooks(&events),
+        vec![
+            ("call", CONTRACT),
+            ("call", ARB_SYS),
+            ("precompile_call", ARB_SYS),
+            ("call_end", ARB_SYS),
+            ("call_end", CONTRACT),
+        ]
+    );
+}
+
+#[test]
+fn test_reverted_precompile_call_is_reported() {
+    // Only chain owners may call ArbOwner
+    let calldata = ArbOwner::getNetworkFeeAccountCall {}.abi_encode();
+    let events = trace(create_call_tx(ARB_OWNER, calldata, 100_000), &[]);
+
+    let [call] = precompile_calls(&events)[..] else {
+        panic!("expected a single precompile call");
+    };
+    assert_eq!(precompile_name(call), "ArbOwner");
+    assert_eq!(call.result, InstructionResult::Revert);
+}
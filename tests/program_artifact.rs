@@ -0,0 +1,108 @@
+//! Tests for exporting compiled Stylus programs for external verification.
+
+use std::num::NonZeroUsize;
+
+use arbos_revm::{
+    program_cache::ProgramCache,
+    stylus_executor::{StylusCompileTarget, native_target, stylus_compile},
+};
+use revm::{
+    context::{ContextTr, JournalTr},
+    primitives::{Address, B256, Bytes, U256, keccak256},
+};
+use stylus::prover::programs::config::CompileConfig;
+
+mod test_utils;
+use test_utils::{
+    TestEvm, compile_wat, create_call_tx, create_evm, deploy_wasm_program, execute_tx,
+    fund_account, setup_context_with_arbos_state,
+};
+
+/// Deploy the storage test program behind a private cache, returning the EVM, the program's
+/// address and code hash, and its wasm.
+fn setup() -> (TestEvm, Address, B256, Bytes) {
+    let mut context = setup_context_with_arbos_state();
+    let wasm = Bytes::from(compile_wat("test-data/storage.wat"));
+    let address = deploy_wasm_program(&mut context, &wasm);
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    let code_hash = context.journal_mut().code_hash(address).unwrap().data;
+    let evm =
+        create_evm(context).with_program_cache(ProgramCache::new(NonZeroUsize::new(16).unwrap()));
+    (evm, address, code_hash, wasm)
+}
+
+#[test]
+fn test_artifact_matches_executed_program() {
+    let (mut evm, address, code_hash, wasm) = setup();
+    // Selector 0x00 = read slot 0
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    assert!(execute_tx(&mut evm, create_call_tx(address, args, 10_000_000)).is_success());
+
+    let artifact = evm.program_artifact(code_hash).unwrap();
+    assert_eq!(artifact.code_hash, code_hash);
+    assert_eq!(artifact.arbos_version, evm.ctx.cfg.arbos_version);
+    assert!(!artifact.debug);
+    assert_eq!(artifact.target, native_target());
+    assert_eq!(artifact.native_code_hash, keccak256(&artifact.native_code));
+
+    let compile_config = CompileConfig::version(artifact.stylus_version, artifact.debug);
+    let compiled = stylus_compile(&wasm, &compile_config).unwrap();
+    assert_eq!(artifact.native_code, compiled);
+}
+
+#[test]
+fn test_artifact_compiles_uncached_program() {
+    let (mut evm, _, code_hash, _) = setup();
+
+    let artifact = evm.program_artifact(code_hash).unwrap();
+    assert_eq!(artifact.native_code_hash, keccak256(&artifact.native_code));
+    // Now served from the cache
+    assert_eq!(evm.program_artifact(code_hash).unwrap(), artifact);
+}
+
+#[test]
+fn test_artifact_for_unknown_code_fails() {
+    let (mut evm, _, _, _) = setup();
+    assert!(evm.program_artifact(B256::repeat_byte(0xab)).is_err());
+}
+
+#[test]
+fn test_artifact_cross_compiles_for_validator_targets() {
+    let (mut evm, _, code_hash, _) = setup();
+    let host = evm.program_artifact(code_hash).unwrap();
+    let cache = evm.ctx.local.program_cache.clone();
+    cache.clear();
+
+    for target in [StylusCompileTarget::Amd64, StylusCompileTarget::Arm64] {
+        evm.ctx.cfg.stylus_compile_target = target;
+        let artifact = evm.program_artifact(code_hash).unwrap();
+        assert_eq!(artifact.target, target.name());
+        assert_eq!(artifact.module_hash, host.module_hash);
+        assert!(!artifact.native_code.is_empty());
+        assert_eq!(artifact.native_code_hash, keccak256(&artifact.native_code));
+    }
+    // Native code for other targets is kept out of the cache
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_compile_targets_follow_nitro_names() {
+    assert_eq!(StylusCompileTarget::Host.name(), native_target());
+    assert_eq!(StylusCompileTarget::Amd64.name(), "amd64");
+    assert_eq!(StylusCompileTarget::Arm64.name(), "arm64");
+    for target in [
+        StylusCompileTarget::Host,
+        StylusCompileTarget::Amd64,
+        StylusCompileTarget::Arm64,
+    ] {
+        assert!(
+            target.target().is_ok(),
+            "{target:?} has an invalid description"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! Tests for sharing the compiled program cache between EVM instances.
+
+use std::{num::NonZeroUsize, time::Duration};
+
+use alloy_sol_types::{SolCall, sol};
+use arbos_revm::{
+    program_cache::{ProgramCache, ProgramCacheStats, ProgramCacheTag},
+    state::{ArbState, ArbStateGetter},
+};
+use revm::{
+    context::{ContextTr, JournalTr, TxEnv, result::ExecutionResult},
+    primitives::{Address, B256, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, TestEvm, create_call_tx, create_call_tx_with_nonce, create_evm,
+    deploy_wat_program, execute_tx, fund_account, setup_context_with_arbos_state,
+};
+
+/// Deploy the storage test program, returning the context and the program's code hash.
+fn setup() -> (TestContext, Address, B256) {
+    let mut context = setup_context_with_arbos_state();
+    let address = deploy_wat_program(&mut context, include_bytes!("../test-data/storage.wat"));
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    let code_hash = context.journal_mut().code_hash(address).unwrap().data;
+    (context, address, code_hash)
+}
+
+fn tag(context: &mut TestContext, debug: bool) -> ProgramCacheTag {
+    let stylus_version = context
+        .arb_state(None, true)
+        .programs()
+        .stylus_params()
+        .get()
+        .unwrap()
+        .version;
+    ProgramCacheTag {
+        stylus_version,
+        arbos_version: context.cfg.arbos_version,
+        debug,
+    }
+}
+
+/// Call the program once through an EVM using `cache`.
+fn run(context: TestContext, address: Address, cache: &ProgramCache) {
+    let mut evm = create_evm(context).with_program_cache(cache.clone());
+    // Selector 0x00 = read slot 0
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    execute_tx(&mut evm, create_call_tx(address, args, 10_000_000));
+}
+
+fn private_cache() -> ProgramCache {
+    ProgramCache::new(NonZeroUsize::new(16).unwrap())
+}
+
+#[test]
+fn test_instances_share_cache() {
+    let cache = private_cache();
+
+    let (mut context, address, code_hash) = setup();
+    let tag = tag(&mut context, false);
+    run(context, address, &cache);
+    assert_eq!(cache.len(), 1);
+    let first = cache.get(&code_hash, tag).expect("program not cached");
+
+    let (context, _, _) = setup();
+    run(context, address, &cache);
+    assert_eq!(cache.len(), 1);
+    let second = cache.get(&code_hash, tag).expect("program not cached");
+    assert_eq!(first.0, second.0);
+
+    assert!(!cache.ptr_eq(&ProgramCache::global()));
+}
+
+#[test]
+fn test_config_change_replaces_entry() {
+    let cache = private_cache();
+
+    let (mut context, address, code_hash) = setup();
+    let release = tag(&mut context, false);
+    run(context, address, &cache);
+
+    let (mut context, _, _) = setup();
+    context.cfg.debug_mode = true;
+    let debug = tag(&mut context, true);
+    run(context, address, &cache);
+
+    assert_eq!(cache.len(), 1);
+    assert!(cache.get(&code_hash, debug).is_some());
+    assert!(cache.get(&code_hash, release).is_none());
+}
+
+#[test]
+fn test_invalidate_starts_new_generation() {
+    let cache = private_cache();
+
+    let (mut context, address, code_hash) = setup();
+    let tag = tag(&mut context, false);
+    run(context, address, &cache);
+
+    assert_eq!(cache.invalidate(), 1);
+    assert_eq!(cache.generation(), 1);
+    assert!(cache.get(&code_hash, tag).is_none());
+
+    let (context, _, _) = setup();
+    run(context, address, &cache);
+    assert!(cache.get(&code_hash, tag).is_some());
+}
+
+#[test]
+fn test_pinned_program_survives_eviction() {
+    let cache = ProgramCache::new(NonZeroUsize::new(1).unwrap());
+
+    let (mut context, storage, storage_hash) = setup();
+    let tag = tag(&mut context, false);
+    let keccak = deploy_wat_program(&mut context, include_bytes!("../test-data/keccak.wat"));
+    let keccak_hash = context.journal_mut().code_hash(keccak).unwrap().data;
+    assert!(cache.pin(storage_hash));
+    assert!(!cache.pin(storage_hash));
+    assert_eq!(cache.pinned(), vec![storage_hash]);
+
+    let mut evm = create_evm(context).with_program_cache(cache.clone());
+    for (nonce, address) in [storage, keccak].into_iter().enumerate() {
+        let mut args = vec![0x00u8];
+        args.extend_from_slice(&[0u8; 32]);
+        execute_tx(
+            &mut evm,
+            create_call_tx_with_nonce(address, args, 10_000_000, nonce as u64),
+        );
+    }
+
+    // The pinned program is held beside the single LRU slot
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&storage_hash, tag).is_some());
+    assert!(cache.get(&keccak_hash, tag).is_some());
+
+    // Unpinned, it competes for the slot again and evicts the other program
+    assert!(cache.unpin(&storage_hash));
+    assert!(!cache.is_pinned(&storage_hash));
+    assert_eq!(cache.len(), 1);
+    assert!(cache.get(&storage_hash, tag).is_some());
+    assert!(cache.get(&keccak_hash, tag).is_none());
+}
+
+#[test]
+fn test_programs_compiled_ahead_of_time() {
+    let cache = private_cache();
+
+    let (mut context, storage, storage_hash) = setup();
+    let tag = tag(&mut context, false);
+    let keccak = deploy_wat_program(&mut context, include_bytes!("../test-data/keccak.wat"));
+    let keccak_hash = context.journal_mut().code_hash(keccak).unwrap().data;
+    let mut evm = create_evm(context).with_program_cache(cache.clone());
+
+    let unknown = B256::repeat_byte(0xab);
+    let results = evm.precompile_programs([storage_hash, keccak_hash, unknown]);
+    assert_eq!(
+        results.iter().map(|(hash, _)| *hash).collect::<Vec<_>>(),
+        vec![storage_hash, keccak_hash, unknown]
+    );
+    assert!(results[0].1.is_ok() && results[1].1.is_ok());
+    assert!(results[2].1.is_err());
+    assert_eq!(cache.len(), 2);
+    let compiled = cache.get(&storage_hash, tag).expect("program not cached");
+    assert!(cache.get(&keccak_hash, tag).is_some());
+
+    // Calls use the compiled program, and cached programs aren't compiled again
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    assert!(execute_tx(&mut evm, create_call_tx(storage, args, 10_000_000)).is_success());
+    assert!(evm.precompile_programs([storage_hash])[0].1.is_ok());
+    assert_eq!(cache.get(&storage_hash, tag).unwrap().0, compiled.0);
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_stats_count_cached_and_cold_calls() {
+    let (context, address, _) = setup();
+    let mut evm = create_evm(context).with_program_cache(private_cache());
+    // Selector 0x00 = read slot 0
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+
+    for nonce in 0..3 {
+        let tx = create_call_tx_with_nonce(address, args.clone(), 10_000_000, nonce);
+        assert!(execute_tx(&mut evm, tx).is_success());
+    }
+
+    let stats = evm.take_program_cache_stats();
+    assert_eq!(stats.cold_calls, 1);
+    assert_eq!(stats.cached_calls, 2);
+    assert!(stats.compile_time > Duration::ZERO);
+    assert!(stats.ink_used > 0);
+    assert_eq!(*evm.program_cache_stats(), ProgramCacheStats::default());
+}
+
+sol! {
+    interface ArbWasm {
+        function activateProgram(address program)
+            external payable returns (uint16 version, uint256 dataFee);
+    }
+
+    interface ArbWasmCache {
+        function cacheProgram(address addr) external;
+        function evictCodehash(bytes32 codehash) external;
+        function codehashIsCached(bytes32 codehash) external view returns (bool);
+    }
+}
+
+const ARB_WASM: Address = address!("0x0000000000000000000000000000000000000071");
+const ARB_WASM_CACHE: Address = address!("0x0000000000000000000000000000000000000072");
+
+/// Activate the storage test program, cached through ArbWasmCache if `cache_program`, and call
+/// it, returning the EVM, the program's code hash and the gas used by the call.
+fn call_cache_managed_program(cache: &ProgramCache, cache_program: bool) -> (TestEvm, B256, u64) {
+    let (mut context, address, code_hash) = setup();
+    let owner = Address::repeat_byte(0x01);
+    fund_account(&mut context, owner, U256::from(10u128.pow(18)));
+    context
+        .arb_state(None, false)
+        .chain_owners()
+        .add(owner)
+        .unwrap();
+    let mut evm = create_evm(context).with_program_cache(cache.clone());
+
+    let activate = TxEnv {
+        value: U256::from(10u128.pow(17)),
+        ..create_call_tx(
+            ARB_WASM,
+            ArbWasm::activateProgramCall { program: address }.abi_encode(),
+            20_000_000,
+        )
+    };
+    assert!(execute_tx(&mut evm, activate).is_success());
+    let mut nonce = 1;
+    if cache_program {
+        let calldata = ArbWasmCache::cacheProgramCall { addr: address }.abi_encode();
+        let tx = create_call_tx_with_nonce(ARB_WASM_CACHE, calldata, 10_000_000, nonce);
+        assert!(execute_tx(&mut evm, tx).is_success());
+        nonce += 1;
+    }
+
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    let result = execute_tx(
+        &mut evm,
+        create_call_tx_with_nonce(address, args, 10_000_000, nonce),
+    );
+    assert!(result.is_success());
+    (evm, code_hash, result.gas_used())
+}
+
+#[test]
+fn test_programs_cached_through_arb_wasm_cache_are_pinned() {
+    let uncached = private_cache();
+    let (_, code_hash, uncached_gas) = call_cache_managed_program(&uncached, false);
+    assert!(!uncached.is_pinned(&code_hash));
+
+    let cache = private_cache();
+    let (mut evm, code_hash, cached_gas) = call_cache_managed_program(&cache, true);
+    assert!(cache.is_pinned(&code_hash));
+    // Cached programs are charged the cached init gas instead of the full init gas
+    assert!(cached_gas < uncached_gas);
+
+    let is_cached = |evm: &mut TestEvm, nonce| {
+        let calldata = ArbWasmCache::codehashIsCachedCall {
+            codehash: code_hash,
+        }
+        .abi_encode();
+        let tx = create_call_tx_with_nonce(ARB_WASM_CACHE, calldata, 10_000_000, nonce);
+        match execute_tx(evm, tx) {
+            ExecutionResult::Success { output, .. } => {
+                ArbWasmCache::codehashIsCachedCall::abi_decode_returns(output.data()).unwrap()
+            }
+            other => panic!("codehashIsCached failed: {other:?}"),
+        }
+    };
+    assert!(is_cached(&mut evm, 3));
+
+    // Evicting the program returns it to the LRU
+    let calldata = ArbWasmCache::evictCodehashCall {
+        codehash: code_hash,
+    }
+    .abi_encode();
+    let tx = create_call_tx_with_nonce(ARB_WASM_CACHE, calldata, 10_000_000, 4);
+    assert!(execute_tx(&mut evm, tx).is_success());
+    assert!(!cache.is_pinned(&code_hash));
+    assert!(!is_cached(&mut evm, 5));
+}
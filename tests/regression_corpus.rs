@@ -0,0 +1,201 @@
+//! Runs the regression corpus in `test-data/corpus`: transactions with the outcome expected under
+//! each ArbOS version they are checked at. A case failing after a change to pricing or Stylus
+//! code points at a consensus difference. See `test-data/corpus/README.md` for the case format.
+
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use arbos_revm::overrides::{AccountOverride, StateOverride, StorageOverride};
+use revm::{
+    context::{ContextTr, JournalTr, TxEnv, result::ExecutionResult},
+    primitives::{Address, Bytes, TxKind, U256},
+};
+use serde_json::Value;
+
+mod test_utils;
+use test_utils::{TestEvm, create_evm, execute_tx, setup_context_with_arbos_state};
+
+const CORPUS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/corpus");
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, String> {
+    value.get(key).ok_or_else(|| format!("missing `{key}`"))
+}
+
+fn string<'a>(value: &'a Value, key: &str) -> Result<&'a str, String> {
+    field(value, key)?
+        .as_str()
+        .ok_or_else(|| format!("`{key}` is not a string"))
+}
+
+fn number(value: &Value, key: &str) -> Result<u64, String> {
+    field(value, key)?
+        .as_u64()
+        .ok_or_else(|| format!("`{key}` is not a number"))
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|err| format!("invalid value `{value}`: {err}"))
+}
+
+fn entries(value: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    value.as_object().into_iter().flatten()
+}
+
+/// ArbOS state under `version` with the accounts of the case's prestate, as state from before
+/// the transaction.
+fn prestate(case: &Value, version: u16) -> Result<TestEvm, String> {
+    let mut overrides = StateOverride::new();
+    for (address, account) in entries(field(case, "prestate")?) {
+        let mut storage = HashMap::new();
+        for (slot, value) in entries(account.get("storage").unwrap_or(&Value::Null)) {
+            let value = value.as_str().ok_or("storage values must be strings")?;
+            storage.insert(parse(slot)?, parse(value)?);
+        }
+        let account = AccountOverride {
+            balance: string(account, "balance").map(parse).ok().transpose()?,
+            nonce: number(account, "nonce").ok(),
+            code: string(account, "code").map(parse).ok().transpose()?,
+            storage: Some(StorageOverride::Diff(storage)),
+        };
+        overrides.insert(parse(address)?, account);
+    }
+
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.arbos_version = version;
+    let mut evm = create_evm(context);
+    evm.apply_state_overrides(&overrides).unwrap();
+    Ok(evm)
+}
+
+fn transaction(case: &Value) -> Result<TxEnv, String> {
+    let tx = field(case, "transaction")?;
+    let kind = match tx.get("to").and_then(Value::as_str) {
+        Some(to) => TxKind::Call(parse(to)?),
+        None => TxKind::Create,
+    };
+    Ok(TxEnv {
+        caller: parse(string(tx, "from")?)?,
+        kind,
+        data: parse(string(tx, "input").unwrap_or("0x"))?,
+        value: parse(string(tx, "value").unwrap_or("0x0"))?,
+        gas_limit: number(tx, "gas")?,
+        gas_price: number(tx, "gasPrice")?.into(),
+        nonce: number(tx, "nonce").unwrap_or_default(),
+        ..Default::default()
+    })
+}
+
+/// Differences between the outcome of the case under ArbOS `version` and `expected`.
+fn check(case: &Value, version: u16, expected: &Value) -> Result<Vec<String>, String> {
+    let mut evm = prestate(case, version)?;
+    let result = execute_tx(&mut evm, transaction(case)?);
+
+    let mut diffs = Vec::new();
+    let (status, output) = match &result {
+        ExecutionResult::Success { output, .. } => ("success", output.data().clone()),
+        ExecutionResult::Revert { output, .. } => ("revert", output.clone()),
+        ExecutionResult::Halt { .. } => ("halt", Bytes::new()),
+    };
+    let expected_status = string(expected, "status")?;
+    if status != expected_status {
+        diffs.push(format!("status {status}, expected {expected_status}"));
+    }
+    if let Ok(gas_used) = number(expected, "gasUsed")
+        && result.gas_used() != gas_used
+    {
+        diffs.push(format!(
+            "gas used {}, expected {gas_used}",
+            result.gas_used()
+        ));
+    }
+    if let Ok(expected_output) = string(expected, "output")
+        && output != parse::<Bytes>(expected_output)?
+    {
+        diffs.push(format!("output {output}, expected {expected_output}"));
+    }
+
+    let journal = evm.0.ctx.journal_mut();
+    for (address, account) in entries(expected.get("poststate").unwrap_or(&Value::Null)) {
+        let address: Address = parse(address)?;
+        let info = journal.load_account(address).unwrap().data.info.clone();
+        if let Ok(balance) = string(account, "balance")
+            && info.balance != parse::<U256>(balance)?
+        {
+            diffs.push(format!(
+                "{address} balance {}, expected {balance}",
+                info.balance
+            ));
+        }
+        if let Ok(nonce) = number(account, "nonce")
+            && info.nonce != nonce
+        {
+            diffs.push(format!("{address} nonce {}, expected {nonce}", info.nonce));
+        }
+        for (slot, value) in entries(account.get("storage").unwrap_or(&Value::Null)) {
+            let actual = journal.sload(address, parse(slot)?).unwrap().data;
+            let value = value.as_str().unwrap_or_default();
+            if actual != parse::<U256>(value)? {
+                diffs.push(format!(
+                    "{address} slot {slot} {actual:#x}, expected {value}"
+                ));
+            }
+        }
+    }
+    Ok(diffs)
+}
+
+/// Run every expectation of the case in `path`, returning a line per failure.
+fn run_case(path: &Path) -> Vec<String> {
+    let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+    let case: Value = match fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|err| err.to_string()))
+    {
+        Ok(case) => case,
+        Err(err) => return vec![format!("{name}: {err}")],
+    };
+
+    let mut failures = Vec::new();
+    let expectations = case.get("expected").and_then(Value::as_array);
+    for expected in expectations.into_iter().flatten() {
+        let versions = expected.get("arbosVersions").and_then(Value::as_array);
+        for version in versions.into_iter().flatten() {
+            let Some(version) = version.as_u64().and_then(|v| u16::try_from(v).ok()) else {
+                failures.push(format!("{name}: invalid ArbOS version {version}"));
+                continue;
+            };
+            match check(&case, version, expected) {
+                Ok(diffs) => failures.extend(
+                    diffs
+                        .into_iter()
+                        .map(|diff| format!("{name} at ArbOS {version}: {diff}")),
+                ),
+                Err(err) => failures.push(format!("{name}: {err}")),
+            }
+        }
+    }
+    failures
+}
+
+#[test]
+fn test_regression_corpus() {
+    let mut paths: Vec<_> = fs::read_dir(CORPUS_DIR)
+        .expect("failed to read the corpus directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "the corpus is empty");
+
+    let failures: Vec<String> = paths.iter().flat_map(|path| run_case(path)).collect();
+    assert!(
+        failures.is_empty(),
+        "{} corpus expectations failed:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,123 @@
+//! Tests for the chain ID and replay protection rules of each transaction type.
+
+use arbos_revm::{
+    ArbitrumInternalTx, ArbitrumRetryTx, ArbitrumTransactionError,
+    constants::{ARBITRUM_INTERNAL_TX_TYPE, ARBITRUM_RETRY_TX_TYPE},
+};
+use revm::{
+    ExecuteEvm,
+    context::{TxEnv, result::EVMError},
+    primitives::{Address, B256, U256},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx, create_evm, fund_account, setup_context_with_arbos_state,
+};
+
+const CHAIN_ID: u64 = 42;
+
+fn setup(reject_unprotected_txs: bool) -> TestEvm {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.inner.chain_id = CHAIN_ID;
+    context.cfg.inner.tx_chain_id_check = true;
+    context.cfg.reject_unprotected_txs = reject_unprotected_txs;
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    create_evm(context)
+}
+
+fn call_tx(tx_type: u8, chain_id: Option<u64>) -> TxEnv {
+    TxEnv {
+        tx_type,
+        chain_id,
+        ..create_call_tx(Address::repeat_byte(0x42), Vec::new(), 100_000)
+    }
+}
+
+fn transaction_error(
+    result: Result<impl std::fmt::Debug, EVMError<impl std::fmt::Debug, ArbitrumTransactionError>>,
+) -> ArbitrumTransactionError {
+    match result {
+        Err(EVMError::Transaction(error)) => error,
+        other => panic!("expected a transaction error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_typed_tx_needs_the_chain_id() {
+    let mut evm = setup(false);
+    assert_eq!(
+        transaction_error(evm.transact_one(call_tx(2, Some(1)).into())),
+        ArbitrumTransactionError::InvalidChainId {
+            tx_type: 2,
+            expected: CHAIN_ID,
+            got: 1,
+        }
+    );
+    assert_eq!(
+        transaction_error(evm.transact_one(call_tx(2, None).into())),
+        ArbitrumTransactionError::MissingChainId { tx_type: 2 }
+    );
+    assert!(
+        evm.transact_one(call_tx(2, Some(CHAIN_ID)).into())
+            .unwrap()
+            .is_success()
+    );
+}
+
+#[test]
+fn test_unprotected_legacy_tx_follows_the_config() {
+    let mut evm = setup(false);
+    assert!(
+        evm.transact_one(call_tx(0, None).into())
+            .unwrap()
+            .is_success()
+    );
+
+    let mut evm = setup(true);
+    assert_eq!(
+        transaction_error(evm.transact_one(call_tx(0, None).into())),
+        ArbitrumTransactionError::UnprotectedTx
+    );
+    assert!(
+        evm.transact_one(call_tx(0, Some(CHAIN_ID)).into())
+            .unwrap()
+            .is_success()
+    );
+}
+
+#[test]
+fn test_unsigned_txs_must_match_the_chain_id() {
+    let mut evm = setup(true);
+
+    let retry_tx = ArbitrumRetryTx {
+        chain_id: 1,
+        from: Address::repeat_byte(0x01),
+        gas: 100_000,
+        to: Address::repeat_byte(0x42),
+        ticket_id: B256::repeat_byte(0x11),
+        ..Default::default()
+    };
+    assert_eq!(
+        transaction_error(evm.transact_one(retry_tx.into())),
+        ArbitrumTransactionError::InvalidChainId {
+            tx_type: ARBITRUM_RETRY_TX_TYPE,
+            expected: CHAIN_ID,
+            got: 1,
+        }
+    );
+
+    let start_block = ArbitrumInternalTx::start_block(1, U256::ZERO, 0, 1, 0);
+    assert_eq!(
+        transaction_error(evm.transact_one(start_block.into())),
+        ArbitrumTransactionError::InvalidChainId {
+            tx_type: ARBITRUM_INTERNAL_TX_TYPE,
+            expected: CHAIN_ID,
+            got: 1,
+        }
+    );
+}
@@ -0,0 +1,650 @@
+//! Tests for the retryable ticket lifecycle: submission, auto-redeem scheduling, keepalive,
+//! cancellation, manual redeems and reaping.
+
+use alloy_sol_types::{SolCall, SolEvent, sol};
+use arbos_revm::{
+    ArbitrumInternalTx, ArbitrumSubmitRetryableTx, ArbitrumTransactionError,
+    block::StartBlockInfo,
+    constants::ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE,
+    state::{
+        ArbState, ArbStateGetter,
+        retryable::{RETRYABLE_LIFETIME_SECONDS, retryable_escrow_address},
+        types::StorageBackedTr,
+    },
+};
+use revm::{
+    ExecuteEvm,
+    context::{
+        ContextTr, JournalTr, TxEnv,
+        result::{EVMError, ExecutionResult},
+    },
+    primitives::{Address, B256, Bytes, TxKind, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_evm, deploy_evm_code, execute_tx, fund_account, setup_context_with_arbos_state,
+};
+
+sol! {
+    interface ArbRetryableTx {
+        function submitRetryable(
+            bytes32 requestId,
+            uint256 l1BaseFee,
+            uint256 deposit,
+            uint256 callvalue,
+            uint256 gasFeeCap,
+            uint64 gasLimit,
+            uint256 maxSubmissionFee,
+            address feeRefundAddress,
+            address beneficiary,
+            address retryTo,
+            bytes calldata retryData
+        ) external;
+        function getTimeout(bytes32 ticketId) external view returns (uint256);
+        function keepalive(bytes32 ticketId) external returns (uint256);
+        function cancel(bytes32 ticketId) external;
+        function redeem(bytes32 ticketId) external returns (bytes32);
+
+        event TicketCreated(bytes32 indexed ticketId);
+        event RedeemScheduled(
+            bytes32 indexed ticketId,
+            bytes32 indexed retryTxHash,
+            uint64 indexed sequenceNum,
+            uint64 donatedGas,
+            address gasDonor,
+            uint256 maxRefund,
+            uint256 submissionFeeRefund
+        );
+        event Canceled(bytes32 indexed ticketId);
+    }
+}
+
+const ARB_RETRYABLE_TX: Address = address!("0x000000000000000000000000000000000000006e");
+const L1_SENDER: Address = address!("0x00000000000000000000000000000000000000a1");
+const USER: Address = address!("0x0101010101010101010101010101010101010101");
+const REVERTER: Address = address!("0x00000000000000000000000000000000000000c3");
+const REVERTING_REDEEMER: Address = address!("0x00000000000000000000000000000000000000c4");
+const DOUBLE_REDEEMER: Address = address!("0x00000000000000000000000000000000000000c5");
+const INFRA_FEE_ACCOUNT: Address = address!("0x00000000000000000000000000000000000000c6");
+const CALLVALUE: u64 = 5_000;
+
+/// Code forwarding its calldata to ArbRetryableTx `calls` times with 200k gas each, then
+/// reverting or stopping.
+fn redeemer(calls: usize, revert: bool) -> Vec<u8> {
+    // CALLDATASIZE PUSH1 0 PUSH1 0 CALLDATACOPY
+    let mut code = vec![0x36, 0x60, 0x00, 0x60, 0x00, 0x37];
+    for _ in 0..calls {
+        // CALL(200000, ARB_RETRYABLE_TX, 0, 0, CALLDATASIZE, 0, 0) POP
+        code.extend([
+            0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00, 0x60, 0x00, 0x60, 0x6e, 0x62, 0x03, 0x0d,
+            0x40, 0xf1, 0x50,
+        ]);
+    }
+    if revert {
+        // PUSH1 0 PUSH1 0 REVERT
+        code.extend([0x60, 0x00, 0x60, 0x00, 0xfd]);
+    } else {
+        // STOP
+        code.push(0x00);
+    }
+    code
+}
+
+struct Session {
+    evm: TestEvm,
+    nonce: u64,
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut context = setup_context_with_arbos_state();
+        fund_account(&mut context, USER, U256::from(1_000_000_000_u64));
+        // PUSH1 0 PUSH1 0 REVERT
+        deploy_evm_code(&mut context, REVERTER, &[0x60, 0x00, 0x60, 0x00, 0xfd]);
+        deploy_evm_code(&mut context, REVERTING_REDEEMER, &redeemer(1, true));
+        deploy_evm_code(&mut context, DOUBLE_REDEEMER, &redeemer(2, false));
+        Self {
+            evm: create_evm(context),
+            nonce: 0,
+        }
+    }
+
+    fn timestamp(&self) -> u64 {
+        self.evm.0.ctx.block.timestamp.saturating_to()
+    }
+
+    /// Submit a retryable to `USER`, returning the ticket ID and the submission's result.
+    fn submit(&mut self, gas_limit: u64) -> (B256, ExecutionResult) {
+        self.submit_to(USER, gas_limit)
+    }
+
+    fn submit_to(&mut self, retry_to: Address, gas_limit: u64) -> (B256, ExecutionResult) {
+        let call = ArbRetryableTx::submitRetryableCall {
+            requestId: B256::repeat_byte(0x11),
+            l1BaseFee: U256::ZERO,
+            deposit: U256::from(1_000_000),
+            callvalue: U256::from(CALLVALUE),
+            gasFeeCap: U256::ZERO,
+            gasLimit: gas_limit,
+            maxSubmissionFee: U256::ZERO,
+            feeRefundAddress: L1_SENDER,
+            beneficiary: USER,
+            retryTo: retry_to,
+            retryData: Bytes::new(),
+        };
+        let tx = TxEnv {
+            tx_type: ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE,
+            caller: L1_SENDER,
+            kind: TxKind::Call(ARB_RETRYABLE_TX),
+            data: call.abi_encode().into(),
+            gas_limit,
+            ..Default::default()
+        };
+
+        let result = execute_tx(&mut self.evm, tx);
+        let ticket_id = match &result {
+            ExecutionResult::Success { output, .. } => B256::from_slice(output.data()),
+            other => panic!("submission failed: {:?}", other),
+        };
+        (ticket_id, result)
+    }
+
+    /// Execute a redeem of `ticket_id` refunding to `L1_SENDER`.
+    fn redeem(&mut self, ticket_id: B256) -> ExecutionResult {
+        self.redeem_with_fee_cap(ticket_id, U256::ZERO)
+    }
+
+    fn redeem_with_fee_cap(&mut self, ticket_id: B256, gas_fee_cap: U256) -> ExecutionResult {
+        let retry_tx = self
+            .evm
+            .0
+            .ctx
+            .arb_state(None, false)
+            .retryable_state()
+            .retryable(ticket_id)
+            .make_tx(
+                1,
+                0,
+                gas_fee_cap,
+                100_000,
+                ticket_id,
+                L1_SENDER,
+                U256::MAX,
+                U256::ZERO,
+            )
+            .unwrap();
+        self.evm
+            .transact_one(retry_tx.into())
+            .expect("retry transaction failed")
+    }
+
+    fn call(&mut self, calldata: Vec<u8>) -> ExecutionResult {
+        self.call_to(ARB_RETRYABLE_TX, calldata)
+    }
+
+    fn call_to(&mut self, to: Address, calldata: Vec<u8>) -> ExecutionResult {
+        let tx = self.user_tx(to, calldata);
+        execute_tx(&mut self.evm, tx)
+    }
+
+    fn user_tx(&mut self, to: Address, calldata: Vec<u8>) -> TxEnv {
+        let tx = TxEnv {
+            caller: USER,
+            kind: TxKind::Call(to),
+            data: calldata.into(),
+            gas_limit: 1_000_000,
+            gas_price: 1,
+            nonce: self.nonce,
+            ..Default::default()
+        };
+        self.nonce += 1;
+        tx
+    }
+
+    fn num_tries(&mut self, ticket_id: B256) -> u64 {
+        self.evm
+            .0
+            .ctx
+            .arb_state(None, true)
+            .retryable(ticket_id)
+            .num_tries()
+            .get()
+            .unwrap()
+    }
+
+    fn timeout(&mut self, ticket_id: B256) -> Option<u64> {
+        match self.call(
+            ArbRetryableTx::getTimeoutCall {
+                ticketId: ticket_id,
+            }
+            .abi_encode(),
+        ) {
+            ExecutionResult::Success { output, .. } => {
+                Some(U256::from_be_slice(output.data()).to())
+            }
+            _ => None,
+        }
+    }
+
+    fn balance(&mut self, address: Address) -> U256 {
+        self.evm
+            .0
+            .ctx
+            .journal_mut()
+            .load_account(address)
+            .unwrap()
+            .data
+            .info
+            .balance
+    }
+}
+
+#[test]
+fn test_submit_retryable_creates_ticket() {
+    let mut session = Session::new();
+
+    let (ticket_id, result) = session.submit(0);
+
+    let logs = result.logs();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(
+        logs[0].data.topics()[0],
+        ArbRetryableTx::TicketCreated::SIGNATURE_HASH
+    );
+    assert_eq!(logs[0].data.topics()[1], ticket_id);
+
+    let expected = session.timestamp() + RETRYABLE_LIFETIME_SECONDS;
+    assert_eq!(session.timeout(ticket_id), Some(expected));
+    assert_eq!(
+        session.balance(retryable_escrow_address(ticket_id)),
+        U256::from(CALLVALUE)
+    );
+}
+
+#[test]
+fn test_submit_retryable_schedules_auto_redeem() {
+    let mut session = Session::new();
+
+    let (ticket_id, result) = session.submit(100_000);
+
+    assert_eq!(result.gas_used(), 100_000);
+    let logs = result.logs();
+    assert_eq!(logs.len(), 2);
+    let scheduled = &logs[1].data;
+    assert_eq!(
+        scheduled.topics()[0],
+        ArbRetryableTx::RedeemScheduled::SIGNATURE_HASH
+    );
+    assert_eq!(scheduled.topics()[1], ticket_id);
+    assert_eq!(scheduled.topics()[3], B256::ZERO);
+}
+
+#[test]
+fn test_keepalive_extends_timeout_once_per_lifetime() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+    let timeout = session.timeout(ticket_id).unwrap();
+
+    let keepalive = ArbRetryableTx::keepaliveCall {
+        ticketId: ticket_id,
+    }
+    .abi_encode();
+    match session.call(keepalive.clone()) {
+        ExecutionResult::Success { output, .. } => assert_eq!(
+            U256::from_be_slice(output.data()),
+            U256::from(timeout + RETRYABLE_LIFETIME_SECONDS)
+        ),
+        other => panic!("keepalive failed: {:?}", other),
+    }
+    assert_eq!(
+        session.timeout(ticket_id),
+        Some(timeout + RETRYABLE_LIFETIME_SECONDS)
+    );
+
+    // The new timeout is more than one lifetime away.
+    assert!(matches!(
+        session.call(keepalive),
+        ExecutionResult::Revert { .. }
+    ));
+
+    let queue_size = session
+        .evm
+        .0
+        .ctx
+        .arb_state(None, true)
+        .retryable_state()
+        .timeout_queue()
+        .size()
+        .unwrap();
+    assert_eq!(queue_size, 2);
+}
+
+#[test]
+fn test_cancel_refunds_escrow_to_beneficiary() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+    let balance_before = session.balance(USER);
+
+    let result = session.call(
+        ArbRetryableTx::cancelCall {
+            ticketId: ticket_id,
+        }
+        .abi_encode(),
+    );
+    let ExecutionResult::Success { logs, .. } = result else {
+        panic!("cancel failed: {:?}", result);
+    };
+    assert_eq!(
+        logs[0].data.topics()[0],
+        ArbRetryableTx::Canceled::SIGNATURE_HASH
+    );
+
+    assert_eq!(
+        session.balance(retryable_escrow_address(ticket_id)),
+        U256::ZERO
+    );
+    // The basefee is zero, so the call itself costs nothing.
+    assert_eq!(
+        session.balance(USER),
+        balance_before + U256::from(CALLVALUE)
+    );
+    assert_eq!(session.timeout(ticket_id), None);
+}
+
+#[test]
+fn test_redeem_schedules_retry() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+
+    let result = session.call(
+        ArbRetryableTx::redeemCall {
+            ticketId: ticket_id,
+        }
+        .abi_encode(),
+    );
+    let ExecutionResult::Success { logs, output, .. } = result else {
+        panic!("redeem failed: {:?}", result);
+    };
+
+    let event = ArbRetryableTx::RedeemScheduled::decode_log_data(&logs[0].data).unwrap();
+    assert_eq!(event.ticketId, ticket_id);
+    assert_eq!(event.retryTxHash, B256::from_slice(output.data()));
+    assert_eq!(event.sequenceNum, 0);
+    assert_eq!(event.gasDonor, USER);
+    assert!(event.donatedGas >= 21_000);
+
+    assert_eq!(session.num_tries(ticket_id), 1);
+}
+
+#[test]
+fn test_redeem_in_reverted_call_is_not_scheduled() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+    let timeout = session.timeout(ticket_id);
+
+    let result = session.call_to(
+        REVERTING_REDEEMER,
+        ArbRetryableTx::redeemCall {
+            ticketId: ticket_id,
+        }
+        .abi_encode(),
+    );
+
+    assert!(matches!(result, ExecutionResult::Revert { .. }));
+    assert!(session.evm.take_scheduled_redeems().is_empty());
+    assert_eq!(session.num_tries(ticket_id), 0);
+    assert_eq!(session.timeout(ticket_id), timeout);
+}
+
+#[test]
+fn test_retry_of_redeemed_ticket_is_dropped_from_block() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+
+    // Both redeems are scheduled, but the first retry deletes the ticket
+    let tx = session.user_tx(
+        DOUBLE_REDEEMER,
+        ArbRetryableTx::redeemCall {
+            ticketId: ticket_id,
+        }
+        .abi_encode(),
+    );
+    let mut block_env = session.evm.0.ctx.block.clone();
+    block_env.number += U256::from(1);
+    let result = session
+        .evm
+        .transact_block([tx.into()], block_env, StartBlockInfo::default())
+        .expect("block execution failed");
+
+    let [_, redeeming, retry] = result.receipts.as_slice() else {
+        panic!("expected 3 receipts, got {}", result.receipts.len());
+    };
+    assert!(redeeming.result.is_success());
+    assert_eq!(redeeming.result.logs().len(), 2);
+    assert!(retry.result.is_success());
+    assert!(result.rejected.is_empty());
+    assert_eq!(session.timeout(ticket_id), None);
+}
+
+#[test]
+fn test_expired_ticket_is_reaped_at_block_start() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+    let timeout = session.timeout(ticket_id).unwrap();
+
+    session.evm.0.ctx.block.timestamp = U256::from(timeout + 1);
+    let start_block = ArbitrumInternalTx::start_block(1, U256::ZERO, 0, 1, 0);
+    assert!(
+        session
+            .evm
+            .transact_one(start_block.into())
+            .unwrap()
+            .is_success()
+    );
+
+    let mut arb_state = session.evm.0.ctx.arb_state(None, true);
+    assert_eq!(arb_state.retryable(ticket_id).timeout().get().unwrap(), 0);
+    assert_eq!(
+        arb_state.retryable_state().timeout_queue().size().unwrap(),
+        0
+    );
+    drop(arb_state);
+    assert_eq!(
+        session.balance(retryable_escrow_address(ticket_id)),
+        U256::ZERO
+    );
+}
+
+#[test]
+fn test_successful_redeem_pays_callvalue_and_deletes_ticket() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+    let balance_before = session.balance(USER);
+
+    assert!(session.redeem(ticket_id).is_success());
+
+    assert_eq!(
+        session.balance(USER),
+        balance_before + U256::from(CALLVALUE)
+    );
+    assert_eq!(
+        session.balance(retryable_escrow_address(ticket_id)),
+        U256::ZERO
+    );
+    assert_eq!(session.timeout(ticket_id), None);
+}
+
+#[test]
+fn test_failed_redeem_returns_callvalue_to_escrow() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit_to(REVERTER, 0);
+    let timeout = session.timeout(ticket_id);
+
+    assert!(matches!(
+        session.redeem(ticket_id),
+        ExecutionResult::Revert { .. }
+    ));
+
+    // The ticket survives for another attempt, with its callvalue back in escrow.
+    assert_eq!(
+        session.balance(retryable_escrow_address(ticket_id)),
+        U256::from(CALLVALUE)
+    );
+    assert_eq!(
+        session.balance(L1_SENDER),
+        U256::from(1_000_000 - CALLVALUE)
+    );
+    assert_eq!(session.timeout(ticket_id), timeout);
+
+    // Cancelling then refunds the escrow to the beneficiary.
+    let balance_before = session.balance(USER);
+    assert!(
+        session
+            .call(
+                ArbRetryableTx::cancelCall {
+                    ticketId: ticket_id
+                }
+                .abi_encode()
+            )
+            .is_success()
+    );
+    assert_eq!(
+        session.balance(USER),
+        balance_before + U256::from(CALLVALUE)
+    );
+}
+
+#[test]
+fn test_redeem_refund_the_fee_account_cannot_cover_is_skipped() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+    session.evm.0.ctx.block.basefee = 1;
+
+    // The network fee account holds nothing to refund the unused gas with, which is logged
+    // rather than failing the redeem
+    let result = session.redeem_with_fee_cap(ticket_id, U256::from(1));
+    assert!(result.is_success(), "{result:?}");
+    assert_eq!(session.timeout(ticket_id), None);
+}
+
+#[test]
+fn test_redeem_refunds_infra_fee_share_from_infra_fee_account() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+    session.evm.0.ctx.block.basefee = 1;
+    session
+        .evm
+        .0
+        .ctx
+        .arb_state(None, false)
+        .infra_fee_account()
+        .set(INFRA_FEE_ACCOUNT)
+        .unwrap();
+    fund_account(
+        &mut session.evm.0.ctx,
+        INFRA_FEE_ACCOUNT,
+        U256::from(1_000_000),
+    );
+    let sender_before = session.balance(L1_SENDER);
+
+    // The minimum base fee is above the fee cap, so all of the unused gas is refunded by the
+    // infra fee account
+    let result = session.redeem_with_fee_cap(ticket_id, U256::from(1));
+    assert!(result.is_success(), "{result:?}");
+    let gas_left = 100_000 - result.gas_used();
+    assert_eq!(
+        session.balance(INFRA_FEE_ACCOUNT),
+        U256::from(1_000_000 - gas_left)
+    );
+    assert_eq!(
+        session.balance(L1_SENDER),
+        sender_before + U256::from(gas_left)
+    );
+}
+
+#[test]
+fn test_redeem_of_missing_ticket_is_rejected() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+    assert!(session.redeem(ticket_id).is_success());
+
+    let retry_tx = arbos_revm::ArbitrumRetryTx {
+        chain_id: 1,
+        from: L1_SENDER,
+        gas: 100_000,
+        to: USER,
+        ticket_id,
+        refund_to: L1_SENDER,
+        ..Default::default()
+    };
+    assert!(matches!(
+        session.evm.transact_one(retry_tx.into()),
+        Err(EVMError::Transaction(ArbitrumTransactionError::RetryableNotFound { ticket_id: id }))
+            if id == ticket_id
+    ));
+}
+
+#[test]
+fn test_auto_redeem_is_queued_and_executes() {
+    let mut session = Session::new();
+    let chain_id = session.evm.0.ctx.cfg.inner.chain_id;
+    let submission = ArbitrumSubmitRetryableTx {
+        chain_id,
+        request_id: B256::repeat_byte(0x22),
+        from: L1_SENDER,
+        deposit_value: U256::from(1_000_000),
+        gas: 100_000,
+        retry_to: USER,
+        retry_value: U256::from(CALLVALUE),
+        beneficiary: USER,
+        fee_refund_addr: L1_SENDER,
+        ..Default::default()
+    };
+    let ticket_id = submission.hash();
+
+    let result = session.evm.transact_one(submission.into()).unwrap();
+    assert_eq!(B256::from_slice(result.output().unwrap()), ticket_id);
+
+    let redeems = session.evm.take_scheduled_redeems();
+    assert_eq!(redeems.len(), 1);
+    assert_eq!(redeems[0].ticket_id, ticket_id);
+    assert_eq!(redeems[0].gas, 100_000);
+    assert!(session.evm.take_scheduled_redeems().is_empty());
+
+    let balance_before = session.balance(USER);
+    let retry_tx = redeems.into_iter().next().unwrap();
+    assert!(
+        session
+            .evm
+            .transact_one(retry_tx.into())
+            .unwrap()
+            .is_success()
+    );
+
+    assert_eq!(
+        session.balance(USER),
+        balance_before + U256::from(CALLVALUE)
+    );
+    assert_eq!(session.timeout(ticket_id), None);
+}
+
+#[test]
+fn test_manual_redeem_is_queued() {
+    let mut session = Session::new();
+    let (ticket_id, _) = session.submit(0);
+    assert!(session.evm.take_scheduled_redeems().is_empty());
+
+    let result = session.call(
+        ArbRetryableTx::redeemCall {
+            ticketId: ticket_id,
+        }
+        .abi_encode(),
+    );
+    let retry_tx_hash = B256::from_slice(result.output().unwrap());
+
+    let redeems = session.evm.take_scheduled_redeems();
+    assert_eq!(redeems.len(), 1);
+    assert_eq!(redeems[0].hash(), retry_tx_hash);
+    assert_eq!(redeems[0].nonce, 0);
+}
@@ -3,15 +3,26 @@
 
 //! Revert behavior tests for Stylus programs.
 
+use arbos_revm::{
+    ArbitrumEvm,
+    inspector::{PrecompileInspector, StylusInspector},
+    local_context::ArbitrumLocalContextTr,
+    precompiles::ArbitrumPrecompileProvider,
+    stylus_executor::StylusOutcome,
+};
+use arbutil::evm::user::UserOutcomeKind;
 use revm::{
-    context::result::ExecutionResult,
+    InspectEvm, Inspector,
+    context::{ContextTr, result::ExecutionResult},
+    handler::instructions::EthInstructions,
+    interpreter::{CallInputs, CallOutcome},
     primitives::{Address, U256},
 };
 
 mod test_utils;
 use test_utils::{
-    create_call_tx, create_call_tx_with_nonce, create_evm, deploy_wat_program, execute_tx,
-    fund_account, setup_context_with_arbos_state,
+    TestContext, create_call_tx, create_call_tx_with_nonce, create_evm, deploy_evm_code,
+    deploy_wat_program, execute_tx, fund_account, setup_context_with_arbos_state,
 };
 
 // ============================================================================
@@ -434,3 +445,106 @@ fn test_e2e_self_call() {
         }
     }
 }
+
+// ============================================================================
+// Inspector Outcome Tests
+// ============================================================================
+
+/// Collects the Stylus outcome seen by each `call_end`, innermost frame first.
+#[derive(Default)]
+struct OutcomeRecorder(Vec<Option<StylusOutcome>>);
+
+impl Inspector<TestContext> for OutcomeRecorder {
+    fn call_end(
+        &mut self,
+        context: &mut TestContext,
+        _inputs: &CallInputs,
+        _outcome: &mut CallOutcome,
+    ) {
+        self.0.push(context.local().stylus_outcome().cloned());
+    }
+}
+
+impl StylusInspector<TestContext> for OutcomeRecorder {}
+
+impl PrecompileInspector<TestContext> for OutcomeRecorder {}
+
+fn record_outcomes(wat: &[u8], args: Vec<u8>) -> Vec<Option<StylusOutcome>> {
+    let mut context = setup_context_with_arbos_state();
+    let program_address = deploy_wat_program(&mut context, wat);
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        OutcomeRecorder::default(),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::<TestContext>::default(),
+    );
+    evm.inspect_one_tx(create_call_tx(program_address, args, 10_000_000).into())
+        .expect("transaction execution failed");
+
+    evm.into_inspector().0
+}
+
+#[test]
+fn test_inspector_sees_stylus_revert() {
+    let outcomes = record_outcomes(include_bytes!("../test-data/revert.wat"), vec![0x03]);
+
+    let [Some(outcome)] = outcomes.as_slice() else {
+        panic!("expected one Stylus outcome, got {:?}", outcomes);
+    };
+    assert_eq!(outcome.kind, UserOutcomeKind::Revert);
+    assert!(outcome.ink_used > 0);
+    assert!(outcome.failure.is_none());
+}
+
+#[test]
+fn test_inspector_distinguishes_trap_from_revert() {
+    let outcomes = record_outcomes(include_bytes!("../test-data/trap.wat"), vec![]);
+
+    let [Some(outcome)] = outcomes.as_slice() else {
+        panic!("expected one Stylus outcome, got {:?}", outcomes);
+    };
+    assert_eq!(outcome.kind, UserOutcomeKind::Failure);
+    assert!(outcome.failure.is_some());
+}
+
+#[test]
+fn test_inspector_outcome_only_for_the_ending_frame() {
+    let mut context = setup_context_with_arbos_state();
+    let program_address =
+        deploy_wat_program(&mut context, include_bytes!("../test-data/revert.wat"));
+
+    // CALL(GAS, program, 0, 0, 0, 0, 0); STOP
+    let mut relay_code = vec![0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00];
+    relay_code.push(0x73);
+    relay_code.extend_from_slice(program_address.as_slice());
+    relay_code.extend_from_slice(&[0x5a, 0xf1, 0x00]);
+    let relay = Address::repeat_byte(0x44);
+    deploy_evm_code(&mut context, relay, &relay_code);
+
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        OutcomeRecorder::default(),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::<TestContext>::default(),
+    );
+    evm.inspect_one_tx(create_call_tx(relay, vec![], 10_000_000).into())
+        .expect("transaction execution failed");
+
+    // The Stylus callee ends first; the EVM caller that ends after it has no Stylus outcome.
+    let outcomes = evm.into_inspector().0;
+    assert_eq!(outcomes.len(), 2);
+    assert!(outcomes[0].is_some());
+    assert!(outcomes[1].is_none());
+}
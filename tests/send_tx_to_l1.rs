@@ -0,0 +1,154 @@
+//! Tests for L2-to-L1 sends through ArbSys and the outbox Merkle accumulator.
+
+use arbos_revm::state::{ArbState, ArbStateGetter};
+use revm::{
+    context::{ContextTr, JournalTr, TxEnv, result::ExecutionResult},
+    primitives::{Address, B256, Bytes, Log, TxKind, U256, address, keccak256},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, TestEvm, create_call_tx_with_nonce, create_evm, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+const ARB_SYS: Address = address!("0x0000000000000000000000000000000000000064");
+const DESTINATION: Address = address!("0x00000000000000000000000000000000000000d1");
+
+fn withdraw_eth_calldata(destination: Address) -> Vec<u8> {
+    let mut calldata = keccak256("withdrawEth(address)")[..4].to_vec();
+    calldata.extend_from_slice(B256::left_padding_from(destination.as_slice()).as_slice());
+    calldata
+}
+
+fn withdraw(evm: &mut TestEvm, value: u64, nonce: u64) -> (U256, Vec<Log>) {
+    let mut tx = create_call_tx_with_nonce(
+        ARB_SYS,
+        withdraw_eth_calldata(DESTINATION),
+        1_000_000,
+        nonce,
+    );
+    tx.value = U256::from(value);
+
+    match execute_tx(evm, tx) {
+        ExecutionResult::Success { output, logs, .. } => (U256::from_be_slice(output.data()), logs),
+        other => panic!("withdrawEth failed: {:?}", other),
+    }
+}
+
+fn setup() -> TestContext {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    context
+}
+
+#[test]
+fn test_withdraw_eth_emits_l2_to_l1_tx() {
+    let mut evm = create_evm(setup());
+
+    let (leaf, logs) = withdraw(&mut evm, 1_000, 0);
+    assert_eq!(leaf, U256::ZERO);
+
+    let event = logs.last().expect("missing L2ToL1Tx");
+    assert_eq!(event.address, ARB_SYS);
+    let topics = event.data.topics();
+    assert_eq!(
+        topics[0],
+        keccak256(
+            "L2ToL1Tx(address,address,uint256,uint256,uint256,uint256,uint256,uint256,bytes)"
+        )
+    );
+    assert_eq!(topics[1], B256::left_padding_from(DESTINATION.as_slice()));
+    assert_eq!(topics[3], B256::ZERO);
+
+    // The withdrawn value leaves L2.
+    let balance = evm
+        .0
+        .ctx
+        .journal_mut()
+        .load_account(ARB_SYS)
+        .unwrap()
+        .data
+        .info
+        .balance;
+    assert_eq!(balance, U256::ZERO);
+}
+
+#[test]
+fn test_sends_append_to_accumulator() {
+    let mut evm = create_evm(setup());
+
+    let (first, logs) = withdraw(&mut evm, 0, 0);
+    assert_eq!(first, U256::ZERO);
+    assert_eq!(logs.len(), 1);
+
+    // The second leaf completes the level-one node over both sends.
+    let (second, logs) = withdraw(&mut evm, 0, 1);
+    assert_eq!(second, U256::ONE);
+    assert_eq!(logs.len(), 2);
+    assert_eq!(
+        logs[0].data.topics()[0],
+        keccak256("SendMerkleUpdate(uint256,bytes32,uint256)")
+    );
+    assert_eq!(logs[0].data.topics()[3], B256::from(U256::from(1) << 192));
+
+    let mut arb_state = evm.0.ctx.arb_state(None, true);
+    let mut accumulator = arb_state.send_merkle_accumulator();
+    assert_eq!(accumulator.size().unwrap(), 2);
+    assert_eq!(accumulator.root().unwrap(), logs[0].data.topics()[2]);
+}
+
+#[test]
+fn test_send_merkle_tree_state_restricted_to_address_zero() {
+    let mut context = setup();
+    fund_account(&mut context, Address::ZERO, U256::from(1_000_000_000_u64));
+    let mut evm = create_evm(context);
+    withdraw(&mut evm, 0, 0);
+
+    let calldata = keccak256("sendMerkleTreeState()")[..4].to_vec();
+    let tx = create_call_tx_with_nonce(ARB_SYS, calldata.clone(), 1_000_000, 1);
+    assert!(matches!(
+        execute_tx(&mut evm, tx),
+        ExecutionResult::Revert { .. }
+    ));
+
+    let tx = TxEnv {
+        caller: Address::ZERO,
+        kind: TxKind::Call(ARB_SYS),
+        data: Bytes::from(calldata),
+        gas_limit: 1_000_000,
+        gas_price: 1,
+        ..Default::default()
+    };
+    let output = match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => output.into_data(),
+        other => panic!("sendMerkleTreeState failed: {:?}", other),
+    };
+    assert_eq!(U256::from_be_slice(&output[..32]), U256::ONE);
+    assert_ne!(B256::from_slice(&output[32..64]), B256::ZERO);
+}
+
+#[test]
+fn test_send_merkle_tree_state_rejection_keeps_gas_since_arbos_11() {
+    let calldata = keccak256("sendMerkleTreeState()")[..4].to_vec();
+    let gas_used = |arbos_version: u16| {
+        let mut context = setup();
+        context.cfg.arbos_version = arbos_version;
+        let mut evm = create_evm(context);
+        let tx = create_call_tx_with_nonce(ARB_SYS, calldata.clone(), 1_000_000, 0);
+        match execute_tx(&mut evm, tx) {
+            ExecutionResult::Revert { gas_used, output } => {
+                assert!(output.is_empty());
+                gas_used
+            }
+            other => panic!("sendMerkleTreeState should revert: {:?}", other),
+        }
+    };
+
+    assert_eq!(gas_used(10), 1_000_000);
+    assert!(gas_used(11) < 100_000);
+}
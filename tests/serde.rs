@@ -0,0 +1,76 @@
+//! Tests for persisting execution configuration and ArbOS state fixtures as JSON.
+
+#![cfg(feature = "serde")]
+
+use arbos_revm::{
+    ArbitrumTransaction,
+    config::ArbitrumConfig,
+    state::{ArbosStateParams, program::ProgramInfo},
+};
+use revm::{
+    context::TxEnv,
+    primitives::{Address, Bytes, TxKind, U256},
+};
+use serde::{Serialize, de::DeserializeOwned};
+
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn test_arbos_state_params_round_trip() {
+    let mut params = ArbosStateParams {
+        upgrade_version: 40,
+        chain_id: U256::from(42161),
+        network_fee_account: Address::repeat_byte(0x11),
+        brotli_compression_level: 11,
+        ..Default::default()
+    };
+    params.stylus_params.ink_price = 20_000;
+    params.stylus_params.max_wasm_size = 64 * 1024;
+
+    assert_eq!(round_trip(&params), params);
+}
+
+#[test]
+fn test_program_info_round_trip() {
+    let info = ProgramInfo {
+        version: 2,
+        init_cost: 100,
+        cached_cost: 10,
+        footprint: 3,
+        asm_estimated_kb: 64,
+        age: 3600,
+        cached: true,
+    };
+
+    assert_eq!(round_trip(&info), info);
+}
+
+#[test]
+fn test_config_round_trip() {
+    let mut config: ArbitrumConfig = ArbitrumConfig::default();
+    config.arbos_version = 50;
+    config.debug_mode = true;
+    config.max_stylus_call_depth = Some(32);
+    config.inner.chain_id = 42161;
+
+    assert_eq!(round_trip(&config), config);
+}
+
+#[test]
+fn test_transaction_round_trip() {
+    let tx = ArbitrumTransaction::new_with_enveloped(
+        TxEnv {
+            caller: Address::repeat_byte(0x01),
+            kind: TxKind::Call(Address::repeat_byte(0x42)),
+            data: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+            gas_limit: 100_000,
+            ..Default::default()
+        },
+        Bytes::from_static(&[0x02, 0xab]),
+    );
+
+    assert_eq!(round_trip(&tx), tx);
+}
@@ -0,0 +1,126 @@
+//! Tests for reverting ArbOS state within a transaction and simulating transactions.
+
+use arbos_revm::state::{ArbState, ArbStateGetter, types::StorageBackedTr};
+use revm::{
+    context::{ContextTr, JournalTr, TxEnv},
+    primitives::{Address, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, TestEvm, create_call_tx, create_evm, deploy_evm_code, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const CONTRACT: Address = address!("0x2222222222222222222222222222222222222222");
+
+fn speed_limit(context: &mut TestContext) -> u64 {
+    context
+        .arb_state(None, true)
+        .l2_pricing()
+        .speed_limit_per_second()
+        .get()
+        .unwrap()
+}
+
+fn set_speed_limit(context: &mut TestContext, limit: u64) {
+    context
+        .arb_state(None, false)
+        .l2_pricing()
+        .speed_limit_per_second()
+        .set(limit)
+        .unwrap();
+}
+
+#[test]
+fn test_revert_to_snapshot_restores_arbos_state() {
+    let mut context = setup_context_with_arbos_state();
+    let before = speed_limit(&mut context);
+
+    let snapshot = context.snapshot();
+    set_speed_limit(&mut context, before + 1);
+    assert_eq!(speed_limit(&mut context), before + 1);
+    context.revert_to(snapshot);
+    assert_eq!(speed_limit(&mut context), before);
+}
+
+#[test]
+fn test_snapshots_nest() {
+    let mut context = setup_context_with_arbos_state();
+    let before = speed_limit(&mut context);
+
+    let outer = context.snapshot();
+    set_speed_limit(&mut context, before + 1);
+    let inner = context.snapshot();
+    set_speed_limit(&mut context, before + 2);
+    context.commit_snapshot(inner);
+    assert_eq!(speed_limit(&mut context), before + 2);
+
+    context.revert_to(outer);
+    assert_eq!(speed_limit(&mut context), before);
+}
+
+/// Deploy a contract storing 1 in slot 0.
+fn setup() -> TestEvm {
+    let mut context = setup_context_with_arbos_state();
+    deploy_evm_code(
+        &mut context,
+        CONTRACT,
+        &[0x60, 0x01, 0x60, 0x00, 0x55, 0x00],
+    );
+    fund_account(&mut context, CALLER, U256::from(1_000_000_000_u64));
+    create_evm(context)
+}
+
+fn slot(evm: &mut TestEvm) -> U256 {
+    evm.0
+        .ctx
+        .journal_mut()
+        .sload(CONTRACT, U256::ZERO)
+        .unwrap()
+        .data
+}
+
+fn nonce(evm: &mut TestEvm) -> u64 {
+    evm.0
+        .ctx
+        .journal_mut()
+        .load_account(CALLER)
+        .unwrap()
+        .data
+        .info
+        .nonce
+}
+
+#[test]
+fn test_simulate_discards_transactions() {
+    let mut evm = setup();
+    let before = speed_limit(&mut evm.0.ctx);
+
+    let results = evm.simulate(|evm| {
+        let first = execute_tx(evm, create_call_tx(CONTRACT, vec![], 100_000));
+        let second = execute_tx(
+            evm,
+            TxEnv {
+                nonce: 1,
+                ..create_call_tx(CONTRACT, vec![], 100_000)
+            },
+        );
+        set_speed_limit(&mut evm.0.ctx, before + 1);
+        assert_eq!(slot(evm), U256::from(1));
+        assert_eq!(nonce(evm), 2);
+        (first, second)
+    });
+    assert!(results.0.is_success());
+    assert!(results.1.is_success());
+
+    assert_eq!(slot(&mut evm), U256::ZERO);
+    assert_eq!(nonce(&mut evm), 0);
+    assert_eq!(speed_limit(&mut evm.0.ctx), before);
+
+    // The transactions can still be executed for real
+    let result = execute_tx(&mut evm, create_call_tx(CONTRACT, vec![], 100_000));
+    assert!(result.is_success());
+    assert_eq!(slot(&mut evm), U256::from(1));
+}
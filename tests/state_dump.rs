@@ -0,0 +1,156 @@
+//! Tests for exporting ArbOS state to a dump and importing it into another database.
+
+use arbos_revm::state::{
+    ArbState, ArbStateGetter, ArbosStateDump, program::ProgramInfo, types::StorageBackedTr,
+};
+use revm::primitives::{Address, B256, U256};
+
+mod test_utils;
+use test_utils::{TestContext, setup_context, setup_context_with_arbos_state};
+
+const TIMESTAMP: u64 = 1_800_000_000;
+const ACTIVATED_AT: u64 = TIMESTAMP - 7200;
+
+fn code_hash() -> B256 {
+    B256::repeat_byte(0xc0)
+}
+
+/// ArbOS state with something in every part of the dump.
+fn populated_context() -> TestContext {
+    let mut context = setup_context_with_arbos_state();
+    context.block.timestamp = U256::from(ACTIVATED_AT);
+    let mut arb_state = context.arb_state(None, false);
+    arb_state.arbos_version().set(40).unwrap();
+
+    arb_state
+        .chain_owners()
+        .add(Address::repeat_byte(0x01))
+        .unwrap();
+    arb_state
+        .native_token_owners()
+        .add(Address::repeat_byte(0x02))
+        .unwrap();
+    arb_state.l2_pricing().gas_backlog().set(1_000_000).unwrap();
+    arb_state
+        .l1_pricing()
+        .price_per_unit()
+        .set(U256::from(50_000))
+        .unwrap();
+    arb_state
+        .l1_pricing()
+        .batch_poster_table()
+        .add(Address::repeat_byte(0x03), Address::repeat_byte(0x04))
+        .unwrap();
+
+    let mut programs = arb_state.programs();
+    programs
+        .cache_managers()
+        .add(Address::repeat_byte(0x05))
+        .unwrap();
+    programs
+        .module_hash(&code_hash())
+        .set(B256::repeat_byte(0x0d))
+        .unwrap();
+    let info = ProgramInfo {
+        version: 1,
+        init_cost: 100,
+        cached_cost: 10,
+        footprint: 2,
+        asm_estimated_kb: 40,
+        age: 0,
+        cached: true,
+    };
+    programs.save_program_info(&code_hash(), &info).unwrap();
+    drop(programs);
+
+    let mut retryables = arb_state.retryable_state();
+    for (byte, timeout) in [(0xa1, TIMESTAMP + 100), (0xa2, TIMESTAMP + 200)] {
+        retryables
+            .create_retryable(
+                B256::repeat_byte(byte),
+                timeout,
+                Address::repeat_byte(0x06),
+                Address::repeat_byte(0x07),
+                U256::from(byte),
+                Address::repeat_byte(0x08),
+                &[byte; 40],
+            )
+            .unwrap();
+    }
+    retryables
+        .keepalive(B256::repeat_byte(0xa1), ACTIVATED_AT, u64::MAX)
+        .unwrap();
+    drop(retryables);
+
+    for byte in [0x10, 0x11, 0x12] {
+        arb_state
+            .address_table()
+            .register(Address::repeat_byte(byte))
+            .unwrap();
+    }
+    drop(arb_state);
+
+    context.block.timestamp = U256::from(TIMESTAMP);
+    context
+}
+
+fn export(context: &mut TestContext) -> ArbosStateDump {
+    let mut arb_state = context.arb_state(None, false);
+    let mut dump = arb_state.export().unwrap();
+    dump.programs = arb_state
+        .export_programs(&[code_hash(), B256::repeat_byte(0xff)])
+        .unwrap();
+    dump
+}
+
+#[test]
+fn test_export_reads_every_part_of_the_state() {
+    let dump = export(&mut populated_context());
+
+    assert_eq!(dump.arbos_version, 40);
+    assert_eq!(dump.chain_owners, vec![Address::repeat_byte(0x01)]);
+    assert_eq!(dump.native_token_owners, vec![Address::repeat_byte(0x02)]);
+    assert_eq!(dump.l2_pricing.gas_backlog, 1_000_000);
+    assert_eq!(dump.l1_pricing.snapshot.price_per_unit, U256::from(50_000));
+    assert_eq!(dump.l1_pricing.batch_posters.len(), 1);
+    assert_eq!(
+        dump.l1_pricing.batch_posters[0].pay_recipient,
+        Address::repeat_byte(0x04)
+    );
+    assert_eq!(dump.cache_managers, vec![Address::repeat_byte(0x05)]);
+    assert_eq!(
+        dump.address_table,
+        [0x10, 0x11, 0x12].map(Address::repeat_byte).to_vec()
+    );
+
+    // Only the activated program is exported, aged since its activation
+    assert_eq!(dump.programs.len(), 1);
+    assert_eq!(dump.programs[0].module_hash, B256::repeat_byte(0x0d));
+    assert_eq!(dump.programs[0].info.age, 7200);
+
+    // The kept-alive ticket is queued twice but exported once
+    let ids: Vec<_> = dump.retryables.iter().map(|ticket| ticket.id).collect();
+    assert_eq!(ids, vec![B256::repeat_byte(0xa1), B256::repeat_byte(0xa2)]);
+    assert_eq!(dump.retryables[0].timeout_windows_left, 1);
+    assert_eq!(dump.retryables[1].calldata, vec![0xa2; 40]);
+}
+
+#[test]
+fn test_import_round_trips() {
+    let dump = export(&mut populated_context());
+
+    let mut context = setup_context();
+    context.block.timestamp = U256::from(TIMESTAMP);
+    context.arb_state(None, false).import(&dump).unwrap();
+
+    assert_eq!(export(&mut context), dump);
+    assert_eq!(
+        context
+            .arb_state(None, false)
+            .retryable_state()
+            .timeout_queue()
+            .size()
+            .unwrap(),
+        3
+    );
+}
@@ -4,14 +4,14 @@
 //! Storage and transient storage tests for Stylus programs.
 
 use revm::{
-    context::result::ExecutionResult,
+    context::{ContextTr, JournalTr, result::ExecutionResult},
     primitives::{Address, U256},
 };
 
 mod test_utils;
 use test_utils::{
-    create_call_tx, create_call_tx_with_nonce, create_evm, deploy_wat_program, execute_tx,
-    fund_account, setup_context_with_arbos_state,
+    create_call_tx, create_call_tx_with_nonce, create_evm, deploy_evm_code, deploy_wat_program,
+    execute_tx, fund_account, setup_context_with_arbos_state,
 };
 
 // ============================================================================
@@ -184,6 +184,64 @@ fn test_e2e_storage_overwrite() {
     }
 }
 
+/// EVM runtime code that loads the slot named by its calldata, stores it back incremented and
+/// returns the value it loaded.
+const INCREMENT_SLOT_CODE: &[u8] = &[
+    0x60, 0x00, 0x35, 0x80, 0x54, // load the slot named by the calldata
+    0x80, 0x60, 0x01, 0x01, 0x82, 0x55, // store it incremented
+    0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3, // return the loaded value
+];
+
+#[test]
+fn test_e2e_cached_storage_write_is_flushed_before_a_call() {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/storage-call.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+    let incrementer = Address::repeat_byte(0xe1);
+    deploy_evm_code(&mut context, incrementer, INCREMENT_SLOT_CODE);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let key = U256::from(7);
+    let value = U256::from(42);
+    let mut args = key.to_be_bytes::<32>().to_vec();
+    args.extend_from_slice(&value.to_be_bytes::<32>());
+    args.extend_from_slice(incrementer.as_slice());
+
+    let tx = create_call_tx(program_address, args, 10_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            let data = output.data();
+            assert_eq!(data.len(), 64, "output should be 64 bytes");
+            assert_eq!(
+                U256::from_be_slice(&data[..32]),
+                value,
+                "delegate call should see the program's cached write"
+            );
+            assert_eq!(
+                U256::from_be_slice(&data[32..]),
+                value + U256::from(1),
+                "program should see the delegate call's write"
+            );
+        }
+        other => panic!("execution failed: {:?}", other),
+    }
+
+    // Finishing the program doesn't write its earlier value back over the call's
+    let slot = evm
+        .0
+        .ctx
+        .journal_mut()
+        .sload(program_address, key)
+        .unwrap()
+        .data;
+    assert_eq!(slot, value + U256::from(1));
+}
+
 // ============================================================================
 // Transient Storage Tests (EIP-1153)
 // ============================================================================
@@ -310,3 +368,207 @@ fn test_e2e_transient_storage_cleared_between_txs() {
         }
     }
 }
+
+// ============================================================================
+// Transient Storage Across EVM Frames
+// ============================================================================
+
+const TRANSIENT_KEY: [u8; 32] = [0xABu8; 32];
+
+fn transient_write_args(value: [u8; 32]) -> Vec<u8> {
+    let mut args = vec![0x01u8];
+    args.extend_from_slice(&TRANSIENT_KEY);
+    args.extend_from_slice(&value);
+    args
+}
+
+fn transient_read_args() -> Vec<u8> {
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&TRANSIENT_KEY);
+    args
+}
+
+/// EVM code calling `target` with `args`, copying up to `ret_len` bytes of return data to memory
+/// at 0 and leaving the call's success flag on the stack.
+fn evm_call(target: Address, args: &[u8], ret_len: u8) -> Vec<u8> {
+    let mut code = Vec::new();
+    // MSTORE(0x80 + 32 * i, word i of args)
+    for (i, chunk) in args.chunks(32).enumerate() {
+        let mut word = [0u8; 32];
+        word[..chunk.len()].copy_from_slice(chunk);
+        code.push(0x7f);
+        code.extend_from_slice(&word);
+        code.extend_from_slice(&[0x60, 0x80 + 32 * i as u8, 0x52]);
+    }
+    // CALL(gas, target, 0, 0x80, len(args), 0, ret_len)
+    code.extend_from_slice(&[
+        0x60,
+        ret_len,
+        0x60,
+        0x00,
+        0x60,
+        args.len() as u8,
+        0x60,
+        0x80,
+        0x60,
+        0x00,
+        0x73,
+    ]);
+    code.extend_from_slice(target.as_slice());
+    code.extend_from_slice(&[0x5a, 0xf1]);
+    code
+}
+
+/// Stylus -> EVM -> Stylus: a value a Stylus frame writes is visible to a later frame of the same
+/// program in the same transaction, but not to the EVM contract in between.
+#[test]
+fn test_e2e_transient_storage_shared_across_evm_frames() {
+    let mut context = setup_context_with_arbos_state();
+
+    let program_address = deploy_wat_program(
+        &mut context,
+        include_bytes!("../test-data/transient-storage.wat"),
+    );
+    let caller_address = deploy_wat_program(&mut context, include_bytes!("../test-data/call.wat"));
+
+    let value = [0xCDu8; 32];
+    let mut relay = evm_call(program_address, &transient_write_args(value), 0);
+    // POP
+    relay.push(0x50);
+    relay.extend_from_slice(&evm_call(program_address, &transient_read_args(), 32));
+    // POP, MSTORE(0x20, TLOAD(key)), RETURN(0, 0x40)
+    relay.extend_from_slice(&[0x50, 0x7f]);
+    relay.extend_from_slice(&TRANSIENT_KEY);
+    relay.extend_from_slice(&[0x5c, 0x60, 0x20, 0x52, 0x60, 0x40, 0x60, 0x00, 0xf3]);
+    let relay_address = Address::repeat_byte(0xe0);
+    deploy_evm_code(&mut context, relay_address, &relay);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    // call_contract selector (0x00) + relay address, no calldata
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(relay_address.as_slice());
+
+    let tx = create_call_tx(caller_address, args, 50_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            let data = output.data();
+            assert_eq!(data.len(), 64, "output should be 64 bytes");
+            assert_eq!(
+                &data[..32],
+                &value,
+                "later frame of the program should see its transient write"
+            );
+            assert!(
+                data[32..].iter().all(|&b| b == 0),
+                "transient storage should be scoped to the writing account"
+            );
+        }
+        ExecutionResult::Revert { output, .. } => {
+            panic!("execution reverted: {:?}", output);
+        }
+        ExecutionResult::Halt { reason, .. } => {
+            panic!("execution halted: {:?}", reason);
+        }
+    }
+}
+
+/// A transient write made by a Stylus frame under a reverted EVM frame is rolled back.
+#[test]
+fn test_e2e_transient_storage_reverted_with_frame() {
+    let mut context = setup_context_with_arbos_state();
+
+    let program_address = deploy_wat_program(
+        &mut context,
+        include_bytes!("../test-data/transient-storage.wat"),
+    );
+
+    let kept = [0x11u8; 32];
+    let reverted = [0x22u8; 32];
+
+    let mut reverter = evm_call(program_address, &transient_write_args(reverted), 0);
+    // POP, REVERT(0, 0)
+    reverter.extend_from_slice(&[0x50, 0x60, 0x00, 0x60, 0x00, 0xfd]);
+    let reverter_address = Address::repeat_byte(0xe1);
+    deploy_evm_code(&mut context, reverter_address, &reverter);
+
+    let mut relay = evm_call(program_address, &transient_write_args(kept), 0);
+    relay.push(0x50);
+    relay.extend_from_slice(&evm_call(reverter_address, &[], 0));
+    relay.push(0x50);
+    relay.extend_from_slice(&evm_call(program_address, &transient_read_args(), 32));
+    // POP, RETURN(0, 0x20)
+    relay.extend_from_slice(&[0x50, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+    let relay_address = Address::repeat_byte(0xe0);
+    deploy_evm_code(&mut context, relay_address, &relay);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    let tx = create_call_tx(relay_address, Vec::new(), 50_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            assert_eq!(
+                output.data().as_ref(),
+                &kept,
+                "write under the reverted frame should be rolled back"
+            );
+        }
+        ExecutionResult::Revert { output, .. } => {
+            panic!("execution reverted: {:?}", output);
+        }
+        ExecutionResult::Halt { reason, .. } => {
+            panic!("execution halted: {:?}", reason);
+        }
+    }
+}
+
+/// A Stylus static call stays static through an EVM frame, so the Stylus program it reaches
+/// cannot write transient storage.
+#[test]
+fn test_e2e_static_call_through_evm_prevents_transient_write() {
+    let mut context = setup_context_with_arbos_state();
+
+    let program_address = deploy_wat_program(
+        &mut context,
+        include_bytes!("../test-data/transient-storage.wat"),
+    );
+    let caller_address = deploy_wat_program(&mut context, include_bytes!("../test-data/call.wat"));
+
+    let mut relay = evm_call(program_address, &transient_write_args([0xCDu8; 32]), 0);
+    // MSTORE(0, success), RETURN(0, 0x20)
+    relay.extend_from_slice(&[0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+    let relay_address = Address::repeat_byte(0xe0);
+    deploy_evm_code(&mut context, relay_address, &relay);
+
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(10_000_000_000_u64));
+
+    let mut evm = create_evm(context);
+
+    // static_call_contract selector (0x01) + relay address, no calldata
+    let mut args = vec![0x01u8];
+    args.extend_from_slice(relay_address.as_slice());
+
+    let tx = create_call_tx(caller_address, args, 50_000_000);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { output, .. } => {
+            assert_eq!(output.data().len(), 32, "output should be 32 bytes");
+            assert!(
+                output.data().iter().all(|&b| b == 0),
+                "transient write under a static call should fail"
+            );
+        }
+        ExecutionResult::Revert { output, .. } => {
+            panic!("execution reverted: {:?}", output);
+        }
+        ExecutionResult::Halt { reason, .. } => {
+            panic!("execution halted: {:?}", reason);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Tests comparing the gas and refunds of Stylus storage access with the same access from EVM
+//! bytecode.
+
+use arbos_revm::{
+    ArbitrumEvm,
+    inspector::{PrecompileInspector, StylusHostio, StylusInspector},
+    precompiles::ArbitrumPrecompileProvider,
+};
+use arbutil::evm::api::EvmApiMethod;
+use revm::{
+    InspectEvm, Inspector,
+    bytecode::opcode::{SLOAD, SSTORE},
+    context::result::ExecutionResult,
+    handler::instructions::EthInstructions,
+    interpreter::{Interpreter, interpreter::EthInterpreter, interpreter_types::Jumps},
+    primitives::{Address, U256},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, create_call_tx_with_nonce, create_evm, deploy_evm_code, deploy_wat_program,
+    execute_tx, fund_account, setup_context_with_arbos_state,
+};
+
+/// Reads the slot given by the calldata at offset 1, then writes the word at offset 33 to it,
+/// taking the same calldata as `storage.wat` does for writes.
+const EVM_WRITER: &[u8] = &[
+    0x60, 0x01, 0x35, // PUSH1 1 CALLDATALOAD
+    0x80, 0x54, 0x50, // DUP1 SLOAD POP
+    0x60, 0x21, 0x35, // PUSH1 33 CALLDATALOAD
+    0x90, 0x55, // SWAP1 SSTORE
+    0x00, // STOP
+];
+
+const EVM_WRITER_ADDRESS: Address = Address::repeat_byte(0xE0);
+
+/// Storage access costs seen while inspecting: those of the SLOAD and SSTORE opcodes run by
+/// EVM frames and those of the storage hostios run by Stylus programs, in order.
+#[derive(Default)]
+struct StorageGasRecorder {
+    step: Option<(u8, u64)>,
+    loads: Vec<u64>,
+    stores: Vec<u64>,
+}
+
+impl Inspector<TestContext> for StorageGasRecorder {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut TestContext) {
+        self.step = Some((interp.bytecode.opcode(), interp.gas.remaining()));
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut TestContext) {
+        let Some((opcode, gas_before)) = self.step.take() else {
+            return;
+        };
+        let cost = gas_before - interp.gas.remaining();
+        match opcode {
+            SLOAD => self.loads.push(cost),
+            SSTORE => self.stores.push(cost),
+            _ => {}
+        }
+    }
+}
+
+impl PrecompileInspector<TestContext> for StorageGasRecorder {}
+
+impl StylusInspector<TestContext> for StorageGasRecorder {
+    fn hostio(&mut self, _context: &mut TestContext, hostio: &StylusHostio<'_>) {
+        match hostio.method {
+            EvmApiMethod::GetBytes32 => self.loads.push(hostio.gas),
+            EvmApiMethod::SetTrieSlots => self.stores.push(hostio.gas),
+            _ => {}
+        }
+    }
+}
+
+/// Storage costs and refund of writing `value` to a slot of the contract deployed by `deploy`,
+/// after a first transaction wrote `initial` to it.
+fn write_costs(
+    deploy: impl FnOnce(&mut TestContext) -> Address,
+    initial: U256,
+    value: U256,
+) -> (Vec<u64>, Vec<u64>, u64) {
+    let mut context = setup_context_with_arbos_state();
+    let contract = deploy(&mut context);
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(1_000_000_000_u64));
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        StorageGasRecorder::default(),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::<TestContext>::default(),
+    );
+
+    let calldata = |value: U256| {
+        let mut args = vec![0x01u8];
+        args.extend_from_slice(&U256::from(7).to_be_bytes::<32>());
+        args.extend_from_slice(&value.to_be_bytes::<32>());
+        args
+    };
+    let mut nonce = 0;
+    if !initial.is_zero() {
+        let tx = create_call_tx_with_nonce(contract, calldata(initial), 10_000_000, nonce);
+        let result = evm.inspect_one_tx(tx.into()).unwrap();
+        assert!(result.is_success(), "initial write failed: {result:?}");
+        nonce += 1;
+    }
+
+    evm.0.inspector = StorageGasRecorder::default();
+    let tx = create_call_tx_with_nonce(contract, calldata(value), 10_000_000, nonce);
+    let ExecutionResult::Success { gas_refunded, .. } = evm.inspect_one_tx(tx.into()).unwrap()
+    else {
+        panic!("write failed");
+    };
+
+    let recorder = evm.into_inspector();
+    (recorder.loads, recorder.stores, gas_refunded)
+}
+
+fn assert_same_costs(initial: u64, value: u64) {
+    let (initial, value) = (U256::from(initial), U256::from(value));
+    let stylus = write_costs(
+        |context| deploy_wat_program(context, include_bytes!("../test-data/storage.wat")),
+        initial,
+        value,
+    );
+    let evm = write_costs(
+        |context| {
+            deploy_evm_code(context, EVM_WRITER_ADDRESS, EVM_WRITER);
+            EVM_WRITER_ADDRESS
+        },
+        initial,
+        value,
+    );
+
+    assert_eq!(stylus.0.len(), 1, "expected a single storage load");
+    assert_eq!(stylus.1.len(), 1, "expected a single storage flush");
+    assert_eq!(
+        stylus, evm,
+        "(loads, stores, refund) of {initial} -> {value}"
+    );
+}
+
+#[test]
+fn test_storage_set_costs_match_evm() {
+    // Cold load, then setting a zero slot
+    assert_same_costs(0, 5);
+}
+
+#[test]
+fn test_storage_reset_costs_match_evm() {
+    assert_same_costs(5, 6);
+}
+
+#[test]
+fn test_storage_clear_costs_and_refund_match_evm() {
+    assert_same_costs(5, 0);
+}
+
+/// Stores the calldata size in slot 0, so that a call without calldata clears it.
+const EVM_CLEARER: &[u8] = &[0x36, 0x60, 0x00, 0x55, 0x00];
+
+const EVM_CLEARER_ADDRESS: Address = Address::repeat_byte(0xE1);
+const EVM_CALLER_ADDRESS: Address = Address::repeat_byte(0xE2);
+
+/// Calls [`EVM_CLEARER_ADDRESS`] without calldata.
+fn evm_caller() -> Vec<u8> {
+    let mut code = vec![
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73,
+    ];
+    code.extend_from_slice(EVM_CLEARER_ADDRESS.as_slice());
+    code.extend_from_slice(&[0x5a, 0xf1, 0x00]); // GAS CALL STOP
+    code
+}
+
+/// Refund of a transaction calling the contract deployed by `deploy` with the calldata it
+/// returns, which clears a slot of [`EVM_CLEARER`] through it.
+fn refund_of_clearing_through(deploy: impl FnOnce(&mut TestContext) -> (Address, Vec<u8>)) -> u64 {
+    let mut context = setup_context_with_arbos_state();
+    deploy_evm_code(&mut context, EVM_CLEARER_ADDRESS, EVM_CLEARER);
+    let (contract, calldata) = deploy(&mut context);
+    let caller = Address::repeat_byte(0x01);
+    fund_account(&mut context, caller, U256::from(1_000_000_000_u64));
+    let mut evm = create_evm(context);
+
+    let tx = create_call_tx_with_nonce(EVM_CLEARER_ADDRESS, vec![0x01], 1_000_000, 0);
+    assert!(
+        execute_tx(&mut evm, tx).is_success(),
+        "initial write failed"
+    );
+
+    let tx = create_call_tx_with_nonce(contract, calldata, 10_000_000, 1);
+    match execute_tx(&mut evm, tx) {
+        ExecutionResult::Success { gas_refunded, .. } => gas_refunded,
+        other => panic!("call failed: {other:?}"),
+    }
+}
+
+#[test]
+fn test_refund_of_evm_callee_reaches_stylus_caller() {
+    let stylus = refund_of_clearing_through(|context| {
+        let program = deploy_wat_program(context, include_bytes!("../test-data/call.wat"));
+        let mut args = vec![0x00];
+        args.extend_from_slice(EVM_CLEARER_ADDRESS.as_slice());
+        (program, args)
+    });
+    let evm = refund_of_clearing_through(|context| {
+        deploy_evm_code(context, EVM_CALLER_ADDRESS, &evm_caller());
+        (EVM_CALLER_ADDRESS, vec![])
+    });
+
+    assert!(stylus > 0, "clearing the slot should be refunded");
+    assert_eq!(stylus, evm);
+}
@@ -0,0 +1,177 @@
+//! Tests for running Stylus programs without a transaction.
+
+use arbos_revm::{
+    constants::WASM_PAGE_SIZE,
+    state::{ArbState, ArbStateGetter},
+    stylus_executor::execute_stylus_call,
+};
+use revm::{
+    context::{ContextTr, JournalTr},
+    interpreter::{InstructionResult, InterpreterResult, gas::memory_gas},
+    primitives::{Address, Bytes, U256},
+};
+
+mod test_utils;
+use test_utils::{deploy_evm_code, deploy_wat_program, setup_context_with_arbos_state};
+
+#[test]
+fn test_execute_stylus_call_returns_output() {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/write-args.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let calldata = Bytes::from_static(b"hello from a standalone call");
+    let (result, _) = execute_stylus_call(context, program_address, calldata.clone(), 1_000_000);
+    let result = result.expect("standalone call failed");
+
+    assert!(result.is_ok(), "program should succeed: {:?}", result);
+    assert_eq!(result.output, calldata);
+    assert!(
+        result.gas.spent() > 0,
+        "running the program should cost gas"
+    );
+    assert!(result.gas.spent() < 1_000_000);
+}
+
+#[test]
+fn test_execute_stylus_call_skips_transaction_processing() {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/write-args.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let (result, mut context) =
+        execute_stylus_call(context, program_address, Bytes::new(), 1_000_000);
+    assert!(result.expect("standalone call failed").is_ok());
+
+    // The default transaction's caller is never charged or bumped.
+    let caller = context
+        .journal_mut()
+        .load_account(Address::ZERO)
+        .unwrap()
+        .data
+        .info
+        .clone();
+    assert_eq!(caller.balance, U256::ZERO);
+    assert_eq!(caller.nonce, 0);
+}
+
+#[test]
+fn test_execute_stylus_call_can_be_repeated() {
+    let mut context = setup_context_with_arbos_state();
+
+    let wat = include_bytes!("../test-data/write-args.wat");
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let (first, context) = execute_stylus_call(
+        context,
+        program_address,
+        Bytes::from_static(b"one"),
+        1_000_000,
+    );
+    let (second, _) = execute_stylus_call(
+        context,
+        program_address,
+        Bytes::from_static(b"two"),
+        1_000_000,
+    );
+
+    assert_eq!(first.unwrap().output, Bytes::from_static(b"one"));
+    assert_eq!(second.unwrap().output, Bytes::from_static(b"two"));
+}
+
+#[test]
+fn test_execute_stylus_call_rejects_evm_code() {
+    let mut context = setup_context_with_arbos_state();
+
+    // PUSH1 0, PUSH1 0, RETURN
+    let address = Address::repeat_byte(0x42);
+    deploy_evm_code(&mut context, address, &[0x60, 0x00, 0x60, 0x00, 0xf3]);
+
+    let (result, _) = execute_stylus_call(context, address, Bytes::new(), 1_000_000);
+    let result = result.expect("standalone call failed");
+
+    assert!(result.is_revert());
+    assert_eq!(
+        result.output,
+        Bytes::from("specified bytecode is not a Stylus program")
+    );
+}
+
+/// Standalone call to the program in `wat` with `calldata_len` bytes of calldata, with Stylus
+/// buffers limited or not.
+fn call_with_calldata(wat: &[u8], calldata_len: usize, limit_buffers: bool) -> InterpreterResult {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.limit_stylus_buffers = limit_buffers;
+    let program_address = deploy_wat_program(&mut context, wat);
+
+    let calldata = Bytes::from(vec![0u8; calldata_len]);
+    let (result, _) = execute_stylus_call(context, program_address, calldata, 1_000_000);
+    result.expect("standalone call failed")
+}
+
+/// Gas spent by a standalone call to the program in `wat` with `calldata_len` bytes of calldata.
+fn gas_spent_with_calldata(wat: &[u8], calldata_len: usize) -> u64 {
+    let result = call_with_calldata(wat, calldata_len, false);
+    assert!(result.is_ok(), "program should succeed: {:?}", result);
+    result.gas.spent()
+}
+
+/// Length of calldata one byte beyond what a program can address.
+fn beyond_addressable_memory() -> usize {
+    let page_limit = setup_context_with_arbos_state()
+        .arb_state(None, true)
+        .programs()
+        .stylus_params()
+        .get()
+        .unwrap()
+        .page_limit;
+    usize::from(page_limit) * WASM_PAGE_SIZE + 1
+}
+
+#[test]
+fn test_execute_stylus_call_accepts_calldata_beyond_addressable_memory() {
+    let beyond = beyond_addressable_memory();
+
+    // A program that never reads its calldata pays nothing for it, however large
+    let wat = include_bytes!("../test-data/add.wat");
+    assert_eq!(
+        gas_spent_with_calldata(wat, beyond),
+        gas_spent_with_calldata(wat, 0)
+    );
+}
+
+#[test]
+fn test_limited_stylus_call_rejects_calldata_beyond_addressable_memory() {
+    let wat = include_bytes!("../test-data/add.wat");
+    let result = call_with_calldata(wat, beyond_addressable_memory(), true);
+
+    assert_eq!(result.result, InstructionResult::OutOfGas);
+    assert_eq!(result.gas.spent(), 1_000_000);
+}
+
+#[test]
+fn test_limited_stylus_call_charges_calldata_memory() {
+    let wat = include_bytes!("../test-data/add.wat");
+    let limited = call_with_calldata(wat, 1024, true);
+
+    assert!(limited.is_ok(), "program should succeed: {:?}", limited);
+    assert_eq!(
+        limited.gas.spent(),
+        gas_spent_with_calldata(wat, 1024) + memory_gas(1024)
+    );
+}
+
+#[test]
+fn test_execute_stylus_call_charges_for_reading_calldata() {
+    let wat = include_bytes!("../test-data/write-args.wat");
+
+    let small = gas_spent_with_calldata(wat, 32);
+    let large = gas_spent_with_calldata(wat, 32 * 1024);
+
+    assert!(
+        large > small,
+        "copying more calldata should cost more: {large} <= {small}"
+    );
+}
@@ -0,0 +1,110 @@
+//! Tests for telling the failures of Stylus programs apart from EVM halts.
+
+use arbos_revm::ArbitrumHaltReason;
+use revm::{
+    context::result::{ExecutionResult, HaltReason, OutOfGasError},
+    primitives::{Address, U256},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, create_call_tx, create_evm, deploy_evm_code, deploy_wat_program, execute_tx,
+    fund_account, setup_context_with_arbos_state,
+};
+
+fn setup() -> TestContext {
+    let mut context = setup_context_with_arbos_state();
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    context
+}
+
+/// Call `program` in `context`, returning the outcome and the Stylus failure recorded for it.
+fn call(context: TestContext, program: Address) -> (ExecutionResult, Option<ArbitrumHaltReason>) {
+    let mut evm = create_evm(context);
+    let result = execute_tx(&mut evm, create_call_tx(program, vec![], 1_000_000));
+    (result, evm.execution_details().stylus_failure.clone())
+}
+
+fn halt_reason(result: &ExecutionResult) -> &HaltReason {
+    match result {
+        ExecutionResult::Halt { reason, .. } => reason,
+        other => panic!("expected a halt, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_out_of_ink_is_told_apart_from_out_of_gas() {
+    let mut context = setup();
+    let program = deploy_wat_program(
+        &mut context,
+        include_bytes!("../test-data/infinite-loop.wat"),
+    );
+
+    let (result, failure) = call(context, program);
+    assert_eq!(
+        *halt_reason(&result),
+        HaltReason::OutOfGas(OutOfGasError::Basic)
+    );
+    assert_eq!(failure, Some(ArbitrumHaltReason::OutOfInk));
+    assert_eq!(
+        failure.unwrap().halt_reason(),
+        Some(HaltReason::OutOfGas(OutOfGasError::Basic))
+    );
+}
+
+#[test]
+fn test_wasm_stack_overflow() {
+    let mut context = setup();
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/recursion.wat"));
+
+    let (result, failure) = call(context, program);
+    assert_eq!(*halt_reason(&result), HaltReason::StackOverflow);
+    assert_eq!(failure, Some(ArbitrumHaltReason::WasmStackOverflow));
+}
+
+#[test]
+fn test_program_not_activated() {
+    let mut context = setup();
+    context.cfg.disable_auto_activate = true;
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/add.wat"));
+
+    let (result, failure) = call(context, program);
+    assert!(!result.is_success());
+    assert_eq!(failure, Some(ArbitrumHaltReason::ProgramNotActivated));
+}
+
+#[test]
+fn test_activation_failure() {
+    // Stylus programs must export a memory and an entrypoint
+    let mut context = setup();
+    let program = deploy_wat_program(&mut context, b"(module)");
+
+    let (result, failure) = call(context, program);
+    assert!(matches!(result, ExecutionResult::Revert { .. }));
+    assert_eq!(failure, Some(ArbitrumHaltReason::ActivationFailed));
+    assert_eq!(failure.unwrap().halt_reason(), None);
+}
+
+#[test]
+fn test_trap_and_evm_halts_record_no_stylus_failure() {
+    let mut context = setup();
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/trap.wat"));
+    let (result, failure) = call(context, program);
+    assert!(matches!(result, ExecutionResult::Revert { .. }));
+    assert_eq!(failure, None);
+
+    // An EVM contract looping until it runs out of gas: JUMPDEST; PUSH1 0; JUMP
+    let mut context = setup();
+    let contract = Address::repeat_byte(0x22);
+    deploy_evm_code(&mut context, contract, &[0x5b, 0x60, 0x00, 0x56]);
+    let (result, failure) = call(context, contract);
+    assert_eq!(
+        *halt_reason(&result),
+        HaltReason::OutOfGas(OutOfGasError::Basic)
+    );
+    assert_eq!(failure, None);
+}
@@ -0,0 +1,179 @@
+//! Tests for tracing the host calls of Stylus programs.
+
+use arbos_revm::{
+    ArbitrumEvm,
+    inspector::{PrecompileInspector, StylusHostio, StylusInspector},
+    precompiles::ArbitrumPrecompileProvider,
+    state::{ArbState, ArbStateGetter},
+};
+use arbutil::evm::api::EvmApiMethod;
+use revm::{
+    InspectEvm, Inspector,
+    handler::instructions::EthInstructions,
+    interpreter::{CreateInputs, CreateOutcome},
+    primitives::{Address, U256},
+};
+
+mod test_utils;
+use test_utils::{
+    TestContext, create_call_tx, deploy_wat_program, fund_account, setup_context_with_arbos_state,
+};
+
+/// A host call as seen by [`HostioRecorder`].
+struct RecordedHostio {
+    address: Address,
+    method: EvmApiMethod,
+    input: Vec<u8>,
+    gas: u64,
+    ink: u64,
+}
+
+/// Collects every host call reported while inspecting.
+#[derive(Default)]
+struct HostioRecorder(Vec<RecordedHostio>);
+
+impl Inspector<TestContext> for HostioRecorder {}
+
+impl PrecompileInspector<TestContext> for HostioRecorder {}
+
+impl StylusInspector<TestContext> for HostioRecorder {
+    fn hostio(&mut self, _context: &mut TestContext, hostio: &StylusHostio<'_>) {
+        self.0.push(RecordedHostio {
+            address: hostio.address,
+            method: hostio.method,
+            input: hostio.input.to_vec(),
+            gas: hostio.gas,
+            ink: hostio.ink,
+        });
+    }
+}
+
+/// Call the program built from `wat` with `args` under a [`HostioRecorder`], returning the
+/// program's address, the recorded host calls and the Stylus ink price.
+fn trace(wat: &[u8], args: Vec<u8>) -> (Address, Vec<RecordedHostio>, u64) {
+    let mut context = setup_context_with_arbos_state();
+    let program = deploy_wat_program(&mut context, wat);
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+    let ink_price = context
+        .arb_state(None, true)
+        .programs()
+        .stylus_params()
+        .get()
+        .unwrap()
+        .ink_price;
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        HostioRecorder::default(),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::<TestContext>::default(),
+    );
+    let result = evm
+        .inspect_one_tx(create_call_tx(program, args, 10_000_000).into())
+        .expect("transaction execution failed");
+    assert!(result.is_success());
+
+    (program, evm.into_inspector().0, u64::from(ink_price))
+}
+
+#[test]
+fn test_storage_hostios_are_reported() {
+    // Selector 0x01 = write slot 1 with 0x2a
+    let mut args = vec![0x01u8];
+    args.extend_from_slice(&U256::from(1).to_be_bytes::<32>());
+    args.extend_from_slice(&U256::from(0x2a).to_be_bytes::<32>());
+    let (program, hostios, ink_price) = trace(include_bytes!("../test-data/storage.wat"), args);
+
+    let load = hostios
+        .iter()
+        .find(|hostio| matches!(hostio.method, EvmApiMethod::GetBytes32))
+        .expect("storage load not reported");
+    assert_eq!(load.input, U256::from(1).to_be_bytes::<32>());
+    assert!(load.gas > 0);
+
+    let flush = hostios
+        .iter()
+        .find(|hostio| matches!(hostio.method, EvmApiMethod::SetTrieSlots))
+        .expect("storage flush not reported");
+    assert!(flush.gas > 0);
+
+    for hostio in &hostios {
+        assert_eq!(hostio.address, program);
+        assert_eq!(hostio.ink, hostio.gas * ink_price);
+    }
+}
+
+#[test]
+fn test_emit_log_is_reported() {
+    // One topic, then the log data
+    let mut args = vec![0x01u8];
+    args.extend_from_slice(&[0xaa; 32]);
+    args.extend_from_slice(b"data");
+    let (program, hostios, _) = trace(include_bytes!("../test-data/log.wat"), args);
+
+    // Traces the runtime captures of its hostios aside
+    let hostios: Vec<_> = hostios
+        .into_iter()
+        .filter(|hostio| !matches!(hostio.method, EvmApiMethod::CaptureHostIO))
+        .collect();
+    let [log] = hostios.as_slice() else {
+        panic!("expected a single host call, got {}", hostios.len());
+    };
+    assert!(matches!(log.method, EvmApiMethod::EmitLog));
+    assert_eq!(log.address, program);
+    let mut expected = 1u32.to_be_bytes().to_vec();
+    expected.extend_from_slice(&[0xaa; 32]);
+    expected.extend_from_slice(b"data");
+    assert_eq!(log.input, expected);
+}
+
+/// Collects the addresses of the contracts created while inspecting.
+#[derive(Default)]
+struct CreateRecorder(Vec<Address>);
+
+impl Inspector<TestContext> for CreateRecorder {
+    fn create_end(
+        &mut self,
+        _context: &mut TestContext,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        if outcome.result.is_ok() {
+            self.0.extend(outcome.address);
+        }
+    }
+}
+
+impl PrecompileInspector<TestContext> for CreateRecorder {}
+
+impl StylusInspector<TestContext> for CreateRecorder {}
+
+#[test]
+fn test_stylus_create_is_reported() {
+    let mut context = setup_context_with_arbos_state();
+    let program = deploy_wat_program(&mut context, include_bytes!("../test-data/create.wat"));
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+
+    let mut evm = ArbitrumEvm::new_with_inspector(
+        context,
+        CreateRecorder::default(),
+        EthInstructions::default(),
+        ArbitrumPrecompileProvider::<TestContext>::default(),
+    );
+    // Selector 0x02 = CREATE with minimal contract
+    let result = evm
+        .inspect_one_tx(create_call_tx(program, vec![0x02], 10_000_000).into())
+        .expect("transaction execution failed");
+
+    let created = program.create(0);
+    assert_eq!(result.output().unwrap().as_ref(), created.as_slice());
+    assert_eq!(evm.into_inspector().0, vec![created]);
+}
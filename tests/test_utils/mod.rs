@@ -9,6 +9,7 @@ use arbos_revm::{
     ArbitrumContext, ArbitrumEvm, ArbitrumTransaction,
     config::ArbitrumConfig,
     constants::STYLUS_DISCRIMINANT,
+    instructions::arbitrum_instructions,
     local_context::ArbitrumLocalContext,
     precompiles::ArbitrumPrecompileProvider,
     state::{ArbState, arbos_state::ArbosStateParams},
@@ -73,18 +74,20 @@ pub fn create_evm(context: TestContext) -> TestEvm {
     ArbitrumEvm::new_with_inspector(
         context,
         NoOpInspector {},
-        EthInstructions::default(),
+        arbitrum_instructions(),
         ArbitrumPrecompileProvider::default(),
     )
 }
 
 /// Deploy a WAT program to the test context and return its address
+#[allow(dead_code)]
 pub fn deploy_wat_program(context: &mut TestContext, wat: &[u8]) -> Address {
     let wasm_bytes = wasmer::wat2wasm(wat).expect("failed to compile WAT");
     deploy_wasm_program(context, &wasm_bytes)
 }
 
 /// Deploy a WASM program to the test context and return its address
+#[allow(dead_code)]
 pub fn deploy_wasm_program(context: &mut TestContext, wasm: &[u8]) -> Address {
     // Compress with brotli using empty dictionary
     let compressed = brotli::compress(wasm, 11, 22, brotli::Dictionary::Empty)
@@ -108,7 +111,17 @@ pub fn deploy_wasm_program(context: &mut TestContext, wasm: &[u8]) -> Address {
     code_address
 }
 
+/// Deploy raw EVM runtime code at the given address
+#[allow(dead_code)]
+pub fn deploy_evm_code(context: &mut TestContext, address: Address, code: &[u8]) {
+    context.journal_mut().load_account(address).unwrap();
+    context
+        .journal_mut()
+        .set_code(address, Bytecode::new_raw(Bytes::copy_from_slice(code)));
+}
+
 /// Fund an address with ETH
+#[allow(dead_code)]
 pub fn fund_account(context: &mut TestContext, address: Address, amount: U256) {
     context.journal_mut().load_account(address).unwrap();
     context.journal_mut().balance_incr(address, amount).unwrap();
@@ -0,0 +1,115 @@
+//! Tests for capping the gas a transaction executes with at ArbOS's per-block gas limit before
+//! ArbOS 50 and its per-transaction gas limit from then on.
+
+use arbos_revm::{
+    ArbitrumHaltReason,
+    state::{ArbState, ArbStateGetter, types::StorageBackedTr},
+};
+use revm::{
+    context::result::{ExecutionResult, HaltReason, OutOfGasError},
+    primitives::{Address, U256, address},
+};
+
+mod test_utils;
+use test_utils::{
+    TestEvm, create_call_tx, create_evm, deploy_evm_code, execute_tx, fund_account,
+    setup_context_with_arbos_state,
+};
+
+const CALLER: Address = address!("0x0101010101010101010101010101010101010101");
+const LOOP: Address = address!("0x00000000000000000000000000000000000100f0");
+const STOP: Address = address!("0x00000000000000000000000000000000000100f1");
+const INTRINSIC_GAS: u64 = 21_000;
+const LIMIT: u64 = 100_000;
+
+/// Loops until it runs out of gas.
+const LOOP_CODE: &[u8] = &[
+    0x5b, 0x60, 0x00, 0x56, // JUMPDEST, JUMP(0)
+];
+
+/// ArbOS `arbos_version`, with a per-block gas limit of [`LIMIT`] and a per-transaction gas
+/// limit of twice that, or the other way round when `per_tx` is set.
+fn setup(arbos_version: u16, per_tx: bool) -> TestEvm {
+    let mut context = setup_context_with_arbos_state();
+    context.cfg.arbos_version = arbos_version;
+    fund_account(&mut context, CALLER, U256::from(10u128.pow(18)));
+    deploy_evm_code(&mut context, LOOP, LOOP_CODE);
+    deploy_evm_code(&mut context, STOP, &[0x00]);
+
+    let (block_limit, tx_limit) = if per_tx {
+        (2 * LIMIT, LIMIT)
+    } else {
+        (LIMIT, 2 * LIMIT)
+    };
+    let mut arb_state = context.arb_state(None, false);
+    let mut l2_pricing = arb_state.l2_pricing();
+    l2_pricing.per_block_gas_limit().set(block_limit).unwrap();
+    l2_pricing.per_tx_gas_limit().set(tx_limit).unwrap();
+    drop(l2_pricing);
+    drop(arb_state);
+    create_evm(context)
+}
+
+fn assert_out_of_gas(result: &ExecutionResult, gas_used: u64) {
+    match result {
+        ExecutionResult::Halt {
+            reason: HaltReason::OutOfGas(OutOfGasError::Basic),
+            gas_used: used,
+        } => assert_eq!(*used, gas_used),
+        other => panic!("expected running out of gas, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_block_gas_limit_caps_execution_before_arbos_50() {
+    let mut evm = setup(40, false);
+
+    let result = execute_tx(&mut evm, create_call_tx(LOOP, vec![], 1_000_000));
+
+    // The gas above the limit is held back and refunded
+    assert_out_of_gas(&result, INTRINSIC_GAS + LIMIT);
+    let details = evm.execution_details();
+    assert_eq!(details.compute_hold_gas, 1_000_000 - INTRINSIC_GAS - LIMIT);
+    assert_eq!(
+        details.gas_limit_failure,
+        Some(ArbitrumHaltReason::TxGasLimitExceeded)
+    );
+}
+
+#[test]
+fn test_tx_gas_limit_caps_execution_from_arbos_50() {
+    let mut evm = setup(50, true);
+
+    let result = execute_tx(&mut evm, create_call_tx(LOOP, vec![], 1_000_000));
+
+    assert_out_of_gas(&result, INTRINSIC_GAS + LIMIT);
+    assert_eq!(
+        evm.execution_details().gas_limit_failure,
+        Some(ArbitrumHaltReason::TxGasLimitExceeded)
+    );
+}
+
+#[test]
+fn test_held_gas_is_refunded_on_success() {
+    let mut evm = setup(50, true);
+
+    let result = execute_tx(&mut evm, create_call_tx(STOP, vec![], 1_000_000));
+
+    assert!(result.is_success());
+    assert_eq!(result.gas_used(), INTRINSIC_GAS);
+    let details = evm.execution_details();
+    assert_eq!(details.compute_hold_gas, 1_000_000 - INTRINSIC_GAS - LIMIT);
+    assert_eq!(details.gas_limit_failure, None);
+}
+
+#[test]
+fn test_running_out_of_gas_within_the_limit() {
+    let mut evm = setup(50, true);
+
+    let result = execute_tx(&mut evm, create_call_tx(LOOP, vec![], 50_000));
+
+    assert_out_of_gas(&result, 50_000);
+    let details = evm.execution_details();
+    assert_eq!(details.compute_hold_gas, 0);
+    assert_eq!(details.gas_limit_failure, None);
+}
@@ -0,0 +1,145 @@
+//! Tests for upgrading ArbOS state to a scheduled version.
+
+use arbos_revm::{
+    block::StartBlockInfo,
+    constants::{
+        ARB_NATIVE_TOKEN_MANAGER_ADDRESS, ARBOS_L1_PRICER_FUNDS_ADDRESS,
+        INITIAL_PER_BATCH_GAS_COST_V12, INITIAL_PER_TX_GAS_LIMIT_V50, PRECOMPILE_MARKER_CODE,
+    },
+    state::{ArbState, ArbStateGetter, ArbosStateError, ArbosStateParams, types::StorageBackedTr},
+};
+use revm::{
+    context::{BlockEnv, JournalTr},
+    primitives::{Address, U256},
+};
+
+mod test_utils;
+use test_utils::{TestContext, create_evm, fund_account, setup_context};
+
+/// Context with ArbOS state at `version` and an upgrade to `upgrade_to` scheduled for
+/// `upgrade_timestamp`.
+fn setup(version: u16, upgrade_to: u64, upgrade_timestamp: u64) -> TestContext {
+    let mut context = setup_context();
+    context.cfg.arbos_version = version;
+    let mut arb_state = context.arb_state(None, false);
+    arb_state
+        .initialize(&ArbosStateParams {
+            upgrade_version: upgrade_to,
+            upgrade_timestamp,
+            ..Default::default()
+        })
+        .expect("failed to initialize ArbOS state");
+    arb_state.arbos_version().set(u64::from(version)).unwrap();
+    drop(arb_state);
+    context
+}
+
+fn block_env(number: u64, timestamp: u64) -> BlockEnv {
+    BlockEnv {
+        number: U256::from(number),
+        timestamp: U256::from(timestamp),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_scheduled_upgrade_applies_at_block_start() {
+    let mut context = setup(40, 50, 2_000);
+    assert!(
+        context
+            .journal_mut()
+            .code(ARB_NATIVE_TOKEN_MANAGER_ADDRESS)
+            .unwrap()
+            .data
+            .is_empty()
+    );
+    let mut evm = create_evm(context);
+
+    let result = evm
+        .transact_block([], block_env(1, 1_999), StartBlockInfo::default())
+        .expect("block execution failed");
+    assert_eq!(result.header_info.arbos_version, 40);
+
+    let result = evm
+        .transact_block([], block_env(2, 2_000), StartBlockInfo::default())
+        .expect("block execution failed");
+    assert!(result.receipts[0].result.is_success());
+    assert_eq!(result.header_info.arbos_version, 50);
+
+    let context = &mut evm.0.ctx;
+    assert_eq!(
+        context
+            .arb_state(None, true)
+            .l2_pricing()
+            .per_tx_gas_limit()
+            .get()
+            .unwrap(),
+        INITIAL_PER_TX_GAS_LIMIT_V50
+    );
+    // Introduced in ArbOS 41
+    assert_eq!(
+        context
+            .journal_mut()
+            .code(ARB_NATIVE_TOKEN_MANAGER_ADDRESS)
+            .unwrap()
+            .data
+            .as_ref(),
+        PRECOMPILE_MARKER_CODE
+    );
+}
+
+#[test]
+fn test_upgrade_runs_each_migration() {
+    let owner = Address::repeat_byte(0x0e);
+    let mut context = setup(2, 0, 0);
+    fund_account(
+        &mut context,
+        ARBOS_L1_PRICER_FUNDS_ADDRESS,
+        U256::from(1_234_u64),
+    );
+    let mut arb_state = context.arb_state(None, false);
+    arb_state.chain_owners().add(owner).unwrap();
+
+    arb_state
+        .upgrade_arbos_version(11, false)
+        .expect("upgrade failed");
+
+    assert_eq!(arb_state.arbos_version().get().unwrap(), 11);
+    let mut l1_pricing = arb_state.l1_pricing();
+    assert_eq!(
+        l1_pricing.l1_fees_available().get().unwrap(),
+        U256::from(1_234_u64)
+    );
+    assert_eq!(
+        l1_pricing.per_batch_gas_cost().get().unwrap(),
+        INITIAL_PER_BATCH_GAS_COST_V12
+    );
+    assert_eq!(l1_pricing.amortized_cost_cap_bips().get().unwrap(), 0);
+    // ArbOS 11 clears the owner list but not the membership index
+    assert!(arb_state.chain_owners().all().unwrap().is_empty());
+    assert!(arb_state.is_chain_owner(owner).unwrap());
+}
+
+#[test]
+fn test_upgrade_waits_for_timestamp_and_skips_uninitialized_state() {
+    let mut context = setup(40, 50, 2_000);
+    let mut arb_state = context.arb_state(None, false);
+    arb_state.upgrade_arbos_version_if_necessary(1_999).unwrap();
+    assert_eq!(arb_state.arbos_version().get().unwrap(), 40);
+
+    arb_state.arbos_version().set(0).unwrap();
+    arb_state.upgrade_arbos_version_if_necessary(2_000).unwrap();
+    assert_eq!(arb_state.arbos_version().get().unwrap(), 0);
+}
+
+#[test]
+fn test_unrecognized_version_is_rejected() {
+    let mut context = setup(50, 0, 0);
+    let result = context
+        .arb_state(None, false)
+        .upgrade_arbos_version(51, false);
+    assert!(matches!(
+        result,
+        Err(ArbosStateError::UnrecognizedArbosVersion(51))
+    ));
+}
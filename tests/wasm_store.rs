@@ -0,0 +1,105 @@
+//! Tests for loading natively compiled Stylus programs from a wasm store directory.
+
+use std::{
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+};
+
+use arbos_revm::{
+    program_cache::ProgramCache,
+    state::{ArbState, ArbStateGetter},
+    stylus_executor::{stylus_activate, stylus_compile, stylus_native_code, wasm_store_path},
+};
+use revm::{
+    context::{ContextTr, JournalTr},
+    primitives::{Address, B256, Bytes, U256},
+};
+use stylus::prover::{machine::Module, programs::config::CompileConfig};
+
+mod test_utils;
+use test_utils::{
+    TestContext, compile_wat, create_call_tx, create_evm, deploy_wasm_program, execute_tx,
+    fund_account, setup_context_with_arbos_state,
+};
+
+/// An empty wasm store unique to the calling test.
+fn wasm_store(name: &str) -> PathBuf {
+    let store = std::env::temp_dir().join(format!("arbos-revm-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&store);
+    store
+}
+
+/// Deploy the storage test program, returning its address, its wasm, the activated module and
+/// the config it compiles under.
+fn setup() -> (TestContext, Address, Bytes, Module, CompileConfig) {
+    let mut context = setup_context_with_arbos_state();
+    let wasm = Bytes::from(compile_wat("test-data/storage.wat"));
+    let address = deploy_wasm_program(&mut context, &wasm);
+    fund_account(
+        &mut context,
+        Address::repeat_byte(0x01),
+        U256::from(1_000_000_000_u64),
+    );
+
+    let code_hash = context.journal_mut().code_hash(address).unwrap().data;
+    let params = context
+        .arb_state(None, true)
+        .programs()
+        .stylus_params()
+        .get()
+        .unwrap();
+    let (module, _) = stylus_activate(
+        None,
+        &wasm,
+        code_hash,
+        context.cfg.arbos_version,
+        params.version,
+        params.page_limit,
+        false,
+    )
+    .unwrap();
+    let config = CompileConfig::version(params.version, false);
+    (context, address, wasm, module, config)
+}
+
+fn store_native_code(store: &Path, module: &Module, native: &[u8]) {
+    let path = wasm_store_path(store, B256::from_slice(module.hash().as_slice()));
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, native).unwrap();
+}
+
+#[test]
+fn test_native_code_is_read_from_the_store() {
+    let (_, _, wasm, module, config) = setup();
+    let store = wasm_store("read");
+    store_native_code(&store, &module, b"prebuilt");
+
+    let native = stylus_native_code(Some(&store), &module, &wasm, &config).unwrap();
+    assert_eq!(native, b"prebuilt");
+
+    // Programs missing from the store are compiled as usual
+    fs::remove_dir_all(&store).unwrap();
+    let native = stylus_native_code(Some(&store), &module, &wasm, &config).unwrap();
+    assert_eq!(native, stylus_compile(&wasm, &config).unwrap());
+}
+
+#[test]
+fn test_programs_run_from_the_store() {
+    let (mut context, address, wasm, module, config) = setup();
+    let store = wasm_store("run");
+    let native = stylus_compile(&wasm, &config).unwrap();
+    store_native_code(&store, &module, &native);
+    context.cfg.wasm_store_dir = Some(store.clone());
+
+    let cache = ProgramCache::new(NonZeroUsize::new(16).unwrap());
+    let mut evm = create_evm(context).with_program_cache(cache.clone());
+    // Selector 0x00 = read slot 0
+    let mut args = vec![0x00u8];
+    args.extend_from_slice(&[0u8; 32]);
+    let result = execute_tx(&mut evm, create_call_tx(address, args, 10_000_000));
+
+    assert!(result.is_success());
+    assert_eq!(cache.len(), 1);
+    fs::remove_dir_all(&store).unwrap();
+}